@@ -1,37 +1,129 @@
 use criterion::{black_box, criterion_group, criterion_main, Bencher, Criterion};
 use dbgen::{
-    eval::{CompileContext, State},
+    error::Error,
+    eval::{CompileContext, Schema, State},
     format::Options,
-    parser::Template,
-    span::Registry,
+    parser::{ParseOptions, Template},
+    span::{Registry, ResultExt, S},
+    value::Value,
+    writer::{Env, Writer},
 };
 use rand::SeedableRng;
 use rand_hc::Hc128Rng;
 use std::{
     fs::read_to_string,
-    io::{sink, Write},
+    io::{sink, Sink, Write},
 };
 
-fn run_benchmark(b: &mut Bencher<'_>, path: &str) {
+fn run_benchmark(b: &mut Bencher<'_>, path: &str, reuse_values: bool) {
     let mut registry = Registry::default();
-    let mut template = Template::parse(&read_to_string(path).unwrap(), &[], None, &mut registry).unwrap();
+    let mut template = Template::parse(
+        &read_to_string(path).unwrap(),
+        &[],
+        &[],
+        &ParseOptions::default(),
+        &mut registry,
+    )
+    .unwrap();
     let ctx = CompileContext::new(template.variables_count);
     let row = ctx.compile_row(template.tables.swap_remove(0).exprs).unwrap();
     let mut state = State::new(1, Box::new(Hc128Rng::from_seed([0x41; 32])), ctx);
     let options = Options::default();
     let mut sink: Box<dyn Write> = Box::new(sink());
 
+    let mut values = Vec::new();
+
+    b.iter(move || {
+        if reuse_values {
+            black_box(&row).eval_into(black_box(&mut state), &mut values).unwrap();
+        } else {
+            values = black_box(&row).eval(black_box(&mut state)).unwrap();
+        }
+        for value in &values {
+            options.write_sql_value(black_box(&mut *sink), value).unwrap();
+        }
+    });
+}
+
+/// A writer discarding the values after formatting them as SQL.
+struct SinkWriter(Options, Sink);
+
+impl Writer for SinkWriter {
+    fn write_value(&mut self, value: &Value) -> Result<(), S<Error>> {
+        self.0.write_sql_value(&mut self.1, value).no_span_err()
+    }
+
+    fn write_file_header(&mut self, _: &Schema<'_>) -> Result<(), S<Error>> {
+        Ok(())
+    }
+
+    fn write_header(&mut self, _: &Schema<'_>) -> Result<(), S<Error>> {
+        Ok(())
+    }
+
+    fn write_value_header(&mut self, _: &str) -> Result<(), S<Error>> {
+        Ok(())
+    }
+
+    fn write_value_separator(&mut self) -> Result<(), S<Error>> {
+        Ok(())
+    }
+
+    fn write_row_separator(&mut self) -> Result<(), S<Error>> {
+        Ok(())
+    }
+
+    fn write_trailer(&mut self) -> Result<(), S<Error>> {
+        Ok(())
+    }
+}
+
+fn run_env_benchmark(b: &mut Bencher<'_>, template: &str, batched: bool) {
+    let mut registry = Registry::default();
+    let template = Template::parse(template, &[], &[], &ParseOptions::default(), &mut registry).unwrap();
+    let ctx = CompileContext::new(template.variables_count);
+    let tables = template
+        .tables
+        .into_iter()
+        .map(|table| ctx.compile_table(table))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let mut state = State::new(1, Box::new(Hc128Rng::from_seed([0x41; 32])), ctx);
+    let mut env = Env::new(&tables, &mut state, false, |_| {
+        Ok(SinkWriter(Options::default(), sink()))
+    })
+    .unwrap();
+
     b.iter(move || {
-        let values = black_box(&row).eval(black_box(&mut state)).unwrap();
-        for value in values {
-            options.write_sql_value(black_box(&mut *sink), &value).unwrap();
+        if batched {
+            env.write_rows(1024).unwrap();
+        } else {
+            for _ in 0..1024 {
+                env.write_row().unwrap();
+            }
         }
+        env.write_trailer().unwrap();
     });
 }
 
+const NUMERIC_TEMPLATE: &str = "CREATE TABLE t (
+    id {{ rownum }},
+    k {{ rand.range(0, 1000000) }},
+    c {{ 'constant' }}
+);";
+
 fn bench_templates(c: &mut Criterion) {
     c.bench_function("sysbench_oltp_uniform", |b| {
-        run_benchmark(b, "res/sysbench/oltp_uniform_mysql.sql");
+        run_benchmark(b, "res/sysbench/oltp_uniform_mysql.sql", false);
+    });
+    c.bench_function("sysbench_oltp_uniform_eval_into", |b| {
+        run_benchmark(b, "res/sysbench/oltp_uniform_mysql.sql", true);
+    });
+    c.bench_function("numeric_write_row_1024", |b| {
+        run_env_benchmark(b, NUMERIC_TEMPLATE, false);
+    });
+    c.bench_function("numeric_write_rows_1024", |b| {
+        run_env_benchmark(b, NUMERIC_TEMPLATE, true);
     });
 }
 
@@ -1,6 +1,10 @@
 use clap::{Arg, ArgAction, Command};
-use dbdbgen::{cli::ensure_seed, error::Error, jsvm::Vm};
-use dbgen::{span::Registry, FULL_VERSION};
+use dbdbgen::{
+    cli::{ensure_seed, expand_sweep},
+    error::Error,
+    jsvm::Vm,
+};
+use dbgen::FULL_VERSION;
 use std::error::Error as StdError;
 
 fn run() -> Result<(), Error> {
@@ -30,7 +34,11 @@ fn run() -> Result<(), Error> {
     let app = vm.eval_arguments()?;
     let mut matches = app.get_matches(args);
     ensure_seed(&mut matches);
-    let steps = vm.eval_steps(matches)?;
+    let sweep = vm.eval_sweep(&matches)?;
+    let mut steps = Vec::new();
+    for matches in expand_sweep(&matches, &sweep) {
+        steps.extend(vm.eval_steps(matches)?);
+    }
 
     if global_matches.get_flag("dry-run") {
         println!(
@@ -41,19 +49,7 @@ fn run() -> Result<(), Error> {
         return Ok(());
     }
 
-    let steps_count = steps.len();
-    for (step, arg) in steps.into_iter().enumerate() {
-        if !arg.quiet {
-            eprintln!("step {} / {}", step + 1, steps_count);
-        }
-        let mut registry = Registry::default();
-        dbgen::cli::run(arg, &mut registry).map_err(|e| Error::Dbgen {
-            step,
-            message: registry.describe(&e),
-        })?;
-    }
-
-    Ok(())
+    dbdbgen::run_steps(steps)
 }
 
 fn main() {
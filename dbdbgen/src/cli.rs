@@ -6,7 +6,13 @@ use clap::{
 use data_encoding::HEXLOWER_PERMISSIVE;
 use rand::{rngs::OsRng, RngCore as _};
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, ffi::OsString};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{BTreeMap, HashMap},
+    ffi::OsString,
+    fmt,
+    path::Path,
+};
 
 #[derive(Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -70,7 +76,7 @@ pub struct App {
     pub args: HashMap<String, Arg>,
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(untagged)]
 pub enum Match {
     Bool(bool),
@@ -80,8 +86,23 @@ pub enum Match {
     Array(Vec<String>),
 }
 
+impl fmt::Display for Match {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool(b) => b.fmt(f),
+            Self::Str(s) => f.write_str(s),
+            Self::Int(i) => i.fmt(f),
+            Self::Float(x) => x.fmt(f),
+            Self::Array(a) => f.write_str(&a.join(",")),
+        }
+    }
+}
+
 pub type Matches<'a> = HashMap<&'a str, Match>;
 
+/// A parameter sweep, listing the values to be tried for some keys of the matches.
+pub type Sweep = BTreeMap<String, Vec<Match>>;
+
 impl App {
     /// Constructs the clap App from this simplified specification.
     fn to_clap_app(&self) -> Command {
@@ -152,3 +173,47 @@ pub fn ensure_seed(matches: &mut Matches<'_>) {
         Match::Str(HEXLOWER_PERMISSIVE.encode(&buf))
     });
 }
+
+/// Expands the matches into one for every combination of values in the parameter sweep.
+///
+/// Each combination writes into a subdirectory of `out_dir` named like `key1=value1/key2=value2`,
+/// and uses a seed derived from the original seed and the subdirectory name. If the sweep is
+/// empty, the matches are returned unchanged.
+pub fn expand_sweep<'a>(matches: &Matches<'a>, sweep: &'a Sweep) -> Vec<Matches<'a>> {
+    let mut combinations = vec![(matches.clone(), Vec::new())];
+    for (key, values) in sweep {
+        combinations = combinations
+            .into_iter()
+            .flat_map(|(matches, labels)| {
+                values.iter().map(move |value| {
+                    let mut matches = matches.clone();
+                    matches.insert(key, value.clone());
+                    let mut labels = labels.clone();
+                    labels.push(format!("{key}={value}"));
+                    (matches, labels)
+                })
+            })
+            .collect();
+    }
+    if sweep.is_empty() {
+        return combinations.into_iter().map(|(matches, _)| matches).collect();
+    }
+
+    combinations
+        .into_iter()
+        .map(|(mut matches, labels)| {
+            let label = labels.join("/");
+            if let Some(Match::Str(out_dir)) = matches.get_mut("out_dir") {
+                *out_dir = Path::new(out_dir).join(&label).to_string_lossy().into_owned();
+            }
+            if let Some(Match::Str(seed)) = matches.get_mut("seed") {
+                let mut hasher = Sha256::new();
+                hasher.update(&*seed);
+                hasher.update(b"/");
+                hasher.update(&label);
+                *seed = HEXLOWER_PERMISSIVE.encode(&hasher.finalize());
+            }
+            matches
+        })
+        .collect()
+}
@@ -6,6 +6,8 @@ use thiserror::Error as ThisError;
 pub enum Purpose {
     /// Produce argument specifications as CLI for itself.
     Arguments,
+    /// Produce the parameter sweep.
+    Sweep,
     /// Produce configurations for dbgen execution.
     Execution { step: usize },
 }
@@ -14,6 +16,7 @@ impl fmt::Display for Purpose {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Arguments => f.write_str("arguments"),
+            Self::Sweep => f.write_str("sweep"),
             Self::Execution { step } => write!(f, "execution (index={})", step),
         }
     }
@@ -1,5 +1,5 @@
 use crate::{
-    cli::{App, Matches},
+    cli::{App, Matches, Sweep},
     error::{Error, Purpose},
 };
 use data_encoding::HEXLOWER_PERMISSIVE;
@@ -66,6 +66,27 @@ impl<'p> Vm<'p> {
         Ok(app)
     }
 
+    pub fn eval_sweep(&mut self, matches: &Matches<'_>) -> Result<Sweep, Error> {
+        let matches_js = serde_json::to_string(matches).unwrap();
+        self.vm.tla_code("matches", &matches_js);
+
+        let sweep_js = self
+            .vm
+            .evaluate_snippet(
+                self.path,
+                "function(src, matches) \
+                    if !std.objectHasAll(src, 'sweep') then {} \
+                    else if std.isFunction(src.sweep) then src.sweep(matches) \
+                    else src.sweep",
+            )
+            .map_err(|error| Error::Jsonnet {
+                purpose: Purpose::Sweep,
+                message: error.to_string(),
+            })?;
+        let sweep = deserialize(&sweep_js, Purpose::Sweep)?;
+        Ok(sweep)
+    }
+
     pub fn eval_steps(&mut self, matches: Matches<'_>) -> Result<Vec<Args>, Error> {
         let matches_js = serde_json::to_string(&matches).unwrap();
         self.vm.tla_code("matches", &matches_js);
@@ -1,3 +1,30 @@
 pub mod cli;
 pub mod error;
 pub mod jsvm;
+
+use crate::error::Error;
+use dbgen::{
+    cli::{run_in_session, Args, Session},
+    span::Registry,
+};
+
+/// Runs every step of dbgen in order.
+///
+/// The steps share the same session, so the parsed templates and thread pools are reused whenever
+/// possible. The steps may come from [`jsvm::Vm::eval_steps`], or be constructed programmatically
+/// starting from `Args::default()`.
+pub fn run_steps(steps: Vec<Args>) -> Result<(), Error> {
+    let steps_count = steps.len();
+    let mut session = Session::default();
+    let mut registry = Registry::default();
+    for (step, arg) in steps.into_iter().enumerate() {
+        if !arg.quiet {
+            eprintln!("step {} / {}", step + 1, steps_count);
+        }
+        run_in_session(arg, &mut session, &mut registry).map_err(|e| Error::Dbgen {
+            step,
+            message: registry.describe(&e),
+        })?;
+    }
+    Ok(())
+}
@@ -0,0 +1,69 @@
+use dbdbgen::{
+    cli::{ensure_seed, expand_sweep},
+    jsvm::Vm,
+};
+use dbgen::cli::RngName;
+use std::path::Path;
+
+#[test]
+fn sweep_expands_every_combination() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/sweep.jsonnet");
+    let mut vm = Vm::new(path.as_os_str(), false).unwrap();
+    let app = vm.eval_arguments().unwrap();
+    let mut matches = app.get_matches([
+        "sweep.jsonnet",
+        "-o",
+        "out",
+        "-r",
+        "3",
+        "-s",
+        "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef",
+    ]);
+    ensure_seed(&mut matches);
+    let sweep = vm.eval_sweep(&matches).unwrap();
+
+    let mut steps = Vec::new();
+    for matches in expand_sweep(&matches, &sweep) {
+        steps.extend(vm.eval_steps(matches).unwrap());
+    }
+    let actual = steps
+        .iter()
+        .map(|step| {
+            (
+                step.out_dir.to_str().unwrap(),
+                step.seed.unwrap().to_string(),
+                step.rng,
+                step.rows_count,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        actual,
+        [
+            (
+                "out/rng=hc128/rows_count=3",
+                "6f30232c89014f28144d4784d6a1a6af597e01599d688f28ae4d97b86ddf1b2f".to_owned(),
+                RngName::Hc128,
+                3,
+            ),
+            (
+                "out/rng=hc128/rows_count=30",
+                "6b4ac70367814a1324a288c4b9d715431de2bb62a902bf338793e3466e98e9ba".to_owned(),
+                RngName::Hc128,
+                30,
+            ),
+            (
+                "out/rng=step/rows_count=3",
+                "aa90890b4726be8db7980304348176c90838508ef7f37543ed9714893bea183d".to_owned(),
+                RngName::Step,
+                3,
+            ),
+            (
+                "out/rng=step/rows_count=30",
+                "9b862ce8d403bd0351113ade58138acdab35a5f044f6ee124d0e42cc81d99ce1".to_owned(),
+                RngName::Step,
+                30,
+            ),
+        ]
+    );
+}
@@ -1,18 +1,16 @@
 use chrono::NaiveDateTime;
 use dbgen::{
     error::Error,
-    eval::{CompileContext, Schema, State},
+    eval::Schema,
     format::Options,
-    parser::Template,
+    runtime::{Generator, GeneratorOptions},
     span::{Registry, ResultExt, S},
     value::{Value, TIMESTAMP_FORMAT},
-    writer::{Env, Writer},
+    writer::Writer,
     FULL_VERSION,
 };
-use rand::{Rng, SeedableRng};
-use rand_hc::Hc128Rng;
 use serde::Serialize;
-use std::{borrow::Cow, convert::TryFrom, mem};
+use std::{borrow::Cow, convert::TryFrom};
 use wasm_bindgen::prelude::*;
 
 #[derive(Default)]
@@ -77,45 +75,25 @@ fn try_generate_rows(
     span_registry: &mut Registry,
 ) -> Result<Vec<Table>, S<Error>> {
     let now = NaiveDateTime::parse_from_str(now, TIMESTAMP_FORMAT).no_span_err()?;
-    let seed = <&<Hc128Rng as SeedableRng>::Seed>::try_from(seed)
+    let seed = <[u8; 32]>::try_from(seed)
         .map_err(|e| Error::InvalidArguments(format!("invalid seed: {}", e)))
         .no_span_err()?;
 
-    let template = Template::parse(template, &[], None, span_registry)?;
-    let mut ctx = CompileContext::new(template.variables_count);
-    ctx.current_timestamp = now;
-    let tables = template
-        .tables
-        .into_iter()
-        .map(|t| ctx.compile_table(t))
-        .collect::<Result<Vec<_>, _>>()?;
-
-    // we perform this double seeding to be compatible with the CLI.
-    let mut seeding_rng = Hc128Rng::from_seed(*seed);
-    let mut rng = move || Box::new(Hc128Rng::from_seed(seeding_rng.gen()));
-
-    if !template.global_exprs.is_empty() {
-        let row_gen = ctx.compile_row(template.global_exprs)?;
-        let mut state = State::new(0, rng(), ctx);
-        row_gen.eval(&mut state)?;
-        ctx = state.into_compile_context();
-    }
-
-    let mut state = State::new(1, rng(), ctx);
-    let mut env = Env::new(&tables, &mut state, false, |_| Ok(TableWriter::default()))?;
-    for _ in 0..rows {
-        env.write_row()?;
-    }
-
-    Ok(env
-        .tables()
-        .map(|(table, writer)| {
-            let schema = table.schema(false);
-            Table {
-                name: schema.name.to_owned(),
-                column_names: schema.column_names().map(|s| s.to_owned()).collect(),
-                rows: mem::take(&mut writer.rows),
-            }
+    let options = GeneratorOptions {
+        seed,
+        now,
+        ..GeneratorOptions::default()
+    };
+    let mut generator = Generator::new(template, &options, span_registry)?;
+    let writers = generator.write_rows(rows as u64, |_| Ok(TableWriter::default()))?;
+
+    Ok(generator
+        .schemas()
+        .zip(writers)
+        .map(|(schema, writer)| Table {
+            name: schema.name.to_owned(),
+            column_names: schema.column_names().map(|s| s.to_owned()).collect(),
+            rows: writer.rows,
         })
         .collect())
 }
@@ -125,7 +103,7 @@ pub fn generate_rows(template: &str, rows: usize, now: &str, seed: &[u8]) -> Res
     let mut registry = Registry::default();
     match try_generate_rows(template, rows, now, seed, &mut registry) {
         Ok(result) => serde_wasm_bindgen::to_value(&result).map_err(|e| e.to_string().into()),
-        Err(e) => Err(registry.describe(&e).into()),
+        Err(e) => Err(serde_wasm_bindgen::to_value(&registry.diagnose(&e)).unwrap_throw()),
     }
 }
 
@@ -114,12 +114,21 @@ impl Feistel {
     ///
     /// It is expected both input and output to be less than `len`.
     fn get(&self, i: u64) -> u64 {
+        self.get_tweaked(i, 0)
+    }
+
+    /// Permutes a number using the network whose round function is altered by the tweak.
+    ///
+    /// Every tweak gives a different permutation, and the tweak 0 gives the same one as `get`.
+    fn get_tweaked(&self, i: u64, tweak: u64) -> u64 {
         use fastrand::Rng;
 
+        // spread the tweak over all bits, so that adjacent tweaks do not give related rounds.
+        let tweak = tweak.wrapping_mul(0x9e37_79b9_7f4a_7c15);
         let (mut a, mut b) = Self::split_number(i, self.modulo);
         loop {
             for key in &self.seed {
-                let c = Rng::with_seed(key.wrapping_add(b.into())).u32(..) & self.mask;
+                let c = Rng::with_seed(key.wrapping_add(b.into()) ^ tweak).u32(..) & self.mask;
                 (a, b) = (b, c.wrapping_add(a));
                 if let Some(modulo) = self.modulo {
                     let modulo = modulo.get();
@@ -205,6 +214,33 @@ impl Permutation {
     }
 }
 
+/// A family of permutations of the indices of equally sized blocks, one for every block.
+///
+/// This shuffles every block of a long sequence independently, without storing a permutation per
+/// block.
+#[derive(Clone, Debug)]
+pub struct BlockPermutation(Feistel);
+
+impl BlockPermutation {
+    /// Creates new permutations of blocks of the given length, which must be at least 2.
+    ///
+    /// The result is *not yet ready to use*. One must explicitly call [`self.shuffle()`] later to
+    /// initialize the permutations.
+    pub fn prepare(len: u64) -> Self {
+        Self(Feistel::prepare(len))
+    }
+
+    /// Get the permuted index at original index `i` of the given block.
+    pub fn get(&self, block: u64, i: u64) -> u64 {
+        self.0.get_tweaked(i, block)
+    }
+
+    /// Shuffles (reseeds) the permutations.
+    pub fn shuffle(&mut self, rng: &mut dyn RngCore) {
+        self.0.shuffle(rng);
+    }
+}
+
 #[derive(Clone, Debug)]
 enum A {
     /// An concrete array of values.
@@ -336,4 +372,29 @@ mod tests {
         assert!(sorted.iter().copied().eq(0..256), "{sorted:?}");
         assert_ne!(shuffled, sorted);
     }
+
+    #[test]
+    fn test_block_permutation() {
+        let mut permutation = BlockPermutation::prepare(10);
+        permutation.0.seed = [
+            0x0123_4567_89ab_cdef,
+            0xfedc_ba98_7654_3210,
+            0x1357_9bdf_0246_8ace,
+            0xeca8_6420_fdb9_7531,
+            0x0f1e_2d3c_4b5a_6978,
+            0x8796_a5b4_c3d2_e1f0,
+            0x2468_ace0_1357_9bdf,
+            0xfdb9_7531_eca8_6420,
+        ];
+        let blocks = (0..4)
+            .map(|block| (0..10).map(|i| permutation.get(block, i)).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        for block in &blocks {
+            let mut sorted = block.clone();
+            sorted.sort();
+            assert!(sorted.iter().copied().eq(0..10), "{block:?}");
+        }
+        assert_eq!(blocks[0], (0..10).map(|i| permutation.0.get(i)).collect::<Vec<_>>());
+        assert!(blocks.iter().skip(1).any(|block| *block != blocks[0]), "{blocks:?}");
+    }
 }
@@ -0,0 +1,488 @@
+//! Auditing where the generated values come from and how much entropy they carry.
+//!
+//! When a template is derived from a production schema, a privacy review needs to know which
+//! columns are fully synthetic and which ones reproduce values read from input files (e.g.
+//! `rand.from_file` or `lookup_file`). The entropy is estimated from a sample of generated rows.
+
+use crate::{
+    error::Error,
+    eval::{Schema, State, Table},
+    parser::{function_name, Expr, Template},
+    span::S,
+    value::Value,
+    writer::{Env, Writer},
+};
+use std::{collections::HashMap, fmt};
+
+/// Functions whose values are read from input files or the environment.
+const INPUT_FUNCTIONS: &[&str] = &[
+    "env",
+    "rand.from_file",
+    "rand.from_histogram",
+    "rand.markov",
+    "lookup_file",
+];
+
+/// The maximum number of distinct values tallied in every column. The occurrences of other values
+/// are only counted in total, to bound the memory used by auditing a large number of rows.
+const MAX_TALLIED_VALUES: usize = 10000;
+
+/// Describes the input read by an expression, e.g. `lookup_file('cities.csv')`.
+fn describe_input(name: &str, args: &[S<Expr>]) -> String {
+    match args.first().map(|arg| &arg.inner) {
+        Some(Expr::Value(path @ Value::Bytes(_))) => format!("{name}({path})"),
+        _ => format!("{name}(…)"),
+    }
+}
+
+/// Finds the inputs an expression derives from.
+///
+/// `variables` contains the inputs every local variable has been assigned from.
+fn collect_inputs(expr: &Expr, variables: &[Vec<String>], inputs: &mut Vec<String>) {
+    match expr {
+        Expr::RowNum | Expr::SubRowNum | Expr::CurrentTimestamp | Expr::ScaleFactor | Expr::Value(_) => {}
+        Expr::GetVariable(index) => inputs.extend_from_slice(&variables[*index]),
+        Expr::SetVariable(_, value) => collect_inputs(&value.inner, variables, inputs),
+        Expr::Function { function, args } => {
+            if let Some(name) = function_name(*function).filter(|name| INPUT_FUNCTIONS.contains(name)) {
+                inputs.push(describe_input(name, args));
+            }
+            for arg in args {
+                collect_inputs(&arg.inner, variables, inputs);
+            }
+        }
+        Expr::CaseValueWhen {
+            value,
+            conditions,
+            otherwise,
+        } => {
+            for e in value.iter().chain(otherwise) {
+                collect_inputs(&e.inner, variables, inputs);
+            }
+            for (condition, result) in conditions {
+                collect_inputs(&condition.inner, variables, inputs);
+                collect_inputs(&result.inner, variables, inputs);
+            }
+        }
+        Expr::Pinned { namespace, inner } => {
+            collect_inputs(&namespace.inner, variables, inputs);
+            collect_inputs(&inner.inner, variables, inputs);
+        }
+    }
+}
+
+/// Finds the variable assignments inside an expression, and adds the inputs of the assigned
+/// values to `variables`. Returns whether any variable gained new inputs.
+fn propagate_variables(expr: &Expr, variables: &mut [Vec<String>]) -> bool {
+    let mut changed = false;
+    match expr {
+        Expr::SetVariable(index, value) => {
+            let mut inputs = Vec::new();
+            collect_inputs(&value.inner, variables, &mut inputs);
+            for input in inputs {
+                if !variables[*index].contains(&input) {
+                    variables[*index].push(input);
+                    changed = true;
+                }
+            }
+            changed |= propagate_variables(&value.inner, variables);
+        }
+        Expr::Function { args, .. } => {
+            for arg in args {
+                changed |= propagate_variables(&arg.inner, variables);
+            }
+        }
+        Expr::CaseValueWhen {
+            value,
+            conditions,
+            otherwise,
+        } => {
+            for e in value.iter().chain(otherwise) {
+                changed |= propagate_variables(&e.inner, variables);
+            }
+            for (condition, result) in conditions {
+                changed |= propagate_variables(&condition.inner, variables);
+                changed |= propagate_variables(&result.inner, variables);
+            }
+        }
+        Expr::Pinned { namespace, inner } => {
+            changed |= propagate_variables(&namespace.inner, variables);
+            changed |= propagate_variables(&inner.inner, variables);
+        }
+        Expr::RowNum
+        | Expr::SubRowNum
+        | Expr::CurrentTimestamp
+        | Expr::ScaleFactor
+        | Expr::Value(_)
+        | Expr::GetVariable(_) => {}
+    }
+    changed
+}
+
+/// Finds the input files every column of every table in the template is derived from.
+///
+/// The result is indexed by the table and then the column. A column without any inputs is fully
+/// synthetic.
+pub fn column_inputs(template: &Template) -> Vec<Vec<Vec<String>>> {
+    let all_exprs = template
+        .global_exprs
+        .iter()
+        .chain(&template.order_by)
+        .chain(template.table_order_by.iter().map(|(_, order_by)| order_by))
+        .chain(template.tables.iter().flat_map(|table| &table.exprs));
+
+    // variables may be assigned in a later column and read by an earlier one (of the next row), so
+    // we repeat until no more inputs can be found.
+    let mut variables = vec![Vec::new(); template.variables_count];
+    loop {
+        let mut changed = false;
+        for expr in all_exprs.clone() {
+            changed |= propagate_variables(&expr.inner, &mut variables);
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    template
+        .tables
+        .iter()
+        .map(|table| {
+            table
+                .exprs
+                .iter()
+                .map(|expr| {
+                    let mut inputs = Vec::new();
+                    collect_inputs(&expr.inner, &variables, &mut inputs);
+                    inputs.sort();
+                    inputs.dedup();
+                    inputs
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// The audit result of a single column.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColumnAudit {
+    /// The table name.
+    pub table: String,
+    /// The column name.
+    pub column: String,
+    /// The input files the values are derived from. Empty if the column is fully synthetic.
+    pub inputs: Vec<String>,
+    /// Number of sampled values.
+    pub samples: u64,
+    /// Number of distinct values among the samples, not counting the untallied values.
+    pub distinct: usize,
+    /// Number of sampled values which are not tallied, because the column already has 10000 more
+    /// common values. They are assumed to be distinct when estimating the Shannon entropy.
+    pub untallied: u64,
+    /// Estimated min-entropy in bits, i.e. `-log2` of the frequency of the most common value.
+    pub min_entropy: f64,
+    /// Estimated Shannon entropy in bits.
+    pub shannon_entropy: f64,
+}
+
+impl ColumnAudit {
+    /// Estimates the entropies from the number of occurrences of every distinct value.
+    fn new(table: String, column: String, inputs: Vec<String>, tally: &ColumnTally) -> Self {
+        let ColumnTally { counts, untallied } = tally;
+        let samples = counts.values().sum::<u64>() + untallied;
+        let max_count = counts.values().copied().max().unwrap_or(0);
+        // ALLOW_REASON: the sample sizes are far below 2^52.
+        #[allow(clippy::cast_precision_loss)]
+        let (min_entropy, shannon_entropy) = if samples == 0 {
+            (0.0, 0.0)
+        } else {
+            let total = samples as f64;
+            let shannon = counts
+                .values()
+                .map(|&count| {
+                    let p = count as f64 / total;
+                    -p * p.log2()
+                })
+                .sum::<f64>()
+                + *untallied as f64 / total * total.log2();
+            (-(max_count as f64 / total).log2(), shannon)
+        };
+        Self {
+            table,
+            column,
+            inputs,
+            samples,
+            distinct: counts.len(),
+            untallied: *untallied,
+            min_entropy: min_entropy.max(0.0),
+            shannon_entropy: shannon_entropy.max(0.0),
+        }
+    }
+}
+
+impl fmt::Display for ColumnAudit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}: min-entropy {:.2} bits, Shannon entropy {:.2} bits, {} distinct ",
+            self.table, self.column, self.min_entropy, self.shannon_entropy, self.distinct,
+        )?;
+        if self.untallied > 0 {
+            write!(f, "(and {} untallied) ", self.untallied)?;
+        }
+        write!(f, "of {} samples, ", self.samples)?;
+        if self.inputs.is_empty() {
+            f.write_str("fully synthetic")
+        } else {
+            write!(f, "derived from {}", self.inputs.join(", "))
+        }
+    }
+}
+
+/// The occurrences of the values in a column.
+#[derive(Clone, Default, Debug)]
+struct ColumnTally {
+    /// The number of occurrences of every value, keyed by its SQL representation. At most
+    /// [`MAX_TALLIED_VALUES`] values are kept.
+    counts: HashMap<String, u64>,
+    /// The number of occurrences of the values not kept in `counts`.
+    untallied: u64,
+}
+
+impl ColumnTally {
+    /// Counts an occurrence of the value.
+    fn add(&mut self, value: String) {
+        if let Some(count) = self.counts.get_mut(&value) {
+            *count += 1;
+        } else if self.counts.len() < MAX_TALLIED_VALUES {
+            self.counts.insert(value, 1);
+        } else {
+            self.untallied += 1;
+        }
+    }
+
+    /// Adds the occurrences counted by another tally, keeping only the most common values.
+    fn merge(&mut self, other: Self) {
+        for (value, count) in other.counts {
+            *self.counts.entry(value).or_default() += count;
+        }
+        self.untallied += other.untallied;
+        if self.counts.len() > MAX_TALLIED_VALUES {
+            let mut counts = self.counts.drain().collect::<Vec<_>>();
+            counts.sort_unstable_by(|(a, m), (b, n)| n.cmp(m).then_with(|| a.cmp(b)));
+            self.untallied += counts[MAX_TALLIED_VALUES..].iter().map(|(_, count)| count).sum::<u64>();
+            counts.truncate(MAX_TALLIED_VALUES);
+            self.counts = counts.into_iter().collect();
+        }
+    }
+}
+
+/// A [`Writer`] counting the occurrences of the values in every column.
+#[derive(Default)]
+struct Tally {
+    /// The occurrences of the values in every column.
+    columns: Vec<ColumnTally>,
+    /// Index of the column of the next value in the current row.
+    column_index: usize,
+}
+
+impl Tally {
+    /// Adds the occurrences counted by another tally.
+    fn merge(mut self, other: Self) -> Self {
+        if self.columns.len() < other.columns.len() {
+            self.columns.resize_with(other.columns.len(), ColumnTally::default);
+        }
+        for (column, other_column) in self.columns.iter_mut().zip(other.columns) {
+            column.merge(other_column);
+        }
+        self
+    }
+}
+
+impl Writer for Tally {
+    fn write_value(&mut self, value: &Value) -> Result<(), S<Error>> {
+        if self.columns.len() <= self.column_index {
+            self.columns.resize_with(self.column_index + 1, ColumnTally::default);
+        }
+        self.columns[self.column_index].add(value.to_string());
+        self.column_index += 1;
+        Ok(())
+    }
+
+    fn write_file_header(&mut self, _: &Schema<'_>) -> Result<(), S<Error>> {
+        Ok(())
+    }
+
+    fn write_header(&mut self, _: &Schema<'_>) -> Result<(), S<Error>> {
+        self.column_index = 0;
+        Ok(())
+    }
+
+    fn write_value_header(&mut self, _: &str) -> Result<(), S<Error>> {
+        Ok(())
+    }
+
+    fn write_value_separator(&mut self) -> Result<(), S<Error>> {
+        Ok(())
+    }
+
+    fn write_row_separator(&mut self) -> Result<(), S<Error>> {
+        self.column_index = 0;
+        Ok(())
+    }
+
+    fn write_trailer(&mut self) -> Result<(), S<Error>> {
+        Ok(())
+    }
+}
+
+/// Generates the given number of rows from each root table, and audits every column.
+///
+/// The `inputs` should be computed by [`column_inputs()`] from the template the `tables` were
+/// compiled from. Note that the estimated entropies can never exceed `log2(rows)`.
+pub fn audit(
+    tables: &[Table],
+    inputs: Vec<Vec<Vec<String>>>,
+    state: &mut State,
+    qualified: bool,
+    rows: u64,
+) -> Result<Vec<ColumnAudit>, S<Error>> {
+    // sharded tables must be routed to stay consistent with the generated files.
+    let mut env = Env::with_partitions(tables, state, qualified, None, |_, _, _| Ok(Tally::default()))?;
+    for _ in 0..rows {
+        env.write_row()?;
+    }
+    env.write_trailer()?;
+
+    let mut result = Vec::new();
+    let mut tallies = env.into_writers().into_iter();
+    for (table, table_inputs) in tables.iter().zip(inputs) {
+        // the shards of a table are audited together.
+        let tally = tallies
+            .by_ref()
+            .take(table.shard_indices().count())
+            .fold(Tally::default(), Tally::merge);
+        let schema = table.schema(qualified);
+        let mut column_names = schema.column_names();
+        for (i, column_inputs) in table_inputs.into_iter().enumerate() {
+            let column = column_names.next().map_or_else(|| format!("#{}", i + 1), str::to_owned);
+            let column_tally = tally.columns.get(i).cloned().unwrap_or_default();
+            result.push(ColumnAudit::new(
+                schema.name.to_owned(),
+                column,
+                column_inputs,
+                &column_tally,
+            ));
+        }
+    }
+    Ok(result)
+}
+
+#[test]
+fn test_column_inputs() {
+    use crate::{parser::ParseOptions, span::Registry};
+
+    let template = Template::parse(
+        "CREATE TABLE t (
+            a INT {{ @x := lookup_file('cities.csv', rownum, 'name') }},
+            b INT {{ @x || 'suffix' }},
+            c INT {{ rand.range(0, 10) }},
+            d TEXT {{ rand.markov('corpus.txt', 5) }}
+        );",
+        &[],
+        &[],
+        &ParseOptions::default(),
+        &mut Registry::default(),
+    )
+    .unwrap();
+    let inputs = column_inputs(&template);
+    assert_eq!(
+        inputs,
+        [[
+            vec!["lookup_file('cities.csv')".to_owned()],
+            vec!["lookup_file('cities.csv')".to_owned()],
+            vec![],
+            vec!["rand.markov('corpus.txt')".to_owned()],
+        ]]
+    );
+}
+
+#[test]
+fn test_column_inputs_from_order_by() {
+    use crate::{parser::ParseOptions, span::Registry};
+
+    let options = ParseOptions {
+        order_by: Some("@x := lookup_file('cities.csv', rownum, 'name')"),
+        ..ParseOptions::default()
+    };
+    let template = Template::parse(
+        "CREATE TABLE t (a TEXT {{ @x }});",
+        &[],
+        &[],
+        &options,
+        &mut Registry::default(),
+    )
+    .unwrap();
+    assert_eq!(
+        column_inputs(&template),
+        [[vec!["lookup_file('cities.csv')".to_owned()]]]
+    );
+}
+
+#[test]
+fn test_entropy() {
+    let tally = ColumnTally {
+        counts: [("1".to_owned(), 2), ("2".to_owned(), 1), ("3".to_owned(), 1)]
+            .into_iter()
+            .collect(),
+        untallied: 0,
+    };
+    let audit = ColumnAudit::new("t".to_owned(), "a".to_owned(), Vec::new(), &tally);
+    assert_eq!(audit.samples, 4);
+    assert_eq!(audit.distinct, 3);
+    assert!((audit.min_entropy - 1.0).abs() < 1e-9);
+    assert!((audit.shannon_entropy - 1.5).abs() < 1e-9);
+}
+
+#[test]
+fn test_untallied_values() {
+    let mut tally = ColumnTally::default();
+    for i in 0..MAX_TALLIED_VALUES + 10 {
+        tally.add(i.to_string());
+    }
+    tally.add("0".to_owned());
+    assert_eq!(tally.counts.len(), MAX_TALLIED_VALUES);
+    assert_eq!(tally.counts["0"], 2);
+    assert_eq!(tally.untallied, 10);
+
+    // merging keeps the most common values.
+    let mut other = ColumnTally::default();
+    for _ in 0..3 {
+        other.add("new".to_owned());
+    }
+    tally.merge(other);
+    assert_eq!(tally.counts.len(), MAX_TALLIED_VALUES);
+    assert_eq!(tally.counts["new"], 3);
+    assert_eq!(tally.counts["0"], 2);
+    assert_eq!(tally.untallied, 11);
+
+    let audit = ColumnAudit::new("t".to_owned(), "a".to_owned(), Vec::new(), &tally);
+    assert_eq!(audit.samples, MAX_TALLIED_VALUES as u64 + 14);
+    assert_eq!(audit.untallied, 11);
+    assert!(audit.to_string().contains("(and 11 untallied) of 10014 samples"));
+}
+
+#[test]
+fn test_env_input() {
+    use crate::{parser::ParseOptions, span::Registry};
+
+    let template = Template::parse(
+        "CREATE TABLE t (a TEXT {{ env('HOME') }});",
+        &[],
+        &[],
+        &ParseOptions::default(),
+        &mut Registry::default(),
+    )
+    .unwrap();
+    assert_eq!(column_inputs(&template), [[vec!["env('HOME')".to_owned()]]]);
+}
@@ -1,12 +1,33 @@
-use clap::Parser as _;
+use clap::{CommandFactory as _, FromArgMatches as _};
 use dbgen::{
-    cli::{run, Args},
+    cli::{run, verify, Args, ErrorFormat, VerifyArgs},
     span::Registry,
 };
+use std::process::exit;
 
 fn main() {
+    let matches = Args::command()
+        .subcommand(VerifyArgs::command().name("verify"))
+        .args_conflicts_with_subcommands(true)
+        .subcommand_negates_reqs(true)
+        .get_matches();
+
     let mut registry = Registry::default();
-    if let Err(e) = run(Args::parse(), &mut registry) {
-        eprintln!("{}", registry.describe(&e));
+    let mut error_format = ErrorFormat::Human;
+    let mut template = None;
+    let result = if let Some(("verify", matches)) = matches.subcommand() {
+        verify(
+            &VerifyArgs::from_arg_matches(matches).unwrap_or_else(|e| e.exit()),
+            &mut registry,
+        )
+    } else {
+        let args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+        error_format = args.error_format;
+        template.clone_from(&args.template);
+        run(args, &mut registry)
+    };
+    if let Err(e) = result {
+        eprintln!("{}", error_format.describe(&registry, &e, template.as_deref()));
+        exit(1);
     }
 }
@@ -1,22 +1,35 @@
 //! CLI driver of `dbgen`.
 
 use crate::{
+    audit,
+    ddl::{self, SchemaDialect},
     error::Error,
-    eval::{CompileContext, Schema, State, Table},
-    format::{CsvFormat, Format, Options, SqlFormat, SqlInsertSetFormat},
+    eval::{self, fnv1a, CompileContext, Schema, State, Table},
+    explain,
+    format::{
+        find_column, unquote, ClickHouseRowBinaryFormat, ClickHouseTsvFormat, CsvFormat, CsvQuote, DebeziumFormat,
+        Dialect, FixedWidthFormat, Format, GeometryFormat, InsertMode, IntervalFormat, MySqlDumpFormat, Options,
+        PgCopyFormat, SqlFormat, SqlInsertSetFormat, TimestampFormat, UpsertStyle,
+    },
     lexctr::LexCtr,
-    parser::{QName, Template},
-    span::{Registry, ResultExt, SpanExt, S},
+    lint,
+    parser::{ParseOptions, QName, Template},
+    partition::PartitionFilter,
+    plugin,
+    progress::{FileProgress, JsonLines, Progress, ProgressSink},
+    sort::{self, SpillOptions},
+    span::{deserialize_spans_as, serialize_spans_after, Diagnostic, Registry, ResultExt, SpanExt, SpanLocation, S},
     value::{Value, TIMESTAMP_FORMAT},
-    writer::{self, Writer},
+    warning::{ColumnCheck, Warning, WarningKind},
+    writer::{self, ChangeOp, Writer},
 };
 
 use chrono::{NaiveDateTime, ParseResult, Utc};
-use clap::{Parser, ValueEnum};
+use clap::{CommandFactory, FromArgMatches, Parser, ValueEnum};
 use data_encoding::{DecodeError, DecodeKind, HEXLOWER_PERMISSIVE};
 use flate2::write::GzEncoder;
 use muldiv::MulDiv;
-use pbr::{MultiBar, Units};
+use pbr::{MultiBar, Pipe, ProgressBar, Units};
 use rand::{
     distributions::{Distribution, Standard},
     rngs::{mock::StepRng, OsRng},
@@ -24,23 +37,31 @@ use rand::{
 };
 use rayon::{
     iter::{IntoParallelIterator, ParallelIterator},
-    ThreadPoolBuilder,
+    ThreadPool, ThreadPoolBuilder,
 };
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::{
     borrow::Cow,
-    collections::HashMap,
+    cell::RefCell,
+    collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
     convert::TryInto,
+    ffi::OsString,
     fmt,
-    fs::{create_dir_all, read_to_string, File},
-    io::{self, sink, stdin, BufWriter, Read, Write},
-    mem,
+    fs::{create_dir_all, read_dir, read_to_string, remove_file, rename, File},
+    io::{self, sink, stdin, BufReader, BufWriter, Read, Write},
+    iter, mem,
+    num::{NonZeroU32, NonZeroU64},
     path::{Path, PathBuf},
+    rc::Rc,
     str::FromStr,
-    sync::atomic::{AtomicBool, AtomicU64, Ordering},
-    thread::{sleep, spawn},
-    time::Duration,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    thread::{sleep, spawn, JoinHandle},
+    time::{Duration, Instant},
 };
+use xxhash_rust::xxh64::Xxh64;
 use xz2::write::XzEncoder;
 
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
@@ -70,7 +91,7 @@ struct RowArgs {
 }
 
 /// Arguments to the `dbgen` CLI program.
-#[derive(Parser, Debug, Serialize, Deserialize)]
+#[derive(Parser, Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 #[command(long_version(crate::FULL_VERSION), next_line_help(true))]
 // ALLOW_REASON: command line arguments using bool is expected.
@@ -119,10 +140,30 @@ pub struct Args {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_insert_rows_count: Option<u32>,
 
+    /// Keep generating data until interrupted, instead of stopping after a fixed number of rows.
+    #[arg(long, conflicts_with_all(&["total_count", "last_file_inserts_count", "last_insert_rows_count"]))]
+    #[serde(skip_serializing_if = "is_false")]
+    pub continuous: bool,
+
+    /// Start a new data file after the current one has been written for this many seconds.
+    #[arg(long, value_parser = parse_seconds)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rotate_interval: Option<u64>,
+
+    /// Delete data files which have been closed for more than this many seconds.
+    #[arg(long, value_parser = parse_seconds, requires("continuous"))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retention: Option<u64>,
+
     /// Total number of rows of the main table.
     #[arg(short = 'N', long, value_parser = parse_row_count, conflicts_with_all(&["files_count", "last_file_inserts_count", "last_insert_rows_count"]))]
     pub total_count: Option<u64>,
 
+    /// Scale factor multiplying the total number of rows, exposed as `scale_factor` in the template.
+    #[arg(long, value_parser = parse_scale_factor)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scale_factor: Option<f64>,
+
     /// Number of rows per file generator thread.
     #[arg(short = 'R', long, value_parser = parse_row_count, conflicts_with_all(&["inserts_count"]))]
     pub rows_per_file: Option<u64>,
@@ -131,6 +172,22 @@ pub struct Args {
     #[arg(short = 'z', long, value_parser = |s: &str| parse_size::parse_size(s))]
     pub size: Option<u64>,
 
+    /// Number of rows of the main table in each data file.
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rows_per_data_file: Option<NonZeroU64>,
+
+    /// Write the rows of tables containing this column into Hive-style subdirectories named
+    /// `column=value`, one per distinct value of the column.
+    #[arg(long, conflicts_with_all(&["rows_per_data_file", "manifest"]))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partition_by: Option<String>,
+
+    /// Write a manifest with the row count, size and checksum alongside every data file.
+    #[arg(long)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub manifest: bool,
+
     /// Escape backslashes when writing a string.
     #[arg(long)]
     #[serde(skip_serializing_if = "is_false")]
@@ -151,6 +208,11 @@ pub struct Args {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub template_string: Option<String>,
 
+    /// Directory caching the parsed templates, to skip parsing on repeated runs.
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template_cache: Option<PathBuf>,
+
     /// Random number generator seed (should have 64 hex digits).
     #[arg(short, long)]
     pub seed: Option<Seed>,
@@ -165,11 +227,118 @@ pub struct Args {
     #[serde(skip_serializing_if = "is_hc128")]
     pub rng: RngName,
 
+    /// Derive the random number generator of every row from the seed and the row number.
+    #[arg(long, conflicts_with = "rng")]
+    #[serde(skip_serializing_if = "is_false")]
+    pub stable_seeding: bool,
+
     /// Disable progress bar.
     #[arg(short, long)]
     #[serde(skip_serializing_if = "is_false")]
     pub quiet: bool,
 
+    /// How to report the progress.
+    #[arg(long, value_enum, default_value = "bar")]
+    #[serde(skip_serializing_if = "is_progress_bar")]
+    pub progress: ProgressName,
+
+    /// How to print errors and warnings.
+    #[arg(long, value_enum, default_value = "human")]
+    #[serde(skip_serializing_if = "is_human")]
+    pub error_format: ErrorFormat,
+
+    /// Limit the number of rows of the main table written per second across all threads.
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rows_per_second: Option<NonZeroU32>,
+
+    /// Only generate rows belonging to these partitions of partitioned tables.
+    #[arg(long, value_delimiter(','))]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub partitions: Vec<String>,
+
+    /// Only write these columns, omitting the rest from the output.
+    #[arg(long, value_delimiter(','), conflicts_with("skip_columns"))]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub only_columns: Vec<String>,
+
+    /// Omit these columns from the output.
+    #[arg(long, value_delimiter(','))]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub skip_columns: Vec<String>,
+
+    /// Write only these columns in the given order in the CSV output, e.g. "b,a,c".
+    #[arg(long, value_delimiter(','), conflicts_with_all(["only_columns", "skip_columns"]))]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub csv_columns: Vec<String>,
+
+    /// Report the maximum length of string values in every column after generation.
+    #[arg(long)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub report_lengths: bool,
+
+    /// Print the input files every column is derived from and the estimated entropy of every
+    /// column, instead of writing any files.
+    #[arg(long)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub audit: bool,
+
+    /// Print the compiled expression tree of every column, instead of writing any files.
+    #[arg(long)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub explain: bool,
+
+    /// Together with `--explain`, also generate the rows in memory and report the average time
+    /// spent evaluating every column.
+    #[arg(long, requires("explain"))]
+    #[serde(skip_serializing_if = "is_false")]
+    pub explain_timing: bool,
+
+    /// Print the tables, the expected number of files and rows, and the estimated output size,
+    /// without writing anything.
+    #[arg(long)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub dry_run: bool,
+
+    /// Check the template for suspicious constructs instead of generating data, and fail if any
+    /// is found.
+    #[arg(long)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub check: bool,
+
+    /// Do not warn about values not fitting the column types.
+    #[arg(long)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub no_warnings: bool,
+
+    /// Fail when a value violates the column type, NOT NULL or CHECK constraints.
+    #[arg(long)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub validate_constraints: bool,
+
+    /// Probability that a row is immediately followed by an exact duplicate of itself.
+    #[arg(long, value_parser = parse_duplicate_rate)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duplicate_rate: Option<f64>,
+
+    /// Writes change events instead of plain rows, with the relative weights of inserts, updates
+    /// and deletes, e.g. `70,20,10`.
+    #[arg(long, value_delimiter = ',', value_parser = parse_change_weight)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub cdc_mix: Vec<f64>,
+
+    /// Writes a mixed stream of INSERT, UPDATE and DELETE statements instead of plain rows, with
+    /// the relative weights of each statement, e.g. `insert=80,update=15,delete=5`.
+    #[arg(long, value_delimiter = ',', value_parser = parse_dml_weight, conflicts_with = "cdc_mix")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub dml_mix: Vec<DmlWeight>,
+
+    /// Maximum number of inserted rows retained per table, shard and file to be updated or deleted
+    /// by change events.
+    #[arg(long, default_value = "10000")]
+    #[serde(skip_serializing_if = "is_default_cdc_retention")]
+    pub cdc_retention: usize,
+
     /// Time zone used for timestamps.
     #[arg(long, default_value = "UTC")]
     #[serde(skip_serializing_if = "is_utc")]
@@ -205,11 +374,112 @@ pub struct Args {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub format_null: Option<String>,
 
+    /// The widths of columns in the fixed-width format, e.g. "name=30". The widths of the other
+    /// columns are inferred from their types.
+    #[arg(long, value_delimiter(','), value_parser = parse_column_width)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub column_width: Vec<String>,
+
+    /// How INSERT statements treat rows conflicting with the existing data, written in the syntax
+    /// of the --dialect.
+    #[arg(long, value_enum, default_value = "insert", conflicts_with("upsert"))]
+    #[serde(skip_serializing_if = "is_plain_insert")]
+    pub insert_mode: InsertMode,
+
+    /// Turn the INSERT statements of the SQL format into upserts with the given clause.
+    #[arg(long, value_enum)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upsert: Option<UpsertStyle>,
+
+    /// The columns identifying a conflicting row of an upsert, e.g. the primary key.
+    #[arg(long, value_delimiter(','), requires("upsert"))]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub upsert_keys: Vec<String>,
+
+    /// The columns updated by an upsert. Defaults to every written column other than the keys.
+    #[arg(long, value_delimiter(','), requires("upsert"))]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub upsert_columns: Vec<String>,
+
+    /// Wrap every N statements of the data files in a BEGIN/COMMIT transaction.
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wrap_transaction: Option<NonZeroU32>,
+
+    /// Custom content written at the start of every data file, e.g. "SET foreign_key_checks=0;".
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_prologue: Option<String>,
+
+    /// Custom content written at the end of every data file, e.g. "ANALYZE TABLE t;".
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_epilogue: Option<String>,
+
+    /// The SQL dialect of the target database, which decides the identifier quotation marks and
+    /// the syntax of literals.
+    #[arg(long, value_enum)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dialect: Option<Dialect>,
+
+    /// Translates the schema files into the dialect of the target database, keeping only the
+    /// column names, types and nullability.
+    #[arg(long, value_enum)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema_dialect: Option<SchemaDialect>,
+
+    /// The representation of a time interval value.
+    #[arg(long, value_enum, default_value = "sql")]
+    #[serde(skip_serializing_if = "is_sql_interval")]
+    pub format_interval: IntervalFormat,
+
+    /// The representation of a timestamp value.
+    #[arg(long, value_enum, default_value = "sql")]
+    #[serde(skip_serializing_if = "is_sql_timestamp")]
+    pub format_timestamp: TimestampFormat,
+
+    /// The literal form of timestamps in the SQL and CSV formats: "none", "single-quote", or a
+    /// pattern where "{}" is replaced by the timestamp, e.g. "toDateTime('{}')".
+    #[arg(long, value_parser = parse_timestamp_wrapper)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format_timestamp_wrapper: Option<String>,
+
+    /// The representation of a geometric point value.
+    #[arg(long, value_enum, default_value = "wkt")]
+    #[serde(skip_serializing_if = "is_wkt")]
+    pub format_geometry: GeometryFormat,
+
     /// Include column names or headers in the output.
     #[arg(long)]
     #[serde(skip_serializing_if = "is_false")]
     pub headers: bool,
 
+    /// The character separating values in the CSV format, e.g. "tab", "|" or ";".
+    #[arg(long, value_parser = parse_csv_delimiter)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub csv_delimiter: Option<String>,
+
+    /// When strings are quoted in the CSV format.
+    #[arg(long, value_enum, default_value = "always")]
+    #[serde(skip_serializing_if = "is_always_quoted")]
+    pub csv_quote: CsvQuote,
+
+    /// End every row with "\r\n" instead of "\n" in the CSV format.
+    #[arg(long)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub csv_crlf: bool,
+
+    /// Replace line feeds inside strings by this content in the CSV format, e.g. "\n".
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub csv_newline_escape: Option<String>,
+
+    /// Write a script per table loading the CSV data files into the --dialect database, using
+    /// `LOAD DATA LOCAL INFILE` for MySQL (the default) or `\copy` for PostgreSQL.
+    #[arg(long, conflicts_with = "retention")]
+    #[serde(skip_serializing_if = "is_false")]
+    pub load_script: bool,
+
     /// Compress data output.
     #[arg(short, long, value_enum)]
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -239,6 +509,54 @@ pub struct Args {
     #[arg(long, short = 'D')]
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub initialize: Vec<String>,
+
+    /// Defines the template variable `@name`, in the form `name=value`.
+    #[arg(long = "var", value_parser = parse_var)]
+    #[serde(rename = "var", skip_serializing_if = "Vec::is_empty")]
+    pub vars: Vec<String>,
+
+    /// Loads functions from the plugin at this path.
+    #[arg(long = "plugin")]
+    #[serde(rename = "plugin", skip_serializing_if = "Vec::is_empty")]
+    pub plugins: Vec<PathBuf>,
+
+    /// Replaces the expression of a column, in the form `table.column={{ expr }}`.
+    #[arg(long = "override")]
+    #[serde(rename = "override", skip_serializing_if = "Vec::is_empty")]
+    pub overrides: Vec<String>,
+
+    /// Only writes the rows of the main tables satisfying this condition.
+    #[arg(long = "where")]
+    #[serde(rename = "where", skip_serializing_if = "Option::is_none")]
+    pub row_filter: Option<String>,
+
+    /// Sorts the rows of every table within each data file by this expression.
+    #[arg(long, conflicts_with_all(["cdc_mix", "dml_mix", "continuous", "size", "rows_per_data_file", "rotate_interval"]))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order_by: Option<String>,
+
+    /// Sorts the rows of a table within each data file by its own expression, in the form
+    /// `table=expr`, overriding `--order-by`.
+    #[arg(long, conflicts_with_all(["cdc_mix", "dml_mix", "continuous", "size", "rows_per_data_file", "rotate_interval"]))]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub table_order_by: Vec<String>,
+
+    /// Maximum number of rows per table and file held in memory while sorting, before they are
+    /// spilled into a temporary file in the output directory.
+    #[arg(long, default_value = "100000")]
+    #[serde(skip_serializing_if = "is_default_sort_buffer_rows")]
+    pub sort_buffer_rows: usize,
+
+    /// Infers the expressions of tables without any expressions from their column types.
+    #[arg(long)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub infer_expressions: bool,
+
+    /// Generates only this version of a template declaring multiple schema versions. By default,
+    /// every version is written into its own subdirectory `v1`, `v2`, … of the output directory.
+    #[arg(long)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<u32>,
 }
 
 impl Default for Args {
@@ -253,16 +571,44 @@ impl Default for Args {
             rows_count: 1,
             last_file_inserts_count: None,
             last_insert_rows_count: None,
+            continuous: false,
+            rotate_interval: None,
+            retention: None,
             total_count: None,
+            scale_factor: None,
             rows_per_file: None,
             size: None,
+            rows_per_data_file: None,
+            partition_by: None,
+            manifest: false,
             escape_backslash: false,
             template: None,
             template_string: None,
+            template_cache: None,
             seed: None,
             jobs: 0,
             rng: RngName::Hc128,
+            stable_seeding: false,
             quiet: false,
+            progress: ProgressName::Bar,
+            error_format: ErrorFormat::Human,
+            rows_per_second: None,
+            partitions: Vec::new(),
+            only_columns: Vec::new(),
+            skip_columns: Vec::new(),
+            csv_columns: Vec::new(),
+            report_lengths: false,
+            audit: false,
+            explain: false,
+            explain_timing: false,
+            dry_run: false,
+            check: false,
+            no_warnings: false,
+            validate_constraints: false,
+            duplicate_rate: None,
+            cdc_mix: Vec::new(),
+            dml_mix: Vec::new(),
+            cdc_retention: DEFAULT_CDC_RETENTION,
             time_zone: "UTC".to_owned(),
             zoneinfo: PathBuf::from("/usr/share/zoneinfo"),
             now: None,
@@ -270,13 +616,41 @@ impl Default for Args {
             format_true: None,
             format_false: None,
             format_null: None,
+            column_width: Vec::new(),
+            insert_mode: InsertMode::Insert,
+            upsert: None,
+            upsert_keys: Vec::new(),
+            upsert_columns: Vec::new(),
+            wrap_transaction: None,
+            file_prologue: None,
+            file_epilogue: None,
+            dialect: None,
+            schema_dialect: None,
+            format_interval: IntervalFormat::Sql,
+            format_timestamp: TimestampFormat::Sql,
+            format_timestamp_wrapper: None,
+            format_geometry: GeometryFormat::Wkt,
             headers: false,
+            csv_delimiter: None,
+            csv_quote: CsvQuote::Always,
+            csv_crlf: false,
+            csv_newline_escape: None,
+            load_script: false,
             compression: None,
             compress_level: 6,
             components: vec![ComponentName::Table, ComponentName::Data],
             no_schemas: false,
             no_data: false,
             initialize: Vec::new(),
+            vars: Vec::new(),
+            plugins: Vec::new(),
+            overrides: Vec::new(),
+            row_filter: None,
+            order_by: None,
+            table_order_by: Vec::new(),
+            sort_buffer_rows: DEFAULT_SORT_BUFFER_ROWS,
+            infer_expressions: false,
+            schema_version: None,
         }
     }
 }
@@ -314,6 +688,22 @@ fn is_six(u: &u8) -> bool {
     *u == 6
 }
 
+/// The default value of `--cdc-retention`.
+const DEFAULT_CDC_RETENTION: usize = 10000;
+
+#[allow(clippy::trivially_copy_pass_by_ref)] // the signature is required by serde.
+fn is_default_cdc_retention(u: &usize) -> bool {
+    *u == DEFAULT_CDC_RETENTION
+}
+
+/// The default value of `--sort-buffer-rows`.
+const DEFAULT_SORT_BUFFER_ROWS: usize = sort::DEFAULT_BUFFER_ROWS;
+
+#[allow(clippy::trivially_copy_pass_by_ref)] // the signature is required by serde.
+fn is_default_sort_buffer_rows(u: &usize) -> bool {
+    *u == DEFAULT_SORT_BUFFER_ROWS
+}
+
 fn is_utc(tz: &str) -> bool {
     tz == "UTC"
 }
@@ -334,6 +724,46 @@ fn is_sql(format: &FormatName) -> bool {
     *format == FormatName::Sql
 }
 
+// ALLOW_REASON: the arguments of serde helper must be references.
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn is_sql_interval(format: &IntervalFormat) -> bool {
+    *format == IntervalFormat::Sql
+}
+
+// ALLOW_REASON: the arguments of serde helper must be references.
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn is_sql_timestamp(format: &TimestampFormat) -> bool {
+    *format == TimestampFormat::Sql
+}
+
+// ALLOW_REASON: the arguments of serde helper must be references.
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn is_wkt(format: &GeometryFormat) -> bool {
+    *format == GeometryFormat::Wkt
+}
+
+// ALLOW_REASON: the arguments of serde helper must be references.
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn is_plain_insert(mode: &InsertMode) -> bool {
+    *mode == InsertMode::Insert
+}
+
+// ALLOW_REASON: the arguments of serde helper must be references.
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn is_always_quoted(quote: &CsvQuote) -> bool {
+    *quote == CsvQuote::Always
+}
+
+// ALLOW_REASON: the arguments of serde helper must be references.
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn is_progress_bar(progress: &ProgressName) -> bool {
+    *progress == ProgressName::Bar
+}
+
+fn is_human(error_format: &ErrorFormat) -> bool {
+    *error_format == ErrorFormat::Human
+}
+
 fn is_default_components(components: &[ComponentName]) -> bool {
     ComponentName::union_all(components.iter().copied()) == ComponentName::Table as u8 | ComponentName::Data as u8
 }
@@ -343,7 +773,208 @@ fn parse_row_count(input: &str) -> Result<u64, parse_size::Error> {
     Config::new().with_byte_suffix(ByteSuffix::Deny).parse_size(input)
 }
 
+/// Parses a scale factor, which must be a positive finite number.
+fn parse_scale_factor(input: &str) -> Result<f64, String> {
+    match input.parse::<f64>() {
+        Ok(scale_factor) if scale_factor.is_finite() && scale_factor > 0.0 => Ok(scale_factor),
+        _ => Err(format!("expected a positive number, found {input:?}")),
+    }
+}
+
+/// Parses a weight of the change event mix, which must be a non-negative finite number.
+fn parse_change_weight(input: &str) -> Result<f64, String> {
+    match input.parse::<f64>() {
+        Ok(weight) if weight.is_finite() && weight >= 0.0 => Ok(weight),
+        _ => Err(format!("expected a non-negative number, found {input:?}")),
+    }
+}
+
+/// Checks that a column width is in the form `name=width`. The name may itself contain `=` if
+/// quoted.
+fn parse_column_width(input: &str) -> Result<String, String> {
+    let (_, width) = input
+        .rsplit_once('=')
+        .ok_or_else(|| format!("expected name=width, found {input:?}"))?;
+    width
+        .parse::<usize>()
+        .map_err(|_| format!("expected a non-negative integer width, found {width:?}"))?;
+    Ok(input.to_owned())
+}
+
+/// Checks that the CSV delimiter is a single ASCII character other than a quotation mark or a line
+/// break. `tab` and `\t` are accepted as the tab character.
+fn parse_csv_delimiter(input: &str) -> Result<String, String> {
+    let delimiter = match input {
+        "tab" | r"\t" => "\t",
+        _ => input,
+    };
+    match delimiter.as_bytes() {
+        [b'"' | b'\r' | b'\n'] => Err(format!("the delimiter cannot be {input:?}")),
+        [b] if b.is_ascii() => Ok(delimiter.to_owned()),
+        _ => Err(format!("expected a single ASCII character, found {input:?}")),
+    }
+}
+
+/// Checks that the timestamp wrapper is `none`, `single-quote`, or a pattern containing `{}` exactly
+/// once, and converts it into the pattern.
+fn parse_timestamp_wrapper(input: &str) -> Result<String, String> {
+    let pattern = match input {
+        "none" => "{}",
+        "single-quote" => "'{}'",
+        _ => input,
+    };
+    if pattern.matches("{}").count() != 1 {
+        return Err(format!(
+            "expected none, single-quote, or a pattern containing {{}} exactly once, found {input:?}"
+        ));
+    }
+    Ok(pattern.to_owned())
+}
+
+/// Parses a weight of the DML mix in the form `statement=weight`, where the statement is
+/// `insert`, `update` or `delete`.
+fn parse_dml_weight(input: &str) -> Result<DmlWeight, String> {
+    let (statement, weight) = input
+        .split_once('=')
+        .ok_or_else(|| format!("expected statement=weight, found {input:?}"))?;
+    let op = match &*statement.to_ascii_lowercase() {
+        "insert" => ChangeOp::Insert,
+        "update" => ChangeOp::Update,
+        "delete" => ChangeOp::Delete,
+        _ => return Err(format!("expected insert, update or delete, found {statement:?}")),
+    };
+    let weight = parse_change_weight(weight)?;
+    Ok(DmlWeight { op, weight })
+}
+
+/// Parses a duplicate rate, which must be a probability between 0 and 1.
+fn parse_duplicate_rate(input: &str) -> Result<f64, String> {
+    match input.parse::<f64>() {
+        Ok(rate) if (0.0..=1.0).contains(&rate) => Ok(rate),
+        _ => Err(format!("expected a number between 0 and 1, found {input:?}")),
+    }
+}
+
+/// Checks that a template variable is in the form `name=value`.
+fn parse_var(input: &str) -> Result<String, String> {
+    let (name, _) = input
+        .split_once('=')
+        .ok_or_else(|| format!("expected name=value, found {input:?}"))?;
+    let mut chars = name.chars();
+    if chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        Ok(input.to_owned())
+    } else {
+        Err(format!("invalid variable name {name:?}"))
+    }
+}
+
+/// Obtains the weights of inserts, updates and deletes from `--cdc-mix` or `--dml-mix`, and
+/// checks that the output format can describe these changes.
+fn change_weights(args: &Args) -> Result<Option<[f64; 3]>, S<Error>> {
+    let (weights, formats): (_, &[FormatName]) = if !args.dml_mix.is_empty() {
+        let mut weights = [0.0; 3];
+        for entry in &args.dml_mix {
+            let index = match entry.op {
+                ChangeOp::Insert => 0,
+                ChangeOp::Update => 1,
+                ChangeOp::Delete => 2,
+            };
+            weights[index] += entry.weight;
+        }
+        (
+            Some(weights),
+            &[FormatName::Sql, FormatName::SqlInsertSet, FormatName::Mysqldump],
+        )
+    } else if !args.cdc_mix.is_empty() {
+        (
+            <[f64; 3]>::try_from(&*args.cdc_mix).ok(),
+            &[
+                FormatName::Sql,
+                FormatName::SqlInsertSet,
+                FormatName::Mysqldump,
+                FormatName::Debezium,
+            ],
+        )
+    } else {
+        return Ok(None);
+    };
+    let weights = weights
+        .filter(|weights| weights.iter().sum::<f64>() > 0.0)
+        .ok_or_else(|| {
+            Error::UnsupportedCliParameter {
+                kind: "change event mix",
+                value: if args.dml_mix.is_empty() {
+                    args.cdc_mix.iter().map(f64::to_string).collect::<Vec<_>>().join(",")
+                } else {
+                    args.dml_mix
+                        .iter()
+                        .map(DmlWeight::to_string)
+                        .collect::<Vec<_>>()
+                        .join(",")
+                },
+            }
+            .no_span()
+        })?;
+    if !formats.contains(&args.format) {
+        return Err(Error::UnsupportedCliParameter {
+            kind: "output format of change events",
+            value: format!("{:?}", args.format),
+        }
+        .no_span());
+    }
+    Ok(Some(weights))
+}
+
+/// Converts a template variable `name=value` into a global expression assigning the variable.
+///
+/// The value is assigned as a number if it looks like one, and as a string otherwise.
+fn var_to_global_expr(var: &str) -> String {
+    let (name, value) = var.split_once('=').unwrap_or((var, ""));
+    let digits = value.strip_prefix('-').unwrap_or(value);
+    if digits.bytes().all(|b| b.is_ascii_digit() || b == b'.') && digits.parse::<f64>().is_ok() {
+        format!("@{name} := {value}")
+    } else {
+        format!("@{name} := '{}'", value.replace('\'', "''"))
+    }
+}
+
+/// Parses a duration into number of seconds. The number may be followed by one of the units `s`,
+/// `m`, `h` or `d`.
+fn parse_seconds(input: &str) -> Result<u64, String> {
+    let input = input.trim();
+    let (number, unit) = input.split_at(input.find(|c: char| !c.is_ascii_digit()).unwrap_or(input.len()));
+    let multiplier = match unit.trim_start() {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        _ => return Err(format!("unknown time unit `{unit}`")),
+    };
+    number
+        .parse::<u64>()
+        .map_err(|e| e.to_string())?
+        .checked_mul(multiplier)
+        .ok_or_else(|| "duration is too long".to_owned())
+}
+
 impl Args {
+    /// Computes the total number of rows of the main table, multiplied by the scale factor.
+    fn scaled_total_count(&self) -> Option<u64> {
+        let total_count = self.total_count?;
+        Some(match self.scale_factor {
+            // ALLOW_REASON: the scaled count is rounded to a non-negative integer.
+            #[allow(
+                clippy::cast_possible_truncation,
+                clippy::cast_precision_loss,
+                clippy::cast_sign_loss
+            )]
+            Some(scale_factor) => ((total_count as f64) * scale_factor).round().max(1.0) as u64,
+            None => total_count,
+        })
+    }
+
     /// Computes the row-related arguments.
     fn row_args(&self) -> RowArgs {
         let mut res = RowArgs {
@@ -365,7 +996,7 @@ impl Args {
         }
 
         // compute the total number of rows.
-        if let Some(total_rows_count) = self.total_count {
+        if let Some(total_rows_count) = self.scaled_total_count() {
             let (files_count, excess_rows_count) = div_rem_plus_one(total_rows_count, res.rows_per_file);
             res.files_count = files_count.try_into().expect("--total-count is too large");
             if excess_rows_count == res.rows_per_file {
@@ -420,12 +1051,8 @@ impl<T> PathResultExt for io::Result<T> {
     }
 }
 
-/// Indicator whether all tables are written. Used by the progress bar thread to break the loop.
-static WRITE_FINISHED: AtomicBool = AtomicBool::new(false);
-/// Counter of number of rows being written.
-static WRITE_PROGRESS: AtomicU64 = AtomicU64::new(0);
-/// Counter of number of bytes being written.
-static WRITTEN_SIZE: AtomicU64 = AtomicU64::new(0);
+/// Indicator whether a termination signal is received. Used by continuous generation to stop.
+static STOP_REQUESTED: AtomicBool = AtomicBool::new(false);
 
 /// Reads the template file
 fn read_template_file(path: &Path) -> Result<String, S<Error>> {
@@ -438,13 +1065,168 @@ fn read_template_file(path: &Path) -> Result<String, S<Error>> {
     .with_path("read template", path)
 }
 
+/// The inputs of parsing a template, identifying an entry in the template cache.
+#[derive(Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+struct TemplateSource {
+    version: String,
+    input: String,
+    initialize: Vec<String>,
+    overrides: Vec<String>,
+    row_filter: Option<String>,
+    order_by: Option<String>,
+    table_order_by: Vec<String>,
+    infer_expressions: bool,
+    schema_name: Option<String>,
+}
+
+impl TemplateSource {
+    /// The options of parsing the template.
+    fn parse_options(&self) -> ParseOptions<'_> {
+        ParseOptions {
+            row_filter: self.row_filter.as_deref(),
+            order_by: self.order_by.as_deref(),
+            table_order_by: self
+                .table_order_by
+                .iter()
+                .filter_map(|entry| entry.split_once('='))
+                .collect(),
+            infer_expressions: self.infer_expressions,
+            override_schema: self.schema_name.as_deref(),
+        }
+    }
+
+    /// All inputs the spans of the parsed template may refer to.
+    fn inputs(&self) -> Vec<&str> {
+        let mut inputs = vec![&*self.input];
+        inputs.extend(self.initialize.iter().map(|s| &**s));
+        inputs.extend(self.overrides.iter().map(|s| &**s));
+        inputs.extend(self.row_filter.as_deref());
+        inputs.extend(self.order_by.as_deref());
+        inputs.extend(
+            self.table_order_by
+                .iter()
+                .filter_map(|entry| Some(entry.split_once('=')?.1)),
+        );
+        inputs
+    }
+}
+
+/// An entry in the template cache.
+///
+/// The spans in the template are serialized as indices into `spans`, which locate them in the
+/// inputs of the `source`, so that errors found when generating data from a cached template still
+/// point to the template.
+#[derive(Serialize, Deserialize)]
+struct TemplateCacheEntry<T> {
+    source: TemplateSource,
+    spans: Vec<Option<SpanLocation>>,
+    template: T,
+}
+
+/// Parses the template, or reuses the parsed template in the cache directory if the same template
+/// has been parsed with the same arguments before.
+fn parse_cached_template(
+    cache_dir: &Path,
+    source: TemplateSource,
+    quiet: bool,
+    span_registry: &mut Registry,
+) -> Result<Template, S<Error>> {
+    let key = fnv1a(serde_json::to_vec(&source).expect("source should be serializable"));
+    let path = cache_dir.join(format!("{key:016x}.json"));
+
+    // a corrupted or mismatched entry is treated as a cache miss and overwritten.
+    let cached = File::open(&path)
+        .ok()
+        .and_then(|file| serde_json::from_reader::<_, TemplateCacheEntry<serde_json::Value>>(BufReader::new(file)).ok())
+        .filter(|entry| entry.source == source)
+        .and_then(|entry| {
+            let spans = span_registry.relocate(&entry.spans, &source.inputs());
+            deserialize_spans_as(spans, || Template::deserialize(entry.template)).ok()
+        });
+    if let Some(template) = cached {
+        if !quiet {
+            println!("Using cached template: {}", path.display());
+        }
+        return Ok(template);
+    }
+
+    let base = span_registry.registered_count();
+    let template = Template::parse(
+        &source.input,
+        &source.initialize,
+        &source.overrides,
+        &source.parse_options(),
+        span_registry,
+    )?;
+    let spans = span_registry.locate(base, &source.inputs());
+    let entry = TemplateCacheEntry {
+        source,
+        spans,
+        template,
+    };
+
+    create_dir_all(cache_dir).with_path("create template cache directory", cache_dir)?;
+    let temp_path = path.with_extension("tmp");
+    let mut file = BufWriter::new(File::create(&temp_path).with_path("create template cache", &temp_path)?);
+    serialize_spans_after(base, || serde_json::to_writer(&mut file, &entry))
+        .map_err(io::Error::from)
+        .and_then(|()| file.flush())
+        .with_path("write template cache", &temp_path)?;
+    drop(file);
+    rename(&temp_path, &path).with_path("rename template cache", &path)?;
+    Ok(entry.template)
+}
+
+/// Resources reused when running the CLI program multiple times in the same process, e.g. the steps
+/// of `dbdbgen`.
+///
+/// The parsed templates are kept for the following runs with the same template and parse options,
+/// and the thread pools for the following runs with the same number of jobs. Since the parsed
+/// templates refer to the spans registered while parsing, the same [`Registry`] must be passed to
+/// every run of a session.
+#[derive(Default)]
+pub struct Session {
+    /// The parsed templates, keyed by the inputs of parsing.
+    templates: HashMap<TemplateSource, Template>,
+    /// The thread pools, keyed by the number of jobs.
+    pools: HashMap<usize, ThreadPool>,
+    /// The receiver of the progress, overriding the `--progress` argument.
+    progress_sink: Option<Box<dyn ProgressSink>>,
+}
+
+impl Session {
+    /// Reports the progress of every following run to the sink, instead of the reporter selected
+    /// by the `--progress` argument.
+    pub fn set_progress_sink(&mut self, sink: Box<dyn ProgressSink>) {
+        self.progress_sink = Some(sink);
+    }
+}
+
+impl fmt::Debug for Session {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Session")
+            .field("templates_count", &self.templates.len())
+            .field("pools", &self.pools)
+            .field("has_progress_sink", &self.progress_sink.is_some())
+            .finish()
+    }
+}
+
 /// Runs the CLI program.
+pub fn run(args: Args, span_registry: &mut Registry) -> Result<(), S<Error>> {
+    run_in_session(args, &mut Session::default(), span_registry)
+}
+
+/// Runs the CLI program, reusing the resources of the session.
 // ALLOW_REASON: we will try to refactor this some day...
 #[allow(clippy::too_many_lines)]
-pub fn run(args: Args, span_registry: &mut Registry) -> Result<(), S<Error>> {
+pub fn run_in_session(args: Args, session: &mut Session, span_registry: &mut Registry) -> Result<(), S<Error>> {
     let row_args = args.row_args();
-    let input = match (args.template_string, &args.template) {
-        (Some(input), _) => input,
+    for path in &args.plugins {
+        plugin::load(path).no_span_err()?;
+    }
+    let input = match (&args.template_string, &args.template) {
+        (Some(input), _) => input.clone(),
         (None, Some(template)) => read_template_file(template)?,
         _ => {
             return Err(Error::UnsupportedCliParameter {
@@ -454,9 +1236,79 @@ pub fn run(args: Args, span_registry: &mut Registry) -> Result<(), S<Error>> {
             .no_span())
         }
     };
-    let mut template = Template::parse(&input, &args.initialize, args.schema_name.as_deref(), span_registry)?;
+    if let Some(entry) = args.table_order_by.iter().find(|entry| !entry.contains('=')) {
+        return Err(Error::UnsupportedCliParameter {
+            kind: "per-table sort key (must be in the form `table=expr`)",
+            value: entry.clone(),
+        }
+        .no_span());
+    }
+    let source = TemplateSource {
+        version: crate::FULL_VERSION.to_owned(),
+        input,
+        initialize: args
+            .vars
+            .iter()
+            .map(|var| var_to_global_expr(var))
+            .chain(args.initialize.iter().cloned())
+            .collect(),
+        overrides: args.overrides.clone(),
+        row_filter: args.row_filter.clone(),
+        order_by: args.order_by.clone(),
+        table_order_by: args.table_order_by.clone(),
+        infer_expressions: args.infer_expressions,
+        schema_name: args.schema_name.clone(),
+    };
+    let mut template = if let Some(template) = session.templates.get(&source) {
+        template.clone()
+    } else {
+        let template = if let Some(cache_dir) = &args.template_cache {
+            parse_cached_template(cache_dir, source.clone(), args.quiet, span_registry)?
+        } else {
+            Template::parse(
+                &source.input,
+                &source.initialize,
+                &source.overrides,
+                &source.parse_options(),
+                span_registry,
+            )?
+        };
+        session.templates.insert(source, template.clone());
+        template
+    };
+
+    let versions_count = template.versions_count();
+    match args.schema_version {
+        _ if args.check => {}
+        Some(version) if (1..=versions_count).contains(&version) => template.select_version(version)?,
+        Some(version) => {
+            return Err(Error::UnsupportedCliParameter {
+                kind: "schema version",
+                value: version.to_string(),
+            }
+            .no_span())
+        }
+        None if versions_count > 0 => {
+            // every version is generated from the same seed and timestamp to contain the same rows.
+            let mut args = args;
+            args.seed = Some(args.seed.unwrap_or_else(|| OsRng.gen()));
+            args.now = Some(args.now.unwrap_or_else(|| Utc::now().naive_utc()));
+            let out_dir = mem::take(&mut args.out_dir);
+            for version in 1..=versions_count {
+                let mut args = args.clone();
+                args.schema_version = Some(version);
+                args.out_dir = out_dir.join(format!("v{version}"));
+                run_in_session(args, session, span_registry)?;
+            }
+            return Ok(());
+        }
+        None => {}
+    }
 
-    let pool = ThreadPoolBuilder::new().num_threads(args.jobs).build().no_span_err()?;
+    let pool = match session.pools.entry(args.jobs) {
+        Entry::Occupied(entry) => entry.into_mut(),
+        Entry::Vacant(entry) => entry.insert(ThreadPoolBuilder::new().num_threads(args.jobs).build().no_span_err()?),
+    };
 
     if let Some(override_table_name) = &args.table_name {
         if template.tables.len() != 1 {
@@ -464,18 +1316,172 @@ pub fn run(args: Args, span_registry: &mut Registry) -> Result<(), S<Error>> {
         }
         template.tables[0].name = QName::parse(override_table_name).no_span_err()?;
     }
+    if let Some(dialect) = args.dialect {
+        for table in &mut template.tables {
+            table.requote_identifiers(dialect);
+        }
+    }
 
+    let meta_seed = args.seed.unwrap_or_else(|| OsRng.gen());
     let mut ctx = CompileContext::new(template.variables_count);
+    ctx.namespace_seed = meta_seed.0;
+    ctx.stable_seeding = args.stable_seeding;
+    ctx.scale_factor = args.scale_factor.unwrap_or(1.0);
     ctx.zoneinfo = args.zoneinfo;
     ctx.time_zone = ctx.parse_time_zone(&args.time_zone).no_span_err()?;
     ctx.current_timestamp = args.now.unwrap_or_else(|| Utc::now().naive_utc());
-    let tables = template
+    if args.check {
+        let lints = lint::lint(&template, &ctx);
+        for lint in &lints {
+            eprintln!(
+                "{}",
+                args.error_format
+                    .describe_warning(span_registry, lint, args.template.as_deref())
+            );
+        }
+        if !lints.is_empty() {
+            return Err(Error::LintFailed { count: lints.len() }.no_span());
+        }
+        if !args.quiet {
+            println!("No suspicious constructs found.");
+        }
+        return Ok(());
+    }
+    let template_tables_count = template.tables.len();
+    let audit_inputs = args.audit.then(|| audit::column_inputs(&template));
+    let mut tables = template
         .tables
         .into_iter()
         .map(|t| ctx.compile_table(t))
-        .collect::<Result<_, _>>()?;
-
-    create_dir_all(&args.out_dir).with_path("create output directory", &args.out_dir)?;
+        .collect::<Result<Vec<_>, _>>()?;
+    ctx.clock_stride = eval::clock_stride(&tables);
+    if !args.partitions.is_empty() {
+        restrict_partitions(&mut tables, &args.partitions)?;
+    }
+    if let Some(filter) = template.row_filter {
+        ctx.compile_row_filter(&mut tables, filter)?;
+    }
+    if let Some(order_by) = template.order_by {
+        ctx.compile_order_by(&mut tables, order_by)?;
+    }
+    for (table_name, order_by) in template.table_order_by {
+        ctx.compile_table_order_by(&mut tables, &table_name, order_by)?;
+    }
+    if tables.iter().any(|table| table.order_by.is_some()) {
+        // the sorted rows are only written when the data file ends, so the file must neither be
+        // split nor written forever. clap rejects these combinations already, but serialized
+        // arguments bypass it.
+        let splitting = [
+            ("--continuous", args.continuous),
+            ("--size", args.size.is_some()),
+            ("--rows-per-data-file", args.rows_per_data_file.is_some()),
+            ("--rotate-interval", args.rotate_interval.is_some()),
+        ];
+        if let Some((flag, _)) = splitting.iter().find(|(_, enabled)| *enabled) {
+            return Err(Error::UnsupportedCliParameter {
+                kind: "parameter combined with --order-by",
+                value: (*flag).to_owned(),
+            }
+            .no_span());
+        }
+    }
+    if !args.only_columns.is_empty() || !args.skip_columns.is_empty() {
+        restrict_columns(&mut tables, &args.only_columns, &args.skip_columns)?;
+    }
+    check_column_widths(&tables, &args.column_width)?;
+    if let Some(dialect) = args.schema_dialect {
+        translate_auxiliary_statements(&mut tables, dialect, args.quiet);
+    }
+    check_sharded_auxiliary_statements(&tables)?;
+    if let Some(style) = args.upsert {
+        check_upsert(
+            &tables,
+            args.format,
+            args.dialect,
+            style,
+            &args.upsert_keys,
+            &args.upsert_columns,
+        )?;
+    }
+    if !args.csv_columns.is_empty() {
+        if args.format != FormatName::Csv {
+            return Err(Error::UnsupportedCliParameter {
+                kind: "output format for --csv-columns",
+                value: format!("{:?}", args.format),
+            }
+            .no_span());
+        }
+        select_columns(&mut tables, &args.csv_columns, "CSV column")?;
+    }
+    if args.wrap_transaction.is_some() && args.format == FormatName::Mysqldump {
+        // mysqldump locks the tables, and BEGIN would implicitly release the locks.
+        return Err(Error::UnsupportedCliParameter {
+            kind: "output format for --wrap-transaction",
+            value: format!("{:?}", args.format),
+        }
+        .no_span());
+    }
+    let change_weights = change_weights(&args)?;
+    if let Some(column) = &args.partition_by {
+        if !tables
+            .iter()
+            .any(|t| t.schema(false).column_names().any(|name| name == column))
+        {
+            return Err(Error::UnsupportedCliParameter {
+                kind: "partition column",
+                value: column.clone(),
+            }
+            .no_span());
+        }
+    }
+
+    let show_progress = !args.quiet;
+    if show_progress {
+        println!("Using seed: {meta_seed}");
+    }
+    let mut seeding_rng = meta_seed.make_rng();
+
+    let rng_name = args.rng;
+
+    // Evaluate the global expressions if necessary.
+    if !template.global_exprs.is_empty() {
+        let row_gen = ctx.compile_row(template.global_exprs)?;
+        let mut state = State::new(0, rng_name.create(&mut seeding_rng), ctx);
+        row_gen.eval(&mut state)?;
+        ctx = state.into_compile_context();
+    }
+
+    if let Some(inputs) = audit_inputs {
+        let mut state = State::new(1, rng_name.create(&mut seeding_rng), ctx);
+        let columns = audit::audit(&tables, inputs, &mut state, args.qualified, row_args.total_count)?;
+        println!("Audit of {} sampled rows:", row_args.total_count);
+        for column in columns {
+            println!("  {column}");
+        }
+        return Ok(());
+    }
+
+    if args.explain {
+        let timings = if args.explain_timing {
+            let mut state = State::new(1, rng_name.create(&mut seeding_rng), ctx);
+            Some(explain::profile(&tables, &mut state, row_args.total_count)?)
+        } else {
+            None
+        };
+        print!("{}", explain::explain(&tables, args.qualified, timings.as_deref()));
+        return Ok(());
+    }
+
+    if args.dry_run {
+        println!(
+            "Dry run: {} rows of every main table, in {} files of {} rows each",
+            row_args.total_count, row_args.files_count, row_args.rows_per_file
+        );
+        print!("{}", explain::dry_run(&tables, args.qualified, row_args.total_count));
+        return Ok(());
+    }
+
+    create_dir_all(&args.out_dir).with_path("create output directory", &args.out_dir)?;
 
     let compress_level = args.compress_level;
     let mut components_mask = ComponentName::union_all(args.components);
@@ -486,65 +1492,230 @@ pub fn run(args: Args, span_registry: &mut Registry) -> Result<(), S<Error>> {
         ComponentName::Schema.remove_from(&mut components_mask);
         ComponentName::Table.remove_from(&mut components_mask);
     }
+    // the delimiter is validated again, since it may come from a deserialized `Args` too.
+    let csv_delimiter = match &args.csv_delimiter {
+        None => b',',
+        Some(delimiter) => parse_csv_delimiter(delimiter)
+            .map_err(|_| {
+                Error::UnsupportedCliParameter {
+                    kind: "CSV delimiter",
+                    value: delimiter.clone(),
+                }
+                .no_span()
+            })?
+            .as_bytes()[0],
+    };
+    // the duplicate rate is validated again for the same reason, since it is used as a probability.
+    if let Some(rate) = args.duplicate_rate.filter(|rate| !(0.0..=1.0).contains(rate)) {
+        return Err(Error::UnsupportedCliParameter {
+            kind: "duplicate rate",
+            value: rate.to_string(),
+        }
+        .no_span());
+    }
+    if args.format == FormatName::Csv && args.csv_quote == CsvQuote::Never && args.headers {
+        check_unquoted_csv_headers(&tables, csv_delimiter, args.csv_newline_escape.is_some())?;
+    }
+    let timestamp_wrapper = match &args.format_timestamp_wrapper {
+        None => None,
+        Some(wrapper) => {
+            let pattern = parse_timestamp_wrapper(wrapper).map_err(|_| {
+                Error::UnsupportedCliParameter {
+                    kind: "timestamp wrapper",
+                    value: wrapper.clone(),
+                }
+                .no_span()
+            })?;
+            let (prefix, suffix) = pattern.split_once("{}").unwrap_or_default();
+            Some((prefix.to_owned(), suffix.to_owned()))
+        }
+    };
+    if args.insert_mode != InsertMode::Insert && args.upsert.is_some() {
+        // clap rejects this combination already, but serialized arguments bypass it.
+        return Err(Error::UnsupportedCliParameter {
+            kind: "insert mode combined with --upsert",
+            value: format!("{:?}", args.insert_mode),
+        }
+        .no_span());
+    }
+    if args.insert_mode != InsertMode::Insert
+        && args.format == FormatName::SqlInsertSet
+        && !matches!(args.dialect, None | Some(Dialect::Mysql))
+    {
+        // `INSERT … SET` is a MySQL extension, so only the MySQL keywords make sense.
+        return Err(Error::UnsupportedCliParameter {
+            kind: "insert mode of the sql-insert-set format",
+            value: format!("{:?}", args.insert_mode),
+        }
+        .no_span());
+    }
+    if args.insert_mode.keyword(args.dialect).is_none() {
+        return Err(Error::UnsupportedCliParameter {
+            kind: "insert mode of the dialect",
+            value: format!("{:?}", args.insert_mode),
+        }
+        .no_span());
+    }
+    let load_script = if args.load_script {
+        Some(check_load_script(
+            args.format,
+            args.dialect,
+            args.compression,
+            args.csv_newline_escape.as_deref(),
+            args.escape_backslash || args.dialect == Some(Dialect::Mysql),
+        )?)
+    } else {
+        None
+    };
     let format = args.format;
+    let columns_omitted = tables.iter().any(|t| t.skipped_columns.contains(&true));
     let env = Env {
         out_dir: args.out_dir,
         file_num_digits: args.files_count.to_string().len(),
         tables,
         qualified: args.qualified,
+        schema_dialect: args.schema_dialect,
         rows_count: args.rows_count,
         format,
         format_options: Options {
-            escape_backslash: args.escape_backslash,
-            headers: args.headers,
+            escape_backslash: args.escape_backslash || args.dialect == Some(Dialect::Mysql),
+            // the column list is needed for the database to fill in the default values of the
+            // omitted columns.
+            headers: args.headers || (columns_omitted && format != FormatName::Csv),
             true_string: args
                 .format_true
-                .map_or_else(|| format.default_true_string(), Cow::Owned),
+                .map_or_else(|| format.default_true_string(args.dialect), Cow::Owned),
             false_string: args
                 .format_false
-                .map_or_else(|| format.default_false_string(), Cow::Owned),
+                .map_or_else(|| format.default_false_string(args.dialect), Cow::Owned),
             null_string: args
                 .format_null
                 .map_or_else(|| format.default_null_string(), Cow::Owned),
+            interval_format: args.format_interval,
+            timestamp_format: args.format_timestamp,
+            timestamp_wrapper,
+            geometry_format: args.format_geometry,
+            insert_mode: args.insert_mode,
+            upsert: args.upsert,
+            upsert_keys: args.upsert_keys,
+            upsert_columns: args.upsert_columns,
+            dialect: args.dialect,
+            column_widths: args
+                .column_width
+                .iter()
+                .filter_map(|entry| {
+                    let (name, width) = entry.rsplit_once('=')?;
+                    Some((unquote(name), width.parse().ok()?))
+                })
+                .collect(),
+            csv_delimiter,
+            csv_quote: args.csv_quote,
+            csv_crlf: args.csv_crlf,
+            csv_newline_escape: args.csv_newline_escape,
         },
         compression: args.compression.map(|c| (c, compress_level)),
         components_mask,
         file_size: args.size,
+        rows_per_data_file: args.rows_per_data_file,
+        partition_by: args.partition_by,
+        sort_buffer_rows: args.sort_buffer_rows,
+        manifest_seed: args.manifest.then_some(meta_seed),
+        rotate_interval: args.rotate_interval.map(Duration::from_secs),
+        retention: args.retention.map(Duration::from_secs),
+        throttle: args.rows_per_second.map(Throttle::new),
+        max_lengths: args
+            .report_lengths
+            .then(|| Mutex::new(vec![Vec::new(); template_tables_count])),
+        load_script: load_script.map(|dialect| (dialect, Mutex::default())),
+        reported_warnings: (!args.no_warnings).then(Mutex::default),
+        continuous: args.continuous,
+        print_warnings: args.continuous && !args.quiet,
+        validate_constraints: args.validate_constraints,
+        duplicate_rate: args.duplicate_rate,
+        change_weights,
+        change_retention: args.cdc_retention,
+        transaction_size: args.wrap_transaction,
+        file_prologue: args.file_prologue,
+        file_epilogue: args.file_epilogue,
+        progress: Arc::new(ProgressCounters::new(row_args.files_count)),
     };
 
+    // in continuous mode every file is generated forever by its own thread, so a file would never
+    // start if the threads are fewer than the files.
+    let threads_count = pool.current_num_threads();
+    if args.continuous && usize::try_from(row_args.files_count).map_or(true, |files_count| files_count > threads_count)
+    {
+        return Err(Error::UnsupportedCliParameter {
+            kind: "number of files in --continuous mode (must not exceed the number of jobs)",
+            value: format!("{} files with {threads_count} jobs", row_args.files_count),
+        }
+        .no_span());
+    }
+
     if ComponentName::Schema.is_in(env.components_mask) {
         env.write_schema_schema()?;
     }
     if ComponentName::Table.is_in(env.components_mask) {
         env.write_table_schema()?;
     }
-
-    let meta_seed = args.seed.unwrap_or_else(|| OsRng.gen());
-    let show_progress = !args.quiet;
-    if show_progress {
-        println!("Using seed: {meta_seed}");
+    if ComponentName::InformationSchema.is_in(env.components_mask) {
+        env.write_information_schema()?;
     }
-    let mut seeding_rng = meta_seed.make_rng();
 
-    let rng_name = args.rng;
+    if args.continuous {
+        STOP_REQUESTED.store(false, Ordering::Relaxed);
+        match ctrlc::set_handler(|| STOP_REQUESTED.store(true, Ordering::Relaxed)) {
+            Ok(()) | Err(ctrlc::Error::MultipleHandlers) => {}
+            Err(e) => return Err(Error::from(e).no_span()),
+        }
+        if show_progress {
+            println!("Generating data continuously, press Ctrl+C to stop.");
+        }
 
-    // Evaluate the global expressions if necessary.
-    if !template.global_exprs.is_empty() {
-        let row_gen = ctx.compile_row(template.global_exprs)?;
-        let mut state = State::new(0, rng_name.create(&mut seeding_rng), ctx);
-        row_gen.eval(&mut state)?;
-        ctx = state.into_compile_context();
-    }
+        let iv = (0..row_args.files_count)
+            .map(|i| {
+                let mut thread_seeding_rng = rand_hc::Hc128Rng::from_seed(seeding_rng.gen());
+                let files_count = u64::from(row_args.files_count);
+                // interleave the segments among the threads, so the row numbers never overlap.
+                let segments = (0..).map(move |j: u64| {
+                    let row_num = (j * files_count + u64::from(i)) * row_args.rows_per_file + 1;
+                    (rng_name.create(&mut thread_seeding_rng), row_num)
+                });
+                let file_info = FileInfo {
+                    file_index: i + 1,
+                    inserts_count: row_args.inserts_count,
+                    last_insert_rows_count: row_args.final_insert_rows_count,
+                };
+                (file_info, segments)
+            })
+            .collect::<Vec<_>>();
+        pool.install(|| {
+            iv.into_par_iter()
+                .try_for_each(|(file_info, segments)| env.write_continuous_data_file(&file_info, segments, &ctx))
+        })?;
 
-    WRITE_FINISHED.store(false, Ordering::Relaxed);
-    WRITE_PROGRESS.store(0, Ordering::Relaxed);
-    WRITTEN_SIZE.store(0, Ordering::Relaxed);
+        env.write_load_scripts()?;
+        if let Some(max_lengths) = &env.max_lengths {
+            env.print_max_lengths(&max_lengths.lock().unwrap());
+        }
+        return Ok(());
+    }
 
-    let progress_bar_thread = spawn(move || {
-        if show_progress {
-            run_progress_thread(row_args.total_count);
+    let custom_progress_sink = session.progress_sink.take();
+    let is_custom_progress_sink = custom_progress_sink.is_some();
+    let progress_sink = custom_progress_sink.or_else(|| -> Option<Box<dyn ProgressSink>> {
+        match args.progress {
+            ProgressName::Json => Some(Box::new(JsonLines(io::stdout()))),
+            ProgressName::Bar if show_progress => Some(Box::new(ProgressBars::default())),
+            ProgressName::Bar => None,
         }
     });
+    let progress_counters = env.progress.clone();
+    let progress_thread = spawn(move || {
+        let mut sink = progress_sink?;
+        run_progress_thread(&mut *sink, &progress_counters, row_args.total_count);
+        Some(sink)
+    });
 
     let iv = (0..row_args.files_count)
         .map(move |i| {
@@ -568,17 +1739,342 @@ pub fn run(args: Args, span_registry: &mut Registry) -> Result<(), S<Error>> {
             )
         })
         .collect::<Vec<_>>();
-    let res = pool.install(move || {
+    let res = pool.install(|| {
         iv.into_par_iter().try_for_each(|(seed, file_info, row_num)| {
             let mut state = State::new(row_num, seed, ctx.clone());
             env.write_data_file(&file_info, &mut state)
         })
     });
 
-    WRITE_FINISHED.store(true, Ordering::Relaxed);
-    progress_bar_thread.join().unwrap();
+    env.progress.finished.store(true, Ordering::Relaxed);
+    let progress_sink = progress_thread.join().unwrap();
+    if is_custom_progress_sink {
+        session.progress_sink = progress_sink;
+    }
 
     res?;
+
+    env.write_load_scripts()?;
+    if let Some(max_lengths) = &env.max_lengths {
+        env.print_max_lengths(&max_lengths.lock().unwrap());
+    }
+    Ok(())
+}
+
+/// Checks that every name refers to a column of some table.
+fn check_columns_exist<'a>(
+    tables: &[Table],
+    names: impl IntoIterator<Item = &'a str>,
+    kind: &'static str,
+) -> Result<(), S<Error>> {
+    for name in names {
+        if !tables.iter().any(|t| find_column(&t.schema(false), name).is_some()) {
+            return Err(Error::UnsupportedCliParameter {
+                kind,
+                value: name.to_owned(),
+            }
+            .no_span());
+        }
+    }
+    Ok(())
+}
+
+/// Writes only the columns listed in `columns`, in the listed order.
+///
+/// Tables containing none of the listed columns are written in full.
+fn select_columns(tables: &mut [Table], columns: &[String], kind: &'static str) -> Result<(), S<Error>> {
+    check_columns_exist(tables, columns.iter().map(String::as_str), kind)?;
+    for table in tables {
+        let schema = table.schema(false);
+        let column_order = columns
+            .iter()
+            .filter_map(|name| find_column(&schema, name))
+            .collect::<Vec<_>>();
+        if column_order.is_empty() {
+            continue;
+        }
+        let column_count = schema.column_names().count();
+        table.skipped_columns = (0..column_count).map(|i| !column_order.contains(&i)).collect();
+        table.column_order = column_order;
+    }
+    Ok(())
+}
+
+/// Writes only the columns listed in `only_columns` in the listed order, or omits the columns
+/// listed in `skip_columns` from the output.
+///
+/// With `only_columns`, tables containing none of the listed columns are written in full.
+fn restrict_columns(tables: &mut [Table], only_columns: &[String], skip_columns: &[String]) -> Result<(), S<Error>> {
+    if !only_columns.is_empty() {
+        return select_columns(tables, only_columns, "selected column");
+    }
+    check_columns_exist(tables, skip_columns.iter().map(String::as_str), "skipped column")?;
+    for table in tables {
+        let schema = table.schema(false);
+        table.skipped_columns = (0..schema.column_names().count())
+            .map(|i| skip_columns.iter().any(|name| find_column(&schema, name) == Some(i)))
+            .collect();
+    }
+    Ok(())
+}
+
+/// Translates the auxiliary statements like `CREATE INDEX` into the dialect of the schema files.
+/// Statements which cannot be translated are dropped with a warning.
+fn translate_auxiliary_statements(tables: &mut [Table], dialect: SchemaDialect, quiet: bool) {
+    for table in tables {
+        table.auxiliary_statements.retain_mut(|statement| {
+            if let Some(translated) = ddl::translate_auxiliary_statement(dialect, statement) {
+                *statement = translated;
+                true
+            } else {
+                if !quiet {
+                    eprintln!(
+                        "Warning: dropped the statement which cannot be translated into {}: {statement}",
+                        dialect.name()
+                    );
+                }
+                false
+            }
+        });
+    }
+}
+
+/// Checks that the auxiliary statements of sharded tables can be rewritten for every shard.
+fn check_sharded_auxiliary_statements(tables: &[Table]) -> Result<(), S<Error>> {
+    for table in tables.iter().filter(|table| table.shards.is_some()) {
+        for statement in &table.auxiliary_statements {
+            if ddl::shard_auxiliary_statement(statement, 0).is_none() {
+                return Err(Error::UnsupportedCliParameter {
+                    kind: "auxiliary statement of a sharded table",
+                    value: statement.clone(),
+                }
+                .no_span());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks that every column given a width by `--column-width` exists in some table.
+fn check_column_widths(tables: &[Table], column_widths: &[String]) -> Result<(), S<Error>> {
+    let names = column_widths
+        .iter()
+        .map(|entry| entry.rsplit_once('=').map_or(&**entry, |(name, _)| name));
+    check_columns_exist(tables, names, "fixed-width column")
+}
+
+/// Checks that the column names in the header row can be written without quoting, i.e. they
+/// contain no delimiter, quotation mark or line break.
+fn check_unquoted_csv_headers(tables: &[Table], delimiter: u8, newline_escaped: bool) -> Result<(), S<Error>> {
+    for table in tables {
+        let schema = table.schema(false);
+        for name in schema.output_column_names() {
+            let name = unquote(name);
+            if name
+                .bytes()
+                .any(|b| b == delimiter || b == b'"' || b == b'\r' || (b == b'\n' && !newline_escaped))
+            {
+                return Err(Error::UnsupportedCliParameter {
+                    kind: "CSV header which needs quoting with --csv-quote never",
+                    value: name.into_owned(),
+                }
+                .no_span());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the upsert options are applicable to the output format and the tables.
+fn check_upsert(
+    tables: &[Table],
+    format: FormatName,
+    dialect: Option<Dialect>,
+    style: UpsertStyle,
+    keys: &[String],
+    columns: &[String],
+) -> Result<(), S<Error>> {
+    if format != FormatName::Sql {
+        return Err(Error::UnsupportedCliParameter {
+            kind: "output format for --upsert",
+            value: format!("{format:?}"),
+        }
+        .no_span());
+    }
+    if !style.is_supported_by(dialect) {
+        return Err(Error::UnsupportedCliParameter {
+            kind: "upsert style of the dialect",
+            value: format!("{style:?}"),
+        }
+        .no_span());
+    }
+    if style == UpsertStyle::OnConflictDoUpdate && keys.is_empty() {
+        return Err(Error::UnsupportedCliParameter {
+            kind: "upsert without --upsert-keys",
+            value: "on-conflict-do-update".to_owned(),
+        }
+        .no_span());
+    }
+    check_columns_exist(tables, keys.iter().map(String::as_str), "upsert key column")?;
+    check_columns_exist(tables, columns.iter().map(String::as_str), "upsert column")
+}
+
+/// Checks that the load scripts can be written for the output format, and returns the dialect of
+/// the database loading the data files.
+///
+/// Escaped line feeds can only be restored by MySQL, which reads `\n` when backslashes are
+/// escaped as well.
+fn check_load_script(
+    format: FormatName,
+    dialect: Option<Dialect>,
+    compression: Option<CompressionName>,
+    newline_escape: Option<&str>,
+    escape_backslash: bool,
+) -> Result<Dialect, S<Error>> {
+    if format != FormatName::Csv {
+        return Err(Error::UnsupportedCliParameter {
+            kind: "output format for --load-script",
+            value: format!("{format:?}"),
+        }
+        .no_span());
+    }
+    let dialect = dialect.unwrap_or(Dialect::Mysql);
+    if let Some(newline_escape) = newline_escape {
+        if dialect != Dialect::Mysql || newline_escape != "\\n" || !escape_backslash {
+            return Err(Error::UnsupportedCliParameter {
+                kind: "--csv-newline-escape for the load script",
+                value: newline_escape.to_owned(),
+            }
+            .no_span());
+        }
+    }
+    match (dialect, compression) {
+        (Dialect::Postgres, _) | (Dialect::Mysql, None) => Ok(dialect),
+        // `LOAD DATA` cannot read compressed files.
+        (Dialect::Mysql, Some(compression)) => Err(Error::UnsupportedCliParameter {
+            kind: "compression for the MySQL load script",
+            value: format!("{compression:?}"),
+        }
+        .no_span()),
+        (Dialect::Sqlite | Dialect::Mssql, _) => Err(Error::UnsupportedCliParameter {
+            kind: "dialect for --load-script",
+            value: format!("{dialect:?}"),
+        }
+        .no_span()),
+    }
+}
+
+/// Quotes a string literal of the load script.
+///
+/// Control characters are written as backslash escapes, in an `E'…'` literal for PostgreSQL.
+fn load_script_literal(dialect: Dialect, s: &str) -> String {
+    let needs_escape = dialect == Dialect::Mysql || s.contains(['\t', '\r', '\n']);
+    let mut res = String::from(if dialect == Dialect::Postgres && needs_escape {
+        "E'"
+    } else {
+        "'"
+    });
+    for c in s.chars() {
+        match c {
+            '\'' => res.push_str("''"),
+            '\\' if needs_escape => res.push_str("\\\\"),
+            '\t' if needs_escape => res.push_str("\\t"),
+            '\r' if needs_escape => res.push_str("\\r"),
+            '\n' if needs_escape => res.push_str("\\n"),
+            _ => res.push(c),
+        }
+    }
+    res.push('\'');
+    res
+}
+
+/// Creates the statement loading a CSV data file into the table with the given columns.
+///
+/// The `file` is relative to the output directory, where the script is expected to be run.
+fn load_statement(
+    options: &Options,
+    dialect: Dialect,
+    compression: Option<CompressionName>,
+    table_name: &str,
+    columns: &[String],
+    file: &Path,
+) -> String {
+    let delimiter = char::from(options.csv_delimiter).to_string();
+    let file = file.to_string_lossy();
+    let table_name = dialect.requote(table_name);
+    if dialect == Dialect::Postgres {
+        let source = match compression {
+            None => format!("FROM {}", load_script_literal(dialect, &file)),
+            Some(compression) => {
+                let command = format!("{} -dc {file}", compression.command());
+                format!("FROM PROGRAM {}", load_script_literal(dialect, &command))
+            }
+        };
+        let escape = if options.escape_backslash { ", ESCAPE '\\'" } else { "" };
+        format!(
+            "\\copy {table_name} ({}) {source} \
+             WITH (FORMAT csv, HEADER {}, DELIMITER {}, NULL {}{escape}, ENCODING 'UTF8')",
+            columns.join(", "),
+            options.headers,
+            load_script_literal(dialect, &delimiter),
+            load_script_literal(dialect, &options.null_string),
+        )
+    } else {
+        let enclosure = if options.csv_quote == CsvQuote::Never {
+            ""
+        } else {
+            " OPTIONALLY ENCLOSED BY '\"'"
+        };
+        let escape = if options.escape_backslash { "\\" } else { "" };
+        let line_terminator = if options.csv_crlf { "\r\n" } else { "\n" };
+        // `LOAD DATA` only reads `\N` as NULL when backslashes are escapes. Otherwise the values
+        // are read into variables, and those equal to the NULL string are turned into NULL.
+        let columns = if options.escape_backslash && options.null_string == r"\N" {
+            format!("({})", columns.join(", "))
+        } else {
+            let null_string = load_script_literal(dialect, &options.null_string);
+            let variables = (1..=columns.len()).map(|i| format!("@c{i}")).collect::<Vec<_>>();
+            let assignments = columns
+                .iter()
+                .zip(&variables)
+                .map(|(column, variable)| format!("{column} = NULLIF({variable}, {null_string})"))
+                .collect::<Vec<_>>();
+            format!("({}) SET {}", variables.join(", "), assignments.join(", "))
+        };
+        format!(
+            "LOAD DATA LOCAL INFILE {} INTO TABLE {table_name} CHARACTER SET utf8mb4 \
+             FIELDS TERMINATED BY {}{enclosure} ESCAPED BY {} LINES TERMINATED BY {}{} {columns};",
+            load_script_literal(dialect, &file),
+            load_script_literal(dialect, &delimiter),
+            load_script_literal(dialect, escape),
+            load_script_literal(dialect, line_terminator),
+            if options.headers { " IGNORE 1 LINES" } else { "" },
+        )
+    }
+}
+
+/// Restricts the rows of partitioned tables to the selected partitions.
+fn restrict_partitions(tables: &mut [Table], partitions: &[String]) -> Result<(), S<Error>> {
+    for table in &mut *tables {
+        table.partition_filter = PartitionFilter::new(&table.schema(true), partitions).no_span_err()?;
+    }
+    for partition in partitions {
+        let found = tables
+            .iter()
+            .filter_map(|table| table.partition_filter.as_ref())
+            .any(|filter| {
+                filter
+                    .partition_names()
+                    .any(|name| name.eq_ignore_ascii_case(partition))
+            });
+        if !found {
+            return Err(Error::UnsupportedCliParameter {
+                kind: "partition",
+                value: partition.clone(),
+            }
+            .no_span());
+        }
+    }
     Ok(())
 }
 
@@ -634,6 +2130,12 @@ impl<'de> Deserialize<'de> for Seed {
     }
 }
 
+impl From<[u8; 32]> for Seed {
+    fn from(seed: [u8; 32]) -> Self {
+        Self(seed)
+    }
+}
+
 impl Seed {
     /// Constructs a RNG from this seed.
     pub fn make_rng(&self) -> rand_hc::Hc128Rng {
@@ -641,6 +2143,39 @@ impl Seed {
     }
 }
 
+/// A relative weight of a kind of statement in `--dml-mix`, written as `statement=weight`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DmlWeight {
+    /// The kind of statement.
+    pub op: ChangeOp,
+    /// The relative weight of the statement.
+    pub weight: f64,
+}
+
+impl fmt::Display for DmlWeight {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let statement = match self.op {
+            ChangeOp::Insert => "insert",
+            ChangeOp::Update => "update",
+            ChangeOp::Delete => "delete",
+        };
+        write!(f, "{statement}={}", self.weight)
+    }
+}
+
+impl Serialize for DmlWeight {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_string().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for DmlWeight {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        parse_dml_weight(&s).map_err(serde::de::Error::custom)
+    }
+}
+
 /// Names of random number generators supported by `dbgen`.
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, ValueEnum)]
 #[serde(rename_all = "lowercase")]
@@ -714,6 +2249,20 @@ pub enum FormatName {
     Csv,
     /// SQL in INSERT-SET form
     SqlInsertSet,
+    /// ClickHouse TabSeparated
+    ClickhouseTsv,
+    /// ClickHouse RowBinary
+    ClickhouseRowbinary,
+    /// SQL wrapped by the session settings and table locks of mysqldump
+    Mysqldump,
+    /// PostgreSQL COPY FROM stdin text format
+    Pgcopy,
+    /// Debezium-style JSON change events
+    Debezium,
+    /// Fixed-width text
+    FixedWidth,
+    /// Apache Arrow IPC file (requires the `arrow` feature)
+    Arrow,
 }
 
 impl FromStr for FormatName {
@@ -723,6 +2272,13 @@ impl FromStr for FormatName {
             "sql" => Self::Sql,
             "csv" => Self::Csv,
             "sql-insert-set" => Self::SqlInsertSet,
+            "clickhouse-tsv" => Self::ClickhouseTsv,
+            "clickhouse-rowbinary" => Self::ClickhouseRowbinary,
+            "mysqldump" => Self::Mysqldump,
+            "pgcopy" => Self::Pgcopy,
+            "debezium" => Self::Debezium,
+            "fixed-width" => Self::FixedWidth,
+            "arrow" => Self::Arrow,
             _ => {
                 return Err(Error::UnsupportedCliParameter {
                     kind: "output format",
@@ -737,38 +2293,94 @@ impl FormatName {
     /// Obtains the file extension when using this format.
     fn extension(self) -> &'static str {
         match self {
-            Self::Sql | Self::SqlInsertSet => "sql",
+            Self::Sql | Self::SqlInsertSet | Self::Mysqldump | Self::Pgcopy => "sql",
             Self::Csv => "csv",
+            Self::ClickhouseTsv => "tsv",
+            Self::ClickhouseRowbinary => "bin",
+            Self::Debezium => "json",
+            Self::FixedWidth => "txt",
+            Self::Arrow => "arrow",
         }
     }
 
     /// Creates a formatter writer given the name.
-    fn create(self, options: &Options) -> Box<dyn Format + '_> {
-        match self {
-            Self::Sql => Box::new(SqlFormat(options)),
+    fn create<'a>(self, options: &'a Options, schema: &Schema<'_>) -> Result<Box<dyn Format + 'a>, S<Error>> {
+        Ok(match self {
+            Self::Sql => Box::new(SqlFormat::new(options, schema).map_err(|column| {
+                Error::UnsupportedCliParameter {
+                    kind: "table without the upsert key column",
+                    value: column,
+                }
+                .no_span()
+            })?),
             Self::Csv => Box::new(CsvFormat(options)),
             Self::SqlInsertSet => Box::new(SqlInsertSetFormat(options)),
+            Self::ClickhouseTsv => Box::new(ClickHouseTsvFormat(options)),
+            Self::Mysqldump => Box::new(MySqlDumpFormat(options)),
+            Self::Pgcopy => Box::new(PgCopyFormat(options)),
+            Self::Debezium => Box::new(DebeziumFormat::new(options, schema)),
+            Self::FixedWidth => Box::new(FixedWidthFormat::new(options, schema).map_err(|column| {
+                Error::UnsupportedCliParameter {
+                    kind: "fixed-width column without a known width",
+                    value: column,
+                }
+                .no_span()
+            })?),
+            Self::ClickhouseRowbinary => Box::new(ClickHouseRowBinaryFormat::new(options, schema).map_err(|ty| {
+                Error::UnsupportedCliParameter {
+                    kind: "ClickHouse RowBinary column type",
+                    value: ty,
+                }
+                .no_span()
+            })?),
+            #[cfg(feature = "arrow")]
+            Self::Arrow => Box::new(crate::format::ArrowFormat::new(options, schema)),
+            #[cfg(not(feature = "arrow"))]
+            Self::Arrow => {
+                return Err(Error::UnsupportedCliParameter {
+                    kind: "output format without the `arrow` feature",
+                    value: "arrow".to_owned(),
+                }
+                .no_span())
+            }
+        })
+    }
+
+    /// Obtains the boolean strings of this format when targeting the given SQL dialect.
+    fn default_boolean_strings(self, dialect: Option<Dialect>) -> (&'static str, &'static str) {
+        match (self, dialect) {
+            (Self::Sql | Self::SqlInsertSet | Self::Mysqldump, Some(dialect)) => dialect.boolean_strings(),
+            _ => ("1", "0"),
         }
     }
 
-    // ALLOW_REASON: future compatibility with other formats.
-    #[allow(clippy::unused_self)]
-    fn default_true_string(self) -> Cow<'static, str> {
-        Cow::Borrowed("1")
+    fn default_true_string(self, dialect: Option<Dialect>) -> Cow<'static, str> {
+        Cow::Borrowed(self.default_boolean_strings(dialect).0)
     }
 
-    // ALLOW_REASON: future compatibility with other formats.
-    #[allow(clippy::unused_self)]
-    fn default_false_string(self) -> Cow<'static, str> {
-        Cow::Borrowed("0")
+    fn default_false_string(self, dialect: Option<Dialect>) -> Cow<'static, str> {
+        Cow::Borrowed(self.default_boolean_strings(dialect).1)
     }
 
     fn default_null_string(self) -> Cow<'static, str> {
         Cow::Borrowed(match self {
-            Self::Sql | Self::SqlInsertSet => "NULL",
-            Self::Csv => r"\N",
+            Self::Sql | Self::SqlInsertSet | Self::Mysqldump | Self::Debezium => "NULL",
+            Self::Csv | Self::ClickhouseTsv | Self::ClickhouseRowbinary | Self::Pgcopy => r"\N",
+            Self::FixedWidth | Self::Arrow => "",
         })
     }
+
+    /// The checks of the values which this format cannot represent faithfully with the options.
+    fn output_checks(self, options: &Options) -> Vec<ColumnCheck> {
+        match self {
+            Self::Csv if options.csv_quote == CsvQuote::Never => vec![ColumnCheck::UnquotedCsv {
+                delimiter: options.csv_delimiter,
+                newline_escaped: options.csv_newline_escape.is_some(),
+            }],
+            Self::Debezium => vec![ColumnCheck::JsonInteger],
+            _ => Vec::new(),
+        }
+    }
 }
 
 /// Names of the compression output formats supported by `dbgen`.
@@ -815,6 +2427,15 @@ impl CompressionName {
         }
     }
 
+    /// The command decompressing a file to stdout.
+    fn command(self) -> &'static str {
+        match self {
+            Self::Gzip => "gzip",
+            Self::Xz => "xz",
+            Self::Zstd => "zstd",
+        }
+    }
+
     /// Wraps a writer with a compression layer on top.
     fn wrap<'a, W: Write + 'a>(self, inner: W, level: u8) -> Box<dyn Write + 'a> {
         match self {
@@ -841,6 +2462,10 @@ pub enum ComponentName {
     Table = 2,
     /// The data files.
     Data = 4,
+    /// The `information_schema.json` file.
+    #[serde(rename = "information_schema")]
+    #[value(name = "information_schema")]
+    InformationSchema = 8,
 }
 
 impl FromStr for ComponentName {
@@ -850,6 +2475,7 @@ impl FromStr for ComponentName {
             "schema" => Self::Schema,
             "table" => Self::Table,
             "data" => Self::Data,
+            "information_schema" => Self::InformationSchema,
             _ => {
                 return Err(Error::UnsupportedCliParameter {
                     kind: "component",
@@ -874,48 +2500,217 @@ impl ComponentName {
     }
 }
 
-/// A [`Writer`] which counts how many bytes are written.
-struct FormatWriter<'a> {
+/// Names of the ways to report the progress.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+#[value(rename_all = "lowercase")]
+pub enum ProgressName {
+    /// Draw progress bars on the terminal, unless `--quiet` is given.
+    Bar,
+    /// Print the progress as a line of JSON to stdout every 0.5 seconds.
+    Json,
+}
+
+/// Names of the ways to print errors and warnings.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+#[value(rename_all = "lowercase")]
+pub enum ErrorFormat {
+    /// Human-readable text.
+    Human,
+    /// One line of JSON per diagnostic, see [`Diagnostic`].
+    Json,
+}
+
+impl ErrorFormat {
+    /// Formats an error for printing to stderr.
+    ///
+    /// The `template` is the path of the template file the error spans refer to.
+    pub fn describe(self, registry: &Registry, err: &S<Error>, template: Option<&Path>) -> String {
+        match self {
+            Self::Human => registry.describe(err),
+            Self::Json => Self::to_json(registry.diagnose(err), template),
+        }
+    }
+
+    /// Formats a lint warning for printing to stderr.
+    fn describe_warning(self, registry: &Registry, lint: &S<lint::Lint>, template: Option<&Path>) -> String {
+        match self {
+            Self::Human => registry.describe_warning(lint),
+            Self::Json => Self::to_json(registry.diagnose_warning(lint), template),
+        }
+    }
+
+    fn to_json(mut diagnostic: Diagnostic, template: Option<&Path>) -> String {
+        if diagnostic.span.is_some() {
+            diagnostic.file = template.map(|path| path.display().to_string());
+        }
+        serde_json::to_string(&diagnostic).unwrap()
+    }
+}
+
+/// A [`Write`] which counts how many bytes are written.
+struct CountingWriter {
     /// The target writer.
     writer: BufWriter<Box<dyn Write>>,
     /// Total number of bytes currently written into `writer`.
     written_size: u64,
     /// Total number of bytes written which is not yet committed into
-    /// the `WRITTEN_SIZE` global variable.
+    /// the progress counters.
     uncommitted_size: u64,
-    /// The prefix part of the path.
-    path_prefix: PathBuf,
-    /// The extension of the path.
-    path_extension: &'static str,
-    /// The file size limit and the associated lexicographical counter for when
-    /// size-splitting is needed.
-    target_size_and_counter: Option<(u64, LexCtr)>,
-    /// The output file format.
-    format: &'a dyn Format,
 }
-impl<'a> FormatWriter<'a> {
-    /// Creates a new [`WriteWrapper`].
-    fn new(
-        path_prefix: PathBuf,
+
+impl Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let bytes_written = self.writer.write(buf)?;
+        self.written_size += bytes_written as u64;
+        self.uncommitted_size += bytes_written as u64;
+        Ok(bytes_written)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// The size and the XXH64 checksum of the bytes written into a data file so far.
+type SharedChecksum = Rc<RefCell<(u64, Xxh64)>>;
+
+/// A [`Write`] which computes the size and the checksum of the bytes written into a data file, for
+/// the manifest. It is placed below the compression layer, so the checksum covers the file as
+/// stored.
+struct ChecksumWriter<W> {
+    /// The target writer.
+    writer: W,
+    /// The size and the checksum, shared with the [`FormatWriter`] writing the manifest.
+    checksum: SharedChecksum,
+}
+
+impl<W: Write> Write for ChecksumWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let bytes_written = self.writer.write(buf)?;
+        let (size, hasher) = &mut *self.checksum.borrow_mut();
+        *size += bytes_written as u64;
+        hasher.update(&buf[..bytes_written]);
+        Ok(bytes_written)
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// The maximum lengths of the string values written to a column.
+#[derive(Copy, Clone, Debug)]
+struct MaxLength {
+    /// Maximum number of characters.
+    chars: usize,
+    /// Maximum number of bytes.
+    bytes: usize,
+}
+
+impl MaxLength {
+    /// Updates the maximum lengths to include another set of lengths.
+    fn merge(&mut self, other: Self) {
+        self.chars = self.chars.max(other.chars);
+        self.bytes = self.bytes.max(other.bytes);
+    }
+}
+
+/// A [`Writer`] which writes values into files in the output format.
+struct FormatWriter<'a> {
+    /// The target writer.
+    output: CountingWriter,
+    /// The size and the checksum of the current data file, if a manifest is written.
+    checksum: Option<SharedChecksum>,
+    /// The prefix part of the path.
+    path_prefix: PathBuf,
+    /// The extension of the path.
+    path_extension: &'static str,
+    /// The file size limit, when size-splitting is needed.
+    target_size: Option<u64>,
+    /// The time limit of writing to each file, when time-splitting is needed.
+    target_duration: Option<Duration>,
+    /// The number of rows of the main table in each file, when row-splitting is needed.
+    target_rows: Option<NonZeroU64>,
+    /// The number of rows of the main table written to the current file.
+    written_rows: u64,
+    /// The number of rows of this table actually written to the current file.
+    file_rows: u64,
+    /// The number of rows of this table actually written to the previous file.
+    previous_file_rows: u64,
+    /// The lexicographical counter for when splitting is needed.
+    counter: Option<LexCtr>,
+    /// The time when the current file was opened.
+    opened_at: Instant,
+    /// The files which have been closed after rotation and the time they were closed, oldest
+    /// first. Only recorded when the files are subject to retention.
+    closed_files: VecDeque<(PathBuf, Instant)>,
+    /// The output file format.
+    format: Box<dyn Format + 'a>,
+    /// Index of the column of the next value in the current row.
+    column_index: usize,
+    /// The maximum lengths of string values written to every column, if requested.
+    max_lengths: Option<Vec<Option<MaxLength>>>,
+    /// Number of statements in each transaction, when the statements are wrapped in transactions.
+    transaction_size: Option<NonZeroU32>,
+    /// Number of statements written in the current transaction.
+    transaction_statements: u32,
+    /// Whether a transaction has been started and not yet committed.
+    in_transaction: bool,
+    /// Whether anything has been written since the current statement was started.
+    statement_written: bool,
+    /// The index of the shard written, if the table is sharded.
+    shard: Option<usize>,
+    /// Custom content written at the start of every file.
+    file_prologue: Option<&'a str>,
+    /// Custom content written at the end of every file.
+    file_epilogue: Option<&'a str>,
+}
+impl<'a> FormatWriter<'a> {
+    /// Creates a new [`FormatWriter`].
+    fn new(
+        path_prefix: PathBuf,
         path_extension: &'static str,
         target_size: Option<u64>,
-        format: &'a dyn Format,
+        target_duration: Option<Duration>,
+        target_rows: Option<NonZeroU64>,
+        format: Box<dyn Format + 'a>,
     ) -> Self {
         Self {
-            writer: BufWriter::with_capacity(0, Box::new(sink())),
-            written_size: 0,
-            uncommitted_size: 0,
+            output: CountingWriter {
+                writer: BufWriter::with_capacity(0, Box::new(sink())),
+                written_size: 0,
+                uncommitted_size: 0,
+            },
+            checksum: None,
             path_prefix,
             path_extension,
-            target_size_and_counter: target_size.map(|s| (s, LexCtr::default())),
+            target_size,
+            target_duration,
+            target_rows,
+            written_rows: 0,
+            file_rows: 0,
+            previous_file_rows: 0,
+            counter: (target_size.is_some() || target_duration.is_some() || target_rows.is_some())
+                .then(LexCtr::default),
+            opened_at: Instant::now(),
+            closed_files: VecDeque::new(),
             format,
+            column_index: 0,
+            max_lengths: None,
+            transaction_size: None,
+            transaction_statements: 0,
+            in_transaction: false,
+            statement_written: false,
+            shard: None,
+            file_prologue: None,
+            file_epilogue: None,
         }
     }
 
     /// Returns the current file path.
     fn path(&self) -> PathBuf {
         let mut path_prefix = self.path_prefix.as_os_str().to_owned();
-        if let Some((_, counter)) = &self.target_size_and_counter {
+        if let Some(counter) = &self.counter {
             path_prefix.push(&counter.to_string());
         }
         path_prefix.push(".");
@@ -923,67 +2718,193 @@ impl<'a> FormatWriter<'a> {
         path_prefix.into()
     }
 
-    /// Checks if the current written size exceeds the size limit.
-    fn try_rotate(&mut self) -> bool {
-        if let Some((size, counter)) = &mut self.target_size_and_counter {
-            if self.written_size >= *size {
-                counter.inc();
-                self.written_size = 0;
-                return true;
+    /// Records the length of the value written to the current column.
+    fn record_length(&mut self, value: &Value) {
+        let column_index = self.column_index;
+        self.column_index += 1;
+        let (Some(max_lengths), Value::Bytes(bytes)) = (&mut self.max_lengths, value) else {
+            return;
+        };
+        if max_lengths.len() <= column_index {
+            max_lengths.resize(column_index + 1, None);
+        }
+        let length = MaxLength {
+            chars: bytes.char_len(),
+            bytes: bytes.len(),
+        };
+        let max_length = max_lengths[column_index].get_or_insert(length);
+        max_length.merge(length);
+    }
+
+    /// Returns the number of rows of the main table which can still be written to the current
+    /// file, or `None` if unlimited.
+    fn rows_left(&self) -> Option<u64> {
+        self.target_rows
+            .map(|rows| rows.get().saturating_sub(self.written_rows))
+    }
+
+    /// Checks if the current written size exceeds the size limit, or the current file has been
+    /// opened for longer than the time limit. If so, advances to the next file and returns the
+    /// path of the file to be closed.
+    fn try_rotate(&mut self) -> Option<PathBuf> {
+        let size_exceeded = self.target_size.is_some_and(|size| self.output.written_size >= size);
+        let time_exceeded = self.target_duration.is_some_and(|d| self.opened_at.elapsed() >= d);
+        if !size_exceeded && !time_exceeded {
+            return None;
+        }
+        self.advance()
+    }
+
+    /// Checks if the current file is full by the row limit. If so, advances to the next file and
+    /// returns the path of the file to be closed.
+    ///
+    /// Unlike [`FormatWriter::try_rotate()`], this is checked before writing more rows, so no
+    /// empty file is left behind after the last row.
+    fn try_rotate_by_rows(&mut self) -> Option<PathBuf> {
+        if self.rows_left() == Some(0) {
+            self.advance()
+        } else {
+            None
+        }
+    }
+
+    /// Advances to the next file and returns the path of the file to be closed.
+    fn advance(&mut self) -> Option<PathBuf> {
+        let old_path = self.path();
+        self.counter.as_mut()?.inc();
+        self.output.written_size = 0;
+        self.written_rows = 0;
+        self.previous_file_rows = mem::take(&mut self.file_rows);
+        self.opened_at = Instant::now();
+        Some(old_path)
+    }
+
+    /// Starts a new transaction before writing the first content of a statement, if needed.
+    ///
+    /// The transaction is started lazily, so no empty transaction is written for a table without
+    /// any rows in the statement.
+    fn begin_statement(&mut self) -> Result<(), S<Error>> {
+        self.statement_written = true;
+        if self.transaction_size.is_some() && !self.in_transaction {
+            self.in_transaction = true;
+            self.format
+                .write_group_header(&mut self.output)
+                .with_path_fn("write transaction header", || self.path())?;
+        }
+        Ok(())
+    }
+
+    /// Counts a written statement, and commits the transaction when it is full.
+    fn end_statement(&mut self) -> Result<(), S<Error>> {
+        let Some(transaction_size) = self.transaction_size else {
+            return Ok(());
+        };
+        if !mem::take(&mut self.statement_written) {
+            return Ok(());
+        }
+        self.transaction_statements += 1;
+        if self.transaction_statements >= transaction_size.get() {
+            self.transaction_statements = 0;
+            self.in_transaction = false;
+            self.format
+                .write_group_trailer(&mut self.output)
+                .with_path_fn("write transaction trailer", || self.path())?;
+        }
+        Ok(())
+    }
+
+    /// Commits the unfinished transaction and writes the content at the end of the file at `path`.
+    fn finish_file(&mut self, schema: &Schema<'_>, path: &Path) -> Result<(), S<Error>> {
+        if self.in_transaction {
+            self.transaction_statements = 0;
+            self.in_transaction = false;
+            self.format
+                .write_group_trailer(&mut self.output)
+                .with_path("write transaction trailer", path)?;
+        }
+        self.format
+            .write_file_trailer(&mut self.output, schema)
+            .with_path("write file trailer", path)?;
+        if let Some(epilogue) = self.file_epilogue {
+            write_custom_content(&mut self.output, epilogue).with_path("write file epilogue", path)?;
+        }
+        Ok(())
+    }
+
+    /// Deletes the closed files which have been closed for longer than the retention period.
+    fn remove_expired_files(&mut self, retention: Duration) -> Result<(), S<Error>> {
+        while let Some((path, closed_at)) = self.closed_files.front() {
+            if closed_at.elapsed() < retention {
+                break;
             }
+            remove_file(path).with_path("remove expired data file", path)?;
+            self.closed_files.pop_front();
         }
-        false
+        Ok(())
     }
 }
 
-impl Write for FormatWriter<'_> {
-    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        let bytes_written = self.writer.write(buf)?;
-        self.written_size += bytes_written as u64;
-        self.uncommitted_size += bytes_written as u64;
-        Ok(bytes_written)
-    }
-    fn flush(&mut self) -> io::Result<()> {
-        self.writer.flush()
+/// Writes the custom content to the output, ending with a new line.
+fn write_custom_content(output: &mut impl Write, content: &str) -> Result<(), io::Error> {
+    output.write_all(content.as_bytes())?;
+    if !content.ends_with('\n') {
+        output.write_all(b"\n")?;
     }
+    Ok(())
 }
 
 impl writer::Writer for FormatWriter<'_> {
     fn write_value(&mut self, value: &Value) -> Result<(), S<Error>> {
+        self.record_length(value);
         self.format
-            .write_value(self, value)
+            .write_value(&mut self.output, value)
             .with_path_fn("write value", || self.path())
     }
     fn write_file_header(&mut self, schema: &Schema<'_>) -> Result<(), S<Error>> {
+        if let Some(prologue) = self.file_prologue {
+            write_custom_content(&mut self.output, prologue).with_path_fn("write file prologue", || self.path())?;
+        }
         self.format
-            .write_file_header(self, schema)
+            .write_file_header(&mut self.output, schema)
             .with_path_fn("write file header", || self.path())
     }
     fn write_header(&mut self, schema: &Schema<'_>) -> Result<(), S<Error>> {
+        self.column_index = 0;
+        self.file_rows += 1;
+        self.begin_statement()?;
         self.format
-            .write_header(self, schema)
+            .write_header(&mut self.output, schema)
             .with_path_fn("write header", || self.path())
     }
     fn write_value_header(&mut self, column: &str) -> Result<(), S<Error>> {
         self.format
-            .write_value_header(self, column)
+            .write_value_header(&mut self.output, column)
             .with_path_fn("write value header", || self.path())
     }
     fn write_value_separator(&mut self) -> Result<(), S<Error>> {
         self.format
-            .write_value_separator(self)
+            .write_value_separator(&mut self.output)
             .with_path_fn("write value separator", || self.path())
     }
     fn write_row_separator(&mut self) -> Result<(), S<Error>> {
+        self.column_index = 0;
+        self.file_rows += 1;
         self.format
-            .write_row_separator(self)
+            .write_row_separator(&mut self.output)
             .with_path_fn("write row separator", || self.path())
     }
     fn write_trailer(&mut self) -> Result<(), S<Error>> {
         self.format
-            .write_trailer(self)
+            .write_trailer(&mut self.output)
             .with_path_fn("write trailer", || self.path())
     }
+    fn write_change(&mut self, schema: &Schema<'_>, change: &writer::Change<'_>) -> Result<(), S<Error>> {
+        self.file_rows += 1;
+        self.begin_statement()?;
+        self.format
+            .write_change(&mut self.output, schema, change)
+            .with_path_fn("write change event", || self.path())
+    }
 }
 
 /// The environmental data shared by all data writers.
@@ -992,12 +2913,402 @@ struct Env {
     file_num_digits: usize,
     tables: Vec<Table>,
     qualified: bool,
+    schema_dialect: Option<SchemaDialect>,
     rows_count: u32,
     format: FormatName,
     format_options: Options,
     compression: Option<(CompressionName, u8)>,
     components_mask: u8,
     file_size: Option<u64>,
+    rows_per_data_file: Option<NonZeroU64>,
+    partition_by: Option<String>,
+    /// The maximum number of rows of every sorted table and file held in memory.
+    sort_buffer_rows: usize,
+    manifest_seed: Option<Seed>,
+    rotate_interval: Option<Duration>,
+    retention: Option<Duration>,
+    throttle: Option<Throttle>,
+    max_lengths: Option<Mutex<Vec<Vec<Option<MaxLength>>>>>,
+    /// The dialect of the load scripts, and the file index, table name and relative path of every
+    /// closed data file listed in them.
+    load_script: Option<(Dialect, Mutex<Vec<(u32, String, PathBuf)>>)>,
+    reported_warnings: Option<Mutex<HashSet<(String, String, WarningKind)>>>,
+    /// Whether the data are generated continuously, without reporting the progress.
+    continuous: bool,
+    /// Whether the warnings are printed directly instead of passed to the progress sink.
+    print_warnings: bool,
+    validate_constraints: bool,
+    duplicate_rate: Option<f64>,
+    change_weights: Option<[f64; 3]>,
+    change_retention: usize,
+    transaction_size: Option<NonZeroU32>,
+    file_prologue: Option<String>,
+    file_epilogue: Option<String>,
+    progress: Arc<ProgressCounters>,
+}
+
+/// The progress of a file generator thread, shared with the progress reporting thread.
+#[derive(Default)]
+struct FileCounters {
+    /// Number of rows written.
+    rows: AtomicU64,
+    /// Number of bytes written.
+    bytes: AtomicU64,
+    /// Indicator whether the thread has finished writing all files.
+    finished: AtomicBool,
+}
+
+/// The progress of all file generator threads, shared with the progress reporting thread.
+struct ProgressCounters {
+    /// The time when generation starts.
+    start: Instant,
+    /// Indicator whether all tables are written. Used by the progress thread to break the loop.
+    finished: AtomicBool,
+    /// The progress of every file generator thread.
+    files: Box<[FileCounters]>,
+    /// The warnings found since the previous snapshot.
+    warnings: Mutex<Vec<Warning>>,
+}
+
+impl ProgressCounters {
+    /// Creates the counters for the given number of file generator threads.
+    fn new(files_count: u32) -> Self {
+        Self {
+            start: Instant::now(),
+            finished: AtomicBool::new(false),
+            files: (0..files_count).map(|_| FileCounters::default()).collect(),
+            warnings: Mutex::default(),
+        }
+    }
+
+    /// Takes a snapshot of the current progress. The warnings are moved into the snapshot.
+    fn snapshot(&self, total_rows: u64) -> Progress {
+        let files = (1..)
+            .zip(&*self.files)
+            .map(|(file_index, counters)| FileProgress {
+                file_index,
+                rows: counters.rows.load(Ordering::Relaxed),
+                bytes: counters.bytes.load(Ordering::Relaxed),
+                finished: counters.finished.load(Ordering::Relaxed),
+            })
+            .collect::<Vec<_>>();
+        Progress {
+            rows: files.iter().map(|file| file.rows).sum(),
+            total_rows,
+            bytes: files.iter().map(|file| file.bytes).sum(),
+            elapsed: self.start.elapsed(),
+            files,
+            warnings: mem::take(&mut *self.warnings.lock().unwrap()),
+        }
+    }
+
+    /// Gets the counters of the file generator thread.
+    fn file(&self, info: &FileInfo) -> &FileCounters {
+        &self.files[(info.file_index - 1) as usize]
+    }
+}
+
+/// Paces the rows written by all threads to a fixed rate.
+struct Throttle {
+    /// The time when pacing starts.
+    start: Instant,
+    /// Number of rows allowed per second.
+    rows_per_second: NonZeroU32,
+    /// Number of rows which have been scheduled.
+    scheduled_rows: AtomicU64,
+}
+
+impl Throttle {
+    /// Creates a new throttle starting now.
+    fn new(rows_per_second: NonZeroU32) -> Self {
+        Self {
+            start: Instant::now(),
+            rows_per_second,
+            scheduled_rows: AtomicU64::new(0),
+        }
+    }
+
+    /// Blocks the current thread until the next row is allowed to be written.
+    fn wait(&self) {
+        let row = self.scheduled_rows.fetch_add(1, Ordering::Relaxed);
+        let nanos = u128::from(row) * 1_000_000_000 / u128::from(self.rows_per_second.get());
+        let deadline = self.start + Duration::from_nanos(nanos.try_into().unwrap_or(u64::MAX));
+        if let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+            sleep(remaining);
+        }
+    }
+}
+
+/// The content of the `information_schema.json` file.
+#[derive(Serialize)]
+struct InformationSchema<'a> {
+    tables: Vec<InformationSchemaTable<'a>>,
+    relationships: Vec<InformationSchemaRelationship<'a>>,
+}
+
+/// A table in the `information_schema.json` file.
+#[derive(Serialize)]
+struct InformationSchemaTable<'a> {
+    name: &'a str,
+    qualified_name: &'a str,
+    columns: Vec<InformationSchemaColumn<'a>>,
+}
+
+/// A column in the `information_schema.json` file.
+#[derive(Serialize)]
+struct InformationSchemaColumn<'a> {
+    name: Option<&'a str>,
+    ordinal_position: usize,
+    data_type: Option<&'a str>,
+    is_nullable: bool,
+}
+
+/// A relationship from a dependency directive in the `information_schema.json` file.
+#[derive(Serialize)]
+struct InformationSchemaRelationship<'a> {
+    parent: &'a str,
+    child: &'a str,
+}
+
+/// Splits the text between the column name and the expression into the data type and whether the
+/// column is nullable.
+///
+/// The data type is the text before the first constraint keyword (`NOT NULL`, `PRIMARY KEY`, etc.).
+/// The column is considered nullable unless it is declared `NOT NULL` or `PRIMARY KEY`.
+fn parse_column_type(column_type: &str) -> (Option<&str>, bool) {
+    const CONSTRAINT_KEYWORDS: &[&str] = &[
+        "AUTO_INCREMENT",
+        "CHECK",
+        "COLLATE",
+        "CONSTRAINT",
+        "DEFAULT",
+        "GENERATED",
+        "NOT",
+        "NULL",
+        "PRIMARY",
+        "REFERENCES",
+        "UNIQUE",
+    ];
+
+    let column_type = column_type.trim_end_matches(|c: char| c == ',' || c.is_whitespace());
+    let mut data_type_end = column_type.len();
+    let mut is_nullable = true;
+    let mut previous_word = "";
+    let mut word_start = None;
+    for (i, c) in column_type.char_indices().chain(Some((column_type.len(), ' '))) {
+        let is_word_char = c.is_alphanumeric() || c == '_';
+        match (word_start, is_word_char) {
+            (None, true) => word_start = Some(i),
+            (Some(start), false) => {
+                let word = &column_type[start..i];
+                if CONSTRAINT_KEYWORDS.iter().any(|kw| word.eq_ignore_ascii_case(kw)) {
+                    data_type_end = data_type_end.min(start);
+                }
+                if (previous_word.eq_ignore_ascii_case("NOT") && word.eq_ignore_ascii_case("NULL"))
+                    || word.eq_ignore_ascii_case("PRIMARY")
+                {
+                    is_nullable = false;
+                }
+                previous_word = word;
+                word_start = None;
+            }
+            _ => {}
+        }
+    }
+
+    let data_type = column_type[..data_type_end].trim_end();
+    (Some(data_type).filter(|t| !t.is_empty()), is_nullable)
+}
+
+/// The content of the manifest file written alongside a data file.
+#[derive(Serialize, Deserialize)]
+struct Manifest {
+    /// The file name of the data file.
+    file: String,
+    /// Index of the file generator thread which wrote the data file.
+    file_index: u32,
+    /// Number of rows written to the data file.
+    rows: u64,
+    /// Size of the data file in bytes.
+    size: u64,
+    /// The XXH64 checksum of the data file as 16 hex digits.
+    xxh64: String,
+    /// The seed used to generate the data file.
+    seed: Seed,
+}
+
+/// Returns the path of the manifest file of the data file.
+fn manifest_path(data_file_path: &Path) -> PathBuf {
+    let mut path = data_file_path.as_os_str().to_owned();
+    path.push(".manifest.json");
+    PathBuf::from(path)
+}
+
+/// Computes the size and the XXH64 checksum of the file.
+fn checksum_file(path: &Path) -> Result<(u64, u64), S<Error>> {
+    let mut file = File::open(path).with_path("open data file for checksum", path)?;
+    let mut hasher = Xxh64::new(0);
+    let mut buf = vec![0; 1 << 16];
+    let mut size = 0;
+    loop {
+        let len = file.read(&mut buf).with_path("read data file for checksum", path)?;
+        if len == 0 {
+            return Ok((size, hasher.digest()));
+        }
+        hasher.update(&buf[..len]);
+        size += len as u64;
+    }
+}
+
+/// Arguments to the `dbgen verify` CLI program.
+#[derive(Parser, Debug, Clone)]
+#[command(long_version(crate::FULL_VERSION))]
+pub struct VerifyArgs {
+    /// Directory containing the data files and their manifests.
+    pub out_dir: PathBuf,
+
+    /// Only print the data files which failed verification.
+    #[arg(short, long)]
+    pub quiet: bool,
+
+    /// Also regenerate the data from the seed recorded in the manifests into this directory, and
+    /// check that the content is the same.
+    #[arg(long, requires = "args")]
+    pub regenerate_into: Option<PathBuf>,
+
+    /// The arguments to the `dbgen` CLI program which generated the data files, used with
+    /// `--regenerate-into`.
+    #[arg(last = true)]
+    pub args: Vec<String>,
+}
+
+/// Reads a manifest file.
+fn read_manifest(manifest_path: &Path) -> Result<Manifest, S<Error>> {
+    let file = File::open(manifest_path).with_path("open manifest file", manifest_path)?;
+    serde_json::from_reader(BufReader::new(file))
+        .map_err(io::Error::from)
+        .with_path("read manifest file", manifest_path)
+}
+
+/// Checks the data files in the directory against the manifests written by `--manifest`.
+///
+/// With `--regenerate-into`, the data files are also regenerated from the recorded seed and the
+/// given arguments, and their manifests are compared.
+pub fn verify(args: &VerifyArgs, span_registry: &mut Registry) -> Result<(), S<Error>> {
+    let mut manifest_paths = Vec::new();
+    for entry in read_dir(&args.out_dir).with_path("read output directory", &args.out_dir)? {
+        let path = entry.with_path("read output directory", &args.out_dir)?.path();
+        if path.to_string_lossy().ends_with(".manifest.json") {
+            manifest_paths.push(path);
+        }
+    }
+    manifest_paths.sort();
+
+    let mut failed = 0;
+    let mut manifests = Vec::with_capacity(manifest_paths.len());
+    for manifest_path in &manifest_paths {
+        let manifest = read_manifest(manifest_path)?;
+        let expected = (manifest.size, manifest.xxh64);
+        let actual = checksum_file(&args.out_dir.join(&manifest.file))
+            .ok()
+            .map(|(size, xxh64)| (size, format!("{xxh64:016x}")));
+        match actual {
+            Some(actual) if actual == expected => {
+                if !args.quiet {
+                    println!("OK      {}", manifest.file);
+                }
+            }
+            Some((size, xxh64)) => {
+                failed += 1;
+                println!(
+                    "FAILED  {} (expected {} bytes with XXH64 {}, found {size} bytes with XXH64 {xxh64})",
+                    manifest.file, expected.0, expected.1,
+                );
+            }
+            None => {
+                failed += 1;
+                println!("MISSING {}", manifest.file);
+            }
+        }
+        manifests.push(manifest);
+    }
+
+    if let Some(regenerate_into) = &args.regenerate_into {
+        failed += verify_regenerated(args, regenerate_into, &manifests, span_registry)?;
+    }
+
+    if failed > 0 {
+        return Err(Error::VerificationFailed {
+            failed,
+            total: manifest_paths.len(),
+        }
+        .no_span());
+    }
+    Ok(())
+}
+
+/// Regenerates the data files described by the manifests into `regenerate_into`, and returns the
+/// number of data files whose regenerated content differs.
+fn verify_regenerated(
+    args: &VerifyArgs,
+    regenerate_into: &Path,
+    manifests: &[Manifest],
+    span_registry: &mut Registry,
+) -> Result<usize, S<Error>> {
+    let Some(seed) = manifests.first().map(|m| m.seed) else {
+        return Ok(0);
+    };
+    if manifests.iter().any(|m| m.seed.0 != seed.0) {
+        return Err(Error::UnsupportedCliParameter {
+            kind: "manifests with different seeds in",
+            value: args.out_dir.display().to_string(),
+        }
+        .no_span());
+    }
+    // the output directory is always replaced, so allow it to be given in the original arguments.
+    let argv = iter::once(OsString::from("dbgen"))
+        .chain(args.args.iter().map(OsString::from))
+        .chain([OsString::from("--out-dir"), regenerate_into.as_os_str().to_owned()]);
+    let mut gen_args = Args::command()
+        .args_override_self(true)
+        .try_get_matches_from(argv)
+        .and_then(|matches| Args::from_arg_matches(&matches))
+        .map_err(|e| {
+            Error::UnsupportedCliParameter {
+                kind: "arguments to regenerate",
+                value: e.to_string(),
+            }
+            .no_span()
+        })?;
+    gen_args.seed = Some(seed);
+    gen_args.manifest = true;
+    gen_args.quiet = true;
+    run(gen_args, span_registry)?;
+
+    let mut failed = 0;
+    for manifest in manifests {
+        let regenerated_path = manifest_path(&regenerate_into.join(&manifest.file));
+        let regenerated = read_manifest(&regenerated_path).ok();
+        match regenerated {
+            Some(r) if (r.rows, r.size, &r.xxh64) == (manifest.rows, manifest.size, &manifest.xxh64) => {
+                if !args.quiet {
+                    println!("SAME    {}", manifest.file);
+                }
+            }
+            Some(r) => {
+                failed += 1;
+                println!(
+                    "DIFFERS {} (expected {} rows in {} bytes with XXH64 {}, regenerated {} rows in {} bytes with XXH64 {})",
+                    manifest.file, manifest.rows, manifest.size, manifest.xxh64, r.rows, r.size, r.xxh64,
+                );
+            }
+            None => {
+                failed += 1;
+                println!("DIFFERS {} (not regenerated)", manifest.file);
+            }
+        }
+    }
+    Ok(failed)
 }
 
 /// Information specific to a file and its derived tables.
@@ -1026,120 +3337,659 @@ impl Env {
 
     /// Writes the `CREATE TABLE` schema files.
     fn write_table_schema(&self) -> Result<(), S<Error>> {
-        for table in &self.tables {
-            let path = self.out_dir.join(format!("{}-schema.sql", table.name.unique_name()));
+        for (table, shard) in self.tables.iter().flat_map(|t| t.shard_indices().map(move |s| (t, s))) {
+            let name = table.shard_name(shard);
+            let path = self.out_dir.join(format!("{}-schema.sql", name.unique_name()));
             let mut file = BufWriter::new(File::create(&path).with_path("create table schema file", &path)?);
-            write!(
-                file,
-                "CREATE TABLE {} {}",
-                table.name.table_name(self.qualified),
-                table.content
-            )
-            .with_path("write table schema file", &path)?;
+            if let Some(dialect) = self.schema_dialect {
+                let schema = table.shard_schema(self.qualified, shard);
+                let columns =
+                    schema
+                        .output_column_names()
+                        .zip(schema.output_column_types())
+                        .map(|(name, column_type)| {
+                            let (data_type, is_nullable) = parse_column_type(column_type);
+                            (name, data_type, is_nullable)
+                        });
+                file.write_all(ddl::create_table(dialect, schema.name, columns).as_bytes())
+                    .with_path("write table schema file", &path)?;
+            } else {
+                write!(
+                    file,
+                    "CREATE {}TABLE {}{} {}",
+                    table.modifiers,
+                    if table.if_not_exists { "IF NOT EXISTS " } else { "" },
+                    name.table_name(self.qualified),
+                    table.content
+                )
+                .with_path("write table schema file", &path)?;
+                if !table.auxiliary_statements.is_empty() && !table.content.ends_with('\n') {
+                    writeln!(file).with_path("write table schema file", &path)?;
+                }
+            }
+            for statement in &table.auxiliary_statements {
+                // the statements of sharded tables have been checked to be rewritable.
+                let statement = shard
+                    .and_then(|shard| ddl::shard_auxiliary_statement(statement, shard))
+                    .map_or(Cow::Borrowed(statement.as_str()), Cow::Owned);
+                writeln!(file, "{statement}").with_path("write table schema file", &path)?;
+            }
         }
         Ok(())
     }
 
-    fn open_data_file(&self, path: PathBuf) -> Result<Box<dyn Write>, S<Error>> {
-        Ok(if !ComponentName::Data.is_in(self.components_mask) {
-            Box::new(sink())
-        } else if let Some((compression, level)) = self.compression {
+    /// Writes the `information_schema.json` file.
+    fn write_information_schema(&self) -> Result<(), S<Error>> {
+        // every shard is listed as a separate table.
+        let tables = self
+            .tables
+            .iter()
+            .flat_map(|table| table.shard_indices().map(move |shard| (table, shard)))
+            .map(|(table, shard)| {
+                let schema = table.schema(self.qualified);
+                let columns = schema
+                    .column_names()
+                    .zip(schema.column_types())
+                    .enumerate()
+                    .map(|(i, (name, column_type))| {
+                        let (data_type, is_nullable) = parse_column_type(column_type);
+                        InformationSchemaColumn {
+                            name: Some(name).filter(|n| !n.is_empty()),
+                            ordinal_position: i + 1,
+                            data_type,
+                            is_nullable,
+                        }
+                    })
+                    .collect();
+                let name = table.shard_name(shard);
+                InformationSchemaTable {
+                    name: name.table_name(false),
+                    qualified_name: name.table_name(true),
+                    columns,
+                }
+            })
+            .collect();
+        let relationships = self
+            .tables
+            .iter()
+            .flat_map(|parent| {
+                parent
+                    .derived
+                    .iter()
+                    .map(move |(child, _)| (parent, &self.tables[*child]))
+            })
+            .flat_map(|(parent, child)| {
+                // a row of any parent shard may derive rows in any child shard.
+                parent.shard_indices().flat_map(move |parent_shard| {
+                    child
+                        .shard_indices()
+                        .map(move |child_shard| InformationSchemaRelationship {
+                            parent: parent.shard_name(parent_shard).table_name(false),
+                            child: child.shard_name(child_shard).table_name(false),
+                        })
+                })
+            })
+            .collect();
+
+        let path = self.out_dir.join("information_schema.json");
+        let mut file = BufWriter::new(File::create(&path).with_path("create information schema file", &path)?);
+        serde_json::to_writer_pretty(&mut file, &InformationSchema { tables, relationships })
+            .map_err(io::Error::from)
+            .and_then(|()| writeln!(file))
+            .with_path("write information schema file", &path)?;
+        Ok(())
+    }
+
+    /// Returns the actual path of the data file, including the compression extension.
+    fn data_file_path(&self, path: PathBuf) -> PathBuf {
+        if let Some((compression, _)) = self.compression {
             let mut path = path.into_os_string();
             path.push(".");
             path.push(compression.extension());
-            let path = PathBuf::from(path);
-            compression.wrap(File::create(&path).with_path("create data file", &path)?, level)
+            PathBuf::from(path)
         } else {
-            Box::new(File::create(&path).with_path("create data file", &path)?)
-        })
+            path
+        }
     }
 
-    /// Writes the data file.
-    fn write_data_file(&self, info: &FileInfo, state: &mut State) -> Result<(), S<Error>> {
+    /// Opens the data file at the current path of the writer, replacing (and thus closing) the
+    /// previous file.
+    ///
+    /// Returns the size and the checksum of the previous file if a manifest is written.
+    fn open_data_file(&self, w: &mut FormatWriter<'_>) -> Result<Option<SharedChecksum>, S<Error>> {
+        let mut checksum = None;
+        let writer: Box<dyn Write> = if ComponentName::Data.is_in(self.components_mask) {
+            let path = self.data_file_path(w.path());
+            let file = File::create(&path).with_path("create data file", &path)?;
+            let file: Box<dyn Write> = if self.manifest_seed.is_some() {
+                let shared = SharedChecksum::new(RefCell::new((0, Xxh64::new(0))));
+                checksum = Some(shared.clone());
+                Box::new(ChecksumWriter {
+                    writer: file,
+                    checksum: shared,
+                })
+            } else {
+                Box::new(file)
+            };
+            if let Some((compression, level)) = self.compression {
+                compression.wrap(file, level)
+            } else {
+                file
+            }
+        } else {
+            Box::new(sink())
+        };
+        // the previous file is finished (e.g. the compression trailer is written) when dropped.
+        w.output.writer = BufWriter::new(writer);
+        Ok(mem::replace(&mut w.checksum, checksum))
+    }
+
+    /// Opens the data files of every table for the file generator thread.
+    fn open_data_files<'a>(
+        &'a self,
+        info: &FileInfo,
+        state: &'a mut State,
+    ) -> Result<writer::Env<'a, FormatWriter<'a>>, S<Error>> {
         let path_suffix = format!(".{0:01$}", info.file_index, self.file_num_digits);
-        let format = self.format.create(&self.format_options);
 
-        let mut fwe = writer::Env::new(&self.tables, state, self.qualified, |table| {
-            let path = self.out_dir.join([table.name.unique_name(), &path_suffix].concat());
-            let mut w = FormatWriter::new(path, self.format.extension(), self.file_size, &*format);
-            w.writer = BufWriter::new(self.open_data_file(w.path())?);
-            Ok(w)
-        })?;
+        let partition_by = self.partition_by.as_deref();
+        let mut fwe = writer::Env::with_partitions(
+            &self.tables,
+            state,
+            self.qualified,
+            partition_by,
+            move |table, shard, partition| {
+                let dir = if let Some(partition) = partition {
+                    let dir = self.out_dir.join(partition);
+                    if ComponentName::Data.is_in(self.components_mask) {
+                        create_dir_all(&dir).with_path("create partition directory", &dir)?;
+                    }
+                    dir
+                } else {
+                    self.out_dir.clone()
+                };
+                let path = dir.join([table.shard_name(shard).unique_name(), &path_suffix].concat());
+                let format = self
+                    .format
+                    .create(&self.format_options, &table.shard_schema(self.qualified, shard))?;
+                let mut w = FormatWriter::new(
+                    path,
+                    self.format.extension(),
+                    self.file_size,
+                    self.rotate_interval,
+                    self.rows_per_data_file,
+                    format,
+                );
+                if self.max_lengths.is_some() {
+                    w.max_lengths = Some(Vec::new());
+                }
+                w.transaction_size = self.transaction_size;
+                w.file_prologue = self.file_prologue.as_deref();
+                w.file_epilogue = self.file_epilogue.as_deref();
+                w.shard = shard;
+                self.open_data_file(&mut w)?;
+                Ok(w)
+            },
+        )?;
+        if self.reported_warnings.is_some() {
+            fwe.check_warnings(self.format.output_checks(&self.format_options));
+        }
+        if self.validate_constraints {
+            fwe.validate_constraints()?;
+        }
+        if let Some(rate) = self.duplicate_rate {
+            fwe.duplicate_rows(rate);
+        }
+        if let Some(weights) = self.change_weights {
+            fwe.change_events(weights, self.change_retention)?;
+        }
+        fwe.spill_sorted_rows(SpillOptions {
+            dir: self.out_dir.clone(),
+            buffer_rows: self.sort_buffer_rows,
+        });
+        Ok(fwe)
+    }
 
+    /// Writes an INSERT statement with the given number of rows, and rotates the files if needed.
+    ///
+    /// If the files are split by rows, the statement is broken into several ones so that the
+    /// rotation happens exactly after the limit.
+    fn write_insert(
+        &self,
+        fwe: &mut writer::Env<'_, FormatWriter<'_>>,
+        info: &FileInfo,
+        mut rows_count: u32,
+    ) -> Result<(), S<Error>> {
+        loop {
+            for (table, w) in fwe.tables() {
+                if let Some(old_path) = w.try_rotate_by_rows() {
+                    self.rotate_file(info, table, w, old_path)?;
+                }
+            }
+            let rows_left = fwe.tables().filter_map(|(_, w)| w.rows_left()).min();
+            let statement_rows_count =
+                rows_left.map_or(rows_count, |r| rows_count.min(r.try_into().unwrap_or(u32::MAX)));
+            self.write_statement(fwe, info, statement_rows_count)?;
+            rows_count -= statement_rows_count;
+            if rows_count == 0 {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Writes a single INSERT statement with the given number of rows, and rotates the files if
+    /// needed.
+    fn write_statement(
+        &self,
+        fwe: &mut writer::Env<'_, FormatWriter<'_>>,
+        info: &FileInfo,
+        rows_count: u32,
+    ) -> Result<(), S<Error>> {
+        let written_rows = if let Some(throttle) = &self.throttle {
+            let mut written_rows = 0;
+            for _ in 0..rows_count {
+                throttle.wait();
+                written_rows += fwe.write_row()?;
+            }
+            written_rows
+        } else {
+            fwe.write_rows(rows_count.into())?
+        };
+        fwe.write_trailer()?;
+        self.report_warnings(fwe);
+
+        let mut total_uncommitted_size = 0;
+        for (table, w) in fwe.tables() {
+            w.end_statement()?;
+            w.written_rows += written_rows;
+            total_uncommitted_size += mem::take(&mut w.output.uncommitted_size);
+            if self.throttle.is_some() {
+                // push out the rows immediately instead of in bursts when the buffer is full.
+                w.output.writer.flush().with_path_fn("flush data file", || w.path())?;
+            }
+            if let Some(old_path) = w.try_rotate() {
+                self.rotate_file(info, table, w, old_path)?;
+            }
+            if let Some(retention) = self.retention {
+                w.remove_expired_files(retention)?;
+            }
+        }
+        let counters = self.progress.file(info);
+        counters.bytes.fetch_add(total_uncommitted_size, Ordering::Relaxed);
+        counters.rows.fetch_add(rows_count.into(), Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Closes the data file at `old_path` after the writer has advanced, and opens the next one.
+    fn rotate_file(
+        &self,
+        info: &FileInfo,
+        table: &Table,
+        w: &mut FormatWriter<'_>,
+        old_path: PathBuf,
+    ) -> Result<(), S<Error>> {
+        w.finish_file(&table.shard_schema(self.qualified, w.shard), &old_path)?;
+        w.output
+            .writer
+            .flush()
+            .with_path("flush old file for rotation", &old_path)?;
+        let checksum = self.open_data_file(w)?;
+        self.write_manifest(info, &old_path, w.previous_file_rows, checksum)?;
+        self.record_data_file(info, table.shard_name(w.shard), &old_path);
+        w.write_file_header(&table.shard_schema(self.qualified, w.shard))?;
+        if self.retention.is_some() && ComponentName::Data.is_in(self.components_mask) {
+            let old_path = self.data_file_path(old_path);
+            if self.manifest_seed.is_some() {
+                w.closed_files.push_back((manifest_path(&old_path), Instant::now()));
+            }
+            w.closed_files.push_back((old_path, Instant::now()));
+        }
+        Ok(())
+    }
+
+    /// Finishes and closes the current data file at the end of the file generator thread.
+    fn close_data_file(&self, info: &FileInfo, table: &Table, w: &mut FormatWriter<'_>) -> Result<(), S<Error>> {
+        let path = w.path();
+        w.finish_file(&table.shard_schema(self.qualified, w.shard), &path)?;
+        w.output.writer.flush().with_path("flush data file", &path)?;
+        w.output.writer = BufWriter::with_capacity(0, Box::new(sink()));
+        self.record_data_file(info, table.shard_name(w.shard), &path);
+        self.write_manifest(info, &path, w.file_rows, w.checksum.take())
+    }
+
+    /// Records the closed data file at `path` of the table `name` to be listed in the load
+    /// scripts, if requested.
+    fn record_data_file(&self, info: &FileInfo, name: &QName, path: &Path) {
+        let Some((_, data_files)) = &self.load_script else {
+            return;
+        };
+        if !ComponentName::Data.is_in(self.components_mask) {
+            return;
+        }
+        let path = self.data_file_path(path.to_owned());
+        let relative_path = path.strip_prefix(&self.out_dir).unwrap_or(&path).to_owned();
+        data_files
+            .lock()
+            .unwrap()
+            .push((info.file_index, name.unique_name().to_owned(), relative_path));
+    }
+
+    /// Writes the scripts loading the closed data files into every table.
+    fn write_load_scripts(&self) -> Result<(), S<Error>> {
+        let Some((dialect, data_files)) = &self.load_script else {
+            return Ok(());
+        };
+        if !ComponentName::Data.is_in(self.components_mask) {
+            return Ok(());
+        }
+        let mut data_files = data_files.lock().unwrap();
+        // the stable sort keeps the rotated files of the same generator thread in order.
+        data_files.sort_by_key(|(file_index, _, _)| *file_index);
+        let compression = self.compression.map(|(compression, _)| compression);
+        for (table, shard) in self.tables.iter().flat_map(|t| t.shard_indices().map(move |s| (t, s))) {
+            let unique_name = table.shard_name(shard).unique_name();
+            let path = self.out_dir.join(format!("{unique_name}-load.sql"));
+            let mut file = BufWriter::new(File::create(&path).with_path("create load script file", &path)?);
+            let schema = table.shard_schema(self.qualified, shard);
+            let columns = schema
+                .output_column_names()
+                .map(|name| dialect.requote(name))
+                .collect::<Vec<_>>();
+            for (_, _, data_file) in data_files.iter().filter(|(_, name, _)| name == unique_name) {
+                let statement = load_statement(
+                    &self.format_options,
+                    *dialect,
+                    compression,
+                    schema.name,
+                    &columns,
+                    data_file,
+                );
+                writeln!(file, "{statement}").with_path("write load script file", &path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes the manifest of the closed data file at `path` containing the given number of rows,
+    /// given the size and the checksum computed while writing the file, if requested.
+    fn write_manifest(
+        &self,
+        info: &FileInfo,
+        path: &Path,
+        rows: u64,
+        checksum: Option<SharedChecksum>,
+    ) -> Result<(), S<Error>> {
+        let (Some(seed), Some(checksum)) = (self.manifest_seed, checksum) else {
+            return Ok(());
+        };
+        let path = self.data_file_path(path.to_owned());
+        let (size, hasher) = &*checksum.borrow();
+        let manifest = Manifest {
+            file: path.file_name().unwrap_or_default().to_string_lossy().into_owned(),
+            file_index: info.file_index,
+            rows,
+            size: *size,
+            xxh64: format!("{:016x}", hasher.digest()),
+            seed,
+        };
+        let manifest_path = manifest_path(&path);
+        let mut file = BufWriter::new(File::create(&manifest_path).with_path("create manifest file", &manifest_path)?);
+        serde_json::to_writer_pretty(&mut file, &manifest)
+            .map_err(io::Error::from)
+            .and_then(|()| writeln!(file))
+            .with_path("write manifest file", &manifest_path)
+    }
+
+    /// Reports the warnings found in the data files, skipping those already reported from other
+    /// files.
+    ///
+    /// The warnings are passed to the progress sink, except in continuous mode where there is no
+    /// progress reporting and they are printed directly unless `--quiet`.
+    fn report_warnings(&self, fwe: &mut writer::Env<'_, FormatWriter<'_>>) {
+        let Some(reported_warnings) = &self.reported_warnings else {
+            return;
+        };
+        for warning in fwe.take_warnings() {
+            let key = (warning.table.clone(), warning.column.clone(), warning.kind);
+            if !reported_warnings.lock().unwrap().insert(key) {
+                continue;
+            }
+            if self.print_warnings {
+                eprintln!("Warning: {warning}");
+            } else if !self.continuous {
+                self.progress.warnings.lock().unwrap().push(warning);
+            }
+        }
+    }
+
+    /// Merges the maximum lengths recorded by the writers into the total.
+    fn merge_max_lengths(&self, fwe: &mut writer::Env<'_, FormatWriter<'_>>) {
+        if let Some(max_lengths) = &self.max_lengths {
+            let mut max_lengths = max_lengths.lock().unwrap();
+            for (i, w) in fwe.writers() {
+                let table_max_lengths = &mut max_lengths[i];
+                let file_max_lengths = w.max_lengths.as_deref().unwrap_or_default();
+                if table_max_lengths.len() < file_max_lengths.len() {
+                    table_max_lengths.resize(file_max_lengths.len(), None);
+                }
+                for (max_length, file_max_length) in table_max_lengths.iter_mut().zip(file_max_lengths) {
+                    if let Some(file_max_length) = *file_max_length {
+                        max_length.get_or_insert(file_max_length).merge(file_max_length);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Writes the data file.
+    fn write_data_file(&self, info: &FileInfo, state: &mut State) -> Result<(), S<Error>> {
+        let mut fwe = self.open_data_files(info, state)?;
         for i in 0..info.inserts_count {
             let rows_count = if i == info.inserts_count - 1 {
                 info.last_insert_rows_count
             } else {
                 self.rows_count
             };
-            for _ in 0..rows_count {
-                fwe.write_row()?;
-            }
-            fwe.write_trailer()?;
+            self.write_insert(&mut fwe, info, rows_count)?;
+        }
+        fwe.write_sorted_rows(FormatWriter::end_statement)?;
+        let mut total_uncommitted_size = 0;
+        for (table, w) in fwe.tables() {
+            total_uncommitted_size += mem::take(&mut w.output.uncommitted_size);
+            self.close_data_file(info, table, w)?;
+        }
+        self.progress
+            .file(info)
+            .bytes
+            .fetch_add(total_uncommitted_size, Ordering::Relaxed);
+        self.merge_max_lengths(&mut fwe);
+        self.progress.file(info).finished.store(true, Ordering::Relaxed);
+        Ok(())
+    }
 
-            let mut total_uncommitted_size = 0;
-            for (table, w) in fwe.tables() {
-                total_uncommitted_size += mem::take(&mut w.uncommitted_size);
-                if w.try_rotate() {
-                    let new_path = w.path();
-                    w.writer.flush().with_path("flush old file for rotation", &new_path)?;
-                    w.writer = BufWriter::new(self.open_data_file(new_path)?);
-                    w.write_file_header(&table.schema(self.qualified))?;
+    /// Writes the data file continuously until a termination signal is received.
+    ///
+    /// The rows are generated in segments, each described by the RNG and the starting row number,
+    /// and evaluated from a fresh state like a file in the normal mode.
+    fn write_continuous_data_file(
+        &self,
+        info: &FileInfo,
+        mut segments: impl Iterator<Item = (Box<dyn RngCore + Send>, u64)>,
+        ctx: &CompileContext,
+    ) -> Result<(), S<Error>> {
+        let Some((rng, row_num)) = segments.next() else {
+            return Ok(());
+        };
+        let mut state = State::new(row_num, rng, ctx.clone());
+        let mut fwe = self.open_data_files(info, &mut state)?;
+        'segments: loop {
+            for i in 0..info.inserts_count {
+                if STOP_REQUESTED.load(Ordering::Relaxed) {
+                    break 'segments;
                 }
+                let rows_count = if i == info.inserts_count - 1 {
+                    info.last_insert_rows_count
+                } else {
+                    self.rows_count
+                };
+                self.write_insert(&mut fwe, info, rows_count)?;
             }
-            WRITTEN_SIZE.fetch_add(total_uncommitted_size, Ordering::Relaxed);
-            WRITE_PROGRESS.fetch_add(rows_count.into(), Ordering::Relaxed);
+            let Some((rng, row_num)) = segments.next() else {
+                break;
+            };
+            let state = fwe.state_mut();
+            *state = State::new(row_num, rng, ctx.clone());
+            state.resolve_references(&self.tables).no_span_err()?;
         }
+        for (table, w) in fwe.tables() {
+            self.close_data_file(info, table, w)?;
+        }
+        self.merge_max_lengths(&mut fwe);
+        self.progress.file(info).finished.store(true, Ordering::Relaxed);
         Ok(())
     }
+
+    /// Prints the maximum lengths of string values written to every column.
+    fn print_max_lengths(&self, max_lengths: &[Vec<Option<MaxLength>>]) {
+        println!("Maximum lengths of string values:");
+        for (table, table_max_lengths) in self.tables.iter().zip(max_lengths) {
+            let schema = table.schema(self.qualified);
+            let mut column_names = schema.output_column_names();
+            for (i, max_length) in table_max_lengths.iter().enumerate() {
+                let column_name = column_names.next().map_or_else(|| format!("#{}", i + 1), str::to_owned);
+                if let Some(MaxLength { chars, bytes }) = max_length {
+                    println!("  {}.{column_name}: {chars} characters, {bytes} bytes", schema.name);
+                }
+            }
+        }
+    }
 }
 
-/// Runs the progress bar thread.
+/// Runs the progress reporting thread.
 ///
-/// This function will loop and update the progress bar every 0.5 seconds, until [`WRITE_FINISHED`]
-/// becomes `true`.
-fn run_progress_thread(total_rows: u64) {
-    const TICK_FORMAT: &str = "🕐🕑🕒🕓🕔🕕🕖🕗🕘🕙🕚🕛";
-
-    let mb = MultiBar::new();
-
-    let mut pb = mb.create_bar(total_rows);
-
-    let mut speed_bar = mb.create_bar(0);
-    speed_bar.set_units(Units::Bytes);
-    speed_bar.show_percent = false;
-    speed_bar.show_time_left = false;
-    speed_bar.show_tick = true;
-    speed_bar.show_bar = false;
-    speed_bar.tick_format(TICK_FORMAT);
-
-    pb.message("Progress ");
-    speed_bar.message("Size     ");
-
-    let mb_thread = spawn(move || mb.listen());
-
-    while !WRITE_FINISHED.load(Ordering::Relaxed) {
+/// This function will loop and report the progress to the sink every 0.5 seconds, until all tables
+/// are written.
+fn run_progress_thread(sink: &mut dyn ProgressSink, counters: &ProgressCounters, total_rows: u64) {
+    while !counters.finished.load(Ordering::Relaxed) {
         sleep(Duration::from_millis(500));
-        let rows_count = WRITE_PROGRESS.load(Ordering::Relaxed);
-        pb.set(rows_count);
+        sink.update(&counters.snapshot(total_rows));
+    }
+    sink.finish(&counters.snapshot(total_rows));
+}
+
+/// A [`ProgressSink`] drawing progress bars on the terminal.
+#[derive(Default)]
+struct ProgressBars {
+    /// The row progress bar, the size bar and the thread drawing them, created on first update.
+    bars: Option<(ProgressBar<Pipe>, ProgressBar<Pipe>, JoinHandle<()>)>,
+}
 
-        let written_size = WRITTEN_SIZE.load(Ordering::Relaxed);
-        if rows_count != 0 {
-            speed_bar.total = written_size.mul_div_round(total_rows, rows_count).unwrap_or(u64::MAX);
-            speed_bar.set(written_size);
+impl ProgressBars {
+    /// Updates the progress bars, creating them if needed.
+    fn draw(&mut self, progress: &Progress) {
+        const TICK_FORMAT: &str = "🕐🕑🕒🕓🕔🕕🕖🕗🕘🕙🕚🕛";
+
+        let (pb, speed_bar, _) = self.bars.get_or_insert_with(|| {
+            let mb = MultiBar::new();
+
+            let mut pb = mb.create_bar(progress.total_rows);
+
+            let mut speed_bar = mb.create_bar(0);
+            speed_bar.set_units(Units::Bytes);
+            speed_bar.show_percent = false;
+            speed_bar.show_time_left = false;
+            speed_bar.show_tick = true;
+            speed_bar.show_bar = false;
+            speed_bar.tick_format(TICK_FORMAT);
+
+            pb.message("Progress ");
+            speed_bar.message("Size     ");
+
+            let mb_thread = spawn(move || mb.listen());
+            (pb, speed_bar, mb_thread)
+        });
+
+        pb.set(progress.rows);
+        if progress.rows != 0 {
+            speed_bar.total = progress
+                .bytes
+                .mul_div_round(progress.total_rows, progress.rows)
+                .unwrap_or(u64::MAX);
+            speed_bar.set(progress.bytes);
         }
     }
+}
 
-    pb.finish_println("Done!");
-    speed_bar.finish();
+impl ProgressSink for ProgressBars {
+    fn update(&mut self, progress: &Progress) {
+        for warning in &progress.warnings {
+            eprintln!("Warning: {warning}");
+        }
+        self.draw(progress);
+    }
 
-    mb_thread.join().unwrap();
+    fn finish(&mut self, progress: &Progress) {
+        for warning in &progress.warnings {
+            eprintln!("Warning: {warning}");
+        }
+        self.draw(progress);
+        if let Some((mut pb, mut speed_bar, mb_thread)) = self.bars.take() {
+            pb.finish_println("Done!");
+            speed_bar.finish();
+            mb_thread.join().unwrap();
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_column_type() {
+        assert_eq!(parse_column_type(""), (None, true));
+        assert_eq!(parse_column_type("INTEGER,"), (Some("INTEGER"), true));
+        assert_eq!(parse_column_type("int not null"), (Some("int"), false));
+        assert_eq!(parse_column_type("VARCHAR(20) NULL"), (Some("VARCHAR(20)"), true));
+        assert_eq!(
+            parse_column_type("DECIMAL(10, 2) DEFAULT 0"),
+            (Some("DECIMAL(10, 2)"), true)
+        );
+        assert_eq!(parse_column_type("BIGINT PRIMARY KEY"), (Some("BIGINT"), false));
+        assert_eq!(parse_column_type("NOT NULL"), (None, false));
+        assert_eq!(
+            parse_column_type("nullable_column_type"),
+            (Some("nullable_column_type"), true)
+        );
+    }
+
+    #[test]
+    fn test_parse_seconds() {
+        assert_eq!(parse_seconds("90"), Ok(90));
+        assert_eq!(parse_seconds("90s"), Ok(90));
+        assert_eq!(parse_seconds("15m"), Ok(900));
+        assert_eq!(parse_seconds("2 h"), Ok(7200));
+        assert_eq!(parse_seconds("7d"), Ok(604_800));
+        assert!(parse_seconds("3w").is_err());
+        assert!(parse_seconds("h").is_err());
+    }
+
+    #[test]
+    fn test_parse_dml_weight() {
+        let weight = parse_dml_weight("Update=1.5").unwrap();
+        assert_eq!(
+            weight,
+            DmlWeight {
+                op: ChangeOp::Update,
+                weight: 1.5
+            }
+        );
+        assert_eq!(weight.to_string(), "update=1.5");
+        assert!(parse_dml_weight("upsert=1").is_err());
+        assert!(parse_dml_weight("delete=-1").is_err());
+        assert!(parse_dml_weight("insert").is_err());
+    }
+
     #[test]
     fn test_row_args() {
         let test_cases = vec![
@@ -1279,4 +4129,567 @@ mod tests {
             assert_eq!(args.row_args(), row_args);
         }
     }
+
+    /// A [`ProgressSink`] collecting the reported warnings.
+    struct WarningsSink(Arc<Mutex<Vec<Warning>>>);
+
+    impl ProgressSink for WarningsSink {
+        fn update(&mut self, progress: &Progress) {
+            self.0.lock().unwrap().extend_from_slice(&progress.warnings);
+        }
+
+        fn finish(&mut self, progress: &Progress) {
+            self.update(progress);
+        }
+    }
+
+    #[test]
+    fn test_report_warnings() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let warnings = Arc::new(Mutex::new(Vec::new()));
+        let mut session = Session::default();
+        session.set_progress_sink(Box::new(WarningsSink(warnings.clone())));
+        let args = Args {
+            template_string: Some(
+                "CREATE TABLE result (a DOUBLE {{ 9007199254740992 + rownum }}, b TINYINT {{ rownum * 50 }});"
+                    .to_owned(),
+            ),
+            out_dir: out_dir.path().to_owned(),
+            format: FormatName::Csv,
+            quiet: true,
+            files_count: 2,
+            rows_count: 4,
+            ..Args::default()
+        };
+        run_in_session(args, &mut session, &mut Registry::default()).unwrap();
+
+        // every kind of problem is reported once per column, even if found in several files.
+        let warnings = warnings.lock().unwrap();
+        let mut kinds = warnings
+            .iter()
+            .map(|warning| (&*warning.column, warning.kind))
+            .collect::<Vec<_>>();
+        kinds.sort_by_key(|(column, _)| *column);
+        assert_eq!(
+            kinds,
+            [
+                ("a", WarningKind::ImpreciseFloat),
+                ("b", WarningKind::IntegerOutOfRange)
+            ]
+        );
+        let imprecise = warnings.iter().find(|warning| warning.column == "a").unwrap();
+        assert!(imprecise.value.parse::<u64>().unwrap() > 1 << 53);
+    }
+
+    #[test]
+    fn test_report_output_warnings() {
+        let template = "CREATE TABLE result (
+            id BIGINT PRIMARY KEY {{ 9007199254740992 + rownum }},
+            s TEXT {{ 'a;b' }},
+            t TEXT {{ 'a\"b' }}
+        );";
+        for (format, expected) in [
+            (
+                FormatName::Csv,
+                [
+                    ("s", WarningKind::UnquotedCsvString),
+                    ("t", WarningKind::UnquotedCsvString),
+                ]
+                .as_slice(),
+            ),
+            (
+                FormatName::Debezium,
+                [("id", WarningKind::UnsafeJsonInteger)].as_slice(),
+            ),
+        ] {
+            let out_dir = tempfile::tempdir().unwrap();
+            let warnings = Arc::new(Mutex::new(Vec::new()));
+            let mut session = Session::default();
+            session.set_progress_sink(Box::new(WarningsSink(warnings.clone())));
+            let args = Args {
+                template_string: Some(template.to_owned()),
+                out_dir: out_dir.path().to_owned(),
+                format,
+                csv_delimiter: Some(";".to_owned()),
+                csv_quote: CsvQuote::Never,
+                quiet: true,
+                ..Args::default()
+            };
+            run_in_session(args, &mut session, &mut Registry::default()).unwrap();
+
+            let warnings = warnings.lock().unwrap();
+            let mut kinds = warnings
+                .iter()
+                .map(|warning| (&*warning.column, warning.kind))
+                .collect::<Vec<_>>();
+            kinds.sort_by_key(|(column, _)| *column);
+            assert_eq!(kinds, expected, "{format:?}");
+        }
+    }
+
+    #[test]
+    fn test_unquoted_csv_headers() {
+        for (template, csv_newline_escape, is_ok) in [
+            (r#"CREATE TABLE t ("a;b" INT {{ 1 }});"#, None, false),
+            (r#"CREATE TABLE t ("a""b" INT {{ 1 }});"#, None, false),
+            ("CREATE TABLE t (\"a\nb\" INT {{ 1 }});", None, false),
+            ("CREATE TABLE t (\"a\nb\" INT {{ 1 }});", Some(r"\n".to_owned()), true),
+            (r#"CREATE TABLE t ("a,b" INT {{ 1 }});"#, None, true),
+        ] {
+            let out_dir = tempfile::tempdir().unwrap();
+            let args = Args {
+                template_string: Some(template.to_owned()),
+                out_dir: out_dir.path().to_owned(),
+                format: FormatName::Csv,
+                headers: true,
+                csv_delimiter: Some(";".to_owned()),
+                csv_quote: CsvQuote::Never,
+                csv_newline_escape,
+                quiet: true,
+                ..Args::default()
+            };
+            let result = run_in_session(args, &mut Session::default(), &mut Registry::default());
+            match result {
+                Ok(()) => assert!(is_ok, "{template}"),
+                Err(error) => {
+                    assert!(!is_ok, "{template}");
+                    assert_eq!(error.inner.code(), "unsupported_cli_parameter", "{template}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_order_by_with_split_files() {
+        for (table_order_by, rows_per_data_file, is_ok) in [
+            (vec!["t=-rownum".to_owned()], None, true),
+            (vec!["t=-rownum".to_owned()], NonZeroU64::new(2), false),
+            (vec!["u=-rownum".to_owned()], None, false),
+            (vec!["-rownum".to_owned()], None, false),
+            (Vec::new(), NonZeroU64::new(2), true),
+        ] {
+            let out_dir = tempfile::tempdir().unwrap();
+            let args = Args {
+                template_string: Some("CREATE TABLE t (a INT {{ rownum }});".to_owned()),
+                out_dir: out_dir.path().to_owned(),
+                total_count: Some(4),
+                table_order_by: table_order_by.clone(),
+                rows_per_data_file,
+                quiet: true,
+                ..Args::default()
+            };
+            let result = run_in_session(args, &mut Session::default(), &mut Registry::default());
+            match result {
+                Ok(()) => assert!(is_ok, "{table_order_by:?} {rows_per_data_file:?}"),
+                Err(error) => {
+                    assert!(!is_ok, "{table_order_by:?} {rows_per_data_file:?}");
+                    assert_eq!(error.inner.code(), "unsupported_cli_parameter");
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "arrow")]
+    #[test]
+    fn test_arrow_round_trip() {
+        use arrow_array::{
+            cast::AsArray as _,
+            types::{Decimal128Type, Int32Type},
+        };
+        use arrow_ipc::reader::FileReader;
+
+        let out_dir = tempfile::tempdir().unwrap();
+        let args = Args {
+            template_string: Some(
+                "CREATE TABLE result (a INT {{ rownum }}, b DECIMAL(10, 8) {{ 1e-7 * rownum }}, c TEXT {{ 'x' || rownum }});"
+                    .to_owned(),
+            ),
+            out_dir: out_dir.path().to_owned(),
+            format: FormatName::Arrow,
+            quiet: true,
+            rows_count: 2,
+            components: vec![ComponentName::Data],
+            ..Args::default()
+        };
+        run_in_session(args, &mut Session::default(), &mut Registry::default()).unwrap();
+
+        let file = File::open(out_dir.path().join("result.1.arrow")).unwrap();
+        let batches = FileReader::try_new(file, None)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(batches.len(), 1);
+        let batch = &batches[0];
+        assert_eq!(batch.column(0).as_primitive::<Int32Type>().values(), &[1, 2]);
+        assert_eq!(batch.column(1).as_primitive::<Decimal128Type>().values(), &[10, 20]);
+        assert_eq!(
+            batch.column(2).as_string::<i32>().iter().collect::<Vec<_>>(),
+            [Some("x1"), Some("x2")]
+        );
+    }
+
+    #[test]
+    fn test_too_many_partitions() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let args = Args {
+            template_string: Some("CREATE TABLE result (p INT {{ rownum }});".to_owned()),
+            out_dir: out_dir.path().to_owned(),
+            quiet: true,
+            rows_count: (writer::MAX_PARTITIONS + 1).try_into().unwrap(),
+            partition_by: Some("p".to_owned()),
+            components: vec![ComponentName::Data],
+            ..Args::default()
+        };
+        let error = run_in_session(args, &mut Session::default(), &mut Registry::default()).unwrap_err();
+        assert_eq!(error.inner.code(), "too_many_partitions");
+    }
+
+    #[test]
+    fn test_unknown_column_width() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let args = Args {
+            template_string: Some("CREATE TABLE result (a TEXT {{ 'x' }});".to_owned()),
+            out_dir: out_dir.path().to_owned(),
+            quiet: true,
+            format: FormatName::FixedWidth,
+            column_width: vec!["a=3".to_owned(), "b=4".to_owned()],
+            ..Args::default()
+        };
+        let error = run_in_session(args, &mut Session::default(), &mut Registry::default()).unwrap_err();
+        assert!(matches!(
+            error.inner,
+            Error::UnsupportedCliParameter {
+                kind: "fixed-width column",
+                ref value,
+            } if value == "b"
+        ));
+    }
+
+    #[test]
+    fn test_fixed_width_number_too_wide() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let args = Args {
+            template_string: Some("CREATE TABLE result (a INT {{ rownum * 100 }});".to_owned()),
+            out_dir: out_dir.path().to_owned(),
+            quiet: true,
+            format: FormatName::FixedWidth,
+            column_width: vec!["a=3".to_owned()],
+            rows_count: 10,
+            components: vec![ComponentName::Data],
+            ..Args::default()
+        };
+        let error = run_in_session(args, &mut Session::default(), &mut Registry::default()).unwrap_err();
+        let Error::Io { source, .. } = &error.inner else {
+            panic!("unexpected error {error:?}");
+        };
+        assert_eq!(source.to_string(), "number 1000 does not fit the width 3 of column a");
+    }
+
+    #[test]
+    fn test_load_statement() {
+        let columns = ["a".to_owned(), r#""b c""#.to_owned()];
+        let options = Options {
+            headers: true,
+            escape_backslash: true,
+            null_string: Cow::Borrowed(r"\N"),
+            csv_newline_escape: Some(r"\n".to_owned()),
+            ..Options::default()
+        };
+        let test_cases = [
+            (
+                Dialect::Postgres,
+                None,
+                "t.1.csv",
+                r#"\copy t (a, "b c") FROM 't.1.csv' WITH (FORMAT csv, HEADER true, DELIMITER ',', NULL '\N', ESCAPE '\', ENCODING 'UTF8')"#,
+            ),
+            (
+                Dialect::Postgres,
+                Some(CompressionName::Gzip),
+                "t.1.csv.gz",
+                r#"\copy t (a, "b c") FROM PROGRAM 'gzip -dc t.1.csv.gz' WITH (FORMAT csv, HEADER true, DELIMITER ',', NULL '\N', ESCAPE '\', ENCODING 'UTF8')"#,
+            ),
+            (
+                Dialect::Mysql,
+                None,
+                "t.1.csv",
+                r#"LOAD DATA LOCAL INFILE 't.1.csv' INTO TABLE t CHARACTER SET utf8mb4 FIELDS TERMINATED BY ',' OPTIONALLY ENCLOSED BY '"' ESCAPED BY '\\' LINES TERMINATED BY '\n' IGNORE 1 LINES (a, "b c");"#,
+            ),
+        ];
+        for (dialect, compression, file, expected) in test_cases {
+            let statement = load_statement(&options, dialect, compression, "t", &columns, Path::new(file));
+            assert_eq!(statement, expected, "{dialect:?} {compression:?}");
+        }
+
+        let options = Options {
+            null_string: Cow::Borrowed(r"\N"),
+            ..Options::default()
+        };
+        assert_eq!(
+            load_statement(&options, Dialect::Mysql, None, "t", &columns, Path::new("t.1.csv")),
+            r#"LOAD DATA LOCAL INFILE 't.1.csv' INTO TABLE t CHARACTER SET utf8mb4 FIELDS TERMINATED BY ',' OPTIONALLY ENCLOSED BY '"' ESCAPED BY '' LINES TERMINATED BY '\n' (@c1, @c2) SET a = NULLIF(@c1, '\\N'), "b c" = NULLIF(@c2, '\\N');"#,
+        );
+
+        assert!(check_load_script(FormatName::Csv, None, None, Some(r"\n"), true).is_ok());
+        assert!(check_load_script(FormatName::Csv, None, None, Some(r"\n"), false).is_err());
+        assert!(check_load_script(FormatName::Csv, None, None, Some("<br>"), true).is_err());
+        assert!(check_load_script(FormatName::Csv, Some(Dialect::Postgres), None, Some(r"\n"), true).is_err());
+    }
+
+    #[test]
+    fn test_upsert_dialect() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let args = Args {
+            template_string: Some("CREATE TABLE result (id INT {{ rownum }});".to_owned()),
+            out_dir: out_dir.path().to_owned(),
+            quiet: true,
+            dialect: Some(Dialect::Postgres),
+            upsert: Some(UpsertStyle::OnDuplicateKeyUpdate),
+            ..Args::default()
+        };
+        let error = run_in_session(args, &mut Session::default(), &mut Registry::default()).unwrap_err();
+        assert!(matches!(
+            error.inner,
+            Error::UnsupportedCliParameter {
+                kind: "upsert style of the dialect",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_insert_mode_conflicts() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let args = Args {
+            template_string: Some("CREATE TABLE result (id INT {{ rownum }});".to_owned()),
+            out_dir: out_dir.path().to_owned(),
+            quiet: true,
+            insert_mode: InsertMode::Replace,
+            upsert: Some(UpsertStyle::OnDuplicateKeyUpdate),
+            upsert_keys: vec!["id".to_owned()],
+            ..Args::default()
+        };
+        let error = run_in_session(args, &mut Session::default(), &mut Registry::default()).unwrap_err();
+        assert!(matches!(
+            error.inner,
+            Error::UnsupportedCliParameter {
+                kind: "insert mode combined with --upsert",
+                ..
+            }
+        ));
+
+        let args = Args {
+            template_string: Some("CREATE TABLE result (id INT {{ rownum }});".to_owned()),
+            out_dir: out_dir.path().to_owned(),
+            quiet: true,
+            format: FormatName::SqlInsertSet,
+            dialect: Some(Dialect::Sqlite),
+            insert_mode: InsertMode::InsertIgnore,
+            ..Args::default()
+        };
+        let error = run_in_session(args, &mut Session::default(), &mut Registry::default()).unwrap_err();
+        assert!(matches!(
+            error.inner,
+            Error::UnsupportedCliParameter {
+                kind: "insert mode of the sql-insert-set format",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_sharded_auxiliary_statement() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let args = Args {
+            template_string: Some(
+                "{{ shard into 2 tables by rownum }}
+                CREATE TABLE t (id INT {{ rownum }});
+                ALTER TABLE t ADD PRIMARY KEY (id);"
+                    .to_owned(),
+            ),
+            out_dir: out_dir.path().to_owned(),
+            quiet: true,
+            ..Args::default()
+        };
+        let error = run_in_session(args, &mut Session::default(), &mut Registry::default()).unwrap_err();
+        assert!(matches!(
+            error.inner,
+            Error::UnsupportedCliParameter {
+                kind: "auxiliary statement of a sharded table",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_column_annotation_probabilities() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let args = Args {
+            template_string: Some(
+                "CREATE TABLE result (a INT DEFAULT -1 /*#dbgen null=0.3 default=0.2 */ {{ rownum }});".to_owned(),
+            ),
+            out_dir: out_dir.path().to_owned(),
+            quiet: true,
+            rows_count: 10000,
+            components: vec![ComponentName::Data],
+            ..Args::default()
+        };
+        run_in_session(args, &mut Session::default(), &mut Registry::default()).unwrap();
+
+        let output = std::fs::read_to_string(out_dir.path().join("result.1.sql")).unwrap();
+        let (mut nulls, mut defaults, mut others) = (0, 0, 0);
+        for line in output.lines().filter(|line| line.starts_with('(')) {
+            match line.trim_end_matches([',', ';']) {
+                "(NULL)" => nulls += 1,
+                "(-1)" => defaults += 1,
+                _ => others += 1,
+            }
+        }
+        assert_eq!(nulls + defaults + others, 10000);
+        assert!((2700..=3300).contains(&nulls), "nulls = {nulls}");
+        assert!((1700..=2300).contains(&defaults), "defaults = {defaults}");
+    }
+
+    #[test]
+    fn test_template_cache_spans() {
+        let out_dir = tempfile::tempdir().unwrap();
+        let cache_dir = tempfile::tempdir().unwrap();
+        let mut descriptions = Vec::new();
+        for _ in 0..2 {
+            let args = Args {
+                template_string: Some("CREATE TABLE result (\n    a INT {{ rand.range(rownum, 1) }}\n);".to_owned()),
+                template_cache: Some(cache_dir.path().to_owned()),
+                out_dir: out_dir.path().to_owned(),
+                quiet: true,
+                components: vec![ComponentName::Data],
+                ..Args::default()
+            };
+            let mut registry = Registry::default();
+            let error = run_in_session(args, &mut Session::default(), &mut registry).unwrap_err();
+            descriptions.push(registry.describe(&error));
+        }
+        assert!(descriptions[0].contains("rand.range(rownum, 1)"), "{}", descriptions[0]);
+        assert_eq!(descriptions[0], descriptions[1]);
+    }
+
+    #[test]
+    fn test_batched_rows() {
+        // throttling writes the rows one by one, so both runs must produce the same data.
+        let mut outputs = Vec::new();
+        for rows_per_second in [None, NonZeroU32::new(u32::MAX)] {
+            let out_dir = tempfile::tempdir().unwrap();
+            let args = Args {
+                template_string: Some(
+                    "CREATE TABLE result (a INT {{ rownum }}, b INT {{ rand.range(0, 1000000) }}, c TEXT {{ 'x' }});"
+                        .to_owned(),
+                ),
+                out_dir: out_dir.path().to_owned(),
+                quiet: true,
+                rows_count: 3000,
+                rows_per_second,
+                components: vec![ComponentName::Data],
+                ..Args::default()
+            };
+            run_in_session(args, &mut Session::default(), &mut Registry::default()).unwrap();
+            outputs.push(std::fs::read_to_string(out_dir.path().join("result.1.sql")).unwrap());
+        }
+        assert_eq!(outputs[0], outputs[1]);
+    }
+
+    #[test]
+    fn test_stable_seeding_layout() {
+        // the same rows are generated regardless of the number of files and threads.
+        let mut outputs = Vec::new();
+        for (files_count, inserts_count, jobs) in [(1, 20, 1), (4, 5, 3)] {
+            let out_dir = tempfile::tempdir().unwrap();
+            let args = Args {
+                template_string: Some(
+                    "CREATE TABLE result (a INT {{ rownum }}, b INT {{ rand.range(0, 1000000) }}, c TEXT {{ rand.regex('[a-z]{8}') }});"
+                        .to_owned(),
+                ),
+                out_dir: out_dir.path().to_owned(),
+                quiet: true,
+                files_count,
+                inserts_count,
+                rows_count: 5,
+                jobs,
+                seed: Some("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa".parse().unwrap()),
+                stable_seeding: true,
+                components: vec![ComponentName::Data],
+                ..Args::default()
+            };
+            run_in_session(args, &mut Session::default(), &mut Registry::default()).unwrap();
+            let rows = (1..=files_count)
+                .flat_map(|i| {
+                    let output = std::fs::read_to_string(out_dir.path().join(format!("result.{i}.sql"))).unwrap();
+                    output
+                        .lines()
+                        .filter(|line| line.starts_with('('))
+                        .map(|line| line.trim_end_matches([',', ';']).to_owned())
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(rows.len(), 100);
+            outputs.push(rows);
+        }
+        assert_eq!(outputs[0], outputs[1]);
+    }
+
+    #[test]
+    fn test_verify_regenerate() {
+        let template = "CREATE TABLE t (a INT {{ rownum }}, b INT {{ rand.range(0, 1000000) }});";
+        let out_dir = tempfile::tempdir().unwrap();
+        let args = Args {
+            template_string: Some(template.to_owned()),
+            out_dir: out_dir.path().to_owned(),
+            quiet: true,
+            files_count: 2,
+            rows_count: 3,
+            manifest: true,
+            components: vec![ComponentName::Data],
+            ..Args::default()
+        };
+        run_in_session(args, &mut Session::default(), &mut Registry::default()).unwrap();
+
+        let verify_with = |gen_args: &[&str]| {
+            let regenerate_dir = tempfile::tempdir().unwrap();
+            let args = VerifyArgs {
+                out_dir: out_dir.path().to_owned(),
+                quiet: true,
+                regenerate_into: Some(regenerate_dir.path().to_owned()),
+                args: gen_args.iter().map(|&a| a.to_owned()).collect(),
+            };
+            verify(&args, &mut Registry::default())
+        };
+        verify_with(&[
+            "-e",
+            template,
+            "-o",
+            "elsewhere",
+            "-k",
+            "2",
+            "-r",
+            "3",
+            "--components",
+            "data",
+        ])
+        .unwrap();
+        let error = verify_with(&["-e", template, "-k", "2", "-r", "2", "--components", "data"]).unwrap_err();
+        assert_eq!(error.inner.code(), "verification_failed");
+    }
+
+    #[test]
+    fn test_invalid_duplicate_rate() {
+        for rate in [-0.5, 1.5, f64::NAN] {
+            let out_dir = tempfile::tempdir().unwrap();
+            let args = Args {
+                template_string: Some("CREATE TABLE t (a INT {{ rownum }});".to_owned()),
+                out_dir: out_dir.path().to_owned(),
+                quiet: true,
+                duplicate_rate: Some(rate),
+                ..Args::default()
+            };
+            let error = run_in_session(args, &mut Session::default(), &mut Registry::default()).unwrap_err();
+            assert_eq!(error.inner.code(), "unsupported_cli_parameter", "{rate}");
+        }
+    }
 }
@@ -0,0 +1,589 @@
+//! Translating the `CREATE TABLE` statements into the dialect of the target database.
+
+use crate::{
+    format::Dialect,
+    infer::{find_array_bounds, skip_quoted, type_parameters},
+    parser::shard_identifier,
+};
+
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, iter::Peekable, vec::IntoIter};
+
+/// The target database of the translated schema files.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "kebab-case")]
+pub enum SchemaDialect {
+    /// MySQL and MariaDB.
+    Mysql,
+    /// PostgreSQL.
+    Postgres,
+    /// SQLite.
+    Sqlite,
+    /// Microsoft SQL Server.
+    Mssql,
+    /// ClickHouse.
+    Clickhouse,
+    /// Google BigQuery.
+    Bigquery,
+}
+
+impl SchemaDialect {
+    /// The SQL dialect whose quotation marks are used for the identifiers.
+    fn quoting(self) -> Dialect {
+        match self {
+            Self::Mysql | Self::Clickhouse | Self::Bigquery => Dialect::Mysql,
+            Self::Postgres => Dialect::Postgres,
+            Self::Sqlite => Dialect::Sqlite,
+            Self::Mssql => Dialect::Mssql,
+        }
+    }
+
+    /// The name of the target database.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Mysql => "MySQL",
+            Self::Postgres => "PostgreSQL",
+            Self::Sqlite => "SQLite",
+            Self::Mssql => "SQL Server",
+            Self::Clickhouse => "ClickHouse",
+            Self::Bigquery => "BigQuery",
+        }
+    }
+}
+
+/// The database-independent type of a column, recognized from the column type in the template.
+///
+/// This is the single place where SQL type names and their aliases are understood. The schema
+/// translation, the inferred expressions, the fixed-width columns, the Arrow schema, the `CAST`
+/// function and the value checks all classify the column types through this.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ColumnType {
+    /// An integer of the given size in bytes.
+    Integer { bytes: u8, unsigned: bool },
+    /// A floating-point number, either single or double precision.
+    Float { single: bool },
+    /// A fixed-point decimal number, with the precision and scale if specified.
+    Decimal(Option<(usize, usize)>),
+    /// A boolean.
+    Boolean,
+    /// A fixed-length string, with the length if specified.
+    Char(Option<usize>),
+    /// A variable-length string, with the maximum length if specified.
+    Varchar(Option<usize>),
+    /// A fixed-length binary string, with the length if specified.
+    Binary(Option<usize>),
+    /// A variable-length binary string, with the maximum length if specified.
+    Varbinary(Option<usize>),
+    /// A timestamp, with or without the time zone.
+    Timestamp { with_time_zone: bool },
+    /// A date.
+    Date,
+    /// A time of day.
+    Time,
+    /// A time interval.
+    Interval,
+    /// A JSON document.
+    Json,
+    /// A UUID.
+    Uuid,
+    /// An array of the element type.
+    Array(Box<ColumnType>),
+    /// A type which is not recognized.
+    Unknown,
+}
+
+impl ColumnType {
+    /// Recognizes the data type like `character varying(20)` or `int unsigned`. The type may be
+    /// followed by column constraints, e.g. `int unsigned not null`.
+    pub(crate) fn parse(data_type: &str) -> Self {
+        let lowered = data_type.trim().to_ascii_lowercase();
+        if let Some(bounds) = find_array_bounds(&lowered) {
+            return Self::Array(Box::new(Self::parse(&lowered[..bounds])));
+        }
+        let words = lowered
+            .split(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .filter(|w| !w.is_empty())
+            .collect::<Vec<_>>();
+        let has_words = |phrase: &[&str]| words.windows(phrase.len()).any(|w| w == phrase);
+        let unsigned = words.contains(&"unsigned");
+        let params = type_parameters(&lowered);
+        let length = params.first().copied();
+        let integer = |bytes| Self::Integer { bytes, unsigned };
+
+        match words.first().copied().unwrap_or_default() {
+            "tinyint" | "int1" => integer(1),
+            "smallint" | "int2" | "smallserial" | "serial2" => integer(2),
+            "mediumint" | "int3" => integer(3),
+            "int" | "integer" | "int4" | "serial" | "serial4" => integer(4),
+            "bigint" | "int8" | "bigserial" | "serial8" | "int64" | "signed" | "unsigned" => integer(8),
+            "real" | "float4" | "float32" => Self::Float { single: true },
+            "float" => Self::Float {
+                single: length.map_or(false, |bits| bits <= 24),
+            },
+            "double" | "float8" | "float64" => Self::Float { single: false },
+            "decimal" | "numeric" | "dec" | "number" | "bignumeric" => {
+                Self::Decimal(length.map(|precision| (precision, params.get(1).copied().unwrap_or(0))))
+            }
+            "bool" | "boolean" => Self::Boolean,
+            "bit" if length.map_or(true, |bits| bits == 1) => Self::Boolean,
+            "char" | "character" | "nchar" if words.get(1) == Some(&"varying") => Self::Varchar(length),
+            "char" | "character" | "nchar" => Self::Char(length),
+            "varchar" | "nvarchar" | "varchar2" | "nvarchar2" => Self::Varchar(length),
+            "text" | "tinytext" | "mediumtext" | "longtext" | "ntext" | "clob" | "string" => Self::Varchar(None),
+            "binary" => Self::Binary(length),
+            "varbinary" | "blob" | "tinyblob" | "mediumblob" | "longblob" | "bytea" | "bytes" | "image" => {
+                Self::Varbinary(length)
+            }
+            "timestamptz" | "datetimeoffset" => Self::Timestamp { with_time_zone: true },
+            "timestamp" | "datetime" | "datetime2" => Self::Timestamp {
+                with_time_zone: has_words(&["with", "time", "zone"]),
+            },
+            "date" => Self::Date,
+            "time" | "timetz" => Self::Time,
+            "interval" => Self::Interval,
+            "json" | "jsonb" => Self::Json,
+            "uuid" | "uniqueidentifier" => Self::Uuid,
+            _ => Self::Unknown,
+        }
+    }
+
+    /// Writes the name of this type in the dialect.
+    fn write_name(&self, res: &mut String, dialect: SchemaDialect) {
+        use SchemaDialect::{Bigquery, Clickhouse, Mssql, Mysql, Postgres, Sqlite};
+
+        let name = match (self, dialect) {
+            (Self::Integer { bytes, unsigned }, Mysql) => {
+                res.push_str(match bytes {
+                    1 => "TINYINT",
+                    2 => "SMALLINT",
+                    3 => "MEDIUMINT",
+                    4 => "INT",
+                    _ => "BIGINT",
+                });
+                if *unsigned {
+                    res.push_str(" UNSIGNED");
+                }
+                return;
+            }
+            (Self::Integer { bytes, unsigned }, Clickhouse) => {
+                let bits = match bytes {
+                    1 => 8,
+                    2 => 16,
+                    3 | 4 => 32,
+                    _ => 64,
+                };
+                write!(res, "{}Int{bits}", if *unsigned { "U" } else { "" }).unwrap();
+                return;
+            }
+            (Self::Integer { .. }, Sqlite) => "INTEGER",
+            (&Self::Integer { bytes: 1, unsigned }, Mssql) if unsigned => "TINYINT",
+            (&Self::Integer { bytes: 8, unsigned }, Bigquery) if unsigned => "NUMERIC",
+            (Self::Integer { .. }, Bigquery) => "INT64",
+            (&Self::Integer { bytes, unsigned }, Postgres | Mssql) => {
+                // choose the smallest signed type containing the whole range.
+                match 8 * u32::from(bytes) + u32::from(unsigned) {
+                    0..=16 => "SMALLINT",
+                    17..=32 if dialect == Postgres => "INTEGER",
+                    17..=32 => "INT",
+                    33..=64 => "BIGINT",
+                    _ if dialect == Postgres => "NUMERIC(20)",
+                    _ => "DECIMAL(20)",
+                }
+            }
+
+            (Self::Float { single: true }, Mysql) => "FLOAT",
+            (Self::Float { single: true }, Postgres | Sqlite | Mssql) => "REAL",
+            (Self::Float { single: true }, Clickhouse) => "Float32",
+            (Self::Float { single: false }, Mysql) => "DOUBLE",
+            (Self::Float { single: false }, Postgres) => "DOUBLE PRECISION",
+            (Self::Float { single: false }, Sqlite) => "REAL",
+            (Self::Float { single: false }, Mssql) => "FLOAT",
+            (Self::Float { single: false }, Clickhouse) => "Float64",
+            (Self::Float { .. }, Bigquery) => "FLOAT64",
+
+            (Self::Decimal(Some((precision, scale))), _) => {
+                let name = match dialect {
+                    Mysql | Mssql => "DECIMAL",
+                    Postgres | Sqlite => "NUMERIC",
+                    Clickhouse => "Decimal",
+                    Bigquery if *precision - scale <= 29 && *scale <= 9 => "NUMERIC",
+                    Bigquery => "BIGNUMERIC",
+                };
+                write!(res, "{name}({precision}, {scale})").unwrap();
+                return;
+            }
+            (Self::Decimal(None), Mysql) => "DECIMAL(65, 30)",
+            (Self::Decimal(None), Postgres | Sqlite) => "NUMERIC",
+            (Self::Decimal(None), Mssql) => "DECIMAL(38, 10)",
+            (Self::Decimal(None), Clickhouse) => "Decimal(38, 10)",
+            (Self::Decimal(None), Bigquery) => "BIGNUMERIC",
+
+            (Self::Boolean, Mysql | Postgres | Sqlite) => "BOOLEAN",
+            (Self::Boolean, Mssql) => "BIT",
+            (Self::Boolean, Clickhouse) => "Bool",
+            (Self::Boolean, Bigquery) => "BOOL",
+
+            (Self::Char(length), Mysql | Postgres | Mssql) => {
+                let prefix = if dialect == Mssql { "N" } else { "" };
+                write!(res, "{prefix}CHAR({})", length.unwrap_or(1)).unwrap();
+                return;
+            }
+            (Self::Varchar(Some(length)), Mysql | Postgres | Mssql) => {
+                let prefix = if dialect == Mssql { "N" } else { "" };
+                write!(res, "{prefix}VARCHAR({length})").unwrap();
+                return;
+            }
+            (Self::Char(_) | Self::Varchar(_), Mysql | Postgres | Sqlite) => "TEXT",
+            (Self::Char(_) | Self::Varchar(_), Mssql) => "NVARCHAR(MAX)",
+            (Self::Char(_) | Self::Varchar(_), Clickhouse) => "String",
+            (Self::Char(_) | Self::Varchar(_), Bigquery) => "STRING",
+
+            (Self::Binary(_) | Self::Varbinary(_), Mysql | Sqlite) => "BLOB",
+            (Self::Binary(_) | Self::Varbinary(_), Postgres) => "BYTEA",
+            (Self::Binary(_) | Self::Varbinary(_), Mssql) => "VARBINARY(MAX)",
+            (Self::Binary(_) | Self::Varbinary(_), Clickhouse) => "String",
+            (Self::Binary(_) | Self::Varbinary(_), Bigquery) => "BYTES",
+
+            (Self::Timestamp { .. }, Mysql) => "DATETIME(6)",
+            (Self::Timestamp { with_time_zone: false }, Postgres) => "TIMESTAMP",
+            (Self::Timestamp { with_time_zone: true }, Postgres) => "TIMESTAMPTZ",
+            (Self::Timestamp { with_time_zone: false }, Mssql) => "DATETIME2",
+            (Self::Timestamp { with_time_zone: true }, Mssql) => "DATETIMEOFFSET",
+            (Self::Timestamp { .. }, Clickhouse) => "DateTime64(6)",
+            (Self::Timestamp { with_time_zone: false }, Bigquery) => "DATETIME",
+            (Self::Timestamp { with_time_zone: true }, Bigquery) => "TIMESTAMP",
+
+            (Self::Date, Clickhouse) => "Date32",
+            (Self::Date, _) if dialect != Sqlite => "DATE",
+            (Self::Time, Mysql) => "TIME(6)",
+            (Self::Time, Postgres | Mssql | Bigquery) => "TIME",
+            (Self::Interval, Postgres | Bigquery) => "INTERVAL",
+            (Self::Json, Mysql | Postgres | Bigquery) => "JSON",
+            (Self::Uuid, Mysql) => "CHAR(36)",
+            (Self::Uuid, Postgres | Clickhouse) => "UUID",
+            (Self::Uuid, Mssql) => "UNIQUEIDENTIFIER",
+            (Self::Uuid, Bigquery) => "STRING",
+
+            (Self::Array(element), Postgres) => {
+                element.write_name(res, dialect);
+                res.push_str("[]");
+                return;
+            }
+            (Self::Array(element), Clickhouse) => {
+                res.push_str("Array(");
+                element.write_name(res, dialect);
+                res.push(')');
+                return;
+            }
+            (Self::Array(element), Bigquery) => {
+                res.push_str("ARRAY<");
+                element.write_name(res, dialect);
+                res.push('>');
+                return;
+            }
+            (Self::Array(_), Mysql) => "JSON",
+
+            // the remaining types are not supported natively, and are stored as strings.
+            (_, Mysql | Postgres | Sqlite) => "TEXT",
+            (_, Mssql) => "NVARCHAR(MAX)",
+            (_, Clickhouse) => "String",
+            (_, Bigquery) => "STRING",
+        };
+        res.push_str(name);
+    }
+}
+
+/// Splits the statement into identifiers, keywords and punctuations. Returns `None` if the
+/// statement contains anything else, e.g. string literals or operators.
+fn tokenize(statement: &str) -> Option<Vec<&str>> {
+    let bytes = statement.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let start = i;
+        match bytes[i] {
+            c if c.is_ascii_whitespace() => {
+                i += 1;
+                continue;
+            }
+            b'"' | b'`' => i = skip_quoted(statement, i, bytes[i]),
+            b'[' => i = skip_quoted(statement, i, b']'),
+            b'(' | b')' | b',' | b'.' | b';' => i += 1,
+            c if c.is_ascii_alphanumeric() || c == b'_' => {
+                i += statement[i..]
+                    .find(|c: char| !c.is_ascii_alphanumeric() && c != '_' && c != '$')
+                    .unwrap_or(statement.len() - i);
+            }
+            _ => return None,
+        }
+        tokens.push(&statement[start..i]);
+    }
+    Some(tokens)
+}
+
+/// Skips the next token if it is the keyword.
+fn expect_keyword(tokens: &mut Peekable<IntoIter<&str>>, keyword: &str) -> bool {
+    tokens.next_if(|t| t.eq_ignore_ascii_case(keyword)).is_some()
+}
+
+/// A plain index like `CREATE UNIQUE INDEX i ON t (a, b DESC)`.
+struct CreateIndex<'a> {
+    unique: bool,
+    if_not_exists: bool,
+    name: &'a str,
+    /// The parts of the qualified table name.
+    table: Vec<&'a str>,
+    /// The column names and their orders (`ASC` or `DESC`).
+    columns: Vec<(&'a str, Option<&'a str>)>,
+}
+
+impl<'a> CreateIndex<'a> {
+    /// Parses a `CREATE INDEX` statement. Returns `None` if the statement is not a plain index.
+    fn parse(statement: &'a str) -> Option<Self> {
+        let tokens = tokenize(statement)?;
+        let mut tokens = tokens.into_iter().peekable();
+
+        if !expect_keyword(&mut tokens, "create") {
+            return None;
+        }
+        let unique = expect_keyword(&mut tokens, "unique");
+        if !expect_keyword(&mut tokens, "index") {
+            return None;
+        }
+        let if_not_exists = expect_keyword(&mut tokens, "if");
+        if if_not_exists && !(expect_keyword(&mut tokens, "not") && expect_keyword(&mut tokens, "exists")) {
+            return None;
+        }
+        let name = tokens.next()?;
+        if !expect_keyword(&mut tokens, "on") {
+            return None;
+        }
+        let mut table = vec![tokens.next()?];
+        while tokens.next_if_eq(&".").is_some() {
+            table.push(tokens.next()?);
+        }
+        if tokens.next() != Some("(") {
+            return None;
+        }
+        let mut columns = Vec::new();
+        loop {
+            let column = tokens.next()?;
+            if matches!(column, "(" | ")" | "," | "." | ";") {
+                return None;
+            }
+            let order = tokens.next_if(|t| t.eq_ignore_ascii_case("asc") || t.eq_ignore_ascii_case("desc"));
+            columns.push((column, order));
+            match tokens.next()? {
+                "," => {}
+                ")" => break,
+                _ => return None,
+            }
+        }
+        match (tokens.next(), tokens.next()) {
+            (None | Some(";"), None) => Some(Self {
+                unique,
+                if_not_exists,
+                name,
+                table,
+                columns,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Writes the statement, passing every identifier through `requote`.
+    fn write(&self, if_not_exists: bool, requote: impl Fn(&str) -> String) -> String {
+        let mut res = String::from("CREATE ");
+        if self.unique {
+            res.push_str("UNIQUE ");
+        }
+        res.push_str("INDEX ");
+        if if_not_exists {
+            res.push_str("IF NOT EXISTS ");
+        }
+        res.push_str(&requote(self.name));
+        res.push_str(" ON ");
+        for (i, part) in self.table.iter().enumerate() {
+            if i != 0 {
+                res.push('.');
+            }
+            res.push_str(&requote(part));
+        }
+        res.push_str(" (");
+        for (i, (column, order)) in self.columns.iter().enumerate() {
+            if i != 0 {
+                res.push_str(", ");
+            }
+            res.push_str(&requote(column));
+            if let Some(order) = order {
+                res.push(' ');
+                res.push_str(&order.to_ascii_uppercase());
+            }
+        }
+        res.push_str(");");
+        res
+    }
+}
+
+/// Translates an auxiliary statement written after the `CREATE TABLE` statement into the
+/// dialect.
+///
+/// Only plain indices like `CREATE UNIQUE INDEX i ON t (a, b DESC)` are translated, by re-quoting
+/// the identifiers. Returns `None` for any other statement, or if the dialect does not support
+/// `CREATE INDEX` (ClickHouse and BigQuery).
+pub fn translate_auxiliary_statement(dialect: SchemaDialect, statement: &str) -> Option<String> {
+    if matches!(dialect, SchemaDialect::Clickhouse | SchemaDialect::Bigquery) {
+        return None;
+    }
+    let index = CreateIndex::parse(statement)?;
+    let quoting = dialect.quoting();
+    // MySQL and SQL Server do not support `CREATE INDEX IF NOT EXISTS`.
+    let if_not_exists = index.if_not_exists && matches!(dialect, SchemaDialect::Postgres | SchemaDialect::Sqlite);
+    Some(index.write(if_not_exists, |ident| quoting.requote(ident)))
+}
+
+/// Rewrites an auxiliary statement written after the `CREATE TABLE` statement of a sharded table
+/// to apply to the `shard`-th shard, by appending `_shard` to the names of the index and the table.
+///
+/// Only plain indices like `CREATE UNIQUE INDEX i ON t (a, b DESC)` can be rewritten. Returns
+/// `None` for any other statement.
+pub fn shard_auxiliary_statement(statement: &str, shard: usize) -> Option<String> {
+    let mut index = CreateIndex::parse(statement)?;
+    let name = shard_identifier(index.name, shard);
+    let table_name = shard_identifier(index.table.last()?, shard);
+    index.name = &name;
+    *index.table.last_mut()? = &table_name;
+    Some(index.write(index.if_not_exists, str::to_owned))
+}
+
+/// Translates a table into a `CREATE TABLE` statement in the dialect.
+///
+/// Each column is given as the name, the data type and whether it is nullable. Constraints,
+/// defaults and table options written in the template are dropped, since they are generally not
+/// portable.
+pub fn create_table<'a>(
+    dialect: SchemaDialect,
+    table_name: &str,
+    columns: impl IntoIterator<Item = (&'a str, Option<&'a str>, bool)>,
+) -> String {
+    let quoting = dialect.quoting();
+    let mut res = format!("CREATE TABLE {} (", quoting.requote(table_name));
+    for (i, (name, data_type, is_nullable)) in columns.into_iter().enumerate() {
+        if i > 0 {
+            res.push(',');
+        }
+        res.push_str("\n    ");
+        if name.is_empty() {
+            write!(res, "column_{}", i + 1).unwrap();
+        } else {
+            res.push_str(&quoting.requote(name));
+        }
+        res.push(' ');
+
+        let column_type = ColumnType::parse(data_type.unwrap_or_default());
+        let is_array = matches!(column_type, ColumnType::Array(_));
+        if dialect == SchemaDialect::Clickhouse && is_nullable && !is_array {
+            res.push_str("Nullable(");
+            column_type.write_name(&mut res, dialect);
+            res.push(')');
+        } else {
+            column_type.write_name(&mut res, dialect);
+            // BigQuery arrays are never NULL, and cannot be declared NOT NULL either.
+            let can_be_not_null = !(dialect == SchemaDialect::Bigquery && is_array);
+            if !is_nullable && dialect != SchemaDialect::Clickhouse && can_be_not_null {
+                res.push_str(" NOT NULL");
+            }
+        }
+    }
+    res.push_str("\n)");
+    if dialect == SchemaDialect::Clickhouse {
+        res.push_str(" ENGINE = MergeTree ORDER BY tuple()");
+    }
+    res.push_str(";\n");
+    res
+}
+
+#[test]
+fn test_column_type() {
+    let test_cases = [
+        ("int unsigned", SchemaDialect::Postgres, "BIGINT"),
+        ("INT", SchemaDialect::Clickhouse, "Int32"),
+        ("tinyint unsigned", SchemaDialect::Mssql, "TINYINT"),
+        ("bigint unsigned", SchemaDialect::Bigquery, "NUMERIC"),
+        ("character varying(20)", SchemaDialect::Mssql, "NVARCHAR(20)"),
+        ("decimal(10, 2)", SchemaDialect::Bigquery, "NUMERIC(10, 2)"),
+        ("decimal(40, 20)", SchemaDialect::Bigquery, "BIGNUMERIC(40, 20)"),
+        ("timestamp with time zone", SchemaDialect::Bigquery, "TIMESTAMP"),
+        ("datetime", SchemaDialect::Postgres, "TIMESTAMP"),
+        ("integer[]", SchemaDialect::Clickhouse, "Array(Int32)"),
+        ("text[]", SchemaDialect::Bigquery, "ARRAY<STRING>"),
+        ("geometry", SchemaDialect::Mysql, "TEXT"),
+        ("int1", SchemaDialect::Mysql, "TINYINT"),
+        ("serial8 not null", SchemaDialect::Postgres, "BIGINT"),
+        ("signed", SchemaDialect::Clickhouse, "Int64"),
+        ("binary(16)", SchemaDialect::Bigquery, "BYTES"),
+        ("char", SchemaDialect::Mssql, "NCHAR(1)"),
+    ];
+    for (ty, dialect, expected) in test_cases {
+        let mut actual = String::new();
+        ColumnType::parse(ty).write_name(&mut actual, dialect);
+        assert_eq!(actual, expected, "type = {ty}, dialect = {dialect:?}");
+    }
+}
+
+#[test]
+fn test_translate_auxiliary_statement() {
+    let test_cases = [
+        (
+            "CREATE INDEX result_name ON result (`name`);",
+            SchemaDialect::Postgres,
+            Some("CREATE INDEX result_name ON result (\"name\");"),
+        ),
+        (
+            "create unique index if not exists \"i\" on s.t (a desc, \"b\")",
+            SchemaDialect::Mysql,
+            Some("CREATE UNIQUE INDEX `i` ON s.t (a DESC, `b`);"),
+        ),
+        (
+            "CREATE INDEX i ON t (a)",
+            SchemaDialect::Sqlite,
+            Some("CREATE INDEX i ON t (a);"),
+        ),
+        ("CREATE INDEX i ON t (a)", SchemaDialect::Clickhouse, None),
+        ("CREATE INDEX i ON t USING gin (a)", SchemaDialect::Postgres, None),
+        ("CREATE INDEX i ON t (lower(a))", SchemaDialect::Postgres, None),
+        ("ALTER TABLE t ADD COLUMN c INT;", SchemaDialect::Postgres, None),
+        ("COMMENT ON TABLE t IS 'x';", SchemaDialect::Postgres, None),
+    ];
+    for (statement, dialect, expected) in test_cases {
+        assert_eq!(
+            translate_auxiliary_statement(dialect, statement).as_deref(),
+            expected,
+            "statement = {statement}, dialect = {dialect:?}"
+        );
+    }
+}
+
+#[test]
+fn test_shard_auxiliary_statement() {
+    let test_cases = [
+        (
+            "CREATE INDEX orders_customer ON orders (customer_id);",
+            Some("CREATE INDEX orders_customer_1 ON orders_1 (customer_id);"),
+        ),
+        (
+            "create unique index if not exists \"i\" on s.\"t\" (a desc, `b`)",
+            Some("CREATE UNIQUE INDEX IF NOT EXISTS \"i_1\" ON s.\"t_1\" (a DESC, `b`);"),
+        ),
+        ("CREATE INDEX i ON t (lower(a))", None),
+        ("ALTER TABLE t ADD COLUMN c INT;", None),
+    ];
+    for (statement, expected) in test_cases {
+        assert_eq!(
+            shard_auxiliary_statement(statement, 1).as_deref(),
+            expected,
+            "{statement}"
+        );
+    }
+}
@@ -2,7 +2,7 @@
 
 #![allow(clippy::used_underscore_binding)]
 
-use crate::{parser::Rule, span::S};
+use crate::{parser::Rule, span::S, warning::Warning};
 use std::{convert::Infallible, fmt, path::PathBuf};
 use thiserror::Error as ThisError;
 
@@ -18,6 +18,13 @@ pub enum Error {
     #[error("unknown function")]
     UnknownFunction,
 
+    /// The template defines a function with the name of an existing function.
+    #[error("function {0}() is already defined")]
+    FunctionAlreadyDefined(
+        /// The function name.
+        String,
+    ),
+
     /// Integer is too big.
     #[error("integer '{0}' is too big")]
     IntegerOverflow(
@@ -66,6 +73,15 @@ pub enum Error {
         parent: String,
     },
 
+    /// Cannot find the column to override.
+    #[error("cannot find column {column} in table {table} to override")]
+    UnknownOverrideColumn {
+        /// The table name.
+        table: String,
+        /// The column name.
+        column: String,
+    },
+
     /// Derived table name does not match that of the derived table directive.
     #[error("derived table name in the FOR EACH ROW and CREATE TABLE statements do not match ({for_each_row} vs {create_table})")]
     DerivedTableNameMismatch {
@@ -75,6 +91,36 @@ pub enum Error {
         create_table: String,
     },
 
+    /// The `{{ version N adds column }}` or `{{ version N drops column }}` directive is invalid.
+    #[error("invalid version directive: {0}")]
+    InvalidVersionDirective(
+        /// Cause of the error.
+        &'static str,
+    ),
+
+    /// The `{{ shard into N tables by … }}` directive is invalid.
+    #[error("invalid shard directive: {0}")]
+    InvalidShardDirective(
+        /// Cause of the error.
+        &'static str,
+    ),
+
+    /// The `/*#dbgen … */` column annotation is invalid.
+    #[error("invalid column annotation: {0}")]
+    InvalidColumnAnnotation(
+        /// Cause of the error.
+        String,
+    ),
+
+    /// All columns of a table are dropped in a schema version.
+    #[error("table {table} has no columns in schema version {version}")]
+    NoColumnsInVersion {
+        /// The table name.
+        table: String,
+        /// The schema version.
+        version: u32,
+    },
+
     /// Unexpected value type.
     #[error("cannot convert {value} into {expected}")]
     UnexpectedValueType {
@@ -109,6 +155,48 @@ pub enum Error {
     #[error("failed to configure thread pool")]
     Rayon(#[from] rayon::ThreadPoolBuildError),
 
+    /// Failed to install the handler of termination signals.
+    #[cfg(feature = "cli")]
+    #[error("failed to install signal handler")]
+    SignalHandler(#[from] ctrlc::Error),
+
+    /// Failed to load a plugin.
+    #[cfg(feature = "cli")]
+    #[error("failed to load plugin at {path}")]
+    LoadPlugin {
+        /// File path of the plugin.
+        path: PathBuf,
+        /// Source of error.
+        source: libloading::Error,
+    },
+
+    /// Invalid `dbschemagen` profile.
+    #[cfg(feature = "cli")]
+    #[error("failed to parse profile at {path}")]
+    InvalidProfile {
+        /// File path of the profile.
+        path: PathBuf,
+        /// Source of error.
+        source: serde_json::Error,
+    },
+
+    /// Some data files do not match their manifests.
+    #[cfg(feature = "cli")]
+    #[error("{failed} of {total} data files failed verification")]
+    VerificationFailed {
+        /// Number of data files which are missing or whose content differs from the manifest.
+        failed: usize,
+        /// Number of manifests checked.
+        total: usize,
+    },
+
+    /// A generated value violates the column type or constraints.
+    #[error("{0}")]
+    ConstraintViolation(
+        /// Description of the violation.
+        Warning,
+    ),
+
     /// Cannot use `--table-name` when template contains multiple tables.
     #[error("cannot use --table-name when template contains multiple tables")]
     CannotUseTableNameForMultipleTables,
@@ -122,6 +210,52 @@ pub enum Error {
         value: String,
     },
 
+    /// Invalid `PARTITION BY` clause.
+    #[error("invalid partition definition of table {table}: {message}")]
+    InvalidPartition {
+        /// The table name.
+        table: String,
+        /// Cause of the error.
+        message: String,
+    },
+
+    /// A table has more distinct partitions in one data file than the writer keeps open.
+    #[error("table {table} has more than {limit} partitions in one file, consider partitioning by a coarser column")]
+    TooManyPartitions {
+        /// The table name.
+        table: String,
+        /// Maximum number of partitions of a table written by one data file.
+        limit: usize,
+    },
+
+    /// A table has no `PRIMARY KEY` column to identify the rows of change events.
+    #[error("table {table} has no PRIMARY KEY column identifying the rows of change events")]
+    NoPrimaryKey {
+        /// The table name.
+        table: String,
+    },
+
+    /// A sharded table is written by an environment which does not route the rows into shards.
+    #[error("cannot write the sharded table {table} without routing its rows into the shards")]
+    ShardsNotRouted {
+        /// The table name.
+        table: String,
+    },
+
+    /// Cannot generate a tuple different from all previous ones.
+    #[error("cannot generate a unique tuple after {attempts} attempts")]
+    UniqueTupleExhausted {
+        /// Number of tuples generated and rejected.
+        attempts: u32,
+    },
+
+    /// The template check found suspicious constructs.
+    #[error("found {count} suspicious construct(s) in the template")]
+    LintFailed {
+        /// Number of suspicious constructs found.
+        count: usize,
+    },
+
     /// Forced panic.
     #[error("runtime panic: {message}")]
     Panic {
@@ -133,6 +267,55 @@ pub enum Error {
 // ensure the size of error is ≤56 bytes
 const _: usize = 56 - std::mem::size_of::<Error>();
 
+impl Error {
+    /// Returns a stable identifier of the kind of error, e.g. `"unknown_function"`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::ParseTemplate(..) => "parse_template",
+            Self::UnknownFunction => "unknown_function",
+            Self::FunctionAlreadyDefined(..) => "function_already_defined",
+            Self::IntegerOverflow(..) => "integer_overflow",
+            Self::NotEnoughArguments => "not_enough_arguments",
+            Self::InvalidRegex(..) => "invalid_regex",
+            Self::UnknownRegexFlag(..) => "unknown_regex_flag",
+            Self::DecodeError(..) => "decode_error",
+            Self::InvalidArguments(..) => "invalid_arguments",
+            Self::InvalidTimestampString(..) => "invalid_timestamp_string",
+            Self::InvalidOrAmbiguousLocalTime => "invalid_or_ambiguous_local_time",
+            Self::UnknownParentTable { .. } => "unknown_parent_table",
+            Self::UnknownOverrideColumn { .. } => "unknown_override_column",
+            Self::DerivedTableNameMismatch { .. } => "derived_table_name_mismatch",
+            Self::InvalidVersionDirective(..) => "invalid_version_directive",
+            Self::InvalidShardDirective(..) => "invalid_shard_directive",
+            Self::InvalidColumnAnnotation(..) => "invalid_column_annotation",
+            Self::NoColumnsInVersion { .. } => "no_columns_in_version",
+            Self::UnexpectedValueType { .. } => "unexpected_value_type",
+            Self::Io { .. } => "io",
+            Self::InvalidTimeZone { .. } => "invalid_time_zone",
+            #[cfg(feature = "cli")]
+            Self::Rayon(..) => "rayon",
+            #[cfg(feature = "cli")]
+            Self::SignalHandler(..) => "signal_handler",
+            #[cfg(feature = "cli")]
+            Self::LoadPlugin { .. } => "load_plugin",
+            #[cfg(feature = "cli")]
+            Self::InvalidProfile { .. } => "invalid_profile",
+            #[cfg(feature = "cli")]
+            Self::VerificationFailed { .. } => "verification_failed",
+            Self::ConstraintViolation(..) => "constraint_violation",
+            Self::CannotUseTableNameForMultipleTables => "cannot_use_table_name_for_multiple_tables",
+            Self::UnsupportedCliParameter { .. } => "unsupported_cli_parameter",
+            Self::InvalidPartition { .. } => "invalid_partition",
+            Self::TooManyPartitions { .. } => "too_many_partitions",
+            Self::NoPrimaryKey { .. } => "no_primary_key",
+            Self::ShardsNotRouted { .. } => "shards_not_routed",
+            Self::UniqueTupleExhausted { .. } => "unique_tuple_exhausted",
+            Self::LintFailed { .. } => "lint_failed",
+            Self::Panic { .. } => "panic",
+        }
+    }
+}
+
 impl fmt::Display for S<Error> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.inner.fmt(f)
@@ -1,18 +1,41 @@
 //! Evaluating compiled expressions into values.
 
 use crate::{
-    array::{Array, Permutation},
+    array::{Array, BlockPermutation, Permutation},
     error::Error,
-    functions::{Arguments, Function},
+    explain,
+    format::unquote,
+    functions::{
+        dirty::{Corruption, SWAP_NAMESPACE},
+        lookup::LookupTable,
+        rand::{Hierarchy, MarkovChain, PerRowDistribution},
+        Arguments, Function,
+    },
     parser::{Expr, QName},
+    partition::PartitionFilter,
     span::{ResultExt, Span, SpanExt, S},
     value::Value,
 };
 use chrono::{DateTime, NaiveDateTime, Utc};
-use rand::{distributions::Bernoulli, Rng, RngCore};
-use rand_distr::{LogNormal, Uniform};
+use rand::{distributions::Bernoulli, Rng, RngCore, SeedableRng};
+use rand_distr::{
+    weighted_alias::WeightedAliasIndex, Binomial, Exp, Gamma, Geometric, LogNormal, Normal, Pareto, Poisson,
+    StandardNormal, Uniform,
+};
+use rand_hc::Hc128Rng;
 use rand_regex::EncodedString;
-use std::{cmp::Ordering, fmt, fs, ops::Range, path::PathBuf, sync::Arc};
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+    fmt, fs, mem,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering as AtomicOrdering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
 use tzfile::{ArcTz, Tz};
 use zipf::ZipfDistribution;
 
@@ -25,8 +48,78 @@ pub struct CompileContext {
     pub time_zone: ArcTz,
     /// The current timestamp in UTC.
     pub current_timestamp: NaiveDateTime,
+    /// The value of `scale_factor`.
+    pub scale_factor: f64,
     /// The global variables.
     pub variables: Box<[Value]>,
+    /// The seed from which the namespaced random number generators are derived.
+    pub namespace_seed: [u8; 32],
+    /// Whether the random number generator is reseeded from the namespace seed and `rownum` at
+    /// the start of every row.
+    pub stable_seeding: bool,
+    /// The microseconds advanced by `clock.tick()` per row of the main tables, computed by
+    /// [`clock_stride()`]. The clock of a state starting at a later row is offset by this per row.
+    pub clock_stride: i64,
+    /// The columns sampled by `ref()`, registered during compilation.
+    references: Arc<Mutex<Vec<Reference>>>,
+    /// The recently compiled `rand.regex` generators, with the most recently used at the end.
+    regex_cache: Arc<Mutex<Vec<(RegexKey, Arc<rand_regex::Regex>)>>>,
+    /// The permutations shared by name among all tables, and their number of elements.
+    named_permutations: Arc<Mutex<HashMap<String, (Arc<Permutation>, u64)>>>,
+    /// The Markov chains of `rand.markov` built so far, by the path of their corpus.
+    markov_chains: Arc<Mutex<HashMap<PathBuf, Arc<MarkovChain>>>>,
+    /// The number of `unique.tuple()` calls compiled so far, used to identify each call.
+    unique_tuples_count: Arc<AtomicUsize>,
+}
+
+/// Maximum number of compiled generators kept in the `rand.regex` cache.
+const REGEX_CACHE_CAPACITY: usize = 64;
+
+/// The arguments of `rand.regex` identifying a compiled generator: (pattern, flags, max repeat).
+pub(crate) type RegexKey = (String, String, u32);
+
+/// A column of a table whose generated values are sampled by `ref()`.
+#[derive(Clone, Debug)]
+struct Reference {
+    /// The table name.
+    table: String,
+    /// The column name.
+    column: String,
+    /// Maximum number of values kept for sampling.
+    capacity: usize,
+}
+
+/// A bounded random sample of the values generated for a column.
+#[derive(Debug)]
+struct Reservoir {
+    /// The sampled column.
+    reference: Reference,
+    /// Indices of the table and column, once resolved.
+    location: Option<(usize, usize)>,
+    /// The sampled values.
+    values: Vec<Value>,
+    /// Number of values generated for the column so far.
+    seen: u64,
+}
+
+impl Reservoir {
+    /// Offers a newly generated value to the reservoir.
+    ///
+    /// This uses [Algorithm R] with a generator seeded by the count, so that the main random
+    /// number generator is not disturbed by tables being referenced.
+    ///
+    /// [Algorithm R]: https://en.wikipedia.org/wiki/Reservoir_sampling#Simple:_Algorithm_R
+    fn insert(&mut self, value: &Value) {
+        self.seen += 1;
+        if self.values.len() < self.reference.capacity {
+            self.values.push(value.clone());
+        } else {
+            let index = fastrand::Rng::with_seed(self.seen).u64(..self.seen);
+            if let Some(slot) = usize::try_from(index).ok().and_then(|i| self.values.get_mut(i)) {
+                *slot = value.clone();
+            }
+        }
+    }
 }
 
 impl CompileContext {
@@ -36,7 +129,131 @@ impl CompileContext {
             zoneinfo: PathBuf::from("/usr/share/zoneinfo"),
             time_zone: ArcTz::new(Utc.into()),
             current_timestamp: NaiveDateTime::MIN,
+            scale_factor: 1.0,
             variables: vec![Value::Null; variables_count].into_boxed_slice(),
+            namespace_seed: [0; 32],
+            stable_seeding: false,
+            clock_stride: 0,
+            references: Arc::default(),
+            regex_cache: Arc::default(),
+            named_permutations: Arc::default(),
+            markov_chains: Arc::default(),
+            unique_tuples_count: Arc::default(),
+        }
+    }
+
+    /// Allocates the identifier of a compiled `unique.tuple()` call, which keys the tuples it
+    /// produced in [`State`].
+    pub(crate) fn allocate_unique_tuple(&self) -> usize {
+        self.unique_tuples_count.fetch_add(1, AtomicOrdering::Relaxed)
+    }
+
+    /// Registers a column to be sampled by `ref()`, and returns the index of its reservoir.
+    pub(crate) fn register_reference(&self, table: String, column: String, capacity: usize) -> usize {
+        let mut references = self.references.lock().unwrap();
+        if let Some(i) = references.iter().position(|r| r.table == table && r.column == column) {
+            let reference = &mut references[i];
+            reference.capacity = reference.capacity.max(capacity);
+            i
+        } else {
+            references.push(Reference {
+                table,
+                column,
+                capacity,
+            });
+            references.len() - 1
+        }
+    }
+
+    /// Obtains the `rand.regex` generator with the given arguments, compiling it only if it is not
+    /// found among the recently used ones.
+    ///
+    /// The cache is shared by all tables, so identical regexes in several columns use the same
+    /// generator, and regexes computed per row are not recompiled every time.
+    pub(crate) fn cached_regex(
+        &self,
+        key: RegexKey,
+        compile: impl FnOnce(&RegexKey) -> Result<rand_regex::Regex, Error>,
+    ) -> Result<Arc<rand_regex::Regex>, Error> {
+        let mut cache = self.regex_cache.lock().unwrap();
+        let generator = if let Some(i) = cache.iter().position(|(k, _)| *k == key) {
+            cache.remove(i).1
+        } else {
+            let generator = Arc::new(compile(&key)?);
+            if cache.len() >= REGEX_CACHE_CAPACITY {
+                cache.remove(0);
+            }
+            generator
+        };
+        cache.push((key, generator.clone()));
+        Ok(generator)
+    }
+
+    /// Obtains the permutation shared by all tables under the given name.
+    ///
+    /// The first use of a name must provide the number of elements, and the permutation is then
+    /// created by `create`. Later uses may omit the number, but if given it must be the same.
+    pub(crate) fn named_permutation(
+        &self,
+        name: &str,
+        total: Option<u64>,
+        create: impl FnOnce(u64) -> Permutation,
+    ) -> Result<(Arc<Permutation>, u64), Error> {
+        let mut permutations = self.named_permutations.lock().unwrap();
+        if let Some((permutation, existing_total)) = permutations.get(name) {
+            if total.map_or(false, |total| total != *existing_total) {
+                return Err(Error::InvalidArguments(format!(
+                    "permutation '{name}' was already defined with {existing_total} elements"
+                )));
+            }
+            return Ok((permutation.clone(), *existing_total));
+        }
+        let total = total.filter(|total| *total > 0).ok_or_else(|| {
+            Error::InvalidArguments(format!(
+                "the first use of permutation '{name}' must provide a positive number of elements"
+            ))
+        })?;
+        let permutation = Arc::new(create(total));
+        permutations.insert(name.to_owned(), (permutation.clone(), total));
+        Ok((permutation, total))
+    }
+
+    /// Obtains the `rand.markov` chain of the corpus file, building it by `create` only on the
+    /// first use of the path.
+    ///
+    /// The cache is shared by all tables, so a corpus used in several columns, or by a call
+    /// recompiled every row, is only read once.
+    pub(crate) fn cached_markov_chain(
+        &self,
+        path: PathBuf,
+        create: impl FnOnce(&Path) -> Result<MarkovChain, Error>,
+    ) -> Result<Arc<MarkovChain>, Error> {
+        let mut chains = self.markov_chains.lock().unwrap();
+        if let Some(chain) = chains.get(&path) {
+            return Ok(chain.clone());
+        }
+        let chain = Arc::new(create(&path)?);
+        chains.insert(path, chain.clone());
+        Ok(chain)
+    }
+
+    /// Creates a random number generator derived only from the namespace and the namespace seed.
+    ///
+    /// This allows functions to prepare random but reproducible data during compilation.
+    pub fn namespaced_rng(&self, namespace: &str) -> Hc128Rng {
+        let namespace_hash = fnv1a(namespace.bytes());
+        derive_rng(self.namespace_seed, &[namespace_hash, !namespace_hash])
+    }
+
+    /// Returns the value of `scale_factor`, which is an integer if the scale factor is whole.
+    fn scale_factor_value(&self) -> Value {
+        if self.scale_factor.fract() == 0.0 {
+            // ALLOW_REASON: the scale factor is a positive integer here.
+            #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+            let scale_factor = self.scale_factor as u64;
+            scale_factor.into()
+        } else {
+            Value::from_finite_f64(self.scale_factor)
         }
     }
 
@@ -64,8 +281,118 @@ pub struct State {
     pub(crate) row_num: u64,
     /// Defines the value of `subrownum`.
     pub sub_row_num: u64,
+    /// The row number this state started with.
+    initial_row_num: u64,
     rng: Box<dyn RngCore>,
     compile_context: CompileContext,
+    /// The current positions of the named sequences.
+    sequences: Vec<(Box<str>, u64)>,
+    /// The hashes of the tuples produced so far by every `unique.tuple()` call, identified by the
+    /// id allocated when it is compiled.
+    unique_tuples: HashMap<usize, HashSet<u64>>,
+    /// The values evaluated so far in the current row.
+    row_values: Vec<Value>,
+    /// The values sampled from the referenced columns.
+    reservoirs: Vec<Reservoir>,
+    /// The microseconds elapsed on the simulated clock of `clock.tick()`.
+    clock: i64,
+}
+
+/// The initial state of the 64-bit FNV-1a hash.
+const FNV1A_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// Computes the 64-bit FNV-1a hash, which is stable across platforms and Rust versions.
+pub(crate) fn fnv1a(bytes: impl IntoIterator<Item = u8>) -> u64 {
+    fnv1a_update(FNV1A_OFFSET_BASIS, bytes)
+}
+
+/// Continues the 64-bit FNV-1a hash `h` with more bytes.
+fn fnv1a_update(h: u64, bytes: impl IntoIterator<Item = u8>) -> u64 {
+    bytes
+        .into_iter()
+        .fold(h, |h, b| (h ^ u64::from(b)).wrapping_mul(0x100_0000_01b3))
+}
+
+/// Computes the 64-bit FNV-1a hash of the formatted text, without allocating the text.
+struct Fnv1aWriter(u64);
+
+impl fmt::Write for Fnv1aWriter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 = fnv1a_update(self.0, s.bytes());
+        Ok(())
+    }
+}
+
+/// Creates a random number generator from the seed with the given words mixed in.
+fn derive_rng(mut seed: [u8; 32], words: &[u64]) -> Hc128Rng {
+    for (chunk, word) in seed.chunks_exact_mut(8).zip(words) {
+        for (s, b) in chunk.iter_mut().zip(word.to_le_bytes()) {
+            *s ^= b;
+        }
+    }
+    Hc128Rng::from_seed(seed)
+}
+
+/// The increment of the counter of [`CounterRng`], which is the golden ratio as in SplitMix64.
+const COUNTER_RNG_GAMMA: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// The finalizer of SplitMix64, mixing every input bit into every output bit.
+fn mix64(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// A counter-based random number generator, whose `i`-th output is a hash of the key and `i`.
+///
+/// Unlike HC-128, creating a generator is as cheap as hashing the seed, so one can be derived for
+/// every row. The outputs are not cryptographically secure.
+#[derive(Clone, Debug)]
+pub struct CounterRng {
+    key: u64,
+    counter: u64,
+}
+
+impl CounterRng {
+    /// Creates a generator keyed by the seed with the given words mixed in.
+    fn new(seed: [u8; 32], words: &[u64]) -> Self {
+        let key = seed
+            .chunks_exact(8)
+            .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+            .chain(words.iter().copied())
+            .fold(0, |key: u64, word| mix64(key.wrapping_add(COUNTER_RNG_GAMMA) ^ word));
+        Self { key, counter: 0 }
+    }
+}
+
+impl RngCore for CounterRng {
+    fn next_u32(&mut self) -> u32 {
+        // ALLOW_REASON: taking the lower half of the output.
+        #[allow(clippy::cast_possible_truncation)]
+        let res = self.next_u64() as u32;
+        res
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.counter = self.counter.wrapping_add(1);
+        mix64(self.key.wrapping_add(self.counter.wrapping_mul(COUNTER_RNG_GAMMA)))
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(8) {
+            chunk.copy_from_slice(&self.next_u64().to_le_bytes()[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// Creates the random number generator of a row used in stable seeding.
+fn row_rng(seed: [u8; 32], row_num: u64) -> CounterRng {
+    CounterRng::new(seed, &[row_num])
 }
 
 impl fmt::Debug for State {
@@ -75,6 +402,11 @@ impl fmt::Debug for State {
             .field("sub_row_num", &self.sub_row_num)
             .field("rng", &())
             .field("variables", &self.compile_context.variables)
+            .field("sequences", &self.sequences)
+            .field("unique_tuples", &self.unique_tuples)
+            .field("row_values", &self.row_values)
+            .field("reservoirs", &self.reservoirs)
+            .field("clock", &self.clock)
             .finish()
     }
 }
@@ -86,13 +418,97 @@ impl State {
     ///
     /// - `row_num`: The starting row number in this state. The first file should have this set
     ///     to 1, and the second to `rows_count * inserts_count + 1`, etc.
-    /// - `rng`: The seeded random number generator.
-    pub fn new(row_num: u64, rng: Box<dyn RngCore>, compile_context: CompileContext) -> Self {
+    /// - `rng`: The seeded random number generator. This is ignored if the compile context
+    ///     enables stable seeding.
+    pub fn new(row_num: u64, mut rng: Box<dyn RngCore>, compile_context: CompileContext) -> Self {
+        if compile_context.stable_seeding {
+            rng = Box::new(row_rng(compile_context.namespace_seed, row_num));
+        }
+        // continue the clock from the rows before, so the files do not repeat the same readings.
+        let clock = i64::try_from(row_num.saturating_sub(1))
+            .unwrap_or(i64::MAX)
+            .saturating_mul(compile_context.clock_stride);
+        let reservoirs = compile_context
+            .references
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|reference| Reservoir {
+                reference: reference.clone(),
+                location: None,
+                values: Vec::new(),
+                seen: 0,
+            })
+            .collect();
         Self {
             row_num,
             sub_row_num: 1,
+            initial_row_num: row_num,
             rng,
             compile_context,
+            sequences: Vec::new(),
+            unique_tuples: HashMap::new(),
+            row_values: Vec::new(),
+            reservoirs,
+            clock,
+        }
+    }
+
+    /// Finds the tables and columns sampled by `ref()`.
+    pub fn resolve_references(&mut self, tables: &[Table]) -> Result<(), Error> {
+        fn unique_name(name: &str) -> String {
+            QName::new(None, None, name).unique_name().to_owned()
+        }
+
+        for reservoir in &mut self.reservoirs {
+            if reservoir.location.is_some() {
+                continue;
+            }
+            let reference = &reservoir.reference;
+            let table_name = QName::parse(&reference.table)?;
+            let table_index = tables
+                .iter()
+                .position(|t| {
+                    t.name.unique_name() == table_name.unique_name()
+                        || t.name.unique_table_name() == table_name.unique_name()
+                })
+                .ok_or_else(|| Error::InvalidArguments(format!("ref: table {} does not exist", reference.table)))?;
+            let column_name = unique_name(&reference.column);
+            let column_index = tables[table_index]
+                .schema(false)
+                .column_names()
+                .position(|c| unique_name(c) == column_name)
+                .ok_or_else(|| {
+                    Error::InvalidArguments(format!(
+                        "ref: column {} does not exist in table {}",
+                        reference.column, reference.table
+                    ))
+                })?;
+            reservoir.location = Some((table_index, column_index));
+        }
+        Ok(())
+    }
+
+    /// Records the values of a generated row into the reservoirs sampling the table.
+    pub fn record_row(&mut self, table_index: usize, values: &[Value]) {
+        for reservoir in &mut self.reservoirs {
+            if let Some((t, c)) = reservoir.location {
+                if t == table_index {
+                    if let Some(value) = values.get(c) {
+                        reservoir.insert(value);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Picks a random value from the reservoir. Returns NULL if no values are generated yet.
+    fn sample_reference(&mut self, index: usize) -> Value {
+        let values = &self.reservoirs[index].values;
+        if values.is_empty() {
+            Value::Null
+        } else {
+            values[self.rng.gen_range(0..values.len())].clone()
         }
     }
 
@@ -102,24 +518,139 @@ impl State {
     }
 
     /// Increases the rownum by 1.
+    ///
+    /// With stable seeding, the random number generator is also reseeded for the new row.
     pub fn increase_row_num(&mut self) {
         self.row_num += 1;
+        if self.compile_context.stable_seeding {
+            self.rng = Box::new(row_rng(self.compile_context.namespace_seed, self.row_num));
+        }
+    }
+
+    /// Creates a random number generator derived from the namespace and the current row.
+    ///
+    /// The generator only depends on the namespace, `rownum`, `subrownum` and the namespace seed
+    /// of the compile context, so the values it produces are unaffected by how the other
+    /// expressions consume the main random number generator.
+    pub fn namespaced_rng(&self, namespace: &str) -> CounterRng {
+        CounterRng::new(
+            self.compile_context.namespace_seed,
+            &[fnv1a(namespace.bytes()), self.row_num, self.sub_row_num],
+        )
+    }
+
+    /// Computes the checksum of the values evaluated so far in the current row.
+    ///
+    /// This is the 64-bit FNV-1a hash of the SQL literals of the values joined by commas, so the
+    /// result does not depend on the output format.
+    fn row_checksum(&self) -> u64 {
+        use fmt::Write as _;
+
+        let mut hasher = Fnv1aWriter(FNV1A_OFFSET_BASIS);
+        for (i, value) in self.row_values.iter().enumerate() {
+            if i != 0 {
+                hasher.0 = fnv1a_update(hasher.0, *b",");
+            }
+            write!(hasher, "{value}").unwrap();
+        }
+        hasher.0
+    }
+
+    /// Advances the named sequence, skipping `gap` positions, and returns the position before
+    /// advancing.
+    ///
+    /// A sequence first seen in this state starts at the position `initial_row_num - 1`, so that a
+    /// sequence drawn exactly once per row is independent of how rows are split among files.
+    fn next_sequence_position(&mut self, name: &str, gap: u64) -> u64 {
+        let i = if let Some(i) = self.sequences.iter().position(|(n, _)| **n == *name) {
+            i
+        } else {
+            self.sequences
+                .push((name.into(), self.initial_row_num.saturating_sub(1)));
+            self.sequences.len() - 1
+        };
+        let position = &mut self.sequences[i].1;
+        *position += gap;
+        let result = *position;
+        *position += 1;
+        result
+    }
+
+    /// Records the tuple produced by the `unique.tuple()` call with the given id. Returns whether
+    /// the tuple was not produced before.
+    ///
+    /// Only the hash of the tuple is kept, so the memory used does not depend on the size of the
+    /// values. A hash collision merely causes the tuple to be regenerated.
+    fn insert_unique_tuple(&mut self, id: usize, tuple: &[Value]) -> bool {
+        use fmt::Write as _;
+
+        let mut hasher = Fnv1aWriter(FNV1A_OFFSET_BASIS);
+        for (i, value) in tuple.iter().enumerate() {
+            if i != 0 {
+                hasher.0 = fnv1a_update(hasher.0, *b",");
+            }
+            write!(hasher, "{value}").unwrap();
+        }
+        self.unique_tuples.entry(id).or_default().insert(hasher.0)
     }
 }
 
+/// Maximum number of tuples generated by `unique.tuple()` to find one not produced before.
+const MAX_UNIQUE_TUPLE_ATTEMPTS: u32 = 1000;
+
+/// Samples a Poisson distribution as an integer.
+fn sample_count(rng: &mut dyn RngCore, poisson: &Poisson<f64>) -> u64 {
+    // ALLOW_REASON: the Poisson distribution always produces a non-negative integer.
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let count = rng.sample(poisson) as u64;
+    count
+}
+
 /// A compiled table
 #[derive(Debug)]
 pub struct Table {
     /// Table name.
     pub name: QName,
+    /// The modifiers between `CREATE` and `TABLE`, each followed by a space.
+    pub modifiers: String,
+    /// Whether the table is created with `IF NOT EXISTS`.
+    pub if_not_exists: bool,
     /// Content of table schema.
     pub content: String,
+    /// The statements written to the schema file after the table.
+    pub auxiliary_statements: Vec<String>,
     /// The ranges in `content` which column names appear.
     pub column_name_ranges: Vec<Range<usize>>,
+    /// The ranges in `content` which column types appear.
+    pub column_type_ranges: Vec<Range<usize>>,
     /// Compiled row.
     pub row: Row,
     /// Information of dervied tables (index, and number of rows to generate)
     pub derived: Vec<(usize, Compiled)>,
+    /// Restricts the written rows to the selected partitions. Rows outside of them are skipped.
+    pub partition_filter: Option<PartitionFilter>,
+    /// The variables and the lists whose cross product is enumerated by the rows.
+    pub combinations: Vec<(usize, Compiled)>,
+    /// Whether each column is excluded from the output. Empty if every column is written.
+    pub skipped_columns: Vec<bool>,
+    /// The indices of the output columns in the order they are written. Empty if the columns are
+    /// written in the template order.
+    pub column_order: Vec<usize>,
+    /// The condition a row must satisfy to be written.
+    pub row_filter: Option<Compiled>,
+    /// The key sorting the rows within a data file.
+    pub order_by: Option<Compiled>,
+    /// The physical tables sharing this definition, if the rows are routed into shards.
+    pub shards: Option<Shards>,
+}
+
+/// The physical tables sharing the definition of a sharded table.
+#[derive(Debug)]
+pub struct Shards {
+    /// Names of the shards (`table_0`, `table_1`, …).
+    pub names: Vec<QName>,
+    /// The expression selecting the shard of each row, modulo the number of shards.
+    pub key: Compiled,
 }
 
 /// The schema information extracted from the compiled table.
@@ -131,6 +662,12 @@ pub struct Schema<'a> {
     pub content: &'a str,
     /// The ranges in `content` which column names appear.
     column_name_ranges: &'a [Range<usize>],
+    /// The ranges in `content` which column types appear.
+    column_type_ranges: &'a [Range<usize>],
+    /// Whether each column is excluded from the output.
+    skipped_columns: &'a [bool],
+    /// The indices of the output columns in the order they are written.
+    column_order: &'a [usize],
 }
 
 impl<'a> Schema<'a> {
@@ -138,6 +675,41 @@ impl<'a> Schema<'a> {
     pub fn column_names(&self) -> impl Iterator<Item = &str> + '_ {
         self.column_name_ranges.iter().map(move |r| &self.content[r.clone()])
     }
+
+    /// Returns an iterator of column types associated with the table.
+    ///
+    /// The column type is the trimmed text between the column name and the expression, which may
+    /// also include constraints like `NOT NULL`.
+    pub fn column_types(&self) -> impl Iterator<Item = &str> + '_ {
+        self.column_type_ranges
+            .iter()
+            .map(move |r| self.content[r.clone()].trim())
+    }
+
+    /// Returns whether the column at the index is written to the output.
+    pub fn is_output_column(&self, index: usize) -> bool {
+        !self.skipped_columns.get(index).copied().unwrap_or(false)
+    }
+
+    /// Returns an iterator of indices of the columns written to the output, in the output order.
+    pub fn output_column_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        let in_template_order = self.column_order.is_empty();
+        (0..self.column_name_ranges.len())
+            .filter(move |i| in_template_order && self.is_output_column(*i))
+            .chain(self.column_order.iter().copied())
+    }
+
+    /// Returns an iterator of names of the columns written to the output.
+    pub fn output_column_names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.output_column_indices()
+            .map(move |i| &self.content[self.column_name_ranges[i].clone()])
+    }
+
+    /// Returns an iterator of types of the columns written to the output.
+    pub fn output_column_types(&self) -> impl Iterator<Item = &str> + '_ {
+        self.output_column_indices()
+            .map(move |i| self.content[self.column_type_ranges[i].clone()].trim())
+    }
 }
 
 impl Table {
@@ -147,25 +719,176 @@ impl Table {
             name: self.name.table_name(qualified),
             content: &self.content,
             column_name_ranges: &self.column_name_ranges,
+            column_type_ranges: &self.column_type_ranges,
+            skipped_columns: &self.skipped_columns,
+            column_order: &self.column_order,
+        }
+    }
+
+    /// Gets the name of the physical table, which is the `shard`-th shard if the table is sharded.
+    pub fn shard_name(&self, shard: Option<usize>) -> &QName {
+        match (&self.shards, shard) {
+            (Some(shards), Some(shard)) => &shards.names[shard],
+            _ => &self.name,
         }
     }
+
+    /// Gets the schema of the physical table, which is the `shard`-th shard if the table is
+    /// sharded.
+    pub fn shard_schema(&self, qualified: bool, shard: Option<usize>) -> Schema<'_> {
+        Schema {
+            name: self.shard_name(shard).table_name(qualified),
+            ..self.schema(qualified)
+        }
+    }
+
+    /// Iterates the shard indices of the physical tables, which is a single `None` if the table
+    /// is not sharded.
+    pub fn shard_indices(&self) -> impl Iterator<Item = Option<usize>> + '_ {
+        let count = self.shards.as_ref().map_or(1, |shards| shards.names.len());
+        (0..count).map(|i| self.shards.as_ref().map(|_| i))
+    }
+
+    /// Whether the rows of this table can be evaluated in batches by [`Row::eval_columns()`], i.e.
+    /// the row is batchable, and nothing else is evaluated between the rows.
+    pub fn is_batchable(&self) -> bool {
+        self.derived.is_empty()
+            && self.combinations.is_empty()
+            && self.row_filter.is_none()
+            && self.order_by.is_none()
+            && self.shards.is_none()
+            && self.row.is_batchable()
+    }
+
+    /// Evaluates the shard the current row is routed to, or `None` if the table is not sharded.
+    pub fn route_shard(&self, state: &mut State) -> Result<Option<usize>, S<Error>> {
+        let Some(shards) = &self.shards else {
+            return Ok(None);
+        };
+        let key = i128::try_from(shards.key.eval(state)?).span_err(shards.key.0.span)?;
+        let count = i128::try_from(shards.names.len()).unwrap();
+        Ok(Some(usize::try_from(key.rem_euclid(count)).unwrap()))
+    }
+
+    /// Assigns the variables of the `index`-th (0-based) combination of the lists enumerated by
+    /// the rows, where the last list varies the fastest.
+    ///
+    /// Returns `false` if all combinations have been enumerated, i.e. no more rows should be
+    /// generated. Always returns `true` if the table does not enumerate any combinations.
+    pub fn assign_combination(&self, index: u64, state: &mut State) -> Result<bool, S<Error>> {
+        let mut elements = Vec::with_capacity(self.combinations.len());
+        let mut remaining = index;
+        for (variable, list) in self.combinations.iter().rev() {
+            let list = Array::try_from(list.eval(state)?).span_err(list.0.span)?;
+            let len = list.len();
+            if len == 0 {
+                return Ok(false);
+            }
+            elements.push((*variable, list.get(remaining % len)));
+            remaining /= len;
+        }
+        if remaining != 0 {
+            return Ok(false);
+        }
+        for (variable, element) in elements {
+            state.compile_context.variables[variable] = element;
+        }
+        Ok(true)
+    }
 }
 
 impl CompileContext {
     /// Compiles a table.
     pub fn compile_table(&self, table: crate::parser::Table) -> Result<Table, S<Error>> {
+        let shards = table
+            .shards
+            .map(|(count, key)| {
+                Ok::<_, S<Error>>(Shards {
+                    names: (0..count as usize).map(|i| table.name.shard(i)).collect(),
+                    key: self.compile(key)?,
+                })
+            })
+            .transpose()?;
         Ok(Table {
             name: table.name,
+            modifiers: table.modifiers,
+            if_not_exists: table.if_not_exists,
             content: table.content,
+            auxiliary_statements: table.auxiliary_statements,
             column_name_ranges: table.column_name_ranges,
+            column_type_ranges: table.column_type_ranges,
             row: self.compile_row(table.exprs)?,
             derived: table
                 .derived
                 .into_iter()
                 .map(|(i, e)| self.compile(e).map(|c| (i, c)))
                 .collect::<Result<_, _>>()?,
+            partition_filter: None,
+            combinations: table
+                .combinations
+                .into_iter()
+                .map(|(i, e)| self.compile(e).map(|c| (i, c)))
+                .collect::<Result<_, _>>()?,
+            skipped_columns: Vec::new(),
+            column_order: Vec::new(),
+            row_filter: None,
+            order_by: None,
+            shards,
         })
     }
+
+    /// Compiles the condition a row must satisfy to be written, and applies it to the main tables,
+    /// i.e. those not derived from another table.
+    ///
+    /// Rows of the derived tables are written only if their parent row is.
+    pub fn compile_row_filter(&self, tables: &mut [Table], filter: S<Expr>) -> Result<(), S<Error>> {
+        let filter = self.compile(filter)?;
+        let derived = tables
+            .iter()
+            .flat_map(|t| t.derived.iter().map(|(child, _)| *child))
+            .collect::<HashSet<_>>();
+        for (i, table) in tables.iter_mut().enumerate() {
+            if !derived.contains(&i) {
+                table.row_filter = Some(filter.clone());
+            }
+        }
+        Ok(())
+    }
+
+    /// Compiles the key sorting the rows, and applies it to every table.
+    ///
+    /// The key is evaluated right after each row is generated, so it may refer to the variables
+    /// assigned by the row.
+    pub fn compile_order_by(&self, tables: &mut [Table], order_by: S<Expr>) -> Result<(), S<Error>> {
+        let order_by = self.compile(order_by)?;
+        for table in tables {
+            table.order_by = Some(order_by.clone());
+        }
+        Ok(())
+    }
+
+    /// Compiles the key sorting the rows of the table named `table_name`, overriding the key
+    /// given to [`CompileContext::compile_order_by()`]. The names are compared after unquoting.
+    pub fn compile_table_order_by(
+        &self,
+        tables: &mut [Table],
+        table_name: &str,
+        order_by: S<Expr>,
+    ) -> Result<(), S<Error>> {
+        let name = unquote(table_name);
+        let table = tables
+            .iter_mut()
+            .find(|table| unquote(table.name.table_name(false)) == name)
+            .ok_or_else(|| {
+                Error::UnsupportedCliParameter {
+                    kind: "sorted table",
+                    value: table_name.to_owned(),
+                }
+                .span(order_by.span)
+            })?;
+        table.order_by = Some(self.compile(order_by)?);
+        Ok(())
+    }
 }
 
 /// Represents a row of compiled values.
@@ -183,13 +906,75 @@ impl CompileContext {
 }
 
 impl Row {
+    /// Returns the compiled expressions of every column.
+    pub(crate) fn exprs(&self) -> &[Compiled] {
+        &self.0
+    }
+
     /// Evaluates the row into a vector of values.
     pub fn eval(&self, state: &mut State) -> Result<Vec<Value>, S<Error>> {
-        let mut result = Vec::with_capacity(self.0.len());
-        for compiled in &self.0 {
-            result.push(compiled.eval(state)?);
+        let mut values = Vec::with_capacity(self.0.len());
+        self.eval_into(state, &mut values)?;
+        Ok(values)
+    }
+
+    /// Evaluates the row into an existing vector, replacing its content.
+    ///
+    /// Reusing the same vector for every row avoids allocating a new one each time. Only the vector
+    /// itself is reused: the strings of the values are still allocated for every row.
+    pub fn eval_into(&self, state: &mut State, values: &mut Vec<Value>) -> Result<(), S<Error>> {
+        values.clear();
+        values.reserve(self.0.len());
+        mem::swap(&mut state.row_values, values);
+        let result = self.0.iter().try_for_each(|compiled| {
+            let value = compiled.eval(state)?;
+            state.row_values.push(value);
+            Ok(())
+        });
+        mem::swap(&mut state.row_values, values);
+        result
+    }
+
+    /// Evaluates the row like [`Row::eval()`], adding the time spent on every column to
+    /// `durations`.
+    pub(crate) fn eval_profiled(&self, state: &mut State, durations: &mut [Duration]) -> Result<(), S<Error>> {
+        state.row_values.clear();
+        for (compiled, duration) in self.0.iter().zip(durations) {
+            let start = Instant::now();
+            let value = compiled.eval(state)?;
+            *duration += start.elapsed();
+            state.row_values.push(value);
+        }
+        Ok(())
+    }
+
+    /// Whether the rows can be evaluated column-at-a-time by [`Row::eval_columns()`], producing the
+    /// same values as evaluating them one by one.
+    ///
+    /// Every column must be a constant, `rownum`, or a sample from a simple random distribution.
+    /// At most one column may be random, so the random numbers are consumed in the same order.
+    pub fn is_batchable(&self) -> bool {
+        self.0.iter().all(|compiled| compiled.0.inner.is_batchable())
+            && self.0.iter().filter(|compiled| compiled.0.inner.is_random()).count() <= 1
+    }
+
+    /// Evaluates `count` consecutive rows starting from the current row number column-at-a-time,
+    /// replacing the content of `columns` by the values of every column. The row number is not
+    /// advanced.
+    ///
+    /// Returns `false` without evaluating anything if the row is not
+    /// [batchable](Row::is_batchable()), or if the random number generator is reseeded for every
+    /// row due to stable seeding.
+    pub fn eval_columns(&self, state: &mut State, count: usize, columns: &mut Vec<Vec<Value>>) -> bool {
+        if state.compile_context.stable_seeding || !self.is_batchable() {
+            return false;
+        }
+        columns.resize_with(self.0.len(), Vec::new);
+        for (compiled, column) in self.0.iter().zip(columns) {
+            column.clear();
+            compiled.0.inner.eval_column(state, count, column);
         }
-        Ok(result)
+        true
     }
 }
 
@@ -213,6 +998,10 @@ pub enum C {
     GetVariable(usize),
     /// Assigns a value to a local variable.
     SetVariable(usize, Box<Compiled>),
+    /// The checksum of the values evaluated so far in the current row.
+    RowChecksum,
+    /// Random value sampled from the reservoir of a referenced column.
+    Ref(usize),
     /// The `CASE … WHEN` expression.
     CaseValueWhen {
         /// The value to match against.
@@ -222,9 +1011,52 @@ pub enum C {
         /// The result when all conditions failed.
         otherwise: Box<Compiled>,
     },
+    /// The `coalesce` function, evaluating the arguments only until the first non-NULL value.
+    Coalesce(Box<[Compiled]>),
+    /// The logical `AND`/`OR` functions, evaluating the operands only until the result is decided.
+    Logic {
+        /// The identity value. True means `AND` and false means `OR`.
+        identity: bool,
+        /// The operands.
+        args: Box<[Compiled]>,
+    },
+    /// Evaluates the expression using a namespaced random number generator.
+    Pinned {
+        /// The namespace of the random number generator.
+        namespace: Box<str>,
+        /// The expression to evaluate.
+        inner: Box<Compiled>,
+    },
+    /// Looks up a column from a table loaded from file.
+    Lookup {
+        /// The lookup table.
+        table: Arc<LookupTable>,
+        /// The key of the row.
+        key: Box<Compiled>,
+        /// The column index.
+        column: usize,
+    },
 
+    /// Random distribution whose parameters are evaluated on every row.
+    RandPerRow {
+        /// The distribution.
+        distribution: PerRowDistribution,
+        /// The parameters.
+        args: Box<[Compiled]>,
+    },
+    /// Replaces the value by a corrupted one with a probability.
+    Dirty {
+        /// How the value is corrupted.
+        corruption: Corruption,
+        /// Whether to corrupt the value.
+        rate: Bernoulli,
+        /// The value, followed by the replacement used by `dirty.swap`.
+        args: Box<[Compiled]>,
+    },
     /// Regex-based random string.
-    RandRegex(rand_regex::Regex),
+    RandRegex(Arc<rand_regex::Regex>),
+    /// Random binary string with uniformly distributed length.
+    RandBytes(Uniform<usize>),
     /// Uniform distribution for `u64`.
     RandUniformU64(Uniform<u64>),
     /// Uniform distribution for `i64`.
@@ -233,10 +1065,132 @@ pub enum C {
     RandUniformF64(Uniform<f64>),
     /// Zipfian distribution.
     RandZipf(ZipfDistribution),
+    /// Zipfian distribution counted backwards from the largest value.
+    RandLatest {
+        /// The largest value, which is the most frequent.
+        count: usize,
+        /// The distribution of the distance from `count + 1`.
+        zipf: ZipfDistribution,
+    },
+    /// Integers where a small range of hot keys receives most of the probability mass.
+    RandHotspot {
+        /// Whether to pick a hot key.
+        hot: Bernoulli,
+        /// The distribution of the hot keys.
+        hot_keys: Uniform<u64>,
+        /// The distribution of the cold keys, or `None` if all keys are hot.
+        cold_keys: Option<Uniform<u64>>,
+    },
     /// Log-normal distribution.
     RandLogNormal(LogNormal<f64>),
+    /// Normal (Gaussian) distribution.
+    RandNormal(Normal<f64>),
+    /// Exponential distribution.
+    RandExp(Exp<f64>),
+    /// Pareto distribution.
+    RandPareto(Pareto<f64>),
+    /// Multivariate normal distribution, producing an array of correlated values.
+    RandMultivariateNormal {
+        /// The mean of every component.
+        means: Box<[f64]>,
+        /// The lower triangular Cholesky factor of the covariance matrix, in row-major order.
+        cholesky: Box<[f64]>,
+    },
+    /// Uniformly distributed point inside a longitude/latitude bounding box.
+    RandPointInBbox {
+        /// The longitude distribution.
+        lon: Uniform<f64>,
+        /// The latitude distribution.
+        lat: Uniform<f64>,
+    },
+    /// Uniformly distributed point inside a circle on the Earth's surface.
+    RandPointNear {
+        /// The longitude of the center, in degrees.
+        lon: f64,
+        /// The latitude of the center, in degrees.
+        lat: f64,
+        /// The radius of the circle, in radians.
+        radius: f64,
+    },
     /// Bernoulli distribution for `bool` (i.e. a weighted random boolean).
     RandBool(Bernoulli),
+    /// Poisson distribution.
+    RandPoisson(Poisson<f64>),
+    /// Binomial distribution.
+    RandBinomial(Binomial),
+    /// Zero-inflated Poisson distribution.
+    RandZeroInflatedPoisson {
+        /// The distribution deciding whether to produce an excess zero.
+        zero: Bernoulli,
+        /// The Poisson distribution used when an excess zero is not produced.
+        poisson: Poisson<f64>,
+    },
+    /// Negative binomial distribution, expressed as the gamma distribution of the Poisson rate.
+    RandNegBinomial(Gamma<f64>),
+    /// Random text generated by a word-level Markov chain.
+    RandMarkov {
+        /// The Markov chain.
+        chain: Arc<MarkovChain>,
+        /// The number of words to generate.
+        words: Box<Compiled>,
+    },
+    /// Random line from a file.
+    RandFromFile {
+        /// The non-empty lines of the file.
+        lines: Array,
+        /// The Zipfian distribution of the line number, or `None` to pick uniformly.
+        zipf: Option<ZipfDistribution>,
+    },
+    /// Values assigned by exact quotas to the positions of a permutation of `rownum`.
+    RandQuota {
+        /// The values to choose from.
+        values: Array,
+        /// The end position (exclusive) of every value in the permuted order.
+        bounds: Box<[u64]>,
+        /// The permutation of `rownum`.
+        permutation: Box<Permutation>,
+        /// The number of rows over which the quotas are exact.
+        total: u64,
+    },
+    /// A permutation of `1..=total`, indexed by `rownum`.
+    RandPermutation {
+        /// The permutation of `rownum`.
+        permutation: Box<Permutation>,
+        /// The number of rows after which the permutation repeats.
+        total: u64,
+    },
+    /// A permutation shared by name among tables, indexed by an expression.
+    RandSharedPermutation {
+        /// The shared permutation.
+        permutation: Arc<Permutation>,
+        /// The number of elements in the permutation.
+        total: u64,
+        /// The 1-based index into the permutation.
+        index: Box<Compiled>,
+    },
+    /// The 1-based positions shuffled within consecutive blocks, indexed by an expression.
+    NearlySorted {
+        /// The permutations of the positions within every block.
+        permutation: Box<BlockPermutation>,
+        /// The number of positions in every block.
+        window: u64,
+        /// The 1-based position to be shuffled.
+        index: Box<Compiled>,
+    },
+    /// Categorical distribution, choosing one of the values by weight.
+    RandCategorical {
+        /// The values to choose from.
+        values: Array,
+        /// The weights of the values.
+        weights: Box<WeightedAliasIndex<f64>>,
+    },
+    /// The `rand.switch` function with constant probabilities, evaluating only the chosen result.
+    RandSwitch {
+        /// The probabilities of the results.
+        weights: Box<WeightedAliasIndex<f64>>,
+        /// The results to choose from, where the last one is the default.
+        results: Box<[Compiled]>,
+    },
     /// Random f32 with uniform bit pattern
     RandFiniteF32(Uniform<u32>),
     /// Random f64 with uniform bit pattern
@@ -252,12 +1206,78 @@ pub enum C {
     },
     /// Random (version 4) UUID
     RandUuid,
+    /// Time-ordered (version 7) UUID with the given Unix timestamp in milliseconds
+    RandUuidV7(u64),
+    /// ULID with the given Unix timestamp in milliseconds
+    RandUlid(u64),
+    /// The parent `rownum` of the current row in a random hierarchy
+    RandParentRownum(Hierarchy),
+
+    /// Reads the simulated clock, and then advances it by the given microseconds.
+    ClockTick(i64),
+
+    /// Array of values re-evaluated until the combination was not produced before.
+    UniqueTuple {
+        /// The id of the call, allocated by the compile context.
+        id: usize,
+        /// The elements of the array.
+        args: Box<[Compiled]>,
+    },
+
+    /// Named sequence of values.
+    Sequence {
+        /// Name of the sequence.
+        name: Box<str>,
+        /// The first value of the sequence.
+        start: Value,
+        /// The difference between two consecutive values.
+        step: Value,
+        /// The distribution of number of values skipped before every value.
+        gap: Option<Geometric>,
+    },
 }
 
 impl C {
     fn span(self, span: Span) -> Compiled {
         Compiled(S { span, inner: self })
     }
+
+    /// Whether this expression can be evaluated column-at-a-time by [`C::eval_column()`].
+    fn is_batchable(&self) -> bool {
+        self.is_random() || matches!(self, Self::Constant(_) | Self::RowNum)
+    }
+
+    /// Whether this is a batchable expression consuming the random number generator.
+    fn is_random(&self) -> bool {
+        matches!(
+            self,
+            Self::RandUniformU64(_)
+                | Self::RandUniformI64(_)
+                | Self::RandUniformF64(_)
+                | Self::RandNormal(_)
+                | Self::RandBool(_)
+        )
+    }
+
+    /// Evaluates this expression for `count` consecutive rows starting from the current row
+    /// number, appending the values to `column`.
+    ///
+    /// The expression must be batchable, i.e. it neither depends on the other columns nor fails.
+    fn eval_column(&self, state: &mut State, count: usize, column: &mut Vec<Value>) {
+        let rng = &mut state.rng;
+        match self {
+            Self::Constant(value) => column.resize(count, value.clone()),
+            Self::RowNum => column.extend((state.row_num..).take(count).map(Value::from)),
+            Self::RandUniformU64(uniform) => column.extend(rng.sample_iter(uniform).take(count).map(Value::from)),
+            Self::RandUniformI64(uniform) => column.extend(rng.sample_iter(uniform).take(count).map(Value::from)),
+            Self::RandUniformF64(uniform) => {
+                column.extend(rng.sample_iter(uniform).take(count).map(Value::from_finite_f64));
+            }
+            Self::RandNormal(normal) => column.extend(rng.sample_iter(normal).take(count).map(Value::from_finite_f64)),
+            Self::RandBool(bern) => column.extend(rng.sample_iter(bern).take(count).map(Value::from)),
+            _ => unreachable!("{self:?} is not batchable"),
+        }
+    }
 }
 
 /// A compiled expression
@@ -271,6 +1291,7 @@ impl CompileContext {
             Expr::RowNum => C::RowNum,
             Expr::SubRowNum => C::SubRowNum,
             Expr::CurrentTimestamp => C::Constant(Value::Timestamp(self.current_timestamp, self.time_zone.clone())),
+            Expr::ScaleFactor => C::Constant(self.scale_factor_value()),
             Expr::Value(v) => C::Constant(v),
             Expr::GetVariable(index) => C::GetVariable(index),
             Expr::SetVariable(index, e) => C::SetVariable(index, Box::new(self.compile(*e)?)),
@@ -288,6 +1309,8 @@ impl CompileContext {
                         })
                         .collect();
                     function.compile(self, expr.span, args)?
+                } else if let Some(c) = function.compile_partial(self, expr.span, &args)? {
+                    c
                 } else {
                     C::RawFunction {
                         function,
@@ -317,23 +1340,96 @@ impl CompileContext {
                     otherwise,
                 }
             }
+            Expr::Pinned { namespace, inner } => {
+                let namespace_span = namespace.span;
+                let namespace = match self.compile(*namespace)?.0.inner {
+                    C::Constant(Value::Bytes(namespace)) => namespace,
+                    _ => {
+                        return Err(Error::InvalidArguments(
+                            "namespace of pinned() must be a constant string".to_owned(),
+                        )
+                        .span(namespace_span))
+                    }
+                };
+                let namespace = String::from_utf8_lossy(namespace.as_bytes()).into();
+                let inner = self.compile(*inner)?;
+                if inner.is_constant() {
+                    return Ok(inner);
+                }
+                C::Pinned {
+                    namespace,
+                    inner: Box::new(inner),
+                }
+            }
         }
         .span(expr.span))
     }
 }
 
+/// Computes the microseconds advanced by `clock.tick()` while generating one row of every main
+/// table together with its derived rows.
+///
+/// A derived table with a non-constant number of rows is assumed to generate one row.
+pub fn clock_stride(tables: &[Table]) -> i64 {
+    fn table_ticks(tables: &[Table], index: usize) -> i64 {
+        let table = &tables[index];
+        let own = table
+            .row
+            .exprs()
+            .iter()
+            .fold(0_i64, |sum, compiled| sum.saturating_add(compiled.clock_ticks()));
+        table.derived.iter().fold(own, |sum, (child, count)| {
+            let count = count
+                .as_constant()
+                .and_then(|count| i64::try_from(count.clone()).ok())
+                .unwrap_or(1);
+            sum.saturating_add(count.saturating_mul(table_ticks(tables, *child)))
+        })
+    }
+
+    let derived = tables
+        .iter()
+        .flat_map(|table| table.derived.iter().map(|(child, _)| *child))
+        .collect::<HashSet<_>>();
+    (0..tables.len())
+        .filter(|index| !derived.contains(index))
+        .fold(0, |sum, index| sum.saturating_add(table_ticks(tables, index)))
+}
+
 impl Compiled {
     /// Returns whether this compiled value is a constant.
     pub fn is_constant(&self) -> bool {
         matches!(self.0.inner, C::Constant(_))
     }
 
+    /// Returns the value if this compiled value is a constant.
+    pub(crate) fn as_constant(&self) -> Option<&Value> {
+        match &self.0.inner {
+            C::Constant(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Returns the total microseconds advanced by every `clock.tick()` in this expression.
+    fn clock_ticks(&self) -> i64 {
+        let own = match self.0.inner {
+            C::ClockTick(interval) => interval,
+            _ => 0,
+        };
+        explain::describe(&self.0.inner)
+            .1
+            .into_iter()
+            .fold(own, |sum, child| sum.saturating_add(child.clock_ticks()))
+    }
+
     /// Evaluates a compiled expression and updates the state. Returns the evaluated value.
     pub fn eval(&self, state: &mut State) -> Result<Value, S<Error>> {
         let span = self.0.span;
         Ok(match &self.0.inner {
             C::RowNum => state.row_num.into(),
             C::SubRowNum => state.sub_row_num.into(),
+            C::RowChecksum => state.row_checksum().into(),
+            C::Ref(index) => state.sample_reference(*index),
             C::Constant(v) => v.clone(),
             C::RawFunction { function, args } => {
                 let mut eval_args = Arguments::with_capacity(args.len());
@@ -381,13 +1477,189 @@ impl Compiled {
                 otherwise.eval(state)?
             }
 
-            C::RandRegex(generator) => state.rng.sample::<EncodedString, _>(generator).into(),
+            C::Coalesce(args) => {
+                for arg in &**args {
+                    let value = arg.eval(state)?;
+                    if value != Value::Null {
+                        return Ok(value);
+                    }
+                }
+                Value::Null
+            }
+
+            C::Logic { identity, args } => {
+                let mut result = Some(*identity);
+                for arg in &**args {
+                    match Option::<bool>::try_from(arg.eval(state)?).span_err(arg.0.span)? {
+                        Some(v) if v == *identity => {}
+                        Some(v) => return Ok(v.into()),
+                        None => result = None,
+                    }
+                }
+                result.into()
+            }
+
+            C::Pinned { namespace, inner } => {
+                let rng = Box::new(state.namespaced_rng(namespace));
+                let main_rng = mem::replace(&mut state.rng, rng);
+                let result = inner.eval(state);
+                state.rng = main_rng;
+                result?
+            }
+            C::Lookup { table, key, column } => table.get(&key.eval(state)?, *column).span_err(span)?,
+
+            C::RandPerRow { distribution, args } => {
+                let mut params = <[S<Value>; 2]>::default();
+                for (param, arg) in params.iter_mut().zip(&**args) {
+                    *param = arg.eval(state)?.span(arg.0.span);
+                }
+                Self(distribution.create(span, params)?.span(span)).eval(state)?
+            }
+            C::ClockTick(interval) => {
+                let ctx = &state.compile_context;
+                let now = Value::Timestamp(ctx.current_timestamp, ctx.time_zone.clone())
+                    .sql_add(&Value::Interval(state.clock))
+                    .span_err(span)?;
+                state.clock = state.clock.checked_add(*interval).ok_or_else(|| {
+                    Error::IntegerOverflow(format!("clock.tick(): {} + {interval}us", state.clock)).span(span)
+                })?;
+                now
+            }
+            C::Dirty { corruption, rate, args } => {
+                let mut values = Vec::with_capacity(args.len());
+                for arg in &**args {
+                    values.push(arg.eval(state)?);
+                }
+                let mut values = values.into_iter();
+                let value = values.next().unwrap_or_default();
+                let corrupt = if *corruption == Corruption::Swap {
+                    state.namespaced_rng(SWAP_NAMESPACE).sample(rate)
+                } else {
+                    state.rng.sample(rate)
+                };
+                if corrupt {
+                    corruption.apply(&mut *state.rng, value, values.next().unwrap_or_default())
+                } else {
+                    value
+                }
+            }
+            C::RandRegex(generator) => state.rng.sample::<EncodedString, _>(&**generator).into(),
+            C::RandBytes(len) => {
+                let mut bytes = vec![0; state.rng.sample(len)];
+                state.rng.fill_bytes(&mut bytes);
+                bytes.into()
+            }
             C::RandUniformU64(uniform) => state.rng.sample(uniform).into(),
             C::RandUniformI64(uniform) => state.rng.sample(uniform).into(),
             C::RandUniformF64(uniform) => Value::from_finite_f64(state.rng.sample(uniform)),
             C::RandZipf(zipf) => (state.rng.sample(zipf) as u64).into(),
+            C::RandLatest { count, zipf } => ((count + 1 - state.rng.sample(zipf)) as u64).into(),
+            C::RandHotspot {
+                hot,
+                hot_keys,
+                cold_keys,
+            } => match cold_keys {
+                Some(cold_keys) if !state.rng.sample(hot) => state.rng.sample(cold_keys),
+                _ => state.rng.sample(hot_keys),
+            }
+            .into(),
             C::RandLogNormal(log_normal) => Value::from_finite_f64(state.rng.sample(log_normal)),
+            C::RandNormal(normal) => Value::from_finite_f64(state.rng.sample(normal)),
+            C::RandExp(exp) => Value::from_finite_f64(state.rng.sample(exp)),
+            C::RandPareto(pareto) => Value::from_finite_f64(state.rng.sample(pareto)),
+            C::RandMultivariateNormal { means, cholesky } => {
+                let z = means
+                    .iter()
+                    .map(|_| state.rng.sample(StandardNormal))
+                    .collect::<Vec<f64>>();
+                let values = means.iter().enumerate().map(|(i, mean)| {
+                    let row = &cholesky[i * means.len()..][..=i];
+                    Value::from_finite_f64(mean + row.iter().zip(&z).map(|(l, z)| l * z).sum::<f64>())
+                });
+                Value::Array(Array::from_values(values))
+            }
+            C::RandPointInBbox { lon, lat } => Value::Point(state.rng.sample(lon), state.rng.sample(lat)),
+            C::RandPointNear { lon, lat, radius } => {
+                // the small-circle approximation: pick a uniform point in a disk, then scale the
+                // east-west offset by the latitude.
+                let distance = radius * state.rng.gen::<f64>().sqrt();
+                let bearing = state.rng.gen::<f64>() * std::f64::consts::TAU;
+                let d_lat = distance * bearing.cos();
+                let d_lon = distance * bearing.sin() / lat.to_radians().cos();
+                Value::Point(lon + d_lon.to_degrees(), lat + d_lat.to_degrees())
+            }
             C::RandBool(bern) => state.rng.sample(bern).into(),
+            C::RandPoisson(poisson) => sample_count(&mut state.rng, poisson).into(),
+            C::RandBinomial(binomial) => state.rng.sample(binomial).into(),
+            C::RandZeroInflatedPoisson { zero, poisson } => {
+                if state.rng.sample(zero) {
+                    0_u64.into()
+                } else {
+                    sample_count(&mut state.rng, poisson).into()
+                }
+            }
+            C::RandNegBinomial(gamma) => {
+                let rate = state.rng.sample(gamma);
+                if let Ok(poisson) = Poisson::new(rate) {
+                    sample_count(&mut state.rng, &poisson).into()
+                } else {
+                    // the gamma distribution may underflow to zero when r is tiny.
+                    0_u64.into()
+                }
+            }
+            C::RandMarkov { chain, words } => {
+                let count = usize::try_from(words.eval(state)?).span_err(words.0.span)?;
+                chain.generate(&mut *state.rng, count).into()
+            }
+            C::RandFromFile { lines, zipf } => {
+                let index = match zipf {
+                    Some(zipf) => state.rng.sample(zipf) as u64 - 1,
+                    None => state.rng.gen_range(0..lines.len()),
+                };
+                lines.get(index)
+            }
+            C::RandQuota {
+                values,
+                bounds,
+                permutation,
+                total,
+            } => {
+                let position = permutation.get(state.row_num.wrapping_sub(1) % total);
+                values.get(bounds.partition_point(|bound| *bound <= position) as u64)
+            }
+            C::RandPermutation { permutation, total } => {
+                (permutation.get(state.row_num.wrapping_sub(1) % total) + 1).into()
+            }
+            C::RandSharedPermutation {
+                permutation,
+                total,
+                index,
+            } => {
+                let position = u64::try_from(index.eval(state)?).span_err(index.0.span)?;
+                (permutation.get(position.wrapping_sub(1) % total) + 1).into()
+            }
+            C::NearlySorted {
+                permutation,
+                window,
+                index,
+            } => {
+                let position = u64::try_from(index.eval(state)?).span_err(index.0.span)?;
+                let Some(position) = position.checked_sub(1) else {
+                    return Err(
+                        Error::InvalidArguments("position of nearly_sorted() must be positive".to_owned())
+                            .span(index.0.span),
+                    );
+                };
+                let block = position / window;
+                // the last block is shuffled as if it were complete, so a position near u64::MAX
+                // may be mapped beyond the range.
+                (block * window)
+                    .saturating_add(permutation.get(block, position % window))
+                    .saturating_add(1)
+                    .into()
+            }
+            C::RandCategorical { values, weights } => values.get(state.rng.sample(&**weights) as u64),
+            C::RandSwitch { weights, results } => results[state.rng.sample(&**weights)].eval(state)?,
             C::RandFiniteF32(uniform) => {
                 Value::from_finite_f64(f32::from_bits(state.rng.sample(uniform).rotate_right(1)).into())
             }
@@ -425,6 +1697,60 @@ impl Compiled {
                 )
                 .into()
             }
+
+            C::RandUuidV7(unix_ms) => {
+                let g = state.rng.gen::<[u16; 5]>();
+                format!(
+                    "{:08x}-{:04x}-7{:03x}-{:04x}-{:04x}{:04x}{:04x}",
+                    *unix_ms >> 16,
+                    *unix_ms & 0xffff,
+                    g[0] & 0xfff,
+                    (g[1] & 0x3fff) | 0x8000,
+                    g[2],
+                    g[3],
+                    g[4],
+                )
+                .into()
+            }
+
+            C::RandUlid(unix_ms) => {
+                const CROCKFORD_BASE32: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+                let randomness = state.rng.gen::<u128>() >> 48;
+                let ulid = (u128::from(*unix_ms) << 80) | randomness;
+                // ALLOW_REASON: the index is masked to 5 bits.
+                #[allow(clippy::cast_possible_truncation)]
+                let encoded = (0..26)
+                    .rev()
+                    .map(|i| char::from(CROCKFORD_BASE32[(ulid >> (i * 5)) as usize & 31]))
+                    .collect::<String>();
+                encoded.into()
+            }
+            C::RandParentRownum(hierarchy) => hierarchy.parent(state.row_num).into(),
+
+            C::UniqueTuple { id, args } => {
+                for _ in 0..MAX_UNIQUE_TUPLE_ATTEMPTS {
+                    let mut tuple = Vec::with_capacity(args.len());
+                    for arg in &**args {
+                        tuple.push(arg.eval(state)?);
+                    }
+                    if state.insert_unique_tuple(*id, &tuple) {
+                        return Ok(Value::Array(Array::from_values(tuple)));
+                    }
+                }
+                return Err(Error::UniqueTupleExhausted {
+                    attempts: MAX_UNIQUE_TUPLE_ATTEMPTS,
+                }
+                .span(span));
+            }
+
+            C::Sequence { name, start, step, gap } => {
+                let gap = if let Some(gap) = gap { state.rng.sample(gap) } else { 0 };
+                let position = state.next_sequence_position(name, gap);
+                Value::from(position)
+                    .sql_mul(step)
+                    .and_then(|offset| start.sql_add(&offset))
+                    .span_err(span)?
+            }
         })
     }
 }
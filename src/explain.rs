@@ -0,0 +1,356 @@
+//! Explaining how the columns of a template are compiled and evaluated.
+//!
+//! A column whose arguments cannot be constant-folded (e.g. `rand.range(0, rownum)`) has to be
+//! recompiled on every row, which can be orders of magnitude slower than a precompiled generator.
+//! The explanation shows the compiled expression tree of every column to reveal such cases, and
+//! optionally the time spent evaluating every column.
+
+use crate::{
+    error::Error,
+    eval::{Compiled, State, Table, C},
+    parser::function_name,
+    span::S,
+};
+use std::{borrow::Cow, fmt::Write, time::Duration};
+
+/// Describes a compiled node, and returns its children.
+pub(crate) fn describe(c: &C) -> (String, Vec<&Compiled>) {
+    let (label, children): (Cow<'static, str>, Vec<&Compiled>) = match c {
+        C::Constant(value) => (format!("constant {value}").into(), Vec::new()),
+        C::RawFunction { function, args } => {
+            let name = function_name(*function).map_or_else(|| format!("{function:?}"), str::to_owned);
+            (format!("{name}(…), recompiled every row").into(), args.iter().collect())
+        }
+        C::GetVariable(index) => (format!("get variable #{index}").into(), Vec::new()),
+        C::SetVariable(index, value) => (format!("set variable #{index}").into(), vec![value]),
+        C::Ref(index) => (format!("ref #{index}").into(), Vec::new()),
+        C::Pinned { namespace, inner } => (format!("pinned('{namespace}')").into(), vec![inner]),
+        C::Lookup { column, key, .. } => (format!("lookup column #{column}").into(), vec![key]),
+        C::CaseValueWhen {
+            value,
+            conditions,
+            otherwise,
+        } => (
+            "CASE".into(),
+            value
+                .iter()
+                .map(|v| &**v)
+                .chain(conditions.iter().flat_map(|(p, r)| [p, r]))
+                .chain([&**otherwise])
+                .collect(),
+        ),
+        C::Coalesce(args) => ("coalesce".into(), args.iter().collect()),
+        C::Logic { identity: true, args } => ("AND".into(), args.iter().collect()),
+        C::Logic { identity: false, args } => ("OR".into(), args.iter().collect()),
+        C::RowNum => ("rownum".into(), Vec::new()),
+        C::SubRowNum => ("subrownum".into(), Vec::new()),
+        C::RowChecksum => ("row checksum".into(), Vec::new()),
+        C::Sequence { name, .. } => (format!("sequence('{name}')").into(), Vec::new()),
+        C::UniqueTuple { args, .. } => ("unique tuple".into(), args.iter().collect()),
+        C::RandPerRow { distribution, args } => (
+            format!("{}(…), parameters evaluated every row", distribution.name()).into(),
+            args.iter().collect(),
+        ),
+        C::ClockTick(interval) => (format!("clock tick by {interval}us").into(), Vec::new()),
+        C::Dirty { corruption, args, .. } => (format!("dirty {corruption:?}").into(), args.iter().collect()),
+        C::RandRegex(_) => ("random regex".into(), Vec::new()),
+        C::RandBytes(_) => ("random bytes".into(), Vec::new()),
+        C::RandUniformU64(_) | C::RandUniformI64(_) => ("random uniform integer".into(), Vec::new()),
+        C::RandUniformF64(_) => ("random uniform float".into(), Vec::new()),
+        C::RandZipf(_) => ("random zipf".into(), Vec::new()),
+        C::RandLatest { .. } => ("random latest".into(), Vec::new()),
+        C::RandHotspot { .. } => ("random hotspot".into(), Vec::new()),
+        C::RandLogNormal(_) => ("random log-normal".into(), Vec::new()),
+        C::RandNormal(_) => ("random normal".into(), Vec::new()),
+        C::RandExp(_) => ("random exponential".into(), Vec::new()),
+        C::RandPareto(_) => ("random pareto".into(), Vec::new()),
+        C::RandMultivariateNormal { .. } => ("random multivariate normal".into(), Vec::new()),
+        C::RandPointInBbox { .. } => ("random point in bounding box".into(), Vec::new()),
+        C::RandPointNear { .. } => ("random point near center".into(), Vec::new()),
+        C::RandBool(_) => ("random bool".into(), Vec::new()),
+        C::RandPoisson(_) => ("random poisson".into(), Vec::new()),
+        C::RandBinomial(_) => ("random binomial".into(), Vec::new()),
+        C::RandZeroInflatedPoisson { .. } => ("random zero-inflated poisson".into(), Vec::new()),
+        C::RandNegBinomial(_) => ("random negative binomial".into(), Vec::new()),
+        C::RandMarkov { words, .. } => ("random markov text".into(), vec![words]),
+        C::RandFromFile { .. } => ("random line from file".into(), Vec::new()),
+        C::RandQuota { .. } => ("random quota".into(), Vec::new()),
+        C::RandPermutation { .. } => ("random permutation".into(), Vec::new()),
+        C::RandSharedPermutation { index, .. } => ("random shared permutation".into(), vec![index]),
+        C::NearlySorted { index, .. } => ("nearly sorted".into(), vec![index]),
+        C::RandCategorical { .. } => ("random categorical".into(), Vec::new()),
+        C::RandSwitch { results, .. } => ("random switch".into(), results.iter().collect()),
+        C::RandFiniteF32(_) => ("random finite f32".into(), Vec::new()),
+        C::RandFiniteF64(_) => ("random finite f64".into(), Vec::new()),
+        C::RandU31Timestamp(_) => ("random u31 timestamp".into(), Vec::new()),
+        C::RandShuffle { .. } => ("random shuffle".into(), Vec::new()),
+        C::RandUuid => ("random UUIDv4".into(), Vec::new()),
+        C::RandUuidV7(_) => ("random UUIDv7".into(), Vec::new()),
+        C::RandUlid(_) => ("random ULID".into(), Vec::new()),
+        C::RandParentRownum(_) => ("random parent rownum".into(), Vec::new()),
+    };
+    (label.into_owned(), children)
+}
+
+/// Writes the compiled expression tree, one node per line.
+fn write_tree(out: &mut String, compiled: &Compiled, depth: usize) {
+    let (label, children) = describe(&compiled.0.inner);
+    writeln!(out, "{:indent$}{label}", "", indent = depth * 2).unwrap();
+    for child in children {
+        write_tree(out, child, depth + 1);
+    }
+}
+
+/// The maximum number of rows evaluated from each table by [`profile()`].
+pub const MAX_PROFILED_ROWS: u64 = 10_000;
+
+/// Evaluates the given number of rows (at most [`MAX_PROFILED_ROWS`]) from each table, and
+/// measures the average time spent evaluating every column.
+///
+/// Every table is evaluated on its own, ignoring the parent-child relationship of derived tables.
+pub fn profile(tables: &[Table], state: &mut State, rows: u64) -> Result<Vec<Vec<Duration>>, S<Error>> {
+    let rows = rows.min(MAX_PROFILED_ROWS);
+    tables
+        .iter()
+        .map(|table| {
+            let mut durations = vec![Duration::ZERO; table.row.exprs().len()];
+            for _ in 0..rows {
+                table.row.eval_profiled(state, &mut durations)?;
+                state.increase_row_num();
+            }
+            if let Ok(rows) = u32::try_from(rows) {
+                for duration in &mut durations {
+                    *duration = duration.checked_div(rows).unwrap_or_default();
+                }
+            }
+            Ok(durations)
+        })
+        .collect()
+}
+
+/// Explains how every column of every table, and the key sorting its rows, is compiled.
+///
+/// If `timings` is computed by [`profile()`], the average time per row is shown after every
+/// column name.
+pub fn explain(tables: &[Table], qualified: bool, timings: Option<&[Vec<Duration>]>) -> String {
+    let mut out = String::new();
+    for (i, table) in tables.iter().enumerate() {
+        let schema = table.schema(qualified);
+        writeln!(out, "{}", schema.name).unwrap();
+        let mut column_names = schema.column_names();
+        for (j, compiled) in table.row.exprs().iter().enumerate() {
+            let column = column_names.next().map_or_else(|| format!("#{}", j + 1), str::to_owned);
+            write!(out, "  {column}").unwrap();
+            if let Some(duration) = timings.and_then(|t| t.get(i)?.get(j)) {
+                write!(out, " ({duration:?} per row)").unwrap();
+            }
+            out.push('\n');
+            write_tree(&mut out, compiled, 2);
+        }
+        if let Some(order_by) = &table.order_by {
+            out.push_str("  (order by)\n");
+            write_tree(&mut out, order_by, 2);
+        }
+    }
+    out
+}
+
+/// Formats a size in bytes using binary units.
+#[cfg(feature = "cli")]
+fn format_size(bytes: f64) -> String {
+    const UNITS: [&str; 6] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB"];
+    let mut size = bytes;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+/// Summarizes the tables and the expected output of generating `root_rows` rows of every main
+/// table, without generating anything.
+///
+/// The size is estimated from the column types, assuming the SQL format.
+#[cfg(feature = "cli")]
+pub fn dry_run(tables: &[Table], qualified: bool, root_rows: u64) -> String {
+    use crate::schemagen_cli::estimate_average_len;
+
+    let mut rows = vec![Some(root_rows); tables.len()];
+    for (i, table) in tables.iter().enumerate() {
+        for (child, count) in &table.derived {
+            rows[*child] = match &count.0.inner {
+                C::Constant(count) => u64::try_from(count.clone())
+                    .ok()
+                    .zip(rows[i])
+                    .and_then(|(c, r)| c.checked_mul(r)),
+                _ => None,
+            };
+        }
+    }
+
+    let mut out = String::new();
+    let mut total_size = 0.0;
+    let mut unknown_columns = 0;
+    let mut variable_tables = 0;
+    for (i, table) in tables.iter().enumerate() {
+        let schema = table.schema(qualified);
+        write!(out, "{}", schema.name).unwrap();
+        if let Some((parent, (_, count))) = tables
+            .iter()
+            .enumerate()
+            .find_map(|(j, t)| Some((j, t.derived.iter().find(|(child, _)| *child == i)?)))
+        {
+            let parent_name = tables[parent].schema(qualified).name;
+            match &count.0.inner {
+                C::Constant(count) => write!(out, ", {count} rows per row of {parent_name}"),
+                _ => write!(out, ", a variable number of rows per row of {parent_name}"),
+            }
+            .unwrap();
+        }
+
+        // every row is written as `(v1, v2, …),\n`.
+        let mut row_len = 4.0;
+        let mut columns = String::new();
+        for (j, (name, ty)) in schema
+            .output_column_names()
+            .zip(schema.output_column_types())
+            .enumerate()
+        {
+            if j > 0 {
+                row_len += 2.0;
+            }
+            if let Some(len) = estimate_average_len(ty) {
+                row_len += len;
+                writeln!(columns, "  {name} {ty} (~{len:.1} bytes)").unwrap();
+            } else {
+                unknown_columns += 1;
+                writeln!(columns, "  {name} {ty} (unknown size)").unwrap();
+            }
+        }
+
+        if let Some(rows) = rows[i] {
+            // ALLOW_REASON: the estimation does not require full precision.
+            #[allow(clippy::cast_precision_loss)]
+            let size = rows as f64 * row_len;
+            total_size += size;
+            writeln!(out, ", {rows} rows, ~{}", format_size(size)).unwrap();
+        } else {
+            variable_tables += 1;
+            writeln!(out, ", unknown number of rows, ~{row_len:.1} bytes per row").unwrap();
+        }
+        out.push_str(&columns);
+    }
+
+    write!(out, "Estimated data size: ~{}", format_size(total_size)).unwrap();
+    if unknown_columns > 0 {
+        write!(out, ", excluding {unknown_columns} column(s) of unknown size").unwrap();
+    }
+    if variable_tables > 0 {
+        write!(out, ", excluding {variable_tables} table(s) of unknown number of rows").unwrap();
+    }
+    out.push('\n');
+    out
+}
+
+#[test]
+fn test_explain() {
+    use crate::{
+        eval::CompileContext,
+        parser::{ParseOptions, Template},
+        span::Registry,
+    };
+
+    let template = Template::parse(
+        "CREATE TABLE t (
+            a INT {{ rand.range(0, 10) }},
+            b INT {{ rand.zipf(rownum, 0.5) }},
+            c INT {{ rand.range(0, rownum) }}
+        );",
+        &[],
+        &[],
+        &ParseOptions::default(),
+        &mut Registry::default(),
+    )
+    .unwrap();
+    let ctx = CompileContext::new(template.variables_count);
+    let tables = template
+        .tables
+        .into_iter()
+        .map(|t| ctx.compile_table(t))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(
+        explain(&tables, false, None),
+        "t\n  a\n    random uniform integer\n  b\n    rand.zipf(…), recompiled every row\n      rownum\n      constant 0.5\n  c\n    rand.range(…), parameters evaluated every row\n      constant 0\n      rownum\n"
+    );
+}
+
+#[test]
+fn test_explain_order_by() {
+    use crate::{
+        eval::CompileContext,
+        parser::{ParseOptions, Template},
+        span::Registry,
+    };
+
+    let options = ParseOptions {
+        order_by: Some("rownum"),
+        ..ParseOptions::default()
+    };
+    let template = Template::parse(
+        "CREATE TABLE t (a INT {{ 1 }});",
+        &[],
+        &[],
+        &options,
+        &mut Registry::default(),
+    )
+    .unwrap();
+    let ctx = CompileContext::new(template.variables_count);
+    let mut tables = template
+        .tables
+        .into_iter()
+        .map(|t| ctx.compile_table(t))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    ctx.compile_order_by(&mut tables, template.order_by.unwrap()).unwrap();
+    assert_eq!(
+        explain(&tables, false, None),
+        "t\n  a\n    constant 1\n  (order by)\n    rownum\n"
+    );
+}
+
+#[cfg(feature = "cli")]
+#[test]
+fn test_dry_run() {
+    use crate::{
+        eval::CompileContext,
+        parser::{ParseOptions, Template},
+        span::Registry,
+    };
+
+    let template = Template::parse(
+        "CREATE TABLE t (
+            a TIMESTAMP {{ rownum }},
+            c TEXT {{ rownum }}
+        );
+        {{ for each row of t generate 2 rows of u }}
+        CREATE TABLE u (b BOOLEAN {{ TRUE }});",
+        &[],
+        &[],
+        &ParseOptions::default(),
+        &mut Registry::default(),
+    )
+    .unwrap();
+    let ctx = CompileContext::new(template.variables_count);
+    let tables = template
+        .tables
+        .into_iter()
+        .map(|t| ctx.compile_table(t))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(
+        dry_run(&tables, false, 10),
+        "t, 10 rows, ~270.0 B\n  a TIMESTAMP (~21.0 bytes)\n  c TEXT (unknown size)\nu, 2 rows per row of t, 20 rows, ~150.0 B\n  b BOOLEAN (~3.5 bytes)\nEstimated data size: ~420.0 B, excluding 1 column(s) of unknown size\n"
+    );
+}
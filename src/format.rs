@@ -1,17 +1,33 @@
 //! Output formatter
 
-use crate::{bytes::ByteString, eval::Schema, value::Value};
+use crate::{
+    bytes::ByteString,
+    eval::Schema,
+    infer::fixed_width,
+    value::Value,
+    writer::{Change, ChangeOp},
+};
 
-use chrono::{DateTime, Datelike, TimeZone, Timelike};
+use chrono::{Datelike, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone, Timelike};
 use memchr::{memchr2_iter, memchr3_iter, memchr_iter};
 use rand_regex::Encoding;
+use serde::{Deserialize, Serialize};
 use std::{
     borrow::Cow,
-    io::{Error, Write},
-    slice,
+    cell::Cell,
+    cmp::Ordering,
+    collections::HashMap,
+    convert::TryFrom,
+    io::{Error, ErrorKind, Write},
+    mem, slice,
 };
 use tzfile::ArcTz;
 
+#[cfg(feature = "arrow")]
+mod arrow;
+#[cfg(feature = "arrow")]
+pub use self::arrow::ArrowFormat;
+
 /// An shared format description of how to serialize values into strings.
 pub trait Format {
     /// Writes a single value to the writer, formatted according to specific
@@ -35,6 +51,25 @@ pub trait Format {
 
     /// Writes the content of an INSERT statement after all rows.
     fn write_trailer(&self, writer: &mut dyn Write) -> Result<(), Error>;
+
+    /// Writes the content at the end of each file.
+    fn write_file_trailer(&self, writer: &mut dyn Write, schema: &Schema<'_>) -> Result<(), Error>;
+
+    /// Writes the content starting a group of statements, e.g. `BEGIN;`.
+    fn write_group_header(&self, writer: &mut dyn Write) -> Result<(), Error>;
+
+    /// Writes the content ending a group of statements, e.g. `COMMIT;`.
+    fn write_group_trailer(&self, writer: &mut dyn Write) -> Result<(), Error>;
+
+    /// Writes a change event of a row as a complete statement or record.
+    ///
+    /// The default implementation fails, since most formats can only describe inserted rows.
+    fn write_change(&self, _: &mut dyn Write, _: &Schema<'_>, _: &Change<'_>) -> Result<(), Error> {
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "change events are not supported by this format",
+        ))
+    }
 }
 
 /// Common options for the formatters.
@@ -50,6 +85,278 @@ pub struct Options {
     pub false_string: Cow<'static, str>,
     /// The string to print for NULL result.
     pub null_string: Cow<'static, str>,
+    /// How time intervals are written.
+    pub interval_format: IntervalFormat,
+    /// How timestamps are written.
+    pub timestamp_format: TimestampFormat,
+    /// The text written before and after every timestamp in SQL and CSV output, replacing the
+    /// default quotation marks and keywords.
+    pub timestamp_wrapper: Option<(String, String)>,
+    /// How geometric points are written.
+    pub geometry_format: GeometryFormat,
+    /// How INSERT statements treat rows conflicting with the existing data.
+    pub insert_mode: InsertMode,
+    /// The clause turning INSERT statements into upserts, if any.
+    pub upsert: Option<UpsertStyle>,
+    /// The columns identifying a conflicting row of an upsert.
+    pub upsert_keys: Vec<String>,
+    /// The columns updated by an upsert. Every output column other than the keys if empty.
+    pub upsert_columns: Vec<String>,
+    /// The SQL dialect deciding the syntax of binary strings and timestamps, if any.
+    pub dialect: Option<Dialect>,
+    /// The widths of the columns in fixed-width output, overriding those inferred from the types.
+    pub column_widths: HashMap<String, usize>,
+    /// The byte separating values in CSV output.
+    pub csv_delimiter: u8,
+    /// When strings are quoted in CSV output.
+    pub csv_quote: CsvQuote,
+    /// Whether CSV rows end with `\r\n` instead of `\n`.
+    pub csv_crlf: bool,
+    /// The replacement of line feeds inside strings in CSV output. Line feeds are kept if `None`.
+    pub csv_newline_escape: Option<String>,
+}
+
+/// Quoting policies of strings in CSV output.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "kebab-case")]
+pub enum CsvQuote {
+    /// Quote every string (`"abc"`).
+    #[default]
+    Always,
+    /// Quote only the strings containing the delimiter, quotation marks or line breaks, and the
+    /// strings which could be mistaken as NULL.
+    Minimal,
+    /// Never quote strings, and write quotation marks as-is.
+    Never,
+}
+
+/// Behaviors of INSERT statements on rows conflicting with the existing data.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "kebab-case")]
+pub enum InsertMode {
+    /// Fail on conflicting rows (`INSERT`).
+    #[default]
+    Insert,
+    /// Skip the conflicting rows (`INSERT IGNORE`).
+    InsertIgnore,
+    /// Overwrite the conflicting rows (`REPLACE`).
+    Replace,
+}
+
+impl InsertMode {
+    /// The keyword starting an INSERT statement of this mode in the dialect.
+    ///
+    /// Returns `None` if the dialect does not support this mode.
+    pub fn keyword(self, dialect: Option<Dialect>) -> Option<&'static str> {
+        match (self, dialect) {
+            // PostgreSQL skips the conflicting rows with `ON CONFLICT DO NOTHING` instead.
+            (Self::Insert, _) | (Self::InsertIgnore, Some(Dialect::Postgres)) => Some("INSERT"),
+            (Self::InsertIgnore, Some(Dialect::Mysql) | None) => Some("INSERT IGNORE"),
+            (Self::InsertIgnore, Some(Dialect::Sqlite)) => Some("INSERT OR IGNORE"),
+            (Self::Replace, Some(Dialect::Mysql) | None) => Some("REPLACE"),
+            (Self::Replace, Some(Dialect::Sqlite)) => Some("INSERT OR REPLACE"),
+            (Self::InsertIgnore | Self::Replace, _) => None,
+        }
+    }
+}
+
+/// Clauses turning an INSERT statement into an upsert.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "kebab-case")]
+pub enum UpsertStyle {
+    /// MySQL `ON DUPLICATE KEY UPDATE c = VALUES(c)`.
+    OnDuplicateKeyUpdate,
+    /// PostgreSQL and SQLite `ON CONFLICT (k) DO UPDATE SET c = EXCLUDED.c`.
+    OnConflictDoUpdate,
+}
+
+impl UpsertStyle {
+    /// Checks whether the dialect supports this upsert clause. Every style is accepted if the
+    /// dialect is unknown.
+    pub fn is_supported_by(self, dialect: Option<Dialect>) -> bool {
+        matches!(
+            (self, dialect),
+            (_, None)
+                | (Self::OnDuplicateKeyUpdate, Some(Dialect::Mysql))
+                | (Self::OnConflictDoUpdate, Some(Dialect::Postgres | Dialect::Sqlite))
+        )
+    }
+}
+
+/// Representations of a time interval value.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "kebab-case")]
+pub enum IntervalFormat {
+    /// Standard SQL format (`1 02:30:00`).
+    #[default]
+    Sql,
+    /// ISO 8601 duration (`PT26H30M`).
+    Iso8601,
+    /// Total number of seconds (`95400`).
+    Seconds,
+    /// Total number of milliseconds (`95400000`).
+    Milliseconds,
+}
+
+/// Representations of a timestamp value.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "kebab-case")]
+pub enum TimestampFormat {
+    /// Local time without any time zone information (`2024-01-01 12:00:00`).
+    #[default]
+    Sql,
+    /// UTC time without any time zone information (`2024-01-01 04:00:00`).
+    Utc,
+    /// Local time with the UTC offset suffix (`2024-01-01 12:00:00+08:00`).
+    Offset,
+    /// RFC 3339 format, i.e. local time with a `T` separator and the UTC offset suffix
+    /// (`2024-01-01T12:00:00+08:00`).
+    Rfc3339,
+    /// Number of seconds since the Unix epoch (`1704081600`).
+    Seconds,
+    /// Number of milliseconds since the Unix epoch (`1704081600000`).
+    Milliseconds,
+}
+
+impl TimestampFormat {
+    /// The keyword to prefix a timestamp literal in PostgreSQL, if any.
+    fn postgres_keyword(self) -> &'static [u8] {
+        match self {
+            Self::Sql | Self::Utc => b"TIMESTAMP ",
+            Self::Offset | Self::Rfc3339 => b"TIMESTAMPTZ ",
+            Self::Seconds | Self::Milliseconds => b"",
+        }
+    }
+}
+
+/// Representations of a geometric point value.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "kebab-case")]
+pub enum GeometryFormat {
+    /// Well-known text (`'POINT(1.5 2)'`).
+    #[default]
+    Wkt,
+    /// Well-known binary, as a binary string.
+    Wkb,
+    /// Well-known text wrapped in a function call (`ST_GeomFromText('POINT(1.5 2)')`).
+    StGeomFromText,
+    /// Well-known binary wrapped in a function call (`ST_GeomFromWKB(X'…')`).
+    StGeomFromWkb,
+}
+
+impl GeometryFormat {
+    /// Whether the point is written in well-known binary.
+    fn is_binary(self) -> bool {
+        matches!(self, Self::Wkb | Self::StGeomFromWkb)
+    }
+}
+
+/// Encodes a point in the little-endian well-known binary format.
+fn point_wkb(x: f64, y: f64) -> [u8; 21] {
+    let mut wkb = [0; 21];
+    wkb[0] = 1;
+    wkb[1..5].copy_from_slice(&1_u32.to_le_bytes());
+    wkb[5..13].copy_from_slice(&x.to_le_bytes());
+    wkb[13..].copy_from_slice(&y.to_le_bytes());
+    wkb
+}
+
+/// SQL dialects of the target database.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "kebab-case")]
+pub enum Dialect {
+    /// MySQL and MariaDB.
+    Mysql,
+    /// PostgreSQL.
+    Postgres,
+    /// SQLite.
+    Sqlite,
+    /// Microsoft SQL Server.
+    Mssql,
+}
+
+/// Rewrites the quoted parts of an identifier or a qualified name by passing the unescaped
+/// content to `write_ident`.
+fn rewrite_quoted(name: &str, mut write_ident: impl FnMut(&mut String, &str)) -> String {
+    let mut res = String::with_capacity(name.len() + 2);
+    let mut chars = name.chars().peekable();
+    while let Some(c) = chars.next() {
+        let close = match c {
+            '`' | '"' => c,
+            '[' => ']',
+            _ => {
+                res.push(c);
+                continue;
+            }
+        };
+        let mut ident = String::new();
+        while let Some(c) = chars.next() {
+            if c == close {
+                if close == ']' || chars.peek() != Some(&close) {
+                    break;
+                }
+                chars.next();
+            }
+            ident.push(c);
+        }
+        write_ident(&mut res, &ident);
+    }
+    res
+}
+
+/// Removes the quotation marks from an identifier or a qualified name, e.g. `` `a``b` `` becomes
+/// ``a`b``.
+pub(crate) fn unquote(name: &str) -> String {
+    rewrite_quoted(name, String::push_str)
+}
+
+/// Finds the index of the column in the schema. The names are compared after unquoting, so
+/// `"a b"` and `a b` refer to the same column.
+pub(crate) fn find_column(schema: &Schema<'_>, name: &str) -> Option<usize> {
+    let name = unquote(name);
+    schema.column_names().position(|column| unquote(column) == name)
+}
+
+impl Dialect {
+    /// Writes a quoted identifier using the quotation marks of this dialect.
+    fn write_quoted_identifier(self, res: &mut String, ident: &str) {
+        let (open, close) = match self {
+            Self::Mysql => ('`', '`'),
+            Self::Postgres | Self::Sqlite => ('"', '"'),
+            Self::Mssql => ('[', ']'),
+        };
+        res.push(open);
+        for c in ident.chars() {
+            if c == close {
+                res.push(c);
+            }
+            res.push(c);
+        }
+        res.push(close);
+    }
+
+    /// Rewrites the quoted parts of an identifier or a qualified name using the quotation marks of
+    /// this dialect, e.g. `` `db`.`tbl` `` becomes `"db"."tbl"` in PostgreSQL.
+    ///
+    /// Unquoted parts are kept intact, since quoting them would make them case-sensitive.
+    pub fn requote(self, name: &str) -> String {
+        rewrite_quoted(name, |res, ident| self.write_quoted_identifier(res, ident))
+    }
+
+    /// The default strings printed for the TRUE and FALSE results.
+    pub fn boolean_strings(self) -> (&'static str, &'static str) {
+        match self {
+            Self::Postgres => ("TRUE", "FALSE"),
+            Self::Mysql | Self::Sqlite | Self::Mssql => ("1", "0"),
+        }
+    }
 }
 
 impl Default for Options {
@@ -60,13 +367,36 @@ impl Default for Options {
             true_string: Cow::Borrowed("1"),
             false_string: Cow::Borrowed("0"),
             null_string: Cow::Borrowed("NULL"),
+            interval_format: IntervalFormat::Sql,
+            timestamp_format: TimestampFormat::Sql,
+            timestamp_wrapper: None,
+            geometry_format: GeometryFormat::Wkt,
+            insert_mode: InsertMode::Insert,
+            upsert: None,
+            upsert_keys: Vec::new(),
+            upsert_columns: Vec::new(),
+            dialect: None,
+            column_widths: HashMap::new(),
+            csv_delimiter: b',',
+            csv_quote: CsvQuote::Always,
+            csv_crlf: false,
+            csv_newline_escape: None,
         }
     }
 }
 
 /// SQL formatter.
 #[derive(Debug)]
-pub struct SqlFormat<'a>(pub &'a Options);
+pub struct SqlFormat<'a> {
+    options: &'a Options,
+    /// The upsert clause following the rows, e.g. `ON CONFLICT (id) DO UPDATE SET v = EXCLUDED.v`.
+    /// Empty if `options.upsert` is `None`.
+    upsert_clause: String,
+}
+
+/// SQL formatter wrapped by the session settings and table locks of `mysqldump`.
+#[derive(Debug)]
+pub struct MySqlDumpFormat<'a>(pub &'a Options);
 
 /// CSV formatter.
 #[derive(Debug)]
@@ -76,35 +406,141 @@ pub struct CsvFormat<'a>(pub &'a Options);
 #[derive(Debug)]
 pub struct SqlInsertSetFormat<'a>(pub &'a Options);
 
-/// Writes a timestamp in ISO 8601 format.
-fn write_timestamp(writer: &mut dyn Write, quote: &str, timestamp: &DateTime<ArcTz>) -> Result<(), Error> {
+/// ClickHouse TabSeparated formatter.
+#[derive(Debug)]
+pub struct ClickHouseTsvFormat<'a>(pub &'a Options);
+
+/// PostgreSQL `COPY … FROM stdin` text formatter.
+#[derive(Debug)]
+pub struct PgCopyFormat<'a>(pub &'a Options);
+
+/// Fixed-width text formatter, padding or truncating every value to the width of its column.
+#[derive(Debug)]
+pub struct FixedWidthFormat<'a> {
+    options: &'a Options,
+    /// The unquoted names of every output column.
+    names: Vec<String>,
+    /// The widths of every output column, in characters.
+    widths: Vec<usize>,
+    /// The index of the column the next value belongs to.
+    column: Cell<usize>,
+}
+
+/// Debezium-style JSON formatter, writing every row as a change event record.
+#[derive(Debug)]
+pub struct DebeziumFormat<'a> {
+    options: &'a Options,
+    /// The unquoted table name.
+    table: String,
+}
+
+/// ClickHouse RowBinary formatter.
+#[derive(Debug)]
+pub struct ClickHouseRowBinaryFormat<'a> {
+    options: &'a Options,
+    /// The types of every column.
+    types: Vec<ClickHouseType>,
+    /// The index of the column the next value belongs to.
+    column: Cell<usize>,
+}
+
+/// Writes a timestamp in the given format.
+///
+/// The timestamp is given in UTC and converted to the time zone `tz` as needed. The quotes are
+/// omitted if the timestamp is written as a plain number.
+fn write_timestamp(
+    writer: &mut dyn Write,
+    quote: &str,
+    timestamp: &NaiveDateTime,
+    tz: &ArcTz,
+    format: TimestampFormat,
+) -> Result<(), Error> {
+    let (local, offset, separator) = match format {
+        TimestampFormat::Seconds | TimestampFormat::Milliseconds => {
+            // the time before the epoch is rounded down, e.g. -0.5s becomes -1.
+            let unit = if format == TimestampFormat::Seconds {
+                1_000_000
+            } else {
+                1_000
+            };
+            return write!(writer, "{}", epoch_micros(timestamp).div_euclid(unit));
+        }
+        TimestampFormat::Sql => (tz.from_utc_datetime(timestamp).naive_local(), None, b" "),
+        TimestampFormat::Utc => (*timestamp, None, b" "),
+        TimestampFormat::Offset | TimestampFormat::Rfc3339 => {
+            let local = tz.from_utc_datetime(timestamp);
+            let offset = local.offset().fix().local_minus_utc();
+            let separator = if format == TimestampFormat::Rfc3339 { b"T" } else { b" " };
+            (local.naive_local(), Some(offset), separator)
+        }
+    };
+    writer.write_all(quote.as_bytes())?;
+    write_date(writer, "", local.date())?;
+    writer.write_all(separator)?;
+    write_time(writer, "", local.time())?;
+    if let Some(offset) = offset {
+        let sign = if offset < 0 { '-' } else { '+' };
+        let offset = offset.unsigned_abs() / 60;
+        write!(writer, "{sign}{:02}:{:02}", offset / 60, offset % 60)?;
+    }
+    writer.write_all(quote.as_bytes())
+}
+
+/// Computes the number of microseconds since the Unix epoch of a UTC timestamp.
+fn epoch_micros(timestamp: &NaiveDateTime) -> i64 {
+    timestamp.and_utc().timestamp_micros()
+}
+
+/// Writes a date in ISO 8601 format.
+fn write_date(writer: &mut dyn Write, quote: &str, date: NaiveDate) -> Result<(), Error> {
+    write!(
+        writer,
+        "{quote}{:04}-{:02}-{:02}{quote}",
+        date.year(),
+        date.month(),
+        date.day()
+    )
+}
+
+/// Writes a time of day in ISO 8601 format.
+fn write_time(writer: &mut dyn Write, quote: &str, time: NaiveTime) -> Result<(), Error> {
     write!(
         writer,
-        "{}{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
-        quote,
-        timestamp.year(),
-        timestamp.month(),
-        timestamp.day(),
-        timestamp.hour(),
-        timestamp.minute(),
-        timestamp.second(),
+        "{quote}{:02}:{:02}:{:02}",
+        time.hour(),
+        time.minute(),
+        time.second()
     )?;
-    let ns = timestamp.nanosecond();
+    let ns = time.nanosecond();
     if ns != 0 {
         write!(writer, ".{:06}", ns / 1000)?;
     }
     writer.write_all(quote.as_bytes())
 }
 
-/// Writes a time interval in the standard SQL format.
-fn write_interval(writer: &mut dyn Write, quote: &str, mut interval: i64) -> Result<(), Error> {
+/// Writes the fractional part of a number, with trailing zeros removed.
+fn write_fraction(writer: &mut dyn Write, numerator: u64, denominator: u64, width: usize) -> Result<(), Error> {
+    let fraction = numerator % denominator;
+    if fraction != 0 {
+        let digits = format!("{fraction:0width$}");
+        write!(writer, ".{}", digits.trim_end_matches('0'))?;
+    }
+    Ok(())
+}
+
+/// Writes a time interval in the given format.
+///
+/// The quotes are omitted if the interval is written as a plain number.
+fn write_interval(writer: &mut dyn Write, quote: &str, interval: i64, format: IntervalFormat) -> Result<(), Error> {
+    let quote = match format {
+        IntervalFormat::Sql | IntervalFormat::Iso8601 => quote,
+        IntervalFormat::Seconds | IntervalFormat::Milliseconds => "",
+    };
     writer.write_all(quote.as_bytes())?;
-    if interval == i64::MIN {
-        return write!(writer, "-106751991 04:00:54.775808{quote}");
-    } else if interval < 0 {
-        interval = -interval;
+    if interval < 0 {
         writer.write_all(b"-")?;
     }
+    let interval = interval.unsigned_abs();
 
     let seconds = interval / 1_000_000;
     let microseconds = interval % 1_000_000;
@@ -115,23 +551,48 @@ fn write_interval(writer: &mut dyn Write, quote: &str, mut interval: i64) -> Res
     let hours = minutes / 60;
     let minutes = minutes % 60;
 
-    let days = hours / 24;
-    let hours = hours % 24;
-
-    if days > 0 {
-        write!(writer, "{days} ")?;
-    }
-    write!(writer, "{hours:02}:{minutes:02}:{seconds:02}")?;
-    if microseconds > 0 {
-        write!(writer, ".{microseconds:06}")?;
+    match format {
+        IntervalFormat::Sql => {
+            let days = hours / 24;
+            let hours = hours % 24;
+            if days > 0 {
+                write!(writer, "{days} ")?;
+            }
+            write!(writer, "{hours:02}:{minutes:02}:{seconds:02}")?;
+            if microseconds > 0 {
+                write!(writer, ".{microseconds:06}")?;
+            }
+        }
+        IntervalFormat::Iso8601 => {
+            writer.write_all(b"PT")?;
+            if hours > 0 {
+                write!(writer, "{hours}H")?;
+            }
+            if minutes > 0 {
+                write!(writer, "{minutes}M")?;
+            }
+            if seconds > 0 || microseconds > 0 || interval == 0 {
+                write!(writer, "{seconds}")?;
+                write_fraction(writer, microseconds, 1_000_000, 6)?;
+                writer.write_all(b"S")?;
+            }
+        }
+        IntervalFormat::Seconds => {
+            write!(writer, "{}", interval / 1_000_000)?;
+            write_fraction(writer, interval, 1_000_000, 6)?;
+        }
+        IntervalFormat::Milliseconds => {
+            write!(writer, "{}", interval / 1_000)?;
+            write_fraction(writer, interval, 1_000, 3)?;
+        }
     }
 
     writer.write_all(quote.as_bytes())
 }
 
 #[derive(Debug, Copy, Clone)]
-enum EscapeRule {
-    Escape(&'static [u8]),
+enum EscapeRule<'a> {
+    Escape(&'a [u8]),
     Unescape(u8),
 }
 
@@ -151,7 +612,7 @@ impl EscapeState {
         &bytes[self.prev_end..cur]
     }
 
-    fn apply_rule<'b>(&mut self, rule: &'b EscapeRule) -> &'b [u8] {
+    fn apply_rule<'b>(&mut self, rule: &'b EscapeRule<'b>) -> &'b [u8] {
         let ret = match rule {
             EscapeRule::Escape(replacement) => {
                 self.unescape_ready = false;
@@ -173,7 +634,7 @@ impl EscapeState {
     }
 }
 
-fn write_with_escape(writer: &mut dyn Write, bytes: &[u8], rules: &[(u8, EscapeRule)]) -> Result<(), Error> {
+fn write_with_escape(writer: &mut dyn Write, bytes: &[u8], rules: &[(u8, EscapeRule<'_>)]) -> Result<(), Error> {
     let mut state = EscapeState::default();
     match *rules {
         [] => {}
@@ -215,14 +676,37 @@ fn write_with_escape(writer: &mut dyn Write, bytes: &[u8], rules: &[(u8, EscapeR
 }
 
 impl Options {
+    /// The keyword starting an INSERT statement of the insert mode in the dialect.
+    ///
+    /// An unsupported combination falls back to a plain `INSERT`, but the CLI rejects it upfront.
+    fn insert_keyword(&self) -> &'static str {
+        self.insert_mode.keyword(self.dialect).unwrap_or("INSERT")
+    }
+
+    /// Writes a binary string literal using the syntax of the dialect.
+    fn write_sql_binary(&self, writer: &mut dyn Write, bytes: &[u8]) -> Result<(), Error> {
+        let (prefix, suffix): (&[u8], &[u8]) = match self.dialect {
+            Some(Dialect::Postgres) => (br"'\x", b"'"),
+            Some(Dialect::Mssql) => (b"0x", b""),
+            Some(Dialect::Mysql | Dialect::Sqlite) | None => (b"X'", b"'"),
+        };
+        writer.write_all(prefix)?;
+        for b in bytes {
+            write!(writer, "{b:02X}")?;
+        }
+        writer.write_all(suffix)
+    }
+
     fn write_sql_bytes(&self, writer: &mut dyn Write, bytes: &ByteString) -> Result<(), Error> {
         if bytes.encoding() == Encoding::Binary {
-            writer.write_all(b"X'")?;
-            for b in bytes.as_bytes() {
-                write!(writer, "{b:02X}")?;
-            }
+            self.write_sql_binary(writer, bytes.as_bytes())
         } else {
-            writer.write_all(b"'")?;
+            let open_quote: &[u8] = if self.dialect == Some(Dialect::Mssql) {
+                b"N'"
+            } else {
+                b"'"
+            };
+            writer.write_all(open_quote)?;
             write_with_escape(
                 writer,
                 bytes.as_bytes(),
@@ -236,8 +720,93 @@ impl Options {
                     &[(b'\'', EscapeRule::Escape(b"''"))]
                 },
             )?;
+            writer.write_all(b"'")
+        }
+    }
+
+    /// Writes a change event as an SQL statement. Inserts are written as a single-row statement of
+    /// the `format`, while updates and deletes identify the row by the value of its key column.
+    fn write_sql_change(
+        &self,
+        format: &dyn Format,
+        writer: &mut dyn Write,
+        schema: &Schema<'_>,
+        change: &Change<'_>,
+    ) -> Result<(), Error> {
+        match (change.op, change.after) {
+            (ChangeOp::Insert, Some(after)) => {
+                format.write_header(writer, schema)?;
+                let mut is_first = true;
+                for (i, (column, value)) in schema.column_names().zip(after).enumerate() {
+                    if !schema.is_output_column(i) {
+                        continue;
+                    }
+                    if !mem::take(&mut is_first) {
+                        format.write_value_separator(writer)?;
+                    }
+                    format.write_value_header(writer, column)?;
+                    format.write_value(writer, value)?;
+                }
+                return format.write_trailer(writer);
+            }
+            (ChangeOp::Update, Some(after)) => {
+                write!(writer, "UPDATE {} SET ", schema.name)?;
+                let mut is_first = true;
+                for (i, (column, value)) in schema.column_names().zip(after).enumerate() {
+                    if i == change.key_column || !schema.is_output_column(i) {
+                        continue;
+                    }
+                    if !mem::take(&mut is_first) {
+                        writer.write_all(b", ")?;
+                    }
+                    write!(writer, "{column} = ")?;
+                    self.write_sql_value(writer, value)?;
+                }
+            }
+            _ => write!(writer, "DELETE FROM {}", schema.name)?,
+        }
+        let column = schema.column_names().nth(change.key_column).unwrap_or_default();
+        write!(writer, " WHERE {column} = ")?;
+        let key = change.before.and_then(|before| before.get(change.key_column));
+        self.write_sql_value(writer, key.unwrap_or(&Value::Null))?;
+        writer.write_all(b";\n")
+    }
+
+    /// Writes the statement starting a transaction.
+    fn write_begin_transaction(&self, writer: &mut dyn Write) -> Result<(), Error> {
+        let statement: &[u8] = if self.dialect == Some(Dialect::Mssql) {
+            b"BEGIN TRANSACTION;\n"
+        } else {
+            b"BEGIN;\n"
+        };
+        writer.write_all(statement)
+    }
+
+    /// Writes a point in SQL format.
+    fn write_sql_point(&self, writer: &mut dyn Write, x: f64, y: f64) -> Result<(), Error> {
+        match self.geometry_format {
+            GeometryFormat::Wkt => write!(writer, "'POINT({x} {y})'"),
+            GeometryFormat::Wkb => self.write_sql_binary(writer, &point_wkb(x, y)),
+            GeometryFormat::StGeomFromText => write!(writer, "ST_GeomFromText('POINT({x} {y})')"),
+            GeometryFormat::StGeomFromWkb => {
+                writer.write_all(b"ST_GeomFromWKB(")?;
+                self.write_sql_binary(writer, &point_wkb(x, y))?;
+                writer.write_all(b")")
+            }
+        }
+    }
+
+    /// Writes a point in the text formats, either as well-known text or as hex-encoded well-known
+    /// binary.
+    fn write_text_point(&self, writer: &mut dyn Write, x: f64, y: f64) -> Result<(), Error> {
+        if self.geometry_format.is_binary() {
+            for b in point_wkb(x, y) {
+                write!(writer, "{b:02X}")?;
+            }
+            Ok(())
+        } else {
+            write!(writer, "POINT({x} {y})")
         }
-        writer.write_all(b"'")
     }
 
     /// Writes a value in SQL format.
@@ -246,8 +815,33 @@ impl Options {
             Value::Null => writer.write_all(self.null_string.as_bytes()),
             Value::Number(number) => number.write_io(writer, &self.true_string, &self.false_string),
             Value::Bytes(bytes) => self.write_sql_bytes(writer, bytes),
-            Value::Timestamp(timestamp, tz) => write_timestamp(writer, "'", &tz.from_utc_datetime(timestamp)),
-            Value::Interval(interval) => write_interval(writer, "'", *interval),
+            Value::Timestamp(timestamp, tz) => {
+                if let Some((prefix, suffix)) = &self.timestamp_wrapper {
+                    writer.write_all(prefix.as_bytes())?;
+                    write_timestamp(writer, "", timestamp, tz, self.timestamp_format)?;
+                    return writer.write_all(suffix.as_bytes());
+                }
+                if self.dialect == Some(Dialect::Postgres) {
+                    writer.write_all(self.timestamp_format.postgres_keyword())?;
+                }
+                write_timestamp(writer, "'", timestamp, tz, self.timestamp_format)
+            }
+            Value::Date(date) => {
+                if self.dialect == Some(Dialect::Postgres) {
+                    writer.write_all(b"DATE ")?;
+                }
+                write_date(writer, "'", *date)
+            }
+            Value::Time(time) => {
+                if self.dialect == Some(Dialect::Postgres) {
+                    writer.write_all(b"TIME ")?;
+                }
+                write_time(writer, "'", *time)
+            }
+            Value::Interval(interval) => write_interval(writer, "'", *interval, self.interval_format),
+            Value::Point(x, y) => self.write_sql_point(writer, *x, *y),
+            Value::Json(json) => self.write_sql_bytes(writer, &json.to_string().into()),
+            Value::Raw(raw) => writer.write_all(raw.as_bytes()),
             Value::Array(array) => {
                 writer.write_all(b"ARRAY[")?;
                 for (i, item) in array.iter().enumerate() {
@@ -262,9 +856,106 @@ impl Options {
     }
 }
 
+impl<'a> SqlFormat<'a> {
+    /// Creates a new SQL formatter. If `options.upsert` is set, the upsert clause uses the key
+    /// columns and the updated columns of the options which are present in the schema.
+    ///
+    /// Returns the name of the key column if it is missing from the schema.
+    pub fn new(options: &'a Options, schema: &Schema<'_>) -> Result<Self, String> {
+        let upsert_clause = match options.upsert {
+            None => String::new(),
+            Some(style) => upsert_clause(options, schema, style)?,
+        };
+        Ok(Self { options, upsert_clause })
+    }
+
+    /// Creates a new SQL formatter without any upsert clause.
+    fn plain(options: &'a Options) -> Self {
+        Self {
+            options,
+            upsert_clause: String::new(),
+        }
+    }
+}
+
+/// Builds the clause turning an INSERT statement of the table into an upsert.
+///
+/// The columns are looked up by their unquoted names, and written using the quotation marks of
+/// the dialect.
+fn upsert_clause(options: &Options, schema: &Schema<'_>, style: UpsertStyle) -> Result<String, String> {
+    use std::fmt::Write;
+
+    let column_name = |index: usize| {
+        let name = schema.column_names().nth(index).unwrap_or_default();
+        options
+            .dialect
+            .map_or_else(|| name.to_owned(), |dialect| dialect.requote(name))
+    };
+    let keys = options
+        .upsert_keys
+        .iter()
+        .map(|key| find_column(schema, key).ok_or_else(|| key.clone()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let output_columns = schema.output_column_indices().collect::<Vec<_>>();
+    let update_columns = if options.upsert_columns.is_empty() {
+        output_columns
+            .iter()
+            .copied()
+            .filter(|column| !keys.contains(column))
+            .map(column_name)
+            .collect::<Vec<_>>()
+    } else {
+        options
+            .upsert_columns
+            .iter()
+            .filter_map(|column| find_column(schema, column))
+            .filter(|column| output_columns.contains(column))
+            .map(column_name)
+            .collect()
+    };
+
+    let mut clause = String::new();
+    match style {
+        UpsertStyle::OnDuplicateKeyUpdate => {
+            clause.push_str("ON DUPLICATE KEY UPDATE ");
+            if update_columns.is_empty() {
+                // updating a key column to itself is the conventional no-op, which keeps the
+                // existing row intact.
+                let column = keys
+                    .first()
+                    .or(output_columns.first())
+                    .map_or_else(String::new, |&i| column_name(i));
+                write!(clause, "{column} = {column}").unwrap();
+            }
+            for (i, column) in update_columns.iter().enumerate() {
+                if i != 0 {
+                    clause.push_str(", ");
+                }
+                write!(clause, "{column} = VALUES({column})").unwrap();
+            }
+        }
+        UpsertStyle::OnConflictDoUpdate => {
+            let keys = keys.into_iter().map(column_name).collect::<Vec<_>>();
+            write!(clause, "ON CONFLICT ({}) ", keys.join(", ")).unwrap();
+            if update_columns.is_empty() {
+                clause.push_str("DO NOTHING");
+            } else {
+                clause.push_str("DO UPDATE SET ");
+            }
+            for (i, column) in update_columns.iter().enumerate() {
+                if i != 0 {
+                    clause.push_str(", ");
+                }
+                write!(clause, "{column} = EXCLUDED.{column}").unwrap();
+            }
+        }
+    }
+    Ok(clause)
+}
+
 impl Format for SqlFormat<'_> {
     fn write_value(&self, writer: &mut dyn Write, value: &Value) -> Result<(), Error> {
-        self.0.write_sql_value(writer, value)
+        self.options.write_sql_value(writer, value)
     }
 
     fn write_file_header(&self, _: &mut dyn Write, _: &Schema<'_>) -> Result<(), Error> {
@@ -272,10 +963,10 @@ impl Format for SqlFormat<'_> {
     }
 
     fn write_header(&self, writer: &mut dyn Write, schema: &Schema<'_>) -> Result<(), Error> {
-        write!(writer, "INSERT INTO {} ", schema.name)?;
-        if self.0.headers {
+        write!(writer, "{} INTO {} ", self.options.insert_keyword(), schema.name)?;
+        if self.options.headers {
             writer.write_all(b"(")?;
-            for (i, col) in schema.column_names().enumerate() {
+            for (i, col) in schema.output_column_names().enumerate() {
                 if i != 0 {
                     writer.write_all(b", ")?;
                 }
@@ -299,8 +990,30 @@ impl Format for SqlFormat<'_> {
     }
 
     fn write_trailer(&self, writer: &mut dyn Write) -> Result<(), Error> {
+        if self.options.upsert.is_some() {
+            return writeln!(writer, ")\n{};", self.upsert_clause);
+        }
+        if self.options.insert_mode == InsertMode::InsertIgnore && self.options.dialect == Some(Dialect::Postgres) {
+            return writer.write_all(b")\nON CONFLICT DO NOTHING;\n");
+        }
         writer.write_all(b");\n")
     }
+
+    fn write_file_trailer(&self, _: &mut dyn Write, _: &Schema<'_>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write_group_header(&self, writer: &mut dyn Write) -> Result<(), Error> {
+        self.options.write_begin_transaction(writer)
+    }
+
+    fn write_group_trailer(&self, writer: &mut dyn Write) -> Result<(), Error> {
+        writer.write_all(b"COMMIT;\n")
+    }
+
+    fn write_change(&self, writer: &mut dyn Write, schema: &Schema<'_>, change: &Change<'_>) -> Result<(), Error> {
+        self.options.write_sql_change(self, writer, schema, change)
+    }
 }
 
 impl Format for SqlInsertSetFormat<'_> {
@@ -313,7 +1026,7 @@ impl Format for SqlInsertSetFormat<'_> {
     }
 
     fn write_header(&self, writer: &mut dyn Write, schema: &Schema<'_>) -> Result<(), Error> {
-        writeln!(writer, "INSERT INTO {} SET", schema.name)
+        writeln!(writer, "{} INTO {} SET", self.0.insert_keyword(), schema.name)
     }
 
     fn write_value_header(&self, writer: &mut dyn Write, column: &str) -> Result<(), Error> {
@@ -331,39 +1044,131 @@ impl Format for SqlInsertSetFormat<'_> {
     fn write_trailer(&self, writer: &mut dyn Write) -> Result<(), Error> {
         writer.write_all(b";\n\n")
     }
+
+    fn write_file_trailer(&self, _: &mut dyn Write, _: &Schema<'_>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write_group_header(&self, writer: &mut dyn Write) -> Result<(), Error> {
+        self.0.write_begin_transaction(writer)
+    }
+
+    fn write_group_trailer(&self, writer: &mut dyn Write) -> Result<(), Error> {
+        writer.write_all(b"COMMIT;\n")
+    }
+
+    fn write_change(&self, writer: &mut dyn Write, schema: &Schema<'_>, change: &Change<'_>) -> Result<(), Error> {
+        self.0.write_sql_change(self, writer, schema, change)
+    }
 }
 
-impl CsvFormat<'_> {
-    fn write_bytes(&self, writer: &mut dyn Write, bytes: &ByteString) -> Result<(), Error> {
-        writer.write_all(b"\"")?;
-        write_with_escape(
-            writer,
-            bytes.as_bytes(),
-            if self.0.escape_backslash {
-                &[(b'"', EscapeRule::Escape(b"\"\"")), (b'\\', EscapeRule::Escape(br"\\"))]
-            } else {
-                &[(b'"', EscapeRule::Escape(b"\"\""))]
-            },
+impl Format for MySqlDumpFormat<'_> {
+    fn write_value(&self, writer: &mut dyn Write, value: &Value) -> Result<(), Error> {
+        self.0.write_sql_value(writer, value)
+    }
+
+    fn write_file_header(&self, writer: &mut dyn Write, schema: &Schema<'_>) -> Result<(), Error> {
+        writer.write_all(
+            b"/*!40101 SET @OLD_CHARACTER_SET_CLIENT=@@CHARACTER_SET_CLIENT */;\n\
+            /*!40101 SET NAMES utf8mb4 */;\n\
+            /*!40103 SET @OLD_TIME_ZONE=@@TIME_ZONE */;\n\
+            /*!40103 SET TIME_ZONE='+00:00' */;\n\
+            /*!40014 SET @OLD_UNIQUE_CHECKS=@@UNIQUE_CHECKS, UNIQUE_CHECKS=0 */;\n\
+            /*!40014 SET @OLD_FOREIGN_KEY_CHECKS=@@FOREIGN_KEY_CHECKS, FOREIGN_KEY_CHECKS=0 */;\n\
+            /*!40101 SET @OLD_SQL_MODE=@@SQL_MODE, SQL_MODE='NO_AUTO_VALUE_ON_ZERO' */;\n\n",
         )?;
-        writer.write_all(b"\"")
+        writeln!(writer, "LOCK TABLES {} WRITE;", schema.name)?;
+        writeln!(writer, "/*!40000 ALTER TABLE {} DISABLE KEYS */;", schema.name)
     }
 
-    fn write_column_name(&self, writer: &mut dyn Write, name: &[u8]) -> Result<(), Error> {
-        writer.write_all(b"\"")?;
-        let (mut rules, name) = match name.first() {
-            Some(b'"') => (Vec::new(), &name[1..(name.len() - 1)]),
-            Some(b'`') => (
-                vec![(b'`', EscapeRule::Unescape(b'`')), (b'"', EscapeRule::Escape(b"\"\""))],
-                &name[1..(name.len() - 1)],
-            ),
-            Some(b'[') => (vec![(b'"', EscapeRule::Escape(b"\"\""))], &name[1..(name.len() - 1)]),
-            _ => (Vec::new(), name),
+    fn write_header(&self, writer: &mut dyn Write, schema: &Schema<'_>) -> Result<(), Error> {
+        SqlFormat::plain(self.0).write_header(writer, schema)
+    }
+
+    fn write_value_header(&self, _: &mut dyn Write, _: &str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write_value_separator(&self, writer: &mut dyn Write) -> Result<(), Error> {
+        SqlFormat::plain(self.0).write_value_separator(writer)
+    }
+
+    fn write_row_separator(&self, writer: &mut dyn Write) -> Result<(), Error> {
+        SqlFormat::plain(self.0).write_row_separator(writer)
+    }
+
+    fn write_trailer(&self, writer: &mut dyn Write) -> Result<(), Error> {
+        SqlFormat::plain(self.0).write_trailer(writer)
+    }
+
+    fn write_file_trailer(&self, writer: &mut dyn Write, schema: &Schema<'_>) -> Result<(), Error> {
+        writeln!(writer, "/*!40000 ALTER TABLE {} ENABLE KEYS */;", schema.name)?;
+        writer.write_all(
+            b"UNLOCK TABLES;\n\n\
+            /*!40101 SET SQL_MODE=@OLD_SQL_MODE */;\n\
+            /*!40014 SET FOREIGN_KEY_CHECKS=@OLD_FOREIGN_KEY_CHECKS */;\n\
+            /*!40014 SET UNIQUE_CHECKS=@OLD_UNIQUE_CHECKS */;\n\
+            /*!40103 SET TIME_ZONE=@OLD_TIME_ZONE */;\n\
+            /*!40101 SET CHARACTER_SET_CLIENT=@OLD_CHARACTER_SET_CLIENT */;\n",
+        )
+    }
+
+    fn write_group_header(&self, writer: &mut dyn Write) -> Result<(), Error> {
+        self.0.write_begin_transaction(writer)
+    }
+
+    fn write_group_trailer(&self, writer: &mut dyn Write) -> Result<(), Error> {
+        writer.write_all(b"COMMIT;\n")
+    }
+
+    fn write_change(&self, writer: &mut dyn Write, schema: &Schema<'_>, change: &Change<'_>) -> Result<(), Error> {
+        self.0.write_sql_change(self, writer, schema, change)
+    }
+}
+
+impl CsvFormat<'_> {
+    /// Whether a string must be quoted under the minimal quoting policy, i.e. it contains the
+    /// delimiter, a quotation mark or a line break, or could be mistaken as NULL. Line feeds do
+    /// not need quoting when they are replaced by `--csv-newline-escape`.
+    fn needs_quotes(&self, bytes: &[u8]) -> bool {
+        let is_newline_escaped = self.0.csv_newline_escape.is_some();
+        bytes.is_empty()
+            || bytes == self.0.null_string.as_bytes()
+            || bytes
+                .iter()
+                .any(|&b| b == self.0.csv_delimiter || b == b'"' || b == b'\r' || (b == b'\n' && !is_newline_escaped))
+    }
+
+    fn write_bytes(&self, writer: &mut dyn Write, bytes: &[u8]) -> Result<(), Error> {
+        let is_quoted = match self.0.csv_quote {
+            CsvQuote::Always => true,
+            CsvQuote::Minimal => self.needs_quotes(bytes),
+            CsvQuote::Never => false,
         };
+        let mut rules = Vec::with_capacity(3);
+        if is_quoted {
+            writer.write_all(b"\"")?;
+            rules.push((b'"', EscapeRule::Escape(b"\"\"")));
+        }
         if self.0.escape_backslash {
             rules.push((b'\\', EscapeRule::Escape(br"\\")));
         }
-        write_with_escape(writer, name, &rules)?;
-        writer.write_all(b"\"")
+        if let Some(newline_escape) = &self.0.csv_newline_escape {
+            rules.push((b'\n', EscapeRule::Escape(newline_escape.as_bytes())));
+        }
+        write_with_escape(writer, bytes, &rules)?;
+        if is_quoted {
+            writer.write_all(b"\"")?;
+        }
+        Ok(())
+    }
+
+    fn line_terminator(&self) -> &'static [u8] {
+        if self.0.csv_crlf {
+            b"\r\n"
+        } else {
+            b"\n"
+        }
     }
 }
 
@@ -372,9 +1177,21 @@ impl Format for CsvFormat<'_> {
         match value {
             Value::Null => writer.write_all(self.0.null_string.as_bytes()),
             Value::Number(number) => number.write_io(writer, &self.0.true_string, &self.0.false_string),
-            Value::Bytes(bytes) => self.write_bytes(writer, bytes),
-            Value::Timestamp(timestamp, tz) => write_timestamp(writer, "", &tz.from_utc_datetime(timestamp)),
-            Value::Interval(interval) => write_interval(writer, "", *interval),
+            Value::Bytes(bytes) => self.write_bytes(writer, bytes.as_bytes()),
+            Value::Timestamp(timestamp, tz) => {
+                if let Some((prefix, suffix)) = &self.0.timestamp_wrapper {
+                    writer.write_all(prefix.as_bytes())?;
+                    write_timestamp(writer, "", timestamp, tz, self.0.timestamp_format)?;
+                    return writer.write_all(suffix.as_bytes());
+                }
+                write_timestamp(writer, "", timestamp, tz, self.0.timestamp_format)
+            }
+            Value::Date(date) => write_date(writer, "", *date),
+            Value::Time(time) => write_time(writer, "", *time),
+            Value::Interval(interval) => write_interval(writer, "", *interval, self.0.interval_format),
+            Value::Json(json) => self.write_bytes(writer, json.to_string().as_bytes()),
+            Value::Raw(raw) => writer.write_all(raw.as_bytes()),
+            Value::Point(x, y) => self.0.write_text_point(writer, *x, *y),
             Value::Array(array) => {
                 writer.write_all(b"{")?;
                 for (i, item) in array.iter().enumerate() {
@@ -392,11 +1209,11 @@ impl Format for CsvFormat<'_> {
         if !self.0.headers {
             return Ok(());
         }
-        for (i, col) in schema.column_names().enumerate() {
+        for (i, col) in schema.output_column_names().enumerate() {
             if i != 0 {
                 self.write_value_separator(writer)?;
             }
-            self.write_column_name(writer, col.as_bytes())?;
+            self.write_bytes(writer, unquote(col).as_bytes())?;
         }
         self.write_row_separator(writer)
     }
@@ -406,7 +1223,7 @@ impl Format for CsvFormat<'_> {
     }
 
     fn write_value_separator(&self, writer: &mut dyn Write) -> Result<(), Error> {
-        writer.write_all(b",")
+        writer.write_all(&[self.0.csv_delimiter])
     }
 
     fn write_value_header(&self, _: &mut dyn Write, _: &str) -> Result<(), Error> {
@@ -414,17 +1231,844 @@ impl Format for CsvFormat<'_> {
     }
 
     fn write_row_separator(&self, writer: &mut dyn Write) -> Result<(), Error> {
-        writer.write_all(b"\n")
+        writer.write_all(self.line_terminator())
     }
 
     fn write_trailer(&self, writer: &mut dyn Write) -> Result<(), Error> {
-        writer.write_all(b"\n")
+        writer.write_all(self.line_terminator())
+    }
+
+    fn write_file_trailer(&self, _: &mut dyn Write, _: &Schema<'_>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write_group_header(&self, _: &mut dyn Write) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write_group_trailer(&self, _: &mut dyn Write) -> Result<(), Error> {
+        Ok(())
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Writes a string with the escaping rules of ClickHouse text formats.
+fn write_clickhouse_bytes(writer: &mut dyn Write, bytes: &ByteString, quote: &[u8]) -> Result<(), Error> {
+    const RULES: [(u8, EscapeRule<'static>); 5] = [
+        (b'\\', EscapeRule::Escape(br"\\")),
+        (b'\t', EscapeRule::Escape(br"\t")),
+        (b'\n', EscapeRule::Escape(br"\n")),
+        (b'\r', EscapeRule::Escape(br"\r")),
+        (b'\0', EscapeRule::Escape(br"\0")),
+    ];
+    writer.write_all(quote)?;
+    if quote.is_empty() {
+        write_with_escape(writer, bytes.as_bytes(), &RULES)?;
+    } else {
+        let mut rules = RULES.to_vec();
+        rules.push((b'\'', EscapeRule::Escape(br"\'")));
+        write_with_escape(writer, bytes.as_bytes(), &rules)?;
+    }
+    writer.write_all(quote)
+}
+
+impl ClickHouseTsvFormat<'_> {
+    /// Writes an array element, which follows the syntax of the Values format.
+    fn write_element(&self, writer: &mut dyn Write, value: &Value) -> Result<(), Error> {
+        match value {
+            Value::Null => writer.write_all(b"NULL"),
+            Value::Bytes(bytes) | Value::Raw(bytes) => write_clickhouse_bytes(writer, bytes, b"'"),
+            Value::Timestamp(timestamp, tz) => write_timestamp(writer, "'", timestamp, tz, self.0.timestamp_format),
+            Value::Date(date) => write_date(writer, "'", *date),
+            Value::Time(time) => write_time(writer, "'", *time),
+            Value::Interval(interval) => write_interval(writer, "'", *interval, self.0.interval_format),
+            Value::Json(json) => write_clickhouse_bytes(writer, &json.to_string().into(), b"'"),
+            _ => self.write_value(writer, value),
+        }
+    }
+}
+
+/// Writes the `INSERT INTO … FORMAT …` statement preceding the ClickHouse data.
+fn write_clickhouse_insert(
+    writer: &mut dyn Write,
+    options: &Options,
+    schema: &Schema<'_>,
+    format: &str,
+) -> Result<(), Error> {
+    write!(writer, "INSERT INTO {} ", schema.name)?;
+    if options.headers {
+        writer.write_all(b"(")?;
+        for (i, col) in schema.output_column_names().enumerate() {
+            if i != 0 {
+                writer.write_all(b", ")?;
+            }
+            writer.write_all(col.as_bytes())?;
+        }
+        writer.write_all(b") ")?;
+    }
+    writeln!(writer, "FORMAT {format}")
+}
+
+impl Format for ClickHouseTsvFormat<'_> {
+    fn write_value(&self, writer: &mut dyn Write, value: &Value) -> Result<(), Error> {
+        match value {
+            Value::Null => writer.write_all(self.0.null_string.as_bytes()),
+            Value::Number(number) => number.write_io(writer, &self.0.true_string, &self.0.false_string),
+            Value::Bytes(bytes) | Value::Raw(bytes) => write_clickhouse_bytes(writer, bytes, b""),
+            Value::Timestamp(timestamp, tz) => write_timestamp(writer, "", timestamp, tz, self.0.timestamp_format),
+            Value::Date(date) => write_date(writer, "", *date),
+            Value::Time(time) => write_time(writer, "", *time),
+            Value::Interval(interval) => write_interval(writer, "", *interval, self.0.interval_format),
+            Value::Json(json) => write_clickhouse_bytes(writer, &json.to_string().into(), b""),
+            Value::Point(x, y) => self.0.write_text_point(writer, *x, *y),
+            Value::Array(array) => {
+                writer.write_all(b"[")?;
+                for (i, item) in array.iter().enumerate() {
+                    if i != 0 {
+                        writer.write_all(b",")?;
+                    }
+                    self.write_element(writer, &item)?;
+                }
+                writer.write_all(b"]")
+            }
+        }
+    }
+
+    fn write_file_header(&self, writer: &mut dyn Write, schema: &Schema<'_>) -> Result<(), Error> {
+        write_clickhouse_insert(writer, self.0, schema, "TabSeparated")
+    }
+
+    fn write_header(&self, _: &mut dyn Write, _: &Schema<'_>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write_value_header(&self, _: &mut dyn Write, _: &str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write_value_separator(&self, writer: &mut dyn Write) -> Result<(), Error> {
+        writer.write_all(b"\t")
+    }
+
+    fn write_row_separator(&self, writer: &mut dyn Write) -> Result<(), Error> {
+        writer.write_all(b"\n")
+    }
+
+    fn write_trailer(&self, writer: &mut dyn Write) -> Result<(), Error> {
+        writer.write_all(b"\n")
+    }
+
+    fn write_file_trailer(&self, _: &mut dyn Write, _: &Schema<'_>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write_group_header(&self, _: &mut dyn Write) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write_group_trailer(&self, _: &mut dyn Write) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Data types supported by the ClickHouse RowBinary format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ClickHouseType {
+    /// `Int8` to `Int128` and `UInt8` to `UInt128`, with the number of bytes.
+    Int {
+        bytes: usize,
+        signed: bool,
+    },
+    Float32,
+    Float64,
+    Bool,
+    String,
+    FixedString(usize),
+    Date,
+    Date32,
+    DateTime,
+    /// `DateTime64`, with the number of decimal places in the fractional seconds.
+    DateTime64(u32),
+    Uuid,
+    Nullable(Box<Self>),
+    Array(Box<Self>),
+}
+
+impl ClickHouseType {
+    /// Parses a type at the beginning of the input, and returns the remaining input.
+    ///
+    /// Wrappers which do not affect the RowBinary encoding (`LowCardinality`) are removed, and
+    /// extra arguments like the time zone of `DateTime` are ignored.
+    fn parse_prefix(input: &str) -> Option<(Self, &str)> {
+        let input = input.trim_start();
+        let name_len = input
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .unwrap_or(input.len());
+        let (name, mut rest) = input.split_at(name_len);
+        let mut args = "";
+        if let Some(r) = rest.trim_start().strip_prefix('(') {
+            let mut depth = 0;
+            let mut in_quote = false;
+            let end = r.find(|c| {
+                match c {
+                    '\'' => in_quote = !in_quote,
+                    '(' if !in_quote => depth += 1,
+                    ')' if !in_quote => {
+                        if depth == 0 {
+                            return true;
+                        }
+                        depth -= 1;
+                    }
+                    _ => {}
+                }
+                false
+            })?;
+            args = &r[..end];
+            rest = &r[(end + 1)..];
+        }
+
+        let ty = match name {
+            "Int8" => Self::Int { bytes: 1, signed: true },
+            "Int16" => Self::Int { bytes: 2, signed: true },
+            "Int32" => Self::Int { bytes: 4, signed: true },
+            "Int64" => Self::Int { bytes: 8, signed: true },
+            "Int128" => Self::Int {
+                bytes: 16,
+                signed: true,
+            },
+            "UInt8" => Self::Int {
+                bytes: 1,
+                signed: false,
+            },
+            "UInt16" => Self::Int {
+                bytes: 2,
+                signed: false,
+            },
+            "UInt32" => Self::Int {
+                bytes: 4,
+                signed: false,
+            },
+            "UInt64" => Self::Int {
+                bytes: 8,
+                signed: false,
+            },
+            "UInt128" => Self::Int {
+                bytes: 16,
+                signed: false,
+            },
+            "Float32" => Self::Float32,
+            "Float64" => Self::Float64,
+            "Bool" => Self::Bool,
+            "String" => Self::String,
+            "FixedString" => Self::FixedString(args.trim().parse().ok()?),
+            "Date" => Self::Date,
+            "Date32" => Self::Date32,
+            "DateTime" => Self::DateTime,
+            "DateTime64" => Self::DateTime64(args.split(',').next()?.trim().parse().ok().filter(|p| *p <= 9)?),
+            "UUID" => Self::Uuid,
+            "Nullable" => Self::Nullable(Box::new(Self::parse(args)?)),
+            "LowCardinality" => Self::parse(args)?,
+            "Array" => Self::Array(Box::new(Self::parse(args)?)),
+            _ => return None,
+        };
+        Some((ty, rest))
+    }
+
+    /// Parses the entire input as a type.
+    fn parse(input: &str) -> Option<Self> {
+        let (ty, rest) = Self::parse_prefix(input)?;
+        rest.trim().is_empty().then_some(ty)
+    }
+}
+
+/// Writes an unsigned LEB128 integer.
+fn write_varint(writer: &mut dyn Write, mut value: u64) -> Result<(), Error> {
+    loop {
+        // ALLOW_REASON: the value is masked to 7 bits.
+        #[allow(clippy::cast_possible_truncation)]
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            return writer.write_all(&[byte]);
+        }
+        writer.write_all(&[byte | 0x80])?;
+    }
+}
+
+impl<'a> ClickHouseRowBinaryFormat<'a> {
+    /// Creates a new RowBinary formatter, using the column types of the schema.
+    ///
+    /// Returns the column type which is not supported if the schema cannot be written in RowBinary.
+    pub fn new(options: &'a Options, schema: &Schema<'_>) -> Result<Self, String> {
+        let types = schema
+            .output_column_types()
+            .map(|ty| {
+                ClickHouseType::parse_prefix(ty)
+                    .map(|r| r.0)
+                    .ok_or_else(|| ty.to_owned())
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            options,
+            types,
+            column: Cell::new(0),
+        })
+    }
+
+    fn write_typed_value(&self, writer: &mut dyn Write, ty: &ClickHouseType, value: &Value) -> Result<(), Error> {
+        let mismatch = || {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("cannot write {value} as {ty:?} in RowBinary"),
+            )
+        };
+
+        match (ty, value) {
+            (ClickHouseType::Nullable(_), Value::Null) => writer.write_all(&[1]),
+            (ClickHouseType::Nullable(inner), _) => {
+                writer.write_all(&[0])?;
+                self.write_typed_value(writer, inner, value)
+            }
+            (ClickHouseType::Int { bytes, signed }, Value::Number(n)) => {
+                let v = i128::try_from(*n).map_err(|_| mismatch())?;
+                let bits = 8 * bytes;
+                let in_range = if *signed {
+                    bits == 128 || (-(1 << (bits - 1))..(1 << (bits - 1))).contains(&v)
+                } else {
+                    v >= 0 && (bits == 128 || v < (1 << bits))
+                };
+                if !in_range {
+                    return Err(mismatch());
+                }
+                writer.write_all(&v.to_le_bytes()[..*bytes])
+            }
+            // ALLOW_REASON: the precision loss is expected.
+            #[allow(clippy::cast_possible_truncation)]
+            (ClickHouseType::Float32, Value::Number(n)) => writer.write_all(&(f64::from(*n) as f32).to_le_bytes()),
+            (ClickHouseType::Float64, Value::Number(n)) => writer.write_all(&f64::from(*n).to_le_bytes()),
+            (ClickHouseType::Bool, Value::Number(n)) => writer.write_all(&[u8::from(n.sql_sign() != Ordering::Equal)]),
+            (ClickHouseType::String, Value::Bytes(bytes)) => {
+                write_varint(writer, bytes.len() as u64)?;
+                writer.write_all(bytes.as_bytes())
+            }
+            (ClickHouseType::String, Value::Array(_)) => Err(mismatch()),
+            (ClickHouseType::String, _) => {
+                let mut buf = Vec::new();
+                ClickHouseTsvFormat(self.options).write_value(&mut buf, value)?;
+                write_varint(writer, buf.len() as u64)?;
+                writer.write_all(&buf)
+            }
+            (ClickHouseType::FixedString(len), Value::Bytes(bytes)) => {
+                let padding = len.checked_sub(bytes.len()).ok_or_else(mismatch)?;
+                writer.write_all(bytes.as_bytes())?;
+                writer.write_all(&vec![0; padding])
+            }
+            (ClickHouseType::Date, Value::Timestamp(timestamp, tz)) => {
+                let days = u16::try_from(local_days(timestamp, tz)).map_err(|_| mismatch())?;
+                writer.write_all(&days.to_le_bytes())
+            }
+            (ClickHouseType::Date32, Value::Timestamp(timestamp, tz)) => {
+                let days = i32::try_from(local_days(timestamp, tz)).map_err(|_| mismatch())?;
+                writer.write_all(&days.to_le_bytes())
+            }
+            (ClickHouseType::Date, Value::Date(date)) => {
+                let days = u16::try_from(epoch_days(*date)).map_err(|_| mismatch())?;
+                writer.write_all(&days.to_le_bytes())
+            }
+            (ClickHouseType::Date32, Value::Date(date)) => {
+                let days = i32::try_from(epoch_days(*date)).map_err(|_| mismatch())?;
+                writer.write_all(&days.to_le_bytes())
+            }
+            (ClickHouseType::DateTime, Value::Timestamp(timestamp, _)) => {
+                let seconds = u32::try_from(timestamp.and_utc().timestamp()).map_err(|_| mismatch())?;
+                writer.write_all(&seconds.to_le_bytes())
+            }
+            (ClickHouseType::DateTime64(precision), Value::Timestamp(timestamp, _)) => {
+                let timestamp = timestamp.and_utc();
+                let ticks = i64::checked_mul(timestamp.timestamp(), 10_i64.pow(*precision))
+                    .and_then(|t| {
+                        t.checked_add(i64::from(
+                            timestamp.timestamp_subsec_nanos() / 10_u32.pow(9 - precision),
+                        ))
+                    })
+                    .ok_or_else(mismatch)?;
+                writer.write_all(&ticks.to_le_bytes())
+            }
+            (ClickHouseType::Uuid, Value::Bytes(bytes)) => {
+                let hex = bytes
+                    .as_bytes()
+                    .iter()
+                    .filter(|b| **b != b'-')
+                    .copied()
+                    .collect::<Vec<_>>();
+                let uuid = std::str::from_utf8(&hex)
+                    .ok()
+                    .filter(|h| h.len() == 32)
+                    .and_then(|h| u128::from_str_radix(h, 16).ok())
+                    .ok_or_else(mismatch)?;
+                // ClickHouse stores a UUID as two little-endian 64-bit halves, high half first.
+                let bytes = uuid.to_le_bytes();
+                writer.write_all(&bytes[8..])?;
+                writer.write_all(&bytes[..8])
+            }
+            (ClickHouseType::Array(inner), Value::Array(array)) => {
+                write_varint(writer, array.len())?;
+                for item in array.iter() {
+                    self.write_typed_value(writer, inner, &item)?;
+                }
+                Ok(())
+            }
+            _ => Err(mismatch()),
+        }
+    }
+}
+
+/// Computes the number of days since 1970-01-01 of the local date of the timestamp.
+fn local_days(timestamp: &NaiveDateTime, tz: &ArcTz) -> i64 {
+    epoch_days(tz.from_utc_datetime(timestamp).date_naive())
+}
+
+/// Computes the number of days between 1970-01-01 and the date.
+fn epoch_days(date: NaiveDate) -> i64 {
+    date.signed_duration_since(NaiveDate::default()).num_days()
+}
+
+impl Format for ClickHouseRowBinaryFormat<'_> {
+    fn write_value(&self, writer: &mut dyn Write, value: &Value) -> Result<(), Error> {
+        let column = self.column.get();
+        self.column.set(column + 1);
+        let ty = self
+            .types
+            .get(column)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, "too many values for RowBinary row"))?;
+        self.write_typed_value(writer, ty, value)
+    }
+
+    fn write_file_header(&self, writer: &mut dyn Write, schema: &Schema<'_>) -> Result<(), Error> {
+        write_clickhouse_insert(writer, self.options, schema, "RowBinary")
+    }
+
+    fn write_header(&self, _: &mut dyn Write, _: &Schema<'_>) -> Result<(), Error> {
+        self.column.set(0);
+        Ok(())
+    }
+
+    fn write_value_header(&self, _: &mut dyn Write, _: &str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write_value_separator(&self, _: &mut dyn Write) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write_row_separator(&self, _: &mut dyn Write) -> Result<(), Error> {
+        self.column.set(0);
+        Ok(())
+    }
+
+    fn write_trailer(&self, _: &mut dyn Write) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write_file_trailer(&self, _: &mut dyn Write, _: &Schema<'_>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write_group_header(&self, _: &mut dyn Write) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write_group_trailer(&self, _: &mut dyn Write) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Escaping rules of the PostgreSQL COPY text format.
+const PG_COPY_RULES: [(u8, EscapeRule<'static>); 4] = [
+    (b'\\', EscapeRule::Escape(br"\\")),
+    (b'\t', EscapeRule::Escape(br"\t")),
+    (b'\n', EscapeRule::Escape(br"\n")),
+    (b'\r', EscapeRule::Escape(br"\r")),
+];
+
+impl PgCopyFormat<'_> {
+    /// Writes the PostgreSQL text representation of a value, before applying the COPY escaping.
+    fn write_text(&self, writer: &mut dyn Write, value: &Value) -> Result<(), Error> {
+        match value {
+            Value::Null => writer.write_all(b"NULL"),
+            Value::Number(number) => number.write_io(writer, &self.0.true_string, &self.0.false_string),
+            Value::Bytes(bytes) if bytes.encoding() == Encoding::Binary => {
+                writer.write_all(br"\x")?;
+                for b in bytes.as_bytes() {
+                    write!(writer, "{b:02x}")?;
+                }
+                Ok(())
+            }
+            Value::Bytes(bytes) | Value::Raw(bytes) => writer.write_all(bytes.as_bytes()),
+            Value::Timestamp(timestamp, tz) => write_timestamp(writer, "", timestamp, tz, self.0.timestamp_format),
+            Value::Date(date) => write_date(writer, "", *date),
+            Value::Time(time) => write_time(writer, "", *time),
+            Value::Interval(interval) => write_interval(writer, "", *interval, self.0.interval_format),
+            Value::Json(json) => write!(writer, "{json}"),
+            Value::Point(x, y) => self.0.write_text_point(writer, *x, *y),
+            Value::Array(array) => {
+                writer.write_all(b"{")?;
+                for (i, item) in array.iter().enumerate() {
+                    if i != 0 {
+                        writer.write_all(b",")?;
+                    }
+                    if let Value::Null | Value::Array(_) = item {
+                        self.write_text(writer, &item)?;
+                    } else {
+                        let mut element = Vec::new();
+                        self.write_text(&mut element, &item)?;
+                        writer.write_all(b"\"")?;
+                        write_with_escape(
+                            writer,
+                            &element,
+                            &[
+                                (b'"', EscapeRule::Escape(br#"\""#)),
+                                (b'\\', EscapeRule::Escape(br"\\")),
+                            ],
+                        )?;
+                        writer.write_all(b"\"")?;
+                    }
+                }
+                writer.write_all(b"}")
+            }
+        }
+    }
+}
+
+impl Format for PgCopyFormat<'_> {
+    fn write_value(&self, writer: &mut dyn Write, value: &Value) -> Result<(), Error> {
+        match value {
+            Value::Null => writer.write_all(self.0.null_string.as_bytes()),
+            Value::Bytes(bytes) if bytes.encoding() != Encoding::Binary => {
+                write_with_escape(writer, bytes.as_bytes(), &PG_COPY_RULES)
+            }
+            Value::Number(_)
+            | Value::Timestamp(..)
+            | Value::Date(_)
+            | Value::Time(_)
+            | Value::Interval(_)
+            | Value::Point(..) => self.write_text(writer, value),
+            Value::Bytes(_) | Value::Array(_) | Value::Json(_) | Value::Raw(_) => {
+                let mut text = Vec::new();
+                self.write_text(&mut text, value)?;
+                write_with_escape(writer, &text, &PG_COPY_RULES)
+            }
+        }
+    }
+
+    fn write_file_header(&self, _: &mut dyn Write, _: &Schema<'_>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write_header(&self, writer: &mut dyn Write, schema: &Schema<'_>) -> Result<(), Error> {
+        write!(writer, "COPY {} ", schema.name)?;
+        if self.0.headers {
+            writer.write_all(b"(")?;
+            for (i, col) in schema.output_column_names().enumerate() {
+                if i != 0 {
+                    writer.write_all(b", ")?;
+                }
+                writer.write_all(col.as_bytes())?;
+            }
+            writer.write_all(b") ")?;
+        }
+        writer.write_all(b"FROM stdin;\n")
+    }
+
+    fn write_value_header(&self, _: &mut dyn Write, _: &str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write_value_separator(&self, writer: &mut dyn Write) -> Result<(), Error> {
+        writer.write_all(b"\t")
+    }
+
+    fn write_row_separator(&self, writer: &mut dyn Write) -> Result<(), Error> {
+        writer.write_all(b"\n")
+    }
+
+    fn write_trailer(&self, writer: &mut dyn Write) -> Result<(), Error> {
+        writer.write_all(b"\n\\.\n")
+    }
+
+    fn write_file_trailer(&self, _: &mut dyn Write, _: &Schema<'_>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write_group_header(&self, writer: &mut dyn Write) -> Result<(), Error> {
+        self.0.write_begin_transaction(writer)
+    }
+
+    fn write_group_trailer(&self, writer: &mut dyn Write) -> Result<(), Error> {
+        writer.write_all(b"COMMIT;\n")
+    }
+}
+
+impl<'a> FixedWidthFormat<'a> {
+    /// Creates a new fixed-width formatter. The width of each column is taken from
+    /// `options.column_widths` if the column name is found there, and otherwise inferred from the
+    /// column type.
+    ///
+    /// Returns the name of the column whose width cannot be determined.
+    pub fn new(options: &'a Options, schema: &Schema<'_>) -> Result<Self, String> {
+        let names = schema.output_column_names().map(unquote).collect();
+        let widths = schema
+            .output_column_names()
+            .zip(schema.output_column_types())
+            .map(|(name, ty)| {
+                options
+                    .column_widths
+                    .get(&unquote(name))
+                    .copied()
+                    .or_else(|| fixed_width(ty))
+                    .ok_or_else(|| name.to_owned())
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            options,
+            names,
+            widths,
+            column: Cell::new(0),
+        })
+    }
+
+    /// Writes a value as unquoted and unescaped text.
+    fn write_text(&self, writer: &mut dyn Write, value: &Value) -> Result<(), Error> {
+        match value {
+            Value::Null => writer.write_all(self.options.null_string.as_bytes()),
+            Value::Number(number) => number.write_io(writer, &self.options.true_string, &self.options.false_string),
+            Value::Bytes(bytes) | Value::Raw(bytes) => writer.write_all(bytes.as_bytes()),
+            Value::Timestamp(timestamp, tz) => {
+                write_timestamp(writer, "", timestamp, tz, self.options.timestamp_format)
+            }
+            Value::Date(date) => write_date(writer, "", *date),
+            Value::Time(time) => write_time(writer, "", *time),
+            Value::Interval(interval) => write_interval(writer, "", *interval, self.options.interval_format),
+            Value::Json(json) => write!(writer, "{json}"),
+            Value::Point(x, y) => self.options.write_text_point(writer, *x, *y),
+            Value::Array(array) => {
+                writer.write_all(b"{")?;
+                for (i, item) in array.iter().enumerate() {
+                    if i != 0 {
+                        writer.write_all(b",")?;
+                    }
+                    self.write_text(writer, &item)?;
+                }
+                writer.write_all(b"}")
+            }
+        }
+    }
+
+    /// Writes the text padded with spaces or truncated to exactly `width` characters.
+    fn write_padded(writer: &mut dyn Write, text: Vec<u8>, width: usize, align_right: bool) -> Result<(), Error> {
+        let mut text = ByteString::from(text);
+        let len = text.char_len();
+        if len > width {
+            // truncation keeps the leading characters regardless of the alignment.
+            text.truncate(text.char_range(0..width).end);
+        }
+        let padding = " ".repeat(width.saturating_sub(len));
+        if align_right {
+            writer.write_all(padding.as_bytes())?;
+            writer.write_all(text.as_bytes())
+        } else {
+            writer.write_all(text.as_bytes())?;
+            writer.write_all(padding.as_bytes())
+        }
+    }
+}
+
+impl Format for FixedWidthFormat<'_> {
+    fn write_value(&self, writer: &mut dyn Write, value: &Value) -> Result<(), Error> {
+        let column = self.column.get();
+        self.column.set(column + 1);
+        let width = *self
+            .widths
+            .get(column)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, "too many values for fixed-width row"))?;
+        let mut text = Vec::new();
+        self.write_text(&mut text, value)?;
+        let is_number = matches!(value, Value::Number(_));
+        // a truncated number would silently become a different number.
+        if is_number {
+            let number = String::from_utf8_lossy(&text);
+            if number.chars().count() > width {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "number {number} does not fit the width {width} of column {}",
+                        self.names[column]
+                    ),
+                ));
+            }
+        }
+        Self::write_padded(writer, text, width, is_number)
+    }
+
+    fn write_file_header(&self, writer: &mut dyn Write, schema: &Schema<'_>) -> Result<(), Error> {
+        if !self.options.headers {
+            return Ok(());
+        }
+        for (name, width) in schema.output_column_names().zip(&self.widths) {
+            Self::write_padded(writer, unquote(name).into_bytes(), *width, false)?;
+        }
+        writer.write_all(b"\n")
+    }
+
+    fn write_header(&self, _: &mut dyn Write, _: &Schema<'_>) -> Result<(), Error> {
+        self.column.set(0);
+        Ok(())
+    }
+
+    fn write_value_header(&self, _: &mut dyn Write, _: &str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write_value_separator(&self, _: &mut dyn Write) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write_row_separator(&self, writer: &mut dyn Write) -> Result<(), Error> {
+        self.column.set(0);
+        writer.write_all(b"\n")
+    }
+
+    fn write_trailer(&self, writer: &mut dyn Write) -> Result<(), Error> {
+        writer.write_all(b"\n")
+    }
+
+    fn write_file_trailer(&self, _: &mut dyn Write, _: &Schema<'_>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write_group_header(&self, _: &mut dyn Write) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write_group_trailer(&self, _: &mut dyn Write) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<'a> DebeziumFormat<'a> {
+    /// Creates a new Debezium-style JSON formatter for the table.
+    pub fn new(options: &'a Options, schema: &Schema<'_>) -> Self {
+        Self {
+            options,
+            table: unquote(schema.name),
+        }
+    }
+
+    /// Writes a string as a JSON string.
+    fn write_string(writer: &mut dyn Write, s: &str) -> Result<(), Error> {
+        serde_json::to_writer(writer, s).map_err(Error::from)
+    }
+
+    /// Writes the image of a row as a JSON object, or `null` if there is no image.
+    fn write_image(&self, writer: &mut dyn Write, schema: &Schema<'_>, values: Option<&[Value]>) -> Result<(), Error> {
+        let Some(values) = values else {
+            return writer.write_all(b"null");
+        };
+        writer.write_all(b"{")?;
+        let mut is_first = true;
+        for (i, (column, value)) in schema.column_names().zip(values).enumerate() {
+            if !schema.is_output_column(i) {
+                continue;
+            }
+            if !mem::take(&mut is_first) {
+                self.write_value_separator(writer)?;
+            }
+            self.write_value_header(writer, column)?;
+            self.write_value(writer, value)?;
+        }
+        writer.write_all(b"}")
+    }
+
+    /// Writes the fields after the image of the row.
+    fn write_envelope_trailer(&self, writer: &mut dyn Write, op: ChangeOp) -> Result<(), Error> {
+        writer.write_all(br#","source":{"table":"#)?;
+        Self::write_string(writer, &self.table)?;
+        let op = match op {
+            ChangeOp::Insert => "c",
+            ChangeOp::Update => "u",
+            ChangeOp::Delete => "d",
+        };
+        write!(writer, r#"}},"op":"{op}"}}"#)?;
+        writer.write_all(b"\n")
+    }
+}
+
+impl Format for DebeziumFormat<'_> {
+    fn write_value(&self, writer: &mut dyn Write, value: &Value) -> Result<(), Error> {
+        if let Value::Timestamp(timestamp, tz) = value {
+            let mut buf = Vec::new();
+            write_timestamp(&mut buf, "", timestamp, tz, self.options.timestamp_format)?;
+            return match self.options.timestamp_format {
+                // Unix timestamps are written as JSON numbers.
+                TimestampFormat::Seconds | TimestampFormat::Milliseconds => writer.write_all(&buf),
+                _ => Self::write_string(writer, &String::from_utf8_lossy(&buf)),
+            };
+        }
+        // values without a JSON representation, like intervals, are written as strings.
+        let json = serde_json::Value::try_from(value.clone()).unwrap_or_else(|_| value.to_string().into());
+        serde_json::to_writer(writer, &json).map_err(Error::from)
+    }
+
+    fn write_file_header(&self, _: &mut dyn Write, _: &Schema<'_>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write_header(&self, writer: &mut dyn Write, _: &Schema<'_>) -> Result<(), Error> {
+        writer.write_all(br#"{"before":null,"after":{"#)
+    }
+
+    fn write_value_header(&self, writer: &mut dyn Write, column: &str) -> Result<(), Error> {
+        Self::write_string(writer, &unquote(column))?;
+        writer.write_all(b":")
+    }
+
+    fn write_value_separator(&self, writer: &mut dyn Write) -> Result<(), Error> {
+        writer.write_all(b",")
+    }
+
+    fn write_row_separator(&self, writer: &mut dyn Write) -> Result<(), Error> {
+        self.write_trailer(writer)?;
+        writer.write_all(br#"{"before":null,"after":{"#)
+    }
+
+    fn write_trailer(&self, writer: &mut dyn Write) -> Result<(), Error> {
+        writer.write_all(b"}")?;
+        self.write_envelope_trailer(writer, ChangeOp::Insert)
+    }
+
+    fn write_file_trailer(&self, _: &mut dyn Write, _: &Schema<'_>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write_group_header(&self, _: &mut dyn Write) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write_group_trailer(&self, _: &mut dyn Write) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write_change(&self, writer: &mut dyn Write, schema: &Schema<'_>, change: &Change<'_>) -> Result<(), Error> {
+        writer.write_all(br#"{"before":"#)?;
+        self.write_image(writer, schema, change.before)?;
+        writer.write_all(br#","after":"#)?;
+        self.write_image(writer, schema, change.after)?;
+        self.write_envelope_trailer(writer, change.op)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
     #[test]
     fn test_write_with_escape() {
@@ -463,4 +2107,149 @@ mod tests {
             assert_eq!(&writer, expected);
         }
     }
+
+    #[test]
+    fn test_write_interval() {
+        let test_cases = [
+            (0, "00:00:00", "PT0S", "0", "0"),
+            (1_500_000, "00:00:01.500000", "PT1.5S", "1.5", "1500"),
+            (5_400_000_000, "01:30:00", "PT1H30M", "5400", "5400000"),
+            (
+                -95_400_000_001,
+                "-1 02:30:00.000001",
+                "-PT26H30M0.000001S",
+                "-95400.000001",
+                "-95400000.001",
+            ),
+            (
+                i64::MIN,
+                "-106751991 04:00:54.775808",
+                "-PT2562047788H54.775808S",
+                "-9223372036854.775808",
+                "-9223372036854775.808",
+            ),
+        ];
+
+        for (interval, sql, iso8601, seconds, milliseconds) in test_cases {
+            for (format, expected) in [
+                (IntervalFormat::Sql, sql),
+                (IntervalFormat::Iso8601, iso8601),
+                (IntervalFormat::Seconds, seconds),
+                (IntervalFormat::Milliseconds, milliseconds),
+            ] {
+                let mut writer = Vec::new();
+                write_interval(&mut writer, "", interval, format).unwrap();
+                assert_eq!(String::from_utf8(writer).unwrap(), expected, "{format:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_write_timestamp() {
+        let tz = ArcTz::new(chrono::Utc.into());
+        let test_cases = [
+            (
+                NaiveDate::from_ymd_opt(2024, 1, 1)
+                    .unwrap()
+                    .and_hms_opt(4, 0, 0)
+                    .unwrap(),
+                "2024-01-01 04:00:00",
+                "2024-01-01 04:00:00+00:00",
+                "2024-01-01T04:00:00+00:00",
+                "1704081600",
+                "1704081600000",
+            ),
+            (
+                NaiveDate::from_ymd_opt(1969, 12, 31)
+                    .unwrap()
+                    .and_hms_micro_opt(23, 59, 59, 500)
+                    .unwrap(),
+                "1969-12-31 23:59:59.000500",
+                "1969-12-31 23:59:59.000500+00:00",
+                "1969-12-31T23:59:59.000500+00:00",
+                "-1",
+                "-1000",
+            ),
+            (
+                NaiveDate::from_ymd_opt(2024, 1, 1)
+                    .unwrap()
+                    .and_hms_micro_opt(4, 0, 0, 999_999)
+                    .unwrap(),
+                "2024-01-01 04:00:00.999999",
+                "2024-01-01 04:00:00.999999+00:00",
+                "2024-01-01T04:00:00.999999+00:00",
+                "1704081600",
+                "1704081600999",
+            ),
+        ];
+
+        for (timestamp, sql, offset, rfc3339, seconds, milliseconds) in test_cases {
+            for (format, expected) in [
+                (TimestampFormat::Sql, sql),
+                (TimestampFormat::Utc, sql),
+                (TimestampFormat::Offset, offset),
+                (TimestampFormat::Rfc3339, rfc3339),
+                (TimestampFormat::Seconds, seconds),
+                (TimestampFormat::Milliseconds, milliseconds),
+            ] {
+                let mut writer = Vec::new();
+                write_timestamp(&mut writer, "", &timestamp, &tz, format).unwrap();
+                assert_eq!(String::from_utf8(writer).unwrap(), expected, "{format:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_clickhouse_type() {
+        let test_cases = [
+            (
+                "UInt64",
+                Some(ClickHouseType::Int {
+                    bytes: 8,
+                    signed: false,
+                }),
+            ),
+            ("Int8 NOT NULL", Some(ClickHouseType::Int { bytes: 1, signed: true })),
+            ("FixedString(16)", Some(ClickHouseType::FixedString(16))),
+            ("DateTime('Asia/Tokyo')", Some(ClickHouseType::DateTime)),
+            ("DateTime64(3, 'UTC')", Some(ClickHouseType::DateTime64(3))),
+            (
+                "Array(Nullable(String)) DEFAULT []",
+                Some(ClickHouseType::Array(Box::new(ClickHouseType::Nullable(Box::new(
+                    ClickHouseType::String,
+                ))))),
+            ),
+            ("LowCardinality(String)", Some(ClickHouseType::String)),
+            ("DateTime64(10)", None),
+            ("Decimal(10, 2)", None),
+            ("", None),
+        ];
+        for (input, expected) in test_cases {
+            assert_eq!(ClickHouseType::parse_prefix(input).map(|r| r.0), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_insert_mode_keyword() {
+        let test_cases = [
+            (InsertMode::Insert, None, Some("INSERT")),
+            (InsertMode::Insert, Some(Dialect::Mssql), Some("INSERT")),
+            (InsertMode::InsertIgnore, None, Some("INSERT IGNORE")),
+            (InsertMode::InsertIgnore, Some(Dialect::Postgres), Some("INSERT")),
+            (
+                InsertMode::InsertIgnore,
+                Some(Dialect::Sqlite),
+                Some("INSERT OR IGNORE"),
+            ),
+            (InsertMode::InsertIgnore, Some(Dialect::Mssql), None),
+            (InsertMode::Replace, None, Some("REPLACE")),
+            (InsertMode::Replace, Some(Dialect::Mysql), Some("REPLACE")),
+            (InsertMode::Replace, Some(Dialect::Sqlite), Some("INSERT OR REPLACE")),
+            (InsertMode::Replace, Some(Dialect::Postgres), None),
+            (InsertMode::Replace, Some(Dialect::Mssql), None),
+        ];
+        for (mode, dialect, expected) in test_cases {
+            assert_eq!(mode.keyword(dialect), expected, "{mode:?} {dialect:?}");
+        }
+    }
 }
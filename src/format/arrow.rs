@@ -0,0 +1,338 @@
+//! Apache Arrow IPC output format.
+
+use super::{write_timestamp, Format, Options, TimestampFormat};
+use crate::{
+    ddl::ColumnType,
+    eval::Schema,
+    number::MAX_DECIMAL_PRECISION,
+    value::{Value, DATE_FORMAT, TIME_FORMAT},
+};
+
+use arrow_array::{ArrayRef, BinaryArray, DurationMicrosecondArray, RecordBatch, StringArray};
+use arrow_cast::{cast_with_options, CastOptions};
+use arrow_ipc::writer::FileWriter;
+use arrow_schema::{ArrowError, DataType, Field, Schema as ArrowSchema, TimeUnit};
+use chrono::{NaiveDateTime, TimeZone};
+use std::{
+    cell::{Cell, RefCell},
+    fmt,
+    io::{Error, ErrorKind, Write},
+    mem,
+    sync::Arc,
+};
+use tzfile::ArcTz;
+
+/// Apache Arrow IPC file formatter.
+///
+/// The rows of every INSERT statement are buffered and written as a single record batch, so the
+/// batch size is controlled by `--rows-count`.
+pub struct ArrowFormat<'a> {
+    options: &'a Options,
+    /// The Arrow schema derived from the column types.
+    schema: Arc<ArrowSchema>,
+    /// The buffered values of the current batch, in column-major order.
+    columns: RefCell<Vec<Vec<Value>>>,
+    /// The index of the column the next value belongs to.
+    column: Cell<usize>,
+    /// The IPC writer of the current file. The encoded bytes are buffered and moved to the
+    /// actual output after every call.
+    file: RefCell<Option<FileWriter<Vec<u8>>>>,
+}
+
+impl fmt::Debug for ArrowFormat<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArrowFormat")
+            .field("schema", &self.schema)
+            .field("column", &self.column)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Converts an Arrow error into an I/O error.
+fn arrow_error(e: ArrowError) -> Error {
+    match e {
+        ArrowError::IoError(_, e) => e,
+        e => Error::new(ErrorKind::InvalidData, e),
+    }
+}
+
+/// Converts a UTC timestamp to the wall clock time in the time zone.
+fn local_datetime(timestamp: &NaiveDateTime, tz: &ArcTz) -> NaiveDateTime {
+    tz.from_utc_datetime(timestamp).naive_local()
+}
+
+/// Infers the Arrow data type of a column from its SQL type, e.g. `Decimal128(10, 2)` from
+/// `decimal(10, 2)`.
+///
+/// Types without a better match are stored as UTF-8 strings.
+fn arrow_type(ty: &str) -> DataType {
+    match ColumnType::parse(ty) {
+        ColumnType::Integer {
+            bytes: 1,
+            unsigned: true,
+        } => DataType::UInt8,
+        ColumnType::Integer {
+            bytes: 1,
+            unsigned: false,
+        } => DataType::Int8,
+        ColumnType::Integer {
+            bytes: 2,
+            unsigned: true,
+        } => DataType::UInt16,
+        ColumnType::Integer {
+            bytes: 2,
+            unsigned: false,
+        } => DataType::Int16,
+        ColumnType::Integer {
+            bytes: 3 | 4,
+            unsigned: true,
+        } => DataType::UInt32,
+        ColumnType::Integer {
+            bytes: 3 | 4,
+            unsigned: false,
+        } => DataType::Int32,
+        ColumnType::Integer { unsigned: true, .. } => DataType::UInt64,
+        ColumnType::Integer { unsigned: false, .. } => DataType::Int64,
+        ColumnType::Float { single: true } => DataType::Float32,
+        ColumnType::Float { single: false } => DataType::Float64,
+        ColumnType::Decimal(precision_scale) => {
+            let (precision, scale) = precision_scale.unwrap_or((38, 0));
+            let precision = precision.clamp(1, 76);
+            let scale = scale.min(precision);
+            // ALLOW_REASON: precision and scale are clamped to at most 76.
+            #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+            let (precision, scale) = (precision as u8, scale as i8);
+            if precision <= 38 {
+                DataType::Decimal128(precision, scale)
+            } else {
+                DataType::Decimal256(precision, scale)
+            }
+        }
+        ColumnType::Boolean => DataType::Boolean,
+        ColumnType::Date => DataType::Date32,
+        ColumnType::Time => DataType::Time64(TimeUnit::Microsecond),
+        ColumnType::Timestamp { with_time_zone: true } => {
+            DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into()))
+        }
+        ColumnType::Timestamp { with_time_zone: false } => DataType::Timestamp(TimeUnit::Microsecond, None),
+        ColumnType::Interval => DataType::Duration(TimeUnit::Microsecond),
+        ColumnType::Binary(_) | ColumnType::Varbinary(_) => DataType::Binary,
+        _ => DataType::Utf8,
+    }
+}
+
+impl<'a> ArrowFormat<'a> {
+    /// Creates a new Arrow formatter, using the column types of the schema.
+    pub fn new(options: &'a Options, schema: &Schema<'_>) -> Self {
+        let fields = schema
+            .output_column_names()
+            .zip(schema.output_column_types())
+            .map(|(name, ty)| Field::new(super::unquote(name), arrow_type(ty), true))
+            .collect::<Vec<_>>();
+        Self {
+            options,
+            columns: RefCell::new(vec![Vec::new(); fields.len()]),
+            schema: Arc::new(ArrowSchema::new(fields)),
+            column: Cell::new(0),
+            file: RefCell::new(None),
+        }
+    }
+
+    /// Renders a value as text which can be cast to the given data type.
+    fn write_text(&self, writer: &mut dyn Write, value: &Value, data_type: &DataType) -> Result<(), Error> {
+        match value {
+            Value::Null => Ok(()),
+            Value::Number(number) => match data_type {
+                // floats are written like `1e-7` in their shortest form, which the cast to decimal
+                // does not accept, so they are converted to decimals of the column scale first.
+                DataType::Decimal128(_, scale) | DataType::Decimal256(_, scale) => u8::try_from(*scale)
+                    .ok()
+                    .and_then(|scale| number.to_decimal(MAX_DECIMAL_PRECISION, scale).ok())
+                    .unwrap_or(*number)
+                    .write_io(writer, "true", "false"),
+                _ => number.write_io(writer, "true", "false"),
+            },
+            Value::Bytes(bytes) | Value::Raw(bytes) => writer.write_all(bytes.as_bytes()),
+            Value::Timestamp(timestamp, tz) => match data_type {
+                DataType::Date32 => write!(writer, "{}", local_datetime(timestamp, tz).format("%Y-%m-%d")),
+                DataType::Time64(_) => write!(writer, "{}", local_datetime(timestamp, tz).format("%H:%M:%S%.f")),
+                DataType::Timestamp(_, Some(_)) => write!(writer, "{}", timestamp.format("%Y-%m-%dT%H:%M:%S%.f")),
+                _ => write_timestamp(writer, "", timestamp, tz, TimestampFormat::Sql),
+            },
+            Value::Date(date) => write!(writer, "{}", date.format(DATE_FORMAT)),
+            Value::Time(time) => write!(writer, "{}", time.format(TIME_FORMAT)),
+            Value::Interval(interval) => write!(writer, "{interval}"),
+            Value::Json(json) => write!(writer, "{json}"),
+            Value::Point(x, y) => self.options.write_text_point(writer, *x, *y),
+            Value::Array(array) => {
+                writer.write_all(b"{")?;
+                for (i, item) in array.iter().enumerate() {
+                    if i != 0 {
+                        writer.write_all(b",")?;
+                    }
+                    self.write_text(writer, &item, data_type)?;
+                }
+                writer.write_all(b"}")
+            }
+        }
+    }
+
+    /// Converts the buffered values of a column into an Arrow array.
+    fn build_array(&self, values: &[Value], data_type: &DataType) -> Result<ArrayRef, Error> {
+        let invalid =
+            |value: &Value| Error::new(ErrorKind::InvalidData, format!("cannot store {value:?} as {data_type}"));
+        match data_type {
+            DataType::Binary => {
+                let array = values
+                    .iter()
+                    .map(|value| match value {
+                        Value::Null => Ok(None),
+                        Value::Bytes(bytes) => Ok(Some(bytes.as_bytes())),
+                        _ => Err(invalid(value)),
+                    })
+                    .collect::<Result<BinaryArray, _>>()?;
+                Ok(Arc::new(array))
+            }
+            DataType::Duration(_) => {
+                let array = values
+                    .iter()
+                    .map(|value| match value {
+                        Value::Null => Ok(None),
+                        Value::Interval(interval) => Ok(Some(*interval)),
+                        _ => Err(invalid(value)),
+                    })
+                    .collect::<Result<DurationMicrosecondArray, _>>()?;
+                Ok(Arc::new(array))
+            }
+            _ => {
+                let mut buf = Vec::new();
+                let texts = values
+                    .iter()
+                    .map(|value| {
+                        if *value == Value::Null {
+                            return Ok(None);
+                        }
+                        buf.clear();
+                        self.write_text(&mut buf, value, data_type)?;
+                        Ok(Some(String::from_utf8_lossy(&buf).into_owned()))
+                    })
+                    .collect::<Result<StringArray, Error>>()?;
+                let options = CastOptions {
+                    safe: false,
+                    ..CastOptions::default()
+                };
+                cast_with_options(&texts, data_type, &options).map_err(arrow_error)
+            }
+        }
+    }
+
+    /// Moves the bytes encoded by the IPC writer to the output.
+    fn flush(writer: &mut dyn Write, file: &mut FileWriter<Vec<u8>>) -> Result<(), Error> {
+        let buf = file.get_mut();
+        writer.write_all(buf)?;
+        buf.clear();
+        Ok(())
+    }
+}
+
+impl Format for ArrowFormat<'_> {
+    fn write_value(&self, _: &mut dyn Write, value: &Value) -> Result<(), Error> {
+        let column = self.column.get();
+        self.column.set(column + 1);
+        self.columns
+            .borrow_mut()
+            .get_mut(column)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, "too many values for Arrow record batch"))?
+            .push(value.clone());
+        Ok(())
+    }
+
+    fn write_file_header(&self, writer: &mut dyn Write, _: &Schema<'_>) -> Result<(), Error> {
+        let mut file = FileWriter::try_new(Vec::new(), &self.schema).map_err(arrow_error)?;
+        Self::flush(writer, &mut file)?;
+        *self.file.borrow_mut() = Some(file);
+        Ok(())
+    }
+
+    fn write_header(&self, _: &mut dyn Write, _: &Schema<'_>) -> Result<(), Error> {
+        self.column.set(0);
+        Ok(())
+    }
+
+    fn write_value_header(&self, _: &mut dyn Write, _: &str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write_value_separator(&self, _: &mut dyn Write) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write_row_separator(&self, _: &mut dyn Write) -> Result<(), Error> {
+        self.column.set(0);
+        Ok(())
+    }
+
+    fn write_trailer(&self, writer: &mut dyn Write) -> Result<(), Error> {
+        let mut columns = self.columns.borrow_mut();
+        if columns.first().map_or(true, Vec::is_empty) {
+            return Ok(());
+        }
+        let arrays = columns
+            .iter_mut()
+            .zip(self.schema.fields().iter())
+            .map(|(values, field)| self.build_array(&mem::take(values), field.data_type()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let batch = RecordBatch::try_new(self.schema.clone(), arrays).map_err(arrow_error)?;
+        let mut file = self.file.borrow_mut();
+        let file = file
+            .as_mut()
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, "Arrow record batch written outside of a file"))?;
+        file.write(&batch).map_err(arrow_error)?;
+        Self::flush(writer, file)
+    }
+
+    fn write_file_trailer(&self, writer: &mut dyn Write, _: &Schema<'_>) -> Result<(), Error> {
+        if let Some(mut file) = self.file.borrow_mut().take() {
+            file.finish().map_err(arrow_error)?;
+            Self::flush(writer, &mut file)?;
+        }
+        Ok(())
+    }
+
+    fn write_group_header(&self, _: &mut dyn Write) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn write_group_trailer(&self, _: &mut dyn Write) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arrow_type() {
+        let test_cases = [
+            ("INT NOT NULL", DataType::Int32),
+            ("bigint unsigned", DataType::UInt64),
+            ("DECIMAL(10, 2)", DataType::Decimal128(10, 2)),
+            ("numeric(50, 10)", DataType::Decimal256(50, 10)),
+            ("TIMESTAMP", DataType::Timestamp(TimeUnit::Microsecond, None)),
+            (
+                "timestamp with time zone",
+                DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+            ),
+            ("BLOB", DataType::Binary),
+            ("VARCHAR(40)", DataType::Utf8),
+            ("int1 unsigned", DataType::UInt8),
+            ("serial8", DataType::Int64),
+            ("float(10)", DataType::Float32),
+            ("integer[]", DataType::Utf8),
+        ];
+        for (ty, expected) in test_cases {
+            assert_eq!(arrow_type(ty), expected, "type: {ty}");
+        }
+    }
+}
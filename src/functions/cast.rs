@@ -0,0 +1,171 @@
+//! Type conversion functions.
+
+use super::{args_2, time, Arguments, Function};
+use crate::{
+    ddl::ColumnType,
+    error::Error,
+    eval::{CompileContext, C},
+    number::{Number, MAX_DECIMAL_PRECISION},
+    span::{ResultExt, Span, SpanExt, S},
+    value::Value,
+};
+
+use chrono::NaiveTime;
+use smallvec::smallvec;
+use std::{convert::TryFrom, iter};
+
+/// The target type of a `CAST` expression.
+#[derive(Debug, Copy, Clone)]
+enum CastType {
+    /// An integer within the inclusive range.
+    Integer { min: i128, max: i128 },
+    /// A floating-point number, either single or double precision.
+    Float { single: bool },
+    /// A fixed-point decimal number.
+    Decimal { precision: u8, scale: u8 },
+    /// A boolean.
+    Boolean,
+    /// A string, optionally truncated to the number of characters.
+    Text { length: Option<usize> },
+    /// A timestamp.
+    Timestamp,
+    /// A date.
+    Date,
+    /// A time of day.
+    Time,
+}
+
+impl CastType {
+    /// Parses an SQL type name like `bigint unsigned` or `varchar(20)`.
+    fn parse(span: Span, ty: &str) -> Result<Self, S<Error>> {
+        Ok(match ColumnType::parse(ty) {
+            ColumnType::Integer { bytes, unsigned } => {
+                let bits = 8 * u32::from(bytes);
+                if unsigned {
+                    Self::Integer {
+                        min: 0,
+                        max: (1_i128 << bits) - 1,
+                    }
+                } else {
+                    let base = 1_i128 << (bits - 1);
+                    Self::Integer {
+                        min: -base,
+                        max: base - 1,
+                    }
+                }
+            }
+            ColumnType::Float { single } => Self::Float { single },
+            ColumnType::Decimal(precision_scale) => {
+                let (precision, scale) = precision_scale.unwrap_or((10, 0));
+                let precision = u8::try_from(precision).unwrap_or(u8::MAX);
+                let scale = u8::try_from(scale).unwrap_or(u8::MAX);
+                if !(1..=MAX_DECIMAL_PRECISION).contains(&precision) || scale > precision {
+                    return Err(Error::InvalidArguments(format!("invalid decimal type '{ty}'")).span(span));
+                }
+                Self::Decimal { precision, scale }
+            }
+            ColumnType::Boolean => Self::Boolean,
+            // `CAST(x AS CHAR)` without the length does not truncate in MySQL.
+            ColumnType::Char(length) | ColumnType::Varchar(length) => Self::Text { length },
+            ColumnType::Binary(_) | ColumnType::Varbinary(_) => Self::Text { length: None },
+            ColumnType::Timestamp { .. } => Self::Timestamp,
+            ColumnType::Date => Self::Date,
+            ColumnType::Time => Self::Time,
+            _ => return Err(Error::InvalidArguments(format!("cannot cast to unsupported type '{ty}'")).span(span)),
+        })
+    }
+}
+
+/// Converts a value to a number, parsing strings as decimal numbers.
+fn to_number(span: Span, value: &Value) -> Result<Number, S<Error>> {
+    let number = match value {
+        Value::Number(number) => Some(*number),
+        Value::Bytes(bytes) => std::str::from_utf8(bytes.as_bytes()).ok().and_then(|s| {
+            Number::parse_decimal(s).or_else(|| s.trim().parse::<f64>().ok().and_then(|f| Number::try_from(f).ok()))
+        }),
+        _ => None,
+    };
+    number.ok_or_else(|| {
+        Error::UnexpectedValueType {
+            expected: "number",
+            value: value.to_string(),
+        }
+        .span(span)
+    })
+}
+
+/// Converts a value to a boolean, accepting the same strings as PostgreSQL.
+fn to_boolean(span: Span, value: &Value) -> Result<bool, S<Error>> {
+    let boolean = match value {
+        Value::Number(number) => Some(number.sql_sign().is_ne()),
+        Value::Bytes(bytes) => match &*String::from_utf8_lossy(bytes.as_bytes()).trim().to_ascii_lowercase() {
+            "t" | "true" | "y" | "yes" | "on" | "1" => Some(true),
+            "f" | "false" | "n" | "no" | "off" | "0" => Some(false),
+            _ => None,
+        },
+        _ => None,
+    };
+    boolean.ok_or_else(|| {
+        Error::UnexpectedValueType {
+            expected: "boolean",
+            value: value.to_string(),
+        }
+        .span(span)
+    })
+}
+
+/// The `CAST` SQL function.
+#[derive(Debug)]
+pub struct Cast;
+
+impl Function for Cast {
+    fn compile(&self, ctx: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (value, ty) = args_2::<S<Value>, String>(span, args, None, None)?;
+        let cast_type = CastType::parse(span, &ty)?;
+        if let Value::Null = value.inner {
+            return Ok(C::Constant(Value::Null));
+        }
+        let overflow = || Error::IntegerOverflow(format!("CAST({} AS {ty})", value.inner)).span(span);
+
+        Ok(C::Constant(match cast_type {
+            CastType::Integer { min, max } => {
+                let number = i128::try_from(to_number(span, &value.inner)?).map_err(|_| overflow())?;
+                if !(min..=max).contains(&number) {
+                    return Err(overflow());
+                }
+                number.into()
+            }
+            CastType::Float { single } => {
+                let mut number = f64::from(to_number(span, &value.inner)?);
+                if single {
+                    // ALLOW_REASON: rounding to single precision is the purpose of the cast.
+                    #[allow(clippy::cast_possible_truncation)]
+                    let single = number as f32;
+                    number = single.into();
+                }
+                Value::Number(Number::try_from(number).map_err(|_| overflow())?)
+            }
+            CastType::Decimal { precision, scale } => {
+                let number = to_number(span, &value.inner)?;
+                Value::Number(number.to_decimal(precision, scale).map_err(|_| overflow())?)
+            }
+            CastType::Boolean => to_boolean(span, &value.inner)?.into(),
+            CastType::Text { length } => {
+                let Value::Bytes(mut text) = Value::sql_concat(iter::once(&value.inner)).span_err(span)? else {
+                    unreachable!("concatenating a non-NULL value must produce a string");
+                };
+                if let Some(length) = length {
+                    text.truncate(text.char_range(0..length).end);
+                }
+                Value::Bytes(text)
+            }
+            CastType::Timestamp => match value.inner {
+                Value::Timestamp(..) => value.inner,
+                Value::Date(date) => time::from_local_time(span, date.and_time(NaiveTime::MIN), ctx.time_zone.clone())?,
+                _ => return time::Timestamp.compile(ctx, span, smallvec![value]),
+            },
+            CastType::Date => return time::Date.compile(ctx, span, smallvec![value]),
+            CastType::Time => return time::Time.compile(ctx, span, smallvec![value]),
+        }))
+    }
+}
@@ -3,13 +3,15 @@
 use data_encoding::{Encoding, BASE64, BASE64URL_NOPAD};
 use data_encoding_macro::new_encoding;
 
-use super::{args_1, Arguments, Function};
+use super::{args_1, args_2, Arguments, Function};
 use crate::{
     bytes::ByteString,
     error::Error,
     eval::{CompileContext, C},
-    span::{ResultExt, Span, S},
+    span::{ResultExt, Span, SpanExt, S},
+    value::Value,
 };
+use std::{convert::TryFrom, net::Ipv4Addr};
 
 const HEX_ENCODING: Encoding = new_encoding! {
     symbols: "0123456789ABCDEF",
@@ -76,3 +78,70 @@ impl Function for Encode {
         Ok(C::Constant(encoded.into()))
     }
 }
+
+//------------------------------------------------------------------------------
+
+/// The `inet_aton` SQL function.
+#[derive(Debug)]
+pub struct InetAton;
+
+impl Function for InetAton {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let address = args_1::<String>(span, args, None)?;
+        Ok(C::Constant(
+            address.trim().parse::<Ipv4Addr>().ok().map(u32::from).into(),
+        ))
+    }
+}
+
+/// The `float_to_bits` SQL function.
+#[derive(Debug)]
+pub struct FloatToBits;
+
+impl Function for FloatToBits {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let float = args_1::<f64>(span, args, None)?;
+        Ok(C::Constant(float.to_bits().into()))
+    }
+}
+
+/// The `bits_to_float` SQL function.
+#[derive(Debug)]
+pub struct BitsToFloat;
+
+impl Function for BitsToFloat {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let bits = args_1::<Value>(span, args, None)?;
+        let bits = to_u64_bits(bits).span_err(span)?;
+        let float = f64::from_bits(bits);
+        if float.is_finite() {
+            Ok(C::Constant(Value::from_finite_f64(float)))
+        } else {
+            Err(Error::InvalidArguments(format!("bits_to_float({bits}) is not a finite number")).span(span))
+        }
+    }
+}
+
+/// The `int_to_hex` SQL function.
+#[derive(Debug)]
+pub struct IntToHex;
+
+impl Function for IntToHex {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (int, width) = args_2::<Value, usize>(span, args, None, Some(0))?;
+        let int = to_u64_bits(int).span_err(span)?;
+        Ok(C::Constant(format!("{int:0width$X}").into()))
+    }
+}
+
+/// Converts an integer to its 64-bit pattern. Negative integers are represented in two's
+/// complement.
+fn to_u64_bits(value: Value) -> Result<u64, Error> {
+    match u64::try_from(value.clone()) {
+        Ok(n) => Ok(n),
+        Err(e) => match i64::try_from(value) {
+            Ok(n) => Ok(u64::from_ne_bytes(n.to_ne_bytes())),
+            Err(_) => Err(e),
+        },
+    }
+}
@@ -0,0 +1,126 @@
+//! Dirty data functions, corrupting values for testing the robustness of data pipelines.
+
+use super::{Arguments, Function, TryFromSpannedValue};
+use crate::{
+    error::Error,
+    eval::{CompileContext, Compiled, C},
+    span::{Span, SpanExt, S},
+    value::Value,
+};
+use rand::{distributions::Bernoulli, Rng, RngCore};
+
+/// The dirty data SQL functions, each replacing the value by a corrupted one with a probability.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Corruption {
+    /// The `dirty.wrong_type` SQL function, replacing the value by a string which is not a valid
+    /// number or timestamp.
+    WrongType,
+    /// The `dirty.truncate` SQL function, cutting a string short, preferably in the middle of a
+    /// multi-byte UTF-8 sequence.
+    Truncate,
+    /// The `dirty.out_of_range` SQL function, replacing the value by an impossible date string.
+    OutOfRange,
+    /// The `dirty.swap` SQL function, replacing the value by another one.
+    Swap,
+}
+
+/// The strings produced by `dirty.wrong_type`.
+const WRONG_TYPE_VALUES: &[&str] = &["", "N/A", "null", "NaN", "#VALUE!", "1O0", "12,5", "?"];
+
+/// The strings produced by `dirty.out_of_range`.
+const OUT_OF_RANGE_DATES: &[&str] = &[
+    "0000-00-00 00:00:00",
+    "2023-02-30 00:00:00",
+    "2023-13-01 00:00:00",
+    "2023-01-01 24:00:00",
+    "2023-01-01 12:60:00",
+    "10000-01-01 00:00:00",
+];
+
+/// The namespace of the random number generator deciding whether `dirty.swap` replaces the value.
+///
+/// Since this only depends on `rownum` and `subrownum`, calls with the same probability in the
+/// same row make the same decision, so `dirty.swap(@a, @b, p)` and `dirty.swap(@b, @a, p)` swap
+/// the two columns together.
+pub(crate) const SWAP_NAMESPACE: &str = "dirty.swap";
+
+impl Corruption {
+    /// The number of arguments including the probability.
+    fn arity(self) -> usize {
+        match self {
+            Self::Swap => 3,
+            _ => 2,
+        }
+    }
+
+    /// Creates the compiled expression from the value arguments and the probability.
+    fn create(self, span: Span, values: Vec<Compiled>, p: f64) -> Result<C, S<Error>> {
+        let rate =
+            Bernoulli::new(p).map_err(|e| Error::InvalidArguments(format!("probability ({p}) {e}")).span(span))?;
+        Ok(C::Dirty {
+            corruption: self,
+            rate,
+            args: values.into_boxed_slice(),
+        })
+    }
+
+    /// Corrupts the value. The `other` value is the replacement used by `dirty.swap`.
+    pub(crate) fn apply(self, rng: &mut dyn RngCore, value: Value, other: Value) -> Value {
+        match self {
+            Self::WrongType => WRONG_TYPE_VALUES[rng.gen_range(0..WRONG_TYPE_VALUES.len())].into(),
+            Self::OutOfRange => OUT_OF_RANGE_DATES[rng.gen_range(0..OUT_OF_RANGE_DATES.len())].into(),
+            Self::Swap => other,
+            Self::Truncate => {
+                let Value::Bytes(bytes) = value else {
+                    return value;
+                };
+                let mut bytes = bytes.into_bytes();
+                if bytes.is_empty() {
+                    return bytes.into();
+                }
+                // prefer cutting at the continuation bytes to produce invalid UTF-8.
+                let continuations = bytes.iter().filter(|b| (0x80..0xc0).contains(*b)).count();
+                let len = if continuations > 0 {
+                    let n = rng.gen_range(0..continuations);
+                    bytes
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, b)| (0x80..0xc0).contains(*b))
+                        .nth(n)
+                        .map_or(0, |(i, _)| i)
+                } else {
+                    rng.gen_range(0..bytes.len())
+                };
+                bytes.truncate(len);
+                bytes.into()
+            }
+        }
+    }
+}
+
+impl Function for Corruption {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        if args.len() < self.arity() {
+            return Err(Error::NotEnoughArguments.span(span));
+        }
+        let mut args = args.into_vec();
+        args.truncate(self.arity());
+        let p = f64::try_from_spanned_value(args.pop().unwrap())?;
+        let values = args
+            .into_iter()
+            .map(|arg| Compiled(C::Constant(arg.inner).span(arg.span)))
+            .collect();
+        self.create(span, values, p)
+    }
+
+    fn compile_partial(&self, _: &CompileContext, span: Span, args: &[Compiled]) -> Result<Option<C>, S<Error>> {
+        let Some((p, values)) = args.get(..self.arity()).and_then(<[_]>::split_last) else {
+            return Err(Error::NotEnoughArguments.span(span));
+        };
+        let C::Constant(p_value) = &p.0.inner else {
+            return Ok(None);
+        };
+        let p = f64::try_from_spanned_value(p_value.clone().span(p.0.span))?;
+        self.create(span, values.to_vec(), p).map(Some)
+    }
+}
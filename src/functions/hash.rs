@@ -0,0 +1,70 @@
+//! Hashing and checksum functions.
+
+use super::{args_1, Arguments, Function};
+use crate::{
+    bytes::ByteString,
+    error::Error,
+    eval::{fnv1a, CompileContext, C},
+    span::{Span, S},
+    value::Value,
+};
+use data_encoding::HEXLOWER;
+use md5::Md5;
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+/// The hashing and checksum SQL functions.
+#[derive(Debug, Copy, Clone)]
+pub enum Hash {
+    /// The `md5` SQL function, returning a hex string.
+    Md5,
+    /// The `sha1` SQL function, returning a hex string.
+    Sha1,
+    /// The `sha256` SQL function, returning a hex string.
+    Sha256,
+    /// The `crc32` SQL function, returning an integer.
+    Crc32,
+    /// The `fnv64` SQL function, returning an integer.
+    Fnv64,
+}
+
+/// Computes the digest of the bytes as a lowercase hex string.
+fn hex_digest<D: Digest>(bytes: &[u8]) -> Value {
+    HEXLOWER.encode(&D::digest(bytes)).into()
+}
+
+/// Computes the CRC-32 (IEEE 802.3) checksum of the bytes.
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0_u32;
+    for &b in bytes {
+        crc ^= u32::from(b);
+        for _ in 0..8 {
+            crc = if crc & 1 == 0 {
+                crc >> 1
+            } else {
+                (crc >> 1) ^ 0xedb8_8320
+            };
+        }
+    }
+    !crc
+}
+
+impl Function for Hash {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let input = args_1::<ByteString>(span, args, None)?;
+        let bytes = input.as_bytes();
+        Ok(C::Constant(match self {
+            Self::Md5 => hex_digest::<Md5>(bytes),
+            Self::Sha1 => hex_digest::<Sha1>(bytes),
+            Self::Sha256 => hex_digest::<Sha256>(bytes),
+            Self::Crc32 => crc32(bytes).into(),
+            Self::Fnv64 => fnv1a(bytes.iter().copied()).into(),
+        }))
+    }
+}
+
+#[test]
+fn test_crc32() {
+    assert_eq!(crc32(b""), 0);
+    assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+}
@@ -0,0 +1,54 @@
+//! JSON functions.
+
+use super::{args_1, iter_args, require, Arguments, Function};
+use crate::{
+    error::Error,
+    eval::{CompileContext, C},
+    span::{ResultExt, Span, SpanExt, S},
+    value::Value,
+};
+use std::{convert::TryFrom, sync::Arc};
+
+/// The `json.object` SQL function.
+#[derive(Debug)]
+pub struct Object;
+
+impl Function for Object {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        require(span, args.len() % 2 == 0, || {
+            format!("expecting key-value pairs, but found {} arguments", args.len())
+        })?;
+        let mut map = serde_json::Map::new();
+        let mut args = args.into_iter();
+        while let (Some(key), Some(value)) = (args.next(), args.next()) {
+            let key = String::try_from(key.inner).span_err(key.span)?;
+            let value = serde_json::Value::try_from(value.inner).span_err(value.span)?;
+            map.insert(key, value);
+        }
+        Ok(C::Constant(Value::Json(Arc::new(map.into()))))
+    }
+}
+
+/// The `json.array` SQL function.
+#[derive(Debug)]
+pub struct Array;
+
+impl Function for Array {
+    fn compile(&self, _: &CompileContext, _: Span, args: Arguments) -> Result<C, S<Error>> {
+        let items = iter_args::<serde_json::Value>(args).collect::<Result<Vec<_>, _>>()?;
+        Ok(C::Constant(Value::Json(Arc::new(items.into()))))
+    }
+}
+
+/// The `json.parse` SQL function.
+#[derive(Debug)]
+pub struct Parse;
+
+impl Function for Parse {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let text = args_1::<String>(span, args, None)?;
+        let json = serde_json::from_str(&text)
+            .map_err(|e| Error::InvalidArguments(format!("invalid JSON: {e}")).span(span))?;
+        Ok(C::Constant(Value::Json(Arc::new(json))))
+    }
+}
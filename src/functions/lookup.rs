@@ -0,0 +1,267 @@
+//! Lookup functions.
+
+use super::{args_2, args_3, require, Arguments, Function};
+use crate::{
+    error::Error,
+    eval::{CompileContext, Compiled, C},
+    span::{ResultExt, Span, SpanExt, S},
+    value::Value,
+};
+use std::{
+    collections::{BTreeSet, HashMap},
+    convert::TryFrom,
+    env,
+    fs::read_to_string,
+    path::Path,
+    sync::Arc,
+};
+
+/// A keyed table loaded from a CSV or JSON file.
+#[derive(Debug)]
+pub struct LookupTable {
+    /// The column names, excluding the key column.
+    columns: Vec<String>,
+    /// The rows indexed by the key.
+    rows: HashMap<Vec<u8>, Box<[Value]>>,
+}
+
+/// Converts a value into the key used to find a row.
+///
+/// Strings are used as-is, and numbers are converted to their decimal representation. Returns
+/// `None` for NULL, which never matches any row.
+fn key_bytes(value: &Value) -> Result<Option<Vec<u8>>, Error> {
+    match value {
+        Value::Null => Ok(None),
+        Value::Bytes(bytes) => Ok(Some(bytes.as_bytes().to_vec())),
+        Value::Number(n) => Ok(Some(n.to_string().into_bytes())),
+        _ => Err(Error::UnexpectedValueType {
+            expected: "string or number",
+            value: value.to_string(),
+        }),
+    }
+}
+
+/// Converts an unquoted CSV cell into a value.
+///
+/// Empty cells become NULL, and cells looking like numbers become numbers.
+pub(crate) fn csv_cell_to_value(cell: String) -> Value {
+    if cell.is_empty() {
+        Value::Null
+    } else if let Ok(n) = cell.parse::<i64>() {
+        n.into()
+    } else if let Ok(n) = cell.parse::<u64>() {
+        n.into()
+    } else if let Some(n) = cell.parse::<f64>().ok().filter(|n| n.is_finite()) {
+        Value::from_finite_f64(n)
+    } else {
+        cell.into()
+    }
+}
+
+/// Splits the CSV content into rows of cells, also reporting whether each cell is quoted.
+pub(crate) fn parse_csv(content: &str) -> Result<Vec<Vec<(String, bool)>>, String> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if cell.is_empty() && !quoted => {
+                quoted = true;
+                loop {
+                    match chars.next() {
+                        Some('"') if chars.peek() == Some(&'"') => {
+                            chars.next();
+                            cell.push('"');
+                        }
+                        Some('"') => break,
+                        Some(c) => cell.push(c),
+                        None => return Err("unterminated quoted field".to_owned()),
+                    }
+                }
+            }
+            ',' => row.push((std::mem::take(&mut cell), std::mem::take(&mut quoted))),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' => {
+                row.push((std::mem::take(&mut cell), std::mem::take(&mut quoted)));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => cell.push(c),
+        }
+    }
+    if !cell.is_empty() || quoted || !row.is_empty() {
+        row.push((cell, quoted));
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+impl LookupTable {
+    /// Loads a lookup table from a file. The file is parsed as JSON if the extension is `.json`,
+    /// and as CSV otherwise.
+    fn load(path: &Path) -> Result<Self, Error> {
+        let content = read_to_string(path).map_err(|source| Error::Io {
+            action: "read lookup file",
+            path: path.to_owned(),
+            source,
+        })?;
+        let is_json = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+        let table = if is_json {
+            Self::from_json(&content)
+        } else {
+            Self::from_csv(&content)
+        };
+        table.map_err(|e| Error::InvalidArguments(format!("invalid lookup file {}: {e}", path.display())))
+    }
+
+    /// Parses a CSV file. The first row contains the column names, and the first column contains
+    /// the keys.
+    fn from_csv(content: &str) -> Result<Self, String> {
+        let mut csv_rows = parse_csv(content)?.into_iter();
+        let header = csv_rows.next().ok_or("missing header row")?;
+        if header.len() < 2 {
+            return Err("expecting at least 2 columns".to_owned());
+        }
+        let columns = header.into_iter().skip(1).map(|(name, _)| name).collect::<Vec<_>>();
+
+        let mut rows = HashMap::new();
+        for (csv_row, line) in csv_rows.zip(2..) {
+            if csv_row.len() != columns.len() + 1 {
+                return Err(format!(
+                    "row {line} has {} columns, expecting {}",
+                    csv_row.len(),
+                    columns.len() + 1
+                ));
+            }
+            let mut cells = csv_row.into_iter();
+            let (key, _) = cells.next().expect("row should have a key");
+            let values = cells
+                .map(|(cell, quoted)| if quoted { cell.into() } else { csv_cell_to_value(cell) })
+                .collect();
+            if rows.insert(key.into_bytes(), values).is_some() {
+                return Err(format!("duplicated key in row {line}"));
+            }
+        }
+        Ok(Self { columns, rows })
+    }
+
+    /// Parses a JSON file. The file should be an object mapping every key to an object of columns.
+    fn from_json(content: &str) -> Result<Self, String> {
+        let json: HashMap<String, serde_json::Map<String, serde_json::Value>> =
+            serde_json::from_str(content).map_err(|e| e.to_string())?;
+        let columns = json
+            .values()
+            .flat_map(|row| row.keys())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let mut rows = HashMap::with_capacity(json.len());
+        for (key, row) in &json {
+            let values = columns
+                .iter()
+                .map(|column| match row.get(column) {
+                    None | Some(serde_json::Value::Null) => Ok(Value::Null),
+                    Some(serde_json::Value::Bool(b)) => Ok((*b).into()),
+                    Some(serde_json::Value::String(s)) => Ok(s.clone().into()),
+                    Some(serde_json::Value::Number(n)) => n
+                        .as_i64()
+                        .map(Value::from)
+                        .or_else(|| n.as_u64().map(Value::from))
+                        .or_else(|| n.as_f64().filter(|n| n.is_finite()).map(Value::from_finite_f64))
+                        .ok_or_else(|| format!("unsupported number {n} in key {key}")),
+                    Some(v) => Err(format!("unsupported value {v} in key {key}")),
+                })
+                .collect::<Result<_, _>>()?;
+            rows.insert(key.clone().into_bytes(), values);
+        }
+        Ok(Self { columns, rows })
+    }
+
+    /// Finds the index of the column.
+    fn column_index(&self, name: &str) -> Option<usize> {
+        self.columns.iter().position(|c| c == name)
+    }
+
+    /// Looks up the value of the column in the row matching the key. Returns NULL if no rows
+    /// match.
+    pub(crate) fn get(&self, key: &Value, column: usize) -> Result<Value, Error> {
+        Ok(key_bytes(key)?
+            .and_then(|key| self.rows.get(&key))
+            .map_or(Value::Null, |row| row[column].clone()))
+    }
+}
+
+/// The `lookup_file` SQL function.
+#[derive(Debug)]
+pub struct LookupFile;
+
+/// Loads the lookup table and finds the column index.
+fn load_lookup_table(path: &str, column: &str, span: Span) -> Result<(Arc<LookupTable>, usize), S<Error>> {
+    let table = LookupTable::load(Path::new(path)).span_err(span)?;
+    let index = table.column_index(column).ok_or_else(|| {
+        Error::InvalidArguments(format!("column {column} does not exist in lookup file {path}")).span(span)
+    })?;
+    Ok((Arc::new(table), index))
+}
+
+impl Function for LookupFile {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (path, key, column) = args_3::<String, Value, String>(span, args, None, None, None)?;
+        let (table, index) = load_lookup_table(&path, &column, span)?;
+        Ok(C::Constant(table.get(&key, index).span_err(span)?))
+    }
+
+    fn compile_partial(&self, _: &CompileContext, span: Span, args: &[Compiled]) -> Result<Option<C>, S<Error>> {
+        let [path, key, column] = args else {
+            return Ok(None);
+        };
+        let (Some(path), Some(column)) = (path.as_constant(), column.as_constant()) else {
+            return Ok(None);
+        };
+        let path = String::try_from(path.clone()).span_err(span)?;
+        let column = String::try_from(column.clone()).span_err(span)?;
+        let (table, column) = load_lookup_table(&path, &column, span)?;
+        Ok(Some(C::Lookup {
+            table,
+            key: Box::new(key.clone()),
+            column,
+        }))
+    }
+}
+
+/// The `ref` SQL function.
+#[derive(Debug)]
+pub struct Ref;
+
+impl Function for Ref {
+    fn compile(&self, ctx: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (table, column, capacity) = args_3::<String, String, usize>(span, args, None, None, Some(65536))?;
+        require(span, capacity > 0, || "capacity must be positive".to_owned())?;
+        Ok(C::Ref(ctx.register_reference(table, column, capacity)))
+    }
+}
+
+/// The `env` SQL function.
+#[derive(Debug)]
+pub struct Env;
+
+impl Function for Env {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (name, default) = args_2::<String, Value>(span, args, None, Some(Value::Null))?;
+        Ok(C::Constant(match env::var(&name) {
+            Ok(value) if value.is_empty() => value.into(),
+            // like `--var`, values which look like numbers are treated as numbers.
+            Ok(value) => csv_cell_to_value(value),
+            Err(env::VarError::NotPresent) => default,
+            Err(env::VarError::NotUnicode(_)) => {
+                return Err(
+                    Error::InvalidArguments(format!("environment variable {name} is not valid UTF-8")).span(span),
+                );
+            }
+        }))
+    }
+}
@@ -2,7 +2,7 @@
 
 use crate::{
     error::Error,
-    eval::{CompileContext, C},
+    eval::{CompileContext, Compiled, C},
     span::{ResultExt, Span, SpanExt, S},
     value::Value,
 };
@@ -10,12 +10,19 @@ use crate::{
 use std::{convert::TryFrom, fmt::Debug};
 
 pub mod array;
+pub mod cast;
 pub mod codec;
 pub mod debug;
+pub mod dirty;
+pub mod hash;
+pub mod json;
+pub mod lookup;
 pub mod ops;
 pub mod rand;
+pub mod sequence;
 pub mod string;
 pub mod time;
+pub mod unique;
 
 /// Container of the arguments passed to functions.
 pub type Arguments = smallvec::SmallVec<[S<Value>; 2]>;
@@ -24,6 +31,13 @@ pub type Arguments = smallvec::SmallVec<[S<Value>; 2]>;
 pub trait Function: Sync + Debug {
     /// Compiles or evaluates this function taking the provided arguments.
     fn compile(&self, ctx: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>>;
+
+    /// Compiles this function when some arguments are not constant.
+    ///
+    /// Returns `None` to re-evaluate the arguments and call `compile()` on every row.
+    fn compile_partial(&self, _: &CompileContext, _: Span, _: &[Compiled]) -> Result<Option<C>, S<Error>> {
+        Ok(None)
+    }
 }
 
 trait TryFromSpannedValue: Sized {
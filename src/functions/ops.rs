@@ -1,10 +1,11 @@
 //! Numerical and logical functions.
 
-use super::{args_1, args_2, iter_args, Arguments, Function};
+use super::{args_1, args_2, args_3, iter_args, require, Arguments, Function};
 use crate::{
     error::Error,
-    eval::{CompileContext, C},
-    span::{ResultExt, Span, S},
+    eval::{CompileContext, Compiled, C},
+    number::{Number, MAX_DECIMAL_PRECISION},
+    span::{ResultExt, Span, SpanExt, S},
     value::Value,
 };
 use std::cmp::Ordering;
@@ -167,6 +168,13 @@ impl Function for Logic {
         }
         Ok(C::Constant(result.into()))
     }
+
+    fn compile_partial(&self, _: &CompileContext, _: Span, args: &[Compiled]) -> Result<Option<C>, S<Error>> {
+        Ok(Some(C::Logic {
+            identity: self.identity,
+            args: args.into(),
+        }))
+    }
 }
 
 //------------------------------------------------------------------------------
@@ -286,6 +294,42 @@ impl Function for Round {
 
 //------------------------------------------------------------------------------
 
+/// The `decimal` SQL function.
+#[derive(Debug)]
+pub struct Decimal;
+
+impl Function for Decimal {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (value, precision, scale) = args_3::<Value, u8, u8>(span, args, None, None, Some(0))?;
+        require(
+            span,
+            (1..=MAX_DECIMAL_PRECISION).contains(&precision) && scale <= precision,
+            || format!("invalid decimal precision and scale ({precision}, {scale})"),
+        )?;
+        let number = match &value {
+            Value::Null => return Ok(C::Constant(Value::Null)),
+            Value::Number(number) => Some(*number),
+            Value::Bytes(bytes) => std::str::from_utf8(bytes.as_bytes())
+                .ok()
+                .and_then(Number::parse_decimal),
+            _ => None,
+        }
+        .ok_or_else(|| {
+            Error::UnexpectedValueType {
+                expected: "decimal number",
+                value: value.to_string(),
+            }
+            .span(span)
+        })?;
+        let result = number
+            .to_decimal(precision, scale)
+            .map_err(|_| Error::IntegerOverflow(format!("decimal({value}, {precision}, {scale})")).span(span))?;
+        Ok(C::Constant(Value::Number(result)))
+    }
+}
+
+//------------------------------------------------------------------------------
+
 /// The `div` SQL function.
 #[derive(Debug)]
 pub struct Div;
@@ -323,6 +367,60 @@ impl Function for Coalesce {
             .unwrap_or(Value::Null);
         Ok(C::Constant(res))
     }
+
+    fn compile_partial(&self, _: &CompileContext, _: Span, args: &[Compiled]) -> Result<Option<C>, S<Error>> {
+        // arguments after a constant non-NULL value are never needed.
+        let end = args
+            .iter()
+            .position(|arg| arg.as_constant().is_some_and(|v| *v != Value::Null))
+            .map_or(args.len(), |i| i + 1);
+        Ok(Some(C::Coalesce(args[..end].into())))
+    }
+}
+
+/// The `ifnull` SQL function.
+#[derive(Debug)]
+pub struct IfNull;
+
+impl Function for IfNull {
+    fn compile(&self, ctx: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        require(span, args.len() == 2, || {
+            format!("expecting 2 arguments, but found {}", args.len())
+        })?;
+        Coalesce.compile(ctx, span, args)
+    }
+
+    fn compile_partial(&self, ctx: &CompileContext, span: Span, args: &[Compiled]) -> Result<Option<C>, S<Error>> {
+        require(span, args.len() == 2, || {
+            format!("expecting 2 arguments, but found {}", args.len())
+        })?;
+        Coalesce.compile_partial(ctx, span, args)
+    }
+}
+
+/// The `nullif` SQL function.
+#[derive(Debug)]
+pub struct NullIf;
+
+impl Function for NullIf {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (value, other) = args_2::<Value, Value>(span, args, None, None)?;
+        let is_eq = value.sql_cmp(&other).span_err(span)? == Some(Ordering::Equal);
+        Ok(C::Constant(if is_eq { Value::Null } else { value }))
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// The `row_checksum` SQL function.
+#[derive(Debug)]
+pub struct RowChecksum;
+
+impl Function for RowChecksum {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        require(span, args.is_empty(), || "row_checksum() takes no arguments".to_owned())?;
+        Ok(C::RowChecksum)
+    }
 }
 
 //------------------------------------------------------------------------------
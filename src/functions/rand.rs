@@ -1,17 +1,121 @@
 //! Random generator functions.
 
-use super::{args_1, args_2, args_3, require, Arguments, Function};
+use super::{
+    args_1, args_2, args_3, args_4,
+    lookup::{csv_cell_to_value, parse_csv},
+    require, Arguments, Function, TryFromSpannedValue,
+};
 use crate::{
+    array::{self, Array},
     error::Error,
-    eval::{CompileContext, C},
+    eval::{fnv1a, CompileContext, Compiled, C},
     number::Number,
     span::{ResultExt, Span, SpanExt, S},
+    value::Value,
 };
-use std::convert::TryFrom as _;
+use rand::{Rng as _, RngCore};
+use rand_distr::weighted_alias::WeightedAliasIndex;
+use std::{collections::HashMap, convert::TryFrom as _, fmt::Write as _, fs::read_to_string, path::PathBuf, sync::Arc};
 use zipf::ZipfDistribution;
 
 //------------------------------------------------------------------------------
 
+/// The random distributions whose parameters can vary per row.
+///
+/// When some parameters are not constant, instead of recompiling the whole function on every row,
+/// the parameters are evaluated and the distribution is created directly before sampling it. The
+/// values are the same as if the function were recompiled.
+#[derive(Debug, Copy, Clone)]
+pub enum PerRowDistribution {
+    /// `rand.range`
+    Range,
+    /// `rand.range_inclusive`
+    RangeInclusive,
+    /// `rand.uniform`
+    Uniform,
+    /// `rand.uniform_inclusive`
+    UniformInclusive,
+    /// `rand.normal`
+    Normal,
+    /// `rand.log_normal`
+    LogNormal,
+    /// `rand.exp`
+    Exp,
+    /// `rand.bool`
+    Bool,
+    /// `rand.poisson`
+    Poisson,
+    /// `rand.latest`
+    Latest,
+}
+
+impl PerRowDistribution {
+    /// The name of the SQL function.
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Self::Range => "rand.range",
+            Self::RangeInclusive => "rand.range_inclusive",
+            Self::Uniform => "rand.uniform",
+            Self::UniformInclusive => "rand.uniform_inclusive",
+            Self::Normal => "rand.normal",
+            Self::LogNormal => "rand.log_normal",
+            Self::Exp => "rand.exp",
+            Self::Bool => "rand.bool",
+            Self::Poisson => "rand.poisson",
+            Self::Latest => "rand.latest",
+        }
+    }
+
+    /// The number of parameters of the distribution.
+    fn arity(self) -> usize {
+        match self {
+            Self::Range
+            | Self::RangeInclusive
+            | Self::Uniform
+            | Self::UniformInclusive
+            | Self::Normal
+            | Self::LogNormal
+            | Self::Latest => 2,
+            Self::Exp | Self::Bool | Self::Poisson => 1,
+        }
+    }
+
+    /// Compiles the function with the non-constant parameters into a per-row distribution.
+    ///
+    /// Returns `None` if the number of arguments is wrong, so the error is reported when the
+    /// function is compiled on the first row.
+    fn compile(self, args: &[Compiled]) -> Option<C> {
+        (args.len() == self.arity()).then(|| C::RandPerRow {
+            distribution: self,
+            args: args.into(),
+        })
+    }
+
+    /// Creates the distribution from the parameters evaluated in the current row.
+    pub(crate) fn create(self, span: Span, params: [S<Value>; 2]) -> Result<C, S<Error>> {
+        let [a, b] = params;
+        match self {
+            Self::Range => range(span, convert(a)?, convert(b)?, false),
+            Self::RangeInclusive => range(span, convert(a)?, convert(b)?, true),
+            Self::Uniform => uniform(span, convert(a)?, convert(b)?, false),
+            Self::UniformInclusive => uniform(span, convert(a)?, convert(b)?, true),
+            Self::Normal => normal(span, convert(a)?, convert(b)?),
+            Self::LogNormal => log_normal(span, convert(a)?, convert(b)?),
+            Self::Exp => exp(span, convert(a)?),
+            Self::Bool => bernoulli(span, convert(a)?),
+            Self::Poisson => poisson(span, convert(a)?),
+            Self::Latest => latest(span, convert(a)?, convert(b)?),
+        }
+    }
+}
+
+/// Converts an evaluated parameter into the required type.
+fn convert<T: TryFromSpannedValue>(param: S<Value>) -> Result<T, S<Error>> {
+    T::try_from_spanned_value(param)
+}
+
+//------------------------------------------------------------------------------
+
 /// The `rand.range` SQL function.
 #[derive(Debug)]
 pub struct Range;
@@ -20,28 +124,56 @@ pub struct Range;
 #[derive(Debug)]
 pub struct RangeInclusive;
 
-macro_rules! impl_rand_range {
-    ($name:expr, $cmp:tt, $new:ident) => {
-        fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
-            let (lower, upper) = args_2::<Number, Number>(span, args, None, None)?;
-            require(span, lower $cmp upper, || format!("assertion failed: {} {} {}", lower, stringify!($cmp), upper))?;
-            if let (Ok(a), Ok(b)) = (u64::try_from(lower), u64::try_from(upper)) {
-                Ok(C::RandUniformU64(rand_distr::Uniform::$new(a, b)))
-            } else if let (Ok(a), Ok(b)) = (i64::try_from(lower), i64::try_from(upper)) {
-                Ok(C::RandUniformI64(rand_distr::Uniform::$new(a, b)))
-            } else {
-                Err(Error::IntegerOverflow(format!("{}({}, {})", $name, lower, upper)).span(span))
-            }
-        }
+/// Creates the integer distribution of `rand.range` and `rand.range_inclusive`.
+fn range(span: Span, lower: Number, upper: Number, inclusive: bool) -> Result<C, S<Error>> {
+    let (name, valid) = if inclusive {
+        ("rand.range_inclusive", lower <= upper)
+    } else {
+        ("rand.range", lower < upper)
+    };
+    require(span, valid, || {
+        format!(
+            "assertion failed: {lower} {} {upper}",
+            if inclusive { "<=" } else { "<" }
+        )
+    })?;
+    if let (Ok(a), Ok(b)) = (u64::try_from(lower), u64::try_from(upper)) {
+        Ok(C::RandUniformU64(if inclusive {
+            rand_distr::Uniform::new_inclusive(a, b)
+        } else {
+            rand_distr::Uniform::new(a, b)
+        }))
+    } else if let (Ok(a), Ok(b)) = (i64::try_from(lower), i64::try_from(upper)) {
+        Ok(C::RandUniformI64(if inclusive {
+            rand_distr::Uniform::new_inclusive(a, b)
+        } else {
+            rand_distr::Uniform::new(a, b)
+        }))
+    } else {
+        Err(Error::IntegerOverflow(format!("{name}({lower}, {upper})")).span(span))
     }
 }
 
 impl Function for Range {
-    impl_rand_range!("rand.range", <, new);
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (lower, upper) = args_2(span, args, None, None)?;
+        range(span, lower, upper, false)
+    }
+
+    fn compile_partial(&self, _: &CompileContext, _: Span, args: &[Compiled]) -> Result<Option<C>, S<Error>> {
+        Ok(PerRowDistribution::Range.compile(args))
+    }
 }
 
 impl Function for RangeInclusive {
-    impl_rand_range!("rand.range_inclusive", <=, new_inclusive);
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (lower, upper) = args_2(span, args, None, None)?;
+        range(span, lower, upper, true)
+    }
+
+    fn compile_partial(&self, _: &CompileContext, _: Span, args: &[Compiled]) -> Result<Option<C>, S<Error>> {
+        Ok(PerRowDistribution::RangeInclusive.compile(args))
+    }
 }
 
 //------------------------------------------------------------------------------
@@ -54,22 +186,37 @@ pub struct Uniform;
 #[derive(Debug)]
 pub struct UniformInclusive;
 
-macro_rules! impl_rand_uniform {
-    ($cmp:tt, $new:ident) => {
-        fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
-            let (lower, upper) = args_2::<f64, f64>(span, args, None, None)?;
-            require(span, lower $cmp upper, || format!("assertion failed: {} {} {}", lower, stringify!($cmp), upper))?;
-            Ok(C::RandUniformF64(rand_distr::Uniform::$new(lower, upper)))
-        }
+/// Creates the floating point distribution of `rand.uniform` and `rand.uniform_inclusive`.
+fn uniform(span: Span, lower: f64, upper: f64, inclusive: bool) -> Result<C, S<Error>> {
+    if inclusive {
+        require(span, lower <= upper, || format!("assertion failed: {lower} <= {upper}"))?;
+        Ok(C::RandUniformF64(rand_distr::Uniform::new_inclusive(lower, upper)))
+    } else {
+        require(span, lower < upper, || format!("assertion failed: {lower} < {upper}"))?;
+        Ok(C::RandUniformF64(rand_distr::Uniform::new(lower, upper)))
     }
 }
 
 impl Function for Uniform {
-    impl_rand_uniform!(<, new);
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (lower, upper) = args_2(span, args, None, None)?;
+        uniform(span, lower, upper, false)
+    }
+
+    fn compile_partial(&self, _: &CompileContext, _: Span, args: &[Compiled]) -> Result<Option<C>, S<Error>> {
+        Ok(PerRowDistribution::Uniform.compile(args))
+    }
 }
 
 impl Function for UniformInclusive {
-    impl_rand_uniform!(<=, new_inclusive);
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (lower, upper) = args_2(span, args, None, None)?;
+        uniform(span, lower, upper, true)
+    }
+
+    fn compile_partial(&self, _: &CompileContext, _: Span, args: &[Compiled]) -> Result<Option<C>, S<Error>> {
+        Ok(PerRowDistribution::UniformInclusive.compile(args))
+    }
 }
 
 //------------------------------------------------------------------------------
@@ -87,19 +234,290 @@ impl Function for Zipf {
     }
 }
 
+/// The `rand.hotspot` SQL function.
+#[derive(Debug)]
+pub struct Hotspot;
+
+impl Function for Hotspot {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (count, hot_fraction, hot_probability) = args_3::<u64, f64, f64>(span, args, None, None, None)?;
+        require(span, count > 0, || format!("count ({count}) must be positive"))?;
+        require(span, (0.0..=1.0).contains(&hot_fraction), || {
+            format!("hot fraction ({hot_fraction}) must be between 0 and 1")
+        })?;
+        let hot = rand_distr::Bernoulli::new(hot_probability)
+            .map_err(|e| Error::InvalidArguments(format!("hot probability ({hot_probability}) {e}")).span(span))?;
+        // like YCSB, the hot set consists of the first keys, but always contains at least one.
+        // ALLOW_REASON: the product is between 0 and count.
+        #[allow(
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss,
+            clippy::cast_precision_loss
+        )]
+        let hot_count = ((count as f64 * hot_fraction) as u64).clamp(1, count);
+        Ok(C::RandHotspot {
+            hot,
+            hot_keys: rand_distr::Uniform::new_inclusive(1, hot_count),
+            cold_keys: (hot_count < count).then(|| rand_distr::Uniform::new_inclusive(hot_count + 1, count)),
+        })
+    }
+}
+
+/// The `rand.latest` SQL function.
+#[derive(Debug)]
+pub struct Latest;
+
+/// Creates the distribution of `rand.latest`, which is the Zipfian distribution counted backwards
+/// from `count`.
+fn latest(span: Span, count: usize, exponent: f64) -> Result<C, S<Error>> {
+    let zipf = ZipfDistribution::new(count, exponent).map_err(|()| {
+        Error::InvalidArguments(format!("count ({count}) and exponent ({exponent}) must be positive")).span(span)
+    })?;
+    Ok(C::RandLatest { count, zipf })
+}
+
+impl Function for Latest {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (count, exponent) = args_2(span, args, None, None)?;
+        latest(span, count, exponent)
+    }
+
+    fn compile_partial(&self, _: &CompileContext, _: Span, args: &[Compiled]) -> Result<Option<C>, S<Error>> {
+        Ok(PerRowDistribution::Latest.compile(args))
+    }
+}
+
 //------------------------------------------------------------------------------
 
 /// The `rand.log_normal` SQL function.
 #[derive(Debug)]
 pub struct LogNormal;
 
+/// Creates the distribution of `rand.log_normal`.
+fn log_normal(span: Span, mean: f64, std_dev: f64) -> Result<C, S<Error>> {
+    let std_dev = std_dev.abs();
+    Ok(C::RandLogNormal(rand_distr::LogNormal::new(mean, std_dev).map_err(
+        |e| Error::InvalidArguments(format!("standard deviation ({std_dev}) {e}")).span(span),
+    )?))
+}
+
 impl Function for LogNormal {
     fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
-        let (mean, std_dev) = args_2::<f64, f64>(span, args, None, None)?;
-        let std_dev = std_dev.abs();
-        Ok(C::RandLogNormal(rand_distr::LogNormal::new(mean, std_dev).map_err(
-            |e| Error::InvalidArguments(format!("standard deviation ({std_dev}) {e}")).span(span),
-        )?))
+        let (mean, std_dev) = args_2(span, args, None, None)?;
+        log_normal(span, mean, std_dev)
+    }
+
+    fn compile_partial(&self, _: &CompileContext, _: Span, args: &[Compiled]) -> Result<Option<C>, S<Error>> {
+        Ok(PerRowDistribution::LogNormal.compile(args))
+    }
+}
+
+/// The `rand.normal` SQL function.
+#[derive(Debug)]
+pub struct Normal;
+
+/// Creates the distribution of `rand.normal`.
+fn normal(span: Span, mean: f64, std_dev: f64) -> Result<C, S<Error>> {
+    require(span, std_dev >= 0.0, || {
+        format!("standard deviation ({std_dev}) must be non-negative")
+    })?;
+    Ok(C::RandNormal(rand_distr::Normal::new(mean, std_dev).map_err(|e| {
+        Error::InvalidArguments(format!("mean ({mean}) and standard deviation ({std_dev}): {e}")).span(span)
+    })?))
+}
+
+impl Function for Normal {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (mean, std_dev) = args_2(span, args, None, None)?;
+        normal(span, mean, std_dev)
+    }
+
+    fn compile_partial(&self, _: &CompileContext, _: Span, args: &[Compiled]) -> Result<Option<C>, S<Error>> {
+        Ok(PerRowDistribution::Normal.compile(args))
+    }
+}
+
+/// The `rand.exp` SQL function.
+#[derive(Debug)]
+pub struct Exp;
+
+/// Creates the distribution of `rand.exp`.
+fn exp(span: Span, lambda: f64) -> Result<C, S<Error>> {
+    require(span, lambda > 0.0, || format!("lambda ({lambda}) must be positive"))?;
+    Ok(C::RandExp(rand_distr::Exp::new(lambda).map_err(|e| {
+        Error::InvalidArguments(format!("lambda ({lambda}) {e}")).span(span)
+    })?))
+}
+
+impl Function for Exp {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        exp(span, args_1(span, args, None)?)
+    }
+
+    fn compile_partial(&self, _: &CompileContext, _: Span, args: &[Compiled]) -> Result<Option<C>, S<Error>> {
+        Ok(PerRowDistribution::Exp.compile(args))
+    }
+}
+
+/// The `rand.pareto` SQL function.
+#[derive(Debug)]
+pub struct Pareto;
+
+impl Function for Pareto {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (scale, shape) = args_2::<f64, f64>(span, args, None, None)?;
+        Ok(C::RandPareto(rand_distr::Pareto::new(scale, shape).map_err(|e| {
+            Error::InvalidArguments(format!("scale ({scale}) and shape ({shape}): {e}")).span(span)
+        })?))
+    }
+}
+
+/// Computes the lower triangular matrix *L* of the Cholesky decomposition *LL*<sup>T</sup> of a
+/// symmetric positive semi-definite `n`×`n` matrix, both stored in row-major order.
+///
+/// Returns `None` if the matrix is not positive semi-definite.
+fn cholesky(matrix: &[f64], n: usize) -> Option<Vec<f64>> {
+    let mut l = vec![0.0; n * n];
+    for j in 0..n {
+        let diagonal = matrix[j * n + j] - (0..j).map(|k| l[j * n + k] * l[j * n + k]).sum::<f64>();
+        let tolerance = matrix[j * n + j].abs() * 1e-12;
+        if diagonal < -tolerance || diagonal.is_nan() {
+            return None;
+        }
+        if diagonal <= tolerance {
+            // a degenerate (perfectly correlated) dimension, the remaining column must vanish.
+            for i in j + 1..n {
+                let rest = matrix[i * n + j] - (0..j).map(|k| l[i * n + k] * l[j * n + k]).sum::<f64>();
+                if rest.abs() > tolerance.max(1e-12) {
+                    return None;
+                }
+            }
+            continue;
+        }
+        let pivot = diagonal.sqrt();
+        l[j * n + j] = pivot;
+        for i in j + 1..n {
+            let rest = matrix[i * n + j] - (0..j).map(|k| l[i * n + k] * l[j * n + k]).sum::<f64>();
+            l[i * n + j] = rest / pivot;
+        }
+    }
+    Some(l)
+}
+
+/// The `rand.multivariate_normal` SQL function.
+#[derive(Debug)]
+pub struct MultivariateNormal;
+
+impl Function for MultivariateNormal {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (means, covariance) = args_2::<Array, Array>(span, args, None, None)?;
+        let means = means
+            .iter()
+            .map(f64::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .span_err(span)?;
+        let n = means.len();
+        require(span, covariance.len() == n as u64, || {
+            format!(
+                "number of means ({n}) and rows of the covariance matrix ({}) must be equal",
+                covariance.len()
+            )
+        })?;
+        let mut matrix = Vec::with_capacity(n * n);
+        for row in covariance.iter() {
+            let row = Array::try_from(row).span_err(span)?;
+            require(span, row.len() == n as u64, || {
+                format!(
+                    "the covariance matrix must be {n}×{n}, but found a row of {}",
+                    row.len()
+                )
+            })?;
+            for value in row.iter() {
+                matrix.push(f64::try_from(value).span_err(span)?);
+            }
+        }
+        for i in 0..n {
+            for j in 0..i {
+                let (a, b) = (matrix[i * n + j], matrix[j * n + i]);
+                require(span, (a - b).abs() <= f64::EPSILON * a.abs().max(b.abs()), || {
+                    format!(
+                        "the covariance matrix must be symmetric, but [{0}][{1}] ({a}) ≠ [{1}][{0}] ({b})",
+                        i + 1,
+                        j + 1,
+                    )
+                })?;
+            }
+        }
+        let cholesky = cholesky(&matrix, n).ok_or_else(|| {
+            Error::InvalidArguments("the covariance matrix must be positive semi-definite".to_owned()).span(span)
+        })?;
+        Ok(C::RandMultivariateNormal {
+            means: means.into(),
+            cholesky: cholesky.into(),
+        })
+    }
+}
+
+/// The `rand.correlated_pair` SQL function.
+#[derive(Debug)]
+pub struct CorrelatedPair;
+
+impl Function for CorrelatedPair {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let rho = args_1::<f64>(span, args, None)?;
+        require(span, (-1.0..=1.0).contains(&rho), || {
+            format!("correlation coefficient ({rho}) must be in the range -1 ≤ ρ ≤ 1")
+        })?;
+        Ok(C::RandMultivariateNormal {
+            means: Box::new([0.0, 0.0]),
+            cholesky: Box::new([1.0, 0.0, rho, (1.0 - rho * rho).sqrt()]),
+        })
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// The mean radius of the Earth in meters.
+const EARTH_RADIUS_M: f64 = 6_371_008.8;
+
+/// The `rand.point_in_bbox` SQL function.
+#[derive(Debug)]
+pub struct PointInBbox;
+
+impl Function for PointInBbox {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (min_lon, min_lat, max_lon, max_lat) = args_4::<f64, f64, f64, f64>(span, args, None, None, None, None)?;
+        require(span, min_lon <= max_lon, || {
+            format!("min_lon ({min_lon}) must not be greater than max_lon ({max_lon})")
+        })?;
+        require(span, min_lat <= max_lat, || {
+            format!("min_lat ({min_lat}) must not be greater than max_lat ({max_lat})")
+        })?;
+        Ok(C::RandPointInBbox {
+            lon: rand_distr::Uniform::new_inclusive(min_lon, max_lon),
+            lat: rand_distr::Uniform::new_inclusive(min_lat, max_lat),
+        })
+    }
+}
+
+/// The `rand.point_near` SQL function.
+#[derive(Debug)]
+pub struct PointNear;
+
+impl Function for PointNear {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (lon, lat, radius_m) = args_3::<f64, f64, f64>(span, args, None, None, None)?;
+        require(span, lat.abs() < 90.0, || {
+            format!("latitude ({lat}) must be in the range -90 < lat < 90")
+        })?;
+        require(span, radius_m >= 0.0, || {
+            format!("radius ({radius_m}) must not be negative")
+        })?;
+        Ok(C::RandPointNear {
+            lon,
+            lat,
+            radius: radius_m / EARTH_RADIUS_M,
+        })
     }
 }
 
@@ -109,15 +527,606 @@ impl Function for LogNormal {
 #[derive(Debug)]
 pub struct Bool;
 
+/// Creates the distribution of `rand.bool`.
+fn bernoulli(span: Span, p: f64) -> Result<C, S<Error>> {
+    Ok(C::RandBool(rand_distr::Bernoulli::new(p).map_err(|e| {
+        Error::InvalidArguments(format!("probability ({p}) {e}")).span(span)
+    })?))
+}
+
 impl Function for Bool {
     fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
-        let p = args_1(span, args, None)?;
-        Ok(C::RandBool(rand_distr::Bernoulli::new(p).map_err(|e| {
+        bernoulli(span, args_1(span, args, None)?)
+    }
+
+    fn compile_partial(&self, _: &CompileContext, _: Span, args: &[Compiled]) -> Result<Option<C>, S<Error>> {
+        Ok(PerRowDistribution::Bool.compile(args))
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// The `rand.poisson` SQL function.
+#[derive(Debug)]
+pub struct Poisson;
+
+/// Creates the distribution of `rand.poisson`.
+fn poisson(span: Span, lambda: f64) -> Result<C, S<Error>> {
+    Ok(C::RandPoisson(rand_distr::Poisson::new(lambda).map_err(|e| {
+        Error::InvalidArguments(format!("lambda ({lambda}) {e}")).span(span)
+    })?))
+}
+
+impl Function for Poisson {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        poisson(span, args_1(span, args, None)?)
+    }
+
+    fn compile_partial(&self, _: &CompileContext, _: Span, args: &[Compiled]) -> Result<Option<C>, S<Error>> {
+        Ok(PerRowDistribution::Poisson.compile(args))
+    }
+}
+
+/// The `rand.binomial` SQL function.
+#[derive(Debug)]
+pub struct Binomial;
+
+impl Function for Binomial {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (n, p) = args_2::<u64, f64>(span, args, None, None)?;
+        Ok(C::RandBinomial(rand_distr::Binomial::new(n, p).map_err(|e| {
             Error::InvalidArguments(format!("probability ({p}) {e}")).span(span)
         })?))
     }
 }
 
+/// The `rand.zero_inflated_poisson` SQL function.
+#[derive(Debug)]
+pub struct ZeroInflatedPoisson;
+
+impl Function for ZeroInflatedPoisson {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (p0, lambda) = args_2::<f64, f64>(span, args, None, None)?;
+        let zero = rand_distr::Bernoulli::new(p0)
+            .map_err(|e| Error::InvalidArguments(format!("probability ({p0}) {e}")).span(span))?;
+        let poisson = rand_distr::Poisson::new(lambda)
+            .map_err(|e| Error::InvalidArguments(format!("lambda ({lambda}) {e}")).span(span))?;
+        Ok(C::RandZeroInflatedPoisson { zero, poisson })
+    }
+}
+
+/// The `rand.neg_binomial` SQL function.
+#[derive(Debug)]
+pub struct NegBinomial;
+
+impl Function for NegBinomial {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (r, p) = args_2::<f64, f64>(span, args, None, None)?;
+        require(span, r > 0.0, || format!("number of successes ({r}) must be positive"))?;
+        require(span, 0.0 < p && p < 1.0, || {
+            format!("probability ({p}) must be in the range 0 < p < 1")
+        })?;
+        // NB(r, p) is equivalent to the Gamma-Poisson mixture with rate ~ Γ(r, (1 - p)/p).
+        let gamma = rand_distr::Gamma::new(r, (1.0 - p) / p)
+            .map_err(|e| Error::InvalidArguments(format!("invalid parameters: {e}")).span(span))?;
+        Ok(C::RandNegBinomial(gamma))
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// The `rand.categorical` SQL function.
+#[derive(Debug)]
+pub struct Categorical;
+
+impl Function for Categorical {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (values, weights) = args_2::<Array, Array>(span, args, None, None)?;
+        require(span, values.len() == weights.len(), || {
+            format!(
+                "number of values ({}) and weights ({}) must be equal",
+                values.len(),
+                weights.len()
+            )
+        })?;
+        let weights = weights
+            .iter()
+            .map(f64::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .span_err(span)?;
+        let weights = WeightedAliasIndex::new(weights)
+            .map_err(|e| Error::InvalidArguments(format!("invalid weights: {e}")).span(span))?;
+        Ok(C::RandCategorical {
+            values,
+            weights: Box::new(weights),
+        })
+    }
+}
+
+/// The `rand.switch` SQL function.
+#[derive(Debug)]
+pub struct Switch;
+
+impl Switch {
+    /// Creates the lookup table from the probabilities of the results. The remaining probability
+    /// is given to the default result at the end.
+    fn weights(span: Span, probabilities: Vec<f64>) -> Result<WeightedAliasIndex<f64>, S<Error>> {
+        for p in &probabilities {
+            require(span, (0.0..=1.0).contains(p), || {
+                format!("probability ({p}) must be between 0 and 1")
+            })?;
+        }
+        let total = probabilities.iter().sum::<f64>();
+        // allow some rounding error, e.g. 0.6 + 0.3 + 0.1 > 1.
+        require(span, total <= 1.0 + 1e-9, || {
+            format!("sum of probabilities ({total}) must not exceed 1")
+        })?;
+        let mut weights = probabilities;
+        weights.push((1.0 - total).max(0.0));
+        WeightedAliasIndex::new(weights)
+            .map_err(|e| Error::InvalidArguments(format!("invalid probabilities: {e}")).span(span))
+    }
+}
+
+impl Function for Switch {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let mut values = Vec::with_capacity(args.len() / 2 + 1);
+        let mut probabilities = Vec::with_capacity(args.len() / 2);
+        let mut args = args.into_iter();
+        while let Some(p) = args.next() {
+            let Some(result) = args.next() else {
+                // an odd argument at the end is the default result.
+                values.push(p.inner);
+                break;
+            };
+            probabilities.push(f64::try_from_spanned_value(p)?);
+            values.push(result.inner);
+        }
+        if values.len() == probabilities.len() {
+            values.push(Value::Null);
+        }
+        Ok(C::RandCategorical {
+            values: Array::from_values(values),
+            weights: Box::new(Self::weights(span, probabilities)?),
+        })
+    }
+
+    fn compile_partial(&self, _: &CompileContext, span: Span, args: &[Compiled]) -> Result<Option<C>, S<Error>> {
+        // the lookup table is built once if the probabilities are constant, even if the results
+        // vary per row.
+        let mut results = Vec::with_capacity(args.len() / 2 + 1);
+        let mut probabilities = Vec::with_capacity(args.len() / 2);
+        for pair in args.chunks(2) {
+            let [p, result] = pair else {
+                results.push(pair[0].clone());
+                break;
+            };
+            let Some(p) = p.as_constant() else {
+                return Ok(None);
+            };
+            probabilities.push(f64::try_from(p.clone()).span_err(span)?);
+            results.push(result.clone());
+        }
+        if results.len() == probabilities.len() {
+            results.push(Compiled(S {
+                span,
+                inner: C::Constant(Value::Null),
+            }));
+        }
+        Ok(Some(C::RandSwitch {
+            weights: Box::new(Self::weights(span, probabilities)?),
+            results: results.into_boxed_slice(),
+        }))
+    }
+}
+
+/// The `rand.from_file` SQL function.
+#[derive(Debug)]
+pub struct FromFile;
+
+impl Function for FromFile {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (path, exponent) = args_2::<String, Option<f64>>(span, args, None, Some(None))?;
+        let path = PathBuf::from(path);
+        let content = read_to_string(&path)
+            .map_err(|source| Error::Io {
+                action: "read values file",
+                path: path.clone(),
+                source,
+            })
+            .span_err(span)?;
+        let lines = content
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| Value::from(line.to_owned()))
+            .collect::<Vec<_>>();
+        require(span, !lines.is_empty(), || {
+            format!("values file {} has no content", path.display())
+        })?;
+        let zipf = exponent
+            .map(|exponent| {
+                ZipfDistribution::new(lines.len(), exponent)
+                    .map_err(|()| Error::InvalidArguments(format!("exponent ({exponent}) must be positive")).span(span))
+            })
+            .transpose()?;
+        Ok(C::RandFromFile {
+            lines: Array::from_values(lines),
+            zipf,
+        })
+    }
+}
+
+/// The `rand.markov` SQL function.
+#[derive(Debug)]
+pub struct Markov;
+
+/// A word-level Markov chain built from a text corpus.
+#[derive(Debug)]
+pub struct MarkovChain {
+    /// The distinct words of the corpus.
+    words: Vec<String>,
+    /// The indices of the words starting a sentence.
+    starts: Vec<usize>,
+    /// The indices of the words following every word, repeated as many times as they appear in
+    /// the corpus, so that frequent successors are picked more often.
+    successors: Vec<Vec<usize>>,
+}
+
+impl MarkovChain {
+    /// Builds the Markov chain from the corpus. Words are separated by whitespace, and a sentence
+    /// starts at the beginning of the corpus or after a word ending with `.`, `!` or `?`.
+    fn parse(content: &str) -> Self {
+        let mut indices = HashMap::new();
+        let mut words = Vec::new();
+        let mut starts = Vec::new();
+        let mut successors = Vec::<Vec<usize>>::new();
+        let mut prev: Option<usize> = None;
+        for word in content.split_whitespace() {
+            let index = *indices.entry(word).or_insert_with(|| {
+                words.push(word.to_owned());
+                successors.push(Vec::new());
+                words.len() - 1
+            });
+            match prev {
+                Some(prev) if !words[prev].ends_with(['.', '!', '?']) => successors[prev].push(index),
+                _ => starts.push(index),
+            }
+            prev = Some(index);
+        }
+        Self {
+            words,
+            starts,
+            successors,
+        }
+    }
+
+    /// Generates a text with the given number of words.
+    ///
+    /// When the chain reaches the end of a sentence, the next word starts a new sentence.
+    pub fn generate(&self, rng: &mut dyn RngCore, count: usize) -> String {
+        let mut result = String::new();
+        let mut current = None;
+        for _ in 0..count {
+            let candidates = current.map_or(&*self.starts, |c: usize| &*self.successors[c]);
+            let candidates = if candidates.is_empty() {
+                &*self.starts
+            } else {
+                candidates
+            };
+            let index = candidates[rng.gen_range(0..candidates.len())];
+            if current.is_some() {
+                result.push(' ');
+            }
+            result.push_str(&self.words[index]);
+            current = Some(index);
+        }
+        result
+    }
+}
+
+impl Markov {
+    /// Obtains the Markov chain of the corpus file, reading the file if it was not read before.
+    fn chain(ctx: &CompileContext, span: Span, path: String) -> Result<Arc<MarkovChain>, S<Error>> {
+        ctx.cached_markov_chain(PathBuf::from(path), |path| {
+            let content = read_to_string(path).map_err(|source| Error::Io {
+                action: "read corpus file",
+                path: path.to_owned(),
+                source,
+            })?;
+            let chain = MarkovChain::parse(&content);
+            if chain.starts.is_empty() {
+                return Err(Error::InvalidArguments(format!(
+                    "corpus file {} has no words",
+                    path.display()
+                )));
+            }
+            Ok(chain)
+        })
+        .span_err(span)
+    }
+}
+
+impl Function for Markov {
+    fn compile(&self, ctx: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (path, words) = args_2::<String, usize>(span, args, None, None)?;
+        Ok(C::RandMarkov {
+            chain: Self::chain(ctx, span, path)?,
+            words: Box::new(Compiled(S {
+                span,
+                inner: C::Constant(words.into()),
+            })),
+        })
+    }
+
+    fn compile_partial(&self, ctx: &CompileContext, span: Span, args: &[Compiled]) -> Result<Option<C>, S<Error>> {
+        // the corpus is only read once if the number of words varies per row.
+        let [path, words] = args else {
+            return Ok(None);
+        };
+        let Some(Value::Bytes(path)) = path.as_constant() else {
+            return Ok(None);
+        };
+        let path = String::try_from(Value::Bytes(path.clone())).span_err(span)?;
+        Ok(Some(C::RandMarkov {
+            chain: Self::chain(ctx, span, path)?,
+            words: Box::new(words.clone()),
+        }))
+    }
+}
+
+/// The `rand.quota` SQL function.
+#[derive(Debug)]
+pub struct Quota;
+
+/// Divides `total` into integer counts proportional to the weights, using the largest remainder
+/// method so the counts always sum to `total`.
+fn apportion(weights: &[f64], total: u64) -> Vec<u64> {
+    let sum = weights.iter().sum::<f64>();
+    let mut counts = Vec::with_capacity(weights.len());
+    let mut remainders = Vec::with_capacity(weights.len());
+    for (i, weight) in weights.iter().enumerate() {
+        // ALLOW_REASON: the exact count is between 0 and `total`, so the conversions are fine.
+        #[allow(
+            clippy::cast_precision_loss,
+            clippy::cast_possible_truncation,
+            clippy::cast_sign_loss
+        )]
+        let (count, remainder) = {
+            let exact = weight / sum * total as f64;
+            let floor = exact.floor();
+            (floor as u64, exact - floor)
+        };
+        counts.push(count);
+        remainders.push((remainder, i));
+    }
+    let unassigned = total.saturating_sub(counts.iter().sum());
+    remainders.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+    for (_, i) in remainders
+        .into_iter()
+        .take(usize::try_from(unassigned).unwrap_or(usize::MAX))
+    {
+        counts[i] += 1;
+    }
+    counts
+}
+
+impl Function for Quota {
+    fn compile(&self, ctx: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (values, weights, total) = args_3::<Array, Array, u64>(span, args, None, None, None)?;
+        require(span, values.len() == weights.len(), || {
+            format!(
+                "number of values ({}) and weights ({}) must be equal",
+                values.len(),
+                weights.len()
+            )
+        })?;
+        require(span, total > 0, || "total number of rows must be positive".to_owned())?;
+        let weights = weights
+            .iter()
+            .map(f64::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .span_err(span)?;
+        require(
+            span,
+            weights.iter().all(|w| w.is_finite() && *w >= 0.0) && weights.iter().any(|w| *w > 0.0),
+            || "weights must be non-negative and not all zero".to_owned(),
+        )?;
+
+        let mut bounds = apportion(&weights, total);
+        for i in 1..bounds.len() {
+            bounds[i] += bounds[i - 1];
+        }
+
+        // the permutation only depends on the seed and the arguments, so it is the same in every
+        // thread and file.
+        let mut namespace = format!("rand.quota({total}");
+        for (value, weight) in values.iter().zip(&weights) {
+            write!(namespace, ", {value}: {weight}").unwrap();
+        }
+        let mut permutation = array::Permutation::prepare(total);
+        permutation.shuffle(total, &mut ctx.namespaced_rng(&namespace));
+
+        Ok(C::RandQuota {
+            values,
+            bounds: bounds.into_boxed_slice(),
+            permutation: Box::new(permutation),
+            total,
+        })
+    }
+}
+
+/// The `rand.permutation` SQL function.
+#[derive(Debug)]
+pub struct Permutation;
+
+impl Function for Permutation {
+    fn compile(&self, ctx: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let total = args_1::<u64>(span, args, None)?;
+        require(span, total > 0, || "total number of rows must be positive".to_owned())?;
+
+        // like `rand.quota`, the permutation only depends on the seed and the argument.
+        let mut permutation = array::Permutation::prepare(total);
+        permutation.shuffle(total, &mut ctx.namespaced_rng(&format!("rand.permutation({total})")));
+        Ok(C::RandPermutation {
+            permutation: Box::new(permutation),
+            total,
+        })
+    }
+}
+
+/// The `rand.shared_permutation` SQL function.
+#[derive(Debug)]
+pub struct SharedPermutation;
+
+impl SharedPermutation {
+    /// Obtains the permutation with the given name from the compile context, creating it on first
+    /// use.
+    fn compile_with_index(
+        ctx: &CompileContext,
+        span: Span,
+        name: &str,
+        total: Option<u64>,
+        index: Compiled,
+    ) -> Result<C, S<Error>> {
+        let (permutation, total) = ctx
+            .named_permutation(name, total, |total| {
+                // the permutation only depends on the seed and the name, so it is the same in
+                // every table, thread and file.
+                let mut permutation = array::Permutation::prepare(total);
+                permutation.shuffle(
+                    total,
+                    &mut ctx.namespaced_rng(&format!("rand.shared_permutation({name})")),
+                );
+                permutation
+            })
+            .span_err(span)?;
+        Ok(C::RandSharedPermutation {
+            permutation,
+            total,
+            index: Box::new(index),
+        })
+    }
+}
+
+impl Function for SharedPermutation {
+    fn compile(&self, ctx: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (name, index, total) = args_3::<String, S<Value>, Option<u64>>(span, args, None, None, Some(None))?;
+        let index = Compiled(S {
+            span: index.span,
+            inner: C::Constant(index.inner),
+        });
+        Self::compile_with_index(ctx, span, &name, total, index)
+    }
+
+    fn compile_partial(&self, ctx: &CompileContext, span: Span, args: &[Compiled]) -> Result<Option<C>, S<Error>> {
+        // the index usually varies per row, while the name and size are constant.
+        let (name, index, total) = match args {
+            [name, index] => (name, index, None),
+            [name, index, total] => (name, index, Some(total)),
+            _ => return Ok(None),
+        };
+        let Some(name) = name.as_constant() else {
+            return Ok(None);
+        };
+        let total = match total.map(Compiled::as_constant) {
+            None => None,
+            Some(Some(total)) => Option::<u64>::try_from(total.clone()).span_err(span)?,
+            Some(None) => return Ok(None),
+        };
+        let name = String::try_from(name.clone()).span_err(span)?;
+        Self::compile_with_index(ctx, span, &name, total, index.clone()).map(Some)
+    }
+}
+
+/// The `nearly_sorted` SQL function.
+#[derive(Debug)]
+pub struct NearlySorted;
+
+impl NearlySorted {
+    /// Shuffles the position given by `index` within blocks of `window` positions.
+    fn compile_with_index(ctx: &CompileContext, span: Span, window: u64, index: Compiled) -> Result<C, S<Error>> {
+        require(span, window > 0, || "disorder window must be positive".to_owned())?;
+        if window == 1 {
+            return Ok(index.0.inner);
+        }
+        // like `rand.permutation`, the shuffle only depends on the seed and the window.
+        let mut permutation = array::BlockPermutation::prepare(window);
+        permutation.shuffle(&mut ctx.namespaced_rng(&format!("nearly_sorted({window})")));
+        Ok(C::NearlySorted {
+            permutation: Box::new(permutation),
+            window,
+            index: Box::new(index),
+        })
+    }
+}
+
+impl Function for NearlySorted {
+    fn compile(&self, ctx: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (index, window) = args_2::<S<Value>, u64>(span, args, None, None)?;
+        let index = Compiled(S {
+            span: index.span,
+            inner: C::Constant(index.inner),
+        });
+        Self::compile_with_index(ctx, span, window, index)
+    }
+
+    fn compile_partial(&self, ctx: &CompileContext, span: Span, args: &[Compiled]) -> Result<Option<C>, S<Error>> {
+        // the position usually varies per row, while the window is constant.
+        let [index, window] = args else {
+            return Ok(None);
+        };
+        let Some(window) = window.as_constant() else {
+            return Ok(None);
+        };
+        let window = u64::try_from(window.clone()).span_err(span)?;
+        Self::compile_with_index(ctx, span, window, index.clone()).map(Some)
+    }
+}
+
+/// The `rand.from_histogram` SQL function.
+#[derive(Debug)]
+pub struct FromHistogram;
+
+/// Parses a histogram CSV file into the values and their frequencies.
+fn parse_histogram(content: &str) -> Result<(Vec<Value>, Vec<f64>), String> {
+    let mut values = Vec::new();
+    let mut frequencies = Vec::new();
+    for (row, line) in parse_csv(content)?.into_iter().zip(1..).skip(1) {
+        let [(value, quoted), (frequency, _)] =
+            <[_; 2]>::try_from(row).map_err(|row| format!("row {line} has {} columns, expecting 2", row.len()))?;
+        let frequency = frequency
+            .trim()
+            .parse::<f64>()
+            .map_err(|e| format!("invalid frequency in row {line}: {e}"))?;
+        values.push(if quoted { value.into() } else { csv_cell_to_value(value) });
+        frequencies.push(frequency);
+    }
+    Ok((values, frequencies))
+}
+
+impl Function for FromHistogram {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let path = PathBuf::from(args_1::<String>(span, args, None)?);
+        let content = read_to_string(&path)
+            .map_err(|source| Error::Io {
+                action: "read histogram file",
+                path: path.clone(),
+                source,
+            })
+            .span_err(span)?;
+        let (values, frequencies) = parse_histogram(&content).map_err(|e| {
+            Error::InvalidArguments(format!("invalid histogram file {}: {e}", path.display())).span(span)
+        })?;
+        let weights = WeightedAliasIndex::new(frequencies).map_err(|e| {
+            Error::InvalidArguments(format!("invalid frequencies in histogram file {}: {e}", path.display())).span(span)
+        })?;
+        Ok(C::RandCategorical {
+            values: Array::from_values(values),
+            weights: Box::new(weights),
+        })
+    }
+}
+
 //------------------------------------------------------------------------------
 
 /// The `rand.finite_f32` SQL function.
@@ -136,6 +1145,10 @@ pub struct U31Timestamp;
 #[derive(Debug)]
 pub struct Uuid;
 
+/// The `rand.ulid` SQL function.
+#[derive(Debug)]
+pub struct Ulid;
+
 impl Function for FiniteF32 {
     fn compile(&self, _: &CompileContext, _: Span, _: Arguments) -> Result<C, S<Error>> {
         Ok(C::RandFiniteF32(rand_distr::Uniform::new(0, 0xff00_0000)))
@@ -154,9 +1167,195 @@ impl Function for U31Timestamp {
     }
 }
 
+/// Converts the timestamp argument of `rand.uuid(7)` and `rand.ulid()` into Unix milliseconds.
+fn unix_millis(span: Span, timestamp: Value) -> Result<u64, S<Error>> {
+    let Value::Timestamp(timestamp, _) = timestamp else {
+        return Err(Error::InvalidArguments("timestamp argument must be a timestamp".to_owned()).span(span));
+    };
+    let millis = timestamp.and_utc().timestamp_millis();
+    require(span, (0..1 << 48).contains(&millis), || {
+        format!("timestamp {timestamp} cannot be represented in 48-bit Unix milliseconds")
+    })?;
+    // ALLOW_REASON: the range is checked above.
+    #[allow(clippy::cast_sign_loss)]
+    Ok(millis as u64)
+}
+
 impl Function for Uuid {
-    fn compile(&self, _: &CompileContext, _: Span, _: Arguments) -> Result<C, S<Error>> {
-        Ok(C::RandUuid)
+    fn compile(&self, ctx: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let now = Value::Timestamp(ctx.current_timestamp, ctx.time_zone.clone());
+        let (version, timestamp) = args_2::<u8, Value>(span, args, Some(4), Some(now))?;
+        match version {
+            4 => Ok(C::RandUuid),
+            7 => Ok(C::RandUuidV7(unix_millis(span, timestamp)?)),
+            _ => {
+                Err(Error::InvalidArguments(format!("unsupported UUID version {version}, expecting 4 or 7")).span(span))
+            }
+        }
+    }
+}
+
+impl Function for Ulid {
+    fn compile(&self, ctx: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let now = Value::Timestamp(ctx.current_timestamp, ctx.time_zone.clone());
+        let timestamp = args_1::<Value>(span, args, Some(now))?;
+        Ok(C::RandUlid(unix_millis(span, timestamp)?))
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// The SplitMix64 finalizer, so that similar inputs produce unrelated hashes.
+fn mix(h: u64) -> u64 {
+    let h = (h ^ (h >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    let h = (h ^ (h >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    h ^ (h >> 31)
+}
+
+/// Hashes the key into a 64-bit integer which only depends on the SQL representation of the key
+/// and the salt.
+fn hash_key(key: &Value, salt: &str) -> u64 {
+    let mut bytes = key.to_string().into_bytes();
+    if !salt.is_empty() {
+        bytes.push(0);
+        bytes.extend_from_slice(salt.as_bytes());
+    }
+    mix(fnv1a(bytes))
+}
+
+/// Maps a hash to a float uniformly distributed in `[0, 1)`.
+fn unit(hash: u64) -> f64 {
+    // ALLOW_REASON: the 53-bit integer is exactly representable.
+    #[allow(clippy::cast_precision_loss)]
+    let mantissa = (hash >> 11) as f64;
+    mantissa * 2.0_f64.powi(-53)
+}
+
+/// Maps the hash of a key to a float uniformly distributed in `[0, 1)`.
+fn unit_by_key(key: &Value, salt: &str) -> f64 {
+    unit(hash_key(key, salt))
+}
+
+/// The `rand.by_key` SQL function.
+#[derive(Debug)]
+pub struct ByKey;
+
+impl Function for ByKey {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (key, salt) = args_2::<Value, String>(span, args, None, Some(String::new()))?;
+        Ok(C::Constant(Value::from_finite_f64(unit_by_key(&key, &salt))))
+    }
+}
+
+/// The `rand.range_by_key` SQL function.
+#[derive(Debug)]
+pub struct RangeByKey;
+
+impl Function for RangeByKey {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (key, lower, upper, salt) =
+            args_4::<Value, i64, i64, String>(span, args, None, None, None, Some(String::new()))?;
+        require(span, lower < upper, || format!("assertion failed: {lower} < {upper}"))?;
+        // ALLOW_REASON: the width is positive and fits in 64 bits.
+        #[allow(clippy::cast_sign_loss)]
+        let width = (i128::from(upper) - i128::from(lower)) as u128;
+        // ALLOW_REASON: the offset is less than the width.
+        #[allow(clippy::cast_possible_wrap)]
+        let offset = ((u128::from(hash_key(&key, &salt)) * width) >> 64) as i128;
+        // ALLOW_REASON: the result is between lower and upper.
+        #[allow(clippy::cast_possible_truncation)]
+        let result = (i128::from(lower) + offset) as i64;
+        Ok(C::Constant(result.into()))
+    }
+}
+
+/// The `rand.uniform_by_key` SQL function.
+#[derive(Debug)]
+pub struct UniformByKey;
+
+impl Function for UniformByKey {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (key, lower, upper, salt) =
+            args_4::<Value, f64, f64, String>(span, args, None, None, None, Some(String::new()))?;
+        require(span, lower < upper, || format!("assertion failed: {lower} < {upper}"))?;
+        let value = lower + unit_by_key(&key, &salt) * (upper - lower);
+        Ok(C::Constant(Value::from_finite_f64(value)))
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// The `rand.parent_rownum` SQL function.
+#[derive(Debug)]
+pub struct ParentRownum;
+
+/// A random forest over the `rownum`s, where every row is either a root or a child of an earlier
+/// row.
+///
+/// The tree only depends on the salt and the parameters, so the parent of a row can be computed
+/// without generating the rows before it.
+#[derive(Debug, Clone)]
+pub struct Hierarchy {
+    /// The maximum number of levels in every tree.
+    max_depth: usize,
+    /// The probability that a row (other than the first) is a root.
+    root_probability: f64,
+    /// The salt mixed into the hash of every row.
+    salt: u64,
+}
+
+impl Hierarchy {
+    /// Picks the candidate parent of a row, or `None` if the row is a root.
+    ///
+    /// The candidate is chosen uniformly from all earlier rows, ignoring the depth limit.
+    fn candidate(&self, row_num: u64) -> Option<u64> {
+        if row_num <= 1 {
+            return None;
+        }
+        let h = mix(self.salt ^ row_num);
+        if unit(h) < self.root_probability {
+            return None;
+        }
+        // ALLOW_REASON: the offset is less than `row_num - 1`.
+        #[allow(clippy::cast_possible_truncation)]
+        let offset = ((u128::from(mix(h)) * u128::from(row_num - 1)) >> 64) as u64;
+        Some(offset + 1)
+    }
+
+    /// Computes the parent of a row, or `None` if the row is a root.
+    ///
+    /// A row becomes a child of its candidate parent. If the candidate is already at the deepest
+    /// level, the row becomes a child of the candidate's ancestor one level above instead.
+    pub fn parent(&self, row_num: u64) -> Option<u64> {
+        let mut chain = vec![row_num];
+        while let Some(candidate) = self.candidate(*chain.last().unwrap()) {
+            chain.push(candidate);
+        }
+        // resolve the path from the root to every row in the chain, starting from the root.
+        let mut path = Vec::with_capacity(self.max_depth.min(chain.len()));
+        for row in chain.into_iter().rev() {
+            path.truncate(self.max_depth - 1);
+            path.push(row);
+        }
+        path.len().checked_sub(2).map(|i| path[i])
+    }
+}
+
+impl Function for ParentRownum {
+    fn compile(&self, ctx: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (max_depth, root_probability) = args_2::<u64, f64>(span, args, None, Some(0.0))?;
+        require(span, max_depth >= 1, || "the maximum depth must be positive".to_owned())?;
+        require(span, (0.0..=1.0).contains(&root_probability), || {
+            format!("the root probability {root_probability} must be between 0 and 1")
+        })?;
+        let salt = ctx
+            .namespaced_rng(&format!("rand.parent_rownum({max_depth}, {root_probability})"))
+            .gen();
+        Ok(C::RandParentRownum(Hierarchy {
+            max_depth: usize::try_from(max_depth).unwrap_or(usize::MAX),
+            root_probability,
+            salt,
+        }))
     }
 }
 
@@ -167,14 +1366,39 @@ impl Function for Uuid {
 pub struct Regex;
 
 impl Function for Regex {
-    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
-        let (regex, flags, max_repeat) = args_3::<String, String, _>(span, args, None, Some(String::new()), Some(100))?;
-        let generator = compile_regex_generator(&regex, &flags, max_repeat).span_err(span)?;
+    fn compile(&self, ctx: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let key = args_3::<String, String, _>(span, args, None, Some(String::new()), Some(100))?;
+        let generator = ctx
+            .cached_regex(key, |(regex, flags, max_repeat)| {
+                compile_regex_generator(regex, flags, *max_repeat)
+            })
+            .span_err(span)?;
         Ok(C::RandRegex(generator))
     }
 }
 
+/// The `rand.bytes` SQL function.
+#[derive(Debug)]
+pub struct Bytes;
+
+impl Function for Bytes {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (min_len, max_len) = args_2::<usize, Option<usize>>(span, args, None, Some(None))?;
+        let max_len = max_len.unwrap_or(min_len);
+        require(span, min_len <= max_len, || {
+            format!("minimum length ({min_len}) must not be greater than maximum length ({max_len})")
+        })?;
+        Ok(C::RandBytes(rand_distr::Uniform::new_inclusive(min_len, max_len)))
+    }
+}
+
 fn compile_regex_generator(regex: &str, flags: &str, max_repeat: u32) -> Result<rand_regex::Regex, Error> {
+    let hir = parse_regex(regex, flags)?;
+    Ok(rand_regex::Regex::with_hir(hir, max_repeat)?)
+}
+
+/// Parses a regex pattern of `rand.regex` with the given flags.
+pub(crate) fn parse_regex(regex: &str, flags: &str) -> Result<regex_syntax::hir::Hir, Error> {
     let mut parser = regex_syntax::ParserBuilder::new();
     for flag in flags.chars() {
         match flag {
@@ -190,6 +1414,113 @@ fn compile_regex_generator(regex: &str, flags: &str, max_repeat: u32) -> Result<
         };
     }
 
-    let hir = parser.build().parse(regex)?;
-    Ok(rand_regex::Regex::with_hir(hir, max_repeat)?)
+    Ok(parser.build().parse(regex)?)
+}
+
+#[test]
+fn test_apportion() {
+    assert_eq!(apportion(&[0.1, 0.3, 0.6], 1000), [100, 300, 600]);
+    assert_eq!(apportion(&[1.0, 1.0, 1.0], 10), [4, 3, 3]);
+    assert_eq!(apportion(&[2.0, 0.0, 1.0], 4), [3, 0, 1]);
+    assert_eq!(apportion(&[5.0], 7), [7]);
+}
+
+#[test]
+fn test_cholesky() {
+    assert_eq!(cholesky(&[4.0, 2.0, 2.0, 10.0], 2), Some(vec![2.0, 0.0, 1.0, 3.0]));
+    assert_eq!(cholesky(&[1.0, 1.0, 1.0, 1.0], 2), Some(vec![1.0, 0.0, 1.0, 0.0]));
+    assert_eq!(cholesky(&[1.0, 2.0, 2.0, 1.0], 2), None);
+    assert_eq!(cholesky(&[0.0, 1.0, 1.0, 0.0], 2), None);
+}
+
+#[test]
+fn test_regex_cache() {
+    let ctx = CompileContext::new(0);
+    let key = || ("[a-z]{3}".to_owned(), String::new(), 100);
+    let compile =
+        |(regex, flags, max_repeat): &(String, String, u32)| compile_regex_generator(regex, flags, *max_repeat);
+    let first = ctx.cached_regex(key(), compile).unwrap();
+    let second = ctx.cached_regex(key(), |_| unreachable!()).unwrap();
+    assert!(std::sync::Arc::ptr_eq(&first, &second));
+    let other = ctx
+        .cached_regex(("[a-z]{3}".to_owned(), "i".to_owned(), 100), compile)
+        .unwrap();
+    assert!(!std::sync::Arc::ptr_eq(&first, &other));
+}
+
+#[test]
+fn test_hierarchy() {
+    for (max_depth, root_probability) in [(1, 0.0), (3, 0.0), (4, 0.1), (100, 0.5)] {
+        let hierarchy = Hierarchy {
+            max_depth,
+            root_probability,
+            salt: 12345,
+        };
+        assert_eq!(hierarchy.parent(1), None);
+        let mut depths = vec![0];
+        for row_num in 1..=1000 {
+            let depth = match hierarchy.parent(row_num) {
+                Some(parent) => {
+                    assert!(parent < row_num, "{parent} < {row_num}");
+                    depths[usize::try_from(parent).unwrap()] + 1
+                }
+                None => 1,
+            };
+            assert!(depth <= max_depth, "{row_num} at depth {depth} > {max_depth}");
+            depths.push(depth);
+        }
+        if max_depth <= 4 {
+            assert_eq!(depths.iter().max(), Some(&max_depth));
+        }
+    }
+}
+
+#[test]
+fn test_markov_chain() {
+    let chain = MarkovChain::parse("a b a c.\nd a b!");
+    assert_eq!(chain.words, ["a", "b", "c.", "d", "b!"]);
+    assert_eq!(chain.starts, [0, 3]);
+    assert_eq!(chain.successors, [vec![1, 2, 4], vec![0], vec![], vec![0], vec![]]);
+}
+
+#[test]
+fn test_count_distributions() {
+    use crate::eval::State;
+    use rand::SeedableRng as _;
+
+    let sample = |function: &dyn Function, params: [f64; 2]| {
+        let ctx = CompileContext::new(0);
+        let span = Span::default();
+        let args = params.iter().map(|p| Value::from_finite_f64(*p).span(span)).collect();
+        let compiled = Compiled(function.compile(&ctx, span, args).unwrap().span(span));
+        let mut state = State::new(1, Box::new(rand_hc::Hc128Rng::seed_from_u64(1)), ctx);
+        let counts = (0..10000)
+            .map(|_| u64::try_from(compiled.eval(&mut state).unwrap()).unwrap())
+            .collect::<Vec<_>>();
+        // ALLOW_REASON: the counts and their sum are small enough to be exact in f64.
+        #[allow(clippy::cast_precision_loss)]
+        let mean = counts.iter().sum::<u64>() as f64 / 10000.0;
+        let zeros = counts.iter().filter(|c| **c == 0).count();
+        (mean, zeros)
+    };
+
+    assert_eq!(sample(&ZeroInflatedPoisson, [1.0, 4.5]), (0.0, 10000));
+    // mean = (1 - 0.3) × 4.5, P(0) = 0.3 + 0.7 × exp(-4.5)
+    let (mean, zeros) = sample(&ZeroInflatedPoisson, [0.3, 4.5]);
+    assert!((mean - 3.15).abs() < 0.15, "mean = {mean}");
+    assert!((2900..3250).contains(&zeros), "zeros = {zeros}");
+
+    // mean = 2 × (1 - 0.4) / 0.4, P(0) = 0.4²
+    let (mean, zeros) = sample(&NegBinomial, [2.0, 0.4]);
+    assert!((mean - 3.0).abs() < 0.15, "mean = {mean}");
+    assert!((1450..1750).contains(&zeros), "zeros = {zeros}");
+
+    let ctx = CompileContext::new(0);
+    for params in [[0.0, 0.5], [2.0, 0.0], [2.0, 1.0]] {
+        let args = params
+            .iter()
+            .map(|p| Value::from_finite_f64(*p).span(Span::default()))
+            .collect();
+        assert!(NegBinomial.compile(&ctx, Span::default(), args).is_err());
+    }
 }
@@ -0,0 +1,32 @@
+//! Sequence functions.
+
+use super::{args_4, require, Arguments, Function};
+use crate::{
+    error::Error,
+    eval::{CompileContext, C},
+    span::{Span, S},
+    value::Value,
+};
+
+/// The `sequence` SQL function.
+#[derive(Debug)]
+pub struct Sequence;
+
+impl Function for Sequence {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (name, start, step, gap_probability) =
+            args_4::<String, Value, Value, f64>(span, args, None, None, Some(Value::Number(1.into())), Some(0.0))?;
+        require(span, (0.0..1.0).contains(&gap_probability), || {
+            format!("gap probability ({gap_probability}) must be in the range 0 ≤ p < 1")
+        })?;
+        // the number of skipped values follows the geometric distribution with success probability 1 - p.
+        let gap = (gap_probability > 0.0)
+            .then(|| rand_distr::Geometric::new(1.0 - gap_probability).expect("probability should be in (0, 1]"));
+        Ok(C::Sequence {
+            name: name.into_boxed_str(),
+            start,
+            step,
+            gap,
+        })
+    }
+}
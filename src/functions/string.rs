@@ -5,10 +5,10 @@ use crate::{
     bytes::ByteString,
     error::Error,
     eval::{CompileContext, C},
-    span::{Span, SpanExt, S},
+    span::{ResultExt, Span, SpanExt, S},
     value::Value,
 };
-use std::{convert::TryInto, ops::Range};
+use std::{convert::TryInto, iter, ops::Range};
 
 //------------------------------------------------------------------------------
 
@@ -197,3 +197,56 @@ impl Function for Concat {
         }
     }
 }
+
+//------------------------------------------------------------------------------
+
+/// The `limit` SQL function.
+#[derive(Debug)]
+pub struct Limit;
+
+impl Function for Limit {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (input, limit, mode) = args_3::<Value, usize, String>(span, args, None, None, Some("truncate".to_owned()))?;
+        let truncate = match &*mode {
+            "truncate" => true,
+            "error" => false,
+            _ => {
+                return Err(Error::InvalidArguments(format!(
+                    "unknown limit mode '{mode}', expecting 'truncate' or 'error'"
+                ))
+                .span(span))
+            }
+        };
+        let Value::Bytes(mut input) = input else {
+            return Ok(C::Constant(input));
+        };
+        let char_len = input.char_len();
+        if char_len > limit {
+            if !truncate {
+                return Err(Error::InvalidArguments(format!(
+                    "string of {char_len} characters exceeds the limit of {limit} characters"
+                ))
+                .span(span));
+            }
+            input.truncate(input.char_range(0..limit).end);
+        }
+        Ok(C::Constant(input.into()))
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// The `raw` SQL function.
+#[derive(Debug)]
+pub struct Raw;
+
+impl Function for Raw {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let text = args_1::<Value>(span, args, None)?;
+        let value = Value::sql_concat(iter::once(&text)).span_err(span)?;
+        Ok(C::Constant(match value {
+            Value::Bytes(raw) => Value::Raw(raw),
+            value => value,
+        }))
+    }
+}
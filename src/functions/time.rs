@@ -1,14 +1,18 @@
 //! Time functions.
 
-use super::{args_1, Arguments, Function};
+use super::{args_1, args_2, args_3, Arguments, Function};
 use crate::{
     error::Error,
     eval::{CompileContext, C},
     span::{ResultExt, Span, SpanExt, S},
-    value::{Value, TIMESTAMP_FORMAT},
+    value::{Value, DATE_FORMAT, TIMESTAMP_FORMAT, TIME_FORMAT},
 };
 
-use chrono::NaiveDateTime;
+use chrono::{
+    format::{Item, StrftimeItems},
+    Datelike, Days, Duration, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Timelike,
+};
+use tzfile::ArcTz;
 
 /// The `timestamp` SQL function
 #[derive(Debug)]
@@ -30,3 +34,369 @@ impl Function for Timestamp {
         Ok(C::Constant(Value::Timestamp(timestamp, tz)))
     }
 }
+
+/// The `DATE` SQL function
+#[derive(Debug)]
+pub struct Date;
+
+impl Function for Date {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let date = match args_1::<Value>(span, args, None)? {
+            Value::Null => return Ok(C::Constant(Value::Null)),
+            Value::Date(date) => date,
+            Value::Timestamp(timestamp, tz) => tz.from_utc_datetime(&timestamp).date_naive(),
+            input => {
+                let input = String::try_from(input).span_err(span)?;
+                NaiveDate::parse_from_str(input.trim(), DATE_FORMAT).span_err(span)?
+            }
+        };
+        Ok(C::Constant(Value::Date(date)))
+    }
+}
+
+/// The `TIME` SQL function
+#[derive(Debug)]
+pub struct Time;
+
+impl Function for Time {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let time = match args_1::<Value>(span, args, None)? {
+            Value::Null => return Ok(C::Constant(Value::Null)),
+            Value::Time(time) => time,
+            Value::Timestamp(timestamp, tz) => tz.from_utc_datetime(&timestamp).time(),
+            input => {
+                let input = String::try_from(input).span_err(span)?;
+                NaiveTime::parse_from_str(input.trim(), TIME_FORMAT).span_err(span)?
+            }
+        };
+        Ok(C::Constant(Value::Time(time)))
+    }
+}
+
+/// The `clock.tick` SQL function.
+#[derive(Debug)]
+pub struct ClockTick;
+
+impl Function for ClockTick {
+    fn compile(&self, _: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        match args_1::<Value>(span, args, None)? {
+            Value::Interval(interval) if interval >= 0 => Ok(C::ClockTick(interval)),
+            interval => Err(Error::InvalidArguments(format!(
+                "clock.tick() requires a non-negative interval, but got {interval}"
+            ))
+            .span(span)),
+        }
+    }
+}
+
+/// The `clock.now` SQL function.
+#[derive(Debug)]
+pub struct ClockNow;
+
+impl Function for ClockNow {
+    fn compile(&self, _: &CompileContext, _: Span, _: Arguments) -> Result<C, S<Error>> {
+        Ok(C::ClockTick(0))
+    }
+}
+
+/// Parses a `strftime`-style format string, rejecting unknown specifiers.
+fn parse_format(span: Span, format: &str) -> Result<Vec<Item<'_>>, S<Error>> {
+    let items = StrftimeItems::new(format).collect::<Vec<_>>();
+    if items.iter().any(|item| matches!(item, Item::Error)) {
+        return Err(Error::InvalidArguments(format!("invalid timestamp format '{format}'")).span(span));
+    }
+    Ok(items)
+}
+
+/// The `format_timestamp` SQL function.
+#[derive(Debug)]
+pub struct FormatTimestamp;
+
+impl Function for FormatTimestamp {
+    fn compile(&self, ctx: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (timestamp, format) = args_2::<Value, String>(span, args, None, None)?;
+        let items = parse_format(span, &format)?;
+        let Some((timestamp, tz)) = timestamp_arg(ctx, span, timestamp)? else {
+            return Ok(C::Constant(Value::Null));
+        };
+        let local = tz.from_utc_datetime(&timestamp).naive_local();
+        Ok(C::Constant(
+            local.format_with_items(items.into_iter()).to_string().into(),
+        ))
+    }
+}
+
+/// The `parse_timestamp` SQL function.
+#[derive(Debug)]
+pub struct ParseTimestamp;
+
+impl Function for ParseTimestamp {
+    fn compile(&self, ctx: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (input, format, tz) = args_3::<Value, String, Value>(span, args, None, None, Some(Value::Null))?;
+        parse_format(span, &format)?;
+        let input = match input {
+            Value::Null => return Ok(C::Constant(Value::Null)),
+            input => String::try_from(input).span_err(span)?,
+        };
+        let tz = match tz {
+            Value::Null => ctx.time_zone.clone(),
+            name => ctx
+                .parse_time_zone(&String::try_from(name).span_err(span)?)
+                .span_err(span)?,
+        };
+        // formats without a time part are interpreted as midnight.
+        let local_ts = match NaiveDateTime::parse_from_str(&input, &format) {
+            Ok(local_ts) => local_ts,
+            Err(e) => NaiveDate::parse_from_str(&input, &format)
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+                .ok_or_else(|| Error::from(e).span(span))?,
+        };
+        let timestamp = local_ts
+            .and_local_timezone(&*tz)
+            .single()
+            .ok_or_else(|| Error::InvalidOrAmbiguousLocalTime.span(span))?
+            .naive_utc();
+        Ok(C::Constant(Value::Timestamp(timestamp, tz)))
+    }
+}
+
+/// A calendar or clock field of a timestamp, used by `date_trunc`, `extract` and `date_add`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Field {
+    Year,
+    Quarter,
+    Month,
+    Week,
+    Day,
+    DayOfWeek,
+    IsoDayOfWeek,
+    DayOfYear,
+    Hour,
+    Minute,
+    Second,
+    Millisecond,
+    Microsecond,
+    Epoch,
+}
+
+impl Field {
+    /// Parses the name of a field case-insensitively.
+    fn parse(span: Span, name: &str) -> Result<Self, S<Error>> {
+        Ok(match &*name.to_ascii_lowercase() {
+            "year" => Self::Year,
+            "quarter" => Self::Quarter,
+            "month" => Self::Month,
+            "week" => Self::Week,
+            "day" => Self::Day,
+            "dow" => Self::DayOfWeek,
+            "isodow" => Self::IsoDayOfWeek,
+            "doy" => Self::DayOfYear,
+            "hour" => Self::Hour,
+            "minute" => Self::Minute,
+            "second" => Self::Second,
+            "millisecond" => Self::Millisecond,
+            "microsecond" => Self::Microsecond,
+            "epoch" => Self::Epoch,
+            _ => return Err(Error::InvalidArguments(format!("unknown time field '{name}'")).span(span)),
+        })
+    }
+
+    /// Returns the number of microseconds in this unit if it has a fixed length.
+    fn fixed_micros(self) -> Option<i64> {
+        match self {
+            Self::Hour => Some(3_600_000_000),
+            Self::Minute => Some(60_000_000),
+            Self::Second => Some(1_000_000),
+            Self::Millisecond => Some(1_000),
+            Self::Microsecond => Some(1),
+            _ => None,
+        }
+    }
+}
+
+/// Extracts a timestamp argument, returning `None` if it is NULL.
+///
+/// A date is taken as its midnight in the current time zone, like `CAST(… AS TIMESTAMP)`.
+fn timestamp_arg(ctx: &CompileContext, span: Span, value: Value) -> Result<Option<(NaiveDateTime, ArcTz)>, S<Error>> {
+    match value {
+        Value::Null => Ok(None),
+        Value::Timestamp(timestamp, tz) => Ok(Some((timestamp, tz))),
+        Value::Date(date) => {
+            let timestamp = from_local_time(span, date.and_time(NaiveTime::MIN), ctx.time_zone.clone())?;
+            timestamp_arg(ctx, span, timestamp)
+        }
+        _ => Err(Error::InvalidArguments(format!("{value} is not a timestamp")).span(span)),
+    }
+}
+
+/// Converts a local time back into a timestamp value. Ambiguous local times resolve to the earlier
+/// instant.
+pub(super) fn from_local_time(span: Span, local: NaiveDateTime, tz: ArcTz) -> Result<Value, S<Error>> {
+    let timestamp = local
+        .and_local_timezone(&*tz)
+        .earliest()
+        .ok_or_else(|| Error::InvalidOrAmbiguousLocalTime.span(span))?
+        .naive_utc();
+    Ok(Value::Timestamp(timestamp, tz))
+}
+
+/// Truncates a local time to the start of the unit. Returns `None` if the unit cannot be used for
+/// truncation.
+fn truncate(local: NaiveDateTime, unit: Field) -> Option<NaiveDateTime> {
+    let date = local.date();
+    let nanos = local.nanosecond();
+    match unit {
+        Field::Year => date.with_ordinal(1)?.and_hms_opt(0, 0, 0),
+        Field::Quarter => date
+            .with_day(1)?
+            .with_month0(date.month0() / 3 * 3)?
+            .and_hms_opt(0, 0, 0),
+        Field::Month => date.with_day(1)?.and_hms_opt(0, 0, 0),
+        Field::Week => date
+            .checked_sub_days(Days::new(date.weekday().num_days_from_monday().into()))?
+            .and_hms_opt(0, 0, 0),
+        Field::Day => date.and_hms_opt(0, 0, 0),
+        Field::Hour => date.and_hms_opt(local.hour(), 0, 0),
+        Field::Minute => date.and_hms_opt(local.hour(), local.minute(), 0),
+        Field::Second => local.with_nanosecond(0),
+        Field::Millisecond => local.with_nanosecond(nanos - nanos % 1_000_000),
+        Field::Microsecond => local.with_nanosecond(nanos - nanos % 1_000),
+        Field::DayOfWeek | Field::IsoDayOfWeek | Field::DayOfYear | Field::Epoch => None,
+    }
+}
+
+/// Combines whole seconds and the nanosecond part into a number, which is an integer if there is
+/// no fractional part.
+fn seconds_value(seconds: i64, nanos: u32) -> Value {
+    if nanos == 0 {
+        seconds.into()
+    } else {
+        // ALLOW_REASON: sub-second precision is only needed for timestamps near the present.
+        #[allow(clippy::cast_precision_loss)]
+        Value::from_finite_f64(seconds as f64 + f64::from(nanos) / 1e9)
+    }
+}
+
+/// The `date_trunc` SQL function.
+#[derive(Debug)]
+pub struct DateTrunc;
+
+impl Function for DateTrunc {
+    fn compile(&self, ctx: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (unit, timestamp) = args_2::<String, Value>(span, args, None, None)?;
+        let field = Field::parse(span, &unit)?;
+        let Some((timestamp, tz)) = timestamp_arg(ctx, span, timestamp)? else {
+            return Ok(C::Constant(Value::Null));
+        };
+        let local = tz.from_utc_datetime(&timestamp).naive_local();
+        let truncated = truncate(local, field)
+            .ok_or_else(|| Error::InvalidArguments(format!("cannot truncate a timestamp to '{unit}'")).span(span))?;
+        Ok(C::Constant(from_local_time(span, truncated, tz)?))
+    }
+}
+
+/// The `extract` SQL function.
+#[derive(Debug)]
+pub struct Extract;
+
+impl Function for Extract {
+    fn compile(&self, ctx: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (field, timestamp) = args_2::<String, Value>(span, args, None, None)?;
+        let field = Field::parse(span, &field)?;
+        let Some((timestamp, tz)) = timestamp_arg(ctx, span, timestamp)? else {
+            return Ok(C::Constant(Value::Null));
+        };
+        let local = tz.from_utc_datetime(&timestamp).naive_local();
+        let second = u64::from(local.second());
+        let nanos = local.nanosecond();
+        Ok(C::Constant(match field {
+            Field::Year => local.year().into(),
+            Field::Quarter => (local.month0() / 3 + 1).into(),
+            Field::Month => local.month().into(),
+            Field::Week => local.iso_week().week().into(),
+            Field::Day => local.day().into(),
+            Field::DayOfWeek => local.weekday().num_days_from_sunday().into(),
+            Field::IsoDayOfWeek => local.weekday().number_from_monday().into(),
+            Field::DayOfYear => local.ordinal().into(),
+            Field::Hour => local.hour().into(),
+            Field::Minute => local.minute().into(),
+            Field::Second => seconds_value(local.second().into(), nanos),
+            Field::Millisecond => (second * 1_000 + u64::from(nanos / 1_000_000)).into(),
+            Field::Microsecond => (second * 1_000_000 + u64::from(nanos / 1_000)).into(),
+            Field::Epoch => seconds_value(timestamp.and_utc().timestamp(), timestamp.nanosecond()),
+        }))
+    }
+}
+
+/// The `date_add` SQL function.
+#[derive(Debug)]
+pub struct DateAdd;
+
+impl Function for DateAdd {
+    fn compile(&self, ctx: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let (timestamp, amount, unit) = args_3::<Value, Option<i64>, String>(span, args, None, None, None)?;
+        let field = Field::parse(span, &unit)?;
+        let (Some((timestamp, tz)), Some(amount)) = (timestamp_arg(ctx, span, timestamp)?, amount) else {
+            return Ok(C::Constant(Value::Null));
+        };
+        let overflow = || Error::IntegerOverflow(format!("date_add({timestamp}, {amount}, '{unit}')")).span(span);
+
+        if let Some(micros_per_unit) = field.fixed_micros() {
+            let shifted = amount
+                .checked_mul(micros_per_unit)
+                .and_then(|micros| timestamp.checked_add_signed(Duration::microseconds(micros)))
+                .ok_or_else(overflow)?;
+            return Ok(C::Constant(Value::Timestamp(shifted, tz)));
+        }
+
+        // calendar units are added in local time, so the time of day is kept across DST changes.
+        let local = tz.from_utc_datetime(&timestamp).naive_local();
+        let add_months = |factor: i64| {
+            let months = Months::new(u32::try_from(amount.checked_mul(factor)?.unsigned_abs()).ok()?);
+            if amount < 0 {
+                local.checked_sub_months(months)
+            } else {
+                local.checked_add_months(months)
+            }
+        };
+        let add_days = |factor: i64| {
+            let days = Days::new(amount.checked_mul(factor)?.unsigned_abs());
+            if amount < 0 {
+                local.checked_sub_days(days)
+            } else {
+                local.checked_add_days(days)
+            }
+        };
+        let result = match field {
+            Field::Year => add_months(12),
+            Field::Quarter => add_months(3),
+            Field::Month => add_months(1),
+            Field::Week => add_days(7),
+            Field::Day => add_days(1),
+            _ => return Err(Error::InvalidArguments(format!("cannot add '{unit}' to a timestamp")).span(span)),
+        };
+        Ok(C::Constant(from_local_time(span, result.ok_or_else(overflow)?, tz)?))
+    }
+}
+
+/// The `last_day` SQL function.
+#[derive(Debug)]
+pub struct LastDay;
+
+impl Function for LastDay {
+    fn compile(&self, ctx: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        let Some((timestamp, tz)) = timestamp_arg(ctx, span, args_1::<Value>(span, args, None)?)? else {
+            return Ok(C::Constant(Value::Null));
+        };
+        let local = tz.from_utc_datetime(&timestamp).naive_local();
+        let last_day = local
+            .date()
+            .with_day(1)
+            .and_then(|first| first.checked_add_months(Months::new(1)))
+            .and_then(|next| next.pred_opt())
+            .and_then(|last| last.and_hms_opt(0, 0, 0))
+            .ok_or_else(|| Error::IntegerOverflow(format!("last_day({timestamp})")).span(span))?;
+        Ok(C::Constant(from_local_time(span, last_day, tz)?))
+    }
+}
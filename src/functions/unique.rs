@@ -0,0 +1,54 @@
+//! Uniqueness functions.
+
+use super::{Arguments, Function};
+use crate::{
+    error::Error,
+    eval::{CompileContext, Compiled, C},
+    span::{Span, SpanExt, S},
+};
+
+/// The `unique.tuple` SQL function.
+#[derive(Debug)]
+pub struct Tuple;
+
+impl Function for Tuple {
+    fn compile(&self, ctx: &CompileContext, span: Span, args: Arguments) -> Result<C, S<Error>> {
+        if args.is_empty() {
+            return Err(Error::NotEnoughArguments.span(span));
+        }
+        let args = args
+            .into_iter()
+            .map(|arg| Compiled(C::Constant(arg.inner).span(arg.span)))
+            .collect();
+        Ok(C::UniqueTuple {
+            id: ctx.allocate_unique_tuple(),
+            args,
+        })
+    }
+
+    fn compile_partial(&self, ctx: &CompileContext, _: Span, args: &[Compiled]) -> Result<Option<C>, S<Error>> {
+        Ok(Some(C::UniqueTuple {
+            id: ctx.allocate_unique_tuple(),
+            args: args.into(),
+        }))
+    }
+}
+
+#[test]
+fn test_calls_with_same_span() {
+    use crate::{eval::State, parser::Expr, value::Value};
+    use rand::rngs::mock::StepRng;
+
+    // expressions not written in the template (e.g. generated ones) all have the default span.
+    let ctx = CompileContext::new(0);
+    let call = || {
+        let args = vec![Expr::Value(Value::from(1)).span(Span::default())];
+        ctx.compile(Expr::Function { function: &Tuple, args }.span(Span::default()))
+            .unwrap()
+    };
+    let (first, second) = (call(), call());
+    let mut state = State::new(1, Box::new(StepRng::new(0, 1)), ctx.clone());
+    first.eval(&mut state).unwrap();
+    second.eval(&mut state).unwrap();
+    assert!(first.eval(&mut state).is_err());
+}
@@ -0,0 +1,482 @@
+//! Inferring the expressions of plain `CREATE TABLE` statements from the column types.
+
+use crate::ddl::ColumnType;
+use std::ops::Range;
+
+/// Keywords starting a table constraint instead of a column definition.
+const CONSTRAINT_KEYWORDS: &[&str] = &[
+    "constraint",
+    "primary",
+    "unique",
+    "key",
+    "index",
+    "foreign",
+    "check",
+    "fulltext",
+    "spatial",
+    "exclude",
+    "like",
+];
+
+/// Finds the end of a quoted string starting at `start`, where a doubled quote is an escaped
+/// quote. Returns the length of the content if the string is unterminated.
+pub(crate) fn skip_quoted(content: &str, start: usize, quote: u8) -> usize {
+    let bytes = content.as_bytes();
+    let mut i = start + 1;
+    while i < bytes.len() {
+        if bytes[i] == quote {
+            if bytes.get(i + 1) == Some(&quote) && quote != b']' {
+                i += 2;
+                continue;
+            }
+            return i + 1;
+        }
+        i += 1;
+    }
+    bytes.len()
+}
+
+/// Finds the end of a PostgreSQL escape string `E'…'` starting at `start` (the position of the
+/// quote), where a backslash escapes the next character.
+fn skip_escape_quoted(content: &str, start: usize) -> usize {
+    let bytes = content.as_bytes();
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'\'' if bytes.get(i + 1) == Some(&b'\'') => i += 2,
+            b'\'' => return i + 1,
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Finds the end of a PostgreSQL dollar-quoted string `$tag$…$tag$` starting at `start`. Returns
+/// `None` if the `$` does not start a dollar-quoted string.
+fn skip_dollar_quoted(content: &str, start: usize) -> Option<usize> {
+    let rest = &content[start + 1..];
+    let tag_len = rest.find(|c: char| !c.is_ascii_alphanumeric() && c != '_')?;
+    if !rest[tag_len..].starts_with('$') || rest.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    let delimiter = &content[start..start + tag_len + 2];
+    let body_start = start + delimiter.len();
+    Some(
+        content[body_start..]
+            .find(delimiter)
+            .map_or(content.len(), |end| body_start + end + delimiter.len()),
+    )
+}
+
+/// Finds the length of the column name at the start of the column definition.
+fn column_name_len(definition: &str) -> usize {
+    match definition.as_bytes().first() {
+        Some(b'"') => skip_quoted(definition, 0, b'"'),
+        Some(b'`') => skip_quoted(definition, 0, b'`'),
+        Some(b'[') => skip_quoted(definition, 0, b']'),
+        _ => definition
+            .find(|c: char| !c.is_alphanumeric() && c != '_' && c != '$')
+            .unwrap_or(definition.len()),
+    }
+}
+
+/// Splits the content `(col1 type1, col2 type2, …)` of a `CREATE TABLE` statement into column
+/// definitions, and returns the ranges of the column names and column types.
+///
+/// Table constraints like `PRIMARY KEY (a, b)` are skipped.
+pub fn split_columns(content: &str) -> Vec<(Range<usize>, Range<usize>)> {
+    split_definitions(content)
+        .into_iter()
+        .filter_map(|range| {
+            let definition = &content[range.clone()];
+            let trimmed = definition.trim_start();
+            let name_start = range.start + (definition.len() - trimmed.len());
+            let name_end = name_start + column_name_len(trimmed);
+            let name = &content[name_start..name_end];
+            if name.is_empty() || CONSTRAINT_KEYWORDS.contains(&&*name.to_ascii_lowercase()) {
+                None
+            } else {
+                Some((name_start..name_end, name_end..range.end))
+            }
+        })
+        .collect()
+}
+
+/// Splits the content `(def1, def2, …)` of a `CREATE TABLE` statement at the top-level commas,
+/// and returns the ranges of the definitions, including the table constraints.
+pub(crate) fn split_definitions(content: &str) -> Vec<Range<usize>> {
+    let mut definitions = Vec::new();
+    let Some(open) = content.find('(') else {
+        return definitions;
+    };
+
+    let bytes = content.as_bytes();
+    let mut depth = 0;
+    let mut start = open + 1;
+    let mut i = start;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\''
+                if i > 0
+                    && bytes[i - 1].eq_ignore_ascii_case(&b'e')
+                    && (i < 2 || !bytes[i - 2].is_ascii_alphanumeric()) =>
+            {
+                i = skip_escape_quoted(content, i);
+                continue;
+            }
+            q @ (b'\'' | b'"' | b'`') => {
+                i = skip_quoted(content, i, q);
+                continue;
+            }
+            b'$' => {
+                if let Some(end) = skip_dollar_quoted(content, i) {
+                    i = end;
+                    continue;
+                }
+            }
+            b'(' => depth += 1,
+            b')' if depth > 0 => depth -= 1,
+            b',' if depth == 0 => {
+                definitions.push(start..i);
+                start = i + 1;
+            }
+            b')' => {
+                definitions.push(start..i);
+                break;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    definitions
+}
+
+/// Returns whether the column type declares the column as the `PRIMARY KEY`.
+pub(crate) fn is_primary_key(ty: &str) -> bool {
+    let ty = ty.to_ascii_lowercase();
+    let words = ty
+        .split(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>();
+    words.windows(2).any(|w| w == ["primary", "key"])
+}
+
+/// Finds the value of the `DEFAULT` clause in the column type, e.g. `'x'` from
+/// `varchar(10) DEFAULT 'x' NOT NULL`. The value extends to the next column constraint.
+pub(crate) fn default_clause(ty: &str) -> Option<&str> {
+    let bytes = ty.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            q @ (b'\'' | b'"' | b'`') => i = skip_quoted(ty, i, q),
+            c if c.is_ascii_alphabetic() || c == b'_' => {
+                let len = column_name_len(&ty[i..]);
+                if ty[i..i + len].eq_ignore_ascii_case("default") {
+                    let value = ty[i + len..].trim_start();
+                    return Some(value[..default_value_len(value)].trim_end());
+                }
+                i += len;
+            }
+            _ => i += 1,
+        }
+    }
+    None
+}
+
+/// Computes the length of the `DEFAULT` value at the start of `value`, which ends before the first
+/// comment or column constraint keyword outside of parenthesis, e.g. `nextval('s')` in
+/// `nextval('s') NOT NULL`.
+fn default_value_len(value: &str) -> usize {
+    const CONSTRAINT_KEYWORDS: [&str; 12] = [
+        "not",
+        "null",
+        "check",
+        "primary",
+        "unique",
+        "references",
+        "constraint",
+        "collate",
+        "generated",
+        "on",
+        "auto_increment",
+        "comment",
+    ];
+
+    let bytes = value.as_bytes();
+    let mut depth = 0_usize;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            q @ (b'\'' | b'"' | b'`') => i = skip_quoted(value, i, q),
+            b'/' if bytes.get(i + 1) == Some(&b'*') => return i,
+            b'-' if bytes.get(i + 1) == Some(&b'-') => return i,
+            b'(' => {
+                depth += 1;
+                i += 1;
+            }
+            b')' => {
+                depth = depth.saturating_sub(1);
+                i += 1;
+            }
+            c if c.is_ascii_alphabetic() || c == b'_' => {
+                let len = column_name_len(&value[i..]);
+                let word = &value[i..i + len];
+                if i > 0 && depth == 0 && CONSTRAINT_KEYWORDS.iter().any(|k| word.eq_ignore_ascii_case(k)) {
+                    return i;
+                }
+                i += len;
+            }
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Extracts the numeric parameters of the type, e.g. `[10, 2]` from `decimal(10, 2)`.
+pub(crate) fn type_parameters(ty: &str) -> Vec<usize> {
+    ty.find('(')
+        .and_then(|start| {
+            let end = start + ty[start..].find(')')?;
+            Some(
+                ty[start + 1..end]
+                    .split(',')
+                    .filter_map(|p| p.trim().parse().ok())
+                    .collect(),
+            )
+        })
+        .unwrap_or_default()
+}
+
+/// Infers the width of a column in fixed-width output from its type, e.g. 10 from `char(10)` and
+/// 12 from `decimal(10, 2)` (including the sign and the decimal point).
+///
+/// Returns `None` if the type has no natural width, e.g. `text`.
+pub(crate) fn fixed_width(ty: &str) -> Option<usize> {
+    Some(match ColumnType::parse(ty) {
+        ColumnType::Decimal(Some((precision, scale))) => precision + 1 + usize::from(scale > 0),
+        ColumnType::Char(length) | ColumnType::Binary(length) => length.unwrap_or(1),
+        ColumnType::Varchar(length) | ColumnType::Varbinary(length) => length?,
+        ColumnType::Integer { bytes: 1, .. } => 4,
+        ColumnType::Integer { bytes: 2, .. } => 6,
+        ColumnType::Integer { bytes: 3, .. } => 8,
+        ColumnType::Integer { bytes: 4, .. } => 11,
+        ColumnType::Integer { .. } => 20,
+        ColumnType::Boolean => 1,
+        ColumnType::Date => 10,
+        ColumnType::Time => 8,
+        ColumnType::Timestamp { .. } => 26,
+        ColumnType::Uuid => 36,
+        ColumnType::Unknown => *type_parameters(ty).first()?,
+        _ => return None,
+    })
+}
+
+/// Finds the position of the array bounds in a PostgreSQL array type like `text[]` or
+/// `integer[3]`. Returns `None` if the type is not an array.
+pub(crate) fn find_array_bounds(ty: &str) -> Option<usize> {
+    ty.match_indices('[').map(|(i, _)| i).find(|&i| {
+        ty[..i].ends_with(|c: char| c.is_ascii_alphanumeric() || c == ')' || c == ']')
+            && ty[i + 1..]
+                .trim_start_matches(|c: char| c.is_ascii_digit())
+                .starts_with(']')
+    })
+}
+
+/// Infers the expression generating the values of a column from its type and constraints, e.g.
+/// `rand.range_inclusive(-32768, 32767)` from `smallint not null`.
+///
+/// Columns which are `UNIQUE`, `PRIMARY KEY` or auto-incremented are filled with `rownum`.
+/// Columns without `NOT NULL` are NULL 10% of the time. Array types like `text[]` are filled with
+/// single-element arrays.
+pub fn infer_expression(ty: &str) -> String {
+    let column_type = ColumnType::parse(ty);
+    let ty = ty.trim().to_ascii_lowercase();
+    let words = ty
+        .split(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>();
+    let has_words = |phrase: &[&str]| words.windows(phrase.len()).any(|w| w == phrase);
+
+    let is_primary_key = has_words(&["primary", "key"]);
+    let is_serial = matches!(
+        words.first().copied().unwrap_or_default(),
+        "serial" | "bigserial" | "smallserial" | "serial4" | "serial8" | "serial2"
+    );
+    let is_unique = is_primary_key
+        || is_serial
+        || words.contains(&"unique")
+        || words.contains(&"auto_increment")
+        || words.contains(&"autoincrement")
+        || words.contains(&"identity");
+    let is_not_null = is_primary_key || is_serial || has_words(&["not", "null"]);
+    let is_unsigned = words.contains(&"unsigned");
+
+    let (element_type, is_array) = match column_type {
+        ColumnType::Array(element) => (*element, true),
+        column_type => (column_type, false),
+    };
+    let expr = match element_type {
+        _ if is_serial => "rownum".to_owned(),
+        ColumnType::Integer { .. } if is_unique => "rownum".to_owned(),
+        ColumnType::Integer { bytes, unsigned } => {
+            let bits = 8 * u32::from(bytes);
+            let (min, max) = if unsigned {
+                (0, (1_i128 << bits) - 1)
+            } else {
+                let base = 1_i128 << (bits - 1);
+                (-base, base - 1)
+            };
+            format!("rand.range_inclusive({min}, {max})")
+        }
+        ColumnType::Decimal(precision_scale) => {
+            let (precision, scale) = precision_scale.unwrap_or((10, 0));
+            let precision = precision.min(38);
+            let scale = scale.min(precision);
+            let before = (precision - scale).min(18);
+            let integral = if is_unique {
+                "rownum".to_owned()
+            } else if before == 0 {
+                "0".to_owned()
+            } else {
+                let limit = "9".repeat(before);
+                let min = if is_unsigned {
+                    "0".to_owned()
+                } else {
+                    format!("-{limit}")
+                };
+                format!("rand.range_inclusive({min}, {limit})")
+            };
+            if scale == 0 {
+                integral
+            } else {
+                format!("{integral} || rand.regex('\\.[0-9]{{{scale}}}')")
+            }
+        }
+        ColumnType::Boolean => "rand.bool(0.5)".to_owned(),
+        ColumnType::Float { single: true } => "rand.finite_f32()".to_owned(),
+        ColumnType::Float { single: false } => "rand.finite_f64()".to_owned(),
+        _ if is_unique => "'' || rownum".to_owned(),
+        ColumnType::Char(length) => format!("rand.regex('.{{{}}}', 's')", length.unwrap_or(1)),
+        ColumnType::Varchar(length) => format!("rand.regex('.{{0,{}}}', 's')", length.unwrap_or(255)),
+        ColumnType::Binary(length) => format!("from_hex(rand.regex('[0-9a-f]{{{}}}'))", 2 * length.unwrap_or(1)),
+        ColumnType::Varbinary(length) => {
+            format!("from_hex(rand.regex('([0-9a-f]{{2}}){{0,{}}}'))", length.unwrap_or(255))
+        }
+        ColumnType::Date => "DATE rand.u31_timestamp()".to_owned(),
+        ColumnType::Time => "TIME rand.u31_timestamp()".to_owned(),
+        ColumnType::Timestamp { .. } => "rand.u31_timestamp()".to_owned(),
+        ColumnType::Uuid => "rand.uuid()".to_owned(),
+        _ => "rand.regex('[a-z]{1,10}')".to_owned(),
+    };
+    let expr = if is_array { format!("ARRAY[{expr}]") } else { expr };
+
+    if is_not_null {
+        expr
+    } else {
+        format!("CASE WHEN rand.bool(0.1) THEN NULL ELSE {expr} END")
+    }
+}
+
+#[test]
+fn test_split_columns() {
+    let content = "(\n    id INT PRIMARY KEY,\n    \"a, b\" DECIMAL(10, 2) DEFAULT ',',\n    PRIMARY KEY (id, \"a, b\")\n) ENGINE=InnoDB";
+    let columns = split_columns(content)
+        .into_iter()
+        .map(|(name, ty)| (&content[name], content[ty].trim()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        columns,
+        [("id", "INT PRIMARY KEY"), ("\"a, b\"", "DECIMAL(10, 2) DEFAULT ','")]
+    );
+}
+
+#[test]
+fn test_split_columns_postgres() {
+    let content = "(\n    a text DEFAULT E'\\', b',\n    b text DEFAULT $x$(,$x$,\n    \"c$\" int[]\n)";
+    let columns = split_columns(content)
+        .into_iter()
+        .map(|(name, ty)| (&content[name], content[ty].trim()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        columns,
+        [
+            ("a", "text DEFAULT E'\\', b'"),
+            ("b", "text DEFAULT $x$(,$x$"),
+            ("\"c$\"", "int[]"),
+        ]
+    );
+}
+
+#[test]
+fn test_infer_expression() {
+    let test_cases = [
+        ("INT PRIMARY KEY", "rownum"),
+        ("bigserial", "rownum"),
+        ("smallint not null", "rand.range_inclusive(-32768, 32767)"),
+        ("tinyint unsigned not null", "rand.range_inclusive(0, 255)"),
+        ("varchar(20) unique", "'' || rownum"),
+        ("character varying(5) not null", "rand.regex('.{0,5}', 's')"),
+        (
+            "decimal(5, 2) not null",
+            "rand.range_inclusive(-999, 999) || rand.regex('\\.[0-9]{2}')",
+        ),
+        ("boolean", "CASE WHEN rand.bool(0.1) THEN NULL ELSE rand.bool(0.5) END"),
+        ("integer NOT NULL GENERATED ALWAYS AS IDENTITY", "rownum"),
+        ("text[] not null", "ARRAY[rand.regex('.{0,255}', 's')]"),
+        ("character varying(5)[3] not null", "ARRAY[rand.regex('.{0,5}', 's')]"),
+        ("int1 not null", "rand.range_inclusive(-128, 127)"),
+        ("serial8", "rownum"),
+        ("float(53) not null", "rand.finite_f64()"),
+        ("datetime2 not null", "rand.u31_timestamp()"),
+    ];
+    for (ty, expected) in test_cases {
+        assert_eq!(infer_expression(ty), expected, "type = {ty}");
+    }
+}
+
+#[test]
+fn test_fixed_width() {
+    let test_cases = [
+        ("CHAR(10) NOT NULL", Some(10)),
+        ("varchar(255)", Some(255)),
+        ("DECIMAL(10, 2)", Some(12)),
+        ("numeric(5)", Some(6)),
+        ("INT UNSIGNED", Some(11)),
+        ("timestamp", Some(26)),
+        ("int1", Some(4)),
+        ("serial8", Some(20)),
+        ("timestamp(6) with time zone", Some(26)),
+        ("text", None),
+    ];
+    for (ty, expected) in test_cases {
+        assert_eq!(fixed_width(ty), expected, "type = {ty}");
+    }
+}
+
+#[test]
+fn test_default_clause() {
+    let test_cases = [
+        ("varchar(10) DEFAULT 'x' NOT NULL", Some("'x'")),
+        ("int not null default -1", Some("-1")),
+        ("int default null", Some("null")),
+        (
+            "bigint default nextval('s'::regclass) not null",
+            Some("nextval('s'::regclass)"),
+        ),
+        (
+            "timestamp default current_timestamp on update current_timestamp",
+            Some("current_timestamp"),
+        ),
+        ("text default (concat('not', 'null'))", Some("(concat('not', 'null'))")),
+        ("int default 1 /*#dbgen default=1 */ not null", Some("1")),
+        ("text default", Some("")),
+        ("varchar(10) check (a <> 'default')", None),
+        ("\"default\" int", None),
+        ("int", None),
+    ];
+    for (ty, expected) in test_cases {
+        assert_eq!(default_clause(ty), expected, "type = {ty}");
+    }
+}
@@ -78,18 +78,30 @@ pub const FULL_VERSION: &str = concat!(
 );
 
 pub mod array;
+pub mod audit;
 pub mod bytes;
 #[cfg(feature = "cli")]
 pub mod cli;
+pub mod ddl;
 pub mod error;
 pub mod eval;
+pub mod explain;
 pub mod format;
 pub mod functions;
+pub mod infer;
 pub mod lexctr;
+pub mod lint;
 pub mod number;
 pub mod parser;
+pub mod partition;
+#[cfg(feature = "cli")]
+pub mod plugin;
+pub mod progress;
+pub mod runtime;
 #[cfg(feature = "cli")]
 pub mod schemagen_cli;
+pub mod sort;
 pub mod span;
 pub mod value;
+pub mod warning;
 pub mod writer;
@@ -0,0 +1,360 @@
+//! Checking a template for suspicious constructs.
+
+use crate::{
+    eval::{CompileContext, C},
+    functions::{codec, ops, time, Function},
+    number::Number,
+    parser::{function_name, Expr, Template},
+    span::{Span, SpanExt, S},
+    value::Value,
+    warning::ColumnCheck,
+};
+use std::collections::HashMap;
+use thiserror::Error as ThisError;
+
+/// A suspicious construct found in the template.
+#[derive(ThisError, Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Lint {
+    /// A variable is assigned but never read.
+    #[error("the variable is assigned but never read")]
+    UnusedVariable,
+
+    /// A column expression always evaluates to the same value.
+    #[error(
+        "column {column} always evaluates to {value}, consider writing it as a literal or hoisting it into a global expression"
+    )]
+    ConstantColumn {
+        /// The column name.
+        column: String,
+        /// The constant value.
+        value: String,
+    },
+
+    /// A regex pattern contains unbounded repetitions like `*` or `+`.
+    #[error("the regex pattern contains unbounded repetitions, which are silently capped at {max_repeat} repetitions")]
+    UnboundedRegex {
+        /// The maximum number of repetitions.
+        max_repeat: u32,
+    },
+
+    /// A column expression can produce a value not fitting the column type.
+    #[error("column {column} of type {ty} cannot hold the value {value} produced by this expression")]
+    Overflow {
+        /// The column name.
+        column: String,
+        /// The column type.
+        ty: String,
+        /// The value not fitting the type.
+        value: String,
+    },
+}
+
+impl Lint {
+    /// Returns a stable identifier of the kind of lint.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::UnusedVariable => "unused_variable",
+            Self::ConstantColumn { .. } => "constant_column",
+            Self::UnboundedRegex { .. } => "unbounded_regex",
+            Self::Overflow { .. } => "overflow",
+        }
+    }
+}
+
+/// Collects the lints from a template.
+struct Linter<'a> {
+    ctx: &'a CompileContext,
+    /// The first assignment of every variable.
+    assignments: HashMap<usize, Span>,
+    /// Whether every variable has been read.
+    reads: Vec<bool>,
+    lints: Vec<S<Lint>>,
+}
+
+impl Linter<'_> {
+    /// Visits an expression and its sub-expressions.
+    fn visit(&mut self, expr: &S<Expr>) {
+        match &expr.inner {
+            Expr::RowNum | Expr::SubRowNum | Expr::CurrentTimestamp | Expr::ScaleFactor | Expr::Value(_) => {}
+            Expr::GetVariable(index) => {
+                if let Some(read) = self.reads.get_mut(*index) {
+                    *read = true;
+                }
+            }
+            Expr::SetVariable(index, value) => {
+                self.assignments.entry(*index).or_insert(expr.span);
+                self.visit(value);
+            }
+            Expr::Function { function, args } => {
+                if function_name(*function) == Some("rand.regex") {
+                    self.check_regex(expr, args);
+                }
+                for arg in args {
+                    self.visit(arg);
+                }
+            }
+            Expr::CaseValueWhen {
+                value,
+                conditions,
+                otherwise,
+            } => {
+                for e in value.iter().chain(otherwise) {
+                    self.visit(e);
+                }
+                for (condition, result) in conditions {
+                    self.visit(condition);
+                    self.visit(result);
+                }
+            }
+            Expr::Pinned { namespace, inner } => {
+                self.visit(namespace);
+                self.visit(inner);
+            }
+        }
+    }
+
+    /// Checks if the arguments of `rand.regex` contain unbounded repetitions.
+    fn check_regex(&mut self, expr: &S<Expr>, args: &[S<Expr>]) {
+        let constant = |i: usize| match args.get(i).map(|a| &a.inner) {
+            Some(Expr::Value(value)) => Some(Some(value.clone())),
+            None => Some(None),
+            Some(_) => None,
+        };
+        let (Some(Some(pattern)), Some(flags), Some(max_repeat)) = (constant(0), constant(1), constant(2)) else {
+            return;
+        };
+        let (Ok(pattern), Ok(flags)) = (
+            String::try_from(pattern),
+            flags.map_or_else(|| Ok(String::new()), String::try_from),
+        ) else {
+            return;
+        };
+        let max_repeat = max_repeat.map_or(Ok(100), u32::try_from).unwrap_or(100);
+        let Ok(hir) = crate::functions::rand::parse_regex(&pattern, &flags) else {
+            return;
+        };
+        if hir.properties().maximum_len().is_none() {
+            self.lints.push(Lint::UnboundedRegex { max_repeat }.span(expr.span));
+        }
+    }
+
+    /// Checks if the column expression is a non-trivial constant, or may overflow the column type.
+    fn check_column(&mut self, column: &str, ty: &str, expr: &S<Expr>) {
+        let Ok(compiled) = self.ctx.compile(expr.clone()) else {
+            return;
+        };
+        let values = match (&compiled.0.inner, &expr.inner) {
+            (C::Constant(value), Expr::Function { .. } | Expr::CaseValueWhen { .. }) if !is_literal(&expr.inner) => {
+                self.lints.push(
+                    Lint::ConstantColumn {
+                        column: column.to_owned(),
+                        value: value.to_string(),
+                    }
+                    .span(expr.span),
+                );
+                vec![value.clone()]
+            }
+            (C::Constant(value), _) => vec![value.clone()],
+            (_, Expr::Function { function, args }) => range_bounds(function_name(*function), args),
+            _ => Vec::new(),
+        };
+        let Some(check) = ColumnCheck::from_type(ty) else {
+            return;
+        };
+        if let Some(value) = values.into_iter().find(|value| check.check(value).is_some()) {
+            self.lints.push(
+                Lint::Overflow {
+                    column: column.to_owned(),
+                    ty: ty.to_owned(),
+                    value: value.to_string(),
+                }
+                .span(expr.span),
+            );
+        }
+    }
+}
+
+/// The functions which the parser uses to represent literals like `-1`, `TIMESTAMP '2020-01-01'` or
+/// `X'00'`.
+static LITERAL_FUNCTIONS: &[&dyn Function] = &[
+    &ops::Neg,
+    &time::Timestamp,
+    &time::Date,
+    &time::Time,
+    &codec::DECODE_HEX,
+];
+
+/// Checks if the expression is a literal, possibly written with a syntax represented as a function
+/// call (e.g. `-1` or `INTERVAL 1 DAY`).
+fn is_literal(expr: &Expr) -> bool {
+    match expr {
+        Expr::Value(_) => true,
+        Expr::Function { function, args } => {
+            let debug = format!("{function:?}");
+            let is_literal_function = LITERAL_FUNCTIONS.iter().any(|f| format!("{f:?}") == debug)
+                || matches!(
+                    (debug == format!("{:?}", ops::Arith::Mul), args.get(1).map(|a| &a.inner)),
+                    (true, Some(Expr::Value(Value::Interval(_))))
+                );
+            is_literal_function && args.iter().all(|arg| is_literal(&arg.inner))
+        }
+        _ => false,
+    }
+}
+
+/// Obtains the smallest and largest values generated by `rand.range` or `rand.range_inclusive`
+/// with constant arguments.
+fn range_bounds(name: Option<&str>, args: &[S<Expr>]) -> Vec<Value> {
+    let [S {
+        inner: Expr::Value(lower),
+        ..
+    }, S {
+        inner: Expr::Value(upper),
+        ..
+    }] = args
+    else {
+        return Vec::new();
+    };
+    let upper = match name {
+        Some("rand.range_inclusive") => upper.clone(),
+        Some("rand.range") => match upper.sql_sub(&Value::Number(Number::from(1))) {
+            Ok(upper) => upper,
+            Err(_) => return Vec::new(),
+        },
+        _ => return Vec::new(),
+    };
+    vec![lower.clone(), upper]
+}
+
+/// Checks the template for suspicious constructs:
+///
+/// * variables which are assigned but never read,
+/// * column expressions which always evaluate to the same value,
+/// * `rand.regex` patterns with unbounded repetitions,
+/// * column expressions whose constant values or `rand.range` bounds do not fit the column type.
+pub fn lint(template: &Template, ctx: &CompileContext) -> Vec<S<Lint>> {
+    let mut linter = Linter {
+        ctx,
+        assignments: HashMap::new(),
+        reads: vec![false; template.variables_count],
+        lints: Vec::new(),
+    };
+    for expr in template
+        .global_exprs
+        .iter()
+        .chain(&template.row_filter)
+        .chain(&template.order_by)
+    {
+        linter.visit(expr);
+    }
+    for table in &template.tables {
+        for expr in table
+            .exprs
+            .iter()
+            .chain(table.derived.iter().chain(&table.combinations).map(|(_, e)| e))
+            .chain(table.shards.iter().map(|(_, e)| e))
+        {
+            linter.visit(expr);
+        }
+        for ((name_range, type_range), expr) in table
+            .column_name_ranges
+            .iter()
+            .zip(&table.column_type_ranges)
+            .zip(&table.exprs)
+        {
+            let column = &table.content[name_range.clone()];
+            let ty = table.content[type_range.clone()].trim();
+            linter.check_column(column, ty, expr);
+        }
+    }
+
+    let mut unused = linter
+        .assignments
+        .iter()
+        .filter(|(index, _)| !linter.reads[**index])
+        .map(|(_, span)| Lint::UnusedVariable.span(*span))
+        .collect::<Vec<_>>();
+    unused.sort_by_key(|lint| lint.span);
+    linter.lints.extend(unused);
+    linter.lints
+}
+
+#[test]
+fn test_lint() {
+    use crate::{parser::ParseOptions, span::Registry};
+
+    let template = Template::parse(
+        "{{ @unused := 1 }}
+        CREATE TABLE t (
+            a TINYINT {{ rand.range(0, 1000) }},
+            b TEXT {{ rand.regex('[a-z]+') }},
+            c TEXT {{ 'x' || 'y' }},
+            d INT {{ @used := rownum }},
+            e INT {{ @used }}
+        );",
+        &[],
+        &[],
+        &ParseOptions::default(),
+        &mut Registry::default(),
+    )
+    .unwrap();
+    let ctx = CompileContext::new(template.variables_count);
+    let lints = lint(&template, &ctx).into_iter().map(|l| l.inner).collect::<Vec<_>>();
+    assert_eq!(
+        lints,
+        [
+            Lint::UnboundedRegex { max_repeat: 100 },
+            Lint::Overflow {
+                column: "a".to_owned(),
+                ty: "TINYINT".to_owned(),
+                value: "999".to_owned(),
+            },
+            Lint::ConstantColumn {
+                column: "c".to_owned(),
+                value: "'xy'".to_owned(),
+            },
+            Lint::UnusedVariable,
+        ]
+    );
+}
+
+#[test]
+fn test_literal_columns() {
+    use crate::{parser::ParseOptions, span::Registry};
+
+    let test_cases = [
+        ("INT", "-1", None),
+        ("TIMESTAMP", "TIMESTAMP '2020-01-01 00:00:00'", None),
+        (
+            "TIMESTAMP",
+            "TIMESTAMP WITH TIME ZONE '2020-01-01 00:00:00+08:00'",
+            None,
+        ),
+        ("DATE", "DATE '2020-01-01'", None),
+        ("TIME", "TIME '12:34:56'", None),
+        ("BLOB", "X'00'", None),
+        ("INTERVAL", "INTERVAL 1 DAY", None),
+        ("INT", "-(1 + 2)", Some("-3")),
+    ];
+    for (ty, expr, constant) in test_cases {
+        let template = Template::parse(
+            &format!("CREATE TABLE t (a {ty} {{{{ {expr} }}}});"),
+            &[],
+            &[],
+            &ParseOptions::default(),
+            &mut Registry::default(),
+        )
+        .unwrap();
+        let ctx = CompileContext::new(template.variables_count);
+        let lints = lint(&template, &ctx).into_iter().map(|l| l.inner).collect::<Vec<_>>();
+        let expected = constant
+            .into_iter()
+            .map(|value| Lint::ConstantColumn {
+                column: "a".to_owned(),
+                value: value.to_owned(),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(lints, expected, "{expr}");
+    }
+}
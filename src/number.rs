@@ -1,10 +1,11 @@
 //! Number.
 
 use numcmp::NumCmp;
+use serde::{Deserialize, Serialize};
 use std::{cmp::Ordering, convert::TryFrom, fmt, io};
 
 /// Implementation of a number.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 enum N {
     /// A boolean.
     B(bool),
@@ -12,6 +13,98 @@ enum N {
     I(i128),
     /// A finite floating-point number.
     F(f64),
+    /// A fixed-point decimal number, as the mantissa and the number of digits after the decimal
+    /// point (at most [`MAX_DECIMAL_PRECISION`]).
+    D(i128, u8),
+}
+
+/// The maximum precision (total number of digits) of a decimal number.
+pub const MAX_DECIMAL_PRECISION: u8 = 38;
+
+/// Changes the scale of a decimal mantissa, rounding half away from zero when digits are removed.
+///
+/// Returns `None` on overflow.
+fn rescale(mantissa: i128, from: u32, to: u32) -> Option<i128> {
+    match from.cmp(&to) {
+        Ordering::Less => mantissa.checked_mul(10_i128.checked_pow(to - from)?),
+        Ordering::Equal => Some(mantissa),
+        Ordering::Greater => {
+            let Some(divisor) = 10_i128.checked_pow(from - to) else {
+                // the divisor exceeds every mantissa, so the number rounds to zero.
+                return Some(0);
+            };
+            Some(round_div(mantissa, divisor))
+        }
+    }
+}
+
+/// Divides two integers, rounding half away from zero.
+fn round_div(numerator: i128, denominator: i128) -> i128 {
+    let quotient = numerator / denominator;
+    let remainder = numerator % denominator;
+    if remainder.unsigned_abs() >= denominator.unsigned_abs() - remainder.unsigned_abs() {
+        if (numerator < 0) == (denominator < 0) {
+            quotient + 1
+        } else {
+            quotient - 1
+        }
+    } else {
+        quotient
+    }
+}
+
+/// Parses a decimal string like `-12.345e-6` into the mantissa and scale.
+fn parse_decimal_parts(s: &str) -> Option<(i128, u32)> {
+    let s = s.trim();
+    let (s, exponent) = match s.find(['e', 'E']) {
+        Some(pos) => (&s[..pos], s[pos + 1..].parse::<i32>().ok()?),
+        None => (s, 0),
+    };
+    let (negative, s) = match s.as_bytes().first()? {
+        b'-' => (true, &s[1..]),
+        b'+' => (false, &s[1..]),
+        _ => (false, s),
+    };
+    let (integral, fractional) = s.split_once('.').unwrap_or((s, ""));
+    if integral.is_empty() && fractional.is_empty() {
+        return None;
+    }
+    let mut mantissa = 0_i128;
+    for c in integral.bytes().chain(fractional.bytes()) {
+        if !c.is_ascii_digit() {
+            return None;
+        }
+        mantissa = mantissa.checked_mul(10)?.checked_add((c - b'0').into())?;
+    }
+    if negative {
+        mantissa = -mantissa;
+    }
+    let scale = i32::try_from(fractional.len()).ok()?.checked_sub(exponent)?;
+    match u32::try_from(scale) {
+        Ok(scale) => Some((mantissa, scale)),
+        Err(_) => Some((mantissa.checked_mul(10_i128.checked_pow(scale.unsigned_abs())?)?, 0)),
+    }
+}
+
+/// Displays a decimal mantissa with the given scale.
+struct DisplayDecimal(i128, u8);
+
+impl fmt::Display for DisplayDecimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self(mantissa, scale) = *self;
+        if scale == 0 {
+            return write!(f, "{mantissa}");
+        }
+        let sign = if mantissa < 0 { "-" } else { "" };
+        let digits = mantissa.unsigned_abs().to_string();
+        let scale = usize::from(scale);
+        if digits.len() > scale {
+            let (integral, fractional) = digits.split_at(digits.len() - scale);
+            write!(f, "{sign}{integral}.{fractional}")
+        } else {
+            write!(f, "{sign}0.{digits:0>scale$}")
+        }
+    }
 }
 
 /// The error returned in numerical arithmetics.
@@ -69,6 +162,9 @@ impl From<Number> for f64 {
             #[allow(clippy::cast_precision_loss)]
             N::I(v) => v as Self,
             N::F(v) => v,
+            // ALLOW_REASON: this is expected
+            #[allow(clippy::cast_precision_loss)]
+            N::D(m, s) => m as Self / 10.0_f64.powi(s.into()),
         }
     }
 }
@@ -81,6 +177,7 @@ macro_rules! impl_try_from_number_for_integer {
                 match n.0 {
                     N::B(v) => Ok(v.into()),
                     N::I(v) => Self::try_from(v).map_err(|_| NumberError::Overflow),
+                    N::D(m, s) => Self::try_from(m / 10_i128.pow(s.into())).map_err(|_| NumberError::Overflow),
                     N::F(v) if Self::MIN as f64 <= v && v <= Self::MAX as f64 => Ok(v as $ty),
                     _ => Err(NumberError::Overflow),
                 }
@@ -93,7 +190,7 @@ macro_rules! impl_try_from_number_for_integer {
 impl_try_from_number_for_integer!(u8, u16, u32, u64, usize, i8, i16, i32, i64, i128, isize);
 
 /// An SQL number (could represent an integer or floating point number).
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct Number(N);
 
 impl fmt::Display for Number {
@@ -114,8 +211,71 @@ impl Number {
     fn try_as_i128(self) -> Result<i128, f64> {
         match self.0 {
             N::B(v) => Ok(v.into()),
-            N::I(v) => Ok(v),
-            N::F(v) => Err(v),
+            N::I(v) | N::D(v, 0) => Ok(v),
+            N::F(_) | N::D(..) => Err(self.into()),
+        }
+    }
+
+    /// Obtains the mantissa and scale of this number, if it is not a floating point number.
+    fn try_as_decimal(self) -> Option<(i128, u8)> {
+        match self.0 {
+            N::B(v) => Some((v.into(), 0)),
+            N::I(v) => Some((v, 0)),
+            N::D(m, s) => Some((m, s)),
+            N::F(_) => None,
+        }
+    }
+
+    /// Obtains the mantissas and scales of two numbers, if at least one of them is a decimal and
+    /// neither is a floating point number.
+    fn decimal_operands(self, other: Self) -> Option<((i128, u8), (i128, u8))> {
+        if matches!((self.0, other.0), (N::D(..), _) | (_, N::D(..))) {
+            Some((self.try_as_decimal()?, other.try_as_decimal()?))
+        } else {
+            None
+        }
+    }
+
+    /// Rescales two numbers to a common scale, if at least one of them is a decimal and neither
+    /// is a floating point number.
+    ///
+    /// Returns the two mantissas and the common scale.
+    fn aligned_decimals(self, other: Self) -> Option<(i128, i128, u8)> {
+        let ((a, sa), (b, sb)) = self.decimal_operands(other)?;
+        let scale = sa.max(sb);
+        Some((
+            rescale(a, sa.into(), scale.into())?,
+            rescale(b, sb.into(), scale.into())?,
+            scale,
+        ))
+    }
+
+    /// Parses a decimal number from a string like `-123.45`, keeping every digit exactly.
+    ///
+    /// Returns `None` if the string is not a number or has too many digits.
+    pub fn parse_decimal(s: &str) -> Option<Self> {
+        let (mantissa, scale) = parse_decimal_parts(s)?;
+        let target_scale = MAX_DECIMAL_PRECISION.min(u8::try_from(scale).unwrap_or(u8::MAX));
+        let mantissa = rescale(mantissa, scale, target_scale.into())?;
+        Some(Self(N::D(mantissa, target_scale)))
+    }
+
+    /// Converts this number to a decimal number with the given precision (total number of digits)
+    /// and scale (number of digits after the decimal point), rounding half away from zero.
+    ///
+    /// Floating point numbers are converted from their shortest representation, e.g. `0.1` becomes
+    /// exactly `0.1` instead of `0.1000000000000000055511151231257827`.
+    pub fn to_decimal(self, precision: u8, scale: u8) -> Result<Self, NumberError> {
+        let mantissa = if let N::F(v) = self.0 {
+            let mut output = ryu::Buffer::new();
+            parse_decimal_parts(output.format_finite(v)).and_then(|(m, s)| rescale(m, s, scale.into()))
+        } else {
+            self.try_as_decimal()
+                .and_then(|(m, s)| rescale(m, s.into(), scale.into()))
+        };
+        match mantissa {
+            Some(m) if m.unsigned_abs() < 10_u128.pow(precision.into()) => Ok(Self(N::D(m, scale))),
+            _ => Err(NumberError::Overflow),
         }
     }
 
@@ -129,6 +289,26 @@ impl Number {
                 let mut output = ryu::Buffer::new();
                 sink.write_str(output.format_finite(v))
             }
+            N::D(m, s) => write!(sink, "{}", DisplayDecimal(m, s)),
+        }
+    }
+
+    /// Converts this number into a JSON value. Integers outside the 64-bit range and decimals with
+    /// fractional digits become floating point numbers.
+    pub fn to_json(self) -> serde_json::Value {
+        match self.0 {
+            N::B(v) => v.into(),
+            N::I(v) | N::D(v, 0) => {
+                if let Ok(v) = i64::try_from(v) {
+                    v.into()
+                } else if let Ok(v) = u64::try_from(v) {
+                    v.into()
+                } else {
+                    f64::from(self).into()
+                }
+            }
+            N::F(v) => v.into(),
+            N::D(..) => f64::from(self).into(),
         }
     }
 
@@ -142,6 +322,7 @@ impl Number {
                 let mut output = ryu::Buffer::new();
                 sink.write_all(output.format_finite(v).as_bytes())
             }
+            N::D(m, s) => write!(sink, "{}", DisplayDecimal(m, s)),
         }
     }
 
@@ -149,13 +330,18 @@ impl Number {
     pub fn sql_sign(self) -> Ordering {
         match self.0 {
             N::B(v) => v.cmp(&false),
-            N::I(v) => v.cmp(&0),
+            N::I(v) | N::D(v, _) => v.cmp(&0),
             N::F(v) => v.partial_cmp(&0.0).unwrap_or(Ordering::Equal),
         }
     }
 
     /// Adds this number with another number.
     pub fn add(self, other: Self) -> Result<Self, NumberError> {
+        if let Some((a, b, s)) = self.aligned_decimals(other) {
+            if let Some(c) = a.checked_add(b) {
+                return Ok(Self(N::D(c, s)));
+            }
+        }
         if let (Ok(a), Ok(b)) = (self.try_as_i128(), other.try_as_i128()) {
             if let Some(c) = a.checked_add(b) {
                 return Ok(Self(N::I(c)));
@@ -167,6 +353,11 @@ impl Number {
     /// Negates itself.
     #[must_use]
     pub fn neg(self) -> Self {
+        if let N::D(m, s) = self.0 {
+            if let Some(c) = m.checked_neg() {
+                return Self(N::D(c, s));
+            }
+        }
         if let Ok(a) = self.try_as_i128() {
             if let Some(c) = a.checked_neg() {
                 return Self(N::I(c));
@@ -177,6 +368,11 @@ impl Number {
 
     /// Subtracts this number from another number.
     pub fn sub(self, other: Self) -> Result<Self, NumberError> {
+        if let Some((a, b, s)) = self.aligned_decimals(other) {
+            if let Some(c) = a.checked_sub(b) {
+                return Ok(Self(N::D(c, s)));
+            }
+        }
         if let (Ok(a), Ok(b)) = (self.try_as_i128(), other.try_as_i128()) {
             if let Some(c) = a.checked_sub(b) {
                 return Ok(Self(N::I(c)));
@@ -187,6 +383,15 @@ impl Number {
 
     /// Multiplies this number with another number.
     pub fn mul(self, other: Self) -> Result<Self, NumberError> {
+        if let Some(((a, sa), (b, sb))) = self.decimal_operands(other) {
+            let product_scale = u32::from(sa) + u32::from(sb);
+            let scale = product_scale.min(MAX_DECIMAL_PRECISION.into());
+            if let Some(c) = a.checked_mul(b).and_then(|c| rescale(c, product_scale, scale)) {
+                // ALLOW_REASON: scale ≤ MAX_DECIMAL_PRECISION
+                #[allow(clippy::cast_possible_truncation)]
+                return Ok(Self(N::D(c, scale as u8)));
+            }
+        }
         if let (Ok(a), Ok(b)) = (self.try_as_i128(), other.try_as_i128()) {
             if let Some(c) = a.checked_mul(b) {
                 return Ok(Self(N::I(c)));
@@ -197,6 +402,11 @@ impl Number {
 
     /// Divides this number with another number, truncated as an integer towards zero.
     pub fn div(self, other: Self) -> Result<Self, NumberError> {
+        if let Some((a, b, _)) = self.aligned_decimals(other) {
+            if let Some(c) = a.checked_div(b) {
+                return Ok(Self(N::I(c)));
+            }
+        }
         if let (Ok(a), Ok(b)) = (self.try_as_i128(), other.try_as_i128()) {
             if let Some(c) = a.checked_div(b) {
                 return Ok(Self(N::I(c)));
@@ -214,6 +424,13 @@ impl Number {
 
     /// Computes the remainder (modulus) when this number is divided by another number.
     pub fn rem(self, other: Self) -> Result<Self, NumberError> {
+        if let Some((a, b, s)) = self.aligned_decimals(other) {
+            match b {
+                0 => {}
+                -1 => return Ok(Self(N::D(0, s))),
+                _ => return Ok(Self(N::D(a % b, s))),
+            }
+        }
         if let (Ok(a), Ok(b)) = (self.try_as_i128(), other.try_as_i128()) {
             match b {
                 // Fallthrough for zero denominator.
@@ -235,7 +452,18 @@ impl Number {
     }
 
     /// Divides this number with another number using floating point arithmetic.
+    ///
+    /// If either number is a decimal, the quotient is a decimal with 4 more digits after the
+    /// decimal point than the operands.
     pub fn float_div(self, other: Self) -> Result<Self, NumberError> {
+        if let Some((a, b, s)) = self.aligned_decimals(other) {
+            let scale = (s + 4).min(MAX_DECIMAL_PRECISION);
+            if b != 0 {
+                if let Some(a) = rescale(a, 0, scale.into()) {
+                    return Ok(Self(N::D(round_div(a, b), scale)));
+                }
+            }
+        }
         let a = f64::from(self);
         let b = f64::from(other);
         if b == 0.0 {
@@ -249,6 +477,9 @@ impl Number {
 macro_rules! impl_partial_ord_method {
     ($(fn $fn_name:ident(...) -> $ret:ty = $method:ident;)+) => {
         $(fn $fn_name(&self, other: &Self) -> $ret {
+            if let Some((a, b, _)) = self.aligned_decimals(*other) {
+                return a.$method(b);
+            }
             match (self.try_as_i128(), other.try_as_i128()) {
                 (Ok(a), Ok(b)) => a.$method(b),
                 (Ok(a), Err(b)) => a.$method(b),
@@ -483,4 +714,39 @@ mod tests {
         assert_eq!(Number::from(true).rem(Number::from(false)), Err(NumberError::NaN));
         assert_eq!(Number::from(false).rem(Number::from(true)), Ok(Number::from(0)));
     }
+
+    #[test]
+    fn test_decimal() {
+        let decimal = |s: &str| Number::parse_decimal(s).unwrap();
+        assert_eq!(decimal("-12.50").to_string(), "-12.50");
+        assert_eq!(decimal("1.5e3").to_string(), "1500");
+        assert_eq!(decimal(".05").to_string(), "0.05");
+        assert_eq!(Number::parse_decimal("1.2.3"), None);
+        assert_eq!(Number::parse_decimal("-"), None);
+
+        assert_eq!(
+            Number::from_finite_f64(0.1).to_decimal(38, 20).unwrap().to_string(),
+            "0.10000000000000000000"
+        );
+        assert_eq!(Number::from_finite_f64(2.5).to_decimal(5, 0).unwrap().to_string(), "3");
+        assert_eq!(
+            Number::from_finite_f64(-2.5).to_decimal(5, 0).unwrap().to_string(),
+            "-3"
+        );
+        assert_eq!(Number::from(12345).to_decimal(4, 0), Err(NumberError::Overflow));
+        assert_eq!(Number::from(1).to_decimal(38, 38), Err(NumberError::Overflow));
+
+        assert_eq!(decimal("0.1").add(decimal("0.2")), Ok(decimal("0.3")));
+        assert_eq!(decimal("0.1").sub(Number::from(1)).unwrap().to_string(), "-0.9");
+        assert_eq!(decimal("1.5").mul(decimal("1.5")).unwrap().to_string(), "2.25");
+        assert_eq!(decimal("7.5").div(Number::from(2)), Ok(Number::from(3)));
+        assert_eq!(decimal("7.5").rem(Number::from(2)).unwrap().to_string(), "1.5");
+        assert_eq!(decimal("2").float_div(Number::from(3)).unwrap().to_string(), "0.6667");
+        assert_eq!(decimal("2").float_div(decimal("0.0")), Err(NumberError::NaN));
+        assert_eq!(decimal("-0.5").neg().to_string(), "0.5");
+
+        assert_eq!(decimal("1.10"), decimal("1.1"));
+        assert!(decimal("0.30000000000000000001") > decimal("0.3"));
+        assert_eq!(decimal("-0.5").sql_sign(), Ordering::Less);
+    }
 }
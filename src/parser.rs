@@ -4,13 +4,16 @@ pub(crate) use self::derived::Rule;
 use self::derived::TemplateParser;
 use crate::{
     error::Error,
+    format::Dialect,
     functions::{self, Function},
+    infer,
     span::{Registry, ResultExt, Span, SpanExt, S},
     value::Value,
 };
 
 use pest::{iterators::Pairs, Parser};
-use std::{collections::HashMap, mem, ops::Range};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, mem, ops::Range, sync::RwLock};
 
 mod derived {
     use pest_derive::Parser;
@@ -21,7 +24,7 @@ mod derived {
 }
 
 /// A schema-qualified name with quotation marks still intact.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct QName {
     table_name_index: usize,
     unique_table_name_index: usize,
@@ -108,6 +111,11 @@ impl QName {
         &self.unique_name
     }
 
+    /// Obtains the unique name of the table alone, without the schema and database.
+    pub fn unique_table_name(&self) -> &str {
+        &self.unique_name[self.unique_table_name_index..]
+    }
+
     /// Obtains the qualified schema name (`"db"."schema"`) if it exists.
     pub fn schema_name(&self) -> Option<&str> {
         Some(&self.qualified_name[..self.table_name_index.checked_sub(1)?])
@@ -117,6 +125,46 @@ impl QName {
     pub fn unique_schema_name(&self) -> Option<&str> {
         Some(&self.unique_name[..self.unique_table_name_index.checked_sub(1)?])
     }
+
+    /// Rewrites the quoted parts of the name using the quotation marks of the dialect.
+    ///
+    /// The unique name is not affected.
+    pub fn requote(&mut self, dialect: Dialect) {
+        let table_name = dialect.requote(self.table_name(false));
+        let mut qualified_name = self
+            .schema_name()
+            .map_or_else(String::new, |schema| dialect.requote(schema) + ".");
+        self.table_name_index = qualified_name.len();
+        qualified_name.push_str(&table_name);
+        self.qualified_name = qualified_name;
+    }
+
+    /// Creates the name of the `index`-th shard of this table, by appending `_index` to the
+    /// table name (inside the quotation marks if it is quoted).
+    pub fn shard(&self, index: usize) -> Self {
+        let table_name = shard_identifier(self.table_name(false), index);
+        let mut qualified_name = self.qualified_name[..self.table_name_index].to_owned();
+        let mut unique_name = self.unique_name[..self.unique_table_name_index].to_owned();
+        qualified_name.push_str(&table_name);
+        unescape_into(&mut unique_name, &table_name, true);
+        Self {
+            table_name_index: self.table_name_index,
+            unique_table_name_index: self.unique_table_name_index,
+            qualified_name,
+            unique_name,
+        }
+    }
+}
+
+/// Appends `_index` to an identifier, inside the quotation marks if it is quoted.
+pub(crate) fn shard_identifier(ident: &str, index: usize) -> String {
+    match ident.as_bytes().last() {
+        Some(b'`' | b'"' | b']') if ident.len() > 1 => {
+            let (body, close) = ident.split_at(ident.len() - 1);
+            format!("{body}_{index}{close}")
+        }
+        _ => format!("{ident}_{index}"),
+    }
 }
 
 fn unescape_into(res: &mut String, ident: &str, do_percent_escape: bool) {
@@ -152,26 +200,221 @@ fn unescape_into(res: &mut String, ident: &str, do_percent_escape: bool) {
 }
 
 /// One single table.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Table {
     /// The default table name.
     pub name: QName,
 
+    /// The modifiers between `CREATE` and `TABLE` (e.g. `UNLOGGED `), each followed by a space.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub modifiers: String,
+
+    /// Whether the table is created with `IF NOT EXISTS`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub if_not_exists: bool,
+
     /// The content of the CREATE TABLE statement.
     pub content: String,
 
+    /// The statements following the table, like `CREATE INDEX` or `ALTER TABLE`, which are
+    /// written to the schema file after the table.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub auxiliary_statements: Vec<String>,
+
     /// The ranges in `content` which column names appear.
     pub column_name_ranges: Vec<Range<usize>>,
 
+    /// The ranges in `content` between the column names and the expressions, which usually contain
+    /// the column types.
+    pub column_type_ranges: Vec<Range<usize>>,
+
     /// The expressions to populate the table.
     pub exprs: Vec<S<Expr>>,
 
     /// The indices of the derived tables, and the number of rows to generate.
     pub derived: Vec<(usize, S<Expr>)>,
+
+    /// The variables and the lists whose cross product is enumerated by the rows, if the table is
+    /// preceded by a `for each combination of` directive.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub combinations: Vec<(usize, S<Expr>)>,
+
+    /// The schema versions containing each column. Empty if the table does not declare any.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub column_versions: Vec<ColumnVersions>,
+
+    /// The number of physical tables sharing this definition, and the expression routing every row
+    /// to one of them, if the table is preceded by a `shard into` directive.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shards: Option<(u32, S<Expr>)>,
+}
+
+/// The schema versions containing a column, declared by the `{{ version N adds column }}` and
+/// `{{ version N drops column }}` directives preceding it.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnVersions {
+    /// The first version containing the column, or 0 if it exists from the beginning.
+    pub added: u32,
+    /// The first version not containing the column, if it is dropped.
+    pub dropped: Option<u32>,
+}
+
+impl ColumnVersions {
+    /// Returns whether the column exists in the schema version.
+    pub fn contains(self, version: u32) -> bool {
+        self.added <= version && !matches!(self.dropped, Some(dropped) if dropped <= version)
+    }
+}
+
+impl Table {
+    /// Rewrites the quoted table name and column names using the quotation marks of the dialect.
+    pub fn requote_identifiers(&mut self, dialect: Dialect) {
+        self.name.requote(dialect);
+
+        let mut content = String::with_capacity(self.content.len());
+        let mut name_ends = Vec::with_capacity(self.column_name_ranges.len());
+        let mut last = 0;
+        for range in &mut self.column_name_ranges {
+            if range.is_empty() {
+                // anonymous columns have no names to requote.
+                continue;
+            }
+            content.push_str(&self.content[last..range.start]);
+            let start = content.len();
+            content.push_str(&dialect.requote(&self.content[range.clone()]));
+            last = range.end;
+            name_ends.push((range.end, content.len()));
+            *range = start..content.len();
+        }
+        content.push_str(&self.content[last..]);
+
+        let new_position = |pos: usize| {
+            name_ends
+                .iter()
+                .rev()
+                .find(|(old_end, _)| *old_end <= pos)
+                .map_or(pos, |(old_end, new_end)| pos - old_end + new_end)
+        };
+        for range in &mut self.column_type_ranges {
+            *range = new_position(range.start)..new_position(range.end);
+        }
+        self.content = content;
+    }
+
+    /// Removes the columns not existing in the schema version from the table.
+    ///
+    /// The expressions of the removed columns are still evaluated together with a neighboring
+    /// column, so the remaining columns contain the same values in every version. A hidden
+    /// variable is allocated from `variables_count` if the trailing columns are removed.
+    pub fn select_version(&mut self, version: u32, variables_count: &mut usize) -> Result<(), S<Error>> {
+        let removed = (0..self.exprs.len())
+            .map(|i| {
+                !self
+                    .column_versions
+                    .get(i)
+                    .copied()
+                    .unwrap_or_default()
+                    .contains(version)
+            })
+            .collect::<Vec<_>>();
+        if !removed.contains(&true) {
+            return Ok(());
+        }
+        if !removed.contains(&false) {
+            return Err(Error::NoColumnsInVersion {
+                table: self.name.table_name(true).to_owned(),
+                version,
+            }
+            .no_span());
+        }
+
+        // drop the definitions of the removed columns from the content.
+        let definitions = infer::split_definitions(&self.content);
+        let removed_definitions = definitions
+            .iter()
+            .map(|def| {
+                self.column_name_ranges
+                    .iter()
+                    .zip(&removed)
+                    .any(|(name, removed)| *removed && !name.is_empty() && def.contains(&name.start))
+            })
+            .collect::<Vec<_>>();
+        let mut content = String::with_capacity(self.content.len());
+        content.push_str(&self.content[..definitions.first().map_or(0, |def| def.start)]);
+        let mut shifts = Vec::with_capacity(definitions.len());
+        let mut is_first = true;
+        for (def, removed) in definitions.iter().zip(&removed_definitions) {
+            if *removed {
+                shifts.push(None);
+                continue;
+            }
+            if !mem::take(&mut is_first) {
+                content.push(',');
+            }
+            shifts.push(Some(def.start - content.len()));
+            content.push_str(&self.content[def.clone()]);
+        }
+        content.push_str(&self.content[definitions.last().map_or(0, |def| def.end)..]);
+        let new_range = |range: &Range<usize>| {
+            definitions
+                .iter()
+                .zip(&shifts)
+                .find(|(def, _)| def.start <= range.start && range.end <= def.end)
+                .and_then(|(_, shift)| *shift)
+                .map_or(0..0, |shift| range.start - shift..range.end - shift)
+        };
+
+        let mut column_name_ranges = Vec::with_capacity(self.exprs.len());
+        let mut column_type_ranges = Vec::with_capacity(self.exprs.len());
+        let mut exprs = Vec::<S<Expr>>::with_capacity(self.exprs.len());
+        let mut pending = Vec::new();
+        for (i, expr) in mem::take(&mut self.exprs).into_iter().enumerate() {
+            if removed[i] {
+                pending.push(expr);
+                continue;
+            }
+            column_name_ranges.push(new_range(&self.column_name_ranges[i]));
+            column_type_ranges.push(new_range(&self.column_type_ranges[i]));
+            if pending.is_empty() {
+                exprs.push(expr);
+            } else {
+                let span = expr.span;
+                pending.push(expr);
+                exprs.push(
+                    Expr::Function {
+                        function: &functions::ops::Last,
+                        args: mem::take(&mut pending),
+                    }
+                    .span(span),
+                );
+            }
+        }
+        if let Some(last) = exprs.last_mut().filter(|_| !pending.is_empty()) {
+            // evaluate the trailing removed columns after the last column, and keep its value.
+            let hidden = *variables_count;
+            *variables_count += 1;
+            let span = last.span;
+            let mut args = vec![Expr::SetVariable(hidden, Box::new(mem::take(last))).span(span)];
+            args.append(&mut pending);
+            args.push(Expr::GetVariable(hidden).span(span));
+            *last = Expr::Function {
+                function: &functions::ops::Last,
+                args,
+            }
+            .span(span);
+        }
+
+        self.content = content;
+        self.column_name_ranges = column_name_ranges;
+        self.column_type_ranges = column_type_ranges;
+        self.exprs = exprs;
+        self.column_versions.clear();
+        Ok(())
+    }
 }
 
 /// A parsed template.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Template {
     /// The expressions shared among all tables and rows.
     ///
@@ -183,10 +426,25 @@ pub struct Template {
 
     /// The tables to be written out.
     pub tables: Vec<Table>,
+
+    /// The condition a row of the main tables must satisfy to be written, as in the `--where` CLI
+    /// parameter.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub row_filter: Option<S<Expr>>,
+
+    /// The key ordering the rows of every table within a data file, as in the `--order-by` CLI
+    /// parameter.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order_by: Option<S<Expr>>,
+
+    /// The keys ordering the rows of individual tables given by name, overriding `order_by`, as in
+    /// the `--table-order-by` CLI parameter.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub table_order_by: Vec<(String, S<Expr>)>,
 }
 
 /// A parsed expression.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Expr {
     /// The `rownum` symbol.
     RowNum,
@@ -194,8 +452,10 @@ pub enum Expr {
     SubRowNum,
     /// The `current_timestamp` symbol.
     CurrentTimestamp,
+    /// The `scale_factor` symbol.
+    ScaleFactor,
     /// A constant value.
-    Value(Value),
+    Value(#[serde(with = "serde_literal")] Value),
     /// Symbol of a local variable `@x`.
     GetVariable(usize),
     /// A variable assignment expression `@x := y`.
@@ -203,6 +463,7 @@ pub enum Expr {
     /// A function call.
     Function {
         /// The function.
+        #[serde(with = "serde_function")]
         function: &'static dyn Function,
         /// Function arguments.
         args: Vec<S<Expr>>,
@@ -216,6 +477,13 @@ pub enum Expr {
         /// The result when all conditions failed.
         otherwise: Option<Box<S<Expr>>>,
     },
+    /// A `pinned(namespace, expr)` expression.
+    Pinned {
+        /// The namespace of the random number generator.
+        namespace: Box<S<Expr>>,
+        /// The expression evaluated using the namespaced random number generator.
+        inner: Box<S<Expr>>,
+    },
 }
 
 impl Default for Expr {
@@ -228,20 +496,41 @@ fn is_ident_char(c: char) -> bool {
     c.is_alphanumeric() || matches!(c, '_' | '`' | '"' | '[' | ']')
 }
 
+/// Options of [`Template::parse()`] applied on top of the template content.
+#[derive(Clone, Debug, Default)]
+pub struct ParseOptions<'a> {
+    /// The condition a row of the main tables must satisfy to be written, as in the `--where` CLI
+    /// parameter.
+    pub row_filter: Option<&'a str>,
+    /// The key ordering the rows of every table within a data file, as in the `--order-by` CLI
+    /// parameter.
+    pub order_by: Option<&'a str>,
+    /// The table names and the keys ordering their rows, as in the `--table-order-by` CLI
+    /// parameter.
+    pub table_order_by: Vec<(&'a str, &'a str)>,
+    /// Whether to infer the expressions of tables without any from their column types, as in the
+    /// `--infer-expressions` CLI parameter.
+    pub infer_expressions: bool,
+    /// Overrides the schema name of all tables, as in the `--schema-name` CLI parameter.
+    pub override_schema: Option<&'a str>,
+}
+
 impl Template {
     /// Parses a raw string into a structured template.
     pub fn parse(
         input: &str,
         init_globals: &[String],
-        override_schema: Option<&str>,
+        overrides: &[String],
+        options: &ParseOptions<'_>,
         span_registry: &mut Registry,
     ) -> Result<Self, S<Error>> {
         let mut alloc = Allocator {
             override_schema: [None; 2],
             map: HashMap::new(),
+            functions: HashMap::new(),
             span_registry,
         };
-        if let Some(schema) = override_schema {
+        if let Some(schema) = options.override_schema {
             alloc.set_schema_name(schema).span_err(Span::default())?;
         }
         let mut template = Self {
@@ -258,6 +547,8 @@ impl Template {
         let pairs = TemplateParser::parse(Rule::create_table, input).span_err(Span::default())?;
         let mut table_map = HashMap::new();
         let mut expected_child_name = None::<S<QName>>;
+        let mut combinations = Vec::new();
+        let mut shards = None;
 
         for pair in pairs {
             let span = pair.as_span();
@@ -269,7 +560,9 @@ impl Template {
                         .span(alloc.register(span)),
                 ),
                 Rule::single_table => {
-                    let table = alloc.table_from_pairs(pair.into_inner())?;
+                    let mut table = alloc.table_from_pairs(pair.into_inner())?;
+                    table.combinations = mem::take(&mut combinations);
+                    table.shards = shards.take();
                     let table_name = table.name.unique_name();
                     if let Some(child_name) = &expected_child_name {
                         if child_name.inner.unique_name() != table_name {
@@ -298,13 +591,63 @@ impl Template {
                         .span(parent.span));
                     }
                 }
+                Rule::combination_directive => {
+                    combinations = alloc.combination_directive_from_pairs(pair.into_inner())?;
+                }
+                Rule::shard_directive => shards = Some(alloc.shard_directive_from_pairs(pair.into_inner())?),
+                Rule::define_directive => alloc.define_directive_from_pairs(pair.into_inner())?,
                 r => unreachable!("Unexpected rule {:?}", r),
             }
         }
 
+        if options.infer_expressions {
+            for table in &mut template.tables {
+                if table.exprs.is_empty() {
+                    alloc.infer_table_exprs(table)?;
+                }
+            }
+        }
+
+        for column_override in overrides {
+            alloc.override_column(&mut template.tables, column_override)?;
+        }
+
+        if let Some(input) = options.row_filter {
+            let pairs = TemplateParser::parse(Rule::stmt, input).span_err(Span::default())?;
+            template.row_filter = Some(alloc.stmt_from_pairs(pairs)?.span(Span::default()));
+        }
+        if let Some(input) = options.order_by {
+            let pairs = TemplateParser::parse(Rule::stmt, input).span_err(Span::default())?;
+            template.order_by = Some(alloc.stmt_from_pairs(pairs)?.span(Span::default()));
+        }
+        for (table_name, input) in &options.table_order_by {
+            let pairs = TemplateParser::parse(Rule::stmt, input).span_err(Span::default())?;
+            let order_by = alloc.stmt_from_pairs(pairs)?.span(Span::default());
+            template.table_order_by.push(((*table_name).to_owned(), order_by));
+        }
+
         template.variables_count = alloc.map.len();
         Ok(template)
     }
+
+    /// Returns the number of schema versions declared by the `{{ version N adds column }}` and
+    /// `{{ version N drops column }}` directives, or 0 if there are none.
+    pub fn versions_count(&self) -> u32 {
+        self.tables
+            .iter()
+            .flat_map(|table| &table.column_versions)
+            .map(|versions| versions.added.max(versions.dropped.unwrap_or(0)))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Removes the columns not existing in the schema version from all tables.
+    pub fn select_version(&mut self, version: u32) -> Result<(), S<Error>> {
+        for table in &mut self.tables {
+            table.select_version(version, &mut self.variables_count)?;
+        }
+        Ok(())
+    }
 }
 
 /// Local variable allocator. This structure keeps record of local variables `@x` and assigns a
@@ -312,9 +655,27 @@ impl Template {
 struct Allocator<'a> {
     override_schema: [Option<&'a str>; 2],
     map: HashMap<String, usize>,
+    functions: HashMap<String, UserFunction>,
     span_registry: &'a mut Registry,
 }
 
+/// A function defined by the template using `{{ define name(@a, @b) := body }}`.
+struct UserFunction {
+    /// The variable indices of the parameters in the body.
+    params: Vec<usize>,
+    /// The function body.
+    body: S<Expr>,
+}
+
+/// The probabilities declared by a `/*#dbgen null=p default=q */` column annotation.
+#[derive(Default, Copy, Clone)]
+struct ColumnAnnotation {
+    /// Probability of generating `NULL` instead of the column expression.
+    null: f64,
+    /// Probability of generating the `DEFAULT` value instead of the column expression.
+    default: f64,
+}
+
 #[derive(Default)]
 struct DependencyDirective {
     parent: S<QName>,
@@ -339,16 +700,92 @@ impl<'a> Allocator<'a> {
         *self.map.entry(var_name).or_insert(count)
     }
 
+    /// Allocates a local variable index which cannot be referred by name in the template.
+    fn allocate_hidden(&mut self) -> usize {
+        let count = self.map.len();
+        self.map.insert(format!("\0{count}"), count);
+        count
+    }
+
     fn register(&mut self, span: pest::Span<'_>) -> Span {
         self.span_registry.register(span)
     }
 
+    /// Fills in the columns and expressions of a table without any expressions, inferred from the
+    /// column types.
+    fn infer_table_exprs(&mut self, table: &mut Table) -> Result<(), S<Error>> {
+        for (name_range, type_range) in infer::split_columns(&table.content) {
+            let input = infer::infer_expression(&table.content[type_range.clone()]);
+            let pairs = TemplateParser::parse(Rule::stmt, &input).span_err(Span::default())?;
+            table.exprs.push(self.stmt_from_pairs(pairs)?.span(Span::default()));
+            table.column_name_ranges.push(name_range);
+            table.column_type_ranges.push(type_range);
+        }
+        Ok(())
+    }
+
+    /// Replaces the expression of a column given an override `table.column = expr`.
+    fn override_column(&mut self, tables: &mut [Table], input: &str) -> Result<(), S<Error>> {
+        let mut pairs = TemplateParser::parse(Rule::column_override, input)
+            .span_err(Span::default())?
+            .next()
+            .unwrap()
+            .into_inner();
+        let mut names = pairs
+            .next()
+            .unwrap()
+            .into_inner()
+            .map(|pair| pair.as_str())
+            .collect::<Vec<_>>();
+        let column = names.pop().expect("at least two names");
+        let table = names.pop().expect("at least two names");
+        let (database, schema) = match names[..] {
+            [database, schema] => (Some(database), Some(schema)),
+            [schema] => (None, Some(schema)),
+            _ => (None, None),
+        };
+        let is_qualified = schema.is_some();
+        let table_name = QName::new(database, schema, table);
+
+        let mut unique_column_name = String::with_capacity(column.len());
+        unescape_into(&mut unique_column_name, column, true);
+
+        let target = tables
+            .iter_mut()
+            .filter(|t| {
+                if is_qualified {
+                    t.name.unique_name() == table_name.unique_name()
+                } else {
+                    t.name.unique_table_name() == table_name.unique_table_name()
+                }
+            })
+            .find_map(|t| {
+                let index = t.column_name_ranges.iter().position(|r| {
+                    let mut name = String::with_capacity(r.len());
+                    unescape_into(&mut name, &t.content[r.clone()], true);
+                    name == unique_column_name
+                })?;
+                Some(&mut t.exprs[index])
+            })
+            .ok_or_else(|| {
+                Error::UnknownOverrideColumn {
+                    table: table_name.table_name(true).to_owned(),
+                    column: column.to_owned(),
+                }
+                .no_span()
+            })?;
+        *target = self.stmt_from_pairs(pairs)?.span(Span::default());
+        Ok(())
+    }
+
     /// Creates a single table.
     fn table_from_pairs(&mut self, pairs: Pairs<'_, Rule>) -> Result<Table, S<Error>> {
         let mut table = Table::default();
         let mut previous_end_line = 0;
         let mut column_name_range = 0..0;
         let mut column_name_is_expired = true;
+        let mut pending_versions = None::<(ColumnVersions, Span)>;
+        let mut pending_annotation = None::<(ColumnAnnotation, Span)>;
 
         for pair in pairs {
             let span = pair.as_span();
@@ -356,10 +793,14 @@ impl<'a> Allocator<'a> {
             let s = span.as_str();
             match pair.as_rule() {
                 Rule::kw_create | Rule::kw_table => {}
-                Rule::qname => table.name = QName::from_pairs(pair.into_inner(), self.override_schema),
-                Rule::open_paren | Rule::close_paren => {
-                    table.content.push_str(s);
+                Rule::table_modifier => {
+                    table.modifiers.push_str(&s.to_ascii_uppercase());
+                    table.modifiers.push(' ');
                 }
+                Rule::if_not_exists => table.if_not_exists = true,
+                Rule::auxiliary_statement => table.auxiliary_statements.push(s.trim_end().to_owned()),
+                Rule::qname => table.name = QName::from_pairs(pair.into_inner(), self.override_schema),
+                Rule::open_paren | Rule::close_paren => table.content.push_str(s),
                 Rule::op_comma => {
                     column_name_is_expired = true;
                     table.content.push_str(s);
@@ -369,8 +810,13 @@ impl<'a> Allocator<'a> {
                     if previous_end_line != start_line {
                         // insert an indented '\n' if the whitespace we skipped included it.
                         table.content.push_str("\n    ");
-                    } else if table.content.ends_with(is_ident_char) && s.starts_with(is_ident_char) {
-                        // insert a space if needed to ensure word boundaries
+                    } else if table.content.ends_with(is_ident_char)
+                        && ((s.starts_with(is_ident_char) && !s.starts_with('['))
+                            || span.get_input()[..span.start()].ends_with(char::is_whitespace))
+                    {
+                        // insert a space if needed to ensure word boundaries, or if the tokens were
+                        // separated by whitespace (e.g. `DEFAULT $$x$$`). array bounds like `text[]`
+                        // are kept attached to the type.
                         table.content.push(' ');
                     }
                     let table_content_len = table.content.len();
@@ -381,22 +827,174 @@ impl<'a> Allocator<'a> {
                     }
                 }
                 Rule::stmt => {
+                    let column_type_start = if column_name_is_expired {
+                        table.content.len()
+                    } else {
+                        column_name_range.end
+                    };
+                    table.column_type_ranges.push(column_type_start..table.content.len());
                     table.column_name_ranges.push(column_name_range);
                     column_name_is_expired = true;
                     column_name_range = 0..0;
-                    table.exprs.push(
-                        self.expr_binary_from_pairs(pair.into_inner())?
-                            .span(self.register(span)),
-                    );
+                    let mut expr = self
+                        .expr_binary_from_pairs(pair.into_inner())?
+                        .span(self.register(span));
+                    if let Some((annotation, span)) = pending_annotation.take() {
+                        let column_type = &table.content[column_type_start..];
+                        expr = self.annotated_expr(expr, annotation, column_type, span)?;
+                    }
+                    table.exprs.push(expr);
+                    table
+                        .column_versions
+                        .push(pending_versions.take().map_or_else(ColumnVersions::default, |(v, _)| v));
+                }
+                Rule::version_directive => {
+                    let span = self.register(span);
+                    let mut versions = pending_versions.map_or_else(ColumnVersions::default, |(v, _)| v);
+                    Self::version_directive_from_pairs(&mut versions, pair.into_inner()).span_err(span)?;
+                    pending_versions = Some((versions, span));
+                }
+                Rule::column_annotation => {
+                    let span = self.register(span);
+                    let mut annotation = pending_annotation.map_or_else(ColumnAnnotation::default, |(a, _)| a);
+                    Self::column_annotation_from_pairs(&mut annotation, pair.into_inner()).span_err(span)?;
+                    pending_annotation = Some((annotation, span));
+                    // annotations are written like comments, so they do not affect the line breaks
+                    // of the table content.
+                    continue;
                 }
                 r => unreachable!("Unexpected rule {:?}", r),
             }
             previous_end_line = end_line;
         }
 
+        if let Some((_, span)) = pending_versions {
+            return Err(Error::InvalidVersionDirective("not followed by a column").span(span));
+        }
+        if let Some((_, span)) = pending_annotation {
+            return Err(Error::InvalidColumnAnnotation("not followed by a column".to_owned()).span(span));
+        }
+        if table.column_versions.iter().all(|v| *v == ColumnVersions::default()) {
+            table.column_versions.clear();
+        }
         Ok(table)
     }
 
+    /// Parses a version directive, and updates the versions of the following column.
+    fn version_directive_from_pairs(versions: &mut ColumnVersions, pairs: Pairs<'_, Rule>) -> Result<(), Error> {
+        let mut version = 0;
+        for pair in pairs {
+            match pair.as_rule() {
+                Rule::kw_version | Rule::kw_column => {}
+                Rule::number => {
+                    version = pair
+                        .as_str()
+                        .parse()
+                        .ok()
+                        .filter(|v| *v > 0)
+                        .ok_or(Error::InvalidVersionDirective("the version must be a positive integer"))?;
+                }
+                Rule::kw_adds => versions.added = version,
+                Rule::kw_drops => versions.dropped = Some(version),
+                r => unreachable!("Unexpected rule {:?}", r),
+            }
+        }
+        if matches!(versions.dropped, Some(dropped) if dropped <= versions.added) {
+            return Err(Error::InvalidVersionDirective(
+                "the column must be dropped after it is added",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Parses a column annotation, and updates the probabilities of the following column.
+    fn column_annotation_from_pairs(annotation: &mut ColumnAnnotation, pairs: Pairs<'_, Rule>) -> Result<(), Error> {
+        for pair in pairs {
+            if pair.as_rule() == Rule::column_annotation_start {
+                continue;
+            }
+            let mut inner = pair.into_inner();
+            let key = inner.next().unwrap().as_str();
+            let value = inner.next().unwrap().as_str();
+            let probability = value
+                .parse::<f64>()
+                .ok()
+                .filter(|p| (0.0..=1.0).contains(p))
+                .ok_or_else(|| {
+                    Error::InvalidColumnAnnotation(format!("{key}={value} is not a probability between 0 and 1"))
+                })?;
+            match &*key.to_ascii_lowercase() {
+                "null" => annotation.null = probability,
+                "default" => annotation.default = probability,
+                _ => return Err(Error::InvalidColumnAnnotation(format!("unknown key '{key}'"))),
+            }
+        }
+        if annotation.default > 1.0 - annotation.null {
+            return Err(Error::InvalidColumnAnnotation(
+                "the sum of the probabilities must not exceed 1".to_owned(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Wraps the column expression with the `NULL` and `DEFAULT` mix declared by the column
+    /// annotation, i.e. `CASE WHEN rand.bool(null) THEN NULL WHEN rand.bool(…) THEN default ELSE expr END`.
+    fn annotated_expr(
+        &mut self,
+        expr: S<Expr>,
+        annotation: ColumnAnnotation,
+        column_type: &str,
+        span: Span,
+    ) -> Result<S<Expr>, S<Error>> {
+        let rand_bool = |p: f64| {
+            Expr::Function {
+                function: &functions::rand::Bool,
+                args: vec![Expr::Value(Value::from_finite_f64(p)).span(span)],
+            }
+            .span(span)
+        };
+
+        let mut conditions = Vec::new();
+        if annotation.null > 0.0 {
+            conditions.push((rand_bool(annotation.null), Expr::Value(Value::Null).span(span)));
+        }
+        if annotation.default > 0.0 {
+            let input = infer::default_clause(column_type).ok_or_else(|| {
+                Error::InvalidColumnAnnotation("the column has no DEFAULT clause before the expression".to_owned())
+                    .span(span)
+            })?;
+            if input.is_empty() {
+                return Err(Error::InvalidColumnAnnotation("the DEFAULT clause has no value".to_owned()).span(span));
+            }
+            let default = TemplateParser::parse(Rule::expr_unary, input)
+                .ok()
+                .and_then(|mut pairs| pairs.next())
+                .filter(|pair| pair.as_span().end() == input.len())
+                .and_then(|pair| self.expr_unary_from_pairs(pair.into_inner()).ok())
+                .ok_or_else(|| {
+                    Error::InvalidColumnAnnotation(format!("the DEFAULT value {input} cannot be evaluated by dbgen"))
+                        .span(span)
+                })?
+                .span(span);
+            // the second condition is only checked when the first failed, so the probability is
+            // conditioned on the value not being NULL.
+            conditions.push((
+                rand_bool((annotation.default / (1.0 - annotation.null)).min(1.0)),
+                default,
+            ));
+        }
+
+        if conditions.is_empty() {
+            return Ok(expr);
+        }
+        Ok(Expr::CaseValueWhen {
+            value: None,
+            conditions,
+            otherwise: Some(Box::new(expr)),
+        }
+        .span(span))
+    }
+
     /// Parses a dependency directive.
     fn dependency_directive_from_pairs(&mut self, pairs: Pairs<'_, Rule>) -> Result<DependencyDirective, S<Error>> {
         let mut res = DependencyDirective::default();
@@ -423,6 +1021,127 @@ impl<'a> Allocator<'a> {
         Ok(res)
     }
 
+    /// Parses a function definition and registers it to be called by the following expressions.
+    fn define_directive_from_pairs(&mut self, pairs: Pairs<'_, Rule>) -> Result<(), S<Error>> {
+        let mut name = String::new();
+        let mut params = Vec::new();
+        for pair in pairs {
+            let span = pair.as_span();
+            match pair.as_rule() {
+                Rule::kw_define => {}
+                Rule::qname => {
+                    name = QName::from_pairs(pair.into_inner(), [None; 2]).unique_name().to_owned();
+                    if name == "pinned" || function_from_name(&name).is_ok() || self.functions.contains_key(&name) {
+                        return Err(Error::FunctionAlreadyDefined(name).span(self.register(span)));
+                    }
+                }
+                Rule::define_param => {
+                    let ident = pair.into_inner().next().unwrap();
+                    params.push(self.allocate(ident.as_str()));
+                }
+                Rule::stmt => {
+                    let body = self
+                        .expr_binary_from_pairs(pair.into_inner())?
+                        .span(self.register(span));
+                    self.functions
+                        .insert(mem::take(&mut name), UserFunction { params, body });
+                    return Ok(());
+                }
+                r => unreachable!("Unexpected rule {:?}", r),
+            }
+        }
+        unreachable!("function definition should have a body")
+    }
+
+    /// Expands a call to a function defined by the template.
+    ///
+    /// Every argument is evaluated into a fresh variable before evaluating the body, so the
+    /// arguments are evaluated exactly once and do not clobber the variables of other calls.
+    fn call_user_function(&mut self, name: &str, args: Vec<S<Expr>>, span: Span) -> Result<Expr, S<Error>> {
+        let function = &self.functions[name];
+        if function.params.len() != args.len() {
+            return Err(Error::InvalidArguments(format!(
+                "{name}() requires exactly {} arguments, but {} are given",
+                function.params.len(),
+                args.len()
+            ))
+            .span(span));
+        }
+        let params = function.params.clone();
+        let mut body = function.body.clone();
+        if params.is_empty() {
+            return Ok(body.inner);
+        }
+
+        let mut remap = HashMap::with_capacity(params.len());
+        let mut stmts = Vec::with_capacity(params.len() + 1);
+        for (param, arg) in params.into_iter().zip(args) {
+            let fresh = self.allocate_hidden();
+            remap.insert(param, fresh);
+            let arg_span = arg.span;
+            stmts.push(Expr::SetVariable(fresh, Box::new(arg)).span(arg_span));
+        }
+        remap_variables(&mut body.inner, &remap);
+        stmts.push(body);
+        Ok(Expr::Function {
+            function: &functions::ops::Last,
+            args: stmts,
+        })
+    }
+
+    /// Parses a combination directive into the variables and the lists to enumerate.
+    fn combination_directive_from_pairs(&mut self, pairs: Pairs<'_, Rule>) -> Result<Vec<(usize, S<Expr>)>, S<Error>> {
+        let mut res = Vec::new();
+        for pair in pairs {
+            match pair.as_rule() {
+                Rule::kw_for | Rule::kw_each | Rule::kw_combinations | Rule::kw_of | Rule::op_comma => {}
+                Rule::combination_list => {
+                    let mut index = 0;
+                    for pair in pair.into_inner() {
+                        let span = pair.as_span();
+                        match pair.as_rule() {
+                            Rule::ident => index = self.allocate(pair.as_str()),
+                            Rule::kw_in => {}
+                            Rule::expr => {
+                                let list = self.expr_from_pairs(pair.into_inner())?.span(self.register(span));
+                                res.push((index, list));
+                            }
+                            r => unreachable!("Unexpected rule {:?}", r),
+                        }
+                    }
+                }
+                r => unreachable!("Unexpected rule {:?}", r),
+            }
+        }
+        Ok(res)
+    }
+
+    /// Parses a shard directive into the number of physical tables and the routing expression.
+    fn shard_directive_from_pairs(&mut self, pairs: Pairs<'_, Rule>) -> Result<(u32, S<Expr>), S<Error>> {
+        let mut count = 0;
+        let mut key = None;
+        for pair in pairs {
+            let span = pair.as_span();
+            match pair.as_rule() {
+                Rule::kw_shard | Rule::kw_into | Rule::kw_tables | Rule::kw_by => {}
+                Rule::number => {
+                    count = pair
+                        .as_str()
+                        .parse()
+                        .ok()
+                        .filter(|c| *c > 0)
+                        .ok_or(Error::InvalidShardDirective(
+                            "the number of tables must be a positive integer",
+                        ))
+                        .span_err(self.register(span))?;
+                }
+                Rule::expr => key = Some(self.expr_from_pairs(pair.into_inner())?.span(self.register(span))),
+                r => unreachable!("Unexpected rule {:?}", r),
+            }
+        }
+        Ok((count, key.expect("shard directive must have an expression")))
+    }
+
     /// Creates a statement expression `a; b; c`.
     fn stmt_from_pairs(&mut self, mut pairs: Pairs<'_, Rule>) -> Result<Expr, S<Error>> {
         self.expr_binary_from_pairs(pairs.next().unwrap().into_inner())
@@ -550,11 +1269,14 @@ impl<'a> Allocator<'a> {
             Rule::kw_rownum => Expr::RowNum,
             Rule::kw_subrownum => Expr::SubRowNum,
             Rule::kw_current_timestamp => Expr::CurrentTimestamp,
+            Rule::kw_scale_factor => Expr::ScaleFactor,
             Rule::kw_null => Expr::Value(Value::Null),
             Rule::kw_true => Expr::Value(true.into()),
             Rule::kw_false => Expr::Value(false.into()),
             Rule::expr_group => self.expr_group_from_pairs(pair.into_inner())?,
             Rule::expr_timestamp => self.expr_timestamp_from_pairs(pair.into_inner())?,
+            Rule::expr_date => self.expr_typed_literal_from_pairs(pair.into_inner(), &functions::time::Date)?,
+            Rule::expr_time => self.expr_typed_literal_from_pairs(pair.into_inner(), &functions::time::Time)?,
             Rule::expr_interval => self.expr_interval_from_pairs(pair.into_inner())?,
             Rule::expr_hex => self.expr_hex_from_pairs(pair.into_inner())?,
             Rule::expr_get_variable => self.expr_get_variable_from_pairs(pair.into_inner())?,
@@ -562,6 +1284,8 @@ impl<'a> Allocator<'a> {
             Rule::expr_function => self.expr_function_from_pairs(pair.into_inner())?,
             Rule::expr_substring_function => self.expr_substring_from_pairs(pair.into_inner())?,
             Rule::expr_overlay_function => self.expr_overlay_from_pairs(pair.into_inner())?,
+            Rule::expr_extract_function => self.expr_extract_from_pairs(pair.into_inner())?,
+            Rule::expr_cast_function => self.expr_cast_from_pairs(pair.into_inner())?,
             Rule::expr_case_value_when => self.expr_case_value_when_from_pairs(pair.into_inner())?,
 
             Rule::number => match parse_number(pair.as_str()) {
@@ -582,6 +1306,8 @@ impl<'a> Allocator<'a> {
     /// Creates a function call expression `x.y.z(a, b, c)`.
     fn expr_function_from_pairs(&mut self, pairs: Pairs<'_, Rule>) -> Result<Expr, S<Error>> {
         let mut function: &dyn Function = &functions::ops::Last;
+        let mut pinned_span = None;
+        let mut user_function = None;
         let mut args = Vec::new();
 
         for pair in pairs {
@@ -589,6 +1315,14 @@ impl<'a> Allocator<'a> {
             match pair.as_rule() {
                 Rule::qname => {
                     let q_name = QName::from_pairs(pair.into_inner(), [None; 2]);
+                    if q_name.unique_name() == "pinned" {
+                        pinned_span = Some(span);
+                        continue;
+                    }
+                    if self.functions.contains_key(q_name.unique_name()) {
+                        user_function = Some((q_name.unique_name().to_owned(), self.register(span)));
+                        continue;
+                    }
                     match function_from_name(q_name.unique_name()) {
                         Ok(f) => function = f,
                         Err(e) => return Err(e.span(self.register(span))),
@@ -599,6 +1333,19 @@ impl<'a> Allocator<'a> {
             }
         }
 
+        if let Some(span) = pinned_span {
+            let [namespace, inner]: [S<Expr>; 2] = args.try_into().map_err(|_| {
+                Error::InvalidArguments("pinned() requires exactly 2 arguments".to_owned()).span(self.register(span))
+            })?;
+            return Ok(Expr::Pinned {
+                namespace: Box::new(namespace),
+                inner: Box::new(inner),
+            });
+        }
+        if let Some((name, span)) = user_function {
+            return self.call_user_function(&name, args, span);
+        }
+
         Ok(Expr::Function { function, args })
     }
 
@@ -735,6 +1482,22 @@ impl<'a> Allocator<'a> {
         unreachable!("Pairs exhausted without finding the inner expression");
     }
 
+    /// Creates a `DATE` or `TIME` expression.
+    fn expr_typed_literal_from_pairs(
+        &mut self,
+        mut pairs: Pairs<'_, Rule>,
+        function: &'static dyn Function,
+    ) -> Result<Expr, S<Error>> {
+        let pair = pairs.nth(1).expect("DATE or TIME should be followed by an expression");
+        let span = pair.as_span();
+        Ok(Expr::Function {
+            function,
+            args: vec![self
+                .expr_primary_from_pairs(pair.into_inner())?
+                .span(self.register(span))],
+        })
+    }
+
     /// Creates an `X` (hex) expression.
     fn expr_hex_from_pairs(&mut self, pairs: Pairs<'_, Rule>) -> Result<Expr, S<Error>> {
         for pair in pairs {
@@ -862,6 +1625,92 @@ impl<'a> Allocator<'a> {
         }
         Ok(Expr::Function { function, args })
     }
+
+    /// Creates an `extract` function expression `extract(field from ts)`.
+    fn expr_extract_from_pairs(&mut self, pairs: Pairs<'_, Rule>) -> Result<Expr, S<Error>> {
+        let mut args = Vec::with_capacity(2);
+        for pair in pairs {
+            let span = pair.as_span();
+            match pair.as_rule() {
+                Rule::kw_extract | Rule::kw_from => {}
+                Rule::extract_field => {
+                    args.push(Expr::Value(pair.as_str().to_owned().into()).span(self.register(span)));
+                }
+                Rule::expr => {
+                    let expr = self.expr_from_pairs(pair.into_inner())?;
+                    args.push(expr.span(self.register(span)));
+                }
+                r => unreachable!("Unexpected rule {:?}", r),
+            }
+        }
+        Ok(Expr::Function {
+            function: &functions::time::Extract,
+            args,
+        })
+    }
+
+    /// Creates a `cast` function expression `cast(expr as type)`.
+    fn expr_cast_from_pairs(&mut self, pairs: Pairs<'_, Rule>) -> Result<Expr, S<Error>> {
+        let mut args = Vec::with_capacity(2);
+        for pair in pairs {
+            let span = pair.as_span();
+            match pair.as_rule() {
+                Rule::kw_cast | Rule::kw_as => {}
+                Rule::expr => {
+                    let expr = self.expr_from_pairs(pair.into_inner())?;
+                    args.push(expr.span(self.register(span)));
+                }
+                Rule::cast_type => {
+                    args.push(Expr::Value(pair.as_str().trim().to_owned().into()).span(self.register(span)));
+                }
+                r => unreachable!("Unexpected rule {:?}", r),
+            }
+        }
+        Ok(Expr::Function {
+            function: &functions::cast::Cast,
+            args,
+        })
+    }
+}
+
+/// Renames the local variables in the expression according to the map.
+fn remap_variables(expr: &mut Expr, map: &HashMap<usize, usize>) {
+    match expr {
+        Expr::RowNum | Expr::SubRowNum | Expr::CurrentTimestamp | Expr::ScaleFactor | Expr::Value(_) => {}
+        Expr::GetVariable(index) => {
+            if let Some(new_index) = map.get(index) {
+                *index = *new_index;
+            }
+        }
+        Expr::SetVariable(index, value) => {
+            if let Some(new_index) = map.get(index) {
+                *index = *new_index;
+            }
+            remap_variables(&mut value.inner, map);
+        }
+        Expr::Function { args, .. } => {
+            for arg in args {
+                remap_variables(&mut arg.inner, map);
+            }
+        }
+        Expr::CaseValueWhen {
+            value,
+            conditions,
+            otherwise,
+        } => {
+            for e in value.iter_mut().chain(otherwise) {
+                remap_variables(&mut e.inner, map);
+            }
+            for (condition, result) in conditions {
+                remap_variables(&mut condition.inner, map);
+                remap_variables(&mut result.inner, map);
+            }
+        }
+        Expr::Pinned { namespace, inner } => {
+            remap_variables(&mut namespace.inner, map);
+            remap_variables(&mut inner.inner, map);
+        }
+    }
 }
 
 /// Parses a number (integer or floating-point number) into a value.
@@ -877,41 +1726,277 @@ fn parse_number(input: &str) -> Result<Value, Error> {
     })
 }
 
+/// The functions which can be called by name.
+static NAMED_FUNCTIONS: &[(&str, &dyn Function)] = {
+    use functions::{array, cast, codec, debug, dirty, hash, json, lookup, ops, rand, sequence, string, time, unique};
+    &[
+        ("rand.regex", &rand::Regex),
+        ("rand.range", &rand::Range),
+        ("rand.range_inclusive", &rand::RangeInclusive),
+        ("rand.uniform", &rand::Uniform),
+        ("rand.uniform_inclusive", &rand::UniformInclusive),
+        ("rand.zipf", &rand::Zipf),
+        ("rand.latest", &rand::Latest),
+        ("rand.hotspot", &rand::Hotspot),
+        ("rand.log_normal", &rand::LogNormal),
+        ("rand.normal", &rand::Normal),
+        ("rand.exp", &rand::Exp),
+        ("rand.pareto", &rand::Pareto),
+        ("rand.multivariate_normal", &rand::MultivariateNormal),
+        ("rand.correlated_pair", &rand::CorrelatedPair),
+        ("rand.bool", &rand::Bool),
+        ("rand.poisson", &rand::Poisson),
+        ("rand.binomial", &rand::Binomial),
+        ("rand.zero_inflated_poisson", &rand::ZeroInflatedPoisson),
+        ("rand.neg_binomial", &rand::NegBinomial),
+        ("rand.categorical", &rand::Categorical),
+        ("rand.switch", &rand::Switch),
+        ("rand.by_key", &rand::ByKey),
+        ("rand.range_by_key", &rand::RangeByKey),
+        ("rand.uniform_by_key", &rand::UniformByKey),
+        ("rand.from_file", &rand::FromFile),
+        ("rand.markov", &rand::Markov),
+        ("rand.quota", &rand::Quota),
+        ("rand.permutation", &rand::Permutation),
+        ("rand.shared_permutation", &rand::SharedPermutation),
+        ("nearly_sorted", &rand::NearlySorted),
+        ("rand.from_histogram", &rand::FromHistogram),
+        ("rand.finite_f32", &rand::FiniteF32),
+        ("rand.finite_f64", &rand::FiniteF64),
+        ("rand.u31_timestamp", &rand::U31Timestamp),
+        ("rand.shuffle", &array::Shuffle),
+        ("rand.uuid", &rand::Uuid),
+        ("rand.bytes", &rand::Bytes),
+        ("rand.point_in_bbox", &rand::PointInBbox),
+        ("rand.point_near", &rand::PointNear),
+        ("rand.ulid", &rand::Ulid),
+        ("rand.parent_rownum", &rand::ParentRownum),
+        ("greatest", &ops::GREATEST),
+        ("least", &ops::LEAST),
+        ("round", &ops::Round),
+        ("decimal", &ops::Decimal),
+        ("cast", &cast::Cast),
+        ("div", &ops::Div),
+        ("mod", &ops::Mod),
+        ("char_length", &string::CharLength),
+        ("character_length", &string::CharLength),
+        ("octet_length", &string::OctetLength),
+        ("limit", &string::Limit),
+        ("raw", &string::Raw),
+        ("coalesce", &ops::Coalesce),
+        ("ifnull", &ops::IfNull),
+        ("nvl", &ops::IfNull),
+        ("nullif", &ops::NullIf),
+        ("row_checksum", &ops::RowChecksum),
+        ("generate_series", &array::GenerateSeries),
+        ("sequence", &sequence::Sequence),
+        ("unique.tuple", &unique::Tuple),
+        ("lookup_file", &lookup::LookupFile),
+        ("ref", &lookup::Ref),
+        ("env", &lookup::Env),
+        ("debug.panic", &debug::Panic),
+        ("dirty.wrong_type", &dirty::Corruption::WrongType),
+        ("dirty.truncate", &dirty::Corruption::Truncate),
+        ("dirty.out_of_range", &dirty::Corruption::OutOfRange),
+        ("dirty.swap", &dirty::Corruption::Swap),
+        ("from_hex", &codec::DECODE_HEX),
+        ("to_hex", &codec::ENCODE_HEX),
+        ("from_base64", &codec::DECODE_BASE64),
+        ("from_base64url", &codec::DECODE_BASE64),
+        ("to_base64", &codec::ENCODE_BASE64),
+        ("to_base64url", &codec::ENCODE_BASE64URL),
+        ("inet_aton", &codec::InetAton),
+        ("float_to_bits", &codec::FloatToBits),
+        ("bits_to_float", &codec::BitsToFloat),
+        ("int_to_hex", &codec::IntToHex),
+        ("md5", &hash::Hash::Md5),
+        ("sha1", &hash::Hash::Sha1),
+        ("sha256", &hash::Hash::Sha256),
+        ("crc32", &hash::Hash::Crc32),
+        ("fnv64", &hash::Hash::Fnv64),
+        ("json.object", &json::Object),
+        ("json.array", &json::Array),
+        ("json.parse", &json::Parse),
+        ("date_trunc", &time::DateTrunc),
+        ("date_add", &time::DateAdd),
+        ("extract", &time::Extract),
+        ("last_day", &time::LastDay),
+        ("format_timestamp", &time::FormatTimestamp),
+        ("parse_timestamp", &time::ParseTimestamp),
+        ("clock.tick", &time::ClockTick),
+        ("clock.now", &time::ClockNow),
+    ]
+};
+
+/// The functions registered by [`register_function()`].
+static REGISTERED_FUNCTIONS: RwLock<Vec<(&str, &dyn Function)>> = RwLock::new(Vec::new());
+
+/// Registers a function which can be called by name, in addition to the built-in functions.
+///
+/// This allows programs embedding `dbgen` (and plugins loaded by the CLI) to provide
+/// domain-specific functions. The name is case-insensitive. The registration lasts until the
+/// process exits, and affects all templates parsed afterwards.
+///
+/// The [`Debug`](std::fmt::Debug) output of the function identifies it in the template cache, so a
+/// function with the same debug output as an existing function is rejected like a function with an
+/// existing name.
+pub fn register_function(name: &'static str, function: &'static dyn Function) -> Result<(), Error> {
+    if name == "pinned" || function_from_name(name).is_ok() || function_name(function).is_some() {
+        return Err(Error::FunctionAlreadyDefined(name.to_owned()));
+    }
+    REGISTERED_FUNCTIONS.write().unwrap().push((name, function));
+    Ok(())
+}
+
 /// Obtains a function from its name.
 fn function_from_name(name: &str) -> Result<&'static dyn Function, Error> {
-    use functions::{array, codec, debug, ops, rand, string};
-
-    Ok(match name {
-        "rand.regex" => &rand::Regex,
-        "rand.range" => &rand::Range,
-        "rand.range_inclusive" => &rand::RangeInclusive,
-        "rand.uniform" => &rand::Uniform,
-        "rand.uniform_inclusive" => &rand::UniformInclusive,
-        "rand.zipf" => &rand::Zipf,
-        "rand.log_normal" => &rand::LogNormal,
-        "rand.bool" => &rand::Bool,
-        "rand.finite_f32" => &rand::FiniteF32,
-        "rand.finite_f64" => &rand::FiniteF64,
-        "rand.u31_timestamp" => &rand::U31Timestamp,
-        "rand.shuffle" => &array::Shuffle,
-        "rand.uuid" => &rand::Uuid,
-        "greatest" => &ops::GREATEST,
-        "least" => &ops::LEAST,
-        "round" => &ops::Round,
-        "div" => &ops::Div,
-        "mod" => &ops::Mod,
-        "char_length" | "character_length" => &string::CharLength,
-        "octet_length" => &string::OctetLength,
-        "coalesce" => &ops::Coalesce,
-        "generate_series" => &array::GenerateSeries,
-        "debug.panic" => &debug::Panic,
-        "from_hex" => &codec::DECODE_HEX,
-        "to_hex" => &codec::ENCODE_HEX,
-        "from_base64" | "from_base64url" => &codec::DECODE_BASE64,
-        "to_base64" => &codec::ENCODE_BASE64,
-        "to_base64url" => &codec::ENCODE_BASE64URL,
-        _ => return Err(Error::UnknownFunction),
-    })
+    NAMED_FUNCTIONS
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, function)| *function)
+        .or_else(|| {
+            REGISTERED_FUNCTIONS
+                .read()
+                .unwrap()
+                .iter()
+                .find(|(n, _)| n.eq_ignore_ascii_case(name))
+                .map(|(_, function)| *function)
+        })
+        .ok_or(Error::UnknownFunction)
+}
+
+/// Obtains the name of a function which can be called by name.
+pub fn function_name(function: &dyn Function) -> Option<&'static str> {
+    let debug = format!("{function:?}");
+    let registered = REGISTERED_FUNCTIONS.read().unwrap();
+    NAMED_FUNCTIONS
+        .iter()
+        .chain(registered.iter())
+        .find(|(_, f)| format!("{f:?}") == debug)
+        .map(|(name, _)| *name)
+}
+
+/// The functions which are called by operators and special syntaxes.
+static OPERATOR_FUNCTIONS: &[&dyn Function] = {
+    use functions::{
+        array, ops,
+        string::{self, Overlay, Substring, Unit},
+        time,
+    };
+    &[
+        &ops::LT,
+        &ops::EQ,
+        &ops::GT,
+        &ops::LE,
+        &ops::NE,
+        &ops::GE,
+        &ops::Arith::Add,
+        &ops::Arith::Sub,
+        &ops::Arith::Mul,
+        &ops::Arith::FloatDiv,
+        &ops::Last,
+        &string::Concat,
+        &ops::IS,
+        &ops::IS_NOT,
+        &ops::AND,
+        &ops::OR,
+        &ops::Bitwise::And,
+        &ops::Bitwise::Or,
+        &ops::Bitwise::Xor,
+        &ops::Not,
+        &ops::Neg,
+        &ops::BitNot,
+        &array::ArrayConstructor,
+        &array::Subscript,
+        &time::Timestamp,
+        &time::Date,
+        &time::Time,
+        &Substring(Unit::Characters),
+        &Substring(Unit::Octets),
+        &Overlay(Unit::Characters),
+        &Overlay(Unit::Octets),
+    ]
+};
+
+/// Serializes functions by their debug representation, which [`register_function()`] keeps unique
+/// among all functions.
+mod serde_function {
+    use super::{Function, NAMED_FUNCTIONS, OPERATOR_FUNCTIONS, REGISTERED_FUNCTIONS};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    use std::{collections::HashMap, sync::OnceLock};
+
+    pub(super) fn serialize<S: Serializer>(function: &&'static dyn Function, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&format_args!("{function:?}"))
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<&'static dyn Function, D::Error> {
+        static FUNCTIONS: OnceLock<HashMap<String, &'static dyn Function>> = OnceLock::new();
+        let name = String::deserialize(deserializer)?;
+        FUNCTIONS
+            .get_or_init(|| {
+                NAMED_FUNCTIONS
+                    .iter()
+                    .map(|(_, function)| *function)
+                    .chain(OPERATOR_FUNCTIONS.iter().copied())
+                    .map(|function| (format!("{function:?}"), function))
+                    .collect()
+            })
+            .get(&name)
+            .copied()
+            .or_else(|| {
+                REGISTERED_FUNCTIONS
+                    .read()
+                    .unwrap()
+                    .iter()
+                    .map(|(_, function)| *function)
+                    .find(|function| format!("{function:?}") == name)
+            })
+            .ok_or_else(|| D::Error::custom(format!("unknown function {name}")))
+    }
+}
+
+/// Serializes the constant values appearing in a template.
+mod serde_literal {
+    use crate::{number::Number, value::Value};
+    use chrono::{NaiveDate, NaiveTime};
+    use serde::{ser::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    enum Literal {
+        Null,
+        Number(Number),
+        Bytes(Vec<u8>),
+        Interval(i64),
+        Date(NaiveDate),
+        Time(NaiveTime),
+        Raw(Vec<u8>),
+    }
+
+    pub(super) fn serialize<S: Serializer>(value: &Value, serializer: S) -> Result<S::Ok, S::Error> {
+        let literal = match value {
+            Value::Null => Literal::Null,
+            Value::Number(n) => Literal::Number(*n),
+            Value::Bytes(b) => Literal::Bytes(b.as_bytes().to_vec()),
+            Value::Interval(i) => Literal::Interval(*i),
+            Value::Date(d) => Literal::Date(*d),
+            Value::Time(t) => Literal::Time(*t),
+            Value::Raw(r) => Literal::Raw(r.as_bytes().to_vec()),
+            _ => return Err(S::Error::custom(format!("cannot serialize constant {value}"))),
+        };
+        literal.serialize(serializer)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Value, D::Error> {
+        Ok(match Literal::deserialize(deserializer)? {
+            Literal::Null => Value::Null,
+            Literal::Number(n) => Value::Number(n),
+            Literal::Bytes(b) => b.into(),
+            Literal::Interval(i) => Value::Interval(i),
+            Literal::Date(d) => Value::Date(d),
+            Literal::Time(t) => Value::Time(t),
+            Literal::Raw(r) => Value::Raw(r.into()),
+        })
+    }
 }
 
 /// Obtains a function from the parser rule.
@@ -958,9 +2043,36 @@ fn test_parse_template_error() {
         "create table a (); {{ for each row of a generate 1 row of c }} create table b ();",
         "create table a (); {{ for each row of b generate 1 row of a }} create table b ();",
         "create table a (); {{ for each row of a generate (*) rows of b }} create table b ();",
+        "create table a (x int {{ 1 }} {{ version 2 adds column }});",
+        "create table a ({{ version 0 adds column }} x int {{ 1 }});",
+        "create table a ({{ version 1.5 adds column }} x int {{ 1 }});",
+        "create table a ({{ version 2 drops column }} {{ version 3 adds column }} x int {{ 1 }});",
+        "create table a (x int {{ 1 }} /*#dbgen null=0.5 */);",
+        "create table a (x int /*#dbgen null=2 */ {{ 1 }});",
+        "create table a (x int /*#dbgen null=x */ {{ 1 }});",
+        "create table a (x int default nextval('s') /*#dbgen default=0.5 */ {{ 1 }});",
+        "/*#dbgen null=0.5 */ create table a (x int {{ 1 }});",
+        "create table a (x int {{ 1 }}) /*#dbgen null=0.5 */;",
     ];
     for tc in &test_cases {
-        let res = Template::parse(tc, &[], None, &mut registry);
+        let res = Template::parse(tc, &[], &[], &ParseOptions::default(), &mut registry);
         assert!(res.is_err(), "unexpected for case {}:\n{:#?}", tc, res);
     }
 }
+
+#[test]
+fn test_serde_template() {
+    let mut registry = Registry::default();
+    let input = "create table a (
+        {{ @x := rand.range(1, 10) + 2 * 3 - 4 / 5 }}
+        {{ NOT (1 < 2 AND 3 >= 4 OR 5 IS NOT NULL) }}
+        {{ -@x || ~(@x & 1 | 2 ^ 3) || 'text' || X'00ff' }}
+        {{ substring('abc' FROM 2 USING OCTETS) || overlay('abc' PLACING 'x' FROM 2) }}
+        {{ ARRAY[1.5, 2][1]; TIMESTAMP '2020-01-01 00:00:00' + INTERVAL 3 DAY }}
+        {{ CASE @x WHEN 1 THEN TRUE ELSE FALSE END; pinned('ns', rand.uuid()) }}
+    );";
+    let template = Template::parse(input, &[], &[], &ParseOptions::default(), &mut registry).unwrap();
+    let json = serde_json::to_string(&template).unwrap();
+    let deserialized = serde_json::from_str::<Template>(&json).unwrap();
+    assert_eq!(serde_json::to_string(&deserialized).unwrap(), json);
+}
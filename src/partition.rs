@@ -0,0 +1,535 @@
+//! Restricting generated rows to selected partitions of `PARTITION BY RANGE` and
+//! `PARTITION BY LIST` tables.
+
+use crate::{error::Error, eval::Schema, value::Value};
+use std::{
+    cmp::Ordering,
+    iter::{once, Peekable},
+    mem,
+    str::CharIndices,
+};
+
+/// A token of the table content.
+#[derive(Debug, Clone, PartialEq)]
+enum Token<'a> {
+    /// An unquoted identifier, keyword or number.
+    Word(&'a str),
+    /// A quoted identifier.
+    QuotedIdent(String),
+    /// A string literal.
+    Str(String),
+    /// Any other single character.
+    Punct(char),
+}
+
+impl Token<'_> {
+    /// Checks if this token is the keyword.
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self, Self::Word(w) if w.eq_ignore_ascii_case(keyword))
+    }
+
+    /// Extracts the identifier, with quotation marks removed.
+    fn ident(&self) -> Option<&str> {
+        match self {
+            Self::Word(w) => Some(*w),
+            Self::QuotedIdent(w) => Some(w.as_str()),
+            _ => None,
+        }
+    }
+}
+
+/// Reads the content between the quotation marks, where the quotation mark is escaped by doubling.
+fn read_quoted(chars: &mut Peekable<CharIndices<'_>>, quote: char) -> Result<String, String> {
+    let mut res = String::new();
+    loop {
+        match chars.next() {
+            Some((_, c)) if c == quote => {
+                if chars.peek().map(|(_, c)| *c) == Some(quote) {
+                    chars.next();
+                    res.push(quote);
+                } else {
+                    return Ok(res);
+                }
+            }
+            Some((_, '\\')) if quote == '\'' => {
+                if let Some((_, c)) = chars.next() {
+                    res.push(c);
+                }
+            }
+            Some((_, c)) => res.push(c),
+            None => return Err(format!("unterminated {quote}")),
+        }
+    }
+}
+
+/// Splits the table content into tokens.
+fn tokenize(input: &str) -> Result<Vec<Token<'_>>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '\'' => tokens.push(Token::Str(read_quoted(&mut chars, '\'')?)),
+            '"' | '`' => tokens.push(Token::QuotedIdent(read_quoted(&mut chars, c)?)),
+            c if c.is_alphanumeric() || c == '_' || c == '.' || c == '$' => {
+                let mut end = start + c.len_utf8();
+                while let Some(&(i, c)) = chars.peek() {
+                    if !(c.is_alphanumeric() || c == '_' || c == '.' || c == '$') {
+                        break;
+                    }
+                    end = i + c.len_utf8();
+                    chars.next();
+                }
+                tokens.push(Token::Word(&input[start..end]));
+            }
+            c => tokens.push(Token::Punct(c)),
+        }
+    }
+    Ok(tokens)
+}
+
+/// The values allowed in a partition.
+#[derive(Debug, Clone)]
+enum PartitionValues {
+    /// `VALUES LESS THAN (…)`, with the lower bound taken from the previous partition. The bounds
+    /// are `None` if unbounded.
+    Range { lower: Option<Value>, upper: Option<Value> },
+    /// `VALUES IN (…)`.
+    List(Vec<Value>),
+}
+
+/// Compares a generated value with a partition bound.
+///
+/// Timestamps, dates and times are converted to strings when compared with string bounds, as used
+/// in `PARTITION BY RANGE COLUMNS`.
+fn compare(value: &Value, bound: &Value) -> Result<Option<Ordering>, Error> {
+    if let (Value::Timestamp(..) | Value::Date(_) | Value::Time(_), Value::Bytes(_)) = (value, bound) {
+        Value::sql_concat(once(value))?.sql_cmp(bound)
+    } else {
+        value.sql_cmp(bound)
+    }
+}
+
+impl PartitionValues {
+    /// Checks if the value belongs to this partition.
+    fn contains(&self, value: &Value) -> Result<bool, Error> {
+        Ok(match self {
+            // NULL is considered smaller than any values in range partitioning.
+            Self::Range { lower, .. } if *value == Value::Null => lower.is_none(),
+            Self::Range { lower, upper } => {
+                let above_lower = match lower {
+                    Some(lower) => compare(value, lower)? != Some(Ordering::Less),
+                    None => true,
+                };
+                let below_upper = match upper {
+                    Some(upper) => compare(value, upper)? == Some(Ordering::Less),
+                    None => true,
+                };
+                above_lower && below_upper
+            }
+            Self::List(values) => {
+                for v in values {
+                    let matched = if *value == Value::Null || *v == Value::Null {
+                        *value == *v
+                    } else {
+                        compare(value, v)? == Some(Ordering::Equal)
+                    };
+                    if matched {
+                        return Ok(true);
+                    }
+                }
+                false
+            }
+        })
+    }
+}
+
+/// Parser of the `PARTITION BY` clause.
+struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// Returns the current token.
+    fn peek(&self) -> Option<&Token<'a>> {
+        self.tokens.get(self.pos)
+    }
+
+    /// Returns the current token and moves to the next one.
+    fn advance(&mut self) -> Option<&Token<'a>> {
+        self.pos += 1;
+        self.tokens.get(self.pos - 1)
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), String> {
+        if self.advance().is_some_and(|t| t.is_keyword(keyword)) {
+            Ok(())
+        } else {
+            Err(format!("expecting {keyword}"))
+        }
+    }
+
+    fn expect_punct(&mut self, punct: char) -> Result<(), String> {
+        if self.advance() == Some(&Token::Punct(punct)) {
+            Ok(())
+        } else {
+            Err(format!("expecting '{punct}'"))
+        }
+    }
+
+    fn skip_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.peek().is_some_and(|t| t.is_keyword(keyword));
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    /// Moves to the start of the `PARTITION BY` clause after the column definitions. Returns
+    /// whether the clause is found.
+    fn seek_partition_by(&mut self) -> bool {
+        let mut depth = 0_usize;
+        let mut seen_columns = false;
+        while let Some(token) = self.advance() {
+            match token {
+                Token::Punct('(') => depth += 1,
+                Token::Punct(')') => {
+                    depth = depth.saturating_sub(1);
+                    seen_columns = true;
+                }
+                t if depth == 0 && seen_columns && t.is_keyword("PARTITION") => {
+                    if self.skip_keyword("BY") {
+                        return true;
+                    }
+                }
+                _ => {}
+            }
+        }
+        false
+    }
+
+    /// Skips to the next `,` or `)` not enclosed by parenthesis.
+    fn skip_options(&mut self) {
+        let mut depth = 0_usize;
+        while let Some(token) = self.peek() {
+            match token {
+                Token::Punct(',' | ')') if depth == 0 => return,
+                Token::Punct('(') => depth += 1,
+                Token::Punct(')') => depth -= 1,
+                _ => {}
+            }
+            self.pos += 1;
+        }
+    }
+
+    /// Parses a literal value, returning `None` for `MAXVALUE`.
+    fn literal(&mut self) -> Result<Option<Value>, String> {
+        let negate = self.peek() == Some(&Token::Punct('-'));
+        if negate {
+            self.pos += 1;
+        }
+        let value = match self.advance().ok_or("expecting a value")? {
+            Token::Str(s) if !negate => s.clone().into(),
+            t if t.is_keyword("MAXVALUE") && !negate => return Ok(None),
+            t if t.is_keyword("NULL") && !negate => Value::Null,
+            Token::Word(w) => {
+                let w = *w;
+                if let Ok(n) = w.parse::<i64>() {
+                    (if negate { -n } else { n }).into()
+                } else if let (Ok(n), false) = (w.parse::<u64>(), negate) {
+                    n.into()
+                } else if let Some(n) = w.parse::<f64>().ok().filter(|n| n.is_finite()) {
+                    Value::from_finite_f64(if negate { -n } else { n })
+                } else {
+                    return Err(format!("unsupported partition value {w}"));
+                }
+            }
+            t => return Err(format!("unsupported partition value {t:?}")),
+        };
+        Ok(Some(value))
+    }
+
+    /// Parses the `PARTITION BY` clause, returning the partition key column and the partitions.
+    fn partition_by(&mut self) -> Result<(String, Vec<(String, PartitionValues)>), String> {
+        self.skip_keyword("LINEAR");
+        let is_range = if self.skip_keyword("RANGE") {
+            true
+        } else if self.skip_keyword("LIST") {
+            false
+        } else {
+            return Err("only RANGE and LIST partitioning are supported".to_owned());
+        };
+        self.skip_keyword("COLUMNS");
+        self.expect_punct('(')?;
+        let column = self
+            .advance()
+            .and_then(Token::ident)
+            .ok_or("the partition key must be a single column")?
+            .to_owned();
+        self.expect_punct(')')
+            .map_err(|_| "the partition key must be a single column")?;
+
+        // skip `PARTITIONS n` and similar options until the partition definitions.
+        while self.peek().is_some_and(|t| *t != Token::Punct('(')) {
+            self.pos += 1;
+        }
+        self.expect_punct('(')?;
+
+        let mut partitions = Vec::new();
+        let mut lower = None;
+        loop {
+            self.expect_keyword("PARTITION")?;
+            let name = self
+                .advance()
+                .and_then(Token::ident)
+                .ok_or("expecting partition name")?
+                .to_owned();
+            self.expect_keyword("VALUES")?;
+            let values = if is_range {
+                self.expect_keyword("LESS")?;
+                self.expect_keyword("THAN")?;
+                let has_paren = self.peek() == Some(&Token::Punct('('));
+                if has_paren {
+                    self.pos += 1;
+                }
+                let upper = self.literal()?;
+                if has_paren {
+                    self.expect_punct(')')?;
+                }
+                PartitionValues::Range {
+                    lower: mem::replace(&mut lower, upper.clone()),
+                    upper,
+                }
+            } else {
+                self.expect_keyword("IN")?;
+                self.expect_punct('(')?;
+                let mut values = Vec::new();
+                loop {
+                    values.push(self.literal()?.ok_or("MAXVALUE is not allowed in LIST partitions")?);
+                    if self.advance() != Some(&Token::Punct(',')) {
+                        self.pos -= 1;
+                        break;
+                    }
+                }
+                self.expect_punct(')')?;
+                PartitionValues::List(values)
+            };
+            partitions.push((name, values));
+
+            self.skip_options();
+            match self.advance() {
+                Some(Token::Punct(',')) => {}
+                Some(Token::Punct(')')) => break,
+                _ => return Err("expecting ',' or ')'".to_owned()),
+            }
+        }
+        Ok((column, partitions))
+    }
+}
+
+/// Removes the quotation marks around an identifier.
+fn unquote(ident: &str) -> &str {
+    let bytes = ident.as_bytes();
+    match (bytes.first(), bytes.last()) {
+        (Some(b'"'), Some(b'"')) | (Some(b'`'), Some(b'`')) | (Some(b'['), Some(b']')) if ident.len() >= 2 => {
+            &ident[1..ident.len() - 1]
+        }
+        _ => ident,
+    }
+}
+
+/// Restricts the generated rows of a table to the selected partitions.
+#[derive(Debug, Clone)]
+pub struct PartitionFilter {
+    /// The index of the partition key column.
+    column_index: usize,
+    /// The selected partitions.
+    partitions: Vec<(String, PartitionValues)>,
+}
+
+impl PartitionFilter {
+    /// Creates a filter keeping only rows of the selected partitions of the table.
+    ///
+    /// Returns `None` if the table is not partitioned, or none of its partitions are selected.
+    pub fn new(schema: &Schema<'_>, selected: &[String]) -> Result<Option<Self>, Error> {
+        let invalid = |message: String| Error::InvalidPartition {
+            table: schema.name.to_owned(),
+            message,
+        };
+
+        let mut parser = Parser {
+            tokens: tokenize(schema.content).map_err(invalid)?,
+            pos: 0,
+        };
+        if !parser.seek_partition_by() {
+            return Ok(None);
+        }
+        let (column, mut partitions) = parser.partition_by().map_err(invalid)?;
+        partitions.retain(|(name, _)| selected.iter().any(|s| s.eq_ignore_ascii_case(name)));
+        if partitions.is_empty() {
+            return Ok(None);
+        }
+
+        let column_index = schema
+            .column_names()
+            .position(|name| unquote(name).eq_ignore_ascii_case(&column))
+            .ok_or_else(|| invalid(format!("partition key {column} is not a generated column")))?;
+        Ok(Some(Self {
+            column_index,
+            partitions,
+        }))
+    }
+
+    /// Returns the names of the selected partitions.
+    pub fn partition_names(&self) -> impl Iterator<Item = &str> + '_ {
+        self.partitions.iter().map(|(name, _)| &**name)
+    }
+
+    /// Checks if the row belongs to any of the selected partitions.
+    pub fn accepts(&self, row: &[Value]) -> Result<bool, Error> {
+        let Some(value) = row.get(self.column_index) else {
+            return Ok(true);
+        };
+        for (_, partition) in &self.partitions {
+            if partition.contains(value)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+fn filter_from_template(template: &str, selected: &[&str]) -> Result<Option<PartitionFilter>, Error> {
+    use crate::{
+        eval::CompileContext,
+        parser::{ParseOptions, Template},
+        span::Registry,
+    };
+
+    let mut registry = Registry::default();
+    let template = Template::parse(template, &[], &[], &ParseOptions::default(), &mut registry).unwrap();
+    let ctx = CompileContext::new(template.variables_count);
+    let table = ctx.compile_table(template.tables.into_iter().next().unwrap()).unwrap();
+    let selected = selected.iter().map(|s| (*s).to_owned()).collect::<Vec<_>>();
+    PartitionFilter::new(&table.schema(false), &selected)
+}
+
+#[test]
+fn test_parse_partition_by() {
+    let parse = |input: &str| {
+        let mut parser = Parser {
+            tokens: tokenize(input).unwrap(),
+            pos: 0,
+        };
+        assert!(parser.seek_partition_by());
+        parser.partition_by()
+    };
+
+    let (column, partitions) = parse(
+        "(id INT) PARTITION BY RANGE (`id`) (
+            PARTITION p0 VALUES LESS THAN (10),
+            PARTITION p1 VALUES LESS THAN MAXVALUE
+        )",
+    )
+    .unwrap();
+    assert_eq!(column, "id");
+    assert_eq!(partitions.len(), 2);
+    assert_eq!(partitions[0].0, "p0");
+    assert!(matches!(
+        &partitions[0].1,
+        PartitionValues::Range { lower: None, upper: Some(upper) } if *upper == Value::from(10)
+    ));
+    assert_eq!(partitions[1].0, "p1");
+    assert!(matches!(
+        &partitions[1].1,
+        PartitionValues::Range { lower: Some(lower), upper: None } if *lower == Value::from(10)
+    ));
+
+    let (column, partitions) = parse(
+        "(region INT) PARTITION BY LIST (region) (
+            PARTITION east VALUES IN (1, 2),
+            PARTITION \"west\" VALUES IN (-3, NULL)
+        )",
+    )
+    .unwrap();
+    assert_eq!(column, "region");
+    assert_eq!(partitions.len(), 2);
+    assert_eq!(partitions[0].0, "east");
+    assert!(matches!(&partitions[0].1, PartitionValues::List(v) if *v == [Value::from(1), Value::from(2)]));
+    assert_eq!(partitions[1].0, "west");
+    assert!(matches!(&partitions[1].1, PartitionValues::List(v) if *v == [Value::from(-3), Value::Null]));
+
+    assert!(parse("(a INT, b INT) PARTITION BY RANGE (a, b) (PARTITION p0 VALUES LESS THAN (1))").is_err());
+}
+
+#[test]
+fn test_range_partitions() {
+    let template = "CREATE TABLE t (
+        a INT {{ 1 }},
+        id INT NOT NULL {{ rownum }}
+    ) PARTITION BY RANGE (id) (
+        PARTITION p0 VALUES LESS THAN (10),
+        PARTITION p1 VALUES LESS THAN (20) COMMENT 'second',
+        PARTITION pmax VALUES LESS THAN MAXVALUE
+    );";
+
+    let filter = filter_from_template(template, &["P1", "other"]).unwrap().unwrap();
+    assert_eq!(filter.partition_names().collect::<Vec<_>>(), ["p1"]);
+    assert!(!filter.accepts(&[1.into(), 9.into()]).unwrap());
+    assert!(filter.accepts(&[1.into(), 10.into()]).unwrap());
+    assert!(filter.accepts(&[1.into(), 19.into()]).unwrap());
+    assert!(!filter.accepts(&[1.into(), 20.into()]).unwrap());
+    assert!(!filter.accepts(&[1.into(), Value::Null]).unwrap());
+
+    let filter = filter_from_template(template, &["p0", "pmax"]).unwrap().unwrap();
+    assert!(filter.accepts(&[1.into(), (-5).into()]).unwrap());
+    assert!(!filter.accepts(&[1.into(), 15.into()]).unwrap());
+    assert!(filter.accepts(&[1.into(), 1000.into()]).unwrap());
+    assert!(filter.accepts(&[1.into(), Value::Null]).unwrap());
+
+    assert!(filter_from_template(template, &["other"]).unwrap().is_none());
+}
+
+#[test]
+fn test_range_columns_partitions() {
+    let template = "CREATE TABLE t (
+        `created` DATETIME {{ TIMESTAMP '2024-01-01 00:00:00' }}
+    ) PARTITION BY RANGE COLUMNS (`created`) (
+        PARTITION p2023 VALUES LESS THAN ('2024-01-01'),
+        PARTITION p2024q1 VALUES LESS THAN ('2024-04-01')
+    );";
+    let filter = filter_from_template(template, &["p2024q1"]).unwrap().unwrap();
+    assert!(filter.accepts(&["2024-03-31 23:59:59".to_owned().into()]).unwrap());
+    assert!(!filter.accepts(&["2023-12-31 23:59:59".to_owned().into()]).unwrap());
+}
+
+#[test]
+fn test_list_partitions() {
+    let template = "CREATE TABLE t (
+        region INT {{ rand.range(0, 10) }}
+    ) PARTITION BY LIST (region) (
+        PARTITION east VALUES IN (1, 2, 3),
+        PARTITION west VALUES IN (4, 5, NULL)
+    );";
+    let filter = filter_from_template(template, &["west"]).unwrap().unwrap();
+    assert!(!filter.accepts(&[1.into()]).unwrap());
+    assert!(filter.accepts(&[5.into()]).unwrap());
+    assert!(filter.accepts(&[Value::Null]).unwrap());
+    assert!(!filter.accepts(&[9.into()]).unwrap());
+}
+
+#[test]
+fn test_unsupported_partitions() {
+    let template = "CREATE TABLE t (id INT {{ rownum }}) PARTITION BY HASH (id) PARTITIONS 4;";
+    assert!(filter_from_template(template, &["p0"]).is_err());
+
+    let template = "CREATE TABLE t (created DATE {{ rownum }}) PARTITION BY RANGE (YEAR(created)) (
+        PARTITION p0 VALUES LESS THAN (2024)
+    );";
+    assert!(filter_from_template(template, &["p0"]).is_err());
+
+    let template = "CREATE TABLE t (id INT {{ rownum }});";
+    assert!(filter_from_template(template, &["p0"]).unwrap().is_none());
+}
@@ -0,0 +1,64 @@
+//! Plugins providing extra functions from dynamic libraries.
+//!
+//! A plugin is a dynamic library (`cdylib`) built against the same version of the `dbgen` library
+//! using the same Rust compiler, which exports the entry point [`ENTRY_SYMBOL`] of type [`Entry`]:
+//!
+//! ```ignore
+//! use dbgen::functions::Function;
+//!
+//! #[no_mangle]
+//! pub fn dbgen_plugin_register(register: &mut dyn FnMut(&'static str, &'static dyn Function)) {
+//!     register("faker.vin", &Vin);
+//!     register("faker.iban", &Iban);
+//! }
+//! ```
+
+use crate::{error::Error, functions::Function, parser::register_function};
+use libloading::{Library, Symbol};
+use std::{
+    mem,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+/// The name of the entry point exported by plugins.
+pub const ENTRY_SYMBOL: &str = "dbgen_plugin_register";
+
+/// The type of the entry point exported by plugins, which calls `register` once for every function
+/// provided by the plugin.
+pub type Entry = fn(register: &mut dyn FnMut(&'static str, &'static dyn Function));
+
+/// The paths of the plugins loaded so far.
+static LOADED_PATHS: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Loads the plugin at the given path and registers its functions. Loading the same path again
+/// does nothing.
+///
+/// The library is never unloaded, since the registered functions are kept until the process exits.
+pub fn load(path: &Path) -> Result<(), Error> {
+    let mut loaded_paths = LOADED_PATHS.lock().unwrap();
+    if loaded_paths.iter().any(|p| p == path) {
+        return Ok(());
+    }
+    let map_err = |source| Error::LoadPlugin {
+        path: path.to_owned(),
+        source,
+    };
+
+    // SAFETY: loading the library runs its initialization routines, which we trust as much as the
+    // plugin itself.
+    let library = unsafe { Library::new(path) }.map_err(map_err)?;
+    let mut result = Ok(());
+    {
+        // SAFETY: plugins are required to export the entry point with the type `Entry`.
+        let entry: Symbol<'_, Entry> = unsafe { library.get(ENTRY_SYMBOL.as_bytes()) }.map_err(map_err)?;
+        entry(&mut |name, function| {
+            if result.is_ok() {
+                result = register_function(name, function);
+            }
+        });
+    }
+    mem::forget(library);
+    loaded_paths.push(path.to_owned());
+    result
+}
@@ -0,0 +1,140 @@
+//! Reporting the progress of data generation.
+
+use crate::warning::Warning;
+use serde_json::json;
+use std::{
+    io::{self, Write},
+    time::Duration,
+};
+
+/// The progress of a single file generator thread.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct FileProgress {
+    /// The file index, starting from 1.
+    pub file_index: u32,
+    /// Number of rows written.
+    pub rows: u64,
+    /// Number of bytes written.
+    pub bytes: u64,
+    /// Whether the thread has finished writing all files.
+    pub finished: bool,
+}
+
+/// A snapshot of the progress of data generation.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Progress {
+    /// Number of rows written.
+    pub rows: u64,
+    /// Total number of rows to write.
+    pub total_rows: u64,
+    /// Number of bytes written.
+    pub bytes: u64,
+    /// Time elapsed since the generation started.
+    pub elapsed: Duration,
+    /// The progress of every file generator thread.
+    pub files: Vec<FileProgress>,
+    /// The warnings about suspicious values found since the previous report. Every kind of problem
+    /// is only reported once per column.
+    pub warnings: Vec<Warning>,
+}
+
+impl Progress {
+    /// Estimates the remaining time assuming a constant speed, or `None` if nothing has been
+    /// written yet.
+    pub fn eta(&self) -> Option<Duration> {
+        if self.rows == 0 {
+            return None;
+        }
+        let remaining = self.total_rows.saturating_sub(self.rows);
+        // ALLOW_REASON: the row counts are far below 2^52.
+        #[allow(clippy::cast_precision_loss)]
+        let ratio = remaining as f64 / self.rows as f64;
+        Some(self.elapsed.mul_f64(ratio))
+    }
+}
+
+/// A receiver of the generation progress.
+pub trait ProgressSink: Send {
+    /// Reports the current progress. This is called periodically during generation.
+    fn update(&mut self, progress: &Progress);
+
+    /// Reports the final progress after all data are written.
+    fn finish(&mut self, progress: &Progress);
+}
+
+/// A [`ProgressSink`] writing every report as a line of JSON, for consumption by other programs.
+#[derive(Debug)]
+pub struct JsonLines<W>(pub W);
+
+impl<W: Write> JsonLines<W> {
+    /// Writes the progress as a line of JSON.
+    fn write_line(&mut self, progress: &Progress, finished: bool) -> io::Result<()> {
+        let files = progress
+            .files
+            .iter()
+            .map(|file| {
+                json!({
+                    "file_index": file.file_index,
+                    "rows": file.rows,
+                    "bytes": file.bytes,
+                    "finished": file.finished,
+                })
+            })
+            .collect::<Vec<_>>();
+        let warnings = progress
+            .warnings
+            .iter()
+            .map(|warning| {
+                json!({
+                    "kind": warning.kind.code(),
+                    "message": warning.to_string(),
+                    "table": warning.table,
+                    "column": warning.column,
+                    "row_num": warning.row_num,
+                    "value": warning.value,
+                })
+            })
+            .collect::<Vec<_>>();
+        let line = json!({
+            "rows": progress.rows,
+            "total_rows": progress.total_rows,
+            "bytes": progress.bytes,
+            "elapsed": progress.elapsed.as_secs_f64(),
+            "eta": progress.eta().map(|eta| eta.as_secs_f64()),
+            "finished": finished,
+            "files": files,
+            "warnings": warnings,
+        });
+        serde_json::to_writer(&mut self.0, &line)?;
+        self.0.write_all(b"\n")?;
+        self.0.flush()
+    }
+}
+
+impl<W: Write + Send> ProgressSink for JsonLines<W> {
+    fn update(&mut self, progress: &Progress) {
+        if let Err(e) = self.write_line(progress, false) {
+            eprintln!("Warning: cannot write progress: {e}");
+        }
+    }
+
+    fn finish(&mut self, progress: &Progress) {
+        if let Err(e) = self.write_line(progress, true) {
+            eprintln!("Warning: cannot write progress: {e}");
+        }
+    }
+}
+
+#[test]
+fn test_eta() {
+    let progress = Progress {
+        rows: 250,
+        total_rows: 1000,
+        bytes: 4000,
+        elapsed: Duration::from_secs(10),
+        files: Vec::new(),
+        warnings: Vec::new(),
+    };
+    assert_eq!(progress.eta(), Some(Duration::from_secs(30)));
+    assert_eq!(Progress::default().eta(), None);
+}
@@ -0,0 +1,423 @@
+//! High-level API for embedding the generator into other programs.
+//!
+//! The [`Generator`] takes care of parsing and compiling the template, evaluating the global
+//! expressions and seeding the random number generators the same way as the CLI, so that the
+//! same template and seed produce the same rows.
+//!
+//! ```
+//! use dbgen::{
+//!     runtime::{Generator, GeneratorOptions},
+//!     span::Registry,
+//!     value::Value,
+//! };
+//!
+//! let mut registry = Registry::default();
+//! let mut generator = Generator::new(
+//!     "CREATE TABLE t (a INT {{ rownum * 2 }});",
+//!     &GeneratorOptions::default(),
+//!     &mut registry,
+//! )
+//! .unwrap();
+//! let rows = generator.rows().take(3).map(|row| row.unwrap().values).collect::<Vec<_>>();
+//! assert_eq!(rows, [[Value::from(2)], [Value::from(4)], [Value::from(6)]]);
+//! ```
+
+use crate::{
+    error::Error,
+    eval::{CompileContext, Schema, State, Table},
+    parser::{ParseOptions, Template},
+    span::{Registry, ResultExt, S},
+    value::Value,
+    writer::{Env, Writer},
+};
+use chrono::{NaiveDateTime, Utc};
+use rand::{Rng, SeedableRng};
+use rand_hc::Hc128Rng;
+use std::{cell::RefCell, collections::VecDeque, path::PathBuf, ptr};
+
+/// Options for constructing a [`Generator`].
+#[derive(Clone, Debug)]
+pub struct GeneratorOptions {
+    /// The seed of the random number generators.
+    pub seed: [u8; 32],
+    /// The current timestamp in UTC, used by `current_timestamp`.
+    pub now: NaiveDateTime,
+    /// The time zone used to interpret strings into timestamps.
+    pub time_zone: String,
+    /// The zoneinfo directory where time zones other than UTC can be read.
+    pub zoneinfo: PathBuf,
+    /// Expressions initializing the global variables, as in the `--initialize` CLI parameter.
+    pub init_globals: Vec<String>,
+    /// Replacements of column expressions, as in the `--override` CLI parameter.
+    pub overrides: Vec<String>,
+    /// The condition a row of the main tables must satisfy to be generated, as in the `--where`
+    /// CLI parameter.
+    pub row_filter: Option<String>,
+    /// The key ordering the rows of every table, as in the `--order-by` CLI parameter. Only the
+    /// rows written by a single [`Generator::write_rows()`] call are sorted together, and
+    /// [`Generator::rows()`] ignores it.
+    pub order_by: Option<String>,
+    /// The table names and the keys ordering their rows, overriding `order_by`, as in the
+    /// `--table-order-by` CLI parameter.
+    pub table_order_by: Vec<(String, String)>,
+    /// The schema version to generate, as in the `--schema-version` CLI parameter.
+    pub schema_version: Option<u32>,
+    /// Whether to infer the expressions of tables without any, as in the `--infer-expressions`
+    /// CLI parameter.
+    pub infer_expressions: bool,
+    /// Overrides the schema name of all tables.
+    pub schema_name: Option<String>,
+    /// Whether the table names in the schemas are qualified.
+    pub qualified: bool,
+    /// Whether every row is generated from its own random number generator, as in the
+    /// `--stable-seeding` CLI parameter.
+    pub stable_seeding: bool,
+    /// The value of `scale_factor`, as in the `--scale-factor` CLI parameter.
+    pub scale_factor: f64,
+}
+
+impl Default for GeneratorOptions {
+    fn default() -> Self {
+        Self {
+            seed: [0; 32],
+            now: Utc::now().naive_utc(),
+            time_zone: "UTC".to_owned(),
+            zoneinfo: PathBuf::from("/usr/share/zoneinfo"),
+            init_globals: Vec::new(),
+            overrides: Vec::new(),
+            row_filter: None,
+            order_by: None,
+            table_order_by: Vec::new(),
+            schema_version: None,
+            infer_expressions: false,
+            schema_name: None,
+            qualified: false,
+            stable_seeding: false,
+            scale_factor: 1.0,
+        }
+    }
+}
+
+/// A row generated for a table.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GeneratedRow {
+    /// Index of the table in [`Generator::schemas()`] this row belongs to.
+    pub table: usize,
+    /// The values of the row.
+    pub values: Vec<Value>,
+}
+
+/// Generates rows from a template.
+#[derive(Debug)]
+pub struct Generator {
+    tables: Vec<Table>,
+    state: State,
+    qualified: bool,
+}
+
+impl Generator {
+    /// Parses and compiles the template, and evaluates its global expressions.
+    ///
+    /// The spans of errors can be described using the `span_registry`.
+    pub fn new(template: &str, options: &GeneratorOptions, span_registry: &mut Registry) -> Result<Self, S<Error>> {
+        let mut template = Template::parse(
+            template,
+            &options.init_globals,
+            &options.overrides,
+            &ParseOptions {
+                row_filter: options.row_filter.as_deref(),
+                order_by: options.order_by.as_deref(),
+                table_order_by: options
+                    .table_order_by
+                    .iter()
+                    .map(|(table_name, order_by)| (&**table_name, &**order_by))
+                    .collect(),
+                infer_expressions: options.infer_expressions,
+                override_schema: options.schema_name.as_deref(),
+            },
+            span_registry,
+        )?;
+        if let Some(version) = options.schema_version {
+            template.select_version(version)?;
+        }
+
+        let mut ctx = CompileContext::new(template.variables_count);
+        ctx.zoneinfo.clone_from(&options.zoneinfo);
+        ctx.time_zone = ctx.parse_time_zone(&options.time_zone).no_span_err()?;
+        ctx.current_timestamp = options.now;
+        ctx.namespace_seed = options.seed;
+        ctx.stable_seeding = options.stable_seeding;
+        ctx.scale_factor = options.scale_factor;
+        let mut tables = template
+            .tables
+            .into_iter()
+            .map(|t| ctx.compile_table(t))
+            .collect::<Result<Vec<_>, _>>()?;
+        if let Some(filter) = template.row_filter {
+            ctx.compile_row_filter(&mut tables, filter)?;
+        }
+        if let Some(order_by) = template.order_by {
+            ctx.compile_order_by(&mut tables, order_by)?;
+        }
+        for (table_name, order_by) in template.table_order_by {
+            ctx.compile_table_order_by(&mut tables, &table_name, order_by)?;
+        }
+
+        // we perform this double seeding to be compatible with the CLI.
+        let mut seeding_rng = Hc128Rng::from_seed(options.seed);
+        let mut rng = move || Box::new(Hc128Rng::from_seed(seeding_rng.gen()));
+
+        if !template.global_exprs.is_empty() {
+            let row_gen = ctx.compile_row(template.global_exprs)?;
+            let mut state = State::new(0, rng(), ctx);
+            row_gen.eval(&mut state)?;
+            ctx = state.into_compile_context();
+        }
+
+        Ok(Self {
+            tables,
+            state: State::new(1, rng(), ctx),
+            qualified: options.qualified,
+        })
+    }
+
+    /// Returns the schemas of all tables in the template.
+    pub fn schemas(&self) -> impl Iterator<Item = Schema<'_>> + '_ {
+        self.tables.iter().map(|table| table.schema(self.qualified))
+    }
+
+    /// Returns an endless iterator of generated rows.
+    ///
+    /// Every step of the generator produces one row from each root table, followed by the rows of
+    /// their derived tables.
+    pub fn rows(&mut self) -> Rows<'_> {
+        Rows {
+            generator: self,
+            buffer: RefCell::new(VecDeque::new()),
+        }
+    }
+
+    /// Writes the given number of rows from each root table into writers created by `new_writer`,
+    /// one per table, and returns the writers in the same order as [`Generator::schemas()`].
+    ///
+    /// Each call begins new files, i.e. the writers' file headers are written again. Returns an
+    /// error if any table is sharded, since every shard would need its own writer.
+    pub fn write_rows<W: Writer>(
+        &mut self,
+        rows: u64,
+        new_writer: impl FnMut(&Table) -> Result<W, S<Error>>,
+    ) -> Result<Vec<W>, S<Error>> {
+        let mut env = Env::new(&self.tables, &mut self.state, self.qualified, new_writer)?;
+        env.write_rows(rows)?;
+        env.write_trailer()?;
+        env.write_sorted_rows(|_| Ok(()))?;
+        Ok(env.into_writers())
+    }
+}
+
+/// Iterator of rows returned by [`Generator::rows()`].
+#[derive(Debug)]
+pub struct Rows<'g> {
+    generator: &'g mut Generator,
+    buffer: RefCell<VecDeque<GeneratedRow>>,
+}
+
+impl Iterator for Rows<'_> {
+    type Item = Result<GeneratedRow, S<Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.buffer.get_mut().is_empty() {
+            let generator = &mut *self.generator;
+            let tables = &generator.tables;
+            let buffer = &self.buffer;
+            // the rows of every shard are reported as rows of the sharded table.
+            let result = Env::with_partitions(
+                tables,
+                &mut generator.state,
+                generator.qualified,
+                None,
+                |table, _, _| {
+                    Ok(RowCollector {
+                        table: tables.iter().position(|t| ptr::eq(t, table)).unwrap_or_default(),
+                        buffer,
+                    })
+                },
+            )
+            .and_then(|mut env| {
+                env.write_row()?;
+                env.write_trailer()?;
+                // flushes the rows buffered for `order_by`.
+                env.write_sorted_rows(|_| Ok(()))
+            });
+            if let Err(e) = result {
+                return Some(Err(e));
+            }
+        }
+        self.buffer.get_mut().pop_front().map(Ok)
+    }
+}
+
+/// A [`Writer`] collecting the values into [`GeneratedRow`]s.
+struct RowCollector<'b> {
+    table: usize,
+    buffer: &'b RefCell<VecDeque<GeneratedRow>>,
+}
+
+impl Writer for RowCollector<'_> {
+    fn write_value(&mut self, value: &Value) -> Result<(), S<Error>> {
+        if let Some(row) = self.buffer.borrow_mut().back_mut() {
+            row.values.push(value.clone());
+        }
+        Ok(())
+    }
+
+    fn write_file_header(&mut self, _: &Schema<'_>) -> Result<(), S<Error>> {
+        Ok(())
+    }
+
+    fn write_header(&mut self, _: &Schema<'_>) -> Result<(), S<Error>> {
+        self.write_row_separator()
+    }
+
+    fn write_value_header(&mut self, _: &str) -> Result<(), S<Error>> {
+        Ok(())
+    }
+
+    fn write_value_separator(&mut self) -> Result<(), S<Error>> {
+        Ok(())
+    }
+
+    fn write_row_separator(&mut self) -> Result<(), S<Error>> {
+        self.buffer.borrow_mut().push_back(GeneratedRow {
+            table: self.table,
+            values: Vec::new(),
+        });
+        Ok(())
+    }
+
+    fn write_trailer(&mut self) -> Result<(), S<Error>> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        eval::C,
+        functions::{Arguments, Function},
+        parser::register_function,
+        span::Span,
+    };
+
+    const TEMPLATE: &str = "
+        CREATE TABLE parent (id INT {{ rownum }});
+        /*{{ for each row of parent generate 2 rows of child }}*/
+        CREATE TABLE child (parent_id INT {{ rownum }}, sub INT {{ subrownum }});
+    ";
+
+    #[test]
+    fn test_rows() {
+        let mut registry = Registry::default();
+        let mut generator = Generator::new(TEMPLATE, &GeneratorOptions::default(), &mut registry).unwrap();
+        let names = generator.schemas().map(|s| s.name.to_owned()).collect::<Vec<_>>();
+        assert_eq!(names, ["parent", "child"]);
+
+        let rows = generator.rows().take(4).collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(
+            rows,
+            [
+                GeneratedRow {
+                    table: 0,
+                    values: vec![1.into()],
+                },
+                GeneratedRow {
+                    table: 1,
+                    values: vec![1.into(), 1.into()],
+                },
+                GeneratedRow {
+                    table: 1,
+                    values: vec![1.into(), 2.into()],
+                },
+                GeneratedRow {
+                    table: 0,
+                    values: vec![2.into()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stable_seeding() {
+        fn first_column(template: &str, stable_seeding: bool) -> Vec<Value> {
+            let options = GeneratorOptions {
+                stable_seeding,
+                ..GeneratorOptions::default()
+            };
+            let mut registry = Registry::default();
+            let mut generator = Generator::new(template, &options, &mut registry).unwrap();
+            generator
+                .rows()
+                .take(10)
+                .map(|row| row.unwrap().values.swap_remove(0))
+                .collect()
+        }
+
+        let one = "CREATE TABLE t (a INT {{ rand.range(0, 1000000) }});";
+        let two = "CREATE TABLE t (a INT {{ rand.range(0, 1000000) }}, b INT {{ rand.range(0, 1000000) }});";
+        assert_ne!(first_column(one, false), first_column(two, false));
+        assert_eq!(first_column(one, true), first_column(two, true));
+    }
+
+    #[test]
+    fn test_order_by() {
+        let options = GeneratorOptions {
+            order_by: Some("-rownum".to_owned()),
+            ..GeneratorOptions::default()
+        };
+        let mut registry = Registry::default();
+        let template = "CREATE TABLE t (a INT {{ rownum }});";
+        let mut generator = Generator::new(template, &options, &mut registry).unwrap();
+
+        let buffer = RefCell::new(VecDeque::new());
+        generator
+            .write_rows(3, |_| {
+                Ok(RowCollector {
+                    table: 0,
+                    buffer: &buffer,
+                })
+            })
+            .unwrap();
+        let values = buffer
+            .into_inner()
+            .into_iter()
+            .map(|row| row.values)
+            .collect::<Vec<_>>();
+        assert_eq!(values, [[Value::from(3)], [Value::from(2)], [Value::from(1)]]);
+
+        let row = generator.rows().next().unwrap().unwrap();
+        assert_eq!(row.values, [Value::from(4)]);
+    }
+
+    #[derive(Debug)]
+    struct Answer;
+
+    impl Function for Answer {
+        fn compile(&self, _: &CompileContext, _: Span, _: Arguments) -> Result<C, S<Error>> {
+            Ok(C::Constant(42.into()))
+        }
+    }
+
+    #[test]
+    fn test_register_function() {
+        register_function("test.answer", &Answer).unwrap();
+        assert!(register_function("rand.range", &Answer).is_err());
+        assert!(register_function("test.answer2", &Answer).is_err());
+
+        let mut registry = Registry::default();
+        let template = "CREATE TABLE t (a INT {{ test.answer() }});";
+        let mut generator = Generator::new(template, &GeneratorOptions::default(), &mut registry).unwrap();
+        let row = generator.rows().next().unwrap().unwrap();
+        assert_eq!(row.values, [Value::from(42)]);
+    }
+}
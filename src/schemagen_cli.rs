@@ -11,12 +11,15 @@ use crate::{error::Error, parser::QName};
 use clap::{Parser, ValueEnum};
 use rand::{rngs::OsRng, seq::SliceRandom, Rng, RngCore};
 use rand_distr::{weighted_alias::WeightedAliasIndex, Distribution, Geometric, LogNormal, Pareto};
+use serde::Deserialize;
 use std::{
     collections::{BTreeSet, HashSet},
     f64::consts::LOG2_10,
     fmt::Write,
+    fs::read_to_string,
     iter::{once, repeat_with},
     mem::replace,
+    path::PathBuf,
     str::FromStr,
 };
 
@@ -52,6 +55,10 @@ pub struct Args {
     #[arg(long)]
     pub seed: Option<crate::cli::Seed>,
 
+    /// JSON file describing the mix of column types to generate.
+    #[arg(long, value_parser = Profile::from_path)]
+    pub profile: Option<Profile>,
+
     /// Additional arguments passed to every `dbgen` invocation
     #[arg(trailing_var_arg(true))]
     pub args: Vec<String>,
@@ -136,22 +143,27 @@ fn gen_int_column(dialect: Dialect, rng: &mut dyn RngCore) -> Column {
     };
     let neg_log2_prob = f64::from(bytes + 1) * 8.0;
 
-    let end = (max + 1) as f64;
-    let digits = end.log10().ceil();
-    let mut average_len = digits - (10_f64.powf(digits) - 10.0) / (9.0 * end);
-    if !unsigned {
-        average_len = average_len * 2.0 + 1.0;
-    }
-
     Column {
         ty,
         expr: format!("rand.range_inclusive({min}, {max})"),
         neg_log2_prob,
-        average_len,
+        average_len: int_average_len((max + 1) as f64, unsigned),
         nullable: false,
     }
 }
 
+/// Computes the estimated average formatted length of a random integer with absolute value
+/// below `end`.
+fn int_average_len(end: f64, unsigned: bool) -> f64 {
+    let digits = end.log10().ceil();
+    let average_len = digits - (10_f64.powf(digits) - 10.0) / (9.0 * end);
+    if unsigned {
+        average_len
+    } else {
+        average_len * 2.0 + 1.0
+    }
+}
+
 fn gen_serial_column(dialect: Dialect, _: &mut dyn RngCore) -> Column {
     let ty = match dialect {
         Dialect::MySQL => "bigint unsigned not null",
@@ -275,6 +287,41 @@ fn gen_float_column(dialect: Dialect, rng: &mut dyn RngCore) -> Column {
     }
 }
 
+/// Estimates the average formatted length of a value of an existing column type, using the same
+/// heuristics as the generated columns.
+///
+/// Returns `None` if the type is not recognized.
+pub(crate) fn estimate_average_len(ty: &str) -> Option<f64> {
+    let ty = ty.to_ascii_lowercase();
+    let name_end = ty.find(|c: char| !c.is_ascii_alphanumeric()).unwrap_or(ty.len());
+    let params = ty[name_end..]
+        .trim_start()
+        .strip_prefix('(')
+        .and_then(|rest| rest.split_once(')'))
+        .map(|(params, _)| params.split(',').filter_map(|p| p.trim().parse::<u32>().ok()).collect())
+        .unwrap_or_else(Vec::new);
+    let unsigned = ty.contains("unsigned");
+    let int_bytes = |bytes: i32| {
+        let end = if unsigned { 256.0 } else { 128.0 } * 256_f64.powi(bytes);
+        int_average_len(end, unsigned)
+    };
+    Some(match &ty[..name_end] {
+        "tinyint" => int_bytes(0),
+        "smallint" | "int2" => int_bytes(1),
+        "mediumint" => int_bytes(2),
+        "int" | "integer" | "int4" => int_bytes(3),
+        "bigint" | "int8" => int_bytes(7),
+        "serial" | "bigserial" => 6.0,
+        "decimal" | "numeric" => f64::from(params.first().copied().unwrap_or(10)) + 17.0 / 9.0,
+        "varchar" | "nvarchar" => AVERAGE_LEN_PER_CHAR * 0.5 * f64::from(*params.first()?) + 2.0,
+        "char" | "nchar" => AVERAGE_LEN_PER_CHAR * f64::from(params.first().copied().unwrap_or(1)) + 2.0,
+        "timestamp" | "timestamptz" | "datetime" => 21.0,
+        "boolean" | "bool" => 3.5,
+        "float" | "double" | "real" => 21.966,
+        _ => return None,
+    })
+}
+
 static GENERATORS: [ColumnGenerator; 9] = [
     gen_int_column,
     gen_serial_column,
@@ -287,9 +334,143 @@ static GENERATORS: [ColumnGenerator; 9] = [
     gen_float_column,
 ];
 
-fn gen_column(dialect: Dialect, rng: &mut dyn RngCore) -> Column {
-    let gen = GENERATORS.choose(rng).unwrap();
-    gen(dialect, rng)
+/// The source of a column in the profile.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum ColumnSource {
+    Int,
+    Serial,
+    Varchar,
+    Char,
+    Timestamp,
+    Datetime,
+    NullableBool,
+    Decimal,
+    Float,
+    /// A column with user-provided type and expression.
+    Custom {
+        /// The column type.
+        #[serde(rename = "type")]
+        ty: String,
+        /// `dbgen` expression to generate a value of this type.
+        expr: String,
+        /// The -log₂(probability) which two randomly generated values will collide.
+        unique_bits: f64,
+        /// The estimated average formatted length a generated value of this column.
+        average_len: f64,
+    },
+}
+
+/// An entry in the column type mix of the profile.
+#[derive(Debug, Clone, Deserialize)]
+struct ColumnProfile {
+    #[serde(flatten)]
+    source: ColumnSource,
+    /// The relative frequency of this column.
+    #[serde(default = "default_weight")]
+    weight: f64,
+    /// The probability a generated value is NULL.
+    #[serde(default)]
+    null_ratio: f64,
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+impl ColumnProfile {
+    fn gen(&self, dialect: Dialect, rng: &mut dyn RngCore) -> Column {
+        let gen: ColumnGenerator = match &self.source {
+            ColumnSource::Int => gen_int_column,
+            ColumnSource::Serial => gen_serial_column,
+            ColumnSource::Varchar => gen_varchar_column,
+            ColumnSource::Char => gen_char_column,
+            ColumnSource::Timestamp => gen_timestamp_column,
+            ColumnSource::Datetime => gen_datetime_column,
+            ColumnSource::NullableBool => gen_nullable_bool_column,
+            ColumnSource::Decimal => gen_decimal_column,
+            ColumnSource::Float => gen_float_column,
+            ColumnSource::Custom {
+                ty,
+                expr,
+                unique_bits,
+                average_len,
+            } => {
+                return self.apply_null_ratio(Column {
+                    ty: ty.clone(),
+                    expr: expr.clone(),
+                    neg_log2_prob: *unique_bits,
+                    average_len: *average_len,
+                    nullable: false,
+                })
+            }
+        };
+        self.apply_null_ratio(gen(dialect, rng))
+    }
+
+    fn apply_null_ratio(&self, col: Column) -> Column {
+        let p = self.null_ratio;
+        if p <= 0.0 {
+            return col;
+        }
+        // two values collide if they are both NULL, or both non-NULL and equal.
+        let collision_prob = (1.0 - p).powi(2) * (-col.neg_log2_prob).exp2() + p * p;
+        Column {
+            ty: col.ty.strip_suffix(" not null").unwrap_or(&col.ty).to_owned(),
+            expr: format!("CASE WHEN rand.bool({p}) THEN NULL ELSE {} END", col.expr),
+            neg_log2_prob: -collision_prob.log2(),
+            average_len: (1.0 - p) * col.average_len + p * 4.0,
+            nullable: true,
+        }
+    }
+}
+
+/// The mix of column types to generate, read from a JSON file.
+#[derive(Debug, Clone)]
+pub struct Profile {
+    columns: Vec<ColumnProfile>,
+    distr: WeightedAliasIndex<f64>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProfileFile {
+    columns: Vec<ColumnProfile>,
+}
+
+impl Profile {
+    /// Reads the profile from a JSON file.
+    pub fn from_path(path: &str) -> Result<Self, Error> {
+        let path = PathBuf::from(path);
+        let content = read_to_string(&path).map_err(|source| Error::Io {
+            action: "read profile",
+            path: path.clone(),
+            source,
+        })?;
+        let ProfileFile { columns } =
+            serde_json::from_str(&content).map_err(|source| Error::InvalidProfile { path, source })?;
+        for col in &columns {
+            if !(0.0..=1.0).contains(&col.null_ratio) {
+                return Err(Error::InvalidArguments(format!(
+                    "null ratio ({}) must be between 0 and 1",
+                    col.null_ratio
+                )));
+            }
+        }
+        let distr = WeightedAliasIndex::new(columns.iter().map(|col| col.weight).collect())
+            .map_err(|e| Error::InvalidArguments(format!("invalid column weights in profile: {e}")))?;
+        Ok(Self { columns, distr })
+    }
+}
+
+fn gen_column(dialect: Dialect, profile: Option<&Profile>, mut rng: &mut dyn RngCore) -> Column {
+    if let Some(profile) = profile {
+        let col = &profile.columns[profile.distr.sample(&mut rng)];
+        col.gen(dialect, rng)
+    } else {
+        let gen = GENERATORS.choose(rng).unwrap();
+        gen(dialect, rng)
+    }
 }
 
 struct IndexAppender<'a> {
@@ -367,13 +548,13 @@ struct Table {
     seed: crate::cli::Seed,
 }
 
-fn gen_table(dialect: Dialect, rng: &mut dyn RngCore, target_size: f64) -> Table {
+fn gen_table(dialect: Dialect, profile: Option<&Profile>, rng: &mut dyn RngCore, target_size: f64) -> Table {
     let mut schema = String::from("CREATE TABLE _ (\n");
 
     let columns_count = (LogNormal::new(2.354_259_469_228_055, 0.75).unwrap().sample(rng) as usize).max(1);
     let columns = {
         let rng2 = &mut *rng;
-        repeat_with(move || gen_column(dialect, rng2))
+        repeat_with(move || gen_column(dialect, profile, rng2))
             .take(columns_count)
             .collect::<Vec<_>>()
     };
@@ -414,6 +595,7 @@ fn gen_table(dialect: Dialect, rng: &mut dyn RngCore, target_size: f64) -> Table
 
 fn gen_tables<'a>(
     dialect: Dialect,
+    profile: Option<&'a Profile>,
     mut rng: impl Rng + 'a,
     total_target_size: f64,
     tables_count: u32,
@@ -428,7 +610,7 @@ fn gen_tables<'a>(
     let ratio = total_target_size / total_relative_size;
     relative_sizes
         .into_iter()
-        .map(move |f| gen_table(dialect, &mut rng, f * ratio))
+        .map(move |f| gen_table(dialect, profile, &mut rng, f * ratio))
 }
 
 fn to_human_size(s: f64) -> String {
@@ -466,7 +648,7 @@ pub fn print_script(args: &Args) {
     let rng = meta_seed.make_rng();
     let extra_args = shlex::try_join(args.args.iter().map(|s| &**s)).expect("valid arguments");
     let rows_count_per_file = args.rows_count * args.inserts_count;
-    for (i, table) in gen_tables(args.dialect, rng, args.size, args.tables_count).enumerate() {
+    for (i, table) in gen_tables(args.dialect, args.profile.as_ref(), rng, args.size, args.tables_count).enumerate() {
         println!(
             "# table: s{}, rows count: {}, estimated size: {}\n\
              dbgen{} -i - -o . -s {} -t {}.s{} -R {} -r {} -N {} \
@@ -0,0 +1,365 @@
+//! External merge sort of the rows of sorted tables, spilling sorted runs to temporary files.
+
+use crate::{array::Array, error::Error, number::Number, value::Value};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    env, fmt,
+    fs::{remove_file, File, OpenOptions},
+    io::{self, BufReader, BufWriter, Seek, SeekFrom, Write},
+    mem,
+    path::{Path, PathBuf},
+    process,
+    sync::{
+        atomic::{AtomicU64, Ordering as AtomicOrdering},
+        Arc,
+    },
+};
+use tzfile::ArcTz;
+
+/// The default number of rows of a table kept in memory before spilling them to disk.
+pub const DEFAULT_BUFFER_ROWS: usize = 100_000;
+
+/// The counter making the names of the temporary files unique within the process.
+static RUN_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Decides where and when the buffered rows are spilled to disk.
+#[derive(Clone, Debug)]
+pub struct SpillOptions {
+    /// The directory of the temporary files.
+    pub dir: PathBuf,
+    /// The maximum number of rows kept in memory. When the buffer is full, the rows are sorted and
+    /// written to a temporary file as a run.
+    pub buffer_rows: usize,
+}
+
+impl Default for SpillOptions {
+    fn default() -> Self {
+        Self {
+            dir: env::temp_dir(),
+            buffer_rows: DEFAULT_BUFFER_ROWS,
+        }
+    }
+}
+
+/// Compares the sort keys of two rows, where NULL is smaller than every other value.
+pub fn cmp_sort_keys(a: &Value, b: &Value) -> Result<Ordering, Error> {
+    Ok(match (a, b) {
+        (Value::Null, Value::Null) => Ordering::Equal,
+        (Value::Null, _) => Ordering::Less,
+        (_, Value::Null) => Ordering::Greater,
+        _ => a.sql_cmp(b)?.unwrap_or(Ordering::Equal),
+    })
+}
+
+/// A row of values together with its sort key.
+type KeyedRow = (Value, Vec<Value>);
+
+/// Sorts the rows by their keys. The sort is stable, so rows with equal keys keep their order.
+fn sort_rows(rows: &mut [KeyedRow]) -> Result<(), Error> {
+    let mut error = None;
+    rows.sort_by(|(a, _), (b, _)| {
+        cmp_sort_keys(a, b).unwrap_or_else(|e| {
+            error.get_or_insert(e);
+            Ordering::Equal
+        })
+    });
+    error.map_or(Ok(()), Err)
+}
+
+/// Describes an I/O error of the temporary file at `path`.
+fn io_error(action: &'static str, path: &Path) -> impl FnOnce(io::Error) -> Error + '_ {
+    move |source| Error::Io {
+        action,
+        path: path.to_owned(),
+        source,
+    }
+}
+
+/// A temporary file, removed when dropped.
+#[derive(Debug)]
+struct TempFile(PathBuf);
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        // the file only holds a copy of the buffered rows, so failing to remove it is harmless.
+        remove_file(&self.0).ok();
+    }
+}
+
+/// A buffer sorting rows by their keys, where NULL keys come first.
+///
+/// Rows with equal keys keep the order they are pushed. Whenever the buffer is full, the rows are
+/// sorted and spilled to a temporary file as a run, and the runs are merged when the rows are
+/// taken out by [`SortBuffer::drain()`].
+#[derive(Debug, Default)]
+pub struct SortBuffer {
+    /// The rows not spilled yet.
+    rows: Vec<KeyedRow>,
+    /// The spilled runs, in the order they are written.
+    runs: Vec<(TempFile, File)>,
+    /// The time zones of the spilled timestamps, referred by their indices.
+    time_zones: Vec<ArcTz>,
+}
+
+impl SortBuffer {
+    /// Adds a row with its sort key, spilling the buffered rows if the buffer is full.
+    pub fn push(&mut self, key: Value, values: Vec<Value>, options: &SpillOptions) -> Result<(), Error> {
+        self.rows.push((key, values));
+        if self.rows.len() >= options.buffer_rows {
+            self.spill(&options.dir)?;
+        }
+        Ok(())
+    }
+
+    /// Sorts the buffered rows and writes them to a new temporary file in `dir`.
+    fn spill(&mut self, dir: &Path) -> Result<(), Error> {
+        sort_rows(&mut self.rows)?;
+        let counter = RUN_COUNTER.fetch_add(1, AtomicOrdering::Relaxed);
+        let temp_file = TempFile(dir.join(format!(".dbgen-sort-{}-{counter}.tmp", process::id())));
+        let path = &temp_file.0;
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(path)
+            .map_err(io_error("create sort run file", path))?;
+        let mut writer = BufWriter::new(&file);
+        for (key, values) in self.rows.drain(..) {
+            let row = SpilledRow(
+                spill_value(key, &mut self.time_zones),
+                values
+                    .into_iter()
+                    .map(|value| spill_value(value, &mut self.time_zones))
+                    .collect(),
+            );
+            serde_json::to_writer(&mut writer, &row).map_err(|e| io_error("write sort run file", path)(e.into()))?;
+            writer.write_all(b"\n").map_err(io_error("write sort run file", path))?;
+        }
+        writer.flush().map_err(io_error("write sort run file", path))?;
+        drop(writer);
+        self.runs.push((temp_file, file));
+        Ok(())
+    }
+
+    /// Takes out all rows in the order of their keys, leaving the buffer empty.
+    pub fn drain(&mut self) -> Result<SortedRows, Error> {
+        sort_rows(&mut self.rows)?;
+        let time_zones = mem::take(&mut self.time_zones);
+        let mut sources = Vec::with_capacity(self.runs.len() + 1);
+        for (temp_file, mut file) in mem::take(&mut self.runs) {
+            file.seek(SeekFrom::Start(0))
+                .map_err(io_error("read sort run file", &temp_file.0))?;
+            let path = temp_file.0.clone();
+            let time_zones = time_zones.clone();
+            let rows = serde_json::Deserializer::from_reader(BufReader::new(file))
+                .into_iter::<SpilledRow>()
+                .map(move |row| {
+                    let SpilledRow(key, values) = row.map_err(|e| io_error("read sort run file", &path)(e.into()))?;
+                    let restore = |value| {
+                        restore_value(value, &time_zones)
+                            .ok_or_else(|| io_error("read sort run file", &path)(io::ErrorKind::InvalidData.into()))
+                    };
+                    Ok((
+                        restore(key)?,
+                        values.into_iter().map(restore).collect::<Result<_, _>>()?,
+                    ))
+                });
+            sources.push(Source::new(Box::new(rows), Some(temp_file))?);
+        }
+        let rows = mem::take(&mut self.rows).into_iter().map(Ok);
+        sources.push(Source::new(Box::new(rows), None)?);
+        Ok(SortedRows { sources })
+    }
+}
+
+/// A sorted sequence of rows to be merged.
+struct Source {
+    /// The remaining rows after `head`.
+    rows: Box<dyn Iterator<Item = Result<KeyedRow, Error>>>,
+    /// The smallest row not taken out yet, or `None` if all rows are taken.
+    head: Option<KeyedRow>,
+    /// The temporary file the rows are read from, removed after `rows` closes it.
+    _temp_file: Option<TempFile>,
+}
+
+impl Source {
+    /// Creates a source, reading its first row.
+    fn new(
+        mut rows: Box<dyn Iterator<Item = Result<KeyedRow, Error>>>,
+        temp_file: Option<TempFile>,
+    ) -> Result<Self, Error> {
+        Ok(Self {
+            head: rows.next().transpose()?,
+            rows,
+            _temp_file: temp_file,
+        })
+    }
+}
+
+/// The rows taken out of a [`SortBuffer`] in the order of their keys, merging the spilled runs.
+pub struct SortedRows {
+    /// The spilled runs in the order they are written, followed by the rows kept in memory.
+    sources: Vec<Source>,
+}
+
+impl fmt::Debug for SortedRows {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SortedRows")
+            .field("sources", &self.sources.len())
+            .finish()
+    }
+}
+
+impl Iterator for SortedRows {
+    type Item = Result<Vec<Value>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // the rows of earlier sources are generated earlier, so they win the ties to keep the sort
+        // stable.
+        let mut min_index = None;
+        for (i, source) in self.sources.iter().enumerate() {
+            let Some((key, _)) = &source.head else {
+                continue;
+            };
+            if let Some((min_key, _)) = min_index.and_then(|j: usize| self.sources[j].head.as_ref()) {
+                match cmp_sort_keys(key, min_key) {
+                    Ok(Ordering::Less) => {}
+                    Ok(_) => continue,
+                    Err(e) => return Some(Err(e)),
+                }
+            }
+            min_index = Some(i);
+        }
+        let source = &mut self.sources[min_index?];
+        let next = match source.rows.next().transpose() {
+            Ok(next) => next,
+            Err(e) => return Some(Err(e)),
+        };
+        let (_, values) = mem::replace(&mut source.head, next)?;
+        Some(Ok(values))
+    }
+}
+
+/// A spilled row, as the serialized sort key and values.
+#[derive(Serialize, Deserialize)]
+struct SpilledRow(Spilled, Vec<Spilled>);
+
+/// The serialized form of a spilled [`Value`].
+#[derive(Serialize, Deserialize)]
+enum Spilled {
+    Null,
+    Number(Number),
+    Text(String),
+    Bytes(Vec<u8>),
+    /// A timestamp, with the index of its time zone in [`SortBuffer::time_zones`].
+    Timestamp(NaiveDateTime, usize),
+    Date(NaiveDate),
+    Time(NaiveTime),
+    Interval(i64),
+    /// A point, as the bits of the coordinates which may not be finite.
+    Point(u64, u64),
+    Json(serde_json::Value),
+    Raw(Vec<u8>),
+    Array(Vec<Spilled>),
+}
+
+/// Converts a value into the serialized form, recording its time zone if it is a timestamp.
+fn spill_value(value: Value, time_zones: &mut Vec<ArcTz>) -> Spilled {
+    match value {
+        Value::Null => Spilled::Null,
+        Value::Number(n) => Spilled::Number(n),
+        Value::Bytes(bytes) => match String::from_utf8(bytes.into_bytes()) {
+            Ok(s) => Spilled::Text(s),
+            Err(e) => Spilled::Bytes(e.into_bytes()),
+        },
+        Value::Timestamp(timestamp, tz) => {
+            let index = time_zones.iter().position(|t| *t == tz).unwrap_or_else(|| {
+                time_zones.push(tz);
+                time_zones.len() - 1
+            });
+            Spilled::Timestamp(timestamp, index)
+        }
+        Value::Date(date) => Spilled::Date(date),
+        Value::Time(time) => Spilled::Time(time),
+        Value::Interval(interval) => Spilled::Interval(interval),
+        Value::Point(x, y) => Spilled::Point(x.to_bits(), y.to_bits()),
+        Value::Json(json) => Spilled::Json((*json).clone()),
+        Value::Raw(raw) => Spilled::Raw(raw.into_bytes()),
+        Value::Array(array) => Spilled::Array(array.iter().map(|item| spill_value(item, time_zones)).collect()),
+    }
+}
+
+/// Converts the serialized form back into a value. Returns `None` if the time zone is unknown.
+fn restore_value(spilled: Spilled, time_zones: &[ArcTz]) -> Option<Value> {
+    Some(match spilled {
+        Spilled::Null => Value::Null,
+        Spilled::Number(n) => Value::Number(n),
+        Spilled::Text(s) => s.into(),
+        Spilled::Bytes(bytes) => bytes.into(),
+        Spilled::Timestamp(timestamp, index) => Value::Timestamp(timestamp, time_zones.get(index)?.clone()),
+        Spilled::Date(date) => Value::Date(date),
+        Spilled::Time(time) => Value::Time(time),
+        Spilled::Interval(interval) => Value::Interval(interval),
+        Spilled::Point(x, y) => Value::Point(f64::from_bits(x), f64::from_bits(y)),
+        Spilled::Json(json) => Value::Json(Arc::new(json)),
+        Spilled::Raw(raw) => Value::Raw(raw.into()),
+        Spilled::Array(items) => Value::Array(Array::from_values(
+            items
+                .into_iter()
+                .map(|item| restore_value(item, time_zones))
+                .collect::<Option<Vec<_>>>()?,
+        )),
+    })
+}
+
+#[test]
+fn test_spilled_merge() {
+    let dir = tempfile::tempdir().unwrap();
+    let options = SpillOptions {
+        dir: dir.path().to_owned(),
+        buffer_rows: 3,
+    };
+    let timestamp = NaiveDate::from_ymd_opt(2024, 2, 29)
+        .unwrap()
+        .and_hms_micro_opt(12, 34, 56, 789_012)
+        .unwrap();
+    let payloads = [
+        Value::Timestamp(timestamp, ArcTz::new(chrono::Utc.into())),
+        Value::Array(Array::from_values([
+            Value::from(1),
+            Value::Null,
+            Value::from("a".to_owned()),
+        ])),
+        Value::from(vec![0xff_u8, 0xfe]),
+        Value::Point(0.1, f64::NAN),
+        Value::Json(Arc::new(serde_json::json!({"x": [1.5, null]}))),
+        Value::Number(Number::from_finite_f64(0.1 + 0.2)),
+        Value::Interval(-1),
+        Value::Raw(b"DEFAULT".to_vec().into()),
+    ];
+    let keys = [3, 1, 2, 1, 3, 0, 2, 1];
+
+    let mut buffer = SortBuffer::default();
+    for (i, (key, payload)) in keys.into_iter().zip(&payloads).enumerate() {
+        let row = vec![Value::from(i), payload.clone()];
+        buffer.push(Value::from(key), row, &options).unwrap();
+    }
+    assert_eq!(buffer.runs.len(), 2);
+    assert_eq!(dir.path().read_dir().unwrap().count(), 2);
+
+    let rows = buffer.drain().unwrap().collect::<Result<Vec<_>, _>>().unwrap();
+    let expected_order = [5_usize, 1, 3, 7, 2, 6, 0, 4];
+    assert_eq!(rows.len(), expected_order.len());
+    for (row, i) in rows.iter().zip(expected_order) {
+        assert_eq!(row[0], Value::from(i));
+        match (&row[1], &payloads[i]) {
+            (Value::Point(x1, y1), Value::Point(x2, y2)) => {
+                assert_eq!((x1.to_bits(), y1.to_bits()), (x2.to_bits(), y2.to_bits()));
+            }
+            (actual, expected) => assert_eq!(actual, expected),
+        }
+    }
+    assert_eq!(dir.path().read_dir().unwrap().count(), 0);
+}
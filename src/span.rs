@@ -1,11 +1,16 @@
 //! Span of substrings from the template file, for error reporting.
 
-use crate::parser::Rule;
-use pest::error::{Error, ErrorVariant};
+use crate::{error::Error as DbgenError, lint::Lint, parser::Rule};
+use pest::error::{Error, ErrorVariant, InputLocation, LineColLocation};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    cell::{Cell, RefCell},
+    ops::Range,
+};
 
 /// The span of an object, indicating the start and end offsets where the
 /// object was parsed from the template file.
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Span(usize);
 
 impl Default for Span {
@@ -14,27 +19,156 @@ impl Default for Span {
     }
 }
 
+thread_local! {
+    /// The number of spans registered before the template being serialized, see
+    /// [`serialize_spans_after`].
+    static SERIALIZED_SPANS_BASE: Cell<Option<usize>> = const { Cell::new(None) };
+
+    /// The spans the deserialized spans refer to, see [`deserialize_spans_as`].
+    static DESERIALIZED_SPANS: RefCell<Vec<Span>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Runs `f`, in which the spans are serialized as their indices after the first `base` spans of
+/// the registry.
+///
+/// Outside of this function the spans are serialized as `null`, since they are only meaningful
+/// with the [`Registry`] they are registered in.
+pub fn serialize_spans_after<R>(base: usize, f: impl FnOnce() -> R) -> R {
+    let previous = SERIALIZED_SPANS_BASE.with(|b| b.replace(Some(base)));
+    let res = f();
+    SERIALIZED_SPANS_BASE.with(|b| b.set(previous));
+    res
+}
+
+/// Runs `f`, in which the spans serialized by [`serialize_spans_after`] are deserialized by looking
+/// up their indices in `spans`.
+///
+/// Outside of this function (or when the index is out of range) the spans are deserialized as the
+/// default (null) span.
+pub fn deserialize_spans_as<R>(spans: Vec<Span>, f: impl FnOnce() -> R) -> R {
+    let previous = DESERIALIZED_SPANS.with(|s| s.replace(spans));
+    let res = f();
+    DESERIALIZED_SPANS.with(|s| *s.borrow_mut() = previous);
+    res
+}
+
+impl Serialize for Span {
+    fn serialize<Z: Serializer>(&self, serializer: Z) -> Result<Z::Ok, Z::Error> {
+        let index = SERIALIZED_SPANS_BASE
+            .with(Cell::get)
+            .filter(|_| *self != Self::default())
+            .and_then(|base| self.0.checked_sub(base));
+        index.serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Span {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let index = Option::<usize>::deserialize(deserializer)?;
+        Ok(index
+            .and_then(|i| DESERIALIZED_SPANS.with(|s| s.borrow().get(i).copied()))
+            .unwrap_or_default())
+    }
+}
+
+/// The location of a registered span in one of the inputs of a template, allowing the span to be
+/// registered again after the template is loaded from the template cache.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpanLocation {
+    /// The index of the input containing the span.
+    pub input: usize,
+    /// The byte range of the string the span was parsed from, relative to the input.
+    pub string: (usize, usize),
+    /// The byte range of the span, relative to that string.
+    pub span: (usize, usize),
+}
+
 /// Registry of spans.
 #[derive(Default, Debug, Clone)]
-pub struct Registry(Vec<Error<Rule>>);
+pub struct Registry {
+    errors: Vec<Error<Rule>>,
+    /// The address range of the string each span is parsed from, for [`Registry::locate`].
+    strings: Vec<Range<usize>>,
+}
 
 impl Registry {
     /// Registers a span represented by a Pest span.
     pub fn register(&mut self, span: pest::Span<'_>) -> Span {
-        let res = Span(self.0.len());
-        self.0.push(Error::new_from_span(
+        let res = Span(self.errors.len());
+        let string = span.get_input();
+        let start = string.as_ptr() as usize;
+        self.strings.push(start..start + string.len());
+        self.errors.push(Error::new_from_span(
             ErrorVariant::CustomError { message: String::new() },
             span,
         ));
         res
     }
 
+    /// Returns the number of registered spans.
+    pub fn registered_count(&self) -> usize {
+        self.errors.len()
+    }
+
+    /// Locates the spans registered after the first `base` spans in the `inputs` they are parsed
+    /// from.
+    ///
+    /// The `inputs` must be the same strings (not copies) the spans are parsed from. Spans parsed
+    /// from other strings, e.g. the expressions inferred from the column types, are located as
+    /// `None`.
+    pub fn locate(&self, base: usize, inputs: &[&str]) -> Vec<Option<SpanLocation>> {
+        self.strings[base..]
+            .iter()
+            .zip(&self.errors[base..])
+            .map(|(string, e)| {
+                let input = inputs.iter().position(|input| {
+                    let start = input.as_ptr() as usize;
+                    start <= string.start && string.end <= start + input.len()
+                })?;
+                let offset = inputs[input].as_ptr() as usize;
+                let InputLocation::Span(span) = e.location else {
+                    return None;
+                };
+                Some(SpanLocation {
+                    input,
+                    string: (string.start - offset, string.end - offset),
+                    span,
+                })
+            })
+            .collect()
+    }
+
+    /// Registers the spans located by [`Registry::locate`] again, returning the new spans in the
+    /// same order. Spans which cannot be located are returned as the default (null) span.
+    pub fn relocate(&mut self, locations: &[Option<SpanLocation>], inputs: &[&str]) -> Vec<Span> {
+        locations
+            .iter()
+            .map(|location| {
+                location
+                    .and_then(|loc| {
+                        let string = inputs.get(loc.input)?.get(loc.string.0..loc.string.1)?;
+                        Some(self.register(pest::Span::new(string, loc.span.0, loc.span.1)?))
+                    })
+                    .unwrap_or_default()
+            })
+            .collect()
+    }
+
     /// Describes a spanned error as a human-readable string.
     pub fn describe<E: std::error::Error + 'static>(&self, err: &S<E>) -> String {
+        self.describe_as("Error", err)
+    }
+
+    /// Describes a spanned warning as a human-readable string.
+    pub fn describe_warning<E: std::error::Error + 'static>(&self, warning: &S<E>) -> String {
+        self.describe_as("Warning", warning)
+    }
+
+    fn describe_as<E: std::error::Error + 'static>(&self, kind: &str, err: &S<E>) -> String {
         use std::fmt::Write;
-        let mut buf = format!("Error: {}\n", err.inner);
+        let mut buf = format!("{kind}: {}\n", err.inner);
 
-        if let Some(e) = self.0.get(err.span.0) {
+        if let Some(e) = self.errors.get(err.span.0) {
             writeln!(&mut buf, "{e}\n").unwrap();
         }
 
@@ -46,14 +180,110 @@ impl Registry {
 
         buf
     }
+
+    /// Converts a spanned error into a machine-readable diagnostic.
+    pub fn diagnose(&self, err: &S<DbgenError>) -> Diagnostic {
+        let location = match &err.inner {
+            DbgenError::ParseTemplate(e) => Some(&**e),
+            _ => self.errors.get(err.span.0),
+        };
+        Diagnostic {
+            span: location.map(DiagnosticSpan::from),
+            rendered: self.describe(err),
+            ..Diagnostic::new("error", err.inner.code(), &err.inner)
+        }
+    }
+
+    /// Converts a spanned lint into a machine-readable diagnostic.
+    pub fn diagnose_warning(&self, lint: &S<Lint>) -> Diagnostic {
+        Diagnostic {
+            span: self.errors.get(lint.span.0).map(DiagnosticSpan::from),
+            rendered: self.describe_warning(lint),
+            ..Diagnostic::new("warning", lint.inner.code(), &lint.inner)
+        }
+    }
+}
+
+/// A machine-readable description of an error or warning, e.g. for editors and CI annotators.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    /// The severity, either `"error"` or `"warning"`.
+    pub level: &'static str,
+    /// A stable identifier of the kind of diagnostic, e.g. `"unknown_function"`.
+    pub code: &'static str,
+    /// The main message.
+    pub message: String,
+    /// The messages of the underlying causes, outermost first.
+    pub causes: Vec<String>,
+    /// The template file the span refers to, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// The location in the template the diagnostic refers to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub span: Option<DiagnosticSpan>,
+    /// The human-readable description, as produced by [`Registry::describe`].
+    pub rendered: String,
+}
+
+impl Diagnostic {
+    fn new(level: &'static str, code: &'static str, err: &(dyn std::error::Error + 'static)) -> Self {
+        let mut causes = Vec::new();
+        let mut source = err.source();
+        while let Some(e) = source {
+            causes.push(e.to_string());
+            source = e.source();
+        }
+        Self {
+            level,
+            code,
+            message: err.to_string(),
+            causes,
+            file: None,
+            span: None,
+            rendered: String::new(),
+        }
+    }
+}
+
+/// The location of a diagnostic in the template.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+pub struct DiagnosticSpan {
+    /// The byte offset where the span starts.
+    pub start: usize,
+    /// The byte offset where the span ends (exclusive).
+    pub end: usize,
+    /// The line number where the span starts (1-based).
+    pub line: usize,
+    /// The column number where the span starts (1-based, counting characters).
+    pub column: usize,
+}
+
+impl From<&Error<Rule>> for DiagnosticSpan {
+    fn from(e: &Error<Rule>) -> Self {
+        let (start, end) = match e.location {
+            InputLocation::Pos(pos) => (pos, pos),
+            InputLocation::Span(span) => span,
+        };
+        let (LineColLocation::Pos((line, column)) | LineColLocation::Span((line, column), _)) = e.line_col;
+        Self {
+            start,
+            end,
+            line,
+            column,
+        }
+    }
 }
 
 /// A wrapper of around object, annotating it with a span.
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+///
+/// The span is only serialized within [`serialize_spans_after`], since it is only meaningful with
+/// the [`Registry`] it is registered in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct S<T> {
     /// The object itself.
     pub inner: T,
     /// The span associated with the object.
+    #[serde(default)]
     pub span: Span,
 }
 
@@ -111,3 +341,32 @@ impl<T, E> ResultExt for Result<T, E> {
         self.span_err(Span::default())
     }
 }
+
+#[test]
+fn test_diagnose() {
+    use crate::parser::{ParseOptions, Template};
+
+    let mut registry = Registry::default();
+    let err = Template::parse(
+        "CREATE TABLE t (\n  a INT {{ 1 + }}\n);",
+        &[],
+        &[],
+        &ParseOptions::default(),
+        &mut registry,
+    )
+    .unwrap_err();
+    let diagnostic = registry.diagnose(&err);
+    assert_eq!(diagnostic.level, "error");
+    assert_eq!(diagnostic.code, "parse_template");
+    assert_eq!(diagnostic.message, "failed to parse template");
+    assert_eq!(
+        diagnostic.span,
+        Some(DiagnosticSpan {
+            start: 32,
+            end: 32,
+            line: 2,
+            column: 16,
+        })
+    );
+    assert_eq!(diagnostic.rendered, registry.describe(&err));
+}
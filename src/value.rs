@@ -1,11 +1,12 @@
 //! Values
 
-use chrono::{Duration, NaiveDateTime, TimeZone};
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
 use rand_regex::EncodedString;
 use std::{
     cmp::Ordering,
     convert::{TryFrom, TryInto},
     fmt,
+    sync::Arc,
 };
 use tzfile::ArcTz;
 
@@ -19,6 +20,15 @@ use crate::{
 /// The string format of an SQL timestamp.
 pub const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f";
 
+/// The string format of an SQL date.
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// The string format of an SQL time.
+pub const TIME_FORMAT: &str = "%H:%M:%S%.f";
+
+/// The number of microseconds in a day.
+const MICROSECONDS_PER_DAY: i64 = 86_400_000_000;
+
 /// A scalar value.
 #[derive(Clone, Debug, PartialEq)]
 pub enum Value {
@@ -30,8 +40,18 @@ pub enum Value {
     Bytes(ByteString),
     /// A timestamp. The `NaiveDateTime` field must be in the UTC time zone.
     Timestamp(NaiveDateTime, ArcTz),
+    /// A calendar date without the time.
+    Date(NaiveDate),
+    /// A time of day without the date.
+    Time(NaiveTime),
     /// A time interval, as multiple of microseconds.
     Interval(i64),
+    /// A geometric point (*x*, *y*), i.e. (longitude, latitude) in degrees for geographic data.
+    Point(f64, f64),
+    /// A JSON document.
+    Json(Arc<serde_json::Value>),
+    /// Text written to the output verbatim without quoting or escaping, e.g. an SQL expression.
+    Raw(ByteString),
     /// An array of values. The array may be lazily evaluated.
     Array(Array),
 }
@@ -109,6 +129,20 @@ impl Value {
         Self::Timestamp(ts, tz)
     }
 
+    /// Adds an interval of whole days to a date.
+    fn add_days(date: NaiveDate, interval: i64) -> Result<Self, Error> {
+        if interval % MICROSECONDS_PER_DAY != 0 {
+            return Err(Error::InvalidArguments(format!(
+                "cannot add INTERVAL {interval} MICROSECOND to DATE '{date}', which is not a whole number of days"
+            )));
+        }
+        let days = Duration::days(interval / MICROSECONDS_PER_DAY);
+        Ok(Self::Date(try_or_overflow!(
+            date.checked_add_signed(days),
+            "{date} + {interval}us"
+        )))
+    }
+
     /// Creates a finite floating point value.
     pub(crate) fn from_finite_f64(v: f64) -> Self {
         Self::Number(Number::from_finite_f64(v))
@@ -119,6 +153,7 @@ impl Value {
     /// * Comparing with NULL always return `None`.
     /// * Numbers and intervals are ordered by value.
     /// * Timestamps are ordered by its UTC value, ignoring time zone.
+    /// * Dates and times are ordered chronologically.
     /// * Strings are ordered by UTF-8 binary collation.
     /// * Arrays are ordered lexicographically.
     /// * Comparing between different types are inconsistent among database
@@ -127,8 +162,11 @@ impl Value {
         Ok(match (self, other) {
             (Self::Null, _) | (_, Self::Null) => None,
             (Self::Number(a), Self::Number(b)) => a.partial_cmp(b),
-            (Self::Bytes(a), Self::Bytes(b)) => a.partial_cmp(b),
+            // raw values are compared as the strings they contain.
+            (Self::Bytes(a) | Self::Raw(a), Self::Bytes(b) | Self::Raw(b)) => a.partial_cmp(b),
             (Self::Timestamp(a, _), Self::Timestamp(b, _)) => a.partial_cmp(b),
+            (Self::Date(a), Self::Date(b)) => a.partial_cmp(b),
+            (Self::Time(a), Self::Time(b)) => a.partial_cmp(b),
             (Self::Interval(a), Self::Interval(b)) => a.partial_cmp(b),
             (Self::Array(a), Self::Array(b)) => try_partial_cmp_by(a.iter(), b.iter(), |x, y| x.sql_cmp(&y))?,
             _ => {
@@ -142,8 +180,8 @@ impl Value {
         match self {
             Self::Null => Ordering::Equal,
             Self::Number(a) => a.sql_sign(),
-            Self::Bytes(a) => true.cmp(&a.is_empty()),
-            Self::Timestamp(..) => Ordering::Greater,
+            Self::Bytes(a) | Self::Raw(a) => true.cmp(&a.is_empty()),
+            Self::Timestamp(..) | Self::Date(_) | Self::Time(_) | Self::Point(..) | Self::Json(_) => Ordering::Greater,
             Self::Interval(a) => a.cmp(&0),
             Self::Array(a) => true.cmp(&a.is_empty()),
         }
@@ -168,6 +206,12 @@ impl Value {
                     tz.clone(),
                 )
             }
+            (Self::Date(date), Self::Interval(dur)) | (Self::Interval(dur), Self::Date(date)) => {
+                Self::add_days(*date, *dur)?
+            }
+            (Self::Time(time), Self::Interval(dur)) | (Self::Interval(dur), Self::Time(time)) => {
+                Self::Time(time.overflowing_add_signed(Duration::microseconds(*dur)).0)
+            }
             (Self::Interval(a), Self::Interval(b)) => Self::Interval(try_or_overflow!(a.checked_add(*b), "{a} + {b}")),
             _ => {
                 return Err(Error::InvalidArguments(format!("cannot add {self} to {other}")));
@@ -187,6 +231,20 @@ impl Value {
                 try_or_overflow!(ts.checked_sub_signed(Duration::microseconds(*dur)), "{ts} - {dur}us"),
                 tz.clone(),
             ),
+            (Self::Date(lhs), Self::Date(rhs)) => Self::Interval(try_or_overflow!(
+                lhs.signed_duration_since(*rhs).num_microseconds(),
+                "{lhs} - {rhs}"
+            )),
+            (Self::Date(date), Self::Interval(dur)) => {
+                Self::add_days(*date, try_or_overflow!(dur.checked_neg(), "{date} - {dur}us"))?
+            }
+            (Self::Time(lhs), Self::Time(rhs)) => Self::Interval(try_or_overflow!(
+                lhs.signed_duration_since(*rhs).num_microseconds(),
+                "{lhs} - {rhs}"
+            )),
+            (Self::Time(time), Self::Interval(dur)) => {
+                Self::Time(time.overflowing_sub_signed(Duration::microseconds(*dur)).0)
+            }
             (Self::Interval(a), Self::Interval(b)) => Self::Interval(try_or_overflow!(a.checked_sub(*b), "{a} - {b}")),
             _ => {
                 return Err(Error::InvalidArguments(format!("cannot subtract {self} from {other}")));
@@ -258,11 +316,15 @@ impl Value {
             match item {
                 Self::Null => return Ok(Self::Null),
                 Self::Number(n) => res.extend_number(n),
-                Self::Bytes(b) => res.extend_byte_string(b),
+                Self::Bytes(b) | Self::Raw(b) => res.extend_byte_string(b),
                 Self::Timestamp(timestamp, tz) => {
                     write!(res, "{}", tz.from_utc_datetime(timestamp).format(TIMESTAMP_FORMAT)).unwrap();
                 }
+                Self::Date(date) => write!(res, "{}", date.format(DATE_FORMAT)).unwrap(),
+                Self::Time(time) => write!(res, "{}", time.format(TIME_FORMAT)).unwrap(),
                 Self::Interval(interval) => write!(res, "INTERVAL {interval} MICROSECOND").unwrap(),
+                Self::Point(x, y) => write!(res, "POINT({x} {y})").unwrap(),
+                Self::Json(json) => write!(res, "{json}").unwrap(),
                 Self::Array(_) => {
                     return Err(Error::InvalidArguments(
                         "cannot concatenate arrays using || operator".to_owned(),
@@ -410,6 +472,32 @@ impl TryFrom<Value> for Array {
     }
 }
 
+impl TryFrom<Value> for serde_json::Value {
+    type Error = Error;
+
+    /// Converts a value into JSON. Timestamps, dates and times become strings, arrays become JSON arrays, and points
+    /// become GeoJSON objects.
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        Ok(match value {
+            Value::Null => Self::Null,
+            Value::Number(n) => n.to_json(),
+            Value::Bytes(bytes) | Value::Raw(bytes) => match String::try_from(bytes) {
+                Ok(s) => Self::String(s),
+                Err(e) => return Err(Value::Bytes(e.0).to_unexpected_value_type_error("JSON-compatible value")),
+            },
+            Value::Timestamp(timestamp, tz) => {
+                Self::String(tz.from_utc_datetime(&timestamp).format(TIMESTAMP_FORMAT).to_string())
+            }
+            Value::Date(date) => Self::String(date.format(DATE_FORMAT).to_string()),
+            Value::Time(time) => Self::String(time.format(TIME_FORMAT).to_string()),
+            Value::Point(x, y) => serde_json::json!({ "type": "Point", "coordinates": [x, y] }),
+            Value::Json(json) => (*json).clone(),
+            Value::Array(array) => Self::Array(array.iter().map(Self::try_from).collect::<Result<_, _>>()?),
+            Value::Interval(_) => return Err(value.to_unexpected_value_type_error("JSON-compatible value")),
+        })
+    }
+}
+
 impl<T: Into<Number>> From<T> for Value {
     fn from(value: T) -> Self {
         Self::Number(value.into())
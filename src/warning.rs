@@ -0,0 +1,434 @@
+//! Detecting suspicious values which may be silently corrupted when imported into a database.
+
+use crate::{ddl::ColumnType, value::Value};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use std::{
+    fmt,
+    ops::{Bound, RangeBounds, RangeInclusive},
+};
+
+/// The kinds of suspicious values.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum WarningKind {
+    /// An integer outside the range of the integer column type.
+    IntegerOutOfRange,
+    /// An integer which cannot be exactly represented by the floating point column type.
+    ImpreciseFloat,
+    /// A string longer than the declared length of the column type.
+    StringTooLong,
+    /// A timestamp outside the range of the date or time column type.
+    TimestampOutOfRange,
+    /// A NULL in a column declared `NOT NULL`.
+    NullInNotNullColumn,
+    /// A number violating the `CHECK` constraint of the column.
+    CheckViolated,
+    /// A string which must be quoted in CSV, but is written unquoted.
+    UnquotedCsvString,
+    /// An integer which cannot be exactly represented by the JSON parsers using doubles.
+    UnsafeJsonInteger,
+}
+
+impl WarningKind {
+    /// Describes the problem.
+    fn description(self) -> &'static str {
+        match self {
+            Self::IntegerOutOfRange => "integer is out of range of the column type",
+            Self::ImpreciseFloat => "integer cannot be exactly represented by the floating point column type",
+            Self::StringTooLong => "string is longer than the column type",
+            Self::TimestampOutOfRange => "timestamp is out of range of the column type",
+            Self::NullInNotNullColumn => "null value in a NOT NULL column",
+            Self::CheckViolated => "number violates the CHECK constraint of the column",
+            Self::UnquotedCsvString => {
+                "string containing the delimiter, a quotation mark or a line break is written unquoted"
+            }
+            Self::UnsafeJsonInteger => "integer beyond ±2^53 loses precision in most JSON parsers",
+        }
+    }
+
+    /// Returns a machine-readable code identifying the kind of problem.
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::IntegerOutOfRange => "integer_out_of_range",
+            Self::ImpreciseFloat => "imprecise_float",
+            Self::StringTooLong => "string_too_long",
+            Self::TimestampOutOfRange => "timestamp_out_of_range",
+            Self::NullInNotNullColumn => "null_in_not_null_column",
+            Self::CheckViolated => "check_violated",
+            Self::UnquotedCsvString => "unquoted_csv_string",
+            Self::UnsafeJsonInteger => "unsafe_json_integer",
+        }
+    }
+}
+
+/// A suspicious value written to the output.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Warning {
+    /// The kind of problem.
+    pub kind: WarningKind,
+    /// The table name.
+    pub table: String,
+    /// The column name.
+    pub column: String,
+    /// The row number producing the value.
+    pub row_num: u64,
+    /// The SQL representation of the value.
+    pub value: String,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (table {}, column {}, row {}, value {})",
+            self.kind.description(),
+            self.table,
+            self.column,
+            self.row_num,
+            self.value
+        )
+    }
+}
+
+/// The restriction of values derived from a column type.
+#[derive(Clone, Debug)]
+pub enum ColumnCheck {
+    /// Integers must be within the range.
+    Integer(RangeInclusive<i128>),
+    /// Integers must be exactly representable with the given number of mantissa bits.
+    Float(u32),
+    /// Strings must not be longer than the number of characters.
+    String(usize),
+    /// Timestamps must be within the range.
+    Timestamp(RangeInclusive<NaiveDateTime>),
+    /// The value must not be NULL.
+    NotNull,
+    /// Numbers must be within the bounds.
+    Check(Bound<f64>, Bound<f64>),
+    /// Strings written unquoted in CSV must not contain the delimiter, quotation marks or line
+    /// breaks. Line feeds are allowed if they are escaped.
+    UnquotedCsv {
+        /// The byte separating the values.
+        delimiter: u8,
+        /// Whether line feeds are replaced by an escape sequence.
+        newline_escaped: bool,
+    },
+    /// Integers written as JSON numbers must be within ±2^53.
+    JsonInteger,
+}
+
+/// Splits the text into words, numbers, string literals, comparison operators and parenthesis.
+fn tokenize(text: &str) -> Vec<&str> {
+    let is_word_char = |c: char| c.is_alphanumeric() || matches!(c, '_' | '.' | '"' | '`');
+    let mut tokens = Vec::new();
+    let mut rest = text;
+    loop {
+        rest = rest.trim_start();
+        let Some(c) = rest.chars().next() else {
+            return tokens;
+        };
+        let len = if c == '\'' {
+            // a string literal, where a quote is escaped by doubling it.
+            let mut end = rest.len();
+            let mut quotes = rest.match_indices('\'').skip(1).peekable();
+            while let Some((i, _)) = quotes.next() {
+                if quotes.next_if(|(j, _)| *j == i + 1).is_none() {
+                    end = i + 1;
+                    break;
+                }
+            }
+            end
+        } else if is_word_char(c) || c == '-' {
+            let first = c.len_utf8();
+            rest[first..]
+                .find(|c| !is_word_char(c))
+                .map_or(rest.len(), |i| i + first)
+        } else if [">=", "<=", "<>", "!="].iter().any(|op| rest.starts_with(op)) {
+            2
+        } else {
+            c.len_utf8()
+        };
+        tokens.push(&rest[..len]);
+        rest = &rest[len..];
+    }
+}
+
+/// Parses a comparison in a `CHECK` constraint into the bounds of the column, e.g.
+/// `x BETWEEN 1 AND 10` or `x >= 0`.
+fn parse_comparison(column_name: &str, tokens: &[&str]) -> Option<(Bound<f64>, Bound<f64>)> {
+    let is_column = |token: &str| {
+        token
+            .trim_matches(|c| c == '"' || c == '`')
+            .eq_ignore_ascii_case(column_name)
+    };
+    let number = |token: &str| token.parse::<f64>().ok().filter(|n| n.is_finite());
+    Some(match *tokens {
+        [column, between, low, and, high]
+            if is_column(column) && between.eq_ignore_ascii_case("between") && and.eq_ignore_ascii_case("and") =>
+        {
+            (Bound::Included(number(low)?), Bound::Included(number(high)?))
+        }
+        [left, op, right] => {
+            let (n, op) = if is_column(left) {
+                (number(right)?, op)
+            } else if is_column(right) {
+                let flipped = match op {
+                    ">" => "<",
+                    "<" => ">",
+                    ">=" => "<=",
+                    "<=" => ">=",
+                    _ => op,
+                };
+                (number(left)?, flipped)
+            } else {
+                return None;
+            };
+            match op {
+                ">" => (Bound::Excluded(n), Bound::Unbounded),
+                ">=" => (Bound::Included(n), Bound::Unbounded),
+                "<" => (Bound::Unbounded, Bound::Excluded(n)),
+                "<=" => (Bound::Unbounded, Bound::Included(n)),
+                "=" => (Bound::Included(n), Bound::Included(n)),
+                _ => return None,
+            }
+        }
+        _ => return None,
+    })
+}
+
+impl ColumnCheck {
+    /// Derives the check from the column type, e.g. `Integer(-128..=127)` from `tinyint not null`.
+    /// Returns `None` if the type is not recognized.
+    pub fn from_type(ty: &str) -> Option<Self> {
+        Some(match ColumnType::parse(ty) {
+            ColumnType::Integer { bytes, unsigned } => {
+                let bits = 8 * u32::from(bytes);
+                Self::Integer(if unsigned {
+                    0..=(1_i128 << bits) - 1
+                } else {
+                    let base = 1_i128 << (bits - 1);
+                    -base..=base - 1
+                })
+            }
+            ColumnType::Float { single: true } => Self::Float(24),
+            ColumnType::Float { single: false } => Self::Float(53),
+            ColumnType::Char(length) => Self::String(length.unwrap_or(1)),
+            ColumnType::Varchar(length) => Self::String(length?),
+            ColumnType::Date | ColumnType::Timestamp { .. } => Self::Timestamp(
+                NaiveDate::from_ymd_opt(1, 1, 1)?.and_hms_opt(0, 0, 0)?
+                    ..=NaiveDate::from_ymd_opt(9999, 12, 31)?.and_hms_micro_opt(23, 59, 59, 999_999)?,
+            ),
+            _ => return None,
+        })
+    }
+
+    /// Derives the checks from the column type and the column constraints, i.e. `NOT NULL`,
+    /// `PRIMARY KEY`, and `CHECK` constraints comparing the column with numbers, such as
+    /// `CHECK (x BETWEEN 1 AND 10)` or `CHECK (x > 0 AND x <= 100)`.
+    ///
+    /// Parts of the `CHECK` constraints which are not understood are ignored.
+    pub fn from_constraints(column_name: &str, ty: &str) -> Vec<Self> {
+        let mut checks = Self::from_type(ty).into_iter().collect::<Vec<_>>();
+        let column_name = column_name.trim_matches(|c| matches!(c, '"' | '`' | '[' | ']'));
+        let tokens = tokenize(ty);
+        let mut i = 0;
+        while i < tokens.len() {
+            let token = tokens[i];
+            i += 1;
+            if token.eq_ignore_ascii_case("primary")
+                || (token.eq_ignore_ascii_case("null") && i >= 2 && tokens[i - 2].eq_ignore_ascii_case("not"))
+            {
+                checks.push(Self::NotNull);
+            } else if token.eq_ignore_ascii_case("check") && tokens.get(i) == Some(&"(") {
+                // collect the conjuncts of the parenthesized condition.
+                let mut depth = 1;
+                let mut conjuncts = vec![Vec::new()];
+                let mut has_or = false;
+                let mut in_between = false;
+                i += 1;
+                while i < tokens.len() {
+                    let token = tokens[i];
+                    i += 1;
+                    match token {
+                        "(" => depth += 1,
+                        ")" => depth -= 1,
+                        _ => {}
+                    }
+                    let conjunct = conjuncts.last_mut().unwrap();
+                    if depth == 0 {
+                        break;
+                    } else if depth > 1 || token == ")" {
+                        conjunct.push(token);
+                    } else if token.eq_ignore_ascii_case("or") {
+                        has_or = true;
+                    } else if token.eq_ignore_ascii_case("and") && !in_between {
+                        conjuncts.push(Vec::new());
+                    } else {
+                        if token.eq_ignore_ascii_case("between") {
+                            in_between = true;
+                        } else if token.eq_ignore_ascii_case("and") {
+                            in_between = false;
+                        }
+                        conjunct.push(token);
+                    }
+                }
+                if !has_or {
+                    checks.extend(conjuncts.iter().filter_map(|conjunct| {
+                        let (low, high) = parse_comparison(column_name, conjunct)?;
+                        Some(Self::Check(low, high))
+                    }));
+                }
+            }
+        }
+        checks
+    }
+
+    /// Checks whether the value satisfies the column type.
+    pub fn check(&self, value: &Value) -> Option<WarningKind> {
+        let ok = match (self, value) {
+            (Self::Integer(range), Value::Number(n)) => i128::try_from(*n).is_ok_and(|n| range.contains(&n)),
+            (Self::Float(bits), Value::Number(n)) => match i128::try_from(*n) {
+                Ok(n) => n.unsigned_abs() <= 1_u128 << bits,
+                Err(_) => true,
+            },
+            (Self::String(len), Value::Bytes(bytes)) => bytes.char_len() <= *len,
+            (Self::Timestamp(range), Value::Timestamp(ts, _)) => range.contains(ts),
+            (Self::Timestamp(range), Value::Date(date)) => range.contains(&date.and_time(NaiveTime::MIN)),
+            (Self::NotNull, Value::Null) => false,
+            (Self::Check(low, high), Value::Number(n)) => (*low, *high).contains(&f64::from(*n)),
+            (
+                Self::UnquotedCsv {
+                    delimiter,
+                    newline_escaped,
+                },
+                Value::Bytes(bytes),
+            ) => !bytes
+                .as_bytes()
+                .iter()
+                .any(|&b| b == *delimiter || b == b'"' || b == b'\r' || (b == b'\n' && !newline_escaped)),
+            (Self::JsonInteger, Value::Number(n)) => match i128::try_from(*n) {
+                Ok(n) => n.unsigned_abs() <= 1_u128 << 53,
+                Err(_) => true,
+            },
+            _ => true,
+        };
+        if ok {
+            None
+        } else {
+            Some(match self {
+                Self::Integer(_) => WarningKind::IntegerOutOfRange,
+                Self::Float(_) => WarningKind::ImpreciseFloat,
+                Self::String(_) => WarningKind::StringTooLong,
+                Self::Timestamp(_) => WarningKind::TimestampOutOfRange,
+                Self::NotNull => WarningKind::NullInNotNullColumn,
+                Self::Check(..) => WarningKind::CheckViolated,
+                Self::UnquotedCsv { .. } => WarningKind::UnquotedCsvString,
+                Self::JsonInteger => WarningKind::UnsafeJsonInteger,
+            })
+        }
+    }
+}
+
+#[test]
+fn test_column_check() {
+    let tinyint = ColumnCheck::from_type("TINYINT NOT NULL").unwrap();
+    assert_eq!(tinyint.check(&127.into()), None);
+    assert_eq!(tinyint.check(&128.into()), Some(WarningKind::IntegerOutOfRange));
+    assert_eq!(tinyint.check(&Value::Null), None);
+
+    let unsigned = ColumnCheck::from_type("int unsigned").unwrap();
+    assert_eq!(unsigned.check(&(-1).into()), Some(WarningKind::IntegerOutOfRange));
+
+    let double = ColumnCheck::from_type("double precision").unwrap();
+    assert_eq!(double.check(&(1_u64 << 53).into()), None);
+    assert_eq!(
+        double.check(&((1_u64 << 53) + 1).into()),
+        Some(WarningKind::ImpreciseFloat)
+    );
+
+    let varchar = ColumnCheck::from_type("varchar(3)").unwrap();
+    assert_eq!(varchar.check(&"äbc".to_owned().into()), None);
+    assert_eq!(
+        varchar.check(&"abcd".to_owned().into()),
+        Some(WarningKind::StringTooLong)
+    );
+
+    let serial8 = ColumnCheck::from_type("serial8").unwrap();
+    assert_eq!(serial8.check(&(1_i64 << 40).into()), None);
+
+    assert!(ColumnCheck::from_type("text").is_none());
+}
+
+#[test]
+fn test_output_checks() {
+    let csv = ColumnCheck::UnquotedCsv {
+        delimiter: b'|',
+        newline_escaped: true,
+    };
+    assert_eq!(csv.check(&"a,b\\n".to_owned().into()), None);
+    assert_eq!(csv.check(&"a\nb".to_owned().into()), None);
+    assert_eq!(csv.check(&1.into()), None);
+    for s in ["a|b", "say \"hi\"", "a\r\nb"] {
+        assert_eq!(
+            csv.check(&s.to_owned().into()),
+            Some(WarningKind::UnquotedCsvString),
+            "{s:?}"
+        );
+    }
+    let csv = ColumnCheck::UnquotedCsv {
+        delimiter: b',',
+        newline_escaped: false,
+    };
+    assert_eq!(
+        csv.check(&"a\nb".to_owned().into()),
+        Some(WarningKind::UnquotedCsvString)
+    );
+
+    let json = ColumnCheck::JsonInteger;
+    assert_eq!(json.check(&(-(1_i64 << 53)).into()), None);
+    assert_eq!(json.check(&(1_u64 << 53).into()), None);
+    assert_eq!(
+        json.check(&((1_u64 << 53) + 1).into()),
+        Some(WarningKind::UnsafeJsonInteger)
+    );
+    assert_eq!(json.check(&u64::MAX.into()), Some(WarningKind::UnsafeJsonInteger));
+    assert_eq!(json.check(&Value::from_finite_f64(1e300)), None);
+}
+
+#[test]
+fn test_column_constraints() {
+    let checks = ColumnCheck::from_constraints("x", "TINYINT NOT NULL CHECK (x BETWEEN 1 AND 10)");
+    assert_eq!(checks.len(), 3);
+    let check = |value: Value| checks.iter().find_map(|c| c.check(&value));
+    assert_eq!(check(5.into()), None);
+    assert_eq!(check(Value::Null), Some(WarningKind::NullInNotNullColumn));
+    assert_eq!(check(11.into()), Some(WarningKind::CheckViolated));
+    assert_eq!(check(200.into()), Some(WarningKind::IntegerOutOfRange));
+
+    let checks = ColumnCheck::from_constraints("\"x\"", "real check (0 < x and \"x\" <= 1.5)");
+    let check = |value: Value| checks.iter().find_map(|c| c.check(&value));
+    assert_eq!(check(Value::from_finite_f64(1.5)), None);
+    assert_eq!(check(0.into()), Some(WarningKind::CheckViolated));
+    assert_eq!(check(Value::Null), None);
+
+    assert_eq!(
+        ColumnCheck::from_constraints("x", "int check (x < 0 or x > 10)").len(),
+        1
+    );
+    assert!(ColumnCheck::from_constraints("x", "text check (length(x) > 3)").is_empty());
+    assert!(ColumnCheck::from_constraints("x", "text comment 'été, not null'").is_empty());
+    let checks = ColumnCheck::from_constraints("x", "text comment 'isn''t null' check (x < 0)");
+    assert!(matches!(checks[..], [ColumnCheck::Check(..)]));
+}
+
+#[test]
+fn test_tokenize() {
+    assert_eq!(
+        tokenize("été NOT NULL CHECK (x>=-1)"),
+        ["été", "NOT", "NULL", "CHECK", "(", "x", ">=", "-1", ")"]
+    );
+    assert_eq!(
+        tokenize("comment 'it''s (x > 1)' null"),
+        ["comment", "'it''s (x > 1)'", "null"]
+    );
+    assert_eq!(tokenize("'unterminated"), ["'unterminated"]);
+}
@@ -2,11 +2,20 @@
 
 use crate::{
     error::Error,
-    eval::{Schema, State, Table},
-    span::{ResultExt, S},
+    eval::{Compiled, Schema, State, Table, C},
+    infer,
+    sort::{SortBuffer, SpillOptions},
+    span::{ResultExt, SpanExt, S},
     value::Value,
+    warning::{ColumnCheck, Warning, WarningKind},
+};
+use rand::Rng;
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryInto,
+    fmt::{self, Write as _},
+    iter, mem,
 };
-use std::{convert::TryInto, mem};
 
 /// A generic writer which could accept rows of values.
 pub trait Writer {
@@ -30,6 +39,157 @@ pub trait Writer {
 
     /// Writes the content of an INSERT statement after all rows.
     fn write_trailer(&mut self) -> Result<(), S<Error>>;
+
+    /// Writes a change event of a row as a complete statement or record.
+    ///
+    /// This is only called if the environment emits change events instead of plain rows. The
+    /// default implementation fails, since the event cannot be described as a plain row.
+    fn write_change(&mut self, _: &Schema<'_>, _: &Change<'_>) -> Result<(), S<Error>> {
+        Err(Error::UnsupportedCliParameter {
+            kind: "writer of change events",
+            value: std::any::type_name::<Self>().to_owned(),
+        }
+        .no_span())
+    }
+}
+
+/// The operation of a change event.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ChangeOp {
+    /// A new row is inserted.
+    Insert,
+    /// An existing row is updated.
+    Update,
+    /// An existing row is deleted.
+    Delete,
+}
+
+/// A change event of a row, emitted instead of the plain row if enabled by
+/// [`Env::change_events()`].
+#[derive(Debug, Copy, Clone)]
+pub struct Change<'a> {
+    /// The operation.
+    pub op: ChangeOp,
+    /// The index of the column identifying the row.
+    pub key_column: usize,
+    /// The image of the row before the change, or `None` for inserts.
+    pub before: Option<&'a [Value]>,
+    /// The image of the row after the change, or `None` for deletes.
+    pub after: Option<&'a [Value]>,
+}
+
+/// Writes the values of a row in the output order, omitting the columns excluded from the output.
+fn write_values<W: Writer>(writer: &mut W, schema: &Schema<'_>, values: &[Value]) -> Result<(), S<Error>> {
+    for (i, (column, col_index)) in schema
+        .output_column_names()
+        .zip(schema.output_column_indices())
+        .enumerate()
+    {
+        if i != 0 {
+            writer.write_value_separator()?;
+        }
+        writer.write_value_header(column)?;
+        writer.write_value(&values[col_index])?;
+    }
+    Ok(())
+}
+
+/// The prefix of the namespace of the random number generator deciding whether a row of a table is
+/// duplicated, followed by the table name.
+const DUPLICATE_NAMESPACE: &str = "dbgen.duplicate:";
+
+/// The prefix of the namespace of the random number generator deciding the operation of a change
+/// event, followed by the table name.
+const CHANGE_NAMESPACE: &str = "dbgen.change:";
+
+/// Maximum number of rows evaluated in a batch by [`Env::write_rows()`].
+const BATCH_ROWS: usize = 1024;
+
+/// Maximum number of partitions of a table written by one data file. Every partition keeps its
+/// own writer (and thus an open file) until the data file is finished.
+pub const MAX_PARTITIONS: usize = 1024;
+
+/// The directory name of the partition holding NULL values, following the Hive convention.
+const NULL_PARTITION: &str = "__HIVE_DEFAULT_PARTITION__";
+
+/// Computes the Hive-style directory name `column=value` of the partition containing the value.
+///
+/// Characters which are not safe in a path are percent-encoded.
+fn partition_name(column: &str, value: &Value) -> Result<String, Error> {
+    let mut name = format!("{column}=");
+    let Value::Bytes(bytes) = Value::sql_concat(iter::once(value))? else {
+        name.push_str(NULL_PARTITION);
+        return Ok(name);
+    };
+    for c in String::from_utf8_lossy(bytes.as_bytes()).chars() {
+        if c.is_control() || "\"#%'*/:=?\\[]^{}".contains(c) {
+            write!(name, "%{:02X}", u32::from(c)).unwrap();
+        } else {
+            name.push(c);
+        }
+    }
+    Ok(name)
+}
+
+/// A writer of a table within [`Env`], receiving either all rows or the rows of one partition.
+#[derive(Debug)]
+struct PartitionWriter<W: Writer> {
+    /// The writer.
+    writer: W,
+    /// Records if any rows have been written out. This determines whether an INSERT statement is
+    /// needed to be written or not. This member will be reset to `true` after calling
+    /// [`Env::write_trailer()`].
+    empty: bool,
+    /// The rows of the current data file together with their sort keys, written out in order by
+    /// [`Env::write_sorted_rows()`], if the table is sorted.
+    sorted_rows: SortBuffer,
+    /// The number of rows buffered in `sorted_rows` for the current INSERT statement.
+    statement_rows: usize,
+    /// The number of rows of every INSERT statement buffered in `sorted_rows`.
+    statement_sizes: Vec<usize>,
+    /// The rows inserted into this writer so far which may be updated or deleted by change
+    /// events. They are kept per writer so that every change event goes to the shard or partition
+    /// holding the row.
+    retained_rows: Vec<Vec<Value>>,
+}
+
+impl<W: Writer> PartitionWriter<W> {
+    /// Creates the writer of a table or a partition.
+    fn new(writer: W) -> Self {
+        Self {
+            writer,
+            empty: true,
+            sorted_rows: SortBuffer::default(),
+            statement_rows: 0,
+            statement_sizes: Vec::new(),
+            retained_rows: Vec::new(),
+        }
+    }
+
+    /// Writes out the buffered rows in the order of their keys, as INSERT statements of the same
+    /// sizes as they were generated, calling `end_statement` after each statement.
+    fn write_sorted_rows(
+        &mut self,
+        schema: &Schema<'_>,
+        order_by: &Compiled,
+        end_statement: &mut dyn FnMut(&mut W) -> Result<(), S<Error>>,
+    ) -> Result<(), S<Error>> {
+        let mut rows = self.sorted_rows.drain().span_err(order_by.0.span)?;
+        for size in mem::take(&mut self.statement_sizes) {
+            for (i, values) in rows.by_ref().take(size).enumerate() {
+                let values = values.span_err(order_by.0.span)?;
+                if i == 0 {
+                    self.writer.write_header(schema)
+                } else {
+                    self.writer.write_row_separator()
+                }?;
+                write_values(&mut self.writer, schema, &values)?;
+            }
+            self.writer.write_trailer()?;
+            end_statement(&mut self.writer)?;
+        }
+        Ok(())
+    }
 }
 
 /// The state of a table within [`Env`].
@@ -39,75 +199,485 @@ struct TableState<'a, W: Writer> {
     table: &'a Table,
     /// The table's schema.
     schema: Schema<'a>,
-    /// Writer associated with the table.
-    writer: W,
+    /// The schemas of the physical tables, one per shard if the table is sharded. Otherwise this
+    /// is empty and every writer uses `schema`.
+    shard_schemas: Vec<Schema<'a>>,
+    /// Writers associated with the table. If the table is sharded, there is one writer per shard.
+    /// If the table is partitioned, there is one writer per partition seen so far. Otherwise
+    /// there is exactly one writer.
+    writers: Vec<PartitionWriter<W>>,
+    /// The index of the column deciding the partition of a row, if the table is partitioned.
+    partition_column: Option<usize>,
+    /// The index into `writers` of every partition name.
+    partition_indices: HashMap<String, usize>,
+    /// Checks derived from the column types, and also the column constraints if validated.
+    checks: Vec<Vec<ColumnCheck>>,
+    /// The buffer holding the values of the current row, reused across rows.
+    values: Vec<Value>,
+    /// Whether `values` already holds the values of the current row, evaluated in a batch by
+    /// [`Env::write_rows()`].
+    evaluated: bool,
+    /// The index of the first `PRIMARY KEY` column identifying the rows in change events.
+    key_column: Option<usize>,
+    /// The namespace of the random number generator deciding whether a row is duplicated.
+    duplicate_namespace: String,
+    /// The namespace of the random number generator deciding the operation of a change event.
+    change_namespace: String,
     /// Records that, within an [`Env::write_row()`] call, whether this table has not been visited
     /// yet (either as a root or derived tables). This member will be reset to `true` at the start
     /// of every `Env::write_row()` call.
     fresh: bool,
-    /// Records if any rows have been written out. This determines whether an INSERT statement is
-    /// needed to be written or not. This member will be reset to `true` after calling
-    /// [`Env::write_trailer()`].
-    empty: bool,
 }
 
+impl<W: Writer> TableState<'_, W> {
+    /// Describes the problem of a value written to a column.
+    fn warning(&self, col_index: usize, kind: WarningKind, row_num: u64, value: &Value) -> Warning {
+        Warning {
+            kind,
+            table: self.schema.name.to_owned(),
+            column: self.schema.column_names().nth(col_index).unwrap_or_default().to_owned(),
+            row_num,
+            value: value.to_string(),
+        }
+    }
+}
+
+/// Creates the writer of a table, or of a shard of a table given its index, or of a partition of a
+/// table given its name.
+type NewWriter<'a, W> = dyn FnMut(&Table, Option<usize>, Option<&str>) -> Result<W, S<Error>> + 'a;
+
 /// An environment for writing rows from multiple tables generated from a single template.
-#[derive(Debug)]
 pub struct Env<'a, W: Writer> {
     state: &'a mut State,
     tables: Vec<TableState<'a, W>>,
+    new_writer: Box<NewWriter<'a, W>>,
+    /// The warnings found since the last [`Env::take_warnings()`] call, if enabled.
+    warnings: Option<Vec<Warning>>,
+    /// The table index, column index and warning kind already reported.
+    reported_warnings: HashSet<(usize, usize, WarningKind)>,
+    /// Checks of the values against the output format, applied to every column.
+    output_checks: Vec<ColumnCheck>,
+    /// Whether values violating the checks are errors.
+    validate_constraints: bool,
+    /// The probability that a row is immediately followed by an exact duplicate.
+    duplicate_rate: f64,
+    /// The weights of inserts, updates and deletes, if change events are emitted instead of rows.
+    change_weights: Option<[f64; 3]>,
+    /// Maximum number of inserted rows retained per table to be updated or deleted.
+    change_retention: usize,
+    /// The buffer holding the values of every column of a batch of rows, see [`Env::write_rows()`].
+    batch: Vec<Vec<Value>>,
+    /// Where and when the rows of sorted tables are spilled to disk.
+    spill: SpillOptions,
+}
+
+impl<W: Writer + fmt::Debug> fmt::Debug for Env<'_, W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Env")
+            .field("state", &self.state)
+            .field("tables", &self.tables)
+            .field("warnings", &self.warnings)
+            .field("reported_warnings", &self.reported_warnings)
+            .field("output_checks", &self.output_checks)
+            .field("validate_constraints", &self.validate_constraints)
+            .field("duplicate_rate", &self.duplicate_rate)
+            .field("change_weights", &self.change_weights)
+            .field("change_retention", &self.change_retention)
+            .field("spill", &self.spill)
+            .finish_non_exhaustive()
+    }
 }
 
 impl<'a, W: Writer> Env<'a, W> {
-    /// Constructs a new row-writing environment.
+    /// Constructs a new row-writing environment, where every table has exactly one writer.
+    ///
+    /// Returns an error if any table is sharded, since its rows must be routed to a writer per
+    /// shard using [`Env::with_partitions()`].
     pub fn new(
         tables: &'a [Table],
         state: &'a mut State,
         qualified: bool,
-        mut new_writer: impl FnMut(&Table) -> Result<W, S<Error>>,
+        mut new_writer: impl FnMut(&Table) -> Result<W, S<Error>> + 'a,
+    ) -> Result<Self, S<Error>> {
+        if let Some(table) = tables.iter().find(|table| table.shards.is_some()) {
+            return Err(Error::ShardsNotRouted {
+                table: table.name.table_name(qualified).to_owned(),
+            }
+            .no_span());
+        }
+        Self::with_partitions(tables, state, qualified, None, move |table, _, _| new_writer(table))
+    }
+
+    /// Constructs a new row-writing environment, where the rows of tables containing the column
+    /// `partition_by` are written to a separate writer per distinct value of that column.
+    ///
+    /// The writers of the partitions are created on demand when their first row is written, by
+    /// calling `new_writer` with the Hive-style partition name like `column=value`. Sharded tables
+    /// are never partitioned, instead `new_writer` is called upfront with the index of every shard.
+    pub fn with_partitions(
+        tables: &'a [Table],
+        state: &'a mut State,
+        qualified: bool,
+        partition_by: Option<&str>,
+        new_writer: impl FnMut(&Table, Option<usize>, Option<&str>) -> Result<W, S<Error>> + 'a,
     ) -> Result<Self, S<Error>> {
+        state.resolve_references(tables).no_span_err()?;
+        let mut new_writer: Box<NewWriter<'a, W>> = Box::new(new_writer);
         Ok(Self {
             tables: tables
                 .iter()
                 .map(|table| {
-                    let mut writer = new_writer(table)?;
                     let schema = table.schema(qualified);
-                    writer.write_file_header(&schema)?;
+                    let sharded = table.shards.is_some();
+                    let partition_column = partition_by
+                        .filter(|_| !sharded)
+                        .and_then(|column| schema.column_names().position(|name| name == column));
+                    let shard_schemas: Vec<_> = if sharded {
+                        table
+                            .shard_indices()
+                            .map(|shard| table.shard_schema(qualified, shard))
+                            .collect()
+                    } else {
+                        Vec::new()
+                    };
+                    let mut writers = Vec::new();
+                    if sharded {
+                        for (shard, shard_schema) in shard_schemas.iter().enumerate() {
+                            let mut writer = new_writer(table, Some(shard), None)?;
+                            writer.write_file_header(shard_schema)?;
+                            writers.push(PartitionWriter::new(writer));
+                        }
+                    } else if partition_column.is_none() {
+                        let mut writer = new_writer(table, None, None)?;
+                        writer.write_file_header(&schema)?;
+                        writers.push(PartitionWriter::new(writer));
+                    }
+                    let checks = schema
+                        .column_types()
+                        .map(|ty| ColumnCheck::from_type(ty).into_iter().collect())
+                        .collect();
+                    let key_column = schema.column_types().position(infer::is_primary_key);
+                    let duplicate_namespace = format!("{DUPLICATE_NAMESPACE}{}", schema.name);
+                    let change_namespace = format!("{CHANGE_NAMESPACE}{}", schema.name);
                     Ok::<_, S<Error>>(TableState {
                         table,
                         schema,
-                        writer,
+                        shard_schemas,
+                        writers,
+                        partition_column,
+                        partition_indices: HashMap::new(),
+                        checks,
+                        values: Vec::new(),
+                        evaluated: false,
+                        key_column,
+                        duplicate_namespace,
+                        change_namespace,
                         fresh: true,
-                        empty: true,
                     })
                 })
                 .collect::<Result<_, _>>()?,
+            new_writer,
             state,
+            warnings: None,
+            reported_warnings: HashSet::new(),
+            output_checks: Vec::new(),
+            validate_constraints: false,
+            duplicate_rate: 0.0,
+            change_weights: None,
+            change_retention: 0,
+            batch: Vec::new(),
+            spill: SpillOptions::default(),
         })
     }
 
+    /// Enables checking the written values against the column types, and against the
+    /// `output_checks` describing the values the output format cannot represent faithfully.
+    ///
+    /// Every kind of problem is only reported once per column. The output checks never fail,
+    /// even if the constraints are validated.
+    pub fn check_warnings(&mut self, output_checks: Vec<ColumnCheck>) {
+        self.warnings.get_or_insert_with(Vec::new);
+        self.output_checks = output_checks;
+    }
+
+    /// Enables validating the written values against the column types and constraints, where a
+    /// violating value is an error instead of a warning.
+    ///
+    /// The columns with constant expressions are validated immediately.
+    pub fn validate_constraints(&mut self) -> Result<(), S<Error>> {
+        self.validate_constraints = true;
+        for table in &mut self.tables {
+            table.checks = table
+                .schema
+                .column_names()
+                .zip(table.schema.column_types())
+                .map(|(name, ty)| ColumnCheck::from_constraints(name, ty))
+                .collect();
+            for (col_index, (checks, compiled)) in table.checks.iter().zip(table.table.row.exprs()).enumerate() {
+                let C::Constant(value) = &compiled.0.inner else {
+                    continue;
+                };
+                if let Some(kind) = checks.iter().find_map(|check| check.check(value)) {
+                    let warning = table.warning(col_index, kind, self.state.row_num, value);
+                    return Err(Error::ConstraintViolation(warning).span(compiled.0.span));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Enables writing an exact duplicate right after a row with the given probability, for
+    /// testing deduplication.
+    ///
+    /// Whether a row is duplicated is decided by a random number generator derived from the table
+    /// name, `rownum` and `subrownum`, so the values of the rows are unaffected.
+    pub fn duplicate_rows(&mut self, rate: f64) {
+        self.duplicate_rate = rate;
+    }
+
+    /// Enables writing change events instead of plain rows, where every row becomes an insert, an
+    /// update or a delete according to the relative `weights` of these operations.
+    ///
+    /// Updates and deletes apply to a random row among the last `retention` rows inserted into
+    /// the same writer, i.e. the same shard or partition of the table, identified by the first `PRIMARY KEY` column. An update keeps the key and
+    /// replaces the other values by the newly generated row. When no inserted rows are retained,
+    /// the row is always inserted.
+    ///
+    /// Fails if any table has no `PRIMARY KEY` column.
+    pub fn change_events(&mut self, weights: [f64; 3], retention: usize) -> Result<(), S<Error>> {
+        if let Some(table) = self.tables.iter().find(|table| table.key_column.is_none()) {
+            return Err(Error::NoPrimaryKey {
+                table: table.schema.name.to_owned(),
+            }
+            .no_span());
+        }
+        self.change_weights = Some(weights);
+        self.change_retention = retention;
+        Ok(())
+    }
+
+    /// Sets where and when the buffered rows of sorted tables are spilled to disk, instead of the
+    /// default of [`SpillOptions`].
+    pub fn spill_sorted_rows(&mut self, options: SpillOptions) {
+        self.spill = options;
+    }
+
+    /// Takes the warnings found since the last call.
+    pub fn take_warnings(&mut self) -> Vec<Warning> {
+        self.warnings.as_mut().map(mem::take).unwrap_or_default()
+    }
+
     /// Returns an iterator of tables and writers associated with this environment.
+    ///
+    /// A partitioned table appears once for every partition written so far.
     pub fn tables(&mut self) -> impl Iterator<Item = (&'a Table, &mut W)> + '_ {
-        self.tables.iter_mut().map(|table| (table.table, &mut table.writer))
+        self.tables.iter_mut().flat_map(|table| {
+            let t = table.table;
+            table.writers.iter_mut().map(move |w| (t, &mut w.writer))
+        })
+    }
+
+    /// Returns an iterator of writers associated with this environment, together with the index
+    /// of their table.
+    pub fn writers(&mut self) -> impl Iterator<Item = (usize, &mut W)> + '_ {
+        self.tables
+            .iter_mut()
+            .enumerate()
+            .flat_map(|(i, table)| table.writers.iter_mut().map(move |w| (i, &mut w.writer)))
+    }
+
+    /// Consumes the environment and returns the writers associated with every table.
+    pub fn into_writers(self) -> Vec<W> {
+        self.tables
+            .into_iter()
+            .flat_map(|table| table.writers.into_iter().map(|w| w.writer))
+            .collect()
+    }
+
+    /// Returns the state used to evaluate the rows.
+    ///
+    /// The state can be replaced to continue writing rows starting from a different row number.
+    /// The references of the new state must be resolved using [`State::resolve_references()`].
+    pub fn state_mut(&mut self) -> &mut State {
+        self.state
     }
 
-    fn write_one_row(&mut self, table_index: usize) -> Result<(), S<Error>> {
+    /// Writes a row of the table, and the rows derived from it.
+    ///
+    /// If the table enumerates combinations, the row takes the `combination_index`-th one, and
+    /// nothing is written after all combinations have been enumerated.
+    ///
+    /// Returns whether the row of the table is written, i.e. it is not filtered out and there are
+    /// combinations left.
+    fn write_one_row(&mut self, table_index: usize, combination_index: u64) -> Result<bool, S<Error>> {
         let table = &mut self.tables[table_index];
+        if !table.table.assign_combination(combination_index, self.state)? {
+            return Ok(false);
+        }
 
-        if mem::take(&mut table.empty) {
-            table.writer.write_header(&table.schema)
-        } else {
-            table.writer.write_row_separator()
-        }?;
+        let mut values = mem::take(&mut table.values);
+        if !mem::take(&mut table.evaluated) {
+            table.table.row.eval_into(self.state, &mut values)?;
+        }
+        if let Some(filter) = &table.table.partition_filter {
+            if !filter.accepts(&values).no_span_err()? {
+                table.values = values;
+                return Ok(false);
+            }
+        }
+        if let Some(filter) = &table.table.row_filter {
+            if !filter.eval(self.state)?.is_sql_true().span_err(filter.0.span)? {
+                table.values = values;
+                return Ok(false);
+            }
+        }
+        self.state.record_row(table_index, &values);
 
-        let values = table.table.row.eval(self.state)?;
+        if self.validate_constraints || self.warnings.is_some() {
+            for (col_index, (checks, value)) in table.checks.iter().zip(&values).enumerate() {
+                let kind = checks.iter().find_map(|check| check.check(value));
+                if let (Some(kind), true) = (kind, self.validate_constraints) {
+                    let warning = table.warning(col_index, kind, self.state.row_num, value);
+                    let span = table.table.row.exprs()[col_index].0.span;
+                    return Err(Error::ConstraintViolation(warning).span(span));
+                }
+                let kind = kind.or_else(|| self.output_checks.iter().find_map(|check| check.check(value)));
+                if let (Some(kind), Some(warnings)) = (kind, &mut self.warnings) {
+                    // only describe the problem once, since the same problem likely repeats in
+                    // many rows.
+                    if self.reported_warnings.insert((table_index, col_index, kind)) {
+                        warnings.push(table.warning(col_index, kind, self.state.row_num, value));
+                    }
+                }
+            }
+        }
 
-        for (col_index, (column, value)) in table.schema.column_names().zip(&values).enumerate() {
-            if col_index != 0 {
-                table.writer.write_value_separator()?;
+        let shard = if table.shard_schemas.is_empty() {
+            None
+        } else {
+            table.table.route_shard(self.state)?
+        };
+        let writer_index = match (shard, table.partition_column) {
+            (Some(shard), _) => shard,
+            (None, None) => 0,
+            (None, Some(col_index)) => {
+                let column = table.schema.column_names().nth(col_index).unwrap_or_default();
+                let span = table.table.row.exprs()[col_index].0.span;
+                let name = partition_name(column, &values[col_index]).span_err(span)?;
+                if let Some(index) = table.partition_indices.get(&name) {
+                    *index
+                } else {
+                    if table.writers.len() >= MAX_PARTITIONS {
+                        return Err(Error::TooManyPartitions {
+                            table: table.schema.name.to_owned(),
+                            limit: MAX_PARTITIONS,
+                        }
+                        .span(span));
+                    }
+                    let mut writer = (self.new_writer)(table.table, None, Some(&name))?;
+                    writer.write_file_header(&table.schema)?;
+                    let index = table.writers.len();
+                    table.writers.push(PartitionWriter::new(writer));
+                    table.partition_indices.insert(name, index);
+                    index
+                }
+            }
+        };
+        let PartitionWriter {
+            writer,
+            empty,
+            sorted_rows,
+            statement_rows,
+            retained_rows,
+            ..
+        } = &mut table.writers[writer_index];
+        let schema = table.shard_schemas.get(writer_index).unwrap_or(&table.schema);
+        if let Some(weights) = self.change_weights {
+            // every table has a key, as checked by `change_events()`.
+            let key_column = table.key_column.unwrap_or_default();
+            let mut rng = self.state.namespaced_rng(&table.change_namespace);
+            let op = if retained_rows.is_empty() {
+                ChangeOp::Insert
+            } else {
+                let mut choice = rng.gen_range(0.0..weights.iter().sum::<f64>());
+                [ChangeOp::Insert, ChangeOp::Update, ChangeOp::Delete]
+                    .into_iter()
+                    .zip(weights)
+                    .find(|(_, weight)| {
+                        choice -= weight;
+                        choice < 0.0
+                    })
+                    .map_or(ChangeOp::Delete, |(op, _)| op)
+            };
+            let mut change = Change {
+                op,
+                key_column,
+                before: None,
+                after: None,
+            };
+            match op {
+                ChangeOp::Insert => {
+                    change.after = Some(&values);
+                    writer.write_change(schema, &change)?;
+                    if retained_rows.len() < self.change_retention {
+                        retained_rows.push(values.clone());
+                    } else if self.change_retention > 0 {
+                        let index = rng.gen_range(0..retained_rows.len());
+                        retained_rows[index].clone_from(&values);
+                    }
+                }
+                ChangeOp::Update => {
+                    let index = rng.gen_range(0..retained_rows.len());
+                    let before = &mut retained_rows[index];
+                    let mut after = values.clone();
+                    if let (Some(new_key), Some(old_key)) = (after.get_mut(key_column), before.get(key_column)) {
+                        new_key.clone_from(old_key);
+                    }
+                    change.before = Some(&*before);
+                    change.after = Some(&after);
+                    writer.write_change(schema, &change)?;
+                    *before = after;
+                }
+                ChangeOp::Delete => {
+                    let index = rng.gen_range(0..retained_rows.len());
+                    let before = retained_rows.swap_remove(index);
+                    change.before = Some(&before);
+                    writer.write_change(schema, &change)?;
+                }
+            }
+        } else if let Some(order_by) = &table.table.order_by {
+            let key = order_by.eval(self.state)?;
+            let is_duplicated = self.duplicate_rate > 0.0
+                && self
+                    .state
+                    .namespaced_rng(&table.duplicate_namespace)
+                    .gen_bool(self.duplicate_rate);
+            if is_duplicated {
+                sorted_rows
+                    .push(key.clone(), values.clone(), &self.spill)
+                    .span_err(order_by.0.span)?;
+                *statement_rows += 1;
+            }
+            sorted_rows
+                .push(key, values.clone(), &self.spill)
+                .span_err(order_by.0.span)?;
+            *statement_rows += 1;
+        } else {
+            if mem::take(empty) {
+                writer.write_header(schema)
+            } else {
+                writer.write_row_separator()
+            }?;
+            write_values(writer, schema, &values)?;
+            if self.duplicate_rate > 0.0
+                && self
+                    .state
+                    .namespaced_rng(&table.duplicate_namespace)
+                    .gen_bool(self.duplicate_rate)
+            {
+                writer.write_row_separator()?;
+                write_values(writer, schema, &values)?;
             }
-            table.writer.write_value_header(column)?;
-            table.writer.write_value(value)?;
         }
 
         for (child, count) in &table.table.derived {
@@ -115,11 +685,12 @@ impl<'a, W: Writer> Env<'a, W> {
 
             for r in 1..=count {
                 self.state.sub_row_num = r;
-                self.write_one_row(*child)?;
+                self.write_one_row(*child, r - 1)?;
             }
         }
 
-        Ok(())
+        self.tables[table_index].values = values;
+        Ok(true)
     }
 
     fn mark_descendant_visited(&mut self, root: usize) {
@@ -132,29 +703,109 @@ impl<'a, W: Writer> Env<'a, W> {
     }
 
     /// Writes one row from each root table
-    pub fn write_row(&mut self) -> Result<(), S<Error>> {
+    ///
+    /// Returns the number of rows of the main (first) table actually written, i.e. 0 if the row is
+    /// filtered out (e.g. by `--where`) and 1 otherwise. Duplicated rows are not counted.
+    pub fn write_row(&mut self) -> Result<u64, S<Error>> {
         for table in &mut self.tables {
             table.fresh = true;
         }
+        let mut written = 0;
         for i in 0..self.tables.len() {
             if self.tables[i].fresh {
                 self.mark_descendant_visited(i);
                 self.state.sub_row_num = 1;
-                self.write_one_row(i)?;
+                let row_index = self.state.row_num - 1;
+                if self.write_one_row(i, row_index)? && i == 0 {
+                    written = 1;
+                }
             }
         }
         self.state.increase_row_num();
-        Ok(())
+        Ok(written)
+    }
+
+    /// Writes `count` rows from each root table, producing the same output as calling
+    /// [`Env::write_row()`] `count` times.
+    ///
+    /// If there is only one table and its rows can be evaluated column-at-a-time (see
+    /// [`Row::eval_columns()`](crate::eval::Row::eval_columns)), the rows are evaluated in batches
+    /// of up to 1024 rows, amortizing the cost of dispatching every expression.
+    ///
+    /// Returns the number of rows of the main table actually written, like [`Env::write_row()`].
+    pub fn write_rows(&mut self, count: u64) -> Result<u64, S<Error>> {
+        let mut columns = mem::take(&mut self.batch);
+        let mut remaining = count;
+        let mut written = 0;
+        while remaining > 0 {
+            // ALLOW_REASON: the length is at most `BATCH_ROWS`.
+            #[allow(clippy::cast_possible_truncation)]
+            let len = remaining.min(BATCH_ROWS as u64) as usize;
+            let batched = match &self.tables[..] {
+                [table] if table.table.is_batchable() => table.table.row.eval_columns(self.state, len, &mut columns),
+                _ => false,
+            };
+            if !batched {
+                for _ in 0..remaining {
+                    written += self.write_row()?;
+                }
+                break;
+            }
+            for i in 0..len {
+                let table = &mut self.tables[0];
+                table.values.clear();
+                table
+                    .values
+                    .extend(columns.iter_mut().map(|column| mem::take(&mut column[i])));
+                table.evaluated = true;
+                written += self.write_row()?;
+            }
+            remaining -= len as u64;
+        }
+        self.batch = columns;
+        Ok(written)
     }
 
     /// Concludes an INSERT statement after writing multiple rows.
     ///
     /// This method delegates to [`Writer::write_trailer()`] if any rows have been written out
     /// previously for a table. Otherwise, if no rows have been written, this method does nothing.
+    ///
+    /// The rows of sorted tables are not written out here, but kept until
+    /// [`Env::write_sorted_rows()`] together with the size of the statement.
     pub fn write_trailer(&mut self) -> Result<(), S<Error>> {
         for table in &mut self.tables {
-            if !mem::replace(&mut table.empty, true) {
-                table.writer.write_trailer()?;
+            let is_sorted = table.table.order_by.is_some();
+            for w in &mut table.writers {
+                if is_sorted {
+                    let statement_rows = mem::take(&mut w.statement_rows);
+                    if statement_rows > 0 {
+                        w.statement_sizes.push(statement_rows);
+                    }
+                } else if !mem::replace(&mut w.empty, true) {
+                    w.writer.write_trailer()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes out the rows of sorted tables buffered since the last call, in the order of their
+    /// keys. This should be called at the end of every data file, so the whole file is sorted.
+    ///
+    /// The rows are written as INSERT statements of the same sizes as they were generated, and
+    /// `end_statement` is called after each statement.
+    pub fn write_sorted_rows(
+        &mut self,
+        mut end_statement: impl FnMut(&mut W) -> Result<(), S<Error>>,
+    ) -> Result<(), S<Error>> {
+        for table in &mut self.tables {
+            let Some(order_by) = &table.table.order_by else {
+                continue;
+            };
+            for (i, w) in table.writers.iter_mut().enumerate() {
+                let schema = table.shard_schemas.get(i).unwrap_or(&table.schema);
+                w.write_sorted_rows(schema, order_by, &mut end_statement)?;
             }
         }
         Ok(())
@@ -8,8 +8,8 @@ use std::{
     env,
     error::Error,
     ffi::OsStr,
-    fs::{read, read_dir, remove_file, File},
-    path::Path,
+    fs::{read, read_dir, remove_dir, remove_file, File},
+    path::{Path, PathBuf},
     str::from_utf8,
 };
 use tempfile::tempdir;
@@ -24,6 +24,10 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     let no_print_diff = env::var_os("DIFF").as_deref() == Some(OsStr::new("0"));
 
+    // read by the `env` test.
+    env::set_var("DBGEN_TEST_NUMBER", "42");
+    env::set_var("DBGEN_TEST_STRING", "x42");
+
     let data_dir = Path::new(file!()).with_file_name("data");
     let zoneinfo_dir = Path::new(file!()).with_file_name("zoneinfo");
     let mut content_differed = false;
@@ -48,36 +52,49 @@ fn main() -> Result<(), Box<dyn Error>> {
             e
         })?;
 
-        for result_entry in read_dir(out_dir.path())? {
-            let result_entry = result_entry?;
-            let expected_path = child_path.join(result_entry.file_name());
-            let actual_path = result_entry.path();
-            eprintln!("Comparing {} vs {} ...", expected_path.display(), actual_path.display());
-            let expected_content = read(expected_path)?;
-            let actual_content = read(&actual_path)?;
-            if expected_content != actual_content {
-                content_differed = true;
-                let expected_string = from_utf8(&expected_content)?;
-                let actual_string = from_utf8(&actual_content)?;
-                if no_print_diff {
-                    eprintln!("\x1b[32m{}\x1b[0m", actual_string);
-                } else {
-                    for diff in lines(&expected_string, &actual_string) {
-                        match diff {
-                            DiffResult::Left(missing) => {
-                                eprintln!("\x1b[31m- {}\x1b[0m", missing);
-                            }
-                            DiffResult::Right(unexpected) => {
-                                eprintln!("\x1b[32m+ {}\x1b[0m", unexpected);
-                            }
-                            DiffResult::Both(same, _) => {
-                                eprintln!("  {}", same);
+        let mut pending_dirs = vec![PathBuf::new()];
+        let mut result_dirs = Vec::new();
+        while let Some(result_dir) = pending_dirs.pop() {
+            for result_entry in read_dir(out_dir.path().join(&result_dir))? {
+                let result_entry = result_entry?;
+                let relative_path = result_dir.join(result_entry.file_name());
+                let actual_path = result_entry.path();
+                if result_entry.file_type()?.is_dir() {
+                    pending_dirs.push(relative_path);
+                    result_dirs.push(actual_path);
+                    continue;
+                }
+                let expected_path = child_path.join(relative_path);
+                eprintln!("Comparing {} vs {} ...", expected_path.display(), actual_path.display());
+                let expected_content = read(expected_path)?;
+                let actual_content = read(&actual_path)?;
+                if expected_content != actual_content {
+                    content_differed = true;
+                    let expected_string = from_utf8(&expected_content)?;
+                    let actual_string = from_utf8(&actual_content)?;
+                    if no_print_diff {
+                        eprintln!("\x1b[32m{}\x1b[0m", actual_string);
+                    } else {
+                        for diff in lines(&expected_string, &actual_string) {
+                            match diff {
+                                DiffResult::Left(missing) => {
+                                    eprintln!("\x1b[31m- {}\x1b[0m", missing);
+                                }
+                                DiffResult::Right(unexpected) => {
+                                    eprintln!("\x1b[32m+ {}\x1b[0m", unexpected);
+                                }
+                                DiffResult::Both(same, _) => {
+                                    eprintln!("  {}", same);
+                                }
                             }
                         }
                     }
                 }
+                remove_file(actual_path)?;
             }
-            remove_file(actual_path)?;
+        }
+        for result_dir in result_dirs.into_iter().rev() {
+            remove_dir(result_dir)?;
         }
     }
 
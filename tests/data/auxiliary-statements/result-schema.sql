@@ -0,0 +1,5 @@
+CREATE TABLE result (
+    id INT NOT NULL,
+    code VARCHAR(10) NOT NULL);
+CREATE UNIQUE INDEX result_code ON result (code);
+ALTER TABLE result ADD CONSTRAINT result_pkey PRIMARY KEY (id);
@@ -0,0 +1,6 @@
+CREATE TABLE result (
+    id INT NOT NULL {{ rownum }},
+    code VARCHAR(10) NOT NULL {{ 'c' || rownum }}
+);
+CREATE UNIQUE INDEX result_code ON result (code);
+ALTER TABLE result ADD CONSTRAINT result_pkey PRIMARY KEY (id);
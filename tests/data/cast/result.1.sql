@@ -0,0 +1,17 @@
+INSERT INTO result SET
+int_from_string = 42,
+int_from_float = -3,
+int_from_decimal = 12,
+unsigned = 255,
+double_from_string = 1500.0,
+real = 0.10000000149011612,
+decimal = 3.14,
+bool_from_string = 1,
+bool_from_number = 0,
+truncated_text = '123',
+text_from_timestamp = '2024-01-02 03:04:05',
+timestamp_from_date = '2024-01-02 00:00:00',
+date_from_string = '2024-02-29',
+null = NULL,
+function_call = 7;
+
@@ -0,0 +1,17 @@
+create table result (
+    int_from_string     {{ CAST('42' AS INT) }}
+    int_from_float      {{ CAST(-3.9 AS BIGINT) }}
+    int_from_decimal    {{ CAST(' 12.7 ' AS SMALLINT) }}
+    unsigned            {{ CAST('255' AS TINYINT UNSIGNED) }}
+    double_from_string  {{ CAST('1.5e3' AS DOUBLE PRECISION) }}
+    real                {{ CAST(0.1 AS REAL) }}
+    decimal             {{ CAST('3.14159' AS DECIMAL(5, 2)) }}
+    bool_from_string    {{ CAST('yes' AS BOOLEAN) }}
+    bool_from_number    {{ CAST(0.0 AS BOOLEAN) }}
+    truncated_text      {{ CAST(123456 AS CHAR(3)) }}
+    text_from_timestamp {{ CAST(TIMESTAMP '2024-01-02 03:04:05' AS TEXT) }}
+    timestamp_from_date {{ CAST(DATE '2024-01-02' AS TIMESTAMP) }}
+    date_from_string    {{ CAST('2024-02-29' AS DATE) }}
+    null                {{ CAST(NULL AS INT) }}
+    function_call       {{ cast('7', 'int') }}
+);
@@ -0,0 +1 @@
+CREATE TABLE t (id INT PRIMARY KEY {{ rownum }}, "name" TEXT {{ 'n' || rownum }});
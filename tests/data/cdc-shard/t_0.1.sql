@@ -0,0 +1,3 @@
+INSERT INTO t_0 VALUES
+(20, 2);
+UPDATE t_0 SET v = 40 WHERE id = 2;
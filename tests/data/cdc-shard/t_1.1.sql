@@ -0,0 +1,3 @@
+INSERT INTO t_1 VALUES
+(10, 1);
+UPDATE t_1 SET v = 30 WHERE id = 1;
@@ -0,0 +1,2 @@
+{{ shard into 2 tables by @k }}
+CREATE TABLE t (v INT {{ rownum * 10 }}, id INT PRIMARY KEY {{ @k := rownum }});
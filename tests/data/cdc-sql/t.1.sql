@@ -0,0 +1,4 @@
+INSERT INTO t VALUES
+(10, 1);
+UPDATE t SET v = 20 WHERE id = 1;
+UPDATE t SET v = 30 WHERE id = 1;
@@ -0,0 +1 @@
+CREATE TABLE t (v INT {{ rownum * 10 }}, id INT PRIMARY KEY {{ rownum }});
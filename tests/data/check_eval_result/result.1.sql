@@ -122,6 +122,13 @@ coalesce_12 = 1,
 coalesce_1n = 1,
 coalesce_n2 = 2,
 coalesce_nn = NULL,
+ifnull_12 = 1,
+ifnull_n2 = 2,
+nvl_nn = NULL,
+nullif_11 = NULL,
+nullif_12 = 1,
+nullif_1n = 1,
+nullif_n1 = NULL,
 semicolon = 600,
 bit_and = 64,
 bit_or = 95,
@@ -171,6 +171,13 @@ CREATE TABLE result (
     coalesce_1n {{ coalesce(1, null) }}
     coalesce_n2 {{ coalesce(null, 2) }}
     coalesce_nn {{ coalesce(null, null) }}
+    ifnull_12   {{ ifnull(1, 2) }}
+    ifnull_n2   {{ ifnull(null, 2) }}
+    nvl_nn      {{ nvl(null, null) }}
+    nullif_11   {{ nullif(1, 1.0) }}
+    nullif_12   {{ nullif(1, 2) }}
+    nullif_1n   {{ nullif(1, null) }}
+    nullif_n1   {{ nullif(null, 1) }}
 
     semicolon   {{ @e := 567; @f := @e - 7; @f + 40 }}
 
@@ -0,0 +1,7 @@
+CREATE TABLE result (
+    a UInt64 {{ rownum }},
+    b String {{ 'x\y' || rownum }},
+    c Nullable(String) {{ CASE WHEN rownum = 1 THEN NULL ELSE 'it''s' END }},
+    d Array(Nullable(String)) {{ ARRAY['p''q', NULL] }},
+    e DateTime {{ TIMESTAMP '2024-01-01 00:00:00' }}
+);
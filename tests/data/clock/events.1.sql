@@ -0,0 +1,4 @@
+INSERT INTO events VALUES
+(1, '2024-01-01 00:00:00.500', '2024-01-01 00:00:00.750');
+INSERT INTO events VALUES
+(2, '2024-01-01 00:00:00.770', '2024-01-01 00:00:01.020');
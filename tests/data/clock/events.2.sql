@@ -0,0 +1,4 @@
+INSERT INTO events VALUES
+(3, '2024-01-01 00:00:01.040', '2024-01-01 00:00:01.290');
+INSERT INTO events VALUES
+(4, '2024-01-01 00:00:01.310', '2024-01-01 00:00:01.560');
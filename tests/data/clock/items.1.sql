@@ -0,0 +1,6 @@
+INSERT INTO items VALUES
+(1, '2024-01-01 00:00:00.750'),
+(1, '2024-01-01 00:00:00.760');
+INSERT INTO items VALUES
+(2, '2024-01-01 00:00:01.020'),
+(2, '2024-01-01 00:00:01.030');
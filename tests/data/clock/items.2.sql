@@ -0,0 +1,6 @@
+INSERT INTO items VALUES
+(3, '2024-01-01 00:00:01.290'),
+(3, '2024-01-01 00:00:01.300');
+INSERT INTO items VALUES
+(4, '2024-01-01 00:00:01.560'),
+(4, '2024-01-01 00:00:01.570');
@@ -0,0 +1,11 @@
+CREATE TABLE events (
+    id INT {{ rownum }},
+    started_at TIMESTAMP {{ clock.tick(INTERVAL 250 MILLISECOND) }},
+    now TIMESTAMP {{ clock.now() }}
+);
+
+{{ for each row of events generate 2 rows of items }}
+CREATE TABLE items (
+    event_id INT {{ rownum }},
+    created_at TIMESTAMP {{ clock.tick(INTERVAL 10 MILLISECOND) }}
+);
@@ -0,0 +1,3 @@
+INSERT INTO t VALUES
+(1, NULL, 'none', 100, 7, NULL),
+(2, NULL, 'none', 200, 7, NULL);
@@ -0,0 +1,9 @@
+CREATE TABLE t (
+    id INT {{ rownum }},
+    a INT /*#dbgen null=1 */ {{ rownum * 10 }},
+    b VARCHAR(10) DEFAULT 'none' /*#dbgen default=1 */ {{ 'x' || rownum }},
+    c INT NOT NULL DEFAULT -1 /*#dbgen null=0 default=0 */ {{ rownum * 100 }},
+    d BIGINT DEFAULT 7 /*#dbgen default=1 */ NOT NULL {{ rownum }},
+    /*#dbgen*/ /*#dbgen null=1 */
+    e TEXT /* #dbgen null=1 is an ordinary comment */ {{ 'e' }}
+);
@@ -0,0 +1,8 @@
+INSERT INTO result VALUES
+(1, 'red', 1),
+(2, 'red', 2),
+(3, 'red', 3),
+(4, 'blue', 1);
+INSERT INTO result VALUES
+(5, 'blue', 2),
+(6, 'blue', 3);
@@ -0,0 +1,6 @@
+/*{{ for each combination of @color in ARRAY['red', 'blue'], @size in generate_series(1, 3) }}*/
+CREATE TABLE result (
+    id INT {{ rownum }},
+    color TEXT {{ @color }},
+    size INT {{ @size }}
+);
@@ -0,0 +1,4 @@
+CREATE TABLE result (
+    a INT,
+    b INT,
+    c INT);
@@ -0,0 +1,5 @@
+CREATE TABLE result (
+    a INT {{ rownum }},
+    b INT {{ rownum * 10 }},
+    c INT {{ rownum * 100 }}
+);
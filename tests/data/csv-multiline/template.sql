@@ -0,0 +1,6 @@
+CREATE TABLE result (
+    id INT {{ rownum }},
+    plain TEXT {{ 'abc' }},
+    lines TEXT {{ 'l1
+l2' }}
+);
@@ -0,0 +1,9 @@
+CREATE TABLE result (
+    id INT,
+    plain TEXT,
+    `pipe|name` TEXT,
+    quoted TEXT,
+    lines TEXT,
+    empty TEXT,
+    null_text TEXT,
+    missing TEXT);
@@ -0,0 +1,11 @@
+CREATE TABLE result (
+    id INT {{ rownum }},
+    plain TEXT {{ 'abc' }},
+    `pipe|name` TEXT {{ 'a|b' }},
+    quoted TEXT {{ 'x"y' }},
+    lines TEXT {{ 'l1
+l2' }},
+    empty TEXT {{ '' }},
+    null_text TEXT {{ 'NULL' }},
+    missing TEXT {{ NULL }}
+);
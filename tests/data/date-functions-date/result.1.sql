@@ -0,0 +1,2 @@
+INSERT INTO result VALUES
+(15, 0, '2024-07-01 00:00:00', '2024-02-29 00:00:00', '2024-07-15 01:30:00', '2024-02-29 00:00:00', '2024/07/15 00:00');
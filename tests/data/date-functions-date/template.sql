@@ -0,0 +1,9 @@
+CREATE TABLE result (
+    {{ extract(day from DATE '2024-07-15') }}
+    {{ extract(hour from DATE '2024-07-15') }}
+    {{ date_trunc('month', DATE '2024-07-15') }}
+    {{ date_add(DATE '2024-01-31', 1, 'month') }}
+    {{ date_add(DATE '2024-07-15', 90, 'minute') }}
+    {{ last_day(DATE '2024-02-10') }}
+    {{ format_timestamp(DATE '2024-07-15', '%Y/%m/%d %H:%M') }}
+);
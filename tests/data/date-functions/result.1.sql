@@ -0,0 +1,6 @@
+INSERT INTO result VALUES
+('2024-03-02 13:45:30', '2024-03-01 00:00:00', '2024-02-26 00:00:00', 6, 62, '2024-04-02 13:45:30', '2024-03-31 00:00:00', '2024-02-29 00:00:00'),
+('2024-03-19 13:45:30', '2024-03-01 00:00:00', '2024-03-18 00:00:00', 2, 79, '2024-04-19 13:45:30', '2024-03-31 00:00:00', '2024-03-31 00:00:00'),
+('2024-04-05 14:45:30', '2024-04-01 00:00:00', '2024-04-01 00:00:00', 5, 96, '2024-05-05 14:45:30', '2024-04-30 00:00:00', '2024-04-30 00:00:00'),
+('2024-04-22 14:45:30', '2024-04-01 00:00:00', '2024-04-22 00:00:00', 1, 113, '2024-05-22 14:45:30', '2024-04-30 00:00:00', '2024-05-31 00:00:00'),
+('2024-05-09 14:45:30', '2024-05-01 00:00:00', '2024-05-06 00:00:00', 4, 130, '2024-06-09 14:45:30', '2024-05-31 00:00:00', '2024-06-30 00:00:00');
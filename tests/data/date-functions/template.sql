@@ -0,0 +1,10 @@
+CREATE TABLE result (
+    {{ @ts := TIMESTAMP '2024-02-14 13:45:30' + INTERVAL 17 * rownum DAY }}
+    {{ date_trunc('month', @ts) }}
+    {{ date_trunc('week', @ts) }}
+    {{ extract(dow from @ts) }}
+    {{ extract('doy', @ts) }}
+    {{ date_add(@ts, 1, 'month') }}
+    {{ last_day(@ts) }}
+    {{ date_add(TIMESTAMP '2024-01-31 00:00:00', rownum, 'month') }}
+);
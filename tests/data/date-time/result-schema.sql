@@ -0,0 +1,6 @@
+CREATE TABLE result (
+    d DATE,
+    t TIME,
+    date_of_ts DATE,
+    time_of_ts TIME,
+    days_between INT);
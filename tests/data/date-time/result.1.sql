@@ -0,0 +1,4 @@
+INSERT INTO result VALUES
+('2024-02-28', '23:59:45', '2024-02-29', '12:34:56.500000', 1),
+('2024-02-29', '00:00:00', '2024-02-28', '12:34:56.500000', 1),
+('2024-03-01', '00:00:15', '2024-02-27', '12:34:56.500000', 1);
@@ -0,0 +1,7 @@
+CREATE TABLE result (
+    d DATE /*{{ DATE '2024-02-27' + INTERVAL rownum DAY }}*/,
+    t TIME /*{{ TIME '23:59:30' + INTERVAL 15 * rownum SECOND }}*/,
+    date_of_ts DATE /*{{ DATE TIMESTAMP '2024-03-01 23:34:56' - INTERVAL rownum DAY }}*/,
+    time_of_ts TIME /*{{ TIME TIMESTAMP '2024-03-01 12:34:56.5' }}*/,
+    days_between INT /*{{ DATE '2024-03-01' - DATE '2024-02-01' = INTERVAL 29 DAY }}*/
+);
@@ -0,0 +1 @@
+CREATE TABLE t (id INT PRIMARY KEY {{ rownum }}, ts TIMESTAMP {{ TIMESTAMP '2024-01-01 04:00:00.5' }});
@@ -0,0 +1,12 @@
+INSERT INTO result SET
+exact = 12345678901234567890.0123456789,
+tiny = 0.0000001000,
+rounded = -2.35,
+integral = 42,
+sum = 0.3,
+difference = 99.5,
+product = 4.5,
+quotient = 3.3333,
+remainder = 1.5,
+equal = 1;
+
@@ -0,0 +1,12 @@
+create table result (
+    exact       {{ decimal('12345678901234567890.0123456789', 38, 10) }}
+    tiny        {{ decimal(1e-7, 38, 10) }}
+    rounded     {{ decimal(-2.345, 5, 2) }}
+    integral    {{ decimal(42, 10) }}
+    sum         {{ decimal(0.1, 5, 1) + decimal(0.2, 5, 1) }}
+    difference  {{ decimal('100.00', 10, 2) - 0.5 }}
+    product     {{ decimal(1.5, 5, 1) * 3 }}
+    quotient    {{ decimal(10, 5) / 3 }}
+    remainder   {{ mod(decimal(7.5, 5, 1), 2) }}
+    equal       {{ decimal(2, 5, 1) = 2 }}
+);
@@ -0,0 +1,5 @@
+CREATE TABLE [result] (
+    [a`b] INT,
+    c NVARCHAR(10),
+    d VARBINARY(2),
+    e BIT);
@@ -0,0 +1,3 @@
+INSERT INTO [result] ([a`b], c, d, e) VALUES
+(1, N'it''s', 0x0102, 1),
+(2, N'it''s', 0x0102, 0);
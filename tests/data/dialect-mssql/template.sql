@@ -0,0 +1,6 @@
+CREATE TABLE `result` (
+    `a``b` INT {{ rownum }},
+    c NVARCHAR(10) {{ 'it''s' }},
+    d VARBINARY(2) {{ from_hex('0102') }},
+    e BIT {{ rownum = 1 }}
+);
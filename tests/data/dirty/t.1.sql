@@ -0,0 +1,3 @@
+INSERT INTO t VALUES
+(10, 1, 1, 'abc', '2024-01-01'),
+(20, 2, 2, 'abc', '2024-01-01');
@@ -0,0 +1,7 @@
+CREATE TABLE t (
+    a INT {{ dirty.swap(@a := rownum, @b := rownum * 10, 1) }},
+    b INT {{ dirty.swap(@b, @a, 1) }},
+    c INT {{ dirty.wrong_type(@a, 0) }},
+    d TEXT {{ dirty.truncate('abc', 0) }},
+    e TEXT {{ dirty.out_of_range('2024-01-01', 0.0) }}
+);
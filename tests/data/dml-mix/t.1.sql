@@ -0,0 +1,7 @@
+INSERT INTO t SET
+id = 1,
+v = 10,
+w = -1;
+
+UPDATE t SET v = 20, w = -2 WHERE id = 1;
+UPDATE t SET v = 30, w = -3 WHERE id = 1;
@@ -0,0 +1 @@
+CREATE TABLE t (id INT PRIMARY KEY {{ rownum }}, v INT {{ rownum * 10 }}, w INT {{ -rownum }});
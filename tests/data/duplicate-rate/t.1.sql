@@ -0,0 +1,5 @@
+INSERT INTO t VALUES
+(1),
+(1),
+(2),
+(2);
@@ -0,0 +1,4 @@
+CREATE TABLE t (id INT {{ rownum }});
+
+{{ for each row of t generate 1 row of u }}
+CREATE TABLE u (t_id INT {{ rownum }}, sub INT {{ subrownum }});
@@ -0,0 +1,5 @@
+INSERT INTO u VALUES
+(1, 1),
+(1, 1),
+(2, 1),
+(2, 1);
@@ -0,0 +1,2 @@
+INSERT INTO result VALUES
+('fallback', TRUE, 43, 'x42');
@@ -0,0 +1,6 @@
+CREATE TABLE result (
+    a {{ env('DBGEN_TEST_UNDEFINED_VARIABLE', 'fallback') }},
+    b {{ env('DBGEN_TEST_UNDEFINED_VARIABLE') IS NULL }},
+    c {{ env('DBGEN_TEST_NUMBER', 'fallback') + 1 }},
+    d {{ env('DBGEN_TEST_STRING') }}
+);
@@ -0,0 +1,8 @@
+SET foreign_key_checks = 0;
+BEGIN;
+INSERT INTO result VALUES
+(1);
+INSERT INTO result VALUES
+(2);
+COMMIT;
+ANALYZE TABLE result;
@@ -0,0 +1,6 @@
+CREATE TABLE result (
+    id INT {{ rownum }},
+    code CHAR(3) {{ 'AB' || rownum }},
+    amount DECIMAL(5, 2) {{ rownum * 25 }},
+    note TEXT {{ CASE rownum WHEN 1 THEN 'truncated text' END }}
+);
@@ -0,0 +1,3 @@
+CREATE TABLE result (
+    ts TIMESTAMPTZ,
+    summer_ts TIMESTAMPTZ);
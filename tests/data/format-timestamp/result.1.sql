@@ -0,0 +1,3 @@
+INSERT INTO result VALUES
+('2024-01-01T13:00:00+08:00', '1979-07-01T12:00:00.500000+09:00'),
+('2024-01-01T14:00:00+08:00', '1979-07-01T12:00:00.500000+09:00');
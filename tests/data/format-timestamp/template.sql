@@ -0,0 +1,4 @@
+CREATE TABLE result (
+    ts TIMESTAMPTZ /*{{ TIMESTAMP '2024-01-01 12:00:00' + INTERVAL rownum HOUR }}*/,
+    summer_ts TIMESTAMPTZ /*{{ TIMESTAMP '1979-07-01 12:00:00.5' }}*/
+);
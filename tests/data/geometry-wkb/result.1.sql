@@ -0,0 +1,2 @@
+INSERT INTO result VALUES
+(ST_GeomFromWKB('\x0101000000000000000000F83F0000000000000040'));
@@ -0,0 +1,3 @@
+CREATE TABLE result (
+    bbox        {{ rand.point_in_bbox(1.5, 2, 1.5, 2) }}
+);
@@ -0,0 +1,2 @@
+INSERT INTO result VALUES
+('POINT(1.5 2)', 'POINT(-10 20.25)', 'at POINT(0 0)');
@@ -0,0 +1,5 @@
+CREATE TABLE result (
+    bbox        {{ rand.point_in_bbox(1.5, 2, 1.5, 2) }}
+    near        {{ rand.point_near(-10, 20.25, 0) }}
+    concat      {{ 'at ' || rand.point_in_bbox(0, 0, 0, 0) }}
+);
@@ -0,0 +1,8 @@
+INSERT INTO result SET
+md5 = '900150983cd24fb0d6963f7d28e17f72',
+md5_empty = 'd41d8cd98f00b204e9800998ecf8427e',
+sha1 = 'a9993e364706816aba3e25717850c26c9cd0d89d',
+sha256 = 'ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad',
+crc32 = 3421780262,
+fnv64 = 12638187200555641996,
+fnv64_bytes = 12638187200555641996;
@@ -0,0 +1,9 @@
+CREATE TABLE result (
+    md5         {{ md5('abc') }}
+    md5_empty   {{ md5('') }}
+    sha1        {{ sha1('abc') }}
+    sha256      {{ sha256('abc') }}
+    crc32       {{ crc32('123456789') }}
+    fnv64       {{ fnv64('a') }}
+    fnv64_bytes {{ fnv64(X'61') }}
+);
@@ -0,0 +1,4 @@
+INSERT INTO result VALUES
+(1, '1', 1, 'n1'),
+(2, '2', 2, 'n2'),
+(3, '3', 3, 'n3');
@@ -0,0 +1,7 @@
+CREATE TABLE result (
+    id INT PRIMARY KEY,
+    code VARCHAR(20) UNIQUE NOT NULL,
+    seq BIGSERIAL,
+    note TEXT,
+    PRIMARY KEY (id)
+);
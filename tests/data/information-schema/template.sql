@@ -0,0 +1,11 @@
+create table parent (
+    id INTEGER NOT NULL {{ rownum }},
+    name VARCHAR(20) {{ 'x' }},
+    price DECIMAL(10, 2) DEFAULT 0 {{ 1.5 }}
+);
+
+{{ for each row of parent generate 2 rows of child }}
+create table child (
+    parent_id int primary key {{ rownum }}
+    {{ subrownum }}
+);
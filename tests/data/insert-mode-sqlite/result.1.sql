@@ -0,0 +1,3 @@
+INSERT OR IGNORE INTO result VALUES
+(1),
+(2);
@@ -0,0 +1,3 @@
+CREATE TABLE result (
+    a {{ rownum }}
+);
@@ -0,0 +1,4 @@
+INSERT INTO result VALUES
+(1, 'n1'),
+(2, 'n2')
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,4 @@
+CREATE TABLE result (
+    id INT PRIMARY KEY {{ rownum }},
+    name TEXT {{ 'n' || rownum }}
+);
@@ -0,0 +1,5 @@
+CREATE TABLE result (
+    doc     {{ json.object('id', rownum, 'name', 'it''s "quoted"', 'ok', rownum = 1, 'tags', json.array('a', NULL, 2.5)) }}
+    nested  {{ json.object('inner', json.parse('{"x": [1, {"y": null}]}'), 'list', ARRAY[1, 2]) }}
+    point   {{ json.array(rand.point_in_bbox(1.5, 2, 1.5, 2)) }}
+);
@@ -0,0 +1,5 @@
+INSERT INTO result VALUES
+(10, 10, FALSE, 1),
+(2, 10, FALSE, 2),
+(30, 30, TRUE, 2),
+(4, 30, TRUE, 2);
@@ -0,0 +1,6 @@
+CREATE TABLE result (
+    {{ coalesce(CASE WHEN mod(rownum, 2) = 0 THEN rownum END, @a := rownum * 10) }}
+    {{ @a }}
+    {{ rownum > 2 OR (@b := rownum) IS NULL }}
+    {{ @b }}
+);
@@ -0,0 +1,7 @@
+INSERT INTO result SET
+a = '🥰😘',
+b = '🥰😘😍',
+c = '',
+d = 12345,
+e = NULL;
+
@@ -0,0 +1,7 @@
+create table result (
+    a {{ limit('🥰😘😍', 2) }}
+    b {{ limit('🥰😘😍', 3, 'error') }}
+    c {{ limit('abc', 0, 'truncate') }}
+    d {{ limit(12345, 2) }}
+    e {{ limit(NULL, 2, 'error') }}
+);
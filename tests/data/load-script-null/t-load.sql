@@ -0,0 +1 @@
+LOAD DATA LOCAL INFILE 't.1.csv' INTO TABLE t CHARACTER SET utf8mb4 FIELDS TERMINATED BY ',' OPTIONALLY ENCLOSED BY '"' ESCAPED BY '' LINES TERMINATED BY '\n' (@c1, @c2) SET a = NULLIF(@c1, '\\N'), b = NULLIF(@c2, '\\N');
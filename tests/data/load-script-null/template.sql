@@ -0,0 +1,4 @@
+CREATE TABLE t (
+    a INT {{ rownum }},
+    b TEXT {{ CASE rownum WHEN 1 THEN NULL ELSE 'x' END }}
+);
@@ -0,0 +1 @@
+\copy t (a, "b c") FROM 't.1.csv' WITH (FORMAT csv, HEADER true, DELIMITER ',', NULL '\N', ENCODING 'UTF8')
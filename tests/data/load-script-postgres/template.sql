@@ -0,0 +1,4 @@
+CREATE TABLE t (
+    a INT {{ rownum }},
+    "b c" TEXT {{ 'x' }}
+);
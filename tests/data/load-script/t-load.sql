@@ -0,0 +1,2 @@
+LOAD DATA LOCAL INFILE 't.1000.csv' INTO TABLE t CHARACTER SET utf8mb4 FIELDS TERMINATED BY ',' OPTIONALLY ENCLOSED BY '"' ESCAPED BY '' LINES TERMINATED BY '\r\n' IGNORE 1 LINES (@c1, @c2) SET a = NULLIF(@c1, '\\N'), `b c` = NULLIF(@c2, '\\N');
+LOAD DATA LOCAL INFILE 't.1001.csv' INTO TABLE t CHARACTER SET utf8mb4 FIELDS TERMINATED BY ',' OPTIONALLY ENCLOSED BY '"' ESCAPED BY '' LINES TERMINATED BY '\r\n' IGNORE 1 LINES (@c1, @c2) SET a = NULLIF(@c1, '\\N'), `b c` = NULLIF(@c2, '\\N');
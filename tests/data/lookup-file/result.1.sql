@@ -0,0 +1,5 @@
+INSERT INTO result VALUES
+(1, 'Widget, large', 9.5),
+(2, 'Gadget', 12),
+(3, 'Gizmo "XL"', NULL),
+(4, NULL, NULL);
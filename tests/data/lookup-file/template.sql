@@ -0,0 +1,5 @@
+CREATE TABLE result (
+    {{ rownum }}
+    {{ lookup_file('tests/data/lookup-file/skus.csv', rownum, 'name') }}
+    {{ lookup_file('tests/data/lookup-file/skus.csv', rownum, 'price') }}
+);
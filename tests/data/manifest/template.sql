@@ -0,0 +1,3 @@
+CREATE TABLE t (
+    c1 {{ rownum }}
+);
@@ -0,0 +1,4 @@
+CREATE TABLE result (
+    id INT {{ rownum }},
+    name TEXT {{ 'n' || rownum }}
+);
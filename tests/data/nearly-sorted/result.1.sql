@@ -0,0 +1,31 @@
+INSERT INTO result VALUES
+(TRUE, TRUE, TRUE, TRUE),
+(TRUE, TRUE, TRUE, TRUE),
+(TRUE, TRUE, TRUE, TRUE),
+(TRUE, TRUE, TRUE, TRUE),
+(TRUE, TRUE, TRUE, TRUE),
+(TRUE, TRUE, TRUE, TRUE),
+(TRUE, TRUE, TRUE, TRUE),
+(TRUE, TRUE, TRUE, TRUE),
+(TRUE, TRUE, TRUE, TRUE),
+(TRUE, TRUE, TRUE, TRUE),
+(TRUE, TRUE, TRUE, TRUE),
+(TRUE, TRUE, TRUE, TRUE),
+(TRUE, TRUE, TRUE, TRUE),
+(TRUE, TRUE, TRUE, TRUE),
+(TRUE, TRUE, TRUE, TRUE),
+(TRUE, TRUE, TRUE, TRUE),
+(TRUE, TRUE, TRUE, TRUE),
+(TRUE, TRUE, TRUE, TRUE),
+(TRUE, TRUE, TRUE, TRUE),
+(TRUE, TRUE, TRUE, TRUE),
+(TRUE, TRUE, TRUE, TRUE),
+(TRUE, TRUE, TRUE, TRUE),
+(TRUE, TRUE, TRUE, TRUE),
+(TRUE, TRUE, TRUE, TRUE),
+(TRUE, TRUE, TRUE, TRUE),
+(TRUE, TRUE, TRUE, TRUE),
+(TRUE, TRUE, TRUE, TRUE),
+(TRUE, TRUE, TRUE, TRUE),
+(TRUE, TRUE, TRUE, TRUE),
+(TRUE, TRUE, TRUE, TRUE);
@@ -0,0 +1,13 @@
+CREATE TABLE result (
+    {{ nearly_sorted(rownum, 1) = rownum }}
+    {{ @n := nearly_sorted(rownum, 10); div(@n - 1, 10) = div(rownum - 1, 10) }}
+    {{
+        @seen := CASE WHEN mod(rownum, 10) = 1 THEN 0 ELSE @seen END
+            | ARRAY[1, 2, 4, 8, 16, 32, 64, 128, 256, 512][mod(@n - 1, 10) + 1];
+        mod(rownum, 10) <> 0 OR @seen = 1023
+    }}
+    {{
+        @moved := coalesce(@moved, FALSE) OR @n <> rownum;
+        rownum < 30 OR @moved
+    }}
+);
@@ -0,0 +1,10 @@
+INSERT INTO result SET
+inet_aton = 3232235521,
+inet_aton_bad = NULL,
+float_to_bits = 4609434218613702656,
+float_to_bits_n = 13835058055282163712,
+bits_to_float = 1.5,
+int_to_hex = '00FF',
+int_to_hex_n = 'FFFFFFFFFFFFFFFF',
+int_to_hex_ip = '0A000001';
+
@@ -0,0 +1,10 @@
+CREATE TABLE result (
+    inet_aton       {{ inet_aton('192.168.0.1') }}
+    inet_aton_bad   {{ inet_aton('999.1.1.1') }}
+    float_to_bits   {{ float_to_bits(1.5) }}
+    float_to_bits_n {{ float_to_bits(-2.0) }}
+    bits_to_float   {{ bits_to_float(4609434218613702656) }}
+    int_to_hex      {{ int_to_hex(255, 4) }}
+    int_to_hex_n    {{ int_to_hex(-1) }}
+    int_to_hex_ip   {{ int_to_hex(inet_aton('10.0.0.1'), 8) }}
+);
@@ -0,0 +1,3 @@
+INSERT INTO t ("b c", id) VALUES
+(10, 1),
+(20, 2);
@@ -0,0 +1,5 @@
+CREATE TABLE t (
+    id INT {{ rownum }},
+    "b c" INT {{ rownum * 10 }},
+    d TEXT {{ 'x' }}
+);
@@ -0,0 +1,3 @@
+INSERT INTO t (id, b) VALUES
+(1, 11),
+(2, 21);
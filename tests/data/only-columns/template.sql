@@ -0,0 +1,6 @@
+CREATE TABLE t (
+    id INT {{ rownum }},
+    a INT {{ @a := rownum * 10 }},
+    b INT {{ @a + 1 }},
+    c TEXT {{ 'x' }}
+);
@@ -0,0 +1,8 @@
+INSERT INTO t VALUES
+(5, 0),
+(3, 1),
+(1, 2);
+INSERT INTO t VALUES
+(6, 2),
+(4, 3),
+(2, 4);
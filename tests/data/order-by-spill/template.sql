@@ -0,0 +1,10 @@
+CREATE TABLE t (
+    id INT {{ rownum }},
+    k INT {{ @k := mod(rownum * 7, 5) }}
+);
+
+{{ for each row of t generate 2 rows of u }}
+CREATE TABLE u (
+    t_id INT {{ rownum }},
+    sub INT {{ @k := -subrownum; subrownum }}
+);
@@ -0,0 +1,14 @@
+INSERT INTO u VALUES
+(6, 1),
+(6, 2),
+(5, 1),
+(5, 2),
+(4, 1),
+(4, 2);
+INSERT INTO u VALUES
+(3, 1),
+(3, 2),
+(2, 1),
+(2, 2),
+(1, 1),
+(1, 2);
@@ -0,0 +1,4 @@
+INSERT INTO result VALUES
+(1, 'shipped', 'item-1'),
+(2, 'shipped', 'item-2'),
+(3, 'shipped', 'item-3');
@@ -0,0 +1,6 @@
+/*{{ @prefix := 'item-' }}*/
+CREATE TABLE result (
+    id INT {{ rownum }},
+    status TEXT {{ 'pending' }},
+    "label" TEXT {{ NULL }}
+);
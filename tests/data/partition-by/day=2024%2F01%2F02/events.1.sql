@@ -0,0 +1,2 @@
+INSERT INTO events VALUES
+(3, '2024/01/02');
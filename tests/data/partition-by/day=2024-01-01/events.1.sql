@@ -0,0 +1,3 @@
+INSERT INTO events VALUES
+(1, '2024-01-01'),
+(2, '2024-01-01');
@@ -0,0 +1,2 @@
+INSERT INTO events VALUES
+(4, NULL);
@@ -0,0 +1,6 @@
+INSERT INTO summary VALUES
+(1),
+(2);
+INSERT INTO summary VALUES
+(3),
+(4);
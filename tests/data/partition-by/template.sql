@@ -0,0 +1,8 @@
+CREATE TABLE events (
+    id INT {{ rownum }},
+    day TEXT {{ CASE WHEN rownum <= 2 THEN '2024-01-01' WHEN rownum = 3 THEN '2024/01/02' END }}
+);
+
+CREATE TABLE summary (
+    id INT {{ rownum }}
+);
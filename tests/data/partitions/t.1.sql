@@ -0,0 +1,4 @@
+INSERT INTO t VALUES
+(3, 30);
+INSERT INTO t VALUES
+(4, 40);
@@ -0,0 +1,8 @@
+CREATE TABLE t (
+    id INT NOT NULL {{ rownum }},
+    v INT {{ rownum * 10 }}
+) PARTITION BY RANGE (id) (
+    PARTITION p0 VALUES LESS THAN (3),
+    PARTITION p1 VALUES LESS THAN (5),
+    PARTITION pmax VALUES LESS THAN MAXVALUE
+);
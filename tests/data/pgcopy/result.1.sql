@@ -0,0 +1,8 @@
+COPY result (a, b, c, d) FROM stdin;
+1	x\\y1	{"p\\"q",NULL}	\\x00ff
+2	\N	{"p\\"q",NULL}	\\x00ff
+\.
+COPY result (a, b, c, d) FROM stdin;
+3	x\\y3	{"p\\"q",NULL}	\\x00ff
+4	x\\y4	{"p\\"q",NULL}	\\x00ff
+\.
@@ -0,0 +1,6 @@
+CREATE TABLE result (
+    a INT {{ rownum }},
+    b TEXT {{ CASE WHEN rownum = 2 THEN NULL ELSE 'x\y' || rownum END }},
+    c TEXT[] {{ ARRAY['p"q', NULL] }},
+    d BYTEA {{ from_hex('00ff') }}
+);
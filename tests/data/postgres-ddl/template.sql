@@ -0,0 +1,9 @@
+-- Name: users; Type: TABLE; Schema: public
+CREATE UNLOGGED TABLE IF NOT EXISTS users (
+    id integer NOT NULL GENERATED ALWAYS AS IDENTITY {{ rownum }},
+    name character varying(20) DEFAULT 'anon'::character varying NOT NULL {{ 'u' || rownum }},
+    note text DEFAULT E'it\'s' {{ 'n' }},
+    body text DEFAULT $$a ) b$$ {{ 'b' }},
+    tags text[] DEFAULT '{}'::text[] {{ ARRAY['x'] }},
+    CONSTRAINT users_name_check CHECK (((name)::text <> ''::text))
+);
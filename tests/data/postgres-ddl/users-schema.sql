@@ -0,0 +1,7 @@
+CREATE UNLOGGED TABLE IF NOT EXISTS users (
+    id integer NOT NULL GENERATED ALWAYS AS IDENTITY,
+    name character varying(20) DEFAULT 'anon'::character varying NOT NULL,
+    note text DEFAULT E'it\'s',
+    body text DEFAULT $$a ) b$$,
+    tags text[] DEFAULT '{}'::text[],
+    CONSTRAINT users_name_check CHECK (((name)::text <> ''::text)));
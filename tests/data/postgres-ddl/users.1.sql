@@ -0,0 +1,3 @@
+INSERT INTO users VALUES
+(1, 'u1', 'n', 'b', ARRAY['x']),
+(2, 'u2', 'n', 'b', ARRAY['x']);
@@ -0,0 +1,5 @@
+INSERT INTO orders VALUES
+(4, 7),
+(3, 52),
+(2, 32),
+(1, 28);
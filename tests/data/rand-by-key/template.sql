@@ -0,0 +1,11 @@
+CREATE TABLE users (
+    id INT {{ rownum }},
+    age INT {{ rand.range_by_key(rownum, 1, 100) }},
+    score INT {{ rand.range_by_key('user-' || rownum, -1000, 1000) }},
+    height INT {{ rand.range_by_key(rownum, 1, 100, 'height') }}
+);
+
+CREATE TABLE orders (
+    user_id INT {{ @u := 5 - rownum }},
+    user_age INT {{ rand.range_by_key(@u, 1, 100) }}
+);
@@ -0,0 +1,5 @@
+INSERT INTO users VALUES
+(1, 28, -29, 82),
+(2, 32, 916, 5),
+(3, 52, 240, 68),
+(4, 7, -647, 4);
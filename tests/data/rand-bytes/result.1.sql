@@ -0,0 +1,4 @@
+INSERT INTO result VALUES
+(4096, TRUE, ''),
+(4096, TRUE, ''),
+(4096, TRUE, '');
@@ -0,0 +1,5 @@
+CREATE TABLE result (
+    fixed   {{ octet_length(rand.bytes(4096)) }}
+    ranged  {{ @n := octet_length(rand.bytes(10, 20)); @n >= 10 AND @n <= 20 }}
+    empty   {{ rand.bytes(0) }}
+);
@@ -0,0 +1,3 @@
+INSERT INTO result VALUES
+('b', 7),
+('b', 7);
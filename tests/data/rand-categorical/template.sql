@@ -0,0 +1,4 @@
+CREATE TABLE result (
+    a {{ rand.categorical(ARRAY['a', 'b', 'c'], ARRAY[0, 1, 0]) }}
+    b {{ rand.categorical(ARRAY[7], ARRAY[0.5]) }}
+);
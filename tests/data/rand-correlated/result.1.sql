@@ -0,0 +1,3 @@
+INSERT INTO result VALUES
+(10.0, 20.0, TRUE, TRUE),
+(10.0, 20.0, TRUE, TRUE);
@@ -0,0 +1,6 @@
+CREATE TABLE result (
+    a {{ @m := rand.multivariate_normal(ARRAY[10, 20], ARRAY[ARRAY[0, 0], ARRAY[0, 0]]); @m[1] }}
+    b {{ @m[2] }}
+    c {{ @p := rand.correlated_pair(1.0); @p[1] = @p[2] }}
+    d {{ @q := rand.correlated_pair(-1.0); @q[1] = -@q[2] }}
+);
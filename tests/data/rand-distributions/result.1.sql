@@ -0,0 +1,3 @@
+INSERT INTO result VALUES
+(5.0, 4, 0, TRUE, TRUE, TRUE, TRUE, TRUE),
+(5.0, 4, 0, TRUE, TRUE, TRUE, TRUE, TRUE);
@@ -0,0 +1,10 @@
+CREATE TABLE result (
+    a {{ rand.normal(5, 0) }}
+    b {{ rand.binomial(4, 1.0) }}
+    c {{ rand.binomial(4, 0.0) }}
+    d {{ rand.exp(2.5) > 0 }}
+    e {{ rand.pareto(3, 1.5) >= 3 }}
+    f {{ rand.poisson(0.5) >= 0 }}
+    g {{ rand.hotspot(10, 0.2, 1.0) <= 2 }}
+    h {{ rand.hotspot(10, 0.2, 0.0) > 2 }}
+);
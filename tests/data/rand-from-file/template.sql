@@ -0,0 +1,4 @@
+CREATE TABLE result (
+    a {{ rand.from_file('tests/data/rand-from-file/agents.txt') }}
+    b {{ rand.from_file('tests/data/rand-from-file/agents.txt', 1.2) }}
+);
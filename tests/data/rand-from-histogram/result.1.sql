@@ -0,0 +1,3 @@
+INSERT INTO result VALUES
+('green', 42),
+('green', 42);
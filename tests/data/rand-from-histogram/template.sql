@@ -0,0 +1,4 @@
+CREATE TABLE result (
+    color {{ rand.from_histogram('tests/data/rand-from-histogram/colors.csv') }}
+    size {{ rand.from_histogram('tests/data/rand-from-histogram/sizes.csv') }}
+);
@@ -0,0 +1,4 @@
+INSERT INTO result VALUES
+(TRUE, 1, 1000, 1),
+(TRUE, 2, 1000, 1),
+(TRUE, 3, 1000, 1);
@@ -0,0 +1,6 @@
+CREATE TABLE result (
+    a {{ rand.latest(rownum * 10, 0.99) <= rownum * 10 }}
+    b {{ rand.latest(rownum, 50) }}
+    c {{ rand.latest(1000, 50) }}
+    d {{ rand.latest(1, 0.99) }}
+);
@@ -0,0 +1,4 @@
+INSERT INTO result VALUES
+('The quick brown fox jumps. The quick', 'The quick'),
+('The quick brown fox jumps. The quick', 'The quick brown');
+
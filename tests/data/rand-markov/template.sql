@@ -0,0 +1,4 @@
+CREATE TABLE result (
+    a {{ rand.markov('tests/data/rand-markov/corpus.txt', 7) }}
+    b {{ rand.markov('tests/data/rand-markov/corpus.txt', rownum + 1) }}
+);
@@ -0,0 +1,5 @@
+INSERT INTO result VALUES
+(10, -1, TRUE),
+(20, -2, FALSE),
+(30, -3, TRUE),
+(40, -4, FALSE);
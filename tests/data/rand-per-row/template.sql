@@ -0,0 +1,5 @@
+CREATE TABLE result (
+    {{ rand.range(rownum * 10, rownum * 10 + 1) }}
+    {{ rand.range_inclusive(-rownum, -rownum) }}
+    {{ rand.bool(mod(rownum, 2)) }}
+);
@@ -0,0 +1,5 @@
+INSERT INTO result VALUES
+(1, TRUE),
+(1, TRUE),
+(1, TRUE),
+(1, TRUE);
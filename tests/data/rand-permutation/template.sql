@@ -0,0 +1,4 @@
+CREATE TABLE result (
+    {{ rand.permutation(1) }}
+    {{ @p := rand.permutation(4); 1 <= @p AND @p <= 4 }}
+);
@@ -0,0 +1,11 @@
+INSERT INTO a VALUES
+(1, TRUE, TRUE),
+(2, TRUE, TRUE),
+(3, TRUE, TRUE),
+(4, TRUE, TRUE),
+(5, TRUE, TRUE),
+(6, TRUE, TRUE),
+(7, TRUE, TRUE),
+(8, TRUE, TRUE),
+(9, TRUE, TRUE),
+(10, TRUE, TRUE);
@@ -0,0 +1,11 @@
+INSERT INTO b VALUES
+(TRUE, TRUE),
+(TRUE, TRUE),
+(TRUE, TRUE),
+(TRUE, TRUE),
+(TRUE, TRUE),
+(TRUE, TRUE),
+(TRUE, TRUE),
+(TRUE, TRUE),
+(TRUE, TRUE),
+(TRUE, TRUE);
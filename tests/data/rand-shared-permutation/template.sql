@@ -0,0 +1,16 @@
+CREATE TABLE a (
+    id INT {{ rownum }},
+    in_range BOOLEAN {{ @p := rand.shared_permutation('u', rownum, 10); @p >= 1 AND @p <= 10 }},
+    complete BOOLEAN {{
+        @sum := coalesce(@sum, 0) + @p;
+        @product := coalesce(@product, 1) * @p;
+        @moved := coalesce(@moved, FALSE) OR @p <> rownum;
+        rownum < 10 OR (@sum = 55 AND @product = 3628800 AND @moved)
+    }}
+);
+
+{{ for each row of a generate 1 row of b }}
+CREATE TABLE b (
+    same BOOLEAN {{ rand.shared_permutation('u', rownum) = @p }},
+    wraps BOOLEAN {{ rand.shared_permutation('u', rownum + 10) = @p }}
+);
@@ -0,0 +1,4 @@
+INSERT INTO result VALUES
+('a', 'c', NULL, 'a', 10, -1),
+('a', 'c', NULL, 'a', 20, -2),
+('a', 'c', NULL, 'a', 30, -3);
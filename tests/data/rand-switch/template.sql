@@ -0,0 +1,8 @@
+CREATE TABLE result (
+    {{ rand.switch(1, 'a', 'b') }}
+    {{ rand.switch(0, 'a', 0.0, 'b', 'c') }}
+    {{ rand.switch(0, 'a') }}
+    {{ rand.switch(0.5, 'a', 0.5, 'a') }}
+    {{ rand.switch(1, rownum * 10, rownum) }}
+    {{ rand.switch(0, rownum * 10, -rownum) }}
+);
@@ -0,0 +1,3 @@
+COPY result (a, b) FROM stdin;
+1	f('a\\b')
+\.
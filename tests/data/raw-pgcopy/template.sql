@@ -0,0 +1,4 @@
+CREATE TABLE result (
+    a INT {{ rownum }},
+    b TEXT {{ raw('f(''a\b'')') }}
+);
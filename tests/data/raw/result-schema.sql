@@ -0,0 +1,7 @@
+CREATE TABLE result (
+    id INT,
+    created_at TIMESTAMP,
+    point TEXT,
+    nothing TEXT,
+    concatenated TEXT,
+    compared TEXT);
@@ -0,0 +1,3 @@
+INSERT INTO result VALUES
+(1, NOW(), ST_GeomFromText('POINT(1 0)'), NULL, 'ab', 'equal'),
+(2, NOW(), ST_GeomFromText('POINT(2 0)'), NULL, 'ab', 'equal');
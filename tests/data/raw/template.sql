@@ -0,0 +1,8 @@
+CREATE TABLE result (
+    id INT {{ rownum }},
+    created_at TIMESTAMP {{ raw('NOW()') }},
+    point TEXT {{ raw('ST_GeomFromText(''POINT(' || rownum || ' 0)'')') }},
+    nothing TEXT {{ raw(NULL) }},
+    concatenated TEXT {{ raw('a') || 'b' }},
+    compared TEXT {{ CASE WHEN raw('a') = raw('a') AND raw('a') = 'a' THEN 'equal' END }}
+);
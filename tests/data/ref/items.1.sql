@@ -0,0 +1,4 @@
+INSERT INTO items VALUES
+(1, 7, NULL),
+(2, 7, 'x'),
+(3, 7, 'x');
@@ -0,0 +1,4 @@
+INSERT INTO later VALUES
+('x'),
+('x'),
+('x');
@@ -0,0 +1,4 @@
+INSERT INTO orders VALUES
+(7),
+(7),
+(7);
@@ -0,0 +1,11 @@
+CREATE TABLE orders (
+    id INT {{ 7 }}
+);
+CREATE TABLE items (
+    id INT {{ rownum }},
+    order_id INT {{ ref('ORDERS', 'ID') }},
+    later CHAR {{ ref('later', 'v', 1) }}
+);
+CREATE TABLE later (
+    v CHAR {{ 'x' }}
+);
@@ -0,0 +1,3 @@
+INSERT INTO result VALUES
+(1, 'x1', NULL, 6379207621272972792, 12945802862195027432),
+(2, 'x2', NULL, 9188139181636869064, 7999979459086433091);
@@ -0,0 +1,7 @@
+CREATE TABLE result (
+    a {{ rownum }}
+    b {{ 'x' || rownum }}
+    c {{ NULL }}
+    d {{ row_checksum() }}
+    e {{ row_checksum() }}
+);
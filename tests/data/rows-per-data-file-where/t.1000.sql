@@ -0,0 +1,4 @@
+INSERT INTO t VALUES
+(2);
+INSERT INTO t VALUES
+(4);
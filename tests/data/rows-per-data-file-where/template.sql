@@ -0,0 +1,3 @@
+CREATE TABLE t (
+    id INT {{ @id := rownum }}
+);
@@ -0,0 +1,5 @@
+INSERT INTO a VALUES
+(1),
+(2);
+INSERT INTO a VALUES
+(3);
@@ -0,0 +1,5 @@
+INSERT INTO a VALUES
+(4);
+INSERT INTO a VALUES
+(5),
+(6);
@@ -0,0 +1,8 @@
+INSERT INTO b VALUES
+(1, 1),
+(1, 2),
+(2, 1),
+(2, 2);
+INSERT INTO b VALUES
+(3, 1),
+(3, 2);
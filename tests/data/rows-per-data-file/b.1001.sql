@@ -0,0 +1,8 @@
+INSERT INTO b VALUES
+(4, 1),
+(4, 2);
+INSERT INTO b VALUES
+(5, 1),
+(5, 2),
+(6, 1),
+(6, 2);
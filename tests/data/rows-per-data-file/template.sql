@@ -0,0 +1,9 @@
+CREATE TABLE a (
+    c1 {{ rownum }}
+);
+
+/*{{ for each row of a generate 2 row of b }}*/
+CREATE TABLE b (
+    c1 {{ rownum }},
+    c2 {{ subrownum }}
+);
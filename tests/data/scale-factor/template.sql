@@ -0,0 +1,11 @@
+CREATE TABLE w (
+    id INT {{ rownum }},
+    sf REAL {{ scale_factor }},
+    cap REAL {{ rownum * scale_factor }}
+);
+
+/*{{ for each row of w generate 2 * scale_factor rows of d }}*/
+CREATE TABLE d (
+    w_id INT {{ rownum }},
+    d_id INT {{ subrownum }}
+);
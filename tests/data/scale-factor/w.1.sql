@@ -0,0 +1,4 @@
+INSERT INTO w VALUES
+(1, 1.5, 1.5),
+(2, 1.5, 3.0),
+(3, 1.5, 4.5);
@@ -0,0 +1,5 @@
+CREATE TABLE result (
+    id SMALLINT NOT NULL,
+    "name" VARCHAR(40)
+);
+CREATE UNIQUE INDEX result_name ON result ("name" DESC, id);
@@ -0,0 +1,6 @@
+CREATE TABLE result (
+    id INT1 UNSIGNED NOT NULL {{ rownum }},
+    `name` VARCHAR(40) {{ NULL }}
+);
+CREATE UNIQUE INDEX result_name ON result (`name` DESC, id);
+ALTER TABLE result ADD CONSTRAINT result_id CHECK (id > 0);
@@ -0,0 +1,6 @@
+CREATE TABLE result (
+    id UInt32,
+    `name` Nullable(String),
+    score Nullable(Decimal(10, 2)),
+    tags Array(String)
+) ENGINE = MergeTree ORDER BY tuple();
@@ -0,0 +1,2 @@
+INSERT INTO result VALUES
+(1, NULL, 1.5, ARRAY['a']);
@@ -0,0 +1,7 @@
+CREATE TABLE result (
+    id INT UNSIGNED NOT NULL PRIMARY KEY {{ rownum }},
+    `name` VARCHAR(40) {{ NULL }},
+    score DECIMAL(10, 2) DEFAULT 0 {{ 1.5 }},
+    tags TEXT[] NOT NULL {{ ARRAY['a'] }}
+) ENGINE = InnoDB;
+CREATE INDEX result_name ON result (`name`);
@@ -0,0 +1,7 @@
+CREATE TABLE t (
+    id INT {{ rownum }},
+    {{ version 3 drops column }}
+    legacy INT {{ rownum * 10 }},
+    {{ version 2 adds column }}
+    score INT {{ rownum * 100 }}
+);
@@ -0,0 +1,3 @@
+INSERT INTO t VALUES
+(1, 10),
+(2, 20);
@@ -0,0 +1,4 @@
+CREATE TABLE t (
+    id INT,
+    legacy INT,
+    score INT);
@@ -0,0 +1,3 @@
+INSERT INTO t VALUES
+(1, 10, 100),
+(2, 20, 200);
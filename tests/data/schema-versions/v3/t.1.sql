@@ -0,0 +1,3 @@
+INSERT INTO t VALUES
+(1, 100),
+(2, 200);
@@ -0,0 +1,6 @@
+INSERT INTO result VALUES
+(100, '2024-01-01 00:00:00'),
+(105, '2024-01-01 01:00:00');
+INSERT INTO result VALUES
+(110, '2024-01-01 02:00:00'),
+(115, '2024-01-01 03:00:00');
@@ -0,0 +1,6 @@
+INSERT INTO result VALUES
+(120, '2024-01-01 04:00:00'),
+(125, '2024-01-01 05:00:00');
+INSERT INTO result VALUES
+(130, '2024-01-01 06:00:00'),
+(135, '2024-01-01 07:00:00');
@@ -0,0 +1,4 @@
+CREATE TABLE result (
+    {{ sequence('id', 100, 5) }}
+    {{ sequence('ts', TIMESTAMP '2024-01-01 00:00:00', INTERVAL 1 HOUR) }}
+);
@@ -0,0 +1,4 @@
+CREATE TABLE orders_0 (
+    id INT,
+    customer INT);
+CREATE INDEX orders_customer_0 ON orders_0 (customer DESC);
@@ -0,0 +1,4 @@
+CREATE TABLE orders_1 (
+    id INT,
+    customer INT);
+CREATE INDEX orders_customer_1 ON orders_1 (customer DESC);
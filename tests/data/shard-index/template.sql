@@ -0,0 +1,6 @@
+{{ shard into 2 tables by rownum }}
+CREATE TABLE orders (
+    id INT {{ rownum }},
+    customer INT {{ rownum * 7 }}
+);
+CREATE INDEX orders_customer ON orders (customer DESC);
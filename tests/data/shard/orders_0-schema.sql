@@ -0,0 +1,3 @@
+CREATE TABLE orders_0 (
+    id INT,
+    tenant INT);
@@ -0,0 +1,4 @@
+INSERT INTO orders_0 VALUES
+(3, 6);
+INSERT INTO orders_0 VALUES
+(6, 12);
@@ -0,0 +1,3 @@
+CREATE TABLE orders_1 (
+    id INT,
+    tenant INT);
@@ -0,0 +1,4 @@
+INSERT INTO orders_1 VALUES
+(2, 4);
+INSERT INTO orders_1 VALUES
+(5, 10);
@@ -0,0 +1,3 @@
+CREATE TABLE orders_2 (
+    id INT,
+    tenant INT);
@@ -0,0 +1,4 @@
+INSERT INTO orders_2 VALUES
+(1, 2);
+INSERT INTO orders_2 VALUES
+(4, 8);
@@ -0,0 +1,5 @@
+{{ shard into 3 tables by @tenant }}
+CREATE TABLE orders (
+    id INT {{ rownum }},
+    tenant INT {{ @tenant := rownum * 2 }}
+);
@@ -0,0 +1,4 @@
+INSERT INTO result VALUES
+(1010, 'eu-west', 'it''s #1'),
+(1020, 'eu-west', 'it''s #2'),
+(1030, 'eu-west', 'it''s #3');
@@ -0,0 +1,6 @@
+/*{{ @scale := coalesce(@scale, 1) }}*/
+CREATE TABLE result (
+    id INT {{ rownum * @scale + @offset }},
+    region TEXT {{ @region }},
+    note TEXT {{ @note || rownum }}
+);
@@ -0,0 +1,4 @@
+INSERT INTO result VALUES
+('2024-02-15 13:45:30', '20240215', 'Thu 15 Feb 2024 13:45', '2024-01-15 00:00:00', '2024-03-15 10:01:00'),
+('2024-02-16 13:45:30', '20240216', 'Fri 16 Feb 2024 13:45', '2024-02-15 00:00:00', '2024-03-15 10:02:00'),
+('2024-02-17 13:45:30', '20240217', 'Sat 17 Feb 2024 13:45', '2024-03-15 00:00:00', '2024-03-15 10:03:00');
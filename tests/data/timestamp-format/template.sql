@@ -0,0 +1,7 @@
+CREATE TABLE result (
+    {{ @ts := TIMESTAMP '2024-02-14 13:45:30' + INTERVAL rownum DAY }}
+    {{ format_timestamp(@ts, '%Y%m%d') }}
+    {{ format_timestamp(@ts, '%a %d %b %Y %H:%M') }}
+    {{ parse_timestamp('2024-0' || rownum || '-15', '%Y-%m-%d') }}
+    {{ parse_timestamp('15/03/2024 10:0' || rownum, '%d/%m/%Y %H:%M', 'UTC') }}
+);
@@ -0,0 +1,2 @@
+INSERT INTO result VALUES
+(toDateTime('2024-01-02 03:04:05'), '2024-01-02');
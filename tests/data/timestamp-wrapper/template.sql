@@ -0,0 +1,4 @@
+CREATE TABLE result (
+    ts {{ TIMESTAMP '2024-01-02 03:04:05' }},
+    d {{ DATE '2024-01-02' }}
+);
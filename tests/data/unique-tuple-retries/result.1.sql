@@ -0,0 +1,21 @@
+INSERT INTO result VALUES
+(TRUE),
+(TRUE),
+(TRUE),
+(TRUE),
+(TRUE),
+(TRUE),
+(TRUE),
+(TRUE),
+(TRUE),
+(TRUE),
+(TRUE),
+(TRUE),
+(TRUE),
+(TRUE),
+(TRUE),
+(TRUE),
+(TRUE),
+(TRUE),
+(TRUE),
+(TRUE);
@@ -0,0 +1,3 @@
+CREATE TABLE result (
+    ok {{ @t := unique.tuple(rand.range(1, 21), 'x'); @sum := coalesce(@sum, 0) + @t[1]; rownum < 20 OR @sum = 210 }}
+);
@@ -0,0 +1,3 @@
+INSERT INTO result VALUES
+(TRUE),
+(TRUE);
@@ -0,0 +1,3 @@
+CREATE TABLE result (
+    ok {{ @t := unique.tuple(rand.range(1, 3), 7); @ok := @prev IS NULL OR @t[1] + @prev = 3; @prev := @t[1]; @ok }}
+);
@@ -0,0 +1,4 @@
+INSERT INTO result (`Id`) VALUES
+(1),
+(2)
+ON DUPLICATE KEY UPDATE `Id` = `Id`;
@@ -0,0 +1,4 @@
+CREATE TABLE result (
+    name TEXT {{ 'n' || rownum }},
+    "Id" INT PRIMARY KEY {{ rownum }}
+);
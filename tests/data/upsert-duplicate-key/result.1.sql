@@ -0,0 +1,4 @@
+INSERT INTO result (id, name, score) VALUES
+(1, 'n1', 10),
+(2, 'n2', 20)
+ON DUPLICATE KEY UPDATE score = VALUES(score);
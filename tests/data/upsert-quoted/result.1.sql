@@ -0,0 +1,4 @@
+INSERT INTO result VALUES
+(1, 'n1', 10),
+(2, 'n2', 20)
+ON CONFLICT ("Id") DO UPDATE SET "the name" = EXCLUDED."the name";
@@ -0,0 +1,5 @@
+CREATE TABLE result (
+    `Id` INT PRIMARY KEY {{ rownum }},
+    `the name` TEXT {{ 'n' || rownum }},
+    score INT {{ rownum * 10 }}
+);
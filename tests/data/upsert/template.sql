@@ -0,0 +1,5 @@
+CREATE TABLE result (
+    id INT PRIMARY KEY {{ rownum }},
+    name TEXT {{ 'n' || rownum }},
+    score INT {{ rownum * 10 }}
+);
@@ -0,0 +1,3 @@
+INSERT INTO result VALUES
+(2, 100, 28, 'Hello, dbgen!'),
+(4, 100, 28, 'Hello, dbgen!');
@@ -0,0 +1,9 @@
+{{ define double(@x) := @x * 2 }}
+/*{{ define greet(@name, @suffix) := 'Hello, ' || @name || @suffix }}*/
+{{ define seven() := 7 }}
+CREATE TABLE result (
+    a {{ @x := 100; double(rownum) }}
+    b {{ @x }}
+    c {{ double(double(seven())) }}
+    d {{ greet('dbgen', '!') }}
+);
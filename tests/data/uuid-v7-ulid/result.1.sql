@@ -0,0 +1,3 @@
+INSERT INTO result VALUES
+('4', '017133c9-dec0-7', '00e47279-7865-7', TRUE, '01E4SWKQP0', '00WHS7JY35'),
+('4', '017133c9-dec0-7', '00e47279-7865-7', TRUE, '01E4SWKQP0', '00WHS7JY35');
@@ -0,0 +1,8 @@
+CREATE TABLE result (
+    v4          {{ substring(rand.uuid(4) from 15 for 1) }}
+    v7_now      {{ substring(rand.uuid(7) from 1 for 15) }}
+    v7_ts       {{ substring(rand.uuid(7, timestamp '2001-02-03 04:05:06.789') from 1 for 15) }}
+    v7_sorted   {{ rand.uuid(7, timestamp '2001-02-03 04:05:06.789') < rand.uuid(7) }}
+    ulid_now    {{ substring(rand.ulid() from 1 for 10) }}
+    ulid_ts     {{ substring(rand.ulid(timestamp '2001-02-03 04:05:06.789') from 1 for 10) }}
+);
@@ -0,0 +1,6 @@
+INSERT INTO t VALUES
+(3, 30);
+INSERT INTO t VALUES
+(6, 60);
+INSERT INTO t VALUES
+(9, 90);
@@ -0,0 +1,10 @@
+CREATE TABLE t (
+    id INT {{ rownum }},
+    v INT {{ @v := CASE WHEN mod(rownum, 3) = 0 THEN rownum * 10 END }}
+);
+
+{{ for each row of t generate 2 rows of u }}
+CREATE TABLE u (
+    t_id INT {{ rownum }},
+    sub INT {{ subrownum }}
+);
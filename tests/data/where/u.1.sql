@@ -0,0 +1,9 @@
+INSERT INTO u VALUES
+(3, 1),
+(3, 2);
+INSERT INTO u VALUES
+(6, 1),
+(6, 2);
+INSERT INTO u VALUES
+(9, 1),
+(9, 2);
@@ -0,0 +1,7 @@
+BEGIN;
+INSERT INTO child VALUES
+(2, 1);
+INSERT INTO child VALUES
+(3, 1),
+(3, 2);
+COMMIT;
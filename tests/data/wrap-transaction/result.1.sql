@@ -0,0 +1,10 @@
+BEGIN;
+INSERT INTO result VALUES
+(1);
+INSERT INTO result VALUES
+(2);
+COMMIT;
+BEGIN;
+INSERT INTO result VALUES
+(3);
+COMMIT;
@@ -0,0 +1,4 @@
+CREATE TABLE result ({{ rownum }});
+
+{{ for each row of result generate rownum - 1 rows of child }}
+CREATE TABLE child ({{ rownum }}, {{ subrownum }});
@@ -0,0 +1,46 @@
+use std::{
+    fs::write,
+    process::{Command, Output},
+};
+use tempfile::tempdir;
+
+fn dbgen(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_dbgen")).args(args).output().unwrap()
+}
+
+#[test]
+fn check_fails_on_lints() {
+    let out_dir = tempdir().unwrap();
+    let out_dir = out_dir.path().to_str().unwrap();
+
+    let output = dbgen(&[
+        "--check",
+        "--error-format",
+        "json",
+        "-o",
+        out_dir,
+        "-e",
+        "CREATE TABLE t ({{ @x := rownum }});",
+    ]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains(r#""code":"unused_variable""#));
+
+    let output = dbgen(&["--check", "-o", out_dir, "-e", "CREATE TABLE t ({{ rownum }});"]);
+    assert!(output.status.success());
+}
+
+#[test]
+fn verify_fails_on_missing_files() {
+    let out_dir = tempdir().unwrap();
+    let output = dbgen(&["verify", out_dir.path().to_str().unwrap()]);
+    assert!(output.status.success());
+
+    write(
+        out_dir.path().join("t.1.sql.manifest.json"),
+        r#"{"file":"t.1.sql","file_index":1,"rows":1,"size":1,"xxh64":"0000000000000000","seed":"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"}"#,
+    )
+    .unwrap();
+    let output = dbgen(&["verify", out_dir.path().to_str().unwrap()]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("MISSING t.1.sql"));
+}